@@ -1,9 +1,16 @@
 use std::any::{Any, TypeId};
 
-use crate::runtime::RUNTIME;
+use crate::{
+    id::Id,
+    runtime::RUNTIME,
+    scope::{with_scope, Scope},
+};
 
 /// Try to retrieve a stored Context value in the reactive system.
-/// You can store a Context value anywhere, and retrieve it from anywhere afterwards.
+///
+/// The value is the one provided with [provide_context](provide_context) in the nearest
+/// Scope: the current Scope, or else its parent Scopes, or else the Scope which is current
+/// outside of any other Scope, whose values can be retrieved from anywhere.
 pub fn use_context<T>() -> Option<T>
 where
     T: Clone + 'static,
@@ -11,24 +18,58 @@ where
     let ty = TypeId::of::<T>();
     RUNTIME.with(|runtime| {
         let contexts = runtime.contexts.borrow();
-        let context = contexts
-            .get(&ty)
-            .and_then(|val| val.downcast_ref::<T>())
-            .cloned();
-        context
+        let parents = runtime.parents.borrow();
+        let get = |scope: &Id| {
+            contexts
+                .get(scope)
+                .and_then(|contexts| contexts.get(&ty))
+                .and_then(|val| val.downcast_ref::<T>())
+                .cloned()
+        };
+        let mut scope = Some(*runtime.current_scope.borrow());
+        while let Some(current) = scope {
+            if let Some(context) = get(&current) {
+                return Some(context);
+            }
+            scope = parents.get(&current).copied();
+        }
+        get(&runtime.root_scope)
     })
 }
 
 /// Sets a context value to be stored in the reative system.
-/// The stored context value can be retrieved from anywhere by using [use_context](use_context)
+///
+/// The value is stored in the current Scope, and can be retrieved with
+/// [use_context](use_context) in that Scope and its child Scopes, until the Scope is disposed.
+/// A value provided in a child Scope shadows the one of its parent. Values provided outside of
+/// any other Scope can be retrieved from anywhere.
 pub fn provide_context<T>(value: T)
 where
     T: Clone + 'static,
 {
-    let id = value.type_id();
+    let id = TypeId::of::<T>();
 
     RUNTIME.with(|runtime| {
+        let scope = *runtime.current_scope.borrow();
         let mut contexts = runtime.contexts.borrow_mut();
-        contexts.insert(id, Box::new(value) as Box<dyn Any>);
+        contexts
+            .entry(scope)
+            .or_default()
+            .insert(id, Box::new(value) as Box<dyn Any>);
     });
 }
+
+/// Runs `f` in a child Scope of the current Scope in which `value` is provided, so it's only
+/// seen by what `f` creates, like the views of a subtree. The child Scope is disposed with the
+/// current one.
+pub fn with_context<T, U>(value: T, f: impl FnOnce() -> U) -> U
+where
+    T: Clone + 'static,
+    U: 'static,
+{
+    let scope = Scope::current().create_child();
+    with_scope(scope, move || {
+        provide_context(value);
+        f()
+    })
+}
@@ -121,25 +121,44 @@ pub fn untrack<T>(f: impl FnOnce() -> T) -> T {
     result
 }
 
+/// Runs `f` while deferring the effects triggered by signal updates in it, and then runs each
+/// of those effects once. Batches can be nested, and the effects only run when the outermost
+/// batch returns.
+///
+/// ```rust
+/// # use floem_reactive::{batch, create_effect, create_rw_signal};
+/// let first = create_rw_signal("John");
+/// let last = create_rw_signal("Doe");
+/// create_effect(move |_| println!("{} {}", first.get(), last.get()));
+///
+/// // Prints "Mary Smith" once instead of printing "Mary Doe" first
+/// batch(|| {
+///     first.set("Mary");
+///     last.set("Smith");
+/// });
+/// ```
 pub fn batch<T>(f: impl FnOnce() -> T) -> T {
-    let already_batching = RUNTIME.with(|runtime| {
-        let batching = runtime.batching.get();
-        if !batching {
-            runtime.batching.set(true);
+    /// Ends the batch even if `f` panics, so later updates aren't deferred forever. The effects
+    /// deferred by a batch which panicked are dropped, as they'd otherwise run at the end of an
+    /// unrelated batch.
+    struct BatchGuard;
+
+    impl Drop for BatchGuard {
+        fn drop(&mut self) {
+            RUNTIME.with(|runtime| {
+                runtime.batching.set(false);
+                if std::thread::panicking() {
+                    runtime.pending_effects.take();
+                } else {
+                    runtime.run_pending_effects();
+                }
+            });
         }
-
-        batching
-    });
-
-    let result = f();
-    if !already_batching {
-        RUNTIME.with(|runtime| {
-            runtime.batching.set(false);
-            runtime.run_pending_effects();
-        });
     }
 
-    result
+    let already_batching = RUNTIME.with(|runtime| runtime.batching.replace(true));
+    let _guard = (!already_batching).then(|| BatchGuard);
+    f()
 }
 
 pub(crate) fn run_initial_effect(effect: Rc<dyn EffectTrait>) {
@@ -160,7 +179,7 @@ pub(crate) fn run_initial_effect(effect: Rc<dyn EffectTrait>) {
 
 pub(crate) fn run_effect(effect: Rc<dyn EffectTrait>) {
     let effect_id = effect.id();
-    effect_id.dispose();
+    effect_id.dispose_children();
 
     observer_clean_up(&effect);
 
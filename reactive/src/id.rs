@@ -30,18 +30,40 @@ impl Id {
             let mut children = runtime.children.borrow_mut();
             let children = children.entry(*scope).or_default();
             children.insert(*self);
+            runtime.parents.borrow_mut().insert(*self, *scope);
         });
     }
 
     /// Dispose the relevant resources that's linking to this Id, and the all the children
-    /// and grandchildren.
+    /// and grandchildren, and unlink it from its parent.
     pub(crate) fn dispose(&self) {
-        if let Ok((children, signal)) = RUNTIME.try_with(|runtime| {
+        self.dispose_children();
+        let _ = RUNTIME.try_with(|runtime| {
+            if let Some(parent) = runtime.parents.borrow_mut().remove(self) {
+                if let Some(children) = runtime.children.borrow_mut().get_mut(&parent) {
+                    children.remove(self);
+                }
+            }
+        });
+    }
+
+    /// Dispose the relevant resources that's linking to this Id, and the all the children
+    /// and grandchildren.
+    ///
+    /// The Id stays a child of its parent, since effects dispose themselves before rerunning.
+    pub(crate) fn dispose_children(&self) {
+        if let Ok((children, signal, cleanups)) = RUNTIME.try_with(|runtime| {
+            runtime.contexts.borrow_mut().remove(self);
             (
                 runtime.children.borrow_mut().remove(self),
                 runtime.signals.borrow_mut().remove(self),
+                runtime.cleanups.borrow_mut().remove(self),
             )
         }) {
+            for cleanup in cleanups.into_iter().flatten() {
+                cleanup();
+            }
+
             if let Some(children) = children {
                 for child in children {
                     child.dispose();
@@ -6,10 +6,12 @@ mod runtime;
 mod scope;
 mod signal;
 mod trigger;
+mod undo;
 
-pub use context::{provide_context, use_context};
+pub use context::{provide_context, use_context, with_context};
 pub use effect::{batch, create_effect, create_stateful_updater, create_updater, untrack};
-pub use memo::{create_memo, Memo};
-pub use scope::{as_child_of_current_scope, with_scope, Scope};
+pub use memo::{create_memo, create_memo_with_eq, Memo};
+pub use scope::{as_child_of_current_scope, on_cleanup, with_scope, Scope};
 pub use signal::{create_rw_signal, create_signal, ReadSignal, RwSignal, WriteSignal};
 pub use trigger::{create_trigger, Trigger};
+pub use undo::UndoStack;
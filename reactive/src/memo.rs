@@ -75,6 +75,22 @@ impl<T> Memo<T> {
 pub fn create_memo<T>(f: impl Fn(Option<&T>) -> T + 'static) -> Memo<T>
 where
     T: PartialEq + 'static,
+{
+    create_memo_with_eq(f, T::eq)
+}
+
+/// Create a Memo like [`create_memo`], but which uses `eq` to decide whether the computed
+/// value is the same as the last computed value. The reactive system is only triggered
+/// when `eq` returns false.
+///
+/// This is useful when comparing the whole value is expensive or not what you want,
+/// e.g. comparing items by id instead of by content.
+pub fn create_memo_with_eq<T>(
+    f: impl Fn(Option<&T>) -> T + 'static,
+    eq: impl Fn(&T, &T) -> bool + 'static,
+) -> Memo<T>
+where
+    T: 'static,
 {
     let cx = Scope::current();
     let (getter, setter) = create_signal(None::<T>);
@@ -83,7 +99,11 @@ where
         cx.track();
         let (is_different, new_value) = getter.with_untracked(|value| {
             let new_value = f(value.as_ref());
-            (Some(&new_value) != value.as_ref(), new_value)
+            let is_different = match value.as_ref() {
+                Some(value) => !eq(value, &new_value),
+                None => true,
+            };
+            (is_different, new_value)
         });
         if is_different {
             setter.set(Some(new_value));
@@ -13,6 +13,12 @@ use crate::{
     signal::Signal,
 };
 
+/// The context values provided in a scope, by type.
+type Contexts = HashMap<TypeId, Box<dyn Any>>;
+
+/// The functions to call when a scope is disposed.
+type Cleanups = Vec<Box<dyn FnOnce()>>;
+
 thread_local! {
     pub(crate) static RUNTIME: Runtime = Runtime::new();
 }
@@ -22,9 +28,17 @@ thread_local! {
 pub(crate) struct Runtime {
     pub(crate) current_effect: RefCell<Option<Rc<dyn EffectTrait>>>,
     pub(crate) current_scope: RefCell<Id>,
+    /// The scope which is current when nothing else is, whose contexts are seen from every
+    /// scope.
+    pub(crate) root_scope: Id,
     pub(crate) children: RefCell<HashMap<Id, HashSet<Id>>>,
+    /// The scope each scope, signal and effect was created in, to look up contexts.
+    pub(crate) parents: RefCell<HashMap<Id, Id>>,
     pub(crate) signals: RefCell<HashMap<Id, Signal>>,
-    pub(crate) contexts: RefCell<HashMap<TypeId, Box<dyn Any>>>,
+    /// The contexts provided in each scope.
+    pub(crate) contexts: RefCell<HashMap<Id, Contexts>>,
+    /// The functions registered with `on_cleanup` in each scope.
+    pub(crate) cleanups: RefCell<HashMap<Id, Cleanups>>,
     pub(crate) batching: Cell<bool>,
     pub(crate) pending_effects: RefCell<SmallVec<[Rc<dyn EffectTrait>; 10]>>,
 }
@@ -37,12 +51,16 @@ impl Default for Runtime {
 
 impl Runtime {
     pub(crate) fn new() -> Self {
+        let root_scope = Id::next();
         Self {
             current_effect: RefCell::new(None),
-            current_scope: RefCell::new(Id::next()),
+            current_scope: RefCell::new(root_scope),
+            root_scope,
             children: RefCell::new(HashMap::new()),
+            parents: Default::default(),
             signals: Default::default(),
             contexts: Default::default(),
+            cleanups: Default::default(),
             batching: Cell::new(false),
             pending_effects: RefCell::new(SmallVec::new()),
         }
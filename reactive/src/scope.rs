@@ -3,7 +3,7 @@ use std::{any::Any, cell::RefCell, collections::HashMap, fmt, rc::Rc};
 use crate::{
     create_effect,
     id::Id,
-    memo::{create_memo, Memo},
+    memo::{create_memo, create_memo_with_eq, Memo},
     runtime::RUNTIME,
     signal::{create_rw_signal, create_signal, ReadSignal, RwSignal, Signal, WriteSignal},
     trigger::{create_trigger, Trigger},
@@ -49,6 +49,7 @@ impl Scope {
             let mut children = runtime.children.borrow_mut();
             let children = children.entry(self.0).or_default();
             children.insert(child);
+            runtime.parents.borrow_mut().insert(child, self.0);
         });
         Scope(child)
     }
@@ -77,6 +78,18 @@ impl Scope {
         with_scope(self, || create_memo(f))
     }
 
+    /// Create a Memo with a custom equality under this Scope
+    pub fn create_memo_with_eq<T>(
+        self,
+        f: impl Fn(Option<&T>) -> T + 'static,
+        eq: impl Fn(&T, &T) -> bool + 'static,
+    ) -> Memo<T>
+    where
+        T: 'static,
+    {
+        with_scope(self, || create_memo_with_eq(f, eq))
+    }
+
     /// Create a Trigger under this Scope
     pub fn create_trigger(self) -> Trigger {
         with_scope(self, create_trigger)
@@ -135,6 +148,20 @@ where
     result
 }
 
+/// Registers `f` to be called when the current Scope is disposed, or before the effect it's
+/// called in runs again. Use it to stop work started in the Scope, like timers.
+pub fn on_cleanup(f: impl FnOnce() + 'static) {
+    RUNTIME.with(|runtime| {
+        let scope = *runtime.current_scope.borrow();
+        runtime
+            .cleanups
+            .borrow_mut()
+            .entry(scope)
+            .or_default()
+            .push(Box::new(f));
+    });
+}
+
 /// Wrap the closure so that whenever the closure runs, it will be under a child Scope
 /// of the current Scope
 pub fn as_child_of_current_scope<T, U>(f: impl Fn(T) -> U + 'static) -> impl Fn(T) -> (U, Scope)
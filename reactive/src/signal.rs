@@ -8,7 +8,7 @@ use crate::{
 
 /// A read write Signal which can acts as both a Getter and a Setter
 pub struct RwSignal<T> {
-    id: Id,
+    pub(crate) id: Id,
     ty: PhantomData<T>,
 }
 
@@ -0,0 +1,319 @@
+use std::{
+    any::Any,
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    effect::{batch, create_effect},
+    id::Id,
+    signal::RwSignal,
+    trigger::{create_trigger, Trigger},
+};
+
+/// An undo history of the changes made to a set of tracked signals.
+///
+/// Signals are added with [`track`](UndoStack::track). Every change to them is recorded as a
+/// step, or as part of a single step when it's made within a
+/// [`transaction`](UndoStack::transaction).
+/// ```rust
+/// # use floem_reactive::{create_rw_signal, UndoStack};
+/// let text = create_rw_signal(String::new());
+/// let undo = UndoStack::new();
+/// undo.track(text);
+///
+/// undo.transaction("Type", || text.set("Hello".to_string()));
+/// undo.undo();
+/// assert_eq!(text.get(), "");
+/// ```
+#[derive(Clone)]
+pub struct UndoStack {
+    state: Rc<RefCell<UndoState>>,
+    changed: Trigger,
+}
+
+struct UndoState {
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// The step of the outermost open transaction.
+    open: Option<Step>,
+    depth: usize,
+    /// The signals set by undoing or redoing whose changes weren't recorded yet, so the values
+    /// they restore aren't recorded as a new step. Within a batch, that's only once it ends.
+    restoring: Vec<Id>,
+    coalesce: Option<Coalesce>,
+    /// Stops the next step from being merged into the last one.
+    sealed: bool,
+}
+
+struct Coalesce {
+    window: Duration,
+    max_edits: usize,
+}
+
+struct Step {
+    label: Option<String>,
+    changes: Vec<Change>,
+    edits: usize,
+    last_edit: Instant,
+}
+
+type SetValue = Rc<dyn Fn(&dyn Any)>;
+
+struct Change {
+    signal: Id,
+    old: Rc<dyn Any>,
+    new: Rc<dyn Any>,
+    set: SetValue,
+}
+
+impl Step {
+    fn new(label: Option<String>) -> Self {
+        Self {
+            label,
+            changes: Vec::new(),
+            edits: 1,
+            last_edit: Instant::now(),
+        }
+    }
+
+    /// Adds a change, keeping the oldest value of a signal which was already changed.
+    fn record(&mut self, change: Change) {
+        match self.changes.iter_mut().find(|c| c.signal == change.signal) {
+            Some(existing) => existing.new = change.new,
+            None => self.changes.push(change),
+        }
+    }
+
+    fn merge(&mut self, later: Step) {
+        for change in later.changes {
+            self.record(change);
+        }
+        self.edits += later.edits;
+        self.last_edit = later.last_edit;
+    }
+}
+
+impl UndoState {
+    fn push(&mut self, step: Step) {
+        self.redo.clear();
+        let sealed = std::mem::take(&mut self.sealed);
+        if let (Some(coalesce), Some(last)) = (&self.coalesce, self.undo.last_mut()) {
+            if !sealed
+                && last.label == step.label
+                && last.edits + step.edits <= coalesce.max_edits
+                && step.last_edit.duration_since(last.last_edit) <= coalesce.window
+            {
+                last.merge(step);
+                return;
+            }
+        }
+        self.undo.push(step);
+    }
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoStack {
+    pub fn new() -> Self {
+        Self {
+            state: Rc::new(RefCell::new(UndoState {
+                undo: Vec::new(),
+                redo: Vec::new(),
+                open: None,
+                depth: 0,
+                restoring: Vec::new(),
+                coalesce: None,
+                sealed: false,
+            })),
+            changed: create_trigger(),
+        }
+    }
+
+    /// Merges steps with the same label into one, when each is made within `window` of the
+    /// previous one, up to `max_edits` steps. This turns typing into one undo step per burst.
+    pub fn coalesce(self, window: Duration, max_edits: usize) -> Self {
+        self.state.borrow_mut().coalesce = Some(Coalesce { window, max_edits });
+        self
+    }
+
+    /// Records the changes made to `signal`. The recording lasts as long as the current
+    /// [`Scope`](crate::Scope).
+    pub fn track<T: Clone + 'static>(&self, signal: RwSignal<T>) {
+        let undo = self.clone();
+        create_effect(move |prev: Option<T>| {
+            let value = signal.get();
+            if let Some(prev) = prev {
+                undo.record(Change {
+                    signal: signal.id,
+                    old: Rc::new(prev),
+                    new: Rc::new(value.clone()),
+                    set: Rc::new(move |value| {
+                        if let Some(value) = value.downcast_ref::<T>() {
+                            signal.set(value.clone());
+                        }
+                    }),
+                });
+            }
+            value
+        });
+    }
+
+    fn record(&self, change: Change) {
+        {
+            let mut state = self.state.borrow_mut();
+            if let Some(i) = state.restoring.iter().position(|id| *id == change.signal) {
+                state.restoring.swap_remove(i);
+                return;
+            }
+            if let Some(open) = state.open.as_mut() {
+                open.record(change);
+                return;
+            }
+            let mut step = Step::new(None);
+            step.record(change);
+            state.push(step);
+        }
+        self.changed.notify();
+    }
+
+    /// Runs `f` and records the changes it makes to tracked signals as one step named `label`.
+    /// Transactions within a transaction are part of the outer one.
+    pub fn transaction<T>(&self, label: impl Into<String>, f: impl FnOnce() -> T) -> T {
+        /// Closes the transaction even if `f` panics.
+        struct TransactionGuard<'a>(&'a UndoStack);
+
+        impl Drop for TransactionGuard<'_> {
+            fn drop(&mut self) {
+                let pushed = {
+                    let mut state = self.0.state.borrow_mut();
+                    state.depth -= 1;
+                    if state.depth > 0 {
+                        return;
+                    }
+                    match state.open.take() {
+                        Some(mut step) if !step.changes.is_empty() => {
+                            step.last_edit = Instant::now();
+                            state.push(step);
+                            true
+                        }
+                        _ => false,
+                    }
+                };
+                if pushed {
+                    self.0.changed.notify();
+                }
+            }
+        }
+
+        {
+            let mut state = self.state.borrow_mut();
+            if state.depth == 0 {
+                state.open = Some(Step::new(Some(label.into())));
+            }
+            state.depth += 1;
+        }
+        let _guard = TransactionGuard(self);
+        // Batching makes the tracking effects run once the changes are made, while the
+        // transaction is still open.
+        batch(f)
+    }
+
+    /// Stops the next step from being coalesced with the last one.
+    pub fn break_coalescing(&self) {
+        self.state.borrow_mut().sealed = true;
+    }
+
+    /// Restores the values from before the last step. Returns false if there is nothing to undo.
+    pub fn undo(&self) -> bool {
+        let Some(step) = self.state.borrow_mut().undo.pop() else {
+            return false;
+        };
+        self.restore(
+            step.changes
+                .iter()
+                .rev()
+                .map(|change| (change, &*change.old)),
+        );
+        self.state.borrow_mut().redo.push(step);
+        self.changed.notify();
+        true
+    }
+
+    /// Applies the last undone step again. Returns false if there is nothing to redo.
+    pub fn redo(&self) -> bool {
+        let Some(step) = self.state.borrow_mut().redo.pop() else {
+            return false;
+        };
+        self.restore(step.changes.iter().map(|change| (change, &*change.new)));
+        self.state.borrow_mut().undo.push(step);
+        self.changed.notify();
+        true
+    }
+
+    /// Sets the signals of the changes to the values, without recording it.
+    fn restore<'a>(&self, changes: impl Iterator<Item = (&'a Change, &'a dyn Any)>) {
+        let changes: Vec<_> = changes.collect();
+        {
+            let mut state = self.state.borrow_mut();
+            for (change, _) in &changes {
+                if !state.restoring.contains(&change.signal) {
+                    state.restoring.push(change.signal);
+                }
+            }
+            state.sealed = true;
+        }
+        batch(|| {
+            for (change, value) in changes {
+                (change.set)(value);
+            }
+        });
+    }
+
+    /// Whether there is a step to undo. This subscribes the running effect to changes.
+    pub fn can_undo(&self) -> bool {
+        self.changed.track();
+        !self.state.borrow().undo.is_empty()
+    }
+
+    /// Whether there is a step to redo. This subscribes the running effect to changes.
+    pub fn can_redo(&self) -> bool {
+        self.changed.track();
+        !self.state.borrow().redo.is_empty()
+    }
+
+    /// The label of the transaction [`undo`](UndoStack::undo) would revert.
+    pub fn undo_label(&self) -> Option<String> {
+        self.changed.track();
+        self.state
+            .borrow()
+            .undo
+            .last()
+            .and_then(|step| step.label.clone())
+    }
+
+    /// The label of the transaction [`redo`](UndoStack::redo) would apply.
+    pub fn redo_label(&self) -> Option<String> {
+        self.changed.track();
+        self.state
+            .borrow()
+            .redo
+            .last()
+            .and_then(|step| step.label.clone())
+    }
+
+    /// Forgets all steps.
+    pub fn clear(&self) {
+        {
+            let mut state = self.state.borrow_mut();
+            state.undo.clear();
+            state.redo.clear();
+        }
+        self.changed.notify();
+    }
+}
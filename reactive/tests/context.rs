@@ -0,0 +1,50 @@
+use floem_reactive::{
+    as_child_of_current_scope, create_effect, create_rw_signal, provide_context, use_context,
+    with_context, with_scope, Scope,
+};
+
+#[derive(Clone, Debug, PartialEq)]
+struct Theme(&'static str);
+
+#[test]
+fn nearest_provider_is_used() {
+    let scope = Scope::new();
+    let (inner, outer) = with_scope(scope, || {
+        provide_context(Theme("light"));
+        let inner = with_context(Theme("dark"), use_context::<Theme>);
+        (inner, use_context::<Theme>())
+    });
+    assert_eq!(inner, Some(Theme("dark")));
+    assert_eq!(outer, Some(Theme("light")));
+    assert_eq!(use_context::<Theme>(), None);
+}
+
+#[test]
+fn contexts_are_seen_in_child_scopes_and_effects() {
+    let scope = Scope::new();
+    let signal = create_rw_signal(0);
+    let seen = create_rw_signal(Vec::new());
+    with_scope(scope, || {
+        provide_context(Theme("light"));
+        let child = as_child_of_current_scope(move |_: ()| {
+            create_effect(move |_| {
+                signal.track();
+                let theme = use_context::<Theme>();
+                seen.update(|seen| seen.push(theme));
+            });
+        });
+        child(());
+    });
+    signal.set(1);
+    assert_eq!(seen.get(), [Some(Theme("light")), Some(Theme("light"))]);
+}
+
+#[test]
+fn contexts_are_dropped_with_their_scope() {
+    let scope = Scope::new();
+    let child = scope.create_child();
+    with_scope(child, || provide_context(Theme("dark")));
+    assert_eq!(with_scope(child, use_context::<Theme>), Some(Theme("dark")));
+    scope.dispose();
+    assert_eq!(with_scope(child, use_context::<Theme>), None);
+}
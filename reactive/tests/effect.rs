@@ -68,3 +68,37 @@ fn batch_batch() {
 
     assert_eq!(count.get(), 2);
 }
+
+#[test]
+fn batch_panic() {
+    let name = create_rw_signal("John");
+
+    let count = Rc::new(Cell::new(0));
+
+    create_effect({
+        let count = count.clone();
+        move |_| {
+            name.track();
+
+            count.set(count.get() + 1);
+        }
+    });
+
+    assert_eq!(count.get(), 1);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        batch(|| {
+            name.set("Mary");
+            panic!("failed in batch");
+        })
+    }));
+    assert!(result.is_err());
+
+    // The effects deferred by the batch which panicked don't run at the end of the next one
+    batch(|| {});
+    assert_eq!(count.get(), 1);
+
+    // A panic in a batch ends it, so updates run effects right away again
+    name.set("Jane");
+    assert_eq!(count.get(), 2);
+}
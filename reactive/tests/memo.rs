@@ -0,0 +1,50 @@
+use std::{cell::Cell, rc::Rc};
+
+use floem_reactive::{create_effect, create_memo_with_eq, create_rw_signal};
+
+#[derive(Clone, Debug)]
+struct Item {
+    id: u32,
+    content: String,
+}
+
+#[test]
+fn memo_with_eq_skips_equal_values() {
+    let items = create_rw_signal(vec![Item {
+        id: 1,
+        content: "a".to_string(),
+    }]);
+    let memo = create_memo_with_eq(
+        move |_| items.get(),
+        |a: &Vec<Item>, b: &Vec<Item>| {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.id == b.id)
+        },
+    );
+
+    let count = Rc::new(Cell::new(0));
+    create_effect({
+        let count = count.clone();
+        move |_| {
+            memo.track();
+            count.set(count.get() + 1);
+        }
+    });
+    assert_eq!(count.get(), 1);
+
+    // Same ids with different content are equal by `eq`, so the memo doesn't notify
+    items.set(vec![Item {
+        id: 1,
+        content: "b".to_string(),
+    }]);
+    assert_eq!(count.get(), 1);
+    assert_eq!(memo.with_untracked(|items| items[0].content.clone()), "a");
+
+    items.update(|items| {
+        items.push(Item {
+            id: 2,
+            content: "c".to_string(),
+        })
+    });
+    assert_eq!(count.get(), 2);
+    assert_eq!(memo.with_untracked(|items| items.len()), 2);
+}
@@ -0,0 +1,48 @@
+use std::{cell::RefCell, rc::Rc};
+
+use floem_reactive::{create_rw_signal, on_cleanup, with_scope, Scope};
+
+#[test]
+fn cleanups_run_when_the_scope_is_disposed() {
+    let cleaned = Rc::new(RefCell::new(Vec::new()));
+    let parent = Scope::new();
+    let child = parent.create_child();
+    with_scope(parent, || {
+        let cleaned = cleaned.clone();
+        on_cleanup(move || cleaned.borrow_mut().push("parent"));
+    });
+    with_scope(child, || {
+        let cleaned = cleaned.clone();
+        on_cleanup(move || cleaned.borrow_mut().push("child"));
+    });
+    assert!(cleaned.borrow().is_empty());
+
+    parent.dispose();
+    assert_eq!(*cleaned.borrow(), vec!["parent", "child"]);
+
+    // The cleanups only run once
+    parent.dispose();
+    assert_eq!(cleaned.borrow().len(), 2);
+}
+
+#[test]
+fn cleanups_run_before_the_effect_reruns() {
+    let cleaned = Rc::new(RefCell::new(Vec::new()));
+    let signal = create_rw_signal(0);
+    let scope = Scope::new();
+    scope.create_effect({
+        let cleaned = cleaned.clone();
+        move |_| {
+            let value = signal.get();
+            let cleaned = cleaned.clone();
+            on_cleanup(move || cleaned.borrow_mut().push(value));
+        }
+    });
+    assert!(cleaned.borrow().is_empty());
+
+    signal.set(1);
+    assert_eq!(*cleaned.borrow(), vec![0]);
+
+    scope.dispose();
+    assert_eq!(*cleaned.borrow(), vec![0, 1]);
+}
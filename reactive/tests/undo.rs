@@ -0,0 +1,118 @@
+use std::time::Duration;
+
+use floem_reactive::{batch, create_rw_signal, UndoStack};
+
+#[test]
+fn undo_redo_transaction() {
+    let name = create_rw_signal("John".to_string());
+    let age = create_rw_signal(20);
+    let undo = UndoStack::new();
+    undo.track(name);
+    undo.track(age);
+
+    undo.transaction("Edit person", || {
+        name.set("Mary".to_string());
+        age.set(21);
+        age.set(22);
+    });
+    assert!(undo.can_undo());
+    assert_eq!(undo.undo_label().as_deref(), Some("Edit person"));
+
+    // The transaction is undone as one step
+    assert!(undo.undo());
+    assert_eq!(name.get(), "John");
+    assert_eq!(age.get(), 20);
+    assert!(!undo.can_undo());
+
+    assert!(undo.redo());
+    assert_eq!(name.get(), "Mary");
+    assert_eq!(age.get(), 22);
+    assert!(!undo.can_redo());
+}
+
+#[test]
+fn edits_outside_transactions() {
+    let count = create_rw_signal(0);
+    let undo = UndoStack::new();
+    undo.track(count);
+
+    count.set(1);
+    count.set(2);
+    undo.undo();
+    assert_eq!(count.get(), 1);
+
+    // A new edit clears the redo steps
+    count.set(5);
+    assert!(!undo.can_redo());
+    undo.undo();
+    assert_eq!(count.get(), 1);
+    undo.undo();
+    assert_eq!(count.get(), 0);
+    assert!(!undo.undo());
+}
+
+#[test]
+fn coalesce_typing() {
+    let text = create_rw_signal(String::new());
+    let undo = UndoStack::new().coalesce(Duration::from_secs(60), 3);
+    undo.track(text);
+
+    for c in "abcde".chars() {
+        undo.transaction("Type", || text.update(|text| text.push(c)));
+    }
+    // Keystrokes are grouped into steps of at most 3
+    undo.undo();
+    assert_eq!(text.get(), "abc");
+    undo.undo();
+    assert_eq!(text.get(), "");
+
+    undo.redo();
+    undo.redo();
+    assert_eq!(text.get(), "abcde");
+
+    // A different label, or breaking coalescing, starts a new step
+    undo.transaction("Delete", || text.set("abcd".to_string()));
+    undo.break_coalescing();
+    undo.transaction("Delete", || text.set("abc".to_string()));
+    undo.undo();
+    assert_eq!(text.get(), "abcd");
+    undo.undo();
+    assert_eq!(text.get(), "abcde");
+}
+
+#[test]
+fn undo_and_redo_within_a_batch() {
+    let count = create_rw_signal(0);
+    let undo = UndoStack::new();
+    undo.track(count);
+    count.set(1);
+    count.set(2);
+
+    // The restored value is only seen once the batch ends, and isn't recorded as a new step
+    batch(|| assert!(undo.undo()));
+    assert_eq!(count.get(), 1);
+    assert!(undo.can_redo());
+    batch(|| assert!(undo.redo()));
+    assert_eq!(count.get(), 2);
+    assert!(!undo.can_redo());
+
+    undo.undo();
+    undo.undo();
+    assert_eq!(count.get(), 0);
+    batch(|| {
+        undo.redo();
+        undo.redo();
+        undo.undo();
+    });
+    assert_eq!(count.get(), 1);
+    assert!(undo.can_redo());
+
+    // Edits after the batch are recorded
+    count.set(7);
+    assert!(!undo.can_redo());
+    assert!(undo.undo());
+    assert_eq!(count.get(), 1);
+    assert!(undo.undo());
+    assert_eq!(count.get(), 0);
+    assert!(!undo.undo());
+}
@@ -1,5 +1,5 @@
 pub use floem_cosmic_text as cosmic_text;
-use floem_cosmic_text::TextLayout;
+use floem_cosmic_text::{LayoutGlyph, LayoutRun, TextLayout};
 use floem_peniko::{
     kurbo::{Affine, Point, Rect, Shape},
     BrushRef,
@@ -8,11 +8,35 @@ use image::DynamicImage;
 pub use resvg::tiny_skia;
 pub use resvg::usvg;
 
+/// The graphics API a renderer draws with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+    /// Drawing on the CPU, which works without a GPU, like in virtual machines, remote desktops
+    /// and CI, but is slower.
+    Software,
+}
+
 pub struct Svg<'a> {
     pub tree: &'a usvg::Tree,
     pub hash: &'a [u8],
 }
 
+impl Svg<'_> {
+    /// The transform which scales the svg to fit in a `width` by `height` pixmap, keeping its
+    /// aspect ratio, and centers it in the pixmap.
+    pub fn fit_transform(&self, width: u32, height: u32) -> tiny_skia::Transform {
+        let size = self.tree.size;
+        let scale = (width as f64 / size.width()).min(height as f64 / size.height());
+        let x = (width as f64 - size.width() * scale) / 2.0;
+        let y = (height as f64 - size.height() * scale) / 2.0;
+        tiny_skia::Transform::from_row(scale as f32, 0.0, 0.0, scale as f32, x as f32, y as f32)
+    }
+}
+
 pub struct Img<'a> {
     pub img: &'a DynamicImage,
     pub data: &'a [u8],
@@ -45,9 +69,264 @@ pub trait Renderer {
     /// (even for right-to-left text).
     fn draw_text(&mut self, layout: &TextLayout, pos: impl Into<Point>);
 
+    /// Draw a [`TextLayout`] like [`Renderer::draw_text`], with its lines placed by `lines`, for
+    /// the text styles which aren't part of the layout, like alignment and letter spacing.
+    fn draw_text_lines(&mut self, layout: &TextLayout, pos: impl Into<Point>, lines: &TextLines);
+
     fn draw_svg<'b>(&mut self, svg: Svg<'b>, rect: Rect, brush: Option<impl Into<BrushRef<'b>>>);
 
     fn draw_img(&mut self, img: Img<'_>, rect: Rect);
 
     fn finish(&mut self) -> Option<DynamicImage>;
 }
+
+/// How the lines of text are aligned in the width they're drawn in, see [`TextLines`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Aligns the lines of left-to-right text to the left and those of right-to-left text to
+    /// the right.
+    #[default]
+    Start,
+    /// Aligns the lines to the opposite side of [`TextAlign::Start`].
+    End,
+    Left,
+    Right,
+    Center,
+    /// Stretches the spaces between the words of each line so it fills the width. The last line
+    /// of each paragraph, and lines with a single word, are aligned to the start instead.
+    Justify,
+}
+
+/// How the lines of a [`TextLayout`] are placed when it's drawn with
+/// [`Renderer::draw_text_lines`]. The spacing and alignment are applied when the text is drawn,
+/// after it's wrapped.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextLines {
+    /// The space added after each glyph.
+    pub letter_spacing: f32,
+    /// The space added after each space, on top of the letter spacing.
+    pub word_spacing: f32,
+    pub align: TextAlign,
+    /// The width the lines are aligned in, from where the text is drawn.
+    pub width: f32,
+}
+
+impl TextLines {
+    /// Where the line `run` is drawn. `is_paragraph_end` is `true` for the last line of a
+    /// paragraph, which isn't justified.
+    pub fn line(&self, run: &LayoutRun, is_paragraph_end: bool) -> LinePlacement {
+        let mut placement = LinePlacement {
+            offset: 0.0,
+            letter_spacing: self.letter_spacing,
+            word_spacing: self.word_spacing,
+            justify: 0.0,
+            words: words_range(run),
+        };
+        let spacing: f32 = (0..run.glyphs.len())
+            .map(|index| placement.glyph_spacing(run, index))
+            .sum();
+        let free = (self.width - run.line_w - spacing).max(0.0);
+        let (start, end) = if run.rtl { (free, 0.0) } else { (0.0, free) };
+        placement.offset = match self.align {
+            TextAlign::Start => start,
+            TextAlign::End => end,
+            TextAlign::Left => 0.0,
+            TextAlign::Right => free,
+            TextAlign::Center => free / 2.0,
+            TextAlign::Justify => {
+                let spaces = placement
+                    .words
+                    .clone()
+                    .filter(|index| is_space(run, &run.glyphs[*index]))
+                    .count();
+                if is_paragraph_end || spaces == 0 {
+                    start
+                } else {
+                    placement.justify = free / spaces as f32;
+                    0.0
+                }
+            }
+        };
+        placement
+    }
+
+    /// The width added to the widest line of `layout` by the letter and word spacing.
+    pub fn spacing_width(&self, layout: &TextLayout) -> f32 {
+        let mut widest = 0.0f32;
+        let mut widest_spaced = 0.0f32;
+        for run in layout.layout_runs() {
+            let placement = LinePlacement {
+                offset: 0.0,
+                letter_spacing: self.letter_spacing,
+                word_spacing: self.word_spacing,
+                justify: 0.0,
+                words: 0..0,
+            };
+            let spacing: f32 = (0..run.glyphs.len())
+                .map(|index| placement.glyph_spacing(&run, index))
+                .sum();
+            widest = widest.max(run.line_w);
+            widest_spaced = widest_spaced.max(run.line_w + spacing);
+        }
+        widest_spaced - widest
+    }
+}
+
+/// Where a line of text is drawn, see [`TextLines::line`].
+#[derive(Clone, Debug)]
+pub struct LinePlacement {
+    /// How far the line is moved to the right.
+    pub offset: f32,
+    letter_spacing: f32,
+    word_spacing: f32,
+    /// The space added after the spaces between the words of a justified line.
+    justify: f32,
+    /// The glyphs from the first to the last one which isn't a space.
+    words: std::ops::Range<usize>,
+}
+
+impl LinePlacement {
+    /// The space added after the glyph at `index` in `run`, which moves the glyphs after it.
+    pub fn glyph_spacing(&self, run: &LayoutRun, index: usize) -> f32 {
+        if is_space(run, &run.glyphs[index]) {
+            let justify = if self.words.contains(&index) {
+                self.justify
+            } else {
+                0.0
+            };
+            self.letter_spacing + self.word_spacing + justify
+        } else {
+            self.letter_spacing
+        }
+    }
+}
+
+fn is_space(run: &LayoutRun, glyph: &LayoutGlyph) -> bool {
+    run.text
+        .get(glyph.start..glyph.end)
+        .is_some_and(|text| text.chars().all(|c| c == ' ' || c == '\u{a0}'))
+}
+
+/// The glyphs of `run` from its first to its last one which isn't a space, which leaves out the
+/// spaces at the ends of wrapped lines.
+fn words_range(run: &LayoutRun) -> std::ops::Range<usize> {
+    let first = run.glyphs.iter().position(|glyph| !is_space(run, glyph));
+    let last = run.glyphs.iter().rposition(|glyph| !is_space(run, glyph));
+    match (first, last) {
+        (Some(first), Some(last)) => first..last + 1,
+        _ => 0..0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use floem_cosmic_text::{Attrs, AttrsList, LayoutRun, TextLayout};
+    use resvg::usvg::{self, TreeParsing};
+
+    use crate::{LinePlacement, Svg, TextAlign, TextLines};
+
+    #[test]
+    fn svgs_are_centered_in_their_box() {
+        let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" width="10" height="20"></svg>"#;
+        let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).unwrap();
+        let svg = Svg {
+            tree: &tree,
+            hash: &[],
+        };
+        let transform = svg.fit_transform(40, 20);
+        assert_eq!((transform.sx, transform.sy), (1.0, 1.0));
+        assert_eq!((transform.tx, transform.ty), (15.0, 0.0));
+
+        let transform = svg.fit_transform(10, 40);
+        assert_eq!((transform.sx, transform.tx, transform.ty), (1.0, 0.0, 10.0));
+    }
+
+    fn layout(text: &str) -> TextLayout {
+        let mut layout = TextLayout::new();
+        layout.set_text(text, AttrsList::new(Attrs::new()));
+        layout
+    }
+
+    /// The space added to the line `run` by `placement`.
+    fn added_width(placement: &LinePlacement, run: &LayoutRun) -> f32 {
+        (0..run.glyphs.len())
+            .map(|index| placement.glyph_spacing(run, index))
+            .sum()
+    }
+
+    #[test]
+    fn lines_are_aligned_in_the_free_space() {
+        let offset = |text: &str, align| {
+            let layout = layout(text);
+            let run = layout.layout_runs().next().unwrap();
+            let lines = TextLines {
+                align,
+                width: run.line_w + 40.0,
+                ..Default::default()
+            };
+            // Rounded, as the free space is what's left of the width after the line
+            lines.line(&run, true).offset.round()
+        };
+        assert_eq!(offset("abc", TextAlign::Left), 0.0);
+        assert_eq!(offset("abc", TextAlign::Right), 40.0);
+        assert_eq!(offset("abc", TextAlign::Center), 20.0);
+        assert_eq!(offset("abc", TextAlign::Start), 0.0);
+        assert_eq!(offset("abc", TextAlign::End), 40.0);
+        // The start of right-to-left text is on the right
+        assert_eq!(offset("שלום", TextAlign::Start), 40.0);
+        assert_eq!(offset("שלום", TextAlign::End), 0.0);
+        assert_eq!(offset("שלום", TextAlign::Left), 0.0);
+    }
+
+    #[test]
+    fn justified_lines_stretch_their_spaces_to_fill_the_width() {
+        let layout = layout("aa bb cc ");
+        let run = layout.layout_runs().next().unwrap();
+        let lines = TextLines {
+            align: TextAlign::Justify,
+            width: run.line_w + 40.0,
+            ..Default::default()
+        };
+        let placement = lines.line(&run, false);
+        assert_eq!(placement.offset, 0.0);
+        // Only the spaces between the words are stretched, not the one at the end
+        let spaces: Vec<_> = (0..run.glyphs.len())
+            .map(|index| placement.glyph_spacing(&run, index))
+            .filter(|spacing| *spacing > 0.0)
+            .map(f32::round)
+            .collect();
+        assert_eq!(spaces, [20.0, 20.0]);
+
+        // The last line of a paragraph, and lines without spaces, are aligned to the start
+        let placement = lines.line(&run, true);
+        assert_eq!(
+            (placement.offset, added_width(&placement, &run)),
+            (0.0, 0.0)
+        );
+        let layout = self::layout("abc");
+        let run = layout.layout_runs().next().unwrap();
+        let placement = lines.line(&run, false);
+        assert_eq!(added_width(&placement, &run), 0.0);
+    }
+
+    #[test]
+    fn spacing_is_added_after_glyphs_and_spaces() {
+        let lines = TextLines {
+            letter_spacing: 2.0,
+            word_spacing: 5.0,
+            ..Default::default()
+        };
+        let layout = layout("a b");
+        let run = layout.layout_runs().next().unwrap();
+        assert_eq!(added_width(&lines.line(&run, true), &run), 2.0 * 3.0 + 5.0);
+
+        // Only the widest line counts for the width of the text
+        let layout = self::layout("abc\nab");
+        assert_eq!(lines.spacing_width(&layout).round(), 2.0 * 3.0);
+        let lines = TextLines {
+            word_spacing: 5.0,
+            ..Default::default()
+        };
+        assert_eq!(lines.spacing_width(&self::layout("a b")).round(), 5.0);
+    }
+}
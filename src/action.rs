@@ -1,10 +1,11 @@
 use std::{
-    path::PathBuf,
+    cell::Cell,
+    rc::Rc,
     sync::atomic::AtomicU64,
     time::{Duration, Instant},
 };
 
-use floem_reactive::Scope;
+use floem_reactive::{on_cleanup, with_scope, Scope};
 use floem_winit::window::ResizeDirection;
 use kurbo::{Point, Size, Vec2};
 
@@ -16,6 +17,7 @@ use crate::{
     menu::Menu,
     update::{UpdateMessage, CENTRAL_UPDATE_MESSAGES},
     view::View,
+    window::{BlurStyle, WindowRegion},
     window_handle::{get_current_view, set_current_view},
 };
 
@@ -38,6 +40,38 @@ pub fn minimize_window() {
     add_update_message(UpdateMessage::MinimizeWindow);
 }
 
+/// Requests the current window to be closed, as if its close button was pressed.
+/// Listeners of [`EventListener::WindowCloseRequested`](crate::event::EventListener::WindowCloseRequested)
+/// can keep the window open.
+pub fn request_close_window() {
+    add_update_message(UpdateMessage::RequestCloseWindow);
+}
+
+/// Shows or hides the title bar and borders of the current window.
+pub fn set_window_decorations(decorations: bool) {
+    add_update_message(UpdateMessage::SetWindowDecorations(decorations));
+}
+
+/// Sets the blur applied to what is shown behind the current window, if it's transparent.
+pub fn set_window_blur(blur: BlurStyle) {
+    add_update_message(UpdateMessage::SetWindowBlur(blur));
+}
+
+/// If set to true, pointer input passes through the current window to what is behind it.
+pub fn set_window_click_through(click_through: bool) {
+    add_update_message(UpdateMessage::SetWindowClickThrough(click_through));
+}
+
+/// Sets a function which decides what pressing the primary pointer button does at a point in the
+/// current window, like `-webkit-app-region` on the web. The point is in logical pixels relative
+/// to the window.
+///
+/// This is useful for windows without decorations, to make a custom title bar draggable or to
+/// resize the window from its edges. Presses in [`WindowRegion::Client`] are sent to the views.
+pub fn set_window_hit_test(hit_test: impl Fn(Point) -> WindowRegion + 'static) {
+    add_update_message(UpdateMessage::WindowHitTest(Box::new(hit_test)));
+}
+
 pub fn drag_window() {
     add_update_message(UpdateMessage::DragWindow);
 }
@@ -105,38 +139,108 @@ pub fn exec_after(duration: Duration, action: impl FnOnce(TimerToken) + 'static)
     token
 }
 
+/// Cancels a timer started with [`exec_after`]. Its action is dropped without being called.
+pub fn cancel_timer(token: TimerToken) {
+    add_app_update_event(AppUpdateEvent::CancelTimer { token });
+}
+
+/// A timer started with [`set_timeout`] or [`set_interval`].
+#[derive(Clone)]
+pub struct TimerHandle {
+    state: Rc<TimerState>,
+}
+
+struct TimerState {
+    token: Cell<TimerToken>,
+    cancelled: Cell<bool>,
+}
+
+impl TimerHandle {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Rc::new(TimerState {
+                token: Cell::new(TimerToken::INVALID),
+                cancelled: Cell::new(false),
+            }),
+        }
+    }
+
+    /// Stops the timer. The action isn't called again.
+    pub fn cancel(&self) {
+        self.state.cancelled.set(true);
+        cancel_timer(self.state.token.get());
+    }
+
+    /// Whether the timer was cancelled, or its owner was removed.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.get()
+    }
+
+    /// Stops the timer when the view with `id` is removed.
+    pub fn owner(self, id: Id) -> Self {
+        let timer = self.clone();
+        with_scope(id.scope(), || on_cleanup(move || timer.cancel()));
+        self
+    }
+}
+
+/// Calls `action` on the UI thread once `duration` has elapsed, unless the returned handle
+/// is cancelled first.
+pub fn set_timeout(duration: Duration, action: impl FnOnce() + 'static) -> TimerHandle {
+    let handle = TimerHandle::new();
+    let state = handle.state.clone();
+    let token = exec_after(duration, move |_| {
+        if !state.cancelled.get() {
+            action();
+        }
+    });
+    handle.state.token.set(token);
+    handle
+}
+
+/// Calls `action` on the UI thread every `duration` until the returned handle is cancelled.
+pub fn set_interval(duration: Duration, action: impl Fn() + 'static) -> TimerHandle {
+    fn schedule(duration: Duration, state: Rc<TimerState>, action: Rc<dyn Fn()>) {
+        let token = exec_after(duration, {
+            let state = state.clone();
+            move |_| {
+                if !state.cancelled.get() {
+                    action();
+                    // The action may cancel the timer
+                    if !state.cancelled.get() {
+                        schedule(duration, state, action);
+                    }
+                }
+            }
+        });
+        state.token.set(token);
+    }
+
+    let handle = TimerHandle::new();
+    schedule(duration, handle.state.clone(), Rc::new(action));
+    handle
+}
+
+/// Shows an open dialog and calls `file_info_action` with the chosen file.
+/// Use [`FileDialog`](crate::file::FileDialog) to choose multiple files.
 pub fn open_file(
     options: FileDialogOptions,
     file_info_action: impl Fn(Option<FileInfo>) + 'static,
 ) {
-    let send = create_ext_action(Scope::new(), move |path: Option<PathBuf>| {
-        file_info_action(path.map(|path| FileInfo { path, format: None }))
+    let send = create_ext_action(Scope::new(), move |file: Option<FileInfo>| {
+        file_info_action(file)
     });
     std::thread::spawn(move || {
-        let mut dialog = rfd::FileDialog::new();
-        if let Some(path) = options.starting_directory.as_ref() {
-            dialog = dialog.set_directory(path);
-        }
-        let path = if options.select_directories {
-            dialog.pick_folder()
-        } else {
-            dialog.pick_file()
-        };
-        send(path);
+        send(options.pick().into_iter().next());
     });
 }
 
 pub fn save_as(options: FileDialogOptions, file_info_action: impl Fn(Option<FileInfo>) + 'static) {
-    let send = create_ext_action(Scope::new(), move |path: Option<PathBuf>| {
-        file_info_action(path.map(|path| FileInfo { path, format: None }))
+    let send = create_ext_action(Scope::new(), move |file: Option<FileInfo>| {
+        file_info_action(file)
     });
     std::thread::spawn(move || {
-        let mut dialog = rfd::FileDialog::new();
-        if let Some(path) = options.starting_directory.as_ref() {
-            dialog = dialog.set_directory(path);
-        }
-        let path = dialog.save_file();
-        send(path);
+        send(options.pick_save());
     });
 }
 
@@ -144,6 +248,18 @@ pub fn show_context_menu(menu: Menu, pos: Option<Point>) {
     add_update_message(UpdateMessage::ShowContextMenu { menu, pos });
 }
 
+/// Sets the menu bar of the current window, where each submenu of `menu` is a menu of the bar,
+/// such as File, Edit and View.
+///
+/// The bar is drawn above the view of the window, with the [`MenuBarClass`] and the titles of
+/// its menus with the [`MenuBarItemClass`]. On macOS, `menu` becomes the native menu bar of the
+/// app instead, with the first submenu as the application menu.
+///
+/// Pressing the [shortcut](crate::menu::MenuItem::shortcut) of an enabled item runs its action,
+/// unless the focused view handled the key.
+///
+/// [`MenuBarClass`]: crate::menu::MenuBarClass
+/// [`MenuBarItemClass`]: crate::menu::MenuBarItemClass
 pub fn set_window_menu(menu: Menu) {
     add_update_message(UpdateMessage::WindowMenu { menu });
 }
@@ -179,3 +295,12 @@ pub fn add_overlay<V: View + 'static>(position: Point, view: impl FnOnce(Id) ->
 pub fn remove_overlay(id: Id) {
     add_update_message(UpdateMessage::RemoveOverlay { id });
 }
+
+/// Shows the content of a [`portal`](crate::views::portal) over the current window.
+pub(crate) fn add_portal(view: Box<dyn View>, scope: Scope) {
+    add_update_message(UpdateMessage::AddPortal { view, scope });
+}
+
+pub(crate) fn remove_portal(id: Id) {
+    add_update_message(UpdateMessage::RemovePortal { id });
+}
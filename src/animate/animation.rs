@@ -1,4 +1,4 @@
-use crate::style::{Background, BorderColor, BorderRadius, TextColor};
+use crate::style::{Background, BorderColor, BorderRadius, Fill, TextColor};
 
 use super::{
     anim_val::AnimValue, AnimId, AnimPropKind, AnimState, AnimStateKind, AnimatedProp, Easing,
@@ -140,7 +140,8 @@ impl Animation {
         create_effect(move |_| {
             let background = bg_fn();
 
-            self.id.update_style_prop(Background, Some(background));
+            self.id
+                .update_style_prop(Background, Some(Fill::Solid(background)));
         });
 
         self
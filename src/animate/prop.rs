@@ -2,7 +2,11 @@ use std::{any::Any, rc::Rc};
 
 use floem_peniko::Color;
 
-use crate::{animate::AnimDirection, style::StylePropRef, unit::Px};
+use crate::{
+    animate::AnimDirection,
+    style::{Fill, StylePropRef, StylePropValue},
+    unit::Px,
+};
 
 use super::{anim_val::AnimValue, assert_valid_time, SizeUnit};
 
@@ -145,6 +149,15 @@ impl AnimatedProp {
                         self.animate_color(from, to, time, direction),
                     )));
                 }
+                if let Some(from) = from.downcast_ref::<Option<Fill>>() {
+                    let to = to.downcast_ref::<Option<Fill>>().unwrap();
+                    let (from, to) = match direction {
+                        AnimDirection::Forward => (from, to),
+                        AnimDirection::Backward => (to, from),
+                    };
+                    let fill = from.interpolate(to, time).unwrap_or_else(|| to.clone());
+                    return AnimValue::Prop(Rc::new(fill));
+                }
                 panic!("unknown type for {prop:?}")
             }
             AnimatedProp::Width { from, to, unit: _ }
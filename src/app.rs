@@ -1,6 +1,13 @@
-use std::{cell::RefCell, rc::Rc, sync::Arc};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    sync::{Arc, OnceLock},
+    thread::ThreadId,
+};
 
 use floem_reactive::WriteSignal;
+use floem_renderer::Backend;
 use floem_winit::{
     event_loop::{ControlFlow, EventLoop, EventLoopBuilder, EventLoopProxy},
     monitor::MonitorHandle,
@@ -10,19 +17,39 @@ use once_cell::sync::Lazy;
 use parking_lot::Mutex;
 
 use crate::{
-    action::Timer, app_handle::ApplicationHandle, clipboard::Clipboard, inspector::Capture,
-    profiler::Profile, view::View, window::WindowConfig,
+    action::{Timer, TimerToken},
+    app_handle::ApplicationHandle,
+    clipboard::Clipboard,
+    executor::{set_executor, Executor},
+    inspector::Capture,
+    profiler::Profile,
+    view::View,
+    window::WindowConfig,
 };
 
 use raw_window_handle::HasRawDisplayHandle;
 
 type AppEventCallback = dyn Fn(AppEvent);
 
+static UI_THREAD: OnceLock<ThreadId> = OnceLock::new();
+
+/// Whether this is the thread the [`Application`] was created on, which runs the UI.
+/// This is also true on every thread when no application was created, like in tests.
+pub fn is_ui_thread() -> bool {
+    UI_THREAD
+        .get()
+        .map(|id| *id == std::thread::current().id())
+        .unwrap_or(true)
+}
+
 static EVENT_LOOP_PROXY: Lazy<Arc<Mutex<Option<EventLoopProxy<UserEvent>>>>> =
     Lazy::new(|| Arc::new(Mutex::new(None)));
 
 thread_local! {
     pub(crate) static APP_UPDATE_EVENTS: RefCell<Vec<AppUpdateEvent>> = Default::default();
+    pub(crate) static OPEN_WINDOWS: RefCell<Vec<WindowId>> = Default::default();
+    /// The backends the open windows are rendered with.
+    pub(crate) static WINDOW_BACKENDS: RefCell<HashMap<WindowId, Backend>> = Default::default();
 }
 
 pub fn launch<V: View + 'static>(app_view: impl Fn() -> V + 'static) {
@@ -48,6 +75,16 @@ pub(crate) enum AppUpdateEvent {
     CloseWindow {
         window_id: WindowId,
     },
+    FocusWindow {
+        window_id: WindowId,
+    },
+    RequestCloseWindow {
+        window_id: WindowId,
+    },
+    SetWindowVisible {
+        window_id: WindowId,
+        visible: bool,
+    },
     CaptureWindow {
         window_id: WindowId,
         capture: WriteSignal<Option<Rc<Capture>>>,
@@ -59,6 +96,11 @@ pub(crate) enum AppUpdateEvent {
     RequestTimer {
         timer: Timer,
     },
+    CancelTimer {
+        token: TimerToken,
+    },
+    #[cfg(feature = "stylesheet")]
+    StylesheetChanged,
     #[cfg(target_os = "linux")]
     MenuAction {
         window_id: WindowId,
@@ -94,6 +136,7 @@ impl Application {
         let event_loop = EventLoopBuilder::with_user_event()
             .build()
             .expect("can't start the event loop");
+        let _ = UI_THREAD.set(std::thread::current().id());
         let event_loop_proxy = event_loop.create_proxy();
         *EVENT_LOOP_PROXY.lock() = Some(event_loop_proxy.clone());
         unsafe {
@@ -107,6 +150,13 @@ impl Application {
         }
     }
 
+    /// Sets the executor futures are spawned on. See [`executor`](crate::executor).
+    /// This is ignored if the executor was already set.
+    pub fn executor(self, executor: impl Executor) -> Self {
+        let _ = set_executor(executor);
+        self
+    }
+
     pub fn on_event(mut self, action: impl Fn(AppEvent) + 'static) -> Self {
         self.event_listener = Some(Box::new(action));
         self
@@ -6,21 +6,28 @@ use floem_winit::{
     event_loop::{ControlFlow, EventLoopWindowTarget},
     window::WindowId,
 };
-use kurbo::{Point, Size};
+use kurbo::{Point, Rect, Size};
 
 use crate::{
     action::{Timer, TimerToken},
-    app::{AppUpdateEvent, UserEvent, APP_UPDATE_EVENTS},
-    ext_event::EXT_EVENT_HANDLER,
+    app::{AppUpdateEvent, UserEvent, APP_UPDATE_EVENTS, OPEN_WINDOWS, WINDOW_BACKENDS},
+    event::Event,
+    ext_event::{EXT_EVENT_HANDLER, PROXY_ACTIONS},
     inspector::Capture,
     profiler::{Profile, ProfileEvent},
     view::View,
-    window::WindowConfig,
+    window::{monitor_rect, BlurStyle, WindowConfig},
     window_handle::WindowHandle,
 };
 
+struct WindowParent {
+    parent: WindowId,
+    modal: bool,
+}
+
 pub(crate) struct ApplicationHandle {
     window_handles: HashMap<floem_winit::window::WindowId, WindowHandle>,
+    window_parents: HashMap<WindowId, WindowParent>,
     timers: HashMap<TimerToken, Timer>,
 }
 
@@ -28,10 +35,19 @@ impl ApplicationHandle {
     pub(crate) fn new() -> Self {
         Self {
             window_handles: HashMap::new(),
+            window_parents: HashMap::new(),
             timers: HashMap::new(),
         }
     }
 
+    /// Returns the open modal child window of `window_id`, if there is one.
+    fn modal_child(&self, window_id: WindowId) -> Option<WindowId> {
+        self.window_parents
+            .iter()
+            .find(|(_, parent)| parent.modal && parent.parent == window_id)
+            .map(|(child, _)| *child)
+    }
+
     pub(crate) fn handle_user_event(
         &mut self,
         event_loop: &EventLoopWindowTarget<UserEvent>,
@@ -63,9 +79,39 @@ impl ApplicationHandle {
                 AppUpdateEvent::CloseWindow { window_id } => {
                     self.close_window(window_id, event_loop);
                 }
+                AppUpdateEvent::RequestCloseWindow { window_id } => {
+                    self.handle_window_event(window_id, WindowEvent::CloseRequested, event_loop);
+                }
+                AppUpdateEvent::FocusWindow { window_id } => {
+                    let window = self
+                        .window_handles
+                        .get(&window_id)
+                        .and_then(|handle| handle.window.as_ref());
+                    if let Some(window) = window {
+                        window.focus_window();
+                    }
+                }
+                AppUpdateEvent::SetWindowVisible { window_id, visible } => {
+                    let window = self
+                        .window_handles
+                        .get(&window_id)
+                        .and_then(|handle| handle.window.as_ref());
+                    if let Some(window) = window {
+                        window.set_visible(visible);
+                    }
+                }
                 AppUpdateEvent::RequestTimer { timer } => {
                     self.request_timer(timer, event_loop);
                 }
+                AppUpdateEvent::CancelTimer { token } => {
+                    self.timers.remove(&token);
+                }
+                #[cfg(feature = "stylesheet")]
+                AppUpdateEvent::StylesheetChanged => {
+                    for handle in self.window_handles.values_mut() {
+                        handle.restyle();
+                    }
+                }
                 AppUpdateEvent::CaptureWindow { window_id, capture } => {
                     capture.set(self.capture_window(window_id).map(Rc::new));
                 }
@@ -106,6 +152,29 @@ impl ApplicationHandle {
         event: WindowEvent,
         event_loop: &EventLoopWindowTarget<UserEvent>,
     ) {
+        if let Some(child) = self.modal_child(window_id) {
+            // Windows with a modal child ignore input, and focus the child when clicked.
+            match event {
+                WindowEvent::MouseInput { .. } => {
+                    let child = self
+                        .window_handles
+                        .get(&child)
+                        .and_then(|handle| handle.window.as_ref());
+                    if let Some(child) = child {
+                        child.focus_window();
+                    }
+                    return;
+                }
+                WindowEvent::CloseRequested
+                | WindowEvent::KeyboardInput { .. }
+                | WindowEvent::Ime(..)
+                | WindowEvent::CursorMoved { .. }
+                | WindowEvent::MouseWheel { .. }
+                | WindowEvent::Touch(_) => return,
+                _ => {}
+            }
+        }
+
         let window_handle = match self.window_handles.get_mut(&window_id) {
             Some(window_handle) => window_handle,
             None => return,
@@ -162,19 +231,30 @@ impl ApplicationHandle {
                 window_handle.position(point);
             }
             WindowEvent::CloseRequested => {
-                self.close_window(window_id, event_loop);
+                if window_handle
+                    .event(Event::WindowCloseRequested)
+                    .is_continue()
+                {
+                    self.close_window(window_id, event_loop);
+                }
             }
             WindowEvent::Destroyed => {
                 self.close_window(window_id, event_loop);
             }
-            WindowEvent::DroppedFile(_) => {}
-            WindowEvent::HoveredFile(_) => {}
-            WindowEvent::HoveredFileCancelled => {}
+            WindowEvent::DroppedFile(path) => {
+                window_handle.dropped_file(path);
+            }
+            WindowEvent::HoveredFile(path) => {
+                window_handle.hovered_file(path);
+            }
+            WindowEvent::HoveredFileCancelled => {
+                window_handle.hovered_file_cancelled();
+            }
             WindowEvent::Focused(focused) => {
                 window_handle.focused(focused);
             }
             WindowEvent::KeyboardInput { event, .. } => {
-                window_handle.key_event(event);
+                window_handle.key_event(event.into());
             }
             WindowEvent::ModifiersChanged(modifiers) => {
                 window_handle.modifiers = modifiers.state();
@@ -197,12 +277,21 @@ impl ApplicationHandle {
             WindowEvent::MouseInput { state, button, .. } => {
                 window_handle.mouse_input(button, state);
             }
-            WindowEvent::TouchpadMagnify { .. } => {}
+            WindowEvent::TouchpadMagnify { delta, phase, .. } => {
+                window_handle.touchpad_pinch(delta, 0.0, phase);
+            }
             WindowEvent::SmartMagnify { .. } => {}
-            WindowEvent::TouchpadRotate { .. } => {}
-            WindowEvent::TouchpadPressure { .. } => {}
+            WindowEvent::TouchpadRotate { delta, phase, .. } => {
+                // winit reports counterclockwise degrees
+                window_handle.touchpad_pinch(0.0, -(delta as f64).to_radians(), phase);
+            }
+            WindowEvent::TouchpadPressure { pressure, .. } => {
+                window_handle.touchpad_pressure(pressure as f64);
+            }
             WindowEvent::AxisMotion { .. } => {}
-            WindowEvent::Touch(_) => {}
+            WindowEvent::Touch(touch) => {
+                window_handle.touch(touch);
+            }
             WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
                 window_handle.scale(scale_factor);
             }
@@ -244,6 +333,23 @@ impl ApplicationHandle {
     ) {
         let mut window_builder = floem_winit::window::WindowBuilder::new();
         let transparent = config.as_ref().and_then(|c| c.transparent).unwrap_or(false);
+        let persist_geometry = config
+            .as_ref()
+            .and_then(|config| config.persist_geometry.clone());
+        let click_through = config
+            .as_ref()
+            .and_then(|c| c.click_through)
+            .unwrap_or(false);
+        let partial_present = config.as_ref().is_some_and(|c| c.partial_present);
+        let render_mode = config.as_ref().map(|c| c.render_mode).unwrap_or_default();
+        let backend = config.as_ref().and_then(|c| c.backend);
+        let max_fps = config.as_ref().and_then(|c| c.max_fps);
+        let parent = config.as_ref().and_then(|config| {
+            config.parent.map(|parent| WindowParent {
+                parent,
+                modal: config.modal,
+            })
+        });
         let apply_default_theme = if let Some(config) = config {
             if let Some(size) = config.size {
                 let size = if size.width == 0.0 || size.height == 0.0 {
@@ -257,6 +363,10 @@ impl ApplicationHandle {
             if let Some(pos) = config.position {
                 window_builder = window_builder.with_position(LogicalPosition::new(pos.x, pos.y));
             }
+            #[cfg(target_os = "macos")]
+            let traffic_lights_offset = config
+                .traffic_lights_offset
+                .unwrap_or(Point::new(11.0, 16.0));
             if let Some(show_titlebar) = config.show_titlebar {
                 #[cfg(target_os = "macos")]
                 if !show_titlebar {
@@ -266,7 +376,10 @@ impl ApplicationHandle {
                         .with_title_hidden(true)
                         .with_titlebar_transparent(true)
                         .with_fullsize_content_view(true)
-                        .with_traffic_lights_offset(11.0, 16.0);
+                        .with_traffic_lights_offset(
+                            traffic_lights_offset.x,
+                            traffic_lights_offset.y,
+                        );
                 }
                 #[cfg(not(target_os = "macos"))]
                 if !show_titlebar {
@@ -276,6 +389,30 @@ impl ApplicationHandle {
             if let Some(transparent) = config.transparent {
                 window_builder = window_builder.with_transparent(transparent);
             }
+            if let Some(blur) = config.blur {
+                window_builder = window_builder.with_blur(blur == BlurStyle::Blur);
+            }
+            if let Some(persist) = persist_geometry.as_ref() {
+                if let Some(geometry) = persist.store.load(&persist.key) {
+                    let monitors: Vec<Rect> = event_loop
+                        .available_monitors()
+                        .map(|monitor| {
+                            monitor_rect(monitor.position(), monitor.size(), monitor.scale_factor())
+                        })
+                        .collect();
+                    let geometry = geometry.fit_to_monitors(&monitors);
+                    window_builder = window_builder
+                        .with_position(LogicalPosition::new(
+                            geometry.position.x,
+                            geometry.position.y,
+                        ))
+                        .with_inner_size(LogicalSize::new(
+                            geometry.size.width,
+                            geometry.size.height,
+                        ))
+                        .with_maximized(geometry.maximized);
+                }
+            }
             if let Some(fullscreen) = config.fullscreen {
                 window_builder = window_builder.with_fullscreen(Some(fullscreen));
             }
@@ -294,22 +431,44 @@ impl ApplicationHandle {
             Ok(window) => window,
             Err(_) => return,
         };
+        if click_through {
+            let _ = window.set_cursor_hittest(false);
+        }
         let window_id = window.id();
-        let window_handle = WindowHandle::new(window, view_fn, transparent, apply_default_theme);
+        let mut window_handle =
+            WindowHandle::new(window, view_fn, transparent, apply_default_theme, backend);
+        window_handle.persist_geometry = persist_geometry;
+        window_handle.set_partial_present(partial_present);
+        window_handle.set_render_mode(render_mode, max_fps);
+        let backend = window_handle.backend();
         self.window_handles.insert(window_id, window_handle);
+        if let Some(parent) = parent {
+            self.window_parents.insert(window_id, parent);
+        }
+        OPEN_WINDOWS.with(|windows| windows.borrow_mut().push(window_id));
+        WINDOW_BACKENDS.with(|backends| backends.borrow_mut().insert(window_id, backend));
     }
 
-    fn close_window(
-        &mut self,
-        window_id: WindowId,
-        #[cfg(target_os = "macos")] _event_loop: &EventLoopWindowTarget<UserEvent>,
-        #[cfg(not(target_os = "macos"))] event_loop: &EventLoopWindowTarget<UserEvent>,
-    ) {
+    fn close_window(&mut self, window_id: WindowId, event_loop: &EventLoopWindowTarget<UserEvent>) {
         if let Some(handle) = self.window_handles.get_mut(&window_id) {
+            handle.save_geometry();
             handle.window = None;
             handle.destroy();
         }
         self.window_handles.remove(&window_id);
+        self.window_parents.remove(&window_id);
+        OPEN_WINDOWS.with(|windows| windows.borrow_mut().retain(|id| *id != window_id));
+        WINDOW_BACKENDS.with(|backends| backends.borrow_mut().remove(&window_id));
+
+        let children: Vec<WindowId> = self
+            .window_parents
+            .iter()
+            .filter(|(_, parent)| parent.parent == window_id)
+            .map(|(child, _)| *child)
+            .collect();
+        for child in children {
+            self.close_window(child, event_loop);
+        }
         #[cfg(not(target_os = "macos"))]
         if self.window_handles.is_empty() {
             event_loop.exit();
@@ -323,6 +482,9 @@ impl ApplicationHandle {
     }
 
     pub(crate) fn idle(&mut self) {
+        while let Some(action) = { PROXY_ACTIONS.lock().pop_front() } {
+            action();
+        }
         while let Some(trigger) = { EXT_EVENT_HANDLER.queue.lock().pop_front() } {
             trigger.notify();
         }
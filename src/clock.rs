@@ -0,0 +1,22 @@
+//! The clock gestures, transitions, scroll animations and progress indicators measure time
+//! with.
+//!
+//! It's the system clock, unless a [`HeadlessWindow`](crate::test_support::HeadlessWindow)
+//! replaced it with its own clock, which only moves forward when the window is advanced.
+
+use std::{cell::Cell, time::Instant};
+
+thread_local! {
+    static FIXED_NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+}
+
+/// The current time of the app.
+pub(crate) fn now() -> Instant {
+    FIXED_NOW.with(|now| now.get()).unwrap_or_else(Instant::now)
+}
+
+/// Makes [`now`] return `now` until it's called again, or the system clock with `None`.
+#[cfg(any(test, feature = "test-support"))]
+pub(crate) fn set_fixed_now(now: Option<Instant>) {
+    FIXED_NOW.with(|fixed_now| fixed_now.set(now));
+}
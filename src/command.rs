@@ -0,0 +1,135 @@
+//! # Commands
+//!
+//! A [`Command`] is an action of the app with a name, a handler and a reactive enabled state,
+//! which is defined once and then bound to everything that triggers it: buttons with
+//! [`Decorators::command`], menu items with [`MenuItem::command`] and a keyboard shortcut with
+//! [`Command::shortcut`].
+//!
+//! Buttons and menu items bound to a command are disabled while the command is, and menu items
+//! show its shortcut.
+//! ```rust
+//! use floem::command::Command;
+//! use floem::keyboard::{KeyCode, KeyCombination, ModifiersState};
+//! use floem::menu::{Menu, MenuItem};
+//! use floem::reactive::create_rw_signal;
+//! use floem::views::{button, Decorators};
+//!
+//! let dirty = create_rw_signal(false);
+//! let save = Command::new("save", move || dirty.get(), move || dirty.set(false))
+//!     .shortcut(KeyCombination::new(ModifiersState::CONTROL, KeyCode::KeyS));
+//!
+//! button(|| "Save").command(&save);
+//! Menu::new("File").entry(MenuItem::new("Save").command(&save));
+//! ```
+//!
+//! [`Decorators::command`]: crate::views::Decorators::command
+//! [`MenuItem::command`]: crate::menu::MenuItem::command
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::{Rc, Weak},
+};
+
+use floem_reactive::untrack;
+
+use crate::keyboard::{KeyCombination, KeyEvent};
+
+thread_local! {
+    /// The commands with a shortcut, which are removed once the command is dropped.
+    static SHORTCUTS: RefCell<HashMap<KeyCombination, Weak<CommandInner>>> = Default::default();
+}
+
+struct CommandInner {
+    name: String,
+    enabled: Box<dyn Fn() -> bool>,
+    handler: Box<dyn Fn()>,
+    shortcut: Cell<Option<KeyCombination>>,
+}
+
+/// A named action with an enabled state. Cloning a command gives another handle to the same
+/// command.
+#[derive(Clone)]
+pub struct Command {
+    inner: Rc<CommandInner>,
+}
+
+impl Command {
+    /// A command called `name`, which runs `handler` when it's executed while `enabled`
+    /// returns `true`. `enabled` is called in a reactive context, so the views bound to the
+    /// command update when the signals it reads change.
+    pub fn new(
+        name: impl Into<String>,
+        enabled: impl Fn() -> bool + 'static,
+        handler: impl Fn() + 'static,
+    ) -> Self {
+        Self {
+            inner: Rc::new(CommandInner {
+                name: name.into(),
+                enabled: Box::new(enabled),
+                handler: Box::new(handler),
+                shortcut: Cell::new(None),
+            }),
+        }
+    }
+
+    /// Executes the command when `shortcut` is pressed in any window, unless the focused view
+    /// handled the key. The shortcut is registered until every handle to the command is
+    /// dropped, and replaces the command registered with the same shortcut before.
+    pub fn shortcut(self, shortcut: KeyCombination) -> Self {
+        self.inner.shortcut.set(Some(shortcut));
+        SHORTCUTS.with(|shortcuts| {
+            shortcuts
+                .borrow_mut()
+                .insert(shortcut, Rc::downgrade(&self.inner))
+        });
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn get_shortcut(&self) -> Option<KeyCombination> {
+        self.inner.shortcut.get()
+    }
+
+    /// Whether the command can be executed. This tracks the signals read by the enabled
+    /// function.
+    pub fn is_enabled(&self) -> bool {
+        (self.inner.enabled)()
+    }
+
+    /// Runs the handler if the command is enabled, and returns whether it ran.
+    pub fn execute(&self) -> bool {
+        if !untrack(|| self.is_enabled()) {
+            return false;
+        }
+        (self.inner.handler)();
+        true
+    }
+}
+
+impl fmt::Debug for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Command")
+            .field("name", &self.inner.name)
+            .field("shortcut", &self.inner.shortcut.get())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Executes the command with the shortcut pressed for `event`, and returns whether there was
+/// an enabled one.
+pub(crate) fn execute_shortcut(event: &KeyEvent) -> bool {
+    let Some(shortcut) = KeyCombination::from_key_event(event) else {
+        return false;
+    };
+    let command = SHORTCUTS.with(|shortcuts| {
+        let mut shortcuts = shortcuts.borrow_mut();
+        shortcuts.retain(|_, command| command.strong_count() > 0);
+        shortcuts.get(&shortcut).and_then(Weak::upgrade)
+    });
+    command.is_some_and(|inner| Command { inner }.execute())
+}
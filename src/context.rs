@@ -17,33 +17,58 @@ use crate::{
     action::{exec_after, show_context_menu},
     animate::AnimId,
     event::{Event, EventListener},
+    gesture::GesturePhase,
     id::Id,
     inspector::CaptureState,
+    keyboard::KeyCombination,
     menu::Menu,
     responsive::{GridBreakpoints, ScreenSizeBp},
     style::{
-        BuiltinStyle, CursorStyle, DisplayProp, Style, StyleClassRef, StyleProp, StyleSelector,
-        ZIndex,
+        BuiltinStyle, CursorStyle, DirectionProp, DisplayProp, FlexDirection, FlexDirectionProp,
+        InRtlColumn, PixelSnap, Style, StyleClassRef, StyleProp, StyleSelector, ZIndex,
     },
     unit::PxPct,
-    view::{paint_bg, paint_border, paint_outline, View, ViewData},
+    view::{
+        paint_bg, paint_border, paint_bounds, paint_inset_box_shadow, paint_outline, View, ViewData,
+    },
     view_data::ChangeFlags,
 };
 
 pub use crate::view_data::ViewState;
 
 /// Control whether an event will continue propagating or whether it should stop.
+///
+/// Events are first captured and then bubble. A pointer event is sent to the views under the
+/// pointer, and a key event to the focused view. In the capture phase, the capture listeners
+/// (see [`Id::update_capture_listener`]) get the event from the root down to the view it's
+/// for. Then in the bubble phase, it's sent back up from that view to the root: for each view,
+/// its own handling in [`View::event`](crate::view::View::event) comes first, which is where a
+/// container sends the event to its children, then the built-in behavior like focusing on
+/// click, and then its event listeners. Stopping the event in either phase skips everything
+/// after it.
 pub enum EventPropagation {
     /// Stop event propagation and mark the event as processed
     Stop,
     /// Let event propagation continue
     Continue,
+    /// Let event propagation continue, so the listeners of the ancestors still get the event,
+    /// but skip the default behavior of the ancestors and the window, like focusing on click,
+    /// scrolling with the wheel, or moving focus with Tab. Views can check this with
+    /// [`EventCx::is_default_prevented`].
+    PreventDefault,
 }
 impl EventPropagation {
     pub fn is_continue(&self) -> bool {
         matches!(self, EventPropagation::Continue)
     }
 
+    pub fn is_default_prevented(&self) -> bool {
+        matches!(
+            self,
+            EventPropagation::Stop | EventPropagation::PreventDefault
+        )
+    }
+
     pub fn is_stop(&self) -> bool {
         matches!(self, EventPropagation::Stop)
     }
@@ -62,12 +87,69 @@ pub(crate) struct ResizeListener {
     pub(crate) callback: Box<ResizeCallback>,
 }
 
+/// The listener when the size of the content of the view changes, see
+/// [`Id::update_size_listener`].
+pub(crate) struct SizeListener {
+    pub(crate) size: Size,
+    pub(crate) callback: Box<dyn Fn(Size)>,
+}
+
 /// The listener when the view is got moved to a different position in the window
 pub(crate) struct MoveListener {
     pub(crate) window_origin: Point,
     pub(crate) callback: Box<dyn Fn(Point)>,
 }
 
+/// A request to focus the view once it's mounted, see [`Id::autofocus`].
+pub(crate) struct Autofocus {
+    pub(crate) force: bool,
+    /// The focused view when focus was requested, which has to still have focus, unless
+    /// it's forced.
+    pub(crate) focus: Option<Id>,
+}
+
+/// The listener when the view is scrolled into or out of view.
+pub(crate) struct VisibilityListener {
+    /// The fraction of the view which has to be visible for it to count as visible.
+    pub(crate) threshold: f64,
+    pub(crate) visible: bool,
+    pub(crate) callback: Box<dyn Fn(bool)>,
+}
+
+impl VisibilityListener {
+    /// Calls the callback if the view became visible or invisible.
+    fn update(&mut self, visible_fraction: f64) {
+        let visible = visible_fraction > 0.0 && visible_fraction + f64::EPSILON >= self.threshold;
+        if visible != self.visible {
+            self.visible = visible;
+            (self.callback)(visible);
+        }
+    }
+}
+
+/// The listener when the visible fraction of the view crosses one of its thresholds.
+pub(crate) struct IntersectionListener {
+    /// The sorted thresholds.
+    pub(crate) thresholds: Vec<f64>,
+    /// How many of the thresholds the visible fraction reached, which is `None` until the
+    /// view is laid out.
+    pub(crate) reached: Option<usize>,
+    pub(crate) callback: Box<dyn Fn(f64)>,
+}
+
+impl IntersectionListener {
+    /// Calls the callback if the visible fraction crossed a threshold, or on the first layout.
+    fn update(&mut self, visible_fraction: f64) {
+        let reached = self
+            .thresholds
+            .partition_point(|threshold| visible_fraction + f64::EPSILON >= *threshold);
+        if self.reached != Some(reached) {
+            self.reached = Some(reached);
+            (self.callback)(visible_fraction);
+        }
+    }
+}
+
 pub struct DragState {
     pub(crate) id: Id,
     pub(crate) offset: Vec2,
@@ -97,7 +179,16 @@ pub struct AppState {
     pub(crate) scheduled_updates: Vec<FrameUpdate>,
     pub(crate) request_compute_layout: bool,
     pub(crate) request_paint: bool,
+    /// Whether only the parts of the window which changed are repainted, see
+    /// [`WindowConfig::partial_present`](crate::window::WindowConfig::partial_present).
+    pub(crate) partial_present: bool,
+    /// The views which changed since the last frame.
+    pub(crate) dirty_views: Vec<Id>,
+    /// The parts of the window which changed since the last frame, other than the views in
+    /// `dirty_views`, like where views were before they moved or were removed.
+    pub(crate) damage: Vec<Rect>,
     pub(crate) disabled: HashSet<Id>,
+    pub(crate) checked: HashSet<Id>,
     pub(crate) keyboard_navigable: HashSet<Id>,
     pub(crate) draggable: HashSet<Id>,
     pub(crate) dragging: Option<DragState>,
@@ -107,12 +198,22 @@ pub struct AppState {
     pub(crate) grid_bps: GridBreakpoints,
     pub(crate) clicking: HashSet<Id>,
     pub(crate) hovered: HashSet<Id>,
+    /// The order of the last view brought to the front with [`Id::bring_to_front`].
+    pub(crate) stack_order: u64,
+    /// Whether a listener returned [`EventPropagation::PreventDefault`] for the current event.
+    pub(crate) default_prevented: bool,
+    /// The views which handled the beginning of the touches which are still going, by touch id.
+    pub(crate) touch_targets: HashMap<u64, Id>,
+    /// The focused view and its ancestors, as last reported to the focus-within listeners.
+    pub(crate) focus_within: Vec<Id>,
     /// This keeps track of all views that have an animation,
     /// regardless of the status of the animation
     pub(crate) cursor: Option<CursorStyle>,
     pub(crate) last_cursor: CursorIcon,
     pub(crate) keyboard_navigation: bool,
     pub(crate) window_menu: HashMap<usize, Box<dyn Fn()>>,
+    /// The ids of the window menu items with a shortcut.
+    pub(crate) window_menu_shortcuts: HashMap<KeyCombination, usize>,
     pub(crate) context_menu: HashMap<usize, Box<dyn Fn()>>,
 
     /// This is set if we're currently capturing the window for the inspector.
@@ -141,8 +242,12 @@ impl AppState {
             view_states: HashMap::new(),
             scheduled_updates: Vec::new(),
             request_paint: false,
+            partial_present: false,
+            dirty_views: Vec::new(),
+            damage: Vec::new(),
             request_compute_layout: false,
             disabled: HashSet::new(),
+            checked: HashSet::new(),
             keyboard_navigable: HashSet::new(),
             draggable: HashSet::new(),
             dragging: None,
@@ -150,11 +255,16 @@ impl AppState {
             dragging_over: HashSet::new(),
             clicking: HashSet::new(),
             hovered: HashSet::new(),
+            stack_order: 0,
+            default_prevented: false,
+            touch_targets: HashMap::new(),
+            focus_within: Vec::new(),
             cursor: None,
             last_cursor: CursorIcon::Default,
             keyboard_navigation: false,
             grid_bps: GridBreakpoints::default(),
             window_menu: HashMap::new(),
+            window_menu_shortcuts: HashMap::new(),
             context_menu: HashMap::new(),
             capture: None,
         }
@@ -178,10 +288,20 @@ impl AppState {
             false
         });
         let id = view.id();
+        let paint_rect = self.view_state(id).paint_rect;
+        self.add_damage(paint_rect);
         let view_state = self.view_state(id);
+        if view_state.mounted {
+            if let Some(action) = view_state.unmount_listener.as_ref() {
+                action();
+            }
+        }
         if let Some(action) = view_state.cleanup_listener.as_ref() {
             action();
         }
+        id.dispose_scope();
+        id.remove_debug_info();
+        id.remove_visible_fraction();
         let node = view_state.node;
         if let Ok(children) = self.taffy.children(node) {
             for child in children {
@@ -192,6 +312,7 @@ impl AppState {
         id.remove_id_path();
         self.view_states.remove(&id);
         self.disabled.remove(&id);
+        self.checked.remove(&id);
         self.keyboard_navigable.remove(&id);
         self.draggable.remove(&id);
         self.dragging_over.remove(&id);
@@ -200,6 +321,7 @@ impl AppState {
         self.clicking.remove(&id);
         if self.focus == Some(id) {
             self.focus = None;
+            self.update_focus_within();
         }
         if self.active == Some(id) {
             self.active = None;
@@ -224,7 +346,7 @@ impl AppState {
 
     pub(crate) fn can_focus(&self, id: Id) -> bool {
         self.keyboard_navigable.contains(&id)
-            && !self.is_disabled(&id)
+            && !self.is_disabled_recursive(id)
             && !self.is_hidden_recursive(id)
     }
 
@@ -236,10 +358,28 @@ impl AppState {
         self.disabled.contains(id)
     }
 
+    /// Whether the view or one of its ancestors is disabled, which disables the view too.
+    pub fn is_disabled_recursive(&self, id: Id) -> bool {
+        match id.id_path() {
+            Some(id_path) => id_path.dispatch().iter().any(|id| self.is_disabled(id)),
+            None => self.is_disabled(&id),
+        }
+    }
+
+    /// Whether the view is checked, see [`Id::update_checked`].
+    pub fn is_checked(&self, id: &Id) -> bool {
+        self.checked.contains(id)
+    }
+
     pub fn is_focused(&self, id: &Id) -> bool {
         self.focus.map(|f| &f == id).unwrap_or(false)
     }
 
+    /// Whether the view or one of its descendants is focused.
+    pub fn is_focus_within(&self, id: &Id) -> bool {
+        self.focus_within.contains(id)
+    }
+
     pub fn is_active(&self, id: &Id) -> bool {
         self.active.map(|a| &a == id).unwrap_or(false)
     }
@@ -272,12 +412,14 @@ impl AppState {
         context: &Style,
     ) -> bool {
         let screen_size_bp = self.screen_size_bp;
+        let window_width = self.root_size.width;
         let view_state = self.view_state(id);
         view_state.compute_style(
             view_data,
             view_style,
             view_interact_state,
             screen_size_bp,
+            window_width,
             view_class,
             classes,
             context,
@@ -289,6 +431,19 @@ impl AppState {
         &view_state.combined_style
     }
 
+    /// The z-index of the view, and when it was last brought to the front.
+    pub(crate) fn stacking_order(&self, id: Id) -> (i32, u64) {
+        self.view_states
+            .get(&id)
+            .map(|view_state| {
+                (
+                    view_state.combined_style.get(ZIndex).unwrap_or(0),
+                    view_state.stack_order,
+                )
+            })
+            .unwrap_or_default()
+    }
+
     pub fn get_builtin_style(&mut self, id: Id) -> BuiltinStyle<'_> {
         self.get_computed_style(id).builtin()
     }
@@ -349,10 +504,12 @@ impl AppState {
     }
 
     pub fn request_style(&mut self, id: Id) {
+        self.mark_dirty(id);
         self.request_changes(id, ChangeFlags::STYLE)
     }
 
     pub fn request_layout(&mut self, id: Id) {
+        self.mark_dirty(id);
         self.request_changes(id, ChangeFlags::LAYOUT)
     }
 
@@ -361,9 +518,43 @@ impl AppState {
         self.request_compute_layout = true;
     }
 
-    // `Id` is unused currently, but could be used to calculate damage regions.
-    pub fn request_paint(&mut self, _id: Id) {
+    pub fn request_paint(&mut self, id: Id) {
         self.request_paint = true;
+        self.mark_dirty(id);
+    }
+
+    /// Marks `id` to be repainted in the next frame, when only the parts of the window which
+    /// changed are repainted.
+    fn mark_dirty(&mut self, id: Id) {
+        if self.partial_present {
+            self.dirty_views.push(id);
+        }
+    }
+
+    /// Adds `rect` to the parts of the window repainted in the next frame, when only the parts
+    /// which changed are repainted.
+    pub(crate) fn add_damage(&mut self, rect: Rect) {
+        if self.partial_present && rect.area() > 0.0 {
+            self.damage.push(rect);
+        }
+    }
+
+    /// Returns the part of the window to repaint in this frame, from the views and parts which
+    /// changed since the last frame, or `None` if the whole window has to be repainted.
+    pub(crate) fn take_damage(&mut self) -> Option<Rect> {
+        let dirty_views = std::mem::take(&mut self.dirty_views);
+        let damage = std::mem::take(&mut self.damage);
+        // Captures paint the whole window, and dragged views are painted away from their box
+        if !self.partial_present || self.capture.is_some() || self.dragging.is_some() {
+            return None;
+        }
+        dirty_views
+            .into_iter()
+            .filter_map(|id| self.view_states.get(&id))
+            .map(|view_state| view_state.paint_rect)
+            .chain(damage)
+            .filter(|rect| rect.area() > 0.0)
+            .reduce(|damage, rect| damage.union(rect))
     }
 
     /// `viewport` is relative to the `id` view.
@@ -402,6 +593,42 @@ impl AppState {
         })
     }
 
+    /// Returns the size the content of the view takes, which is the size it would have to be
+    /// to fit its children without overflowing, including its padding and borders. A view
+    /// without children takes its own size.
+    pub(crate) fn get_content_size(&mut self, id: Id) -> Size {
+        let Some(layout) = self.get_layout(id) else {
+            return Size::ZERO;
+        };
+        let size = Size::new(layout.size.width as f64, layout.size.height as f64);
+        let node = self.view_state(id).node;
+        let children = self.taffy.children(node).unwrap_or_default();
+        let Some(extent) = children
+            .into_iter()
+            .filter_map(|child| self.taffy.layout(child).ok())
+            .map(|child| {
+                Rect::from_origin_size(
+                    (child.location.x as f64, child.location.y as f64),
+                    (child.size.width as f64, child.size.height as f64),
+                )
+            })
+            .reduce(|extent, child| extent.union(child))
+        else {
+            return size;
+        };
+        let props = &self.view_state(id).layout_props;
+        let pixels = |px_pct, abs| match px_pct {
+            PxPct::Px(v) => v,
+            PxPct::Pct(pct) => pct * abs,
+        };
+        Size::new(
+            extent.x1.max(0.0) + pixels(props.padding_right(), size.width) + props.border_right().0,
+            extent.y1.max(0.0)
+                + pixels(props.padding_bottom(), size.height)
+                + props.border_bottom().0,
+        )
+    }
+
     pub(crate) fn get_layout_rect(&mut self, id: Id) -> Rect {
         self.view_state(id).layout_rect
     }
@@ -438,6 +665,20 @@ impl AppState {
         self.focus = None;
     }
 
+    /// Records whether the last input came from the keyboard, which decides whether the focused
+    /// view matches the `focus_visible` selector.
+    pub(crate) fn set_keyboard_navigation(&mut self, keyboard_navigation: bool) {
+        if self.keyboard_navigation == keyboard_navigation {
+            return;
+        }
+        self.keyboard_navigation = keyboard_navigation;
+        if let Some(id) = self.focus {
+            if self.has_style_for_sel(id, StyleSelector::FocusVisible) {
+                self.request_style_recursive(id);
+            }
+        }
+    }
+
     pub(crate) fn update_focus(&mut self, id: Id, keyboard_navigation: bool) {
         if self.focus.is_some() {
             return;
@@ -476,6 +717,29 @@ impl AppState {
             .0
     }
 
+    pub(crate) fn update_window_menu(&mut self, menu: &mut Menu) {
+        if let Some(action) = menu.item.action.take() {
+            self.window_menu.insert(menu.item.id as usize, action);
+        }
+        for child in menu.children.iter_mut() {
+            match child {
+                crate::menu::MenuEntry::Separator => {}
+                crate::menu::MenuEntry::Item(item) => {
+                    if let Some(action) = item.action.take() {
+                        self.window_menu.insert(item.id as usize, action);
+                    }
+                    if let Some(shortcut) = item.shortcut.filter(|_| item.enabled) {
+                        self.window_menu_shortcuts
+                            .insert(shortcut, item.id as usize);
+                    }
+                }
+                crate::menu::MenuEntry::SubMenu(m) => {
+                    self.update_window_menu(m);
+                }
+            }
+        }
+    }
+
     pub(crate) fn update_context_menu(&mut self, menu: &mut Menu) {
         if let Some(action) = menu.item.action.take() {
             self.context_menu.insert(menu.item.id as usize, action);
@@ -516,6 +780,9 @@ impl AppState {
             if let Some(action) = self.get_event_listener(id, &EventListener::FocusGained) {
                 (*action)(&Event::FocusGained);
             }
+            if let Some(action) = self.focus_listener(id) {
+                action(true);
+            }
         }
 
         if let Some(old_id) = old {
@@ -528,6 +795,58 @@ impl AppState {
             if let Some(action) = self.get_event_listener(old_id, &EventListener::FocusLost) {
                 (*action)(&Event::FocusLost);
             }
+            if let Some(action) = self.focus_listener(old_id) {
+                action(false);
+            }
+        }
+
+        self.update_focus_within();
+    }
+
+    fn focus_listener(&self, id: Id) -> Option<&dyn Fn(bool)> {
+        self.view_states
+            .get(&id)
+            .and_then(|s| s.focus_listener.as_deref())
+    }
+
+    /// Calls the focus-within listeners of the views which focus moved into or out of, which
+    /// are the views whose subtree has or had focus, and restyles them. Moving focus within a
+    /// subtree doesn't affect its root.
+    fn update_focus_within(&mut self) {
+        let focus_within = self
+            .focus
+            .and_then(|id| id.id_path())
+            .map(|path| path.0)
+            .unwrap_or_default();
+        let old = std::mem::replace(&mut self.focus_within, focus_within);
+        let left: Vec<Id> = old
+            .iter()
+            .filter(|id| !self.focus_within.contains(id))
+            .copied()
+            .collect();
+        let entered: Vec<Id> = self
+            .focus_within
+            .iter()
+            .filter(|id| !old.contains(id))
+            .copied()
+            .collect();
+        for (ids, focused) in [(left, false), (entered, true)] {
+            for id in ids {
+                // Removed views have no state
+                let Some(view_state) = self.view_states.get(&id) else {
+                    continue;
+                };
+                if let Some(action) = view_state.focus_within_listener.as_deref() {
+                    action(focused);
+                }
+                // To apply or remove the styles of the FocusWithin selector
+                if view_state
+                    .has_style_selectors
+                    .has(StyleSelector::FocusWithin)
+                {
+                    self.request_style_recursive(id);
+                }
+            }
         }
     }
 }
@@ -538,6 +857,21 @@ pub struct EventCx<'a> {
 }
 
 impl<'a> EventCx<'a> {
+    /// Whether a listener of a descendant returned [`EventPropagation::PreventDefault`] for
+    /// the current event, so views should skip their default behavior for it.
+    pub fn is_default_prevented(&self) -> bool {
+        self.app_state.default_prevented
+    }
+
+    /// Returns whether `propagation` stops the event, and records whether it prevents the
+    /// default behavior.
+    pub(crate) fn handled(&mut self, propagation: EventPropagation) -> bool {
+        if let EventPropagation::PreventDefault = propagation {
+            self.app_state.default_prevented = true;
+        }
+        propagation.is_processed()
+    }
+
     /// request that this node be styled, laid out and painted again
     /// This will recursively request this for all parents.
     pub fn request_all(&mut self, id: Id) {
@@ -632,6 +966,12 @@ impl<'a> EventCx<'a> {
         // of the view and the viewport of the view if it's in a scroll.
         let event = self.offset_event(id, event);
 
+        // the capture listeners get the event on the way down, before the children
+        if id_path.map_or(true, |id_path| !id_path.is_empty()) && self.capture_event(id, &event) {
+            self.capture_touch(id, &event);
+            return EventPropagation::Stop;
+        }
+
         // if there's id_path, it's an event only for a view.
         if let Some(id_path) = id_path {
             if id_path.is_empty() {
@@ -661,19 +1001,33 @@ impl<'a> EventCx<'a> {
             }
         }
 
+        // wheel listeners get wheel events before the children, which may scroll
+        if let Event::PointerWheel(wheel_event) = &event {
+            if let Some(action) = self
+                .app_state
+                .view_states
+                .get(&id)
+                .and_then(|view_state| view_state.wheel_listener.as_ref())
+            {
+                if action(wheel_event) {
+                    return EventPropagation::Stop;
+                }
+            }
+        }
+
         // if the event was dispatched to an id_path, the event is supposed to be only
         // handled by this view only, so we pass an empty id_path
         // and the event propagation would be stopped at this view
-        if view
-            .event(
-                self,
-                if id_path.is_some() { Some(&[]) } else { None },
-                event.clone(),
-            )
-            .is_processed()
-        {
+        let propagation = view.event(
+            self,
+            if id_path.is_some() { Some(&[]) } else { None },
+            event.clone(),
+        );
+        if self.handled(propagation) {
+            self.capture_touch(id, &event);
             return EventPropagation::Stop;
         }
+        let default_prevented = self.app_state.default_prevented;
 
         match &event {
             Event::PointerDown(event) => {
@@ -683,7 +1037,7 @@ impl<'a> EventCx<'a> {
                     let now_focused = rect.contains(event.pos);
 
                     if now_focused {
-                        if self.app_state.keyboard_navigable.contains(&id) {
+                        if self.app_state.keyboard_navigable.contains(&id) && !default_prevented {
                             // if the view can be focused, we update the focus
                             self.app_state.update_focus(id, false);
                         }
@@ -703,11 +1057,14 @@ impl<'a> EventCx<'a> {
                             Point::new(layout.x0, layout.y1)
                         };
                         if let Some(menu) = &self.app_state.view_state(id).popout_menu {
-                            show_context_menu(menu(), Some(bottom_left));
-                            return EventPropagation::Stop;
+                            if !default_prevented {
+                                show_context_menu(menu(), Some(bottom_left));
+                                return EventPropagation::Stop;
+                            }
                         }
                         if self.app_state.draggable.contains(&id)
                             && self.app_state.drag_start.is_none()
+                            && !default_prevented
                         {
                             self.app_state.drag_start = Some((id, event.pos));
                         }
@@ -717,7 +1074,7 @@ impl<'a> EventCx<'a> {
                     let now_focused = rect.contains(event.pos);
 
                     if now_focused {
-                        if self.app_state.keyboard_navigable.contains(&id) {
+                        if self.app_state.keyboard_navigable.contains(&id) && !default_prevented {
                             // if the view can be focused, we update the focus
                             self.app_state.update_focus(id, false);
                         }
@@ -881,8 +1238,10 @@ impl<'a> EventCx<'a> {
                         )
                     };
                     if let Some(menu) = &self.app_state.view_state(id).context_menu {
-                        show_context_menu(menu(), Some(viewport_event_position));
-                        return EventPropagation::Stop;
+                        if !default_prevented {
+                            show_context_menu(menu(), Some(viewport_event_position));
+                            return EventPropagation::Stop;
+                        }
                     }
                 }
             }
@@ -900,11 +1259,24 @@ impl<'a> EventCx<'a> {
                     }
                 }
             }
+            Event::Touch(touch_event) => {
+                if let Some(action) = self
+                    .app_state
+                    .view_states
+                    .get(&id)
+                    .and_then(|view_state| view_state.touch_listener.as_ref())
+                {
+                    action(touch_event);
+                    self.capture_touch(id, &event);
+                    return EventPropagation::Stop;
+                }
+            }
             _ => (),
         }
 
         for handler in &view.view_data().event_handlers {
-            if (handler)(&event).is_processed() {
+            if self.handled((handler)(&event)) {
+                self.capture_touch(id, &event);
                 return EventPropagation::Stop;
             }
         }
@@ -917,8 +1289,12 @@ impl<'a> EventCx<'a> {
                 } else {
                     true
                 };
-                if should_run && (*action)(&event).is_processed() {
-                    return EventPropagation::Stop;
+                if should_run {
+                    let propagation = (*action)(&event);
+                    if self.handled(propagation) {
+                        self.capture_touch(id, &event);
+                        return EventPropagation::Stop;
+                    }
                 }
             }
         }
@@ -926,6 +1302,46 @@ impl<'a> EventCx<'a> {
         EventPropagation::Continue
     }
 
+    /// Calls the capture listener of `id` for `event`, and returns whether it stopped the event.
+    fn capture_event(&mut self, id: Id, event: &Event) -> bool {
+        let Some(listener) = event.listener() else {
+            return false;
+        };
+        let Some(action) = self
+            .app_state
+            .view_states
+            .get(&id)
+            .and_then(|view_state| view_state.capture_listeners.get(&listener))
+        else {
+            return false;
+        };
+        if let Some(pos) = event.point() {
+            if !self
+                .get_size(id)
+                .unwrap_or_default()
+                .to_rect()
+                .contains(pos)
+            {
+                return false;
+            }
+        }
+        let propagation = (*action)(event);
+        self.handled(propagation)
+    }
+
+    /// Sends the rest of a touch to `id` if it handled `event`, the beginning of the touch.
+    /// The deepest view which handles it gets it, which is the first one to.
+    fn capture_touch(&mut self, id: Id, event: &Event) {
+        if let Event::Touch(touch_event) = event {
+            if touch_event.phase == GesturePhase::Began {
+                self.app_state
+                    .touch_targets
+                    .entry(touch_event.id)
+                    .or_insert(id);
+            }
+        }
+    }
+
     pub(crate) fn get_size(&self, id: Id) -> Option<Size> {
         self.app_state
             .get_layout(id)
@@ -999,9 +1415,16 @@ pub struct InteractionState {
     pub(crate) is_hovered: bool,
     pub(crate) is_selected: bool,
     pub(crate) is_disabled: bool,
+    pub(crate) is_checked: bool,
     pub(crate) is_focused: bool,
+    /// Whether the view or one of its descendants is focused.
+    pub(crate) is_focus_within: bool,
     pub(crate) is_clicking: bool,
     pub(crate) using_keyboard_navigation: bool,
+    /// The index of the view among its siblings, and the number of siblings including itself.
+    pub(crate) child_position: Option<(usize, usize)>,
+    /// Whether the view has no children.
+    pub(crate) is_empty: bool,
 }
 
 pub struct StyleCx<'a> {
@@ -1015,6 +1438,8 @@ pub struct StyleCx<'a> {
     saved_selected: Vec<bool>,
     disabled: bool,
     selected: bool,
+    /// The index of the next child of the view being styled, and the number of its children.
+    siblings: (usize, usize),
 }
 
 impl<'a> StyleCx<'a> {
@@ -1030,6 +1455,7 @@ impl<'a> StyleCx<'a> {
             saved_selected: Default::default(),
             disabled: false,
             selected: false,
+            siblings: (0, 0),
         }
     }
 
@@ -1038,12 +1464,16 @@ impl<'a> StyleCx<'a> {
         self.selected = true;
     }
 
-    fn get_interact_state(&self, id: &Id) -> InteractionState {
+    fn get_interact_state(&mut self, id: &Id) -> InteractionState {
         InteractionState {
+            child_position: self.app_state.view_state(*id).child_position,
+            is_empty: false,
             is_selected: self.selected,
             is_hovered: self.app_state.is_hovered(id),
             is_disabled: self.app_state.is_disabled(id),
+            is_checked: self.app_state.is_checked(id),
             is_focused: self.app_state.is_focused(id),
+            is_focus_within: self.app_state.is_focus_within(id),
             is_clicking: self.app_state.is_clicking(id),
             using_keyboard_navigation: self.app_state.keyboard_navigation,
         }
@@ -1053,7 +1483,18 @@ impl<'a> StyleCx<'a> {
     pub fn style_view(&mut self, view: &mut dyn View) {
         self.save();
         let id = view.id();
+        // The root view and views styled outside of their parent have no siblings
+        let (index, count) = self.siblings;
+        let position = (index < count).then_some((index, count));
+        self.siblings.0 += 1;
         let view_state = self.app_state_mut().view_state(id);
+        if view_state.child_position != position {
+            view_state.child_position = position;
+            // Views which moved among their siblings are restyled for the structural selectors
+            if view_state.has_style_selectors.has_structural() {
+                view_state.requested_changes.insert(ChangeFlags::STYLE);
+            }
+        }
         if !view_state.requested_changes.contains(ChangeFlags::STYLE) {
             return;
         }
@@ -1081,7 +1522,15 @@ impl<'a> StyleCx<'a> {
             });
         }
 
+        // Views which add or remove children request a restyle, which updates `empty` styles
+        let mut children = 0;
+        view.for_each_child(&mut |_| {
+            children += 1;
+            false
+        });
+
         let mut view_interact_state = self.get_interact_state(&id);
+        view_interact_state.is_empty = children == 0;
         view_interact_state.is_disabled |= self.disabled;
         self.disabled = view_interact_state.is_disabled;
         let mut new_frame = self.app_state.compute_style(
@@ -1094,6 +1543,35 @@ impl<'a> StyleCx<'a> {
             &self.current,
         );
 
+        // The parent is styled before its children, so its direction is already resolved
+        let in_rtl_column = id.parent().map_or(false, |parent| {
+            let parent_style = &self.app_state.view_state(parent).combined_style;
+            parent_style.get(DirectionProp).is_rtl()
+                && matches!(
+                    parent_style.get(FlexDirectionProp),
+                    FlexDirection::Column | FlexDirection::ColumnReverse
+                )
+        });
+
+        // The layout of a view depends on its direction, which is usually inherited, so it's
+        // resolved into the style of the view.
+        let view_state = self.app_state.view_state(id);
+        let direction = view_state
+            .combined_style
+            .get_prop::<DirectionProp>()
+            .unwrap_or_else(|| self.current.get(DirectionProp));
+        view_state.combined_style =
+            std::mem::take(&mut view_state.combined_style).direction(direction);
+        view_state.combined_style.resolve_logical_spacing(direction);
+        if in_rtl_column {
+            view_state.combined_style =
+                std::mem::take(&mut view_state.combined_style).set(InRtlColumn, true);
+        }
+        if let Some(display) = view_state.display {
+            view_state.combined_style =
+                std::mem::take(&mut view_state.combined_style).display(display);
+        }
+
         let style = self.app_state_mut().get_computed_style(id).clone();
         self.direct = style;
         Style::apply_only_inherited(&mut self.current, &self.direct);
@@ -1131,7 +1609,9 @@ impl<'a> StyleCx<'a> {
             self.app_state.schedule_style(id);
         }
 
+        let siblings = std::mem::replace(&mut self.siblings, (0, children));
         view.style(self);
+        self.siblings = siblings;
 
         self.restore();
     }
@@ -1240,6 +1720,13 @@ impl<'a> ComputeLayoutCx<'a> {
             .and_then(|s| s.resize_listener.as_mut())
     }
 
+    pub(crate) fn get_size_listener(&mut self, id: Id) -> Option<&mut SizeListener> {
+        self.app_state
+            .view_states
+            .get_mut(&id)
+            .and_then(|s| s.size_listener.as_mut())
+    }
+
     pub(crate) fn get_move_listener(&mut self, id: Id) -> Option<&mut MoveListener> {
         self.app_state
             .view_states
@@ -1247,18 +1734,59 @@ impl<'a> ComputeLayoutCx<'a> {
             .and_then(|s| s.move_listener.as_mut())
     }
 
+    /// Marks `view` and its descendants as unmounted after it was hidden, calling their unmount
+    /// listeners. The descendants of views which aren't mounted aren't mounted either.
+    fn unmount(&mut self, view: &dyn View) {
+        let Some(view_state) = self.app_state.view_states.get_mut(&view.id()) else {
+            return;
+        };
+        if !view_state.mounted {
+            return;
+        }
+        view_state.mounted = false;
+        if let Some(action) = view_state.unmount_listener.as_ref() {
+            action();
+        }
+        view.for_each_child(&mut |child| {
+            self.unmount(child);
+            false
+        });
+    }
+
+    /// Records how much of the view is visible, and calls its visibility and intersection
+    /// listeners.
+    fn update_visibility(&mut self, id: Id, visible_fraction: f64) {
+        id.record_visible_fraction(visible_fraction);
+        let Some(view_state) = self.app_state.view_states.get_mut(&id) else {
+            return;
+        };
+        if let Some(listener) = view_state.visibility_listener.as_mut() {
+            listener.update(visible_fraction);
+        }
+        if let Some(listener) = view_state.intersection_listener.as_mut() {
+            listener.update(visible_fraction);
+        }
+    }
+
     /// Internal method used by Floem. This method derives its calculations based on the [Taffy Node](taffy::prelude::Node) returned by the `View::layout` method.
     ///
     /// It's responsible for:
     /// - calculating and setting the view's origin (local coordinates and window coordinates)
     /// - calculating and setting the view's viewport
     /// - invoking any attached context::ResizeListeners
+    /// - invoking any attached context::SizeListeners, after the layout of the children
     ///
     /// Returns the bounding rect that encompasses this view and its children
     pub fn compute_view_layout(&mut self, view: &mut dyn View) -> Option<Rect> {
         let id = view.id();
         if self.app_state().is_hidden(id) {
-            self.app_state_mut().view_state(id).layout_rect = Rect::ZERO;
+            let view_state = self.app_state_mut().view_state(id);
+            view_state.layout_rect = Rect::ZERO;
+            let paint_rect = std::mem::take(&mut view_state.paint_rect);
+            self.app_state_mut().add_damage(paint_rect);
+            id.record_debug_info(view, Rect::ZERO);
+            self.update_visibility(id, 0.0);
+            self.unmount(view);
             return None;
         }
 
@@ -1284,6 +1812,21 @@ impl<'a> ComputeLayoutCx<'a> {
             self.viewport = self.viewport.intersect(this_viewport);
         }
 
+        // An empty view is visible when its origin is, so it can mark a position in a list
+        let area = size.width * size.height;
+        let visible_fraction = if area > 0.0 {
+            (parent_viewport.intersect(size.to_rect()).area() / area).min(1.0)
+        } else if parent_viewport.x0 <= 0.0
+            && parent_viewport.x1 >= 0.0
+            && parent_viewport.y0 <= 0.0
+            && parent_viewport.y1 >= 0.0
+        {
+            1.0
+        } else {
+            0.0
+        };
+        self.update_visibility(id, visible_fraction);
+
         let window_origin = origin + self.window_origin.to_vec2() - this_viewport_origin;
         self.window_origin = window_origin;
 
@@ -1302,8 +1845,26 @@ impl<'a> ComputeLayoutCx<'a> {
             }
         }
 
+        if self
+            .app_state_mut()
+            .view_state(id)
+            .container_queries
+            .resize(size.width)
+        {
+            self.app_state_mut().request_style(id);
+        }
+
         let child_layout_rect = view.compute_layout(self);
 
+        if self.get_size_listener(id).is_some() {
+            let content_size = self.app_state_mut().get_content_size(id);
+            let listener = self.get_size_listener(id).unwrap();
+            if content_size != listener.size {
+                listener.size = content_size;
+                (*listener.callback)(content_size);
+            }
+        }
+
         let layout_rect = size.to_rect().with_origin(self.window_origin);
         let layout_rect = if let Some(child_layout_rect) = child_layout_rect {
             layout_rect.union(child_layout_rect)
@@ -1311,6 +1872,44 @@ impl<'a> ComputeLayoutCx<'a> {
             layout_rect
         };
         self.app_state_mut().view_state(id).layout_rect = layout_rect;
+        id.record_debug_info(view, layout_rect);
+
+        // The view and its descendants can paint outside of their box, with shadows and outlines
+        let view_state = self.app_state_mut().view_state(id);
+        let bounds = paint_bounds(
+            &view_state.combined_style,
+            &view_state.view_style_props,
+            size,
+        );
+        let mut paint_rect = layout_rect.union(bounds + self.window_origin.to_vec2());
+        view.for_each_child(&mut |child| {
+            if let Some(child_state) = self.app_state.view_states.get(&child.id()) {
+                if child_state.paint_rect.area() > 0.0 {
+                    paint_rect = paint_rect.union(child_state.paint_rect);
+                }
+            }
+            false
+        });
+        let view_state = self.app_state_mut().view_state(id);
+        let old_paint_rect = std::mem::replace(&mut view_state.paint_rect, paint_rect);
+        if old_paint_rect != paint_rect {
+            self.app_state_mut().add_damage(old_paint_rect);
+            self.app_state_mut().add_damage(paint_rect);
+        }
+
+        let focus = self.app_state.focus;
+        let view_state = self.app_state_mut().view_state(id);
+        if !view_state.mounted {
+            view_state.mounted = true;
+            if let Some(action) = view_state.mount_listener.as_ref() {
+                action();
+            }
+            if let Some(autofocus) = view_state.autofocus.take() {
+                if autofocus.force || focus == autofocus.focus {
+                    id.request_focus();
+                }
+            }
+        }
 
         self.restore();
 
@@ -1380,6 +1979,34 @@ impl<'a> LayoutCx<'a> {
         node
     }
 
+    /// Updates the node in `node`, creating it if needed, which places the baseline of the view
+    /// `id` `baseline` pixels below the top of its content box, for rows aligning their items
+    /// with [`Style::items_baseline`]. The layout takes the baseline of a view from its first
+    /// child, so the node has to be the first of the children of the view. It's positioned
+    /// absolutely so the other children aren't moved.
+    pub(crate) fn baseline_node(&mut self, id: Id, node: &mut Option<Node>, baseline: f32) -> Node {
+        let node = *node.get_or_insert_with(|| {
+            self.app_state
+                .taffy
+                .new_leaf(taffy::style::Style::DEFAULT)
+                .unwrap()
+        });
+        // The node is placed from the padding box, inside the border
+        let padding_top = match self.app_state.get_builtin_style(id).padding_top() {
+            PxPct::Px(padding) => padding as f32,
+            // The width the percentage is of isn't known before the layout
+            PxPct::Pct(_) => 0.0,
+        };
+        let style = Style::new()
+            .absolute()
+            .inset_top(0.0)
+            .width(0.0)
+            .height(padding_top + baseline)
+            .to_taffy_style();
+        let _ = self.app_state.taffy.set_style(node, style);
+        node
+    }
+
     /// Internal method used by Floem to invoke the user-defined `View::layout` method.
     pub fn layout_view(&mut self, view: &mut dyn View) -> Node {
         view.layout(self)
@@ -1391,6 +2018,9 @@ pub struct PaintCx<'a> {
     pub(crate) paint_state: &'a mut PaintState,
     pub(crate) transform: Affine,
     pub(crate) clip: Option<RoundedRect>,
+    /// The part of the window repainted in this frame, in window coordinates, or `None` if the
+    /// whole window is.
+    pub(crate) damage: Option<Rect>,
     pub(crate) z_index: Option<i32>,
     pub(crate) saved_transforms: Vec<Affine>,
     pub(crate) saved_clips: Vec<Option<RoundedRect>>,
@@ -1432,9 +2062,20 @@ impl<'a> PaintCx<'a> {
         if self.app_state.is_hidden(id) {
             return;
         }
+        // Views outside of the repainted part of the window are left as they were
+        if let Some(damage) = self.damage {
+            let paint_rect = self.app_state.view_state(id).paint_rect;
+            if damage.intersect(paint_rect).area() == 0.0 {
+                return;
+            }
+        }
 
         self.save();
-        let size = self.transform(id);
+        let mut size = self.transform(id);
+        let snap = self.app_state.get_computed_style(id).get(PixelSnap);
+        if snap {
+            size = self.snap_to_pixels(size);
+        }
         let is_empty = self
             .clip
             .map(|rect| rect.rect().intersect(size.to_rect()).is_empty())
@@ -1448,10 +2089,11 @@ impl<'a> PaintCx<'a> {
             }
 
             paint_bg(self, &style, &view_style_props, size);
+            paint_inset_box_shadow(self, &style, &view_style_props, size);
 
             view.paint(self);
-            paint_border(self, &view_style_props, size);
-            paint_outline(self, &view_style_props, size)
+            paint_border(self, &view_style_props, size, snap);
+            paint_outline(self, &view_style_props, size, snap)
         }
 
         let mut drag_set_to_none = false;
@@ -1503,10 +2145,11 @@ impl<'a> PaintCx<'a> {
                         style
                     };
                     paint_bg(self, &style, &view_style_props, size);
+                    paint_inset_box_shadow(self, &style, &view_style_props, size);
 
                     view.paint(self);
-                    paint_border(self, &view_style_props, size);
-                    paint_outline(self, &view_style_props, size);
+                    paint_border(self, &view_style_props, size, snap);
+                    paint_outline(self, &view_style_props, size, snap);
 
                     self.restore();
                 }
@@ -1607,6 +2250,34 @@ impl<'a> PaintCx<'a> {
         }
     }
 
+    /// Moves the drawing origin to the nearest physical pixel, returning `size` with its right and
+    /// bottom edges also on physical pixels. Transforms which scale, rotate or skew are left as
+    /// they are, as the pixels don't line up with the view there.
+    pub(crate) fn snap_to_pixels(&mut self, size: Size) -> Size {
+        let [a, b, c, d, x, y] = self.transform.as_coeffs();
+        if a != 1.0 || b != 0.0 || c != 0.0 || d != 1.0 {
+            return size;
+        }
+        let scale = self.paint_state.scale;
+        let snap = |value: f64| (value * scale).round() / scale;
+        let (snapped_x, snapped_y) = (snap(x), snap(y));
+        self.offset((snapped_x - x, snapped_y - y));
+        Size::new(
+            (snap(x + size.width) - snapped_x).max(0.0),
+            (snap(y + size.height) - snapped_y).max(0.0),
+        )
+    }
+
+    /// Rounds `width` to whole physical pixels, keeping lines which aren't empty at least one
+    /// pixel wide.
+    pub(crate) fn snap_width(&self, width: f64) -> f64 {
+        if width <= 0.0 {
+            return width;
+        }
+        let scale = self.paint_state.scale;
+        (width * scale).round().max(1.0) / scale
+    }
+
     pub(crate) fn set_z_index(&mut self, z_index: i32) {
         self.z_index = Some(z_index);
         self.paint_state.renderer.set_z_index(z_index);
@@ -1620,23 +2291,41 @@ impl<'a> PaintCx<'a> {
 // TODO: should this be private?
 pub struct PaintState {
     pub(crate) renderer: crate::renderer::Renderer,
+    /// The number of physical pixels per logical pixel the renderer draws with.
+    pub(crate) scale: f64,
 }
 
 impl PaintState {
-    pub fn new<W>(window: &W, scale: f64, size: Size) -> Self
+    pub fn new<W>(
+        window: &W,
+        scale: f64,
+        size: Size,
+        backend: Option<floem_renderer::Backend>,
+    ) -> Self
     where
         W: raw_window_handle::HasRawDisplayHandle + raw_window_handle::HasRawWindowHandle,
     {
         Self {
-            renderer: crate::renderer::Renderer::new(window, scale, size),
+            renderer: crate::renderer::Renderer::new(window, scale, size, backend),
+            scale,
+        }
+    }
+
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) fn new_headless(scale: f64, size: Size) -> Self {
+        Self {
+            renderer: crate::renderer::Renderer::new_headless(scale, size),
+            scale,
         }
     }
 
     pub(crate) fn resize(&mut self, scale: f64, size: Size) {
+        self.scale = scale;
         self.renderer.resize(scale, size);
     }
 
     pub(crate) fn set_scale(&mut self, scale: f64) {
+        self.scale = scale;
         self.renderer.set_scale(scale);
     }
 }
@@ -1700,3 +2389,146 @@ impl DerefMut for PaintCx<'_> {
         &mut self.paint_state.renderer
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use floem_peniko::Color;
+    use image::Rgba;
+    use kurbo::{Point, Size, Vec2};
+
+    use super::EventPropagation;
+    use crate::{
+        event::EventListener,
+        pointer::PointerButton,
+        test_support::HeadlessWindow,
+        view::View,
+        views::{container, empty, scroll, Decorators},
+    };
+
+    #[test]
+    fn prevent_default_keeps_propagating_without_scrolling() {
+        let parent_wheels = Rc::new(Cell::new(0));
+        let (view_parent_wheels, scroll_id) = (parent_wheels.clone(), Rc::new(Cell::new(None)));
+        let view_scroll_id = scroll_id.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let content = empty()
+                .on_event(EventListener::PointerWheel, |_| {
+                    EventPropagation::PreventDefault
+                })
+                .style(|s| s.size(100.0, 400.0));
+            let view = scroll(content)
+                .on_event_cont(EventListener::PointerWheel, move |_| {
+                    view_parent_wheels.set(view_parent_wheels.get() + 1)
+                })
+                .style(|s| s.size_full());
+            view_scroll_id.set(Some(view.id()));
+            view
+        });
+        window.update();
+
+        window.wheel(Point::new(50.0, 50.0), Vec2::new(0.0, 20.0));
+        let offset = scroll_id
+            .get()
+            .unwrap()
+            .save_scroll_state()
+            .unwrap_or_default()
+            .offset;
+        assert_eq!((parent_wheels.get(), offset), (1, Vec2::ZERO));
+    }
+
+    #[test]
+    fn events_are_captured_then_bubble() {
+        type Log = Rc<RefCell<Vec<&'static str>>>;
+        fn logged<V: View>(view: V, log: &Log, capture: &'static str, bubble: &'static str) -> V {
+            let (capture_log, bubble_log) = (log.clone(), log.clone());
+            view.on_event_capture(EventListener::PointerDown, move |_| {
+                capture_log.borrow_mut().push(capture);
+                EventPropagation::Continue
+            })
+            .on_event_cont(EventListener::PointerDown, move |_| {
+                bubble_log.borrow_mut().push(bubble)
+            })
+            .style(|s| s.size_full())
+        }
+        let log: Log = Rc::new(RefCell::new(Vec::new()));
+        let view_log = log.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let inner = logged(empty(), &view_log, "capture inner", "bubble inner");
+            let middle = logged(
+                container(inner),
+                &view_log,
+                "capture middle",
+                "bubble middle",
+            );
+            logged(
+                container(middle),
+                &view_log,
+                "capture outer",
+                "bubble outer",
+            )
+        });
+        window.update();
+
+        window.pointer_down(Point::new(50.0, 50.0), PointerButton::Primary);
+        assert_eq!(
+            *log.borrow(),
+            [
+                "capture outer",
+                "capture middle",
+                "capture inner",
+                "bubble inner",
+                "bubble middle",
+                "bubble outer"
+            ]
+        );
+    }
+
+    #[test]
+    fn capture_listener_can_swallow_events() {
+        let inner_downs = Rc::new(Cell::new(0));
+        let view_inner_downs = inner_downs.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let inner = empty()
+                .on_event_cont(EventListener::PointerDown, move |_| {
+                    view_inner_downs.set(view_inner_downs.get() + 1)
+                })
+                .style(|s| s.size_full());
+            container(inner)
+                .on_event_capture(EventListener::PointerDown, |_| EventPropagation::Stop)
+                .style(|s| s.size_full())
+        });
+        window.update();
+
+        window.pointer_down(Point::new(50.0, 50.0), PointerButton::Primary);
+        assert_eq!(inner_downs.get(), 0);
+    }
+
+    #[test]
+    fn pixel_snap_fills_whole_pixels() {
+        let view = |snap: bool| {
+            move || {
+                empty().style(move |s| {
+                    s.size(20.3, 20.0)
+                        .margin_left(10.3)
+                        .background(Color::BLACK)
+                        .pixel_snap(snap)
+                })
+            }
+        };
+        let mut snapped = HeadlessWindow::new(Size::new(50.0, 20.0), view(true));
+        let image = snapped.render().unwrap();
+        // The view covers 10.3 to 30.6, which is snapped to 10 to 31
+        assert_eq!(*image.get_pixel(10, 10), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(30, 10), Rgba([0, 0, 0, 255]));
+        assert_ne!(*image.get_pixel(31, 10), Rgba([0, 0, 0, 255]));
+
+        let mut blurry = HeadlessWindow::new(Size::new(50.0, 20.0), view(false));
+        let image = blurry.render().unwrap();
+        assert_ne!(*image.get_pixel(10, 10), Rgba([0, 0, 0, 255]));
+    }
+}
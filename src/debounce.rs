@@ -0,0 +1,332 @@
+//! # Debouncing and throttling
+//!
+//! [`debounce`] and [`throttle`] wrap a callback so it runs at most once per `duration`, which
+//! is useful for work like searching as the user types. [`DebounceExt`] does the same for
+//! signals, returning a derived signal which only updates after the source settles.
+//!
+//! Both are trailing-edge: the last value passed in is delivered after the calls stop, unless
+//! the scope they're created in is disposed first, like the scope of a removed view.
+
+use std::{cell::RefCell, rc::Rc, time::Duration};
+
+use floem_reactive::{create_effect, create_signal, on_cleanup, Memo, ReadSignal, RwSignal};
+
+use crate::action::{set_timeout, TimerHandle};
+
+type Schedule = Rc<dyn Fn(Duration, Box<dyn FnOnce()>) -> TimerHandle>;
+
+fn timer_schedule() -> Schedule {
+    Rc::new(|duration, action| set_timeout(duration, action))
+}
+
+/// Returns a function which calls `action` with the last value it was given, once `duration`
+/// has elapsed without another call.
+pub fn debounce<T: 'static>(duration: Duration, action: impl Fn(T) + 'static) -> impl Fn(T) {
+    debounce_with(timer_schedule(), duration, action)
+}
+
+fn debounce_with<T: 'static>(
+    schedule: Schedule,
+    duration: Duration,
+    action: impl Fn(T) + 'static,
+) -> impl Fn(T) {
+    let action = Rc::new(action);
+    let pending: Rc<RefCell<Option<TimerHandle>>> = Rc::new(RefCell::new(None));
+    on_cleanup({
+        let pending = pending.clone();
+        move || {
+            if let Some(timer) = pending.borrow_mut().take() {
+                timer.cancel();
+            }
+        }
+    });
+    move |value| {
+        if let Some(timer) = pending.borrow_mut().take() {
+            timer.cancel();
+        }
+        let action = action.clone();
+        let timer = schedule(duration, Box::new(move || action(value)));
+        *pending.borrow_mut() = Some(timer);
+    }
+}
+
+/// Returns a function which calls `action` right away, then at most once every `duration`
+/// with the last value it was given in that time.
+pub fn throttle<T: 'static>(duration: Duration, action: impl Fn(T) + 'static) -> impl Fn(T) {
+    throttle_with(timer_schedule(), duration, action)
+}
+
+struct Throttle<T> {
+    schedule: Schedule,
+    duration: Duration,
+    action: Rc<dyn Fn(T)>,
+    /// The timer of the window started by the last call, while `waiting`.
+    timer: Option<TimerHandle>,
+    /// Whether a call was made less than `duration` ago.
+    waiting: bool,
+    latest: Option<T>,
+}
+
+impl<T: 'static> Throttle<T> {
+    fn start_window(state: &Rc<RefCell<Self>>) {
+        let (schedule, duration) = {
+            let mut throttle = state.borrow_mut();
+            throttle.waiting = true;
+            (throttle.schedule.clone(), throttle.duration)
+        };
+        let timer = schedule(
+            duration,
+            Box::new({
+                let state = state.clone();
+                move || {
+                    let (latest, action) = {
+                        let mut throttle = state.borrow_mut();
+                        throttle.waiting = false;
+                        throttle.timer = None;
+                        (throttle.latest.take(), throttle.action.clone())
+                    };
+                    if let Some(value) = latest {
+                        action(value);
+                        Self::start_window(&state);
+                    }
+                }
+            }),
+        );
+        state.borrow_mut().timer = Some(timer);
+    }
+}
+
+fn throttle_with<T: 'static>(
+    schedule: Schedule,
+    duration: Duration,
+    action: impl Fn(T) + 'static,
+) -> impl Fn(T) {
+    let state = Rc::new(RefCell::new(Throttle {
+        schedule,
+        duration,
+        action: Rc::new(action),
+        timer: None,
+        waiting: false,
+        latest: None,
+    }));
+    on_cleanup({
+        let state = state.clone();
+        move || {
+            if let Some(timer) = state.borrow_mut().timer.take() {
+                timer.cancel();
+            }
+        }
+    });
+    move |value| {
+        let action = {
+            let mut throttle = state.borrow_mut();
+            if throttle.waiting {
+                throttle.latest = Some(value);
+                return;
+            }
+            throttle.action.clone()
+        };
+        action(value);
+        Throttle::start_window(&state);
+    }
+}
+
+/// Derives signals which follow a signal at a limited rate.
+pub trait DebounceExt<T> {
+    /// Returns a signal which is set to the value of this one once it stops changing for
+    /// `duration`.
+    fn debounced(&self, duration: Duration) -> ReadSignal<T>;
+
+    /// Returns a signal which follows this one, updating at most once every `duration`.
+    fn throttled(&self, duration: Duration) -> ReadSignal<T>;
+}
+
+fn follow<T: Clone + 'static>(
+    initial: T,
+    get: impl Fn() -> T + 'static,
+    limit: impl FnOnce(Box<dyn Fn(T)>) -> Box<dyn Fn(T)>,
+) -> ReadSignal<T> {
+    let (read, write) = create_signal(initial);
+    let set = limit(Box::new(move |value| write.set(value)));
+    create_effect(move |first_run: Option<()>| {
+        let value = get();
+        // The initial value is already in the signal
+        if first_run.is_some() {
+            set(value);
+        }
+    });
+    read
+}
+
+macro_rules! impl_debounce_ext {
+    ($signal:ident) => {
+        impl<T: Clone + 'static> DebounceExt<T> for $signal<T> {
+            fn debounced(&self, duration: Duration) -> ReadSignal<T> {
+                let signal = *self;
+                follow(
+                    self.get_untracked(),
+                    move || signal.get(),
+                    |set| Box::new(debounce(duration, set)),
+                )
+            }
+
+            fn throttled(&self, duration: Duration) -> ReadSignal<T> {
+                let signal = *self;
+                follow(
+                    self.get_untracked(),
+                    move || signal.get(),
+                    |set| Box::new(throttle(duration, set)),
+                )
+            }
+        }
+    };
+}
+
+impl_debounce_ext!(ReadSignal);
+impl_debounce_ext!(RwSignal);
+impl_debounce_ext!(Memo);
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use floem_reactive::{with_scope, Scope};
+
+    use crate::action::TimerHandle;
+
+    use super::{debounce_with, throttle_with, Schedule};
+
+    /// A clock which runs timers when it's advanced, instead of on the event loop.
+    #[derive(Default)]
+    struct Clock {
+        now: RefCell<Duration>,
+        timers: RefCell<Vec<(Duration, TimerHandle, Box<dyn FnOnce()>)>>,
+    }
+
+    impl Clock {
+        fn schedule(self: &Rc<Self>) -> Schedule {
+            let clock = self.clone();
+            Rc::new(move |duration, action| {
+                let timer = TimerHandle::new();
+                let deadline = *clock.now.borrow() + duration;
+                clock
+                    .timers
+                    .borrow_mut()
+                    .push((deadline, timer.clone(), action));
+                timer
+            })
+        }
+
+        fn advance(&self, millis: u64) {
+            let end = *self.now.borrow() + Duration::from_millis(millis);
+            loop {
+                let next = {
+                    let mut timers = self.timers.borrow_mut();
+                    let due = timers
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (deadline, _, _))| *deadline <= end)
+                        .min_by_key(|(_, (deadline, _, _))| *deadline)
+                        .map(|(i, _)| i);
+                    due.map(|i| timers.remove(i))
+                };
+                let Some((deadline, timer, action)) = next else {
+                    break;
+                };
+                *self.now.borrow_mut() = deadline;
+                if !timer.is_cancelled() {
+                    action();
+                }
+            }
+            *self.now.borrow_mut() = end;
+        }
+    }
+
+    fn recorder() -> (Rc<RefCell<Vec<u32>>>, impl Fn(u32)) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        let record = {
+            let calls = calls.clone();
+            move |value| calls.borrow_mut().push(value)
+        };
+        (calls, record)
+    }
+
+    #[test]
+    fn debounce_delivers_last_value() {
+        let clock = Rc::new(Clock::default());
+        let (calls, record) = recorder();
+        let debounced = debounce_with(clock.schedule(), Duration::from_millis(100), record);
+
+        for i in 0..10 {
+            debounced(i);
+            clock.advance(30);
+        }
+        assert!(calls.borrow().is_empty());
+
+        clock.advance(100);
+        assert_eq!(*calls.borrow(), vec![9]);
+
+        debounced(10);
+        clock.advance(100);
+        assert_eq!(*calls.borrow(), vec![9, 10]);
+    }
+
+    #[test]
+    fn throttle_delivers_leading_and_trailing_values() {
+        let clock = Rc::new(Clock::default());
+        let (calls, record) = recorder();
+        let throttled = throttle_with(clock.schedule(), Duration::from_millis(100), record);
+
+        // Calls at 0, 30, ..., 240
+        for i in 0..9 {
+            throttled(i);
+            clock.advance(30);
+        }
+        // The leading call, then the latest value at the end of the windows at 100 and 200
+        assert_eq!(*calls.borrow(), vec![0, 3, 6]);
+
+        clock.advance(30);
+        assert_eq!(*calls.borrow(), vec![0, 3, 6, 8]);
+
+        // The last window ends without new values, so the next call goes through right away
+        clock.advance(100);
+        throttled(9);
+        assert_eq!(*calls.borrow(), vec![0, 3, 6, 8, 9]);
+    }
+
+    #[test]
+    fn pending_calls_are_dropped_with_the_scope() {
+        let clock = Rc::new(Clock::default());
+        let (debounce_calls, debounce_record) = recorder();
+        let (throttle_calls, throttle_record) = recorder();
+        let scope = Scope::new();
+        let (debounced, throttled) = with_scope(scope, || {
+            (
+                debounce_with(
+                    clock.schedule(),
+                    Duration::from_millis(100),
+                    debounce_record,
+                ),
+                throttle_with(
+                    clock.schedule(),
+                    Duration::from_millis(100),
+                    throttle_record,
+                ),
+            )
+        });
+
+        debounced(0);
+        throttled(0);
+        throttled(1);
+        scope.dispose();
+        assert!(clock
+            .timers
+            .borrow()
+            .iter()
+            .all(|(_, timer, _)| timer.is_cancelled()));
+
+        clock.advance(200);
+        assert!(debounce_calls.borrow().is_empty());
+        assert_eq!(*throttle_calls.borrow(), vec![0]);
+    }
+}
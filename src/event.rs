@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use floem_winit::{
     keyboard::{KeyCode, PhysicalKey},
     window::Theme,
@@ -6,9 +8,27 @@ use kurbo::{Point, Size};
 
 use crate::{
     keyboard::KeyEvent,
-    pointer::{PointerInputEvent, PointerMoveEvent, PointerWheelEvent},
+    pointer::{
+        PointerInputEvent, PointerMoveEvent, PointerPinchEvent, PointerWheelEvent, TouchEvent,
+    },
 };
 
+/// Files dragged from outside the app over a view. See [`Id::update_file_drop_listener`](crate::id::Id::update_file_drop_listener).
+///
+/// Positions are relative to the view. Some platforms don't report pointer movement while files
+/// are dragged, in which case the position is the last known pointer position.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileDropEvent {
+    /// Files were dragged into the view.
+    Enter { paths: Vec<PathBuf>, pos: Point },
+    /// Files are dragged over the view.
+    Hover { paths: Vec<PathBuf>, pos: Point },
+    /// Files were dragged out of the view, or the drag was cancelled.
+    Leave,
+    /// Files were dropped on the view.
+    Drop { paths: Vec<PathBuf>, pos: Point },
+}
+
 #[derive(Debug, Hash, PartialEq, Eq)]
 pub enum EventListener {
     KeyDown,
@@ -32,15 +52,26 @@ pub enum EventListener {
     ImePreedit,
     ImeCommit,
     PointerWheel,
+    /// Sent when two fingers pinch or rotate on a touchpad.
+    PointerPinch,
+    /// Sent when a finger touches the screen, moves, or is lifted. Once a view handles the
+    /// beginning of a touch, the rest of the touch is only sent to that view.
+    Touch,
     FocusGained,
     FocusLost,
     ThemeChanged,
     WindowClosed,
+    /// Sent when the user asks to close the window. Returning [`EventPropagation::Stop`](crate::EventPropagation::Stop)
+    /// from a listener keeps the window open, for example to ask to save changes first.
+    WindowCloseRequested,
     WindowResized,
     WindowMoved,
     WindowGotFocus,
     WindowLostFocus,
     WindowMaximizeChanged,
+    /// Sent when the scale factor of the window changes, like when it's moved to a monitor
+    /// with a different DPI. See [`scale_factor`](crate::window::scale_factor).
+    WindowScaleChanged,
 }
 
 #[derive(Debug, Clone)]
@@ -49,6 +80,8 @@ pub enum Event {
     PointerUp(PointerInputEvent),
     PointerMove(PointerMoveEvent),
     PointerWheel(PointerWheelEvent),
+    PointerPinch(PointerPinchEvent),
+    Touch(TouchEvent),
     PointerLeave,
     KeyDown(KeyEvent),
     KeyUp(KeyEvent),
@@ -62,9 +95,14 @@ pub enum Event {
     WindowGotFocus,
     WindowLostFocus,
     WindowClosed,
+    WindowCloseRequested,
     WindowResized(Size),
     WindowMoved(Point),
     WindowMaximizeChanged(bool),
+    WindowScaleChanged {
+        old: f64,
+        new: f64,
+    },
     ThemeChanged(Theme),
     FocusGained,
     FocusLost,
@@ -77,6 +115,8 @@ impl Event {
             | Event::PointerUp(_)
             | Event::PointerMove(_)
             | Event::PointerWheel(_)
+            | Event::PointerPinch(_)
+            | Event::Touch(_)
             | Event::PointerLeave
             | Event::FocusGained
             | Event::FocusLost
@@ -86,9 +126,11 @@ impl Event {
             | Event::ImeCommit(_)
             | Event::ThemeChanged(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowGotFocus
             | Event::WindowLostFocus => false,
             Event::KeyDown(_) | Event::KeyUp(_) => true,
@@ -101,8 +143,12 @@ impl Event {
             | Event::PointerUp(_)
             | Event::PointerMove(_)
             | Event::PointerWheel(_)
+            | Event::PointerPinch(_)
             | Event::PointerLeave => true,
-            Event::KeyDown(_)
+            // Touches are tracked separately from the pointer, so they aren't sent to the
+            // active view
+            Event::Touch(_)
+            | Event::KeyDown(_)
             | Event::KeyUp(_)
             | Event::FocusGained
             | Event::FocusLost
@@ -112,9 +158,11 @@ impl Event {
             | Event::ImeCommit(_)
             | Event::ThemeChanged(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowGotFocus
             | Event::WindowLostFocus => false,
         }
@@ -140,6 +188,8 @@ impl Event {
             Event::PointerDown(_)
             | Event::PointerUp(_)
             | Event::PointerWheel(_)
+            | Event::PointerPinch(_)
+            | Event::Touch(_)
             | Event::FocusGained
             | Event::FocusLost
             | Event::ImeEnabled
@@ -152,10 +202,12 @@ impl Event {
             | Event::PointerMove(_)
             | Event::ThemeChanged(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowGotFocus
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowLostFocus => true,
         }
     }
@@ -167,6 +219,8 @@ impl Event {
             }
             Event::PointerMove(pointer_event) => Some(pointer_event.pos),
             Event::PointerWheel(pointer_event) => Some(pointer_event.pos),
+            Event::PointerPinch(pointer_event) => Some(pointer_event.pos),
+            Event::Touch(touch_event) => Some(touch_event.pos),
             Event::PointerLeave
             | Event::KeyDown(_)
             | Event::KeyUp(_)
@@ -178,9 +232,11 @@ impl Event {
             | Event::ThemeChanged(_)
             | Event::ImeCommit(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowGotFocus
             | Event::WindowLostFocus => None,
         }
@@ -200,6 +256,14 @@ impl Event {
                 pointer_event.pos.x /= scale;
                 pointer_event.pos.y /= scale;
             }
+            Event::PointerPinch(pointer_event) => {
+                pointer_event.pos.x /= scale;
+                pointer_event.pos.y /= scale;
+            }
+            Event::Touch(touch_event) => {
+                touch_event.pos.x /= scale;
+                touch_event.pos.y /= scale;
+            }
             Event::PointerLeave
             | Event::KeyDown(_)
             | Event::KeyUp(_)
@@ -211,9 +275,11 @@ impl Event {
             | Event::ThemeChanged(_)
             | Event::ImeCommit(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowGotFocus
             | Event::WindowLostFocus => {}
         }
@@ -231,6 +297,12 @@ impl Event {
             Event::PointerWheel(pointer_event) => {
                 pointer_event.pos -= offset;
             }
+            Event::PointerPinch(pointer_event) => {
+                pointer_event.pos -= offset;
+            }
+            Event::Touch(touch_event) => {
+                touch_event.pos -= offset;
+            }
             Event::PointerLeave
             | Event::KeyDown(_)
             | Event::KeyUp(_)
@@ -242,9 +314,11 @@ impl Event {
             | Event::ThemeChanged(_)
             | Event::ImeCommit(_)
             | Event::WindowClosed
+            | Event::WindowCloseRequested
             | Event::WindowResized(_)
             | Event::WindowMoved(_)
             | Event::WindowMaximizeChanged(_)
+            | Event::WindowScaleChanged { .. }
             | Event::WindowGotFocus
             | Event::WindowLostFocus => {}
         }
@@ -257,6 +331,8 @@ impl Event {
             Event::PointerUp(_) => Some(EventListener::PointerUp),
             Event::PointerMove(_) => Some(EventListener::PointerMove),
             Event::PointerWheel(_) => Some(EventListener::PointerWheel),
+            Event::PointerPinch(_) => Some(EventListener::PointerPinch),
+            Event::Touch(_) => Some(EventListener::Touch),
             Event::PointerLeave => Some(EventListener::PointerLeave),
             Event::KeyDown(_) => Some(EventListener::KeyDown),
             Event::KeyUp(_) => Some(EventListener::KeyUp),
@@ -265,9 +341,11 @@ impl Event {
             Event::ImePreedit { .. } => Some(EventListener::ImePreedit),
             Event::ImeCommit(_) => Some(EventListener::ImeCommit),
             Event::WindowClosed => Some(EventListener::WindowClosed),
+            Event::WindowCloseRequested => Some(EventListener::WindowCloseRequested),
             Event::WindowResized(_) => Some(EventListener::WindowResized),
             Event::WindowMoved(_) => Some(EventListener::WindowMoved),
             Event::WindowMaximizeChanged(_) => Some(EventListener::WindowMaximizeChanged),
+            Event::WindowScaleChanged { .. } => Some(EventListener::WindowScaleChanged),
             Event::WindowGotFocus => Some(EventListener::WindowGotFocus),
             Event::WindowLostFocus => Some(EventListener::WindowLostFocus),
             Event::FocusLost => Some(EventListener::FocusLost),
@@ -0,0 +1,156 @@
+//! # Executors
+//!
+//! Floem runs futures, like those of [resources](crate::resource::create_resource), on an
+//! [`Executor`]. By default this is a [`ThreadPoolExecutor`] with one thread per CPU core.
+//! Apps using an async runtime can run the futures on it instead by registering an executor at
+//! startup with [`Application::executor`](crate::Application::executor) or [`set_executor`].
+//!
+//! Futures run off the UI thread, so they can't update signals or send messages to views
+//! directly. [`spawn_with_result`] delivers the output of a future to a function on the UI
+//! thread: when the future completes, the output is queued and the event loop is woken up,
+//! which runs the function before processing the update messages of the windows, so any
+//! signal it sets is reflected in the next frame.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    task::{Context, Wake, Waker},
+};
+
+use crossbeam_channel::{Receiver, Sender};
+use floem_reactive::Scope;
+use parking_lot::Mutex;
+
+use crate::ext_event::create_ext_action;
+
+pub type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Runs futures to completion.
+pub trait Executor: Send + Sync + 'static {
+    fn spawn(&self, future: BoxFuture);
+}
+
+static EXECUTOR: OnceLock<Box<dyn Executor>> = OnceLock::new();
+
+/// Sets the executor futures are spawned on. This can only be set once, before any future
+/// is spawned, and returns false otherwise.
+pub fn set_executor(executor: impl Executor) -> bool {
+    EXECUTOR.set(Box::new(executor)).is_ok()
+}
+
+fn executor() -> &'static dyn Executor {
+    EXECUTOR
+        .get_or_init(|| Box::new(ThreadPoolExecutor::default()))
+        .as_ref()
+}
+
+/// Runs `future` on the executor.
+pub fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    executor().spawn(Box::pin(future));
+}
+
+/// Runs `future` on the executor and calls `action` with its output on the UI thread.
+pub fn spawn_with_result<T: Send + 'static>(
+    future: impl Future<Output = T> + Send + 'static,
+    action: impl FnOnce(T) + 'static,
+) {
+    let cx = Scope::new();
+    let send = create_ext_action(cx, move |output| {
+        action(output);
+        // The action runs in a child scope, which is disposed with this one
+        cx.dispose();
+    });
+    spawn(async move { send(future.await) });
+}
+
+/// An executor which polls futures on a fixed number of threads.
+pub struct ThreadPoolExecutor {
+    sender: Sender<Arc<Task>>,
+}
+
+impl Default for ThreadPoolExecutor {
+    fn default() -> Self {
+        let threads = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+        Self::new(threads)
+    }
+}
+
+impl ThreadPoolExecutor {
+    pub fn new(threads: usize) -> Self {
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        for i in 0..threads.max(1) {
+            let receiver: Receiver<Arc<Task>> = receiver.clone();
+            let _ = std::thread::Builder::new()
+                .name(format!("floem-executor-{i}"))
+                .spawn(move || {
+                    while let Ok(task) = receiver.recv() {
+                        task.poll();
+                    }
+                });
+        }
+        Self { sender }
+    }
+}
+
+impl Executor for ThreadPoolExecutor {
+    fn spawn(&self, future: BoxFuture) {
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(future)),
+            sender: self.sender.clone(),
+        });
+        let _ = self.sender.send(task);
+    }
+}
+
+/// A spawned future, which is sent back to the pool when it's woken up.
+struct Task {
+    future: Mutex<Option<BoxFuture>>,
+    sender: Sender<Arc<Task>>,
+}
+
+impl Task {
+    fn poll(self: Arc<Self>) {
+        let mut future = self.future.lock();
+        if let Some(mut fut) = future.take() {
+            let waker = Waker::from(self.clone());
+            let mut cx = Context::from_waker(&waker);
+            if fut.as_mut().poll(&mut cx).is_pending() {
+                *future = Some(fut);
+            }
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        let _ = self.sender.clone().send(self);
+    }
+}
+
+/// An executor which spawns futures on a tokio runtime.
+#[cfg(feature = "tokio")]
+pub struct TokioExecutor(pub tokio::runtime::Handle);
+
+#[cfg(feature = "tokio")]
+impl TokioExecutor {
+    /// Spawns on the runtime of the current thread. Panics if there is none.
+    pub fn current() -> Self {
+        Self(tokio::runtime::Handle::current())
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Executor for TokioExecutor {
+    fn spawn(&self, future: BoxFuture) {
+        self.0.spawn(future);
+    }
+}
+
+impl<F: Fn(BoxFuture) + Send + Sync + 'static> Executor for F {
+    fn spawn(&self, future: BoxFuture) {
+        self(future)
+    }
+}
@@ -1,4 +1,4 @@
-use std::{cell::Cell, collections::VecDeque, sync::Arc};
+use std::{any::Any, cell::Cell, collections::VecDeque, sync::Arc};
 
 use floem_reactive::{create_effect, untrack, with_scope, ReadSignal, Scope, Trigger, WriteSignal};
 use once_cell::sync::Lazy;
@@ -6,6 +6,7 @@ use parking_lot::Mutex;
 
 use crate::{
     app::UserEvent,
+    id::Id,
     window_handle::{get_current_view, set_current_view},
     Application,
 };
@@ -34,6 +35,49 @@ impl ExtEventHandler {
     }
 }
 
+type ProxyAction = Box<dyn FnOnce() + Send>;
+
+pub(crate) static PROXY_ACTIONS: Lazy<Mutex<VecDeque<ProxyAction>>> =
+    Lazy::new(|| Mutex::new(VecDeque::new()));
+
+/// A handle for sending work from background threads to the UI thread.
+///
+/// The signals, views and update messages of Floem can only be used on the UI thread.
+/// `AppProxy` can be cloned and sent to other threads, and the work posted with it runs on the
+/// UI thread on the next event loop iteration, before the windows process their updates.
+/// ```rust,no_run
+/// # use floem::ext_event::AppProxy;
+/// # use floem::id::Id;
+/// # let id = Id::next();
+/// let proxy = AppProxy::new();
+/// std::thread::spawn(move || {
+///     let result = 42;
+///     proxy.update(id, result);
+/// });
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AppProxy;
+
+impl AppProxy {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Runs `action` on the UI thread.
+    pub fn run(&self, action: impl FnOnce() + Send + 'static) {
+        PROXY_ACTIONS.lock().push_back(Box::new(action));
+        Application::with_event_loop_proxy(|proxy| {
+            let _ = proxy.send_event(UserEvent::Idle);
+        });
+    }
+
+    /// Sends `state` to the view with `id`, like [`Id::update_state`].
+    /// Use [`Id::send_state`] to skip the round trip when already on the UI thread.
+    pub fn update(&self, id: Id, state: impl Any + Send) {
+        self.run(move || id.update_state(state));
+    }
+}
+
 pub fn create_ext_action<T: Send + 'static>(
     cx: Scope,
     action: impl FnOnce(T) + 'static,
@@ -1,4 +1,15 @@
-use std::path::{Path, PathBuf};
+use std::{
+    future::{Future, IntoFuture},
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll, Waker},
+};
+
+use floem_reactive::Scope;
+use parking_lot::Mutex;
+
+use crate::ext_event::create_ext_action;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FileSpec {
@@ -168,3 +179,218 @@ impl FileDialogOptions {
         self
     }
 }
+
+impl FileDialogOptions {
+    fn rfd_dialog(&self) -> rfd::FileDialog {
+        let mut dialog = rfd::FileDialog::new();
+        if let Some(path) = self.starting_directory.as_ref() {
+            dialog = dialog.set_directory(path);
+        }
+        if let Some(title) = self.title.as_ref() {
+            dialog = dialog.set_title(title);
+        }
+        if let Some(name) = self.default_name.as_ref() {
+            dialog = dialog.set_file_name(name);
+        }
+        if let Some(types) = self.allowed_types.as_ref() {
+            // rfd selects the first filter by default
+            let default = self.default_type.filter(|default| types.contains(default));
+            for spec in default
+                .iter()
+                .chain(types.iter().filter(|t| Some(**t) != default))
+            {
+                dialog = dialog.add_filter(spec.name, spec.extensions);
+            }
+        }
+        dialog
+    }
+
+    fn file_info(&self, path: PathBuf) -> FileInfo {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        let format = self.allowed_types.as_ref().and_then(|types| {
+            let extension = extension?;
+            types.iter().copied().find(|spec| {
+                spec.extensions
+                    .iter()
+                    .any(|ext| ext.eq_ignore_ascii_case(extension))
+            })
+        });
+        FileInfo { path, format }
+    }
+
+    /// Shows an open dialog and blocks until it's closed.
+    pub(crate) fn pick(&self) -> Vec<FileInfo> {
+        let dialog = self.rfd_dialog();
+        let paths = match (self.select_directories, self.multi_selection) {
+            (true, true) => dialog.pick_folders(),
+            (true, false) => dialog.pick_folder().map(|path| vec![path]),
+            (false, true) => dialog.pick_files(),
+            (false, false) => dialog.pick_file().map(|path| vec![path]),
+        };
+        paths
+            .unwrap_or_default()
+            .into_iter()
+            .map(|path| self.file_info(path))
+            .collect()
+    }
+
+    /// Shows a save dialog and blocks until it's closed.
+    pub(crate) fn pick_save(&self) -> Option<FileInfo> {
+        self.rfd_dialog()
+            .save_file()
+            .map(|path| self.file_info(path))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileDialogKind {
+    Open,
+    Save,
+}
+
+/// A native dialog to choose files to open or where to save a file.
+///
+/// The dialog runs on a separate thread so it doesn't block the UI. It can be awaited, which
+/// returns the chosen files, or shown with [`FileDialog::show`], which calls a function with
+/// the chosen files on the UI thread:
+/// ```rust,no_run
+/// use floem::file::{FileDialog, FileSpec};
+///
+/// # async fn open() {
+/// let files = FileDialog::open()
+///     .filters(vec![FileSpec {
+///         name: "Text",
+///         extensions: &["txt", "md"],
+///     }])
+///     .multi_selection()
+///     .await;
+/// # }
+/// ```
+/// The result is empty if the dialog was cancelled. Save dialogs return at most one file.
+#[derive(Debug, Clone)]
+pub struct FileDialog {
+    kind: FileDialogKind,
+    options: FileDialogOptions,
+}
+
+impl FileDialog {
+    /// A dialog to choose existing files, or directories with [`FileDialog::select_directories`].
+    pub fn open() -> Self {
+        Self {
+            kind: FileDialogKind::Open,
+            options: FileDialogOptions::new(),
+        }
+    }
+
+    /// A dialog to choose where to save a file.
+    pub fn save() -> Self {
+        Self {
+            kind: FileDialogKind::Save,
+            options: FileDialogOptions::new(),
+        }
+    }
+
+    /// Replaces all the options of the dialog.
+    pub fn options(mut self, options: FileDialogOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the file types the user is allowed to select.
+    /// See [`FileDialogOptions::allowed_types`].
+    pub fn filters(mut self, filters: Vec<FileSpec>) -> Self {
+        self.options = self.options.allowed_types(filters);
+        self
+    }
+
+    /// Set directories to be selectable instead of files. Only relevant for open dialogs.
+    pub fn select_directories(mut self) -> Self {
+        self.options = self.options.select_directories();
+        self
+    }
+
+    /// Set multiple items to be selectable. Only relevant for open dialogs.
+    pub fn multi_selection(mut self) -> Self {
+        self.options = self.options.multi_selection();
+        self
+    }
+
+    /// Set the suggested filename.
+    pub fn default_name(mut self, default_name: impl Into<String>) -> Self {
+        self.options = self.options.default_name(default_name);
+        self
+    }
+
+    /// Set the title text of the dialog.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.options = self.options.title(title);
+        self
+    }
+
+    /// Set the directory the dialog starts in.
+    pub fn starting_directory(mut self, path: impl Into<PathBuf>) -> Self {
+        self.options = self.options.force_starting_directory(path);
+        self
+    }
+
+    /// Shows the dialog and calls `action` with the chosen files on the UI thread once it's
+    /// closed. This can be called from event handlers.
+    pub fn show(self, action: impl FnOnce(Vec<FileInfo>) + 'static) {
+        let send = create_ext_action(Scope::new(), action);
+        std::thread::spawn(move || send(self.run()));
+    }
+
+    fn run(&self) -> Vec<FileInfo> {
+        match self.kind {
+            FileDialogKind::Open => self.options.pick(),
+            FileDialogKind::Save => self.options.pick_save().into_iter().collect(),
+        }
+    }
+}
+
+impl IntoFuture for FileDialog {
+    type Output = Vec<FileInfo>;
+    type IntoFuture = FileDialogFuture;
+
+    fn into_future(self) -> Self::IntoFuture {
+        let state = Arc::new(Mutex::new(FileDialogState::default()));
+        {
+            let state = state.clone();
+            std::thread::spawn(move || {
+                let files = self.run();
+                let mut state = state.lock();
+                state.files = Some(files);
+                if let Some(waker) = state.waker.take() {
+                    waker.wake();
+                }
+            });
+        }
+        FileDialogFuture { state }
+    }
+}
+
+#[derive(Default)]
+struct FileDialogState {
+    files: Option<Vec<FileInfo>>,
+    waker: Option<Waker>,
+}
+
+/// The future returned by awaiting a [`FileDialog`].
+pub struct FileDialogFuture {
+    state: Arc<Mutex<FileDialogState>>,
+}
+
+impl Future for FileDialogFuture {
+    type Output = Vec<FileInfo>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.files.take() {
+            Some(files) => Poll::Ready(files),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -0,0 +1,69 @@
+//! Loading fonts bundled with the app.
+//!
+//! Text is shaped with the fonts installed on the system. Fonts loaded with [`load_font_data`]
+//! or [`load_font_file`] are added to them, and are used like the system fonts by their family
+//! name, with [`Style::font_family`](crate::style::Style::font_family) or in a fallback list.
+//! ```rust,no_run
+//! use floem::font::load_font_file;
+//! use floem::views::{label, Decorators};
+//!
+//! load_font_file("assets/Inter.ttf").expect("the font is bundled with the app");
+//! let title = label(|| "Title").style(|s| s.font_family("Inter".to_string()));
+//! ```
+
+use std::{fmt, path::PathBuf, sync::Arc};
+
+use floem_renderer::cosmic_text::{fontdb, FONT_SYSTEM};
+
+/// The id of a loaded font, which there's one of for each font of a collection.
+pub type FontId = fontdb::ID;
+
+/// An error loading a font.
+#[derive(Debug)]
+pub enum FontError {
+    /// The file couldn't be read.
+    Io { path: PathBuf, message: String },
+    /// The data isn't a font or a font collection which can be read.
+    InvalidData,
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FontError::Io { path, message } => {
+                write!(f, "failed to read {}: {message}", path.display())
+            }
+            FontError::InvalidData => f.write_str("the data isn't a valid font"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+/// Loads a font, or all the fonts of a collection like a `.ttc` file, from its data, returning
+/// the ids of the fonts loaded. The fonts can then be used by their family name.
+///
+/// Fonts should be loaded before the windows are created, as text which is already laid out
+/// keeps its fonts until it changes.
+pub fn load_font_data(data: impl Into<Vec<u8>>) -> Result<Vec<FontId>, FontError> {
+    let ids = FONT_SYSTEM
+        .lock()
+        .db_mut()
+        .load_font_source(fontdb::Source::Binary(Arc::new(data.into())));
+    // Data which can't be parsed is skipped by the database without an error
+    if ids.is_empty() {
+        return Err(FontError::InvalidData);
+    }
+    Ok(ids.to_vec())
+}
+
+/// Loads a font, or all the fonts of a collection, from the file at `path`, like
+/// [`load_font_data`].
+pub fn load_font_file(path: impl Into<PathBuf>) -> Result<Vec<FontId>, FontError> {
+    let path = path.into();
+    let data = std::fs::read(&path).map_err(|err| FontError::Io {
+        path,
+        message: err.to_string(),
+    })?;
+    load_font_data(data)
+}
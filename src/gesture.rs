@@ -0,0 +1,826 @@
+//! # Gestures
+//!
+//! Gesture recognizers turn the pointer events of a view into higher-level gestures: pans,
+//! pinches, long presses and swipes. They are attached with [`Decorators::gestures`], or one at
+//! a time with methods like [`Decorators::on_pan`].
+//!
+//! Gestures start from pointer events which reach the view, so children which handle the
+//! pointer themselves, like a [`scroll`](crate::views::scroll) being dragged by its scroll bar,
+//! don't also trigger a gesture. Wheel events, which are how touchpads scroll, never start a
+//! gesture. The first finger touching a view drives its gestures like the pointer.
+//!
+//! Several gestures can be recognized from the same input, like a pan and then a swipe when the
+//! pointer is released quickly. [`Gestures::require_failure`] makes a gesture wait until
+//! another one failed, and fail when the other one is recognized.
+//! ```rust
+//! use std::time::Duration;
+//! use floem::gesture::{GestureKind, Gestures};
+//! use floem::views::{empty, Decorators};
+//!
+//! empty().gestures(
+//!     Gestures::new()
+//!         .on_swipe(|direction| println!("swiped {direction:?}"))
+//!         .on_long_press(Duration::from_millis(500), |pos| println!("long press at {pos}"))
+//!         .on_pan(|pan| println!("{:?} {}", pan.phase, pan.translation))
+//!         // A quick drag is a swipe, not a pan
+//!         .require_failure(GestureKind::Pan, GestureKind::Swipe),
+//! );
+//! ```
+//!
+//! [`Decorators::gestures`]: crate::views::Decorators::gestures
+//! [`Decorators::on_pan`]: crate::views::Decorators::on_pan
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use floem_winit::event::TouchPhase;
+use kurbo::{Point, Vec2};
+
+use crate::{
+    action::{cancel_timer, exec_after, TimerToken},
+    clock,
+    event::Event,
+    id::Id,
+    pointer::TouchEvent,
+    EventPropagation,
+};
+
+/// How far the pointer has to move before a press becomes a pan, and stops being a long press.
+const SLOP: f64 = 8.0;
+/// How far the pointer has to move for a swipe.
+const SWIPE_MIN_DISTANCE: f64 = 40.0;
+/// How fast the pointer has to move when it's released for a swipe, in pixels per second.
+const SWIPE_MIN_VELOCITY: f64 = 300.0;
+/// How long the pointer can rest before being released and still swipe.
+const SWIPE_MAX_REST: Duration = Duration::from_millis(100);
+
+/// Where a continuous gesture is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GesturePhase {
+    Began,
+    Changed,
+    Ended,
+    Cancelled,
+}
+
+impl From<TouchPhase> for GesturePhase {
+    fn from(phase: TouchPhase) -> Self {
+        match phase {
+            TouchPhase::Started => GesturePhase::Began,
+            TouchPhase::Moved => GesturePhase::Changed,
+            TouchPhase::Ended => GesturePhase::Ended,
+            TouchPhase::Cancelled => GesturePhase::Cancelled,
+        }
+    }
+}
+
+/// A pointer being dragged over a view. Positions are relative to the view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PanEvent {
+    pub phase: GesturePhase,
+    pub pos: Point,
+    /// How far the pointer moved since the pan began.
+    pub translation: Vec2,
+    /// How fast the pointer is moving, in pixels per second.
+    pub velocity: Vec2,
+}
+
+/// Two fingers pinching or rotating on a touchpad over a view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinchEvent {
+    pub phase: GesturePhase,
+    pub pos: Point,
+    /// The scale since the pinch began, which is 1.0 at first.
+    pub scale: f64,
+    /// The clockwise rotation since the pinch began, in radians.
+    pub rotation: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// The kinds of gestures, for [`Gestures::require_failure`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GestureKind {
+    Pan,
+    Pinch,
+    LongPress,
+    Swipe,
+}
+
+/// The gestures recognized on a view, see the [module documentation](self).
+#[derive(Default)]
+pub struct Gestures {
+    pan: Option<Rc<dyn Fn(&PanEvent)>>,
+    pinch: Option<Rc<dyn Fn(&PinchEvent)>>,
+    long_press: Option<(Duration, Rc<dyn Fn(Point)>)>,
+    swipe: Option<Rc<dyn Fn(SwipeDirection)>>,
+    /// Pairs of a gesture and a gesture which has to fail before it's recognized.
+    requirements: Vec<(GestureKind, GestureKind)>,
+}
+
+impl Gestures {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Calls `action` when the pointer is pressed and moved, until it's released.
+    pub fn on_pan(mut self, action: impl Fn(&PanEvent) + 'static) -> Self {
+        self.pan = Some(Rc::new(action));
+        self
+    }
+
+    /// Calls `action` when two fingers pinch or rotate on a touchpad.
+    pub fn on_pinch(mut self, action: impl Fn(&PinchEvent) + 'static) -> Self {
+        self.pinch = Some(Rc::new(action));
+        self
+    }
+
+    /// Calls `action` with the position of the pointer when it's pressed for `duration`
+    /// without moving.
+    pub fn on_long_press(mut self, duration: Duration, action: impl Fn(Point) + 'static) -> Self {
+        self.long_press = Some((duration, Rc::new(action)));
+        self
+    }
+
+    /// Calls `action` when the pointer is dragged quickly in a direction and released.
+    pub fn on_swipe(mut self, action: impl Fn(SwipeDirection) + 'static) -> Self {
+        self.swipe = Some(Rc::new(action));
+        self
+    }
+
+    /// Only recognizes `gesture` once `other` failed, and makes it fail when `other` is
+    /// recognized. A pan which requires a swipe to fail only begins when the pointer is
+    /// released without swiping, for example.
+    pub fn require_failure(mut self, gesture: GestureKind, other: GestureKind) -> Self {
+        self.requirements.push((gesture, other));
+        self
+    }
+
+    fn has(&self, kind: GestureKind) -> bool {
+        match kind {
+            GestureKind::Pan => self.pan.is_some(),
+            GestureKind::Pinch => self.pinch.is_some(),
+            GestureKind::LongPress => self.long_press.is_some(),
+            GestureKind::Swipe => self.swipe.is_some(),
+        }
+    }
+}
+
+/// A recognized gesture, which is reported once the gestures it requires to fail failed.
+#[derive(Clone)]
+enum Recognized {
+    Pan(PanEvent),
+    Pinch(PinchEvent),
+    LongPress(Point),
+    Swipe(SwipeDirection),
+}
+
+#[derive(Clone)]
+enum State {
+    /// The gesture can still be recognized.
+    Possible,
+    /// The gesture was recognized, but waits for other gestures to fail.
+    Waiting(Recognized),
+    Recognized,
+    Failed,
+}
+
+struct Press {
+    start: Point,
+    pos: Point,
+    moved_at: Instant,
+    velocity: Vec2,
+    long_press_timer: Option<TimerToken>,
+}
+
+struct Pinch {
+    scale: f64,
+    rotation: f64,
+}
+
+/// Recognizes the gestures of a view from its events.
+pub(crate) struct Recognizer {
+    gestures: Gestures,
+    states: HashMap<GestureKind, State>,
+    press: Option<Press>,
+    pinch: Option<Pinch>,
+    /// Updates of a recognized pan or pinch, which are reported by [`Recognizer::resolve`].
+    pan_changed: Option<PanEvent>,
+    pinch_changed: Option<PinchEvent>,
+    /// The touch which is pressing, when the press isn't from the pointer.
+    touch: Option<u64>,
+    /// Counts the presses, so a long press timer knows whether its press is still going.
+    press_count: u64,
+}
+
+impl Recognizer {
+    /// Returns the event handler recognizing `gestures` for the view `id`.
+    pub(crate) fn handler(id: Id, gestures: Gestures) -> impl Fn(&Event) -> EventPropagation {
+        let recognizer = Rc::new(RefCell::new(Recognizer {
+            gestures,
+            states: HashMap::new(),
+            press: None,
+            pinch: None,
+            pan_changed: None,
+            pinch_changed: None,
+            touch: None,
+            press_count: 0,
+        }));
+        move |event| Self::event(&recognizer, id, event)
+    }
+
+    fn event(this: &Rc<RefCell<Self>>, id: Id, event: &Event) -> EventPropagation {
+        let mut recognizer = this.borrow_mut();
+        let handled = match event {
+            Event::PointerDown(pointer) if pointer.button.is_primary() => {
+                recognizer.press_down(this, pointer.pos);
+                // Moves outside of the view still belong to the gesture
+                id.request_active();
+                false
+            }
+            Event::PointerMove(pointer) => recognizer.press_move(pointer.pos),
+            Event::PointerUp(pointer) if pointer.button.is_primary() => {
+                recognizer.press_up(pointer.pos)
+            }
+            Event::PointerPinch(pinch) => recognizer.pinch(
+                pinch.pos,
+                pinch.scale_delta,
+                pinch.rotation_delta,
+                pinch.phase,
+            ),
+            Event::Touch(touch) => recognizer.touch(this, touch),
+            _ => false,
+        };
+        let recognized = recognizer.resolve();
+        let gestures = recognizer.callbacks();
+        drop(recognizer);
+        // The callbacks are called without borrowing the recognizer, so they can do anything
+        for recognized in recognized {
+            gestures.call(recognized);
+        }
+        if handled {
+            EventPropagation::Stop
+        } else {
+            EventPropagation::Continue
+        }
+    }
+
+    fn callbacks(&self) -> Gestures {
+        Gestures {
+            pan: self.gestures.pan.clone(),
+            pinch: self.gestures.pinch.clone(),
+            long_press: self.gestures.long_press.clone(),
+            swipe: self.gestures.swipe.clone(),
+            requirements: Vec::new(),
+        }
+    }
+
+    fn state(&self, kind: GestureKind) -> Option<&State> {
+        self.states.get(&kind)
+    }
+
+    fn start(&mut self, kind: GestureKind) {
+        if self.gestures.has(kind) {
+            self.states.insert(kind, State::Possible);
+        }
+    }
+
+    fn fail(&mut self, kind: GestureKind) {
+        if let Some(state @ State::Possible) = self.states.get_mut(&kind) {
+            *state = State::Failed;
+        }
+    }
+
+    fn recognize(&mut self, kind: GestureKind, recognized: Recognized) {
+        if let Some(state @ State::Possible) = self.states.get_mut(&kind) {
+            *state = State::Waiting(recognized);
+        }
+    }
+
+    /// Handles a touch like the pointer, and returns whether it's the touch of the press. Only
+    /// the first touch on the view is followed.
+    fn touch(&mut self, this: &Rc<RefCell<Self>>, touch: &TouchEvent) -> bool {
+        match touch.phase {
+            GesturePhase::Began if self.touch.is_none() => {
+                self.touch = Some(touch.id);
+                self.press_down(this, touch.pos);
+            }
+            _ if self.touch != Some(touch.id) => return false,
+            GesturePhase::Began | GesturePhase::Changed => {
+                self.press_move(touch.pos);
+            }
+            GesturePhase::Ended => {
+                self.touch = None;
+                self.press_up(touch.pos);
+            }
+            GesturePhase::Cancelled => {
+                self.touch = None;
+                self.press_cancel();
+            }
+        }
+        true
+    }
+
+    fn press_down(&mut self, this: &Rc<RefCell<Self>>, pos: Point) {
+        self.cancel_long_press();
+        self.press_count += 1;
+        for kind in [GestureKind::Pan, GestureKind::LongPress, GestureKind::Swipe] {
+            self.states.remove(&kind);
+            self.start(kind);
+        }
+        let long_press_timer = self.gestures.long_press.as_ref().map(|(duration, _)| {
+            let this = this.clone();
+            let press_count = self.press_count;
+            exec_after(*duration, move |_| {
+                let mut recognizer = this.borrow_mut();
+                if recognizer.press_count != press_count {
+                    return;
+                }
+                let Some(pos) = recognizer.press.as_ref().map(|press| press.pos) else {
+                    return;
+                };
+                recognizer.recognize(GestureKind::LongPress, Recognized::LongPress(pos));
+                let recognized = recognizer.resolve();
+                let gestures = recognizer.callbacks();
+                drop(recognizer);
+                for recognized in recognized {
+                    gestures.call(recognized);
+                }
+            })
+        });
+        self.press = Some(Press {
+            start: pos,
+            pos,
+            moved_at: clock::now(),
+            velocity: Vec2::ZERO,
+            long_press_timer,
+        });
+    }
+
+    fn cancel_long_press(&mut self) {
+        if let Some(timer) = self.press.as_mut().and_then(|p| p.long_press_timer.take()) {
+            cancel_timer(timer);
+        }
+    }
+
+    fn pan_event(&self, phase: GesturePhase) -> Option<PanEvent> {
+        let press = self.press.as_ref()?;
+        Some(PanEvent {
+            phase,
+            pos: press.pos,
+            translation: press.pos - press.start,
+            velocity: press.velocity,
+        })
+    }
+
+    /// Returns whether the move is part of a pan.
+    fn press_move(&mut self, pos: Point) -> bool {
+        let Some(press) = self.press.as_mut() else {
+            return false;
+        };
+        let now = clock::now();
+        let elapsed = now.duration_since(press.moved_at).as_secs_f64();
+        if elapsed > 0.0 {
+            let velocity = (pos - press.pos) / elapsed;
+            press.velocity = press.velocity * 0.2 + velocity * 0.8;
+        }
+        press.pos = pos;
+        press.moved_at = now;
+        let moved = (pos - press.start).hypot() > SLOP;
+
+        if moved {
+            self.cancel_long_press();
+            self.fail(GestureKind::LongPress);
+        }
+        match self.state(GestureKind::Pan) {
+            Some(State::Possible) if moved => {
+                let began = self.pan_event(GesturePhase::Began).unwrap();
+                self.recognize(GestureKind::Pan, Recognized::Pan(began));
+                true
+            }
+            Some(State::Recognized | State::Waiting(_)) => {
+                self.pan_changed = self.pan_event(GesturePhase::Changed);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the press was a gesture.
+    fn press_up(&mut self, pos: Point) -> bool {
+        let Some(moved_at) = self.press.as_ref().map(|press| press.moved_at) else {
+            return false;
+        };
+        // Whether the pointer rested before the release, which moves it one last time
+        let resting = clock::now().duration_since(moved_at) > SWIPE_MAX_REST;
+        self.press_move(pos);
+        self.cancel_long_press();
+        let press = self.press.take().unwrap();
+
+        if matches!(self.state(GestureKind::Swipe), Some(State::Possible)) {
+            let distance = press.pos - press.start;
+            let direction = if distance.x.abs() > distance.y.abs() {
+                if distance.x > 0.0 {
+                    SwipeDirection::Right
+                } else {
+                    SwipeDirection::Left
+                }
+            } else if distance.y > 0.0 {
+                SwipeDirection::Down
+            } else {
+                SwipeDirection::Up
+            };
+            if !resting
+                && distance.hypot() >= SWIPE_MIN_DISTANCE
+                && press.velocity.hypot() >= SWIPE_MIN_VELOCITY
+            {
+                self.recognize(GestureKind::Swipe, Recognized::Swipe(direction));
+            } else {
+                self.fail(GestureKind::Swipe);
+            }
+        }
+        self.fail(GestureKind::LongPress);
+
+        let ended = PanEvent {
+            phase: GesturePhase::Ended,
+            pos: press.pos,
+            translation: press.pos - press.start,
+            velocity: press.velocity,
+        };
+        match self.states.get_mut(&GestureKind::Pan) {
+            // A pan which is still waiting ends if it's recognized once the press is resolved
+            Some(State::Recognized | State::Waiting(_)) => {
+                self.pan_changed = Some(ended);
+                true
+            }
+            Some(state @ State::Possible) => {
+                *state = State::Failed;
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn press_cancel(&mut self) {
+        self.cancel_long_press();
+        let cancelled = self.pan_event(GesturePhase::Cancelled);
+        self.press = None;
+        for kind in [GestureKind::LongPress, GestureKind::Swipe] {
+            self.fail(kind);
+        }
+        match self.states.get_mut(&GestureKind::Pan) {
+            Some(State::Recognized) => self.pan_changed = cancelled,
+            Some(state) => *state = State::Failed,
+            None => {}
+        }
+    }
+
+    /// Returns whether the pinch was recognized.
+    fn pinch(
+        &mut self,
+        pos: Point,
+        scale_delta: f64,
+        rotation_delta: f64,
+        phase: GesturePhase,
+    ) -> bool {
+        if !self.gestures.has(GestureKind::Pinch) {
+            return false;
+        }
+        if phase == GesturePhase::Began || self.pinch.is_none() {
+            self.states.remove(&GestureKind::Pinch);
+            self.start(GestureKind::Pinch);
+            self.pinch = Some(Pinch {
+                scale: 1.0,
+                rotation: 0.0,
+            });
+        }
+        let pinch = self.pinch.as_mut().unwrap();
+        pinch.scale *= 1.0 + scale_delta;
+        pinch.rotation += rotation_delta;
+        let event = PinchEvent {
+            phase,
+            pos,
+            scale: pinch.scale,
+            rotation: pinch.rotation,
+        };
+        if matches!(phase, GesturePhase::Ended | GesturePhase::Cancelled) {
+            self.pinch = None;
+        }
+        match self.state(GestureKind::Pinch) {
+            Some(State::Possible) => {
+                let began = PinchEvent {
+                    phase: GesturePhase::Began,
+                    ..event.clone()
+                };
+                self.recognize(GestureKind::Pinch, Recognized::Pinch(began));
+                if phase != GesturePhase::Began {
+                    self.pinch_changed = Some(event);
+                }
+                true
+            }
+            Some(State::Recognized | State::Waiting(_)) => {
+                self.pinch_changed = Some(event);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Reports the gestures which were recognized, and whose required failures failed, and
+    /// fails the gestures whose required failures were recognized.
+    fn resolve(&mut self) -> Vec<Recognized> {
+        let mut reported = Vec::new();
+        loop {
+            let mut changed = false;
+            let kinds: Vec<GestureKind> = self.states.keys().copied().collect();
+            for kind in kinds {
+                let Some(State::Waiting(recognized)) = self.states.get(&kind).cloned() else {
+                    continue;
+                };
+                let required = self
+                    .gestures
+                    .requirements
+                    .iter()
+                    .filter(|(gesture, _)| *gesture == kind)
+                    .filter_map(|(_, other)| self.states.get(other));
+                let mut blocked = false;
+                let mut failed = false;
+                for state in required {
+                    match state {
+                        State::Recognized => failed = true,
+                        State::Possible | State::Waiting(_) => blocked = true,
+                        State::Failed => {}
+                    }
+                }
+                if failed {
+                    self.states.insert(kind, State::Failed);
+                    changed = true;
+                } else if !blocked {
+                    self.states.insert(kind, State::Recognized);
+                    reported.push(recognized);
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        // Updates are only reported for gestures which were recognized, and are dropped for
+        // gestures which are still waiting or failed
+        if let Some(pan) = self.pan_changed.take() {
+            if matches!(self.state(GestureKind::Pan), Some(State::Recognized)) {
+                if pan.phase == GesturePhase::Ended || pan.phase == GesturePhase::Cancelled {
+                    self.states.remove(&GestureKind::Pan);
+                }
+                reported.push(Recognized::Pan(pan));
+            } else if pan.phase == GesturePhase::Ended {
+                self.states.remove(&GestureKind::Pan);
+            }
+        }
+        if let Some(pinch) = self.pinch_changed.take() {
+            if matches!(self.state(GestureKind::Pinch), Some(State::Recognized)) {
+                reported.push(Recognized::Pinch(pinch));
+            }
+        }
+        reported
+    }
+}
+
+impl Gestures {
+    fn call(&self, recognized: Recognized) {
+        match recognized {
+            Recognized::Pan(event) => {
+                if let Some(pan) = &self.pan {
+                    pan(&event);
+                }
+            }
+            Recognized::Pinch(event) => {
+                if let Some(pinch) = &self.pinch {
+                    pinch(&event);
+                }
+            }
+            Recognized::LongPress(pos) => {
+                if let Some((_, long_press)) = &self.long_press {
+                    long_press(pos);
+                }
+            }
+            Recognized::Swipe(direction) => {
+                if let Some(swipe) = &self.swipe {
+                    swipe(direction);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use kurbo::{Point, Size, Vec2};
+
+    use super::{GestureKind, GesturePhase, Gestures, PanEvent, SwipeDirection};
+    use crate::{
+        event::Event,
+        keyboard::ModifiersState,
+        pointer::{PointerButton, PointerPinchEvent},
+        test_support::HeadlessWindow,
+        views::{empty, Decorators},
+    };
+
+    /// How long passes between the pointer events of a drag.
+    const FRAME: Duration = Duration::from_millis(10);
+
+    fn gesture_window(gestures: Gestures) -> HeadlessWindow {
+        let mut window = HeadlessWindow::new(Size::new(200.0, 200.0), move || {
+            empty().gestures(gestures).style(|s| s.size(200.0, 200.0))
+        });
+        window.update();
+        window
+    }
+
+    fn recorder<T: 'static>() -> (Rc<RefCell<Vec<T>>>, impl Fn(T) + 'static) {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let record = {
+            let events = events.clone();
+            move |event| events.borrow_mut().push(event)
+        };
+        (events, record)
+    }
+
+    /// Presses the pointer at the first point, moves it through the others a frame apart, and
+    /// releases it at the last point after `rest`.
+    fn drag(window: &mut HeadlessWindow, points: &[(f64, f64)], rest: Duration) {
+        let point = |(x, y): (f64, f64)| Point::new(x, y);
+        window.pointer_down(point(points[0]), PointerButton::Primary);
+        for pos in &points[1..] {
+            window.advance(FRAME);
+            window.pointer_move(point(*pos));
+        }
+        window.advance(rest);
+        window.pointer_up(point(points[points.len() - 1]), PointerButton::Primary);
+    }
+
+    fn phases(events: &[PanEvent]) -> Vec<(GesturePhase, Vec2)> {
+        events
+            .iter()
+            .map(|event| (event.phase, event.translation))
+            .collect()
+    }
+
+    #[test]
+    fn pans_begin_once_the_pointer_moved() {
+        let (pans, record) = recorder();
+        let mut window = gesture_window(Gestures::new().on_pan(move |pan| record(pan.clone())));
+        // The first move is within the slop
+        drag(
+            &mut window,
+            &[(10.0, 10.0), (12.0, 10.0), (30.0, 10.0), (40.0, 20.0)],
+            FRAME,
+        );
+        assert_eq!(
+            phases(&pans.borrow()),
+            [
+                (GesturePhase::Began, Vec2::new(20.0, 0.0)),
+                (GesturePhase::Changed, Vec2::new(30.0, 10.0)),
+                (GesturePhase::Ended, Vec2::new(30.0, 10.0)),
+            ]
+        );
+        assert_eq!(pans.borrow()[0].pos, Point::new(30.0, 10.0));
+    }
+
+    #[test]
+    fn quick_drags_swipe_unless_the_pointer_rested() {
+        let (swipes, record) = recorder();
+        let mut window = gesture_window(Gestures::new().on_swipe(record));
+        drag(
+            &mut window,
+            &[(100.0, 100.0), (100.0, 140.0), (100.0, 180.0)],
+            FRAME,
+        );
+        drag(
+            &mut window,
+            &[(100.0, 100.0), (60.0, 100.0), (20.0, 100.0)],
+            FRAME,
+        );
+        assert_eq!(
+            *swipes.borrow(),
+            [SwipeDirection::Down, SwipeDirection::Left]
+        );
+
+        // Resting before the release
+        drag(
+            &mut window,
+            &[(20.0, 100.0), (60.0, 100.0), (100.0, 100.0)],
+            Duration::from_millis(200),
+        );
+        // Too short
+        drag(&mut window, &[(100.0, 100.0), (100.0, 80.0)], FRAME);
+        assert_eq!(swipes.borrow().len(), 2);
+    }
+
+    #[test]
+    fn long_presses_need_the_pointer_to_stay() {
+        let (presses, record) = recorder();
+        let mut window =
+            gesture_window(Gestures::new().on_long_press(Duration::from_millis(500), record));
+        window.pointer_down(Point::new(20.0, 20.0), PointerButton::Primary);
+        window.advance(Duration::from_millis(400));
+        assert!(presses.borrow().is_empty());
+        window.advance(Duration::from_millis(200));
+        assert_eq!(*presses.borrow(), [Point::new(20.0, 20.0)]);
+        window.pointer_up(Point::new(20.0, 20.0), PointerButton::Primary);
+
+        // Moving past the slop, or releasing early, isn't a long press
+        window.pointer_down(Point::new(20.0, 20.0), PointerButton::Primary);
+        window.advance(Duration::from_millis(100));
+        window.pointer_move(Point::new(40.0, 20.0));
+        window.advance(Duration::from_millis(600));
+        window.pointer_up(Point::new(40.0, 20.0), PointerButton::Primary);
+        window.pointer_down(Point::new(20.0, 20.0), PointerButton::Primary);
+        window.advance(Duration::from_millis(100));
+        window.pointer_up(Point::new(20.0, 20.0), PointerButton::Primary);
+        window.advance(Duration::from_millis(600));
+        assert_eq!(presses.borrow().len(), 1);
+    }
+
+    #[test]
+    fn pinches_accumulate_scale_and_rotation() {
+        let (pinches, record) = recorder();
+        let mut window =
+            gesture_window(Gestures::new().on_pinch(move |pinch| record(pinch.clone())));
+        for (scale_delta, rotation_delta, phase) in [
+            (0.5, 0.1, GesturePhase::Began),
+            (1.0, 0.2, GesturePhase::Changed),
+            (0.0, 0.0, GesturePhase::Ended),
+        ] {
+            window.send_event(Event::PointerPinch(PointerPinchEvent {
+                pos: Point::new(50.0, 50.0),
+                scale_delta,
+                rotation_delta,
+                phase,
+                modifiers: ModifiersState::empty(),
+            }));
+        }
+        let pinches: Vec<_> = pinches
+            .borrow()
+            .iter()
+            .map(|pinch| (pinch.phase, pinch.scale, (pinch.rotation * 10.0).round()))
+            .collect();
+        assert_eq!(
+            pinches,
+            [
+                (GesturePhase::Began, 1.5, 1.0),
+                (GesturePhase::Changed, 3.0, 3.0),
+                (GesturePhase::Ended, 3.0, 3.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn gestures_wait_for_their_required_failures() {
+        let (pans, record_pan) = recorder();
+        let (swipes, record_swipe) = recorder();
+        let mut window = gesture_window(
+            Gestures::new()
+                .on_pan(move |pan| record_pan(pan.clone()))
+                .on_swipe(record_swipe)
+                .require_failure(GestureKind::Pan, GestureKind::Swipe),
+        );
+
+        // The swipe is recognized, so the pan which waited for it fails
+        drag(
+            &mut window,
+            &[(10.0, 10.0), (50.0, 10.0), (90.0, 10.0)],
+            FRAME,
+        );
+        assert_eq!(*swipes.borrow(), [SwipeDirection::Right]);
+        assert!(pans.borrow().is_empty());
+
+        // The swipe fails on release, so the pan begins and ends then, without the changes it
+        // had while waiting
+        drag(
+            &mut window,
+            &[(10.0, 10.0), (30.0, 10.0), (50.0, 10.0)],
+            Duration::from_millis(200),
+        );
+        assert_eq!(swipes.borrow().len(), 1);
+        assert_eq!(
+            phases(&pans.borrow()),
+            [
+                (GesturePhase::Began, Vec2::new(20.0, 0.0)),
+                (GesturePhase::Ended, Vec2::new(40.0, 0.0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,335 @@
+//! # Keeping state across rebuilds
+//!
+//! When the code of an app is changed and the app is recompiled and restarted, or its views are
+//! rebuilt, the state of the views is usually lost: scroll positions go back to the top, text
+//! inputs are emptied and trees collapse. This module keeps opted-in state across rebuilds, so
+//! iterating on the look of a view deep in an app doesn't mean navigating back to it each time.
+//!
+//! State is opted in with [`persisted`], which creates a signal whose value is saved under a key,
+//! and with [`Scroll::persist_scroll`](crate::views::scroll::Scroll::persist_scroll) for scroll
+//! positions. Calling [`persist_to`] when the app starts loads the state saved by the last run
+//! from a file, and saves it there whenever it changes.
+//! ```rust,no_run
+//! use floem::hot_reload::{persist_to, persisted};
+//! use floem::views::{scroll, v_stack, Decorators};
+//! use floem::widgets::text_input;
+//!
+//! persist_to("target/floem_state.txt");
+//! let search = persisted("search", String::new);
+//! v_stack((
+//!     text_input(search),
+//!     scroll(v_stack(())).persist_scroll("results"),
+//! ));
+//! ```
+//!
+//! ## Matching state to views
+//!
+//! State is matched to views by key only, never by the position of the view in the tree, so the
+//! tree can change between rebuilds without state going to the wrong view. The key of a value is
+//! the keys of the enclosing [`persist_scope`]s and the key passed to [`persisted`], joined by
+//! `/`. When the signal is created, it starts with the value saved under exactly that key, and
+//! with the default when there's none, or when the saved value can't be decoded, which happens
+//! when its type changed.
+//!
+//! Views built for each item of a list should be in a [`persist_scope`] keyed by the identity of
+//! the item, like the key given to [`dyn_stack`](crate::views::dyn_stack), so their state follows
+//! the items when they're reordered. Values whose key isn't used after a rebuild are kept, so a
+//! view which is shown again later gets its state back.
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    hash::Hash,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use floem_reactive::{create_effect, create_rw_signal, RwSignal};
+use kurbo::{Point, Vec2};
+
+use crate::debounce::debounce;
+
+/// How long the state has to stay the same before it's written to the file of [`persist_to`].
+const SAVE_DELAY: Duration = Duration::from_millis(500);
+
+thread_local! {
+    static VALUES: RefCell<HashMap<String, String>> = Default::default();
+    static SCOPES: RefCell<Vec<String>> = Default::default();
+    static SAVE: RefCell<Option<Box<dyn Fn(())>>> = Default::default();
+}
+
+/// A value which can be persisted, by encoding it to a string.
+pub trait Persist: Sized {
+    fn encode(&self) -> String;
+    /// Decodes an encoded value, returning `None` if it isn't valid.
+    fn decode(encoded: &str) -> Option<Self>;
+}
+
+macro_rules! persist_from_str {
+    ($($ty:ty),*) => {
+        $(
+            impl Persist for $ty {
+                fn encode(&self) -> String {
+                    self.to_string()
+                }
+
+                fn decode(encoded: &str) -> Option<Self> {
+                    encoded.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+persist_from_str!(
+    String, bool, char, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
+
+impl Persist for Vec2 {
+    fn encode(&self) -> String {
+        format!("{},{}", self.x, self.y)
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        let (x, y) = encoded.split_once(',')?;
+        Some(Vec2::new(x.parse().ok()?, y.parse().ok()?))
+    }
+}
+
+impl Persist for Point {
+    fn encode(&self) -> String {
+        self.to_vec2().encode()
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        Vec2::decode(encoded).map(Vec2::to_point)
+    }
+}
+
+impl<T: Persist> Persist for Option<T> {
+    fn encode(&self) -> String {
+        match self {
+            Some(value) => format!("S{}", value.encode()),
+            None => "N".to_string(),
+        }
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        match encoded.strip_prefix('S') {
+            Some(value) => T::decode(value).map(Some),
+            None if encoded == "N" => Some(None),
+            None => None,
+        }
+    }
+}
+
+/// Encodes each item as its length in bytes, a `:` and the item, so items can contain anything.
+fn encode_items<'a, T: Persist + 'a>(items: impl Iterator<Item = &'a T>) -> String {
+    let mut encoded = String::new();
+    for item in items {
+        let item = item.encode();
+        encoded.push_str(&format!("{}:{item}", item.len()));
+    }
+    encoded
+}
+
+fn decode_items<T: Persist>(mut encoded: &str) -> Option<Vec<T>> {
+    let mut items = Vec::new();
+    while !encoded.is_empty() {
+        let (len, rest) = encoded.split_once(':')?;
+        let len: usize = len.parse().ok()?;
+        let item = rest.get(..len)?;
+        items.push(T::decode(item)?);
+        encoded = &rest[len..];
+    }
+    Some(items)
+}
+
+impl<T: Persist> Persist for Vec<T> {
+    fn encode(&self) -> String {
+        encode_items(self.iter())
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        decode_items(encoded)
+    }
+}
+
+impl<T: Persist + Eq + Hash> Persist for HashSet<T> {
+    fn encode(&self) -> String {
+        encode_items(self.iter())
+    }
+
+    fn decode(encoded: &str) -> Option<Self> {
+        decode_items(encoded).map(|items| items.into_iter().collect())
+    }
+}
+
+/// Runs `f` with `key` added to the keys of the values persisted in it. See the
+/// [module documentation](self#matching-state-to-views).
+pub fn persist_scope<T>(key: impl Display, f: impl FnOnce() -> T) -> T {
+    SCOPES.with(|scopes| scopes.borrow_mut().push(key.to_string()));
+    let result = f();
+    SCOPES.with(|scopes| scopes.borrow_mut().pop());
+    result
+}
+
+pub(crate) fn full_key(key: impl Display) -> String {
+    SCOPES.with(|scopes| {
+        let scopes = scopes.borrow();
+        let mut full_key = String::new();
+        for scope in scopes.iter() {
+            full_key.push_str(scope);
+            full_key.push('/');
+        }
+        full_key.push_str(&key.to_string());
+        full_key
+    })
+}
+
+/// Creates a signal starting with the value saved under `key` by a previous build of the views,
+/// or with `default` if there's none, and saves its value under `key` whenever it changes. See
+/// the [module documentation](self#matching-state-to-views).
+pub fn persisted<T: Persist + 'static>(
+    key: impl Display,
+    default: impl FnOnce() -> T,
+) -> RwSignal<T> {
+    let key = full_key(key);
+    let value = load_value(&key).unwrap_or_else(default);
+    let signal = create_rw_signal(value);
+    create_effect(move |_| {
+        let encoded = signal.with(Persist::encode);
+        store(key.clone(), encoded);
+    });
+    signal
+}
+
+/// The value saved under the full `key`.
+pub(crate) fn load_value<T: Persist>(key: &str) -> Option<T> {
+    VALUES.with(|values| values.borrow().get(key).and_then(|value| T::decode(value)))
+}
+
+/// Saves `value` under the full `key`.
+pub(crate) fn store_value<T: Persist>(key: String, value: &T) {
+    store(key, value.encode());
+}
+
+fn store(key: String, encoded: String) {
+    let changed = VALUES
+        .with(|values| values.borrow_mut().insert(key, encoded.clone()).as_ref() != Some(&encoded));
+    if changed {
+        SAVE.with(|save| {
+            if let Some(save) = save.borrow().as_ref() {
+                save(());
+            }
+        });
+    }
+}
+
+/// Loads the state saved in the file at `path`, and saves the state there whenever it changes.
+/// It should be called before the views are built, when the app starts.
+///
+/// Failing to load or save the state doesn't stop the app, the errors are logged as warnings.
+pub fn persist_to(path: impl Into<PathBuf>) {
+    let path = path.into();
+    match load(&path) {
+        Ok(()) => {}
+        // The file doesn't exist on the first run
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+        Err(err) => log::warn!("failed to load state from {}: {err}", path.display()),
+    }
+    let save_path = path.clone();
+    let save_later = debounce(SAVE_DELAY, move |()| {
+        if let Err(err) = save(&save_path) {
+            log::warn!("failed to save state to {}: {err}", save_path.display());
+        }
+    });
+    SAVE.with(|save| *save.borrow_mut() = Some(Box::new(save_later)));
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+/// Adds the state saved in the file at `path` to the current state.
+pub fn load(path: &Path) -> io::Result<()> {
+    let content = fs::read_to_string(path)?;
+    VALUES.with(|values| {
+        let mut values = values.borrow_mut();
+        for line in content.lines() {
+            if let Some((key, value)) = line.split_once('\t') {
+                values.insert(unescape(key), unescape(value));
+            }
+        }
+    });
+    Ok(())
+}
+
+/// Saves the current state to the file at `path`, with one line per value.
+pub fn save(path: &Path) -> io::Result<()> {
+    let content = VALUES.with(|values| {
+        let values = values.borrow();
+        let mut keys: Vec<&String> = values.keys().collect();
+        keys.sort();
+        keys.into_iter()
+            .map(|key| format!("{}\t{}\n", escape(key), escape(&values[key])))
+            .collect::<String>()
+    });
+    fs::write(path, content)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{escape, unescape, Persist};
+
+    #[test]
+    fn collections_round_trip() {
+        let items = vec!["a:b".to_string(), String::new(), "2:x".to_string()];
+        assert_eq!(Vec::<String>::decode(&items.encode()), Some(items));
+
+        let expanded: HashSet<u32> = [1, 20, 300].into_iter().collect();
+        assert_eq!(HashSet::<u32>::decode(&expanded.encode()), Some(expanded));
+
+        assert_eq!(
+            Option::<String>::decode(&Some(String::new()).encode()),
+            Some(Some(String::new()))
+        );
+        assert_eq!(
+            Option::<String>::decode(&None::<String>.encode()),
+            Some(None)
+        );
+        assert_eq!(Vec::<u32>::decode("3:ab"), None);
+    }
+
+    #[test]
+    fn escaping_round_trips() {
+        let text = "a\tb\nc\\d\\n";
+        assert!(!escape(text).contains(['\t', '\n']));
+        assert_eq!(unescape(&escape(text)), text);
+    }
+}
@@ -0,0 +1,297 @@
+//! # OS-level hotkeys
+//!
+//! Unlike key listeners on views, OS hotkeys are received while the app doesn't have focus,
+//! for example to show a quick-capture window from anywhere.
+//!
+//! OS hotkeys need the `global_hotkey` feature and are supported on Windows, macOS and Linux
+//! with X11. Registering fails with [`HotkeyError::Os`] where the platform has no support,
+//! such as on Wayland.
+//! ```rust,no_run
+//! use floem::hotkey::{register_os_hotkey, Hotkey};
+//! use floem::keyboard::{KeyCode, ModifiersState};
+//!
+//! let hotkey = Hotkey::new(ModifiersState::CONTROL | ModifiersState::SHIFT, KeyCode::Space);
+//! let handle = register_os_hotkey(hotkey, || println!("pressed")).unwrap();
+//! // The hotkey is unregistered when `handle` is dropped.
+//! ```
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+};
+
+use floem_reactive::Scope;
+use global_hotkey::{
+    hotkey::{Code, HotKey, Modifiers},
+    GlobalHotKeyEvent, GlobalHotKeyManager,
+};
+
+use crate::{
+    ext_event::create_signal_from_channel,
+    keyboard::{KeyCode, ModifiersState},
+};
+
+thread_local! {
+    static MANAGER: RefCell<Option<GlobalHotKeyManager>> = const { RefCell::new(None) };
+    static HOTKEY_ACTIONS: RefCell<HashMap<u32, Rc<dyn Fn()>>> = Default::default();
+    static LISTENING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A key pressed together with modifiers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl Hotkey {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    fn platform_hotkey(&self) -> Result<HotKey, HotkeyError> {
+        let mut modifiers = Modifiers::empty();
+        if self.modifiers.shift_key() {
+            modifiers |= Modifiers::SHIFT;
+        }
+        if self.modifiers.control_key() {
+            modifiers |= Modifiers::CONTROL;
+        }
+        if self.modifiers.alt_key() {
+            modifiers |= Modifiers::ALT;
+        }
+        if self.modifiers.super_key() {
+            modifiers |= Modifiers::SUPER;
+        }
+        let code = platform_code(self.key).ok_or(HotkeyError::UnsupportedKey(self.key))?;
+        Ok(HotKey::new(Some(modifiers), code))
+    }
+}
+
+/// An error registering an OS hotkey.
+#[derive(Debug)]
+pub enum HotkeyError {
+    /// The hotkey is already registered, by this app or by another one.
+    AlreadyRegistered(Hotkey),
+    /// The key can't be used for OS hotkeys.
+    UnsupportedKey(KeyCode),
+    /// The OS refused to register the hotkey.
+    Os(String),
+}
+
+impl fmt::Display for HotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyError::AlreadyRegistered(hotkey) => {
+                write!(f, "the hotkey {hotkey:?} is already registered")
+            }
+            HotkeyError::UnsupportedKey(key) => {
+                write!(f, "the key {key:?} can't be used for hotkeys")
+            }
+            HotkeyError::Os(err) => write!(f, "failed to register the hotkey: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyError {}
+
+/// A registered OS hotkey. The hotkey is unregistered when this is dropped.
+pub struct HotkeyHandle {
+    hotkey: HotKey,
+}
+
+impl HotkeyHandle {
+    /// Unregisters the hotkey.
+    pub fn unregister(self) {}
+}
+
+impl Drop for HotkeyHandle {
+    fn drop(&mut self) {
+        HOTKEY_ACTIONS.with(|actions| actions.borrow_mut().remove(&self.hotkey.id()));
+        MANAGER.with(|manager| {
+            if let Some(manager) = manager.borrow().as_ref() {
+                let _ = manager.unregister(self.hotkey);
+            }
+        });
+    }
+}
+
+/// Registers a hotkey with the OS which calls `action` when it's pressed, even when the app
+/// doesn't have focus.
+///
+/// Returns [`HotkeyError::AlreadyRegistered`] if the hotkey is taken, either by this app or
+/// by another one. This must be called after the application is launched.
+pub fn register_os_hotkey(
+    hotkey: Hotkey,
+    action: impl Fn() + 'static,
+) -> Result<HotkeyHandle, HotkeyError> {
+    let platform_hotkey = hotkey.platform_hotkey()?;
+    if HOTKEY_ACTIONS.with(|actions| actions.borrow().contains_key(&platform_hotkey.id())) {
+        return Err(HotkeyError::AlreadyRegistered(hotkey));
+    }
+
+    MANAGER.with(|manager| {
+        let mut manager = manager.borrow_mut();
+        if manager.is_none() {
+            *manager =
+                Some(GlobalHotKeyManager::new().map_err(|err| HotkeyError::Os(err.to_string()))?);
+        }
+        manager
+            .as_ref()
+            .unwrap()
+            .register(platform_hotkey)
+            .map_err(|err| match err {
+                global_hotkey::Error::AlreadyRegistered(_) => {
+                    HotkeyError::AlreadyRegistered(hotkey)
+                }
+                err => HotkeyError::Os(err.to_string()),
+            })
+    })?;
+
+    listen();
+    HOTKEY_ACTIONS.with(|actions| {
+        actions
+            .borrow_mut()
+            .insert(platform_hotkey.id(), Rc::new(action))
+    });
+    Ok(HotkeyHandle {
+        hotkey: platform_hotkey,
+    })
+}
+
+/// Forwards the hotkey events, which are sent from the platform event handlers,
+/// to the actions on the main thread.
+fn listen() {
+    if LISTENING.with(|listening| listening.replace(true)) {
+        return;
+    }
+
+    let events = create_signal_from_channel(GlobalHotKeyEvent::receiver().clone());
+    Scope::new().create_effect(move |_| {
+        let action = events.with(|event| {
+            let event = event.as_ref()?;
+            HOTKEY_ACTIONS.with(|actions| actions.borrow().get(&event.id).cloned())
+        });
+        if let Some(action) = action {
+            action();
+        }
+    });
+}
+
+fn platform_code(key: KeyCode) -> Option<Code> {
+    macro_rules! codes {
+        ($($code:ident),* $(,)?) => {
+            match key {
+                $(KeyCode::$code => Some(Code::$code),)*
+                _ => None,
+            }
+        };
+    }
+    codes!(
+        KeyA,
+        KeyB,
+        KeyC,
+        KeyD,
+        KeyE,
+        KeyF,
+        KeyG,
+        KeyH,
+        KeyI,
+        KeyJ,
+        KeyK,
+        KeyL,
+        KeyM,
+        KeyN,
+        KeyO,
+        KeyP,
+        KeyQ,
+        KeyR,
+        KeyS,
+        KeyT,
+        KeyU,
+        KeyV,
+        KeyW,
+        KeyX,
+        KeyY,
+        KeyZ,
+        Digit0,
+        Digit1,
+        Digit2,
+        Digit3,
+        Digit4,
+        Digit5,
+        Digit6,
+        Digit7,
+        Digit8,
+        Digit9,
+        F1,
+        F2,
+        F3,
+        F4,
+        F5,
+        F6,
+        F7,
+        F8,
+        F9,
+        F10,
+        F11,
+        F12,
+        F13,
+        F14,
+        F15,
+        F16,
+        F17,
+        F18,
+        F19,
+        F20,
+        F21,
+        F22,
+        F23,
+        F24,
+        Space,
+        Enter,
+        Escape,
+        Tab,
+        Backspace,
+        Delete,
+        Insert,
+        Home,
+        End,
+        PageUp,
+        PageDown,
+        ArrowUp,
+        ArrowDown,
+        ArrowLeft,
+        ArrowRight,
+        Minus,
+        Equal,
+        BracketLeft,
+        BracketRight,
+        Backslash,
+        Semicolon,
+        Quote,
+        Backquote,
+        Comma,
+        Period,
+        Slash,
+        Numpad0,
+        Numpad1,
+        Numpad2,
+        Numpad3,
+        Numpad4,
+        Numpad5,
+        Numpad6,
+        Numpad7,
+        Numpad8,
+        Numpad9,
+        NumpadAdd,
+        NumpadSubtract,
+        NumpadMultiply,
+        NumpadDivide,
+        NumpadDecimal,
+        NumpadEnter,
+        PrintScreen,
+    )
+}
@@ -7,28 +7,141 @@
 //!
 //! These ids are assigned via the [ViewContext](crate::ViewContext) and are unique across the entire application.
 //!
+//! ## Deterministic ids
+//!
+//! Ids normally come from a counter, so they depend on everything built before a view, and
+//! differ between runs. Ids allocated in a [`with_id_scope`] are instead derived from the keys
+//! of the enclosing scopes and the order the ids are allocated in within the innermost scope,
+//! so building the same views in the same scopes gives the same ids in every run of the app.
+//! This keeps ids in snapshots and saved state stable.
+//!
+//! Views built later, like the items of a [`dyn_stack`](crate::views::dyn_stack), aren't built
+//! in the scope their parent was built in, so they should be built in their own scope, keyed
+//! by the identity of the item. If an id derived this way is already used by a view, such as
+//! when two scopes in the same parent scope have the same key, the collision is logged as a
+//! warning and a unique id from the counter is used instead.
+//!
 
-use std::{any::Any, cell::RefCell, collections::HashMap, sync::atomic::AtomicU64};
+use std::{
+    any::Any,
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt,
+    hash::{Hash, Hasher},
+    sync::atomic::AtomicU64,
+};
 
-use kurbo::{Point, Rect};
+use floem_reactive::Scope;
+use image::RgbaImage;
+use kurbo::{Point, Rect, Size};
 
 use crate::{
     animate::Animation,
+    app::is_ui_thread,
     context::{EventCallback, MenuCallback, ResizeCallback},
-    event::EventListener,
-    style::{Style, StyleClassRef, StyleSelector},
+    event::{EventListener, FileDropEvent},
+    ext_event::AppProxy,
+    pointer::{PointerWheelEvent, TouchEvent},
+    style::{Display, Style, StyleClassRef, StyleSelector},
     update::{UpdateMessage, CENTRAL_DEFERRED_UPDATE_MESSAGES, CENTRAL_UPDATE_MESSAGES},
+    view::View,
     view_data::{ChangeFlags, StackOffset},
+    views::scroll::{scroll_state, ScrollState},
 };
 
 thread_local! {
     pub(crate) static ID_PATHS: RefCell<HashMap<Id,IdPath>> = Default::default();
+    static VIEW_SCOPES: RefCell<HashMap<Id, Scope>> = Default::default();
+    /// How much of each view was visible in the last layout.
+    static VISIBLE_FRACTIONS: RefCell<HashMap<Id, f64>> = Default::default();
+    #[cfg(any(debug_assertions, feature = "debug_names"))]
+    static DEBUG_INFO: RefCell<HashMap<Id, DebugInfo>> = Default::default();
+    /// Whether layouts record the debug info of the views, see [`record_debug_tree`].
+    #[cfg(any(debug_assertions, feature = "debug_names"))]
+    static RECORD_DEBUG_TREE: std::cell::Cell<bool> = const { std::cell::Cell::new(false) };
+    /// The hashes of the keys of the enclosing [`with_id_scope`]s, with the number of ids
+    /// allocated in each.
+    static ID_SCOPES: RefCell<Vec<(u64, u64)>> = Default::default();
+    /// The ids allocated in id scopes which are in use, to detect collisions.
+    static SCOPED_IDS: RefCell<HashSet<u64>> = Default::default();
+}
+
+/// Set on the ids allocated in id scopes, so they never collide with the ids of the counter.
+const SCOPED_ID_BIT: u64 = 1 << 63;
+
+/// Runs `f` with the ids allocated in it derived from `key` and the keys of the enclosing
+/// scopes, so they're the same in every run. See the
+/// [module documentation](self#deterministic-ids).
+pub fn with_id_scope<T>(key: impl Hash, f: impl FnOnce() -> T) -> T {
+    let parent = ID_SCOPES.with(|scopes| scopes.borrow().last().map(|(scope, _)| *scope));
+    let mut hasher = DefaultHasher::new();
+    parent.hash(&mut hasher);
+    key.hash(&mut hasher);
+    let scope = hasher.finish();
+    ID_SCOPES.with(|scopes| scopes.borrow_mut().push((scope, 0)));
+    let result = f();
+    ID_SCOPES.with(|scopes| scopes.borrow_mut().pop());
+    result
+}
+
+/// Sets whether each layout records the type, layout rect and children of the views, which
+/// [`Id::debug_tree_string`] shows. It's off until the first call to `debug_tree_string`, so
+/// turn it on before the views are laid out to see them in the first dump, like in a test or
+/// before installing a panic hook which prints the tree. Headless windows turn it on.
+///
+/// The debug info is only recorded in debug builds, or with the `debug_names` feature.
+/// Otherwise this does nothing.
+pub fn record_debug_tree(record: bool) {
+    #[cfg(any(debug_assertions, feature = "debug_names"))]
+    RECORD_DEBUG_TREE.with(|enabled| enabled.set(record));
+    #[cfg(not(any(debug_assertions, feature = "debug_names")))]
+    let _ = record;
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Hash)]
+/// What [`Id::debug_tree_string`] shows about a view, other than its id.
+#[cfg(any(debug_assertions, feature = "debug_names"))]
+#[derive(Default)]
+struct DebugInfo {
+    name: Option<String>,
+    kind: std::borrow::Cow<'static, str>,
+    layout_rect: Rect,
+    /// The children of the view in the order it has them, which ids don't follow.
+    children: Vec<Id>,
+}
+
+/// The error of [`Id::try_update_state`] when it's called off the UI thread, which contains
+/// the state which wasn't sent.
+pub struct OffUiThread<S>(pub S);
+
+impl<S> fmt::Debug for OffUiThread<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("OffUiThread(..)")
+    }
+}
+
+impl<S> fmt::Display for OffUiThread<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("view state can only be updated on the UI thread")
+    }
+}
+
+impl<S> std::error::Error for OffUiThread<S> {}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
 /// A stable identifier for an element.
 pub struct Id(u64);
 
+impl fmt::Debug for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut t = f.debug_tuple("Id");
+        t.field(&self.0);
+        if let Some(name) = self.name() {
+            t.field(&name);
+        }
+        t.finish()
+    }
+}
+
 #[derive(Clone, Default, Debug)]
 pub struct IdPath(pub(crate) Vec<Id>);
 
@@ -40,12 +153,38 @@ impl IdPath {
 }
 
 impl Id {
-    /// Allocate a new, unique `Id`.
+    /// Allocate a new, unique `Id`. In a [`with_id_scope`], the id is derived from the scope.
     pub fn next() -> Id {
         static WIDGET_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+        if let Some(id) = Self::next_scoped() {
+            return id;
+        }
         Id(WIDGET_ID_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
     }
 
+    /// Derives the next id of the innermost id scope, if there's one and the id isn't in use.
+    fn next_scoped() -> Option<Id> {
+        let (scope, index) = ID_SCOPES.with(|scopes| {
+            let mut scopes = scopes.borrow_mut();
+            let (scope, count) = scopes.last_mut()?;
+            *count += 1;
+            Some((*scope, *count))
+        })?;
+        let mut hasher = DefaultHasher::new();
+        (scope, index).hash(&mut hasher);
+        let id = hasher.finish() | SCOPED_ID_BIT;
+        if SCOPED_IDS.with(|ids| ids.borrow_mut().insert(id)) {
+            Some(Id(id))
+        } else {
+            log::warn!(
+                "the id {id} derived from an id scope is already used by another view, \
+                 probably because of two id scopes with the same key in the same parent scope, \
+                 so a unique id is used instead"
+            );
+            None
+        }
+    }
+
     pub fn to_raw(self) -> u64 {
         self.0
     }
@@ -100,6 +239,137 @@ impl Id {
 
     pub fn remove_id_path(&self) {
         ID_PATHS.with(|id_paths| id_paths.borrow_mut().remove(self));
+        if self.0 & SCOPED_ID_BIT != 0 {
+            SCOPED_IDS.with(|ids| ids.borrow_mut().remove(&self.0));
+        }
+    }
+
+    /// Sets a label for the view, which is shown by the inspector, by
+    /// [`Id::debug_tree_string`] and in the `Debug` output of the id.
+    ///
+    /// Labels are only kept in debug builds, or with the `debug_names` feature. Otherwise this
+    /// does nothing.
+    pub fn set_name(&self, name: impl Into<String>) {
+        #[cfg(any(debug_assertions, feature = "debug_names"))]
+        DEBUG_INFO
+            .with(|info| info.borrow_mut().entry(*self).or_default().name = Some(name.into()));
+        #[cfg(not(any(debug_assertions, feature = "debug_names")))]
+        let _ = name;
+    }
+
+    /// The label set with [`Id::set_name`].
+    pub fn name(&self) -> Option<String> {
+        #[cfg(any(debug_assertions, feature = "debug_names"))]
+        {
+            DEBUG_INFO
+                .try_with(|info| info.try_borrow().ok()?.get(self)?.name.clone())
+                .ok()
+                .flatten()
+        }
+        #[cfg(not(any(debug_assertions, feature = "debug_names")))]
+        None
+    }
+
+    /// Records the type, layout and children of the view for [`Id::debug_tree_string`].
+    pub(crate) fn record_debug_info(&self, view: &dyn View, layout_rect: Rect) {
+        #[cfg(any(debug_assertions, feature = "debug_names"))]
+        if RECORD_DEBUG_TREE.with(|record| record.get()) {
+            DEBUG_INFO.with(|info| {
+                let mut info = info.borrow_mut();
+                let info = info.entry(*self).or_default();
+                if info.kind.is_empty() {
+                    info.kind = view.debug_name();
+                }
+                info.layout_rect = layout_rect;
+                info.children.clear();
+                view.for_each_child(&mut |child| {
+                    info.children.push(child.id());
+                    false
+                });
+            });
+        }
+        #[cfg(not(any(debug_assertions, feature = "debug_names")))]
+        let _ = (view, layout_rect);
+    }
+
+    pub(crate) fn remove_debug_info(&self) {
+        #[cfg(any(debug_assertions, feature = "debug_names"))]
+        DEBUG_INFO.with(|info| info.borrow_mut().remove(self));
+    }
+
+    /// Returns the subtree of views starting at this one, with a line per view indented by its
+    /// depth. Call it on [`Id::root_id`] to get the whole window.
+    ///
+    /// Each line has the id and label of the view, followed by its type and layout rect as of
+    /// the last layout. The type and layout are only recorded in debug builds, or with the
+    /// `debug_names` feature, once [`record_debug_tree`] turned it on, which this does for the
+    /// next layouts.
+    ///
+    /// This doesn't panic when the view tree is being updated, so it can be called from a panic
+    /// hook.
+    pub fn debug_tree_string(&self) -> String {
+        use std::fmt::Write;
+
+        record_debug_tree(true);
+
+        let mut children: HashMap<Id, Vec<Id>> = HashMap::new();
+        let _ = ID_PATHS.try_with(|id_paths| {
+            if let Ok(id_paths) = id_paths.try_borrow() {
+                for path in id_paths.values() {
+                    if let [.., parent, id] = path.0[..] {
+                        children.entry(parent).or_default().push(id);
+                    }
+                }
+            }
+        });
+
+        let mut out = String::new();
+        let mut stack = vec![(*self, 0)];
+        while let Some((id, depth)) = stack.pop() {
+            let _ = write!(out, "{:width$}{}", "", id.0, width = depth * 2);
+            #[cfg(any(debug_assertions, feature = "debug_names"))]
+            let _ = DEBUG_INFO.try_with(|info| {
+                let Ok(info) = info.try_borrow() else { return };
+                if let Some(info) = info.get(&id) {
+                    if let Some(name) = &info.name {
+                        let _ = write!(out, " {name:?}");
+                    }
+                    let r = info.layout_rect;
+                    let _ = write!(
+                        out,
+                        " {} ({}, {}) - ({}, {})",
+                        info.kind, r.x0, r.y0, r.x1, r.y1
+                    );
+                }
+            });
+            out.push('\n');
+
+            if let Some(children) = children.get_mut(&id) {
+                // Children the view doesn't list, which weren't laid out yet, follow in the order
+                // of their ids
+                let order = id.debug_children();
+                children.sort_unstable_by_key(|child| {
+                    let index = order.iter().position(|listed| listed == child);
+                    (index.unwrap_or(usize::MAX), child.0)
+                });
+                stack.extend(children.iter().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+        out
+    }
+
+    /// The children of the view as of the last layout, in its order.
+    fn debug_children(&self) -> Vec<Id> {
+        #[cfg(any(debug_assertions, feature = "debug_names"))]
+        {
+            DEBUG_INFO
+                .try_with(|info| Some(info.try_borrow().ok()?.get(self)?.children.clone()))
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+        }
+        #[cfg(not(any(debug_assertions, feature = "debug_names")))]
+        Vec::new()
     }
 
     pub fn root_id(&self) -> Option<Id> {
@@ -115,6 +385,29 @@ impl Id {
         self.add_update_message(UpdateMessage::Focus(*self));
     }
 
+    /// Focuses the view once it's mounted, see [`Id::update_mount_listener`], or right away
+    /// if it's already mounted. Unlike [`Id::request_focus`], this works for views which aren't
+    /// in the view tree yet, like the input of a dialog which is being built.
+    ///
+    /// Nothing happens if another view was focused before this one is mounted, so the focus
+    /// isn't taken away from a view the user already moved to. Use [`Id::force_autofocus`]
+    /// to focus the view anyway.
+    pub fn autofocus(&self) {
+        self.add_update_message(UpdateMessage::Autofocus {
+            id: *self,
+            force: false,
+        });
+    }
+
+    /// Focuses the view once it's mounted, even if another view was focused in the meantime.
+    /// See [`Id::autofocus`].
+    pub fn force_autofocus(&self) {
+        self.add_update_message(UpdateMessage::Autofocus {
+            id: *self,
+            force: true,
+        });
+    }
+
     pub fn request_active(&self) {
         self.add_update_message(UpdateMessage::Active(*self));
     }
@@ -126,8 +419,33 @@ impl Id {
         });
     }
 
+    /// Sets whether the view is checked, like a checkbox or a toggle button, which the
+    /// `checked` style selector matches.
+    pub fn update_checked(&self, is_checked: bool) {
+        self.add_update_message(UpdateMessage::Checked {
+            id: *self,
+            is_checked,
+        });
+    }
+
+    /// Sets how the view is displayed, overriding the display of its style. With
+    /// [`Display::None`] the view is removed from the layout and isn't painted, but unlike
+    /// views which aren't built, it keeps its state and the state of its children, so it can
+    /// be shown again quickly with another display.
+    pub fn set_display(&self, display: Display) {
+        self.add_update_message(UpdateMessage::Display { id: *self, display });
+    }
+
+    /// Puts the view above its siblings with the same z-index, so it's painted over them and
+    /// gets the events over them first. The view stays in front until one of its siblings is
+    /// brought to the front, which is useful for floating panels which come to the front when
+    /// they're clicked.
+    pub fn bring_to_front(&self) {
+        self.add_update_message(UpdateMessage::BringToFront { id: *self });
+    }
+
     pub fn request_paint(&self) {
-        self.add_update_message(UpdateMessage::RequestPaint);
+        self.add_update_message(UpdateMessage::RequestPaint { id: *self });
     }
 
     pub fn request_layout(&self) {
@@ -137,11 +455,42 @@ impl Id {
         });
     }
 
+    /// Sends `state` to the view, which receives it in [`View::update`](crate::view::View::update)
+    /// before the next frame.
+    ///
+    /// This must be called on the UI thread, as the update messages are stored per thread and
+    /// would never reach the view otherwise. Use [`Id::try_update_state`] to check this, or
+    /// [`Id::send_state`] to send from any thread.
     pub fn update_state(&self, state: impl Any) {
+        debug_assert!(
+            is_ui_thread(),
+            "Id::update_state was called off the UI thread, use Id::send_state instead"
+        );
+        self.add_update_message(UpdateMessage::State {
+            id: *self,
+            state: Box::new(state),
+        });
+    }
+
+    /// Like [`Id::update_state`], but returns the state back in an error instead of
+    /// sending it when called off the UI thread.
+    pub fn try_update_state<S: Any>(&self, state: S) -> Result<(), OffUiThread<S>> {
+        if !is_ui_thread() {
+            return Err(OffUiThread(state));
+        }
         self.add_update_message(UpdateMessage::State {
             id: *self,
             state: Box::new(state),
         });
+        Ok(())
+    }
+
+    /// Sends `state` to the view from any thread. On the UI thread this is the same as
+    /// [`Id::update_state`], and on other threads it goes through an [`AppProxy`].
+    pub fn send_state(&self, state: impl Any + Send) {
+        if let Err(OffUiThread(state)) = self.try_update_state(state) {
+            AppProxy::new().update(*self, state);
+        }
     }
 
     pub fn update_state_deferred(&self, state: impl Any) {
@@ -162,6 +511,28 @@ impl Id {
         self.add_update_message(UpdateMessage::Class { id: *self, class });
     }
 
+    /// Sets the minimum size of the view, on top of its style. This takes precedence over the
+    /// `min_width` and `min_height` of the style, and is limited by its `width` and `height` like
+    /// they are. A dimension of `0.0` leaves the minimum of the style in place.
+    ///
+    /// This is meant for adjusting a view from an event handler, like clamping a panel while it's
+    /// resized.
+    pub fn set_min_size(&self, width: f64, height: f64) {
+        self.add_update_message(UpdateMessage::MinSize {
+            id: *self,
+            size: Size::new(width, height),
+        });
+    }
+
+    /// Sets the maximum size of the view, on top of its style, like [`Id::set_min_size`]. A
+    /// dimension of `f64::INFINITY` leaves the maximum of the style in place.
+    pub fn set_max_size(&self, width: f64, height: f64) {
+        self.add_update_message(UpdateMessage::MaxSize {
+            id: *self,
+            size: Size::new(width, height),
+        });
+    }
+
     pub(crate) fn update_style_selector(&self, style: Style, selector: StyleSelector) {
         self.add_update_message(UpdateMessage::StyleSelector {
             id: *self,
@@ -178,6 +549,35 @@ impl Id {
         self.add_update_message(UpdateMessage::Draggable { id: *self });
     }
 
+    /// A reactive scope owned by this view. It's disposed, together with the signals and effects
+    /// created under it, when the view is removed or its window is closed.
+    ///
+    /// Event listeners and other callbacks don't run under the scope of the view, so signals and
+    /// effects created in them live until the scope that was current when they were created is
+    /// disposed. Create them under this scope to tie them to the view instead:
+    /// ```rust
+    /// # use floem::reactive::create_rw_signal;
+    /// # use floem::views::{empty, Decorators};
+    /// # use floem::view::View;
+    /// let counter = create_rw_signal(0);
+    /// let view = empty();
+    /// let id = view.id();
+    /// view.on_click_stop(move |_| {
+    ///     id.scope().create_effect(move |_| println!("{}", counter.get()));
+    /// });
+    /// ```
+    pub fn scope(&self) -> Scope {
+        VIEW_SCOPES.with(|scopes| *scopes.borrow_mut().entry(*self).or_default())
+    }
+
+    /// Disposes the scope returned by [`Id::scope`], if it was created.
+    pub(crate) fn dispose_scope(&self) {
+        if let Some(scope) = VIEW_SCOPES.with(|scopes| scopes.borrow_mut().remove(self)) {
+            scope.dispose();
+        }
+    }
+
+    /// Signals and effects created in `action` aren't owned by this view, see [`Id::scope`].
     pub fn update_event_listener(&self, listener: EventListener, action: Box<EventCallback>) {
         self.add_update_message(UpdateMessage::EventListener {
             id: *self,
@@ -186,18 +586,149 @@ impl Id {
         });
     }
 
+    /// Sets a listener which gets events in the capture phase, before the descendants of the
+    /// view, while listeners set with [`Id::update_event_listener`] get them in the bubble
+    /// phase, after the descendants. An event is first captured from the root down to the
+    /// view it's for, and then bubbles back up to the root, so returning
+    /// [`EventPropagation::Stop`](crate::EventPropagation::Stop) from a capture listener keeps
+    /// the event from the descendants, like a modal overlay swallowing clicks.
+    ///
+    /// Signals and effects created in `action` aren't owned by this view, see [`Id::scope`].
+    pub fn update_capture_listener(&self, listener: EventListener, action: Box<EventCallback>) {
+        self.add_update_message(UpdateMessage::CaptureListener {
+            id: *self,
+            listener,
+            action,
+        });
+    }
+
     pub fn update_resize_listener(&self, action: Box<ResizeCallback>) {
         self.add_update_message(UpdateMessage::ResizeListener { id: *self, action });
     }
 
+    /// Sets a function which is called with the size the content of the view takes when it
+    /// changes, which is the size the view would have to be to fit its children without
+    /// overflowing, including its padding and borders, while a
+    /// [resize listener](Id::update_resize_listener) gets the size the view is given. The two
+    /// differ when the view is made smaller or larger than its content, like a popup with a
+    /// maximum height, which overflows when its content is taller. A view without children,
+    /// like an [`empty`](crate::views::empty) view, takes its own size.
+    ///
+    /// The size is only known after layout, so it's called after the resize listener of the
+    /// view, once its children are laid out, and changes made in it are applied in the next
+    /// frame. It isn't called while the view is hidden.
+    pub fn update_size_listener(&self, action: Box<dyn Fn(Size)>) {
+        self.add_update_message(UpdateMessage::SizeListener { id: *self, action });
+    }
+
     pub fn update_move_listener(&self, action: Box<dyn Fn(Point)>) {
         self.add_update_message(UpdateMessage::MoveListener { id: *self, action });
     }
 
+    /// Sets a function which is called with `true` when the view is scrolled into view, and
+    /// with `false` when it's scrolled out of view, like an `IntersectionObserver`.
+    ///
+    /// The view counts as visible when at least `threshold` of it is visible, from 0.0 for any
+    /// part of it to 1.0 for all of it. See [`Id::visible_fraction`].
+    pub fn update_visibility_listener(&self, threshold: f64, action: Box<dyn Fn(bool)>) {
+        self.add_update_message(UpdateMessage::VisibilityListener {
+            id: *self,
+            threshold,
+            action,
+        });
+    }
+
+    /// Sets a function which is called with the visible fraction of the view when it crosses
+    /// one of `thresholds`, like an `IntersectionObserver`, and after the view is first laid
+    /// out. A threshold of 0.5 is crossed when the view becomes half visible, or stops being
+    /// half visible.
+    ///
+    /// The visible fraction accounts for the viewports of all the scroll views the view is in,
+    /// and for the bounds of its other ancestors. See [`Id::visible_fraction`].
+    pub fn update_intersection_listener(&self, thresholds: Vec<f64>, action: Box<dyn Fn(f64)>) {
+        self.add_update_message(UpdateMessage::IntersectionListener {
+            id: *self,
+            thresholds,
+            action,
+        });
+    }
+
+    /// How much of the view is visible, from 0.0 to 1.0, inside the viewports of the scroll
+    /// views it's in and the window. This is as of the last layout, and 0.0 for hidden views
+    /// and views which weren't laid out yet.
+    pub fn visible_fraction(&self) -> f64 {
+        VISIBLE_FRACTIONS.with(|fractions| fractions.borrow().get(self).copied().unwrap_or(0.0))
+    }
+
+    /// Whether any part of the view is visible, see [`Id::visible_fraction`].
+    pub fn is_visible_in_viewport(&self) -> bool {
+        self.visible_fraction() > 0.0
+    }
+
+    pub(crate) fn record_visible_fraction(&self, visible_fraction: f64) {
+        VISIBLE_FRACTIONS.with(|fractions| fractions.borrow_mut().insert(*self, visible_fraction));
+    }
+
+    pub(crate) fn remove_visible_fraction(&self) {
+        VISIBLE_FRACTIONS.with(|fractions| fractions.borrow_mut().remove(self));
+    }
+
+    /// Sets a function which is called when files are dragged from outside the app over this
+    /// view and dropped on it.
+    pub fn update_file_drop_listener(&self, action: Box<dyn Fn(FileDropEvent)>) {
+        self.add_update_message(UpdateMessage::FileDropListener { id: *self, action });
+    }
+
     pub fn update_cleanup_listener(&self, action: Box<dyn Fn()>) {
         self.add_update_message(UpdateMessage::CleanupListener { id: *self, action });
     }
 
+    /// Sets a function which is called when the view is mounted, which is after it's first
+    /// laid out, so its layout is known. The view is unmounted when it or one of its ancestors
+    /// is hidden, and mounted again when it's laid out after being shown.
+    pub fn update_mount_listener(&self, action: Box<dyn Fn()>) {
+        self.add_update_message(UpdateMessage::MountListener { id: *self, action });
+    }
+
+    /// Sets a function which is called when the view is unmounted, which is when it or one of
+    /// its ancestors is hidden after it was mounted, or when it's removed. See
+    /// [`Id::update_mount_listener`].
+    pub fn update_unmount_listener(&self, action: Box<dyn Fn()>) {
+        self.add_update_message(UpdateMessage::UnmountListener { id: *self, action });
+    }
+
+    /// Sets a function which is called with the touches on the view. Each finger is a separate
+    /// touch with its own id, so several touches can be followed at once. Once the view gets
+    /// the beginning of a touch, it gets the rest of it too, even when the finger moves off the
+    /// view, and other views don't.
+    pub fn update_touch_listener(&self, action: Box<dyn Fn(&TouchEvent)>) {
+        self.add_update_message(UpdateMessage::TouchListener { id: *self, action });
+    }
+
+    /// Sets a function which is called with the wheel events over the view before they're
+    /// dispatched to its children, so it gets them before the scroll views in it. Returning
+    /// `true` handles the event, which then isn't dispatched further, so nothing scrolls.
+    ///
+    /// This is useful to zoom a canvas when Ctrl is held, for example, by checking the
+    /// [`PointerWheelEvent::modifiers`].
+    pub fn update_wheel_listener(&self, action: Box<dyn Fn(&PointerWheelEvent) -> bool>) {
+        self.add_update_message(UpdateMessage::WheelListener { id: *self, action });
+    }
+
+    /// Sets a function which is called with `true` when the view gains focus, and with `false`
+    /// when it loses it, once per change.
+    pub fn update_focus_listener(&self, action: Box<dyn Fn(bool)>) {
+        self.add_update_message(UpdateMessage::FocusListener { id: *self, action });
+    }
+
+    /// Sets a function which is called with `true` when focus moves into the view or one of
+    /// its descendants, and with `false` when it moves out of them, once per change. Focus
+    /// moving between the view and its descendants doesn't call it, so a form can validate
+    /// its fields when focus leaves the whole form.
+    pub fn update_focus_within_listener(&self, action: Box<dyn Fn(bool)>) {
+        self.add_update_message(UpdateMessage::FocusWithinListener { id: *self, action });
+    }
+
     pub fn update_animation(&self, animation: Animation) {
         self.add_update_message(UpdateMessage::Animation {
             id: *self,
@@ -221,13 +752,166 @@ impl Id {
         self.add_update_message(UpdateMessage::ScrollTo { id: *self, rect });
     }
 
+    /// The scroll position of the [`scroll`](crate::views::scroll) view with this id, or
+    /// `None` for other views. It can be kept while the view is rebuilt, and given to
+    /// [`Id::restore_scroll_state`] of the new scroll view.
+    pub fn save_scroll_state(&self) -> Option<ScrollState> {
+        scroll_state(*self)
+    }
+
+    /// Scrolls the [`scroll`](crate::views::scroll) view with this id to a position saved with
+    /// [`Id::save_scroll_state`]. This is done after the next layout, so the content of a new
+    /// scroll view is laid out before it's scrolled.
+    pub fn restore_scroll_state(&self, state: ScrollState) {
+        self.update_state_deferred(state);
+    }
+
     pub fn inspect(&self) {
         self.add_update_message(UpdateMessage::Inspect);
     }
 
-    fn add_update_message(&self, msg: UpdateMessage) {
+    /// Renders the window off-screen on the next frame and calls `callback` with the part of it
+    /// covered by this view, including anything painted above the view.
+    ///
+    /// The image has `scale` pixels per logical pixel, which defaults to the scale of the window.
+    /// `callback` receives `None` if the view isn't laid out or the renderer can't capture images.
+    pub fn capture_screenshot(
+        &self,
+        scale: Option<f64>,
+        callback: impl FnOnce(Option<RgbaImage>) + 'static,
+    ) {
+        self.add_update_message(UpdateMessage::CaptureScreenshot {
+            id: *self,
+            scale,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Clicks the primary pointer button at the center of this view, after the pending updates
+    /// are processed. The click goes through the normal event dispatch, so it's received by
+    /// whichever view is at the center, and it's ignored if this view is disabled.
+    ///
+    /// `handled` is called with `true` if a view handled the press or the release.
+    pub fn simulate_click(&self, handled: impl FnOnce(bool) + 'static) {
+        self.add_update_message(UpdateMessage::SimulateClick {
+            id: *self,
+            callback: Box::new(handled),
+        });
+    }
+
+    pub(crate) fn add_update_message(&self, msg: UpdateMessage) {
         CENTRAL_UPDATE_MESSAGES.with(|msgs| {
             msgs.borrow_mut().push((*self, msg));
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use floem_reactive::{create_rw_signal, RwSignal};
+    use kurbo::{Point, Size};
+
+    use super::{with_id_scope, Id};
+    use crate::{
+        event::EventListener,
+        style::Display,
+        test_support::HeadlessWindow,
+        view::View,
+        views::{empty, stack, Decorators},
+        widgets::text_input,
+    };
+
+    #[test]
+    fn hidden_view_keeps_state() {
+        type State = (Id, RwSignal<String>, RwSignal<i32>);
+        let state: Rc<Cell<Option<State>>> = Rc::new(Cell::new(None));
+        let view_state = state.clone();
+        let mut window = HeadlessWindow::new(Size::new(200.0, 100.0), move || {
+            let text = create_rw_signal("typed".to_string());
+            let count = create_rw_signal(0);
+            let input = text_input(text);
+            view_state.set(Some((input.id(), text, count)));
+            input
+        });
+        window.update();
+        let (input, text, count) = state.get().unwrap();
+        count.set(3);
+        assert!(input.is_visible_in_viewport());
+
+        input.set_display(Display::None);
+        window.update();
+        assert!(!input.is_visible_in_viewport());
+
+        input.set_display(Display::Flex);
+        window.update();
+        assert!(input.is_visible_in_viewport());
+        assert!(input.has_id_path());
+        assert_eq!(text.get_untracked(), "typed");
+        assert_eq!(count.get_untracked(), 3);
+    }
+
+    #[test]
+    fn brought_to_front_view_gets_events_first() {
+        let clicked = Rc::new(Cell::new(""));
+        let first_id = Rc::new(Cell::new(None));
+        let (view_clicked, view_first_id) = (clicked.clone(), first_id.clone());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let panel = |name: &'static str, clicked: Rc<Cell<&'static str>>| {
+                empty()
+                    .on_event_stop(EventListener::PointerDown, move |_| clicked.set(name))
+                    .style(|s| s.absolute().size(60.0, 60.0))
+            };
+            let first = panel("first", view_clicked.clone());
+            view_first_id.set(Some(first.id()));
+            stack((first, panel("second", view_clicked))).style(|s| s.size_full())
+        });
+        window.update();
+
+        window.click(Point::new(30.0, 30.0));
+        assert_eq!(clicked.get(), "second");
+
+        first_id.get().unwrap().bring_to_front();
+        window.update();
+        window.click(Point::new(30.0, 30.0));
+        assert_eq!(clicked.get(), "first");
+    }
+
+    #[test]
+    fn scoped_ids_are_deterministic() {
+        let build = || with_id_scope("tree", || [Id::next(), Id::next()]);
+        let first = build();
+        for id in first {
+            id.remove_id_path();
+        }
+        assert_eq!(build(), first);
+        // The views of the second build are still in use, so building again collides
+        assert!(build().iter().all(|id| !first.contains(id)));
+        assert_ne!(with_id_scope("other", Id::next), first[0]);
+    }
+
+    #[test]
+    fn debug_tree_follows_the_order_of_the_views() {
+        let root = Rc::new(Cell::new(None));
+        let view_root = root.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            with_id_scope("debug tree", || {
+                let named = |name: &str| {
+                    let view = empty();
+                    view.id().set_name(name);
+                    view
+                };
+                let view = stack((named("first"), named("second"), named("third")));
+                view_root.set(Some(view.id()));
+                view
+            })
+        });
+        window.update();
+
+        let tree = root.get().unwrap().debug_tree_string();
+        let position = |name: &str| tree.find(&format!("{name:?}")).unwrap();
+        assert!(position("first") < position("second"));
+        assert!(position("second") < position("third"));
+    }
+}
@@ -31,6 +31,7 @@ use taffy::style::{AlignItems, FlexDirection};
 pub struct CapturedView {
     id: Id,
     name: String,
+    label: Option<String>,
     layout: Rect,
     taffy: Layout,
     clipped: Rect,
@@ -54,6 +55,7 @@ impl CapturedView {
         Self {
             id,
             name: view.debug_name().to_string(),
+            label: id.name(),
             layout,
             taffy,
             clipped,
@@ -129,7 +131,10 @@ impl CaptureState {
 }
 
 fn captured_view_name(view: &CapturedView) -> impl View {
-    let name = static_label(view.name.clone());
+    let name = match &view.label {
+        Some(label) => static_label(format!("{} \"{label}\"", view.name)),
+        None => static_label(view.name.clone()),
+    };
     let id = text(view.id.to_raw()).style(|s| {
         s.margin_right(5.0)
             .background(Color::BLACK.with_alpha_factor(0.02))
@@ -430,7 +435,13 @@ fn selected_view(capture: &Rc<Capture>, selected: RwSignal<Option<Id>>) -> impl
         move |current| {
             if let Some(view) = current.and_then(|id| capture.root.find(id)) {
                 let name = info("Type", view.name.clone());
-                let id = info("Id", view.id.to_raw().to_string());
+                let id = info(
+                    "Id",
+                    match &view.label {
+                        Some(label) => format!("{} ({label})", view.id.to_raw()),
+                        None => view.id.to_raw().to_string(),
+                    },
+                );
                 let count = info("Child Count", format!("{}", view.children.len()));
                 let beyond = |view: f64, window| {
                     if view > window {
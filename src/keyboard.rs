@@ -1,7 +1,451 @@
-pub use floem_winit::keyboard::{Key, KeyCode, ModifiersState, NamedKey, NativeKey, PhysicalKey};
+pub use floem_winit::event::ElementState;
+pub use floem_winit::keyboard::{
+    Key, KeyCode, KeyLocation, ModifiersState, NamedKey, NativeKey, PhysicalKey, SmolStr,
+};
 
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct KeyEvent {
-    pub key: floem_winit::event::KeyEvent,
+    pub key: KeyInput,
     pub modifiers: ModifiersState,
 }
+
+/// A key being pressed or released, with the fields of the winit key event. Unlike the winit
+/// event, it can be created to simulate key presses.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct KeyInput {
+    pub physical_key: PhysicalKey,
+    pub logical_key: Key,
+    /// The text typed by the key, if any.
+    pub text: Option<SmolStr>,
+    pub location: KeyLocation,
+    pub state: ElementState,
+    pub repeat: bool,
+}
+
+impl KeyInput {
+    /// A press of the key which types `logical_key`, at the place of that key on a US layout.
+    pub fn pressed(logical_key: Key) -> Self {
+        let physical_key = match key_code(&logical_key) {
+            Some(code) => PhysicalKey::Code(code),
+            None => PhysicalKey::Unidentified(floem_winit::keyboard::NativeKeyCode::Unidentified),
+        };
+        let text = match &logical_key {
+            Key::Character(text) => Some(text.clone()),
+            Key::Named(NamedKey::Space) => Some(SmolStr::new(" ")),
+            _ => None,
+        };
+        Self {
+            physical_key,
+            logical_key,
+            text,
+            location: KeyLocation::Standard,
+            state: ElementState::Pressed,
+            repeat: false,
+        }
+    }
+
+    /// The release of the key of this press.
+    pub fn released(&self) -> Self {
+        Self {
+            text: None,
+            state: ElementState::Released,
+            repeat: false,
+            ..self.clone()
+        }
+    }
+}
+
+impl From<floem_winit::event::KeyEvent> for KeyInput {
+    fn from(event: floem_winit::event::KeyEvent) -> Self {
+        Self {
+            physical_key: event.physical_key,
+            logical_key: event.logical_key,
+            text: event.text,
+            location: event.location,
+            state: event.state,
+            repeat: event.repeat,
+        }
+    }
+}
+
+/// A key together with the modifiers which have to be held, such as <kbd>Ctrl</kbd>+<kbd>S</kbd>,
+/// used for keyboard shortcuts.
+///
+/// The key is a physical key, so shortcuts stay in the same place on every keyboard layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyCombination {
+    pub modifiers: ModifiersState,
+    pub key: KeyCode,
+}
+
+impl KeyCombination {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { modifiers, key }
+    }
+
+    /// The combination which was pressed for `event`, if the key is known.
+    pub fn from_key_event(event: &KeyEvent) -> Option<Self> {
+        match event.key.physical_key {
+            PhysicalKey::Code(key) => Some(Self::new(event.modifiers, key)),
+            PhysicalKey::Unidentified(_) => None,
+        }
+    }
+
+    /// Whether this combination was pressed for `event`.
+    pub fn matches(&self, event: &KeyEvent) -> bool {
+        Self::from_key_event(event).as_ref() == Some(self)
+    }
+
+    fn display_mac(&self) -> String {
+        let mut text = String::new();
+        for (modifier, symbol) in MAC_MODIFIERS {
+            if self.modifiers.contains(modifier) {
+                text.push(symbol);
+            }
+        }
+        match SPECIAL_KEYS.iter().find(|(key, ..)| *key == self.key) {
+            Some((_, _, symbol)) => text.push_str(symbol),
+            None => text.push_str(&key_name(self.key)),
+        }
+        text
+    }
+
+    fn display_with_names(&self, super_name: &str) -> String {
+        let mut text = String::new();
+        for (modifier, name) in [
+            (ModifiersState::CONTROL, "Ctrl"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::SUPER, super_name),
+        ] {
+            if self.modifiers.contains(modifier) {
+                text.push_str(name);
+                text.push('+');
+            }
+        }
+        text.push_str(&key_name(self.key));
+        text
+    }
+
+    /// Parses a combination like `Ctrl+Shift+S` or `⇧⌘S`, as written by the
+    /// [`Display`](std::fmt::Display) implementation on any platform, returning `None` if it
+    /// isn't valid.
+    ///
+    /// Names are case-insensitive, and the parts can be in any order as long as the key is
+    /// last. Besides `Ctrl`, `Alt`, `Shift` and `Super`, the modifiers can be written as
+    /// `Control`, `Option`, `Cmd`, `Command`, `Meta` or `Win`, or with the macOS symbols
+    /// without `+`, like `⇧⌘S`. `CmdOrCtrl` is <kbd>⌘</kbd> on macOS and <kbd>Ctrl</kbd>
+    /// elsewhere, for shortcuts which follow the conventions of each platform.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut modifiers = ModifiersState::empty();
+        let mut rest = text.trim();
+        while let Some((modifier, symbol)) = MAC_MODIFIERS
+            .iter()
+            .find(|(_, symbol)| rest.starts_with(*symbol))
+        {
+            modifiers |= *modifier;
+            rest = &rest[symbol.len_utf8()..];
+        }
+
+        // Splitting from the end keeps a `+` key, like in `Ctrl++`
+        let (names, key) = match rest.rsplit_once('+') {
+            Some((names, "")) => (names.strip_suffix('+')?, "+"),
+            Some((names, key)) => (names, key),
+            None => ("", rest),
+        };
+        if !names.is_empty() {
+            for name in names.split('+') {
+                modifiers |= parse_modifier(name.trim())?;
+            }
+        }
+        Some(Self::new(modifiers, parse_key(key.trim())?))
+    }
+}
+
+/// The modifiers with their macOS symbols, in the order macOS shows them.
+const MAC_MODIFIERS: [(ModifiersState, char); 4] = [
+    (ModifiersState::CONTROL, '⌃'),
+    (ModifiersState::ALT, '⌥'),
+    (ModifiersState::SHIFT, '⇧'),
+    (ModifiersState::SUPER, '⌘'),
+];
+
+/// The keys whose name isn't their key code, with their name and their macOS symbol.
+const SPECIAL_KEYS: [(KeyCode, &str, &str); 25] = [
+    (KeyCode::ArrowUp, "Up", "↑"),
+    (KeyCode::ArrowDown, "Down", "↓"),
+    (KeyCode::ArrowLeft, "Left", "←"),
+    (KeyCode::ArrowRight, "Right", "→"),
+    (KeyCode::Enter, "Enter", "↩"),
+    (KeyCode::Backspace, "Backspace", "⌫"),
+    (KeyCode::Delete, "Delete", "⌦"),
+    (KeyCode::Escape, "Esc", "⎋"),
+    (KeyCode::Tab, "Tab", "⇥"),
+    (KeyCode::Space, "Space", "Space"),
+    (KeyCode::Home, "Home", "↖"),
+    (KeyCode::End, "End", "↘"),
+    (KeyCode::PageUp, "PageUp", "⇞"),
+    (KeyCode::PageDown, "PageDown", "⇟"),
+    (KeyCode::Comma, ",", ","),
+    (KeyCode::Period, ".", "."),
+    (KeyCode::Minus, "-", "-"),
+    (KeyCode::Equal, "=", "="),
+    (KeyCode::Slash, "/", "/"),
+    (KeyCode::Backslash, "\\", "\\"),
+    (KeyCode::Semicolon, ";", ";"),
+    (KeyCode::Quote, "'", "'"),
+    (KeyCode::BracketLeft, "[", "["),
+    (KeyCode::BracketRight, "]", "]"),
+    (KeyCode::Backquote, "`", "`"),
+];
+
+const LETTER_KEYS: [KeyCode; 26] = [
+    KeyCode::KeyA,
+    KeyCode::KeyB,
+    KeyCode::KeyC,
+    KeyCode::KeyD,
+    KeyCode::KeyE,
+    KeyCode::KeyF,
+    KeyCode::KeyG,
+    KeyCode::KeyH,
+    KeyCode::KeyI,
+    KeyCode::KeyJ,
+    KeyCode::KeyK,
+    KeyCode::KeyL,
+    KeyCode::KeyM,
+    KeyCode::KeyN,
+    KeyCode::KeyO,
+    KeyCode::KeyP,
+    KeyCode::KeyQ,
+    KeyCode::KeyR,
+    KeyCode::KeyS,
+    KeyCode::KeyT,
+    KeyCode::KeyU,
+    KeyCode::KeyV,
+    KeyCode::KeyW,
+    KeyCode::KeyX,
+    KeyCode::KeyY,
+    KeyCode::KeyZ,
+];
+
+const DIGIT_KEYS: [KeyCode; 10] = [
+    KeyCode::Digit0,
+    KeyCode::Digit1,
+    KeyCode::Digit2,
+    KeyCode::Digit3,
+    KeyCode::Digit4,
+    KeyCode::Digit5,
+    KeyCode::Digit6,
+    KeyCode::Digit7,
+    KeyCode::Digit8,
+    KeyCode::Digit9,
+];
+
+const FUNCTION_KEYS: [KeyCode; 24] = [
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F8,
+    KeyCode::F9,
+    KeyCode::F10,
+    KeyCode::F11,
+    KeyCode::F12,
+    KeyCode::F13,
+    KeyCode::F14,
+    KeyCode::F15,
+    KeyCode::F16,
+    KeyCode::F17,
+    KeyCode::F18,
+    KeyCode::F19,
+    KeyCode::F20,
+    KeyCode::F21,
+    KeyCode::F22,
+    KeyCode::F23,
+    KeyCode::F24,
+];
+
+/// The name of `key`, which is the name of its key code without the `Key` or `Digit` prefix for
+/// letters and digits, and the character it types for punctuation.
+fn key_name(key: KeyCode) -> String {
+    if let Some((_, name, _)) = SPECIAL_KEYS.iter().find(|(special, ..)| *special == key) {
+        return name.to_string();
+    }
+    let name = format!("{key:?}");
+    name.strip_prefix("Key")
+        .or_else(|| name.strip_prefix("Digit"))
+        .unwrap_or(&name)
+        .to_string()
+}
+
+/// The code of the key typing `key` on a US layout.
+fn key_code(key: &Key) -> Option<KeyCode> {
+    match key {
+        Key::Character(text) => parse_key(text),
+        Key::Named(named) => {
+            // The named keys have the same names as their key codes
+            let name = format!("{named:?}");
+            SPECIAL_KEYS
+                .iter()
+                .find(|(code, ..)| format!("{code:?}") == name)
+                .map(|(code, ..)| *code)
+                .or_else(|| parse_key(&name))
+        }
+        _ => None,
+    }
+}
+
+fn parse_modifier(name: &str) -> Option<ModifiersState> {
+    let modifier = match name.to_lowercase().as_str() {
+        "ctrl" | "control" => ModifiersState::CONTROL,
+        "alt" | "option" | "opt" => ModifiersState::ALT,
+        "shift" => ModifiersState::SHIFT,
+        "super" | "cmd" | "command" | "meta" | "win" => ModifiersState::SUPER,
+        "cmdorctrl" if cfg!(target_os = "macos") => ModifiersState::SUPER,
+        "cmdorctrl" => ModifiersState::CONTROL,
+        _ => return None,
+    };
+    Some(modifier)
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    if name == "+" {
+        return Some(KeyCode::Equal);
+    }
+    let lowercase = name.to_lowercase();
+    let named = SPECIAL_KEYS
+        .iter()
+        .find(|(_, special, symbol)| special.to_lowercase() == lowercase || *symbol == name);
+    if let Some((key, ..)) = named {
+        return Some(*key);
+    }
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_ascii_alphabetic() {
+            return Some(LETTER_KEYS[(c.to_ascii_lowercase() as u8 - b'a') as usize]);
+        }
+        if let Some(digit) = c.to_digit(10) {
+            return Some(DIGIT_KEYS[digit as usize]);
+        }
+    }
+    match lowercase.as_str() {
+        "escape" => return Some(KeyCode::Escape),
+        "return" => return Some(KeyCode::Enter),
+        "del" => return Some(KeyCode::Delete),
+        _ => {}
+    }
+    let number: usize = lowercase.strip_prefix('f')?.parse().ok()?;
+    FUNCTION_KEYS.get(number.checked_sub(1)?).copied()
+}
+
+/// Formats the combination the way the platform shows shortcuts in menus: with symbols in the
+/// order <kbd>⌃⌥⇧⌘</kbd> on macOS, like `⇧⌘S`, and with names joined by `+` elsewhere, like
+/// `Ctrl+Shift+S`. The super key is named `Win` on Windows.
+impl std::fmt::Display for KeyCombination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = if cfg!(target_os = "macos") {
+            self.display_mac()
+        } else if cfg!(target_os = "windows") {
+            self.display_with_names("Win")
+        } else {
+            self.display_with_names("Super")
+        };
+        f.write_str(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ElementState, Key, KeyCode, KeyCombination, KeyInput, ModifiersState, NamedKey, PhysicalKey,
+    };
+
+    #[test]
+    fn display_per_platform() {
+        let save =
+            KeyCombination::new(ModifiersState::SUPER | ModifiersState::SHIFT, KeyCode::KeyS);
+        assert_eq!(save.display_mac(), "⇧⌘S");
+        assert_eq!(save.display_with_names("Win"), "Shift+Win+S");
+        assert_eq!(save.display_with_names("Super"), "Shift+Super+S");
+
+        let up = KeyCombination::new(
+            ModifiersState::CONTROL | ModifiersState::ALT,
+            KeyCode::ArrowUp,
+        );
+        assert_eq!(up.display_mac(), "⌃⌥↑");
+        assert_eq!(up.display_with_names("Super"), "Ctrl+Alt+Up");
+    }
+
+    #[test]
+    fn parse_round_trips() {
+        for combination in [
+            KeyCombination::new(
+                ModifiersState::CONTROL | ModifiersState::SHIFT,
+                KeyCode::KeyS,
+            ),
+            KeyCombination::new(ModifiersState::SUPER, KeyCode::Comma),
+            KeyCombination::new(ModifiersState::ALT, KeyCode::F11),
+            KeyCombination::new(ModifiersState::empty(), KeyCode::Digit7),
+            KeyCombination::new(ModifiersState::CONTROL, KeyCode::Backslash),
+        ] {
+            assert_eq!(
+                KeyCombination::parse(&combination.to_string()),
+                Some(combination)
+            );
+            assert_eq!(
+                KeyCombination::parse(&combination.display_mac()),
+                Some(combination)
+            );
+            assert_eq!(
+                KeyCombination::parse(&combination.display_with_names("Win")),
+                Some(combination)
+            );
+        }
+    }
+
+    #[test]
+    fn parse_aliases() {
+        let expected = KeyCombination::new(
+            ModifiersState::CONTROL | ModifiersState::SHIFT,
+            KeyCode::KeyS,
+        );
+        assert_eq!(KeyCombination::parse("shift + control + s"), Some(expected));
+        assert_eq!(
+            KeyCombination::parse("Cmd+Option+Escape"),
+            Some(KeyCombination::new(
+                ModifiersState::SUPER | ModifiersState::ALT,
+                KeyCode::Escape
+            ))
+        );
+        assert_eq!(
+            KeyCombination::parse("Ctrl++"),
+            Some(KeyCombination::new(ModifiersState::CONTROL, KeyCode::Equal))
+        );
+        assert_eq!(KeyCombination::parse("Ctrl+Hyper+S"), None);
+        assert_eq!(KeyCombination::parse("Ctrl+"), None);
+        assert_eq!(KeyCombination::parse("F25"), None);
+    }
+
+    #[test]
+    fn pressed_keys_have_their_us_layout_codes() {
+        let s = KeyInput::pressed(Key::Character("s".into()));
+        assert_eq!(s.physical_key, PhysicalKey::Code(KeyCode::KeyS));
+        assert_eq!(s.text.as_deref(), Some("s"));
+        assert_eq!(s.state, ElementState::Pressed);
+
+        let released = s.released();
+        assert_eq!(released.physical_key, s.physical_key);
+        assert_eq!(released.text, None);
+        assert_eq!(released.state, ElementState::Released);
+
+        let up = KeyInput::pressed(Key::Named(NamedKey::ArrowUp));
+        assert_eq!(up.physical_key, PhysicalKey::Code(KeyCode::ArrowUp));
+        assert_eq!(up.text, None);
+        let space = KeyInput::pressed(Key::Named(NamedKey::Space));
+        assert_eq!(space.physical_key, PhysicalKey::Code(KeyCode::Space));
+        assert_eq!(space.text.as_deref(), Some(" "));
+    }
+}
@@ -62,6 +62,26 @@
 //! Global state can be implemented using [provide_context](floem_reactive::provide_context) and
 //! [use_context](floem_reactive::use_context).
 //!
+//! Contexts are scoped: a value is seen from the scope it's provided in and its child scopes, like
+//! the views built by a [`dyn_container`](crate::views::dyn_container) or a list, with the
+//! nearest provider winning. [with_context](floem_reactive::with_context) provides a value to
+//! the views of a subtree only:
+//! ```rust
+//! use floem::reactive::{use_context, with_context};
+//! use floem::views::{label, v_stack};
+//!
+//! #[derive(Clone)]
+//! struct Accent(&'static str);
+//!
+//! v_stack((
+//!     label(|| "Default"),
+//!     with_context(Accent("red"), || {
+//!         let accent = use_context::<Accent>().unwrap();
+//!         label(move || accent.0)
+//!     }),
+//! ));
+//! ```
+//!
 //! ## Customizing appearance
 //!
 //! You can style a View instance by calling its [`style`](view::View::style) method. You'll need to import the
@@ -125,10 +145,19 @@ pub mod animate;
 mod app;
 mod app_handle;
 mod clipboard;
+mod clock;
+pub mod command;
 pub mod context;
+pub mod debounce;
 pub mod event;
+pub mod executor;
 pub mod ext_event;
 pub mod file;
+pub mod font;
+pub mod gesture;
+pub mod hot_reload;
+#[cfg(feature = "global_hotkey")]
+pub mod hotkey;
 pub mod id;
 mod inspector;
 pub mod keyboard;
@@ -137,8 +166,15 @@ mod nav;
 pub mod pointer;
 mod profiler;
 pub mod renderer;
+pub mod resource;
 pub mod responsive;
 pub mod style;
+#[cfg(feature = "stylesheet")]
+pub mod stylesheet;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+#[cfg(feature = "tray")]
+pub mod tray;
 pub mod unit;
 mod update;
 pub mod view;
@@ -149,7 +185,7 @@ pub mod widgets;
 pub mod window;
 mod window_handle;
 
-pub use app::{launch, quit_app, AppEvent, Application};
+pub use app::{is_ui_thread, launch, quit_app, AppEvent, Application};
 pub use clipboard::{Clipboard, ClipboardError};
 pub use context::EventPropagation;
 pub use floem_peniko as peniko;
@@ -1,5 +1,82 @@
 use std::sync::atomic::AtomicU64;
 
+use crate::{command::Command, keyboard::KeyCombination, style_class};
+
+#[cfg(target_os = "linux")]
+pub(crate) mod context_menu;
+#[cfg(any(target_os = "macos", all(target_os = "windows", feature = "tray")))]
+pub(crate) mod native;
+
+style_class!(pub MenuBarClass);
+style_class!(pub MenuBarItemClass);
+/// The underlined access key in the title of a menu drawn by Floem.
+style_class!(pub MnemonicClass);
+
+/// Splits `title` into the text which is shown and the byte offset of its access key in that
+/// text. The access key is the character after the first single `&`, and `&&` is shown as `&`.
+pub(crate) fn parse_mnemonic(title: &str) -> (String, Option<usize>) {
+    let mut text = String::with_capacity(title.len());
+    let mut mnemonic = None;
+    let mut chars = title.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '&' {
+            match chars.next() {
+                Some('&') => text.push('&'),
+                Some(next) => {
+                    if mnemonic.is_none() {
+                        mnemonic = Some(text.len());
+                    }
+                    text.push(next);
+                }
+                None => {}
+            }
+        } else {
+            text.push(c);
+        }
+    }
+    (text, mnemonic)
+}
+
+/// The access key of `title`, in lower case so it can be compared with the key pressed.
+pub(crate) fn mnemonic(title: &str) -> Option<char> {
+    let (text, mnemonic) = parse_mnemonic(title);
+    text[mnemonic?..]
+        .chars()
+        .next()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+}
+
+/// The submenus of the window menu, with their index.
+pub(crate) fn menu_bar_menus(menu_bar: &Option<Menu>) -> impl Iterator<Item = (usize, &Menu)> {
+    menu_bar
+        .iter()
+        .flat_map(|menu| menu.children.iter())
+        .enumerate()
+        .filter_map(|(index, entry)| match entry {
+            MenuEntry::SubMenu(m) => Some((index, m)),
+            _ => None,
+        })
+}
+
+/// The text of a menu title, with the access key underlined.
+#[cfg(not(target_os = "macos"))]
+pub(crate) fn mnemonic_title(title: &str) -> crate::views::Stack {
+    use crate::views::{h_stack, text, Decorators};
+
+    let (title, mnemonic) = parse_mnemonic(title);
+    match mnemonic {
+        Some(start) => {
+            let end = start + title[start..].chars().next().map_or(0, char::len_utf8);
+            h_stack((
+                text(&title[..start]),
+                text(&title[start..end]).class(MnemonicClass),
+                text(&title[end..]),
+            ))
+        }
+        None => h_stack((text(title),)),
+    }
+}
+
 /// An entry in a menu.
 ///
 /// An entry is either a [`MenuItem`], a submenu (i.e. [`Menu`]).
@@ -22,6 +99,8 @@ impl From<Menu> for MenuEntry {
 }
 
 impl Menu {
+    /// A menu with `title`, where an `&` marks the next character as the access key, see
+    /// [`MenuItem::new`].
     pub fn new(title: impl Into<String>) -> Self {
         Self {
             popup: false,
@@ -46,6 +125,24 @@ impl Menu {
         self.entry(MenuEntry::Separator)
     }
 
+    /// A copy of the menu without the actions, which keeps the ids of the items so their actions
+    /// can still be found.
+    pub(crate) fn without_actions(&self) -> Menu {
+        Menu {
+            popup: self.popup,
+            item: self.item.without_action(),
+            children: self
+                .children
+                .iter()
+                .map(|entry| match entry {
+                    MenuEntry::Separator => MenuEntry::Separator,
+                    MenuEntry::Item(item) => MenuEntry::Item(item.without_action()),
+                    MenuEntry::SubMenu(m) => MenuEntry::SubMenu(m.without_actions()),
+                })
+                .collect(),
+        }
+    }
+
     pub(crate) fn platform_menu(&self) -> floem_winit::menu::Menu {
         let mut menu = if self.popup {
             floem_winit::menu::Menu::new_for_popup()
@@ -60,15 +157,14 @@ impl Menu {
                 MenuEntry::Item(item) => {
                     menu.add_item(
                         item.id as u32,
-                        &item.title,
-                        // item.key.as_ref(),
+                        &item.platform_title(),
                         item.selected,
                         item.enabled,
                     );
                 }
                 MenuEntry::SubMenu(m) => {
                     let enabled = m.item.enabled;
-                    let title = m.item.title.clone();
+                    let title = m.item.platform_title();
                     menu.add_dropdown(m.platform_menu(), &title, enabled);
                 }
             }
@@ -80,8 +176,8 @@ impl Menu {
 pub struct MenuItem {
     pub(crate) id: u64,
     pub(crate) title: String,
-    // key: Option<HotKey>,
-    selected: Option<bool>,
+    pub(crate) shortcut: Option<KeyCombination>,
+    pub(crate) selected: Option<bool>,
     pub(crate) enabled: bool,
     pub(crate) action: Option<Box<dyn Fn()>>,
 }
@@ -93,13 +189,19 @@ impl From<MenuItem> for MenuEntry {
 }
 
 impl MenuItem {
+    /// An item with `title`. An `&` in the title marks the next character as the access key,
+    /// which is underlined, and activates the item when it's typed while the menu is open,
+    /// such as `"&Save"`. Use `&&` for an `&` in the title.
+    ///
+    /// When several items of a menu share an access key, typing it moves to the next of them
+    /// instead, and Enter activates it.
     pub fn new(title: impl Into<String>) -> Self {
         static COUNTER: AtomicU64 = AtomicU64::new(0);
         let id = COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
         Self {
             id,
             title: title.into(),
-            // key: None,
+            shortcut: None,
             selected: None,
             enabled: true,
             action: None,
@@ -115,4 +217,59 @@ impl MenuItem {
         self.enabled = enabled;
         self
     }
+
+    /// Shows a check mark next to the item while `checked` is `true`.
+    pub fn checked(mut self, checked: bool) -> Self {
+        self.selected = Some(checked);
+        self
+    }
+
+    /// Shows `shortcut` next to the item. When the menu is the window menu, pressing the
+    /// shortcut runs the action of the item, unless the focused view handled the key.
+    pub fn shortcut(mut self, shortcut: KeyCombination) -> Self {
+        self.shortcut = Some(shortcut);
+        self
+    }
+
+    /// Executes `command` when the item is activated. The item is disabled while the command is,
+    /// and shows the shortcut of the command.
+    ///
+    /// Menus are built when they're shown, except for the window menu, which is built again
+    /// when the enabled state of its commands changes if it's set with
+    /// [`Decorators::window_menu`](crate::views::Decorators::window_menu).
+    pub fn command(mut self, command: &Command) -> Self {
+        self.enabled = command.is_enabled();
+        self.shortcut = command.get_shortcut().or(self.shortcut);
+        let command = command.clone();
+        self.action(move || {
+            command.execute();
+        })
+    }
+
+    /// The title for the native menus. Windows underlines the access key after the `&` itself,
+    /// and shows the text after a tab aligned to the right, like a shortcut.
+    #[cfg(target_os = "windows")]
+    fn platform_title(&self) -> String {
+        match self.shortcut {
+            Some(shortcut) => format!("{}\t{shortcut}", self.title),
+            None => self.title.clone(),
+        }
+    }
+
+    /// The title for the native menus, which don't have access keys.
+    #[cfg(not(target_os = "windows"))]
+    fn platform_title(&self) -> String {
+        parse_mnemonic(&self.title).0
+    }
+
+    fn without_action(&self) -> MenuItem {
+        MenuItem {
+            id: self.id,
+            title: self.title.clone(),
+            shortcut: self.shortcut,
+            selected: self.selected,
+            enabled: self.enabled,
+            action: None,
+        }
+    }
 }
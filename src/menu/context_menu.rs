@@ -0,0 +1,602 @@
+//! The context menus drawn by Floem, which are used on Linux, where there's no native context
+//! menu.
+
+use floem_peniko::Color;
+use floem_reactive::{create_effect, create_rw_signal, Memo, RwSignal, Scope};
+use floem_winit::window::WindowId;
+use kurbo::{Point, Size};
+
+use crate::{
+    app::{add_app_update_event, AppUpdateEvent},
+    event::{Event, EventListener},
+    keyboard::{Key, KeyEvent, NamedKey},
+    menu::{menu_bar_menus, mnemonic, mnemonic_title, Menu, MenuEntry, MnemonicClass},
+    style::CursorStyle,
+    unit::UnitExt,
+    view::View,
+    views::{container_box, dyn_stack, empty, stack, svg, text, Decorators},
+};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MenuDisplay {
+    id: Option<u64>,
+    enabled: bool,
+    title: String,
+    mnemonic: Option<char>,
+    checked: Option<bool>,
+    shortcut: Option<String>,
+    children: Option<Vec<Option<MenuDisplay>>>,
+}
+
+fn format_menu(menu: &Menu) -> Vec<Option<MenuDisplay>> {
+    menu.children
+        .iter()
+        .map(|e| match e {
+            MenuEntry::Separator => None,
+            MenuEntry::Item(i) => Some(MenuDisplay {
+                id: Some(i.id),
+                enabled: i.enabled,
+                title: i.title.clone(),
+                mnemonic: mnemonic(&i.title),
+                checked: i.selected,
+                shortcut: i.shortcut.map(|shortcut| shortcut.to_string()),
+                children: None,
+            }),
+            MenuEntry::SubMenu(m) => Some(MenuDisplay {
+                id: None,
+                enabled: m.item.enabled,
+                title: m.item.title.clone(),
+                mnemonic: mnemonic(&m.item.title),
+                checked: None,
+                shortcut: None,
+                children: Some(format_menu(m)),
+            }),
+        })
+        .collect()
+}
+
+/// The entries of the submenu at `path` in `entries`.
+fn submenu_entries<'a>(
+    mut entries: &'a [Option<MenuDisplay>],
+    path: &[usize],
+) -> Option<&'a [Option<MenuDisplay>]> {
+    for &index in path {
+        entries = entries.get(index)?.as_ref()?.children.as_ref()?;
+    }
+    Some(entries)
+}
+
+/// The state of the context menu drawn by Floem, which is shared by all of its items.
+#[derive(Clone, Copy)]
+struct ContextMenuState {
+    window_id: WindowId,
+    context_menu: RwSignal<Option<(Menu, Point)>>,
+    items: Memo<Option<Vec<Option<MenuDisplay>>>>,
+    /// The indices of the highlighted item and the items of the submenus it's in.
+    highlighted: RwSignal<Vec<usize>>,
+    focus_count: RwSignal<i32>,
+    menu_bar: RwSignal<Option<Menu>>,
+    menu_bar_open: RwSignal<Option<usize>>,
+}
+
+impl ContextMenuState {
+    fn close(&self) {
+        self.context_menu.set(None);
+        self.focus_count.set(0);
+    }
+
+    fn activate(&self, id: u64) {
+        self.close();
+        add_app_update_event(AppUpdateEvent::MenuAction {
+            window_id: self.window_id,
+            action_id: id as usize,
+        });
+    }
+
+    /// Activates the item at `path`, or opens its submenu and highlights the first item of it.
+    fn enter(&self, items: &[Option<MenuDisplay>], mut path: Vec<usize>) {
+        let Some((&index, parent)) = path.split_last() else {
+            return;
+        };
+        let Some(Some(item)) = submenu_entries(items, parent).and_then(|e| e.get(index)) else {
+            return;
+        };
+        match &item.children {
+            Some(children) => {
+                if let Some(first) = children
+                    .iter()
+                    .position(|e| e.as_ref().is_some_and(|e| e.enabled))
+                {
+                    path.push(first);
+                }
+                self.highlighted.set(path);
+            }
+            None => {
+                if let Some(id) = item.id {
+                    self.activate(id);
+                }
+            }
+        }
+    }
+
+    /// Opens the menu of the menu bar `step` menus away from the open one, if it's opened from
+    /// the menu bar.
+    fn open_adjacent_bar_menu(&self, step: isize) {
+        let shown = self
+            .context_menu
+            .with_untracked(|menu| menu.as_ref().map(|(menu, _)| menu.item.id));
+        let menus: Vec<(usize, u64)> = self.menu_bar.with_untracked(|menu_bar| {
+            menu_bar_menus(menu_bar)
+                .filter(|(_, m)| m.item.enabled)
+                .map(|(index, m)| (index, m.item.id))
+                .collect()
+        });
+        if let Some(position) = menus.iter().position(|(_, id)| Some(*id) == shown) {
+            let next = (position as isize + step).rem_euclid(menus.len() as isize) as usize;
+            self.menu_bar_open.set(Some(menus[next].0));
+        }
+    }
+
+    fn key_down(&self, event: &KeyEvent) {
+        let Some(items) = self.items.get_untracked() else {
+            return;
+        };
+        let path = self.highlighted.get_untracked();
+        let (parent, current) = match path.split_last() {
+            Some((&last, parent)) => (parent.to_vec(), Some(last)),
+            None => (Vec::new(), None),
+        };
+        let Some(entries) = submenu_entries(&items, &parent) else {
+            return;
+        };
+        let length = entries.len();
+        let enabled = |index: usize| {
+            entries
+                .get(index)
+                .and_then(|e| e.as_ref())
+                .is_some_and(|e| e.enabled)
+        };
+        let child_path = |index: usize| {
+            let mut path = parent.clone();
+            path.push(index);
+            path
+        };
+        let current_item = current
+            .and_then(|i| entries.get(i))
+            .and_then(|e| e.as_ref());
+        match &event.key.logical_key {
+            Key::Named(NamedKey::ArrowDown) if length > 0 => {
+                let start = current.unwrap_or(length - 1);
+                if let Some(next) = (1..=length)
+                    .map(|offset| (start + offset) % length)
+                    .find(|&i| enabled(i))
+                {
+                    self.highlighted.set(child_path(next));
+                }
+            }
+            Key::Named(NamedKey::ArrowUp) if length > 0 => {
+                let start = current.unwrap_or(0);
+                if let Some(next) = (1..=length)
+                    .map(|offset| (start + length * 2 - offset) % length)
+                    .find(|&i| enabled(i))
+                {
+                    self.highlighted.set(child_path(next));
+                }
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                if current_item.is_some_and(|item| item.children.is_some()) {
+                    self.enter(&items, path);
+                } else {
+                    self.open_adjacent_bar_menu(1);
+                }
+            }
+            Key::Named(NamedKey::ArrowLeft) => {
+                if parent.is_empty() {
+                    self.open_adjacent_bar_menu(-1);
+                } else {
+                    self.highlighted.set(parent);
+                }
+            }
+            Key::Named(NamedKey::Escape) => {
+                // Closes the innermost submenu, or the menu itself
+                if parent.is_empty() {
+                    self.close();
+                } else {
+                    self.highlighted.set(parent);
+                }
+            }
+            Key::Named(NamedKey::Enter | NamedKey::Space) if current.is_some() => {
+                self.enter(&items, path);
+            }
+            Key::Character(character) => {
+                let Some(character) = character.chars().next() else {
+                    return;
+                };
+                let character = character.to_lowercase().next().unwrap_or(character);
+                let matching: Vec<usize> = (0..length)
+                    .filter(|&i| {
+                        enabled(i)
+                            && entries[i]
+                                .as_ref()
+                                .is_some_and(|e| e.mnemonic == Some(character))
+                    })
+                    .collect();
+                match matching[..] {
+                    [] => {}
+                    [index] => self.enter(&items, child_path(index)),
+                    // When the access key is shared, it moves to the next item with it
+                    _ => {
+                        let next = matching
+                            .iter()
+                            .find(|&&i| current.is_some_and(|current| i > current))
+                            .unwrap_or(&matching[0]);
+                        self.highlighted.set(child_path(*next));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+pub(crate) fn context_menu_view(
+    cx: Scope,
+    window_id: WindowId,
+    context_menu: RwSignal<Option<(Menu, Point)>>,
+    window_size: RwSignal<Size>,
+    menu_bar: RwSignal<Option<Menu>>,
+    menu_bar_open: RwSignal<Option<usize>>,
+) -> impl View {
+    let context_menu_items = cx.create_memo(move |_| {
+        context_menu.with(|menu| {
+            menu.as_ref()
+                .map(|(menu, _): &(Menu, Point)| format_menu(menu))
+        })
+    });
+    let context_menu_size = cx.create_rw_signal(Size::ZERO);
+    let state = ContextMenuState {
+        window_id,
+        context_menu,
+        items: context_menu_items,
+        highlighted: cx.create_rw_signal(Vec::new()),
+        focus_count: cx.create_rw_signal(0),
+        menu_bar,
+        menu_bar_open,
+    };
+
+    fn view_fn(state: ContextMenuState, path: Vec<usize>, menu: Option<MenuDisplay>) -> impl View {
+        let ContextMenuState {
+            highlighted,
+            focus_count,
+            context_menu,
+            ..
+        } = state;
+        if let Some(menu) = menu {
+            let menu_width = create_rw_signal(0.0);
+            let has_submenu = menu.children.is_some();
+            let checked = menu.checked;
+            let has_shortcut = menu.shortcut.is_some();
+            let is_highlighted = {
+                let path = path.clone();
+                move || highlighted.with(|highlighted| highlighted == &path)
+            };
+            // The submenu is open while it or one of its items is highlighted
+            let show_submenu = {
+                let path = path.clone();
+                move || {
+                    has_submenu && highlighted.with(|highlighted| highlighted.starts_with(&path))
+                }
+            };
+            let enter_path = path.clone();
+            let submenu_svg = r#"<svg width="16" height="16" viewBox="0 0 16 16" xmlns="http://www.w3.org/2000/svg" fill="currentColor"><path fill-rule="evenodd" clip-rule="evenodd" d="M10.072 8.024L5.715 3.667l.618-.62L11 7.716v.618L6.333 13l-.618-.619 4.357-4.357z"/></svg>"#;
+            container_box(
+                stack((
+                    stack((
+                        stack((
+                            text("✓").style(move |s| {
+                                s.width(20.0)
+                                    .apply_if(checked != Some(true), |s| {
+                                        s.color(Color::TRANSPARENT)
+                                    })
+                                    .apply_if(checked.is_none(), |s| s.hide())
+                            }),
+                            mnemonic_title(&menu.title),
+                        )),
+                        text(menu.shortcut.unwrap_or_default()).style(move |s| {
+                            s.margin_left(20.0)
+                                .color(Color::rgb8(150, 150, 150))
+                                .apply_if(!has_shortcut, |s| s.hide())
+                        }),
+                        svg(|| submenu_svg.to_string()).style(move |s| {
+                            s.size(20.0, 20.0)
+                                .color(Color::rgb8(201, 201, 201))
+                                .margin_right(10.0)
+                                .margin_left(20.0)
+                                .apply_if(!has_submenu, |s| s.hide())
+                        }),
+                    ))
+                    .on_event_stop(EventListener::PointerEnter, move |_| {
+                        if menu.enabled {
+                            highlighted.set(enter_path.clone());
+                        }
+                    })
+                    .on_resize(move |rect| {
+                        let width = rect.width();
+                        if menu_width.get_untracked() != width {
+                            menu_width.set(width);
+                        }
+                    })
+                    .on_click_stop(move |_| {
+                        if let Some(id) = menu.id {
+                            state.activate(id);
+                        }
+                    })
+                    .on_secondary_click_stop(move |_| {
+                        if let Some(id) = menu.id {
+                            state.activate(id);
+                        }
+                    })
+                    .disabled(move || !menu.enabled)
+                    .style(move |s| {
+                        s.width(100.pct())
+                            .min_width(100.pct())
+                            .padding_horiz(20.0)
+                            .justify_between()
+                            .items_center()
+                            .apply_if(is_highlighted(), |s| {
+                                s.border_radius(10.0).background(Color::rgb8(65, 65, 65))
+                            })
+                            .active(|s| s.border_radius(10.0).background(Color::rgb8(92, 92, 92)))
+                            .disabled(|s| s.color(Color::rgb8(92, 92, 92)))
+                    }),
+                    dyn_stack(
+                        move || {
+                            menu.children
+                                .clone()
+                                .unwrap_or_default()
+                                .into_iter()
+                                .enumerate()
+                        },
+                        move |s| s.clone(),
+                        move |(index, menu)| {
+                            let mut path = path.clone();
+                            path.push(index);
+                            view_fn(state, path, menu)
+                        },
+                    )
+                    .keyboard_navigatable()
+                    .on_event_stop(EventListener::FocusGained, move |_| {
+                        focus_count.update(|count| {
+                            *count += 1;
+                        });
+                    })
+                    .on_event_stop(EventListener::FocusLost, move |_| {
+                        let count = focus_count
+                            .try_update(|count| {
+                                *count -= 1;
+                                *count
+                            })
+                            .unwrap();
+                        if count < 1 {
+                            context_menu.set(None);
+                        }
+                    })
+                    .on_event_stop(EventListener::KeyDown, move |event| {
+                        if let Event::KeyDown(event) = event {
+                            state.key_down(event);
+                        }
+                    })
+                    .on_event_stop(EventListener::PointerDown, move |_| {})
+                    .style(move |s| {
+                        s.absolute()
+                            .min_width(200.0)
+                            .margin_top(-5.0)
+                            .margin_left(menu_width.get() as f32)
+                            .flex_col()
+                            .border_radius(10.0)
+                            .background(Color::rgb8(44, 44, 44))
+                            .padding(5.0)
+                            .cursor(CursorStyle::Default)
+                            .box_shadow_blur(5.0)
+                            .box_shadow_color(Color::BLACK)
+                            .apply_if(!show_submenu(), |s| s.hide())
+                    }),
+                ))
+                .style(|s| s.min_width(100.pct())),
+            )
+            .style(|s| s.min_width(100.pct()))
+        } else {
+            container_box(empty().style(|s| {
+                s.width(100.pct())
+                    .height(1.0)
+                    .margin_vert(5.0)
+                    .background(Color::rgb8(92, 92, 92))
+            }))
+            .style(|s| s.min_width(100.pct()).padding_horiz(20.0))
+        }
+    }
+
+    let ContextMenuState {
+        highlighted,
+        focus_count,
+        ..
+    } = state;
+    let view = dyn_stack(
+        move || {
+            context_menu_items
+                .get()
+                .unwrap_or_default()
+                .into_iter()
+                .enumerate()
+        },
+        move |s| s.clone(),
+        move |(index, menu)| view_fn(state, vec![index], menu),
+    )
+    .on_resize(move |rect| {
+        context_menu_size.set(rect.size());
+    })
+    .on_event_stop(EventListener::PointerDown, move |_| {})
+    .keyboard_navigatable()
+    .on_event_stop(EventListener::KeyDown, move |event| {
+        if let Event::KeyDown(event) = event {
+            state.key_down(event);
+        }
+    })
+    .on_event_stop(EventListener::FocusGained, move |_| {
+        focus_count.update(|count| {
+            *count += 1;
+        });
+    })
+    .on_event_stop(EventListener::FocusLost, move |_| {
+        let count = focus_count
+            .try_update(|count| {
+                *count -= 1;
+                *count
+            })
+            .unwrap();
+        if count < 1 {
+            context_menu.set(None);
+        }
+    })
+    .style(move |s| {
+        let window_size = window_size.get();
+        let menu_size = context_menu_size.get();
+        let is_acitve = context_menu.with(|m| m.is_some());
+        let mut pos = context_menu.with(|m| m.as_ref().map(|(_, pos)| *pos).unwrap_or_default());
+        if pos.x + menu_size.width > window_size.width {
+            pos.x = window_size.width - menu_size.width;
+        }
+        if pos.y + menu_size.height > window_size.height {
+            pos.y = window_size.height - menu_size.height;
+        }
+        s.absolute()
+            .min_width(200.0)
+            .flex_col()
+            .border_radius(10.0)
+            .background(Color::rgb8(44, 44, 44))
+            .color(Color::rgb8(201, 201, 201))
+            .z_index(999)
+            .line_height(2.0)
+            .padding(5.0)
+            .margin_left(pos.x as f32)
+            .margin_top(pos.y as f32)
+            .cursor(CursorStyle::Default)
+            .apply_if(!is_acitve, |s| s.hide())
+            .box_shadow_blur(5.0)
+            .box_shadow_color(Color::BLACK)
+            .class(MnemonicClass, |s| {
+                s.border_bottom(1.0)
+                    .border_color(Color::rgb8(201, 201, 201))
+            })
+    });
+
+    let id = view.id();
+
+    create_effect(move |_| {
+        if context_menu.with(|m| m.is_some()) {
+            id.request_focus();
+        }
+    });
+    create_effect(move |_| {
+        context_menu.track();
+        highlighted.set(Vec::new());
+    });
+
+    view
+}
+
+#[cfg(test)]
+mod tests {
+    use floem_reactive::{create_memo, create_rw_signal};
+    use floem_winit::window::WindowId;
+    use kurbo::Point;
+
+    use super::{format_menu, ContextMenuState};
+    use crate::{
+        keyboard::{Key, KeyEvent, KeyInput, ModifiersState, NamedKey},
+        menu::{Menu, MenuItem},
+    };
+
+    fn menu_state() -> ContextMenuState {
+        let menu = Menu::new("")
+            .entry(MenuItem::new("&Open"))
+            .separator()
+            .entry(MenuItem::new("&Save").enabled(false))
+            .entry(
+                Menu::new("&Recent")
+                    .entry(MenuItem::new("&First"))
+                    .entry(MenuItem::new("&Second")),
+            )
+            .entry(MenuItem::new("&Print"))
+            .entry(MenuItem::new("&Preferences"));
+        let context_menu = create_rw_signal(Some((menu, Point::ZERO)));
+        ContextMenuState {
+            window_id: unsafe { WindowId::dummy() },
+            context_menu,
+            items: create_memo(move |_| {
+                context_menu.with(|menu| menu.as_ref().map(|(menu, _)| format_menu(menu)))
+            }),
+            highlighted: create_rw_signal(Vec::new()),
+            focus_count: create_rw_signal(1),
+            menu_bar: create_rw_signal(None),
+            menu_bar_open: create_rw_signal(None),
+        }
+    }
+
+    fn press(state: &ContextMenuState, key: Key) -> Vec<usize> {
+        state.key_down(&KeyEvent {
+            key: KeyInput::pressed(key),
+            modifiers: ModifiersState::empty(),
+        });
+        state.highlighted.get_untracked()
+    }
+
+    fn is_open(state: &ContextMenuState) -> bool {
+        state.context_menu.with_untracked(|menu| menu.is_some())
+    }
+
+    #[test]
+    fn arrows_skip_separators_and_disabled_items() {
+        let state = menu_state();
+        let down = || Key::Named(NamedKey::ArrowDown);
+        let up = || Key::Named(NamedKey::ArrowUp);
+        assert_eq!(press(&state, down()), [0]);
+        assert_eq!(press(&state, down()), [3]);
+        assert_eq!(press(&state, up()), [0]);
+        // The highlight wraps around the ends of the menu
+        assert_eq!(press(&state, up()), [5]);
+        assert_eq!(press(&state, down()), [0]);
+    }
+
+    #[test]
+    fn submenus_are_entered_and_left() {
+        let state = menu_state();
+        state.highlighted.set(vec![3]);
+        assert_eq!(press(&state, Key::Named(NamedKey::ArrowRight)), [3, 0]);
+        assert_eq!(press(&state, Key::Named(NamedKey::ArrowDown)), [3, 1]);
+        assert_eq!(press(&state, Key::Named(NamedKey::ArrowLeft)), [3]);
+        assert_eq!(press(&state, Key::Named(NamedKey::Enter)), [3, 0]);
+
+        // Escape closes the submenu, then the menu
+        assert_eq!(press(&state, Key::Named(NamedKey::Escape)), [3]);
+        assert!(is_open(&state));
+        press(&state, Key::Named(NamedKey::Escape));
+        assert!(!is_open(&state));
+    }
+
+    #[test]
+    fn access_keys_highlight_or_activate_items() {
+        let state = menu_state();
+        let p = || Key::Character("p".into());
+        // The access key is shared, so it moves between the items with it
+        assert_eq!(press(&state, p()), [4]);
+        assert_eq!(press(&state, p()), [5]);
+        assert_eq!(press(&state, p()), [4]);
+        // Disabled items are skipped, and an item with its own access key is activated
+        assert_eq!(press(&state, Key::Character("s".into())), [4]);
+        assert!(is_open(&state));
+        assert_eq!(press(&state, Key::Character("r".into())), [3, 0]);
+        press(&state, Key::Character("f".into()));
+        assert!(!is_open(&state));
+    }
+}
@@ -0,0 +1,144 @@
+//! Native menus, used for the menu bar of macOS apps and the menus of tray icons.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+use floem_reactive::Scope;
+// The tray icons use the same crate for their menus
+#[cfg(not(target_os = "macos"))]
+use tray_icon::menu as muda;
+
+use muda::{
+    accelerator::{Accelerator, Code, Modifiers},
+    CheckMenuItem, IsMenuItem, MenuEvent, MenuId, MenuItem, PredefinedMenuItem, Submenu,
+};
+
+use crate::{
+    ext_event::create_signal_from_channel,
+    keyboard::{KeyCombination, ModifiersState},
+    menu::{Menu, MenuEntry},
+};
+
+thread_local! {
+    static MENU_ACTIONS: RefCell<HashMap<MenuId, Rc<dyn Fn()>>> = Default::default();
+    static LISTENING: Cell<bool> = const { Cell::new(false) };
+    #[cfg(target_os = "macos")]
+    static APP_MENU: RefCell<Option<(muda::Menu, Vec<MenuId>)>> = Default::default();
+}
+
+/// Makes `menu` the menu bar of the app, with each of its submenus as a menu of the bar.
+/// The first submenu is the application menu.
+#[cfg(target_os = "macos")]
+pub(crate) fn set_app_menu(menu: Menu) {
+    let mut menu_items = Vec::new();
+    let platform_menu = platform_menu(menu, &mut menu_items);
+    platform_menu.init_for_nsapp();
+    let old = APP_MENU.with(|app_menu| app_menu.replace(Some((platform_menu, menu_items))));
+    if let Some((_, old_items)) = old {
+        remove_menu_actions(&old_items);
+    }
+}
+
+/// Removes the actions of the items of a menu which isn't shown anymore.
+pub(crate) fn remove_menu_actions(items: &[MenuId]) {
+    MENU_ACTIONS.with(|actions| {
+        let mut actions = actions.borrow_mut();
+        for id in items {
+            actions.remove(id);
+        }
+    });
+}
+
+/// Forwards the menu events, which are sent from the platform event handlers, to the actions
+/// on the main thread.
+fn listen() {
+    if LISTENING.with(|listening| listening.replace(true)) {
+        return;
+    }
+
+    let menu_events = create_signal_from_channel(MenuEvent::receiver().clone());
+    let cx = Scope::new();
+    cx.create_effect(move |_| {
+        let action = menu_events.with(|event| {
+            let event = event.as_ref()?;
+            MENU_ACTIONS.with(|actions| actions.borrow().get(&event.id).cloned())
+        });
+        if let Some(action) = action {
+            action();
+        }
+    });
+}
+
+fn accelerator(shortcut: KeyCombination) -> Option<Accelerator> {
+    // The key codes have the same names as the codes of the W3C specification
+    let code: Code = format!("{:?}", shortcut.key).parse().ok()?;
+    let mut modifiers = Modifiers::empty();
+    for (modifier, platform_modifier) in [
+        (ModifiersState::CONTROL, Modifiers::CONTROL),
+        (ModifiersState::ALT, Modifiers::ALT),
+        (ModifiersState::SHIFT, Modifiers::SHIFT),
+        (ModifiersState::SUPER, Modifiers::SUPER),
+    ] {
+        if shortcut.modifiers.contains(modifier) {
+            modifiers |= platform_modifier;
+        }
+    }
+    Some(Accelerator::new(Some(modifiers), code))
+}
+
+/// Converts `menu` into a native menu, adding the ids of its items with an action to
+/// `menu_items`.
+pub(crate) fn platform_menu(menu: Menu, menu_items: &mut Vec<MenuId>) -> muda::Menu {
+    listen();
+    let platform_menu = muda::Menu::new();
+    append_entries(menu.children, menu_items, &|item| {
+        let _ = platform_menu.append(item);
+    });
+    platform_menu
+}
+
+fn append_entries(
+    entries: Vec<MenuEntry>,
+    menu_items: &mut Vec<MenuId>,
+    append: &dyn Fn(&dyn IsMenuItem),
+) {
+    for entry in entries {
+        match entry {
+            MenuEntry::Separator => append(&PredefinedMenuItem::separator()),
+            MenuEntry::Item(item) => {
+                let id = MenuId::new(item.id.to_string());
+                if let Some(action) = item.action {
+                    MENU_ACTIONS
+                        .with(|actions| actions.borrow_mut().insert(id.clone(), Rc::from(action)));
+                    menu_items.push(id.clone());
+                }
+                let accelerator = item.shortcut.and_then(accelerator);
+                match item.selected {
+                    Some(checked) => append(&CheckMenuItem::with_id(
+                        id,
+                        &item.title,
+                        item.enabled,
+                        checked,
+                        accelerator,
+                    )),
+                    None => append(&MenuItem::with_id(
+                        id,
+                        &item.title,
+                        item.enabled,
+                        accelerator,
+                    )),
+                }
+            }
+            MenuEntry::SubMenu(menu) => {
+                let submenu = Submenu::new(&menu.item.title, menu.item.enabled);
+                append_entries(menu.children, menu_items, &|item| {
+                    let _ = submenu.append(item);
+                });
+                append(&submenu);
+            }
+        }
+    }
+}
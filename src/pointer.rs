@@ -1,13 +1,67 @@
 use floem_winit::{event::MouseButton, keyboard::ModifiersState};
 use kurbo::{Point, Vec2};
 
+use crate::gesture::GesturePhase;
+
+/// How many pixels a line of a [`WheelDeltaMode::Line`] wheel event scrolls.
+pub const WHEEL_LINE_HEIGHT: f64 = 60.0;
+
+/// The unit the device reported a wheel event in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WheelDeltaMode {
+    /// Lines, usually from the notches of a mouse wheel, converted to pixels with
+    /// [`WHEEL_LINE_HEIGHT`].
+    Line,
+    /// Pixels, usually from a touchpad.
+    Pixel,
+}
+
 #[derive(Debug, Clone)]
 pub struct PointerWheelEvent {
     pub pos: Point,
+    /// How far to scroll, in pixels.
     pub delta: Vec2,
+    pub delta_mode: WheelDeltaMode,
     pub modifiers: ModifiersState,
 }
 
+/// The kind of device which moves the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PointerType {
+    #[default]
+    Mouse,
+    Pen,
+    Touch,
+}
+
+/// Two fingers pinching or rotating on a touchpad.
+#[derive(Debug, Clone)]
+pub struct PointerPinchEvent {
+    pub pos: Point,
+    /// How much the scale changed, where `0.1` grows by 10%.
+    pub scale_delta: f64,
+    /// How much the fingers rotated clockwise, in radians.
+    pub rotation_delta: f64,
+    pub phase: GesturePhase,
+    pub modifiers: ModifiersState,
+}
+
+/// A finger or a pen touching the screen. Each touch has its own `id`, which stays the same
+/// from when it begins until it ends, so several touches at once can be told apart.
+#[derive(Debug, Clone)]
+pub struct TouchEvent {
+    pub id: u64,
+    pub phase: GesturePhase,
+    pub pos: Point,
+    /// [`PointerType::Pen`] when the platform reports the tilt of the touch, which only pens
+    /// have, and [`PointerType::Touch`] otherwise.
+    pub pointer_type: PointerType,
+    /// See [`PointerInputEvent::pressure`].
+    pub pressure: f64,
+    /// See [`PointerInputEvent::tilt`].
+    pub tilt: Option<f64>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Copy)]
 pub enum PointerButton {
     Primary,
@@ -59,10 +113,95 @@ pub struct PointerInputEvent {
     pub button: PointerButton,
     pub modifiers: ModifiersState,
     pub count: u8,
+    pub pointer_type: PointerType,
+    /// How hard the pointer is pressed, from 0.0 to 1.0. Devices which don't report pressure
+    /// have 0.5 while pressed and 0.0 otherwise.
+    pub pressure: f64,
+    /// The angle between a pen and the surface, in radians, where π/2 is perpendicular.
+    /// `None` when the device doesn't report it.
+    pub tilt: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
 pub struct PointerMoveEvent {
     pub pos: Point,
     pub modifiers: ModifiersState,
+    pub pointer_type: PointerType,
+    /// See [`PointerInputEvent::pressure`].
+    pub pressure: f64,
+    /// See [`PointerInputEvent::tilt`].
+    pub tilt: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use kurbo::{Point, Size};
+
+    use super::PointerButton;
+    use crate::{
+        event::{Event, EventListener},
+        gesture::GesturePhase,
+        test_support::HeadlessWindow,
+        views::{empty, h_stack, Decorators},
+    };
+
+    #[test]
+    fn touches_stay_with_the_view_they_began_on() {
+        let touches = Rc::new(RefCell::new(Vec::new()));
+        let (left_touches, right_touches) = (touches.clone(), touches.clone());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 50.0), move || {
+            h_stack((
+                empty()
+                    .on_touch(move |touch| left_touches.borrow_mut().push(("left", touch.id)))
+                    .style(|s| s.size(50.0, 50.0)),
+                empty()
+                    .on_touch(move |touch| right_touches.borrow_mut().push(("right", touch.id)))
+                    .style(|s| s.size(50.0, 50.0)),
+            ))
+        });
+        window.update();
+
+        window.touch(1, GesturePhase::Began, Point::new(25.0, 25.0));
+        window.touch(2, GesturePhase::Began, Point::new(75.0, 25.0));
+        window.touch(1, GesturePhase::Changed, Point::new(75.0, 25.0));
+        window.touch(2, GesturePhase::Ended, Point::new(25.0, 25.0));
+        window.touch(1, GesturePhase::Ended, Point::new(75.0, 25.0));
+        window.touch(1, GesturePhase::Changed, Point::new(25.0, 25.0));
+        assert_eq!(
+            *touches.borrow(),
+            [
+                ("left", 1),
+                ("right", 2),
+                ("left", 1),
+                ("right", 2),
+                ("left", 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn pointer_moves_carry_pressure() {
+        let pressures = Rc::new(RefCell::new(Vec::new()));
+        let canvas_pressures = pressures.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            empty()
+                .on_event_cont(EventListener::PointerMove, move |event| {
+                    if let Event::PointerMove(event) = event {
+                        canvas_pressures.borrow_mut().push(event.pressure);
+                    }
+                })
+                .style(|s| s.size_full())
+        });
+        window.update();
+
+        window.pointer_down(Point::new(10.0, 10.0), PointerButton::Primary);
+        pressures.borrow_mut().clear();
+        window.set_pressure(0.2);
+        window.pointer_move(Point::new(20.0, 10.0));
+        window.set_pressure(0.9);
+        window.pointer_move(Point::new(30.0, 10.0));
+        assert_eq!(*pressures.borrow(), [0.2, 0.2, 0.9, 0.9]);
+    }
 }
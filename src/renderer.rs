@@ -49,7 +49,7 @@
 //!
 use crate::cosmic_text::TextLayout;
 use floem_peniko::BrushRef;
-use floem_renderer::Img;
+use floem_renderer::{Backend, Img, TextLines};
 use floem_tiny_skia_renderer::TinySkiaRenderer;
 use floem_vger_renderer::VgerRenderer;
 use image::DynamicImage;
@@ -62,7 +62,9 @@ pub enum Renderer {
 }
 
 impl Renderer {
-    pub fn new<W>(window: &W, scale: f64, size: Size) -> Self
+    /// Creates a renderer drawing with `backend`, or with a GPU backend if it's `None`. The
+    /// renderer falls back to software rendering when the GPU backend can't be used.
+    pub fn new<W>(window: &W, scale: f64, size: Size, backend: Option<Backend>) -> Self
     where
         W: raw_window_handle::HasRawDisplayHandle + raw_window_handle::HasRawWindowHandle,
     {
@@ -74,11 +76,17 @@ impl Renderer {
         {
             val
         } else {
-            false
+            backend == Some(Backend::Software)
         };
 
         let vger_err = if !force_tiny_skia {
-            match VgerRenderer::new(window, size.width as u32, size.height as u32, scale) {
+            match VgerRenderer::new(
+                window,
+                size.width as u32,
+                size.height as u32,
+                scale,
+                backend,
+            ) {
                 Ok(vger) => return Self::Vger(vger),
                 Err(err) => Some(err),
             }
@@ -99,6 +107,15 @@ impl Renderer {
         }
     }
 
+    /// Creates a renderer which renders to an offscreen image, for running without a display.
+    pub fn new_headless(scale: f64, size: Size) -> Self {
+        let size = Size::new(size.width.max(1.0), size.height.max(1.0));
+        match TinySkiaRenderer::new_headless(size.width as u32, size.height as u32, scale) {
+            Ok(tiny_skia) => Self::TinySkia(tiny_skia),
+            Err(err) => panic!("Failed to create headless TinySkiaRenderer: {err}"),
+        }
+    }
+
     pub fn resize(&mut self, scale: f64, size: Size) {
         let size = Size::new(size.width.max(1.0), size.height.max(1.0));
         match self {
@@ -113,6 +130,24 @@ impl Renderer {
             Renderer::TinySkia(r) => r.set_scale(scale),
         }
     }
+
+    /// The backend the renderer draws with.
+    pub fn backend(&self) -> Backend {
+        match self {
+            Renderer::Vger(r) => r.backend(),
+            Renderer::TinySkia(_) => Backend::Software,
+        }
+    }
+
+    /// Repaints only `damage` in the next frame, in window coordinates, keeping the rest of the
+    /// previous frame. Returns `false` if the renderer repaints the whole window instead.
+    pub fn set_damage(&mut self, damage: Rect) -> bool {
+        match self {
+            // The GPU surface doesn't keep the previous frame
+            Renderer::Vger(_) => false,
+            Renderer::TinySkia(r) => r.set_damage(damage),
+        }
+    }
 }
 
 impl floem_renderer::Renderer for Renderer {
@@ -187,6 +222,22 @@ impl floem_renderer::Renderer for Renderer {
         }
     }
 
+    fn draw_text_lines(
+        &mut self,
+        layout: &TextLayout,
+        pos: impl Into<kurbo::Point>,
+        lines: &TextLines,
+    ) {
+        match self {
+            Renderer::Vger(v) => {
+                v.draw_text_lines(layout, pos, lines);
+            }
+            Renderer::TinySkia(v) => {
+                v.draw_text_lines(layout, pos, lines);
+            }
+        }
+    }
+
     fn draw_img(&mut self, img: Img<'_>, rect: Rect) {
         match self {
             Renderer::Vger(v) => {
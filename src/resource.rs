@@ -0,0 +1,183 @@
+//! # Resources
+//!
+//! A resource binds the result of an async fetch, like a network request, to signals.
+//! ```rust,no_run
+//! use floem::reactive::create_rw_signal;
+//! use floem::resource::create_resource;
+//!
+//! let user_id = create_rw_signal(1);
+//! let user = create_resource(
+//!     move || user_id.get(),
+//!     |id| async move { Ok::<_, String>(format!("user {id}")) },
+//! );
+//! // `user.value`, `user.loading` and `user.error` can be used in views.
+//! ```
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Waker},
+};
+
+use floem_reactive::{untrack, ReadSignal, Scope, Trigger, WriteSignal};
+use parking_lot::Mutex;
+
+use crate::{executor, ext_event::EXT_EVENT_HANDLER};
+
+/// The state of an async fetch. See [`create_resource`].
+pub struct Resource<T: 'static, E: 'static> {
+    /// Whether a fetch is in flight.
+    pub loading: ReadSignal<bool>,
+    /// The value of the last successful fetch. It's kept while a new fetch is in flight.
+    pub value: ReadSignal<Option<T>>,
+    /// The error of the last fetch if it failed.
+    pub error: ReadSignal<Option<E>>,
+    refetch: Trigger,
+}
+
+impl<T, E> Copy for Resource<T, E> {}
+
+impl<T, E> Clone for Resource<T, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, E> Resource<T, E> {
+    /// Fetches again with the current source.
+    pub fn refetch(&self) {
+        self.refetch.notify();
+    }
+}
+
+/// The results of the fetches which completed, with the cancel state of their fetch.
+type Completed<T, E> = Arc<Mutex<VecDeque<(Arc<CancelState>, Option<Result<T, E>>)>>>;
+
+/// Creates a [`Resource`] which calls `fetcher` with the value of `source`, and again whenever
+/// `source` changes, and runs the returned future off the UI thread.
+///
+/// When the source changes while a fetch is in flight, the previous fetch is cancelled: its
+/// future is dropped the next time it's woken up, and its result is never applied.
+pub fn create_resource<S, T, E, Fut>(
+    source: impl Fn() -> S + 'static,
+    fetcher: impl Fn(S) -> Fut + 'static,
+) -> Resource<T, E>
+where
+    S: 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+{
+    let cx = Scope::current();
+    let (loading, set_loading) = cx.create_signal(false);
+    let (value, set_value) = cx.create_signal(None);
+    let (error, set_error) = cx.create_signal(None);
+    let refetch = cx.create_trigger();
+
+    // The results are sent back to the UI thread with the same trigger for all the fetches
+    let completed: Completed<T, E> = Default::default();
+    let done = cx.create_trigger();
+    {
+        let completed = completed.clone();
+        cx.create_effect(move |_| {
+            done.track();
+            let results: Vec<_> = completed.lock().drain(..).collect();
+            for (cancel, result) in results {
+                if !cancel.is_cancelled() {
+                    apply_result(result, set_loading, set_value, set_error);
+                }
+            }
+        });
+    }
+
+    cx.create_effect(move |prev: Option<Arc<CancelState>>| {
+        refetch.track();
+        let source = source();
+
+        if let Some(prev) = prev {
+            prev.cancel();
+        }
+        let cancel = Arc::new(CancelState::default());
+
+        set_loading.set(true);
+        let future = Cancellable {
+            future: Box::pin(untrack(|| fetcher(source))),
+            state: cancel.clone(),
+        };
+        let completed = completed.clone();
+        let state = cancel.clone();
+        executor::spawn(async move {
+            let result = future.await;
+            completed.lock().push_back((state, result));
+            EXT_EVENT_HANDLER.add_trigger(done);
+        });
+
+        cancel
+    });
+
+    Resource {
+        loading,
+        value,
+        error,
+        refetch,
+    }
+}
+
+fn apply_result<T: 'static, E: 'static>(
+    result: Option<Result<T, E>>,
+    set_loading: WriteSignal<bool>,
+    set_value: WriteSignal<Option<T>>,
+    set_error: WriteSignal<Option<E>>,
+) {
+    match result {
+        Some(Ok(value)) => {
+            set_value.set(Some(value));
+            set_error.set(None);
+        }
+        Some(Err(error)) => set_error.set(Some(error)),
+        None => {}
+    }
+    set_loading.set(false);
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl CancelState {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        if let Some(waker) = self.waker.lock().take() {
+            waker.wake();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// A future which resolves to `None` as soon as it's polled after being cancelled.
+struct Cancellable<T> {
+    future: Pin<Box<dyn Future<Output = T> + Send>>,
+    state: Arc<CancelState>,
+}
+
+impl<T> Future for Cancellable<T> {
+    type Output = Option<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.state.is_cancelled() {
+            return Poll::Ready(None);
+        }
+        *self.state.waker.lock() = Some(cx.waker().clone());
+        self.future.as_mut().poll(cx).map(Some)
+    }
+}
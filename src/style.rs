@@ -27,11 +27,15 @@
 
 use floem_peniko::Color;
 use floem_renderer::cosmic_text;
-use floem_renderer::cosmic_text::{LineHeightValue, Weight};
+use floem_renderer::cosmic_text::{FamilyOwned, LineHeightValue, Weight};
+pub use floem_renderer::TextAlign;
 use im_rc::hashmap::Entry;
 use rustc_hash::FxHasher;
+use smallvec::SmallVec;
 use std::any::{type_name, Any};
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::{self, Debug};
 use std::hash::Hasher;
 use std::hash::{BuildHasherDefault, Hash};
@@ -47,11 +51,13 @@ use taffy::{
     style::{LengthPercentage, Style as TaffyStyle, TrackSizingFunction},
 };
 
+use crate::animate::{Easing, EasingFn, EasingMode};
 use crate::context::InteractionState;
 use crate::responsive::{ScreenSize, ScreenSizeBp};
 use crate::unit::{Px, PxPct, PxPctAuto, UnitExt};
 use crate::view::View;
 use crate::views::{empty, stack, text, Decorators};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub trait StylePropValue: Clone + PartialEq + Debug {
     fn debug_view(&self) -> Option<Box<dyn View>> {
@@ -61,32 +67,373 @@ pub trait StylePropValue: Clone + PartialEq + Debug {
     fn interpolate(&self, _other: &Self, _value: f64) -> Option<Self> {
         None
     }
+
+    /// Parses a value written in a stylesheet, returning `None` if the text isn't valid or the
+    /// type can't be parsed. Lengths are written like `4px` or `4`, percentages like `50%`,
+    /// colors like `#rrggbb` or `#rrggbbaa`, keywords in kebab case like `flex-start`, and a
+    /// missing optional value as `none`.
+    fn parse(_text: &str) -> Option<Self> {
+        None
+    }
+
+    /// Writes the value in the format read by [`StylePropValue::parse`], returning `None` if the
+    /// value can't be written.
+    fn to_text(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Implements [`StylePropValue`] for an enum written as keywords.
+macro_rules! keyword_prop_value {
+    ($ty:ty { $($variant:path => $name:literal),* $(,)? }) => {
+        impl StylePropValue for $ty {
+            fn parse(text: &str) -> Option<Self> {
+                match text.trim() {
+                    $($name => Some($variant),)*
+                    _ => None,
+                }
+            }
+
+            #[allow(unreachable_patterns)]
+            fn to_text(&self) -> Option<String> {
+                match self {
+                    $($variant => Some($name.to_string()),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+fn parse_px(text: &str) -> Option<f64> {
+    let text = text.trim();
+    text.strip_suffix("px")
+        .unwrap_or(text)
+        .trim_end()
+        .parse()
+        .ok()
+}
+
+fn parse_pct(text: &str) -> Option<f64> {
+    text.trim().strip_suffix('%')?.trim_end().parse().ok()
 }
 
-impl StylePropValue for i32 {}
-impl StylePropValue for bool {}
-impl StylePropValue for f32 {}
+impl StylePropValue for i32 {
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+impl StylePropValue for usize {
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+impl StylePropValue for bool {
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+impl StylePropValue for f32 {
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        Some((*self as f64 * (1.0 - value) + *other as f64 * value) as f32)
+    }
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
 impl StylePropValue for f64 {
     fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
         Some(*self * (1.0 - value) + *other * value)
     }
+    fn parse(text: &str) -> Option<Self> {
+        text.trim().parse().ok()
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.to_string())
+    }
 }
-impl StylePropValue for Display {}
-impl StylePropValue for Position {}
-impl StylePropValue for FlexDirection {}
-impl StylePropValue for FlexWrap {}
-impl StylePropValue for AlignItems {}
-impl StylePropValue for AlignContent {}
+keyword_prop_value!(Display {
+    Display::Flex => "flex",
+    Display::Grid => "grid",
+    Display::None => "none",
+});
+keyword_prop_value!(Position {
+    Position::Relative => "relative",
+    Position::Absolute => "absolute",
+});
+keyword_prop_value!(FlexDirection {
+    FlexDirection::Row => "row",
+    FlexDirection::Column => "column",
+    FlexDirection::RowReverse => "row-reverse",
+    FlexDirection::ColumnReverse => "column-reverse",
+});
+keyword_prop_value!(FlexWrap {
+    FlexWrap::NoWrap => "nowrap",
+    FlexWrap::Wrap => "wrap",
+    FlexWrap::WrapReverse => "wrap-reverse",
+});
+keyword_prop_value!(AlignItems {
+    AlignItems::Start => "start",
+    AlignItems::End => "end",
+    AlignItems::FlexStart => "flex-start",
+    AlignItems::FlexEnd => "flex-end",
+    AlignItems::Center => "center",
+    AlignItems::Baseline => "baseline",
+    AlignItems::Stretch => "stretch",
+});
+keyword_prop_value!(AlignContent {
+    AlignContent::Start => "start",
+    AlignContent::End => "end",
+    AlignContent::FlexStart => "flex-start",
+    AlignContent::FlexEnd => "flex-end",
+    AlignContent::Center => "center",
+    AlignContent::Stretch => "stretch",
+    AlignContent::SpaceBetween => "space-between",
+    AlignContent::SpaceEvenly => "space-evenly",
+    AlignContent::SpaceAround => "space-around",
+});
 impl StylePropValue for TrackSizingFunction {}
 impl<T: StylePropValue> StylePropValue for Line<T> {}
 impl StylePropValue for GridPlacement {}
-impl StylePropValue for CursorStyle {}
-impl StylePropValue for BoxShadow {}
-impl StylePropValue for String {}
-impl StylePropValue for Weight {}
-impl StylePropValue for cosmic_text::Style {}
-impl StylePropValue for TextOverflow {}
-impl StylePropValue for LineHeightValue {}
+keyword_prop_value!(Direction {
+    Direction::Ltr => "ltr",
+    Direction::Rtl => "rtl",
+});
+keyword_prop_value!(CursorStyle {
+    CursorStyle::Default => "default",
+    CursorStyle::Pointer => "pointer",
+    CursorStyle::Text => "text",
+    CursorStyle::ColResize => "col-resize",
+    CursorStyle::RowResize => "row-resize",
+    CursorStyle::WResize => "w-resize",
+    CursorStyle::EResize => "e-resize",
+    CursorStyle::SResize => "s-resize",
+    CursorStyle::NResize => "n-resize",
+    CursorStyle::NwResize => "nw-resize",
+    CursorStyle::NeResize => "ne-resize",
+    CursorStyle::SwResize => "sw-resize",
+    CursorStyle::SeResize => "se-resize",
+    CursorStyle::NeswResize => "nesw-resize",
+    CursorStyle::NwseResize => "nwse-resize",
+});
+impl StylePropValue for BoxShadow {
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        if self.inset != other.inset {
+            return None;
+        }
+        Some(BoxShadow {
+            blur_radius: self.blur_radius.interpolate(&other.blur_radius, value)?,
+            color: self.color.interpolate(&other.color, value)?,
+            spread: self.spread.interpolate(&other.spread, value)?,
+            h_offset: self.h_offset.interpolate(&other.h_offset, value)?,
+            v_offset: self.v_offset.interpolate(&other.v_offset, value)?,
+            inset: self.inset,
+        })
+    }
+}
+impl StylePropValue for SmallVec<[BoxShadow; 3]> {
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        if self.len() != other.len() {
+            return None;
+        }
+        self.iter()
+            .zip(other.iter())
+            .map(|(this, other)| this.interpolate(other, value))
+            .collect()
+    }
+}
+impl StylePropValue for String {
+    fn parse(text: &str) -> Option<Self> {
+        Some(text.to_string())
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.clone())
+    }
+}
+impl StylePropValue for Gradient {
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        if self.stops.len() != other.stops.len() || self.color_space != other.color_space {
+            return None;
+        }
+        let shape = match (self.shape, other.shape) {
+            (GradientShape::Linear { angle: this }, GradientShape::Linear { angle: other }) => {
+                GradientShape::Linear {
+                    angle: this.interpolate(&other, value)?,
+                }
+            }
+            (
+                GradientShape::Radial {
+                    center: this_center,
+                    radius: this_radius,
+                },
+                GradientShape::Radial { center, radius },
+            ) => GradientShape::Radial {
+                center: (
+                    this_center.0.interpolate(&center.0, value)?,
+                    this_center.1.interpolate(&center.1, value)?,
+                ),
+                radius: this_radius.interpolate(&radius, value)?,
+            },
+            _ => return None,
+        };
+        let stops = self
+            .stops
+            .iter()
+            .zip(other.stops.iter())
+            .map(|((this_offset, this_color), (offset, color))| {
+                Some((
+                    this_offset.interpolate(offset, value)?,
+                    this_color.interpolate(color, value)?,
+                ))
+            })
+            .collect::<Option<_>>()?;
+        Some(Gradient {
+            shape,
+            stops,
+            color_space: self.color_space,
+        })
+    }
+}
+impl StylePropValue for Fill {
+    fn debug_view(&self) -> Option<Box<dyn View>> {
+        match self {
+            Fill::Solid(color) => color.debug_view(),
+            Fill::Gradient(_) => None,
+        }
+    }
+
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        match (self, other) {
+            (Fill::Solid(this), Fill::Solid(other)) => {
+                this.interpolate(other, value).map(Fill::Solid)
+            }
+            (Fill::Gradient(this), Fill::Gradient(other)) => {
+                this.interpolate(other, value).map(Fill::Gradient)
+            }
+            _ => None,
+        }
+    }
+
+    fn parse(text: &str) -> Option<Self> {
+        <Color as StylePropValue>::parse(text).map(Fill::Solid)
+    }
+
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Fill::Solid(color) => color.to_text(),
+            Fill::Gradient(_) => None,
+        }
+    }
+}
+impl StylePropValue for Weight {
+    fn parse(text: &str) -> Option<Self> {
+        match text.trim() {
+            "thin" => Some(Weight::THIN),
+            "extra-light" => Some(Weight::EXTRA_LIGHT),
+            "light" => Some(Weight::LIGHT),
+            "normal" => Some(Weight::NORMAL),
+            "medium" => Some(Weight::MEDIUM),
+            "semibold" => Some(Weight::SEMIBOLD),
+            "bold" => Some(Weight::BOLD),
+            "extra-bold" => Some(Weight::EXTRA_BOLD),
+            "black" => Some(Weight::BLACK),
+            text => text.parse().ok().map(Weight),
+        }
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(self.0.to_string())
+    }
+}
+keyword_prop_value!(cosmic_text::Style {
+    cosmic_text::Style::Normal => "normal",
+    cosmic_text::Style::Italic => "italic",
+    cosmic_text::Style::Oblique => "oblique",
+});
+keyword_prop_value!(ScrollSnapAlign {
+    ScrollSnapAlign::Start => "start",
+    ScrollSnapAlign::Center => "center",
+    ScrollSnapAlign::End => "end",
+});
+
+/// Written like `x mandatory` or `both proximity`, like the `scroll-snap-type` css property.
+impl StylePropValue for ScrollSnapType {
+    fn parse(text: &str) -> Option<Self> {
+        let (axis, strictness) = text.trim().split_once(' ')?;
+        let axis = match axis {
+            "x" => ScrollSnapAxis::X,
+            "y" => ScrollSnapAxis::Y,
+            "both" => ScrollSnapAxis::Both,
+            _ => return None,
+        };
+        let strictness = match strictness.trim() {
+            "mandatory" => ScrollSnapStrictness::Mandatory,
+            "proximity" => ScrollSnapStrictness::Proximity,
+            _ => return None,
+        };
+        Some(ScrollSnapType { axis, strictness })
+    }
+
+    fn to_text(&self) -> Option<String> {
+        let axis = match self.axis {
+            ScrollSnapAxis::X => "x",
+            ScrollSnapAxis::Y => "y",
+            ScrollSnapAxis::Both => "both",
+        };
+        let strictness = match self.strictness {
+            ScrollSnapStrictness::Mandatory => "mandatory",
+            ScrollSnapStrictness::Proximity => "proximity",
+        };
+        Some(format!("{axis} {strictness}"))
+    }
+}
+
+keyword_prop_value!(TextOverflow {
+    TextOverflow::Wrap => "wrap",
+    TextOverflow::Clip => "clip",
+    TextOverflow::Ellipsis => "ellipsis",
+});
+keyword_prop_value!(TextAlign {
+    TextAlign::Start => "start",
+    TextAlign::End => "end",
+    TextAlign::Left => "left",
+    TextAlign::Right => "right",
+    TextAlign::Center => "center",
+    TextAlign::Justify => "justify",
+});
+keyword_prop_value!(TextTransform {
+    TextTransform::None => "none",
+    TextTransform::Uppercase => "uppercase",
+    TextTransform::Lowercase => "lowercase",
+    TextTransform::Capitalize => "capitalize",
+});
+impl StylePropValue for LineHeightValue {
+    /// A multiple of the font size like `1.5`, or a height like `20px`.
+    fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        match text.strip_suffix("px") {
+            Some(px) => px.trim_end().parse().ok().map(LineHeightValue::Px),
+            None => text.parse().ok().map(LineHeightValue::Normal),
+        }
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(match self {
+            LineHeightValue::Normal(normal) => normal.to_string(),
+            LineHeightValue::Px(px) => format!("{px}px"),
+        })
+    }
+}
 impl StylePropValue for Size<LengthPercentage> {}
 
 impl<T: StylePropValue> StylePropValue for Option<T> {
@@ -101,6 +448,21 @@ impl<T: StylePropValue> StylePropValue for Option<T> {
                 .and_then(|other| this.interpolate(other, value).map(Some))
         })
     }
+
+    fn parse(text: &str) -> Option<Self> {
+        if text.trim() == "none" {
+            Some(None)
+        } else {
+            T::parse(text).map(Some)
+        }
+    }
+
+    fn to_text(&self) -> Option<String> {
+        match self {
+            Some(value) => value.to_text(),
+            None => Some("none".to_string()),
+        }
+    }
 }
 impl<T: StylePropValue> StylePropValue for Vec<T> {
     fn debug_view(&self) -> Option<Box<dyn View>> {
@@ -118,6 +480,12 @@ impl StylePropValue for Px {
     fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
         self.0.interpolate(&other.0, value).map(Px)
     }
+    fn parse(text: &str) -> Option<Self> {
+        parse_px(text).map(Px)
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(format!("{}px", self.0))
+    }
 }
 impl StylePropValue for PxPctAuto {
     fn debug_view(&self) -> Option<Box<dyn View>> {
@@ -128,6 +496,28 @@ impl StylePropValue for PxPctAuto {
         };
         Some(Box::new(text(label)))
     }
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        match (self, other) {
+            (Self::Px(this), Self::Px(other)) => this.interpolate(other, value).map(Self::Px),
+            (Self::Pct(this), Self::Pct(other)) => this.interpolate(other, value).map(Self::Pct),
+            _ => None,
+        }
+    }
+    fn parse(text: &str) -> Option<Self> {
+        if text.trim() == "auto" {
+            return Some(Self::Auto);
+        }
+        parse_pct(text)
+            .map(Self::Pct)
+            .or_else(|| parse_px(text).map(Self::Px))
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(match self {
+            Self::Px(v) => format!("{v}px"),
+            Self::Pct(v) => format!("{v}%"),
+            Self::Auto => "auto".to_string(),
+        })
+    }
 }
 impl StylePropValue for PxPct {
     fn debug_view(&self) -> Option<Box<dyn View>> {
@@ -137,6 +527,24 @@ impl StylePropValue for PxPct {
         };
         Some(Box::new(text(label)))
     }
+    fn interpolate(&self, other: &Self, value: f64) -> Option<Self> {
+        match (self, other) {
+            (Self::Px(this), Self::Px(other)) => this.interpolate(other, value).map(Self::Px),
+            (Self::Pct(this), Self::Pct(other)) => this.interpolate(other, value).map(Self::Pct),
+            _ => None,
+        }
+    }
+    fn parse(text: &str) -> Option<Self> {
+        parse_pct(text)
+            .map(Self::Pct)
+            .or_else(|| parse_px(text).map(Self::Px))
+    }
+    fn to_text(&self) -> Option<String> {
+        Some(match self {
+            Self::Px(v) => format!("{v}px"),
+            Self::Pct(v) => format!("{v}%"),
+        })
+    }
 }
 impl StylePropValue for Color {
     fn debug_view(&self) -> Option<Box<dyn View>> {
@@ -175,6 +583,36 @@ impl StylePropValue for Color {
             .round() as u8;
         Some(Color { r, g, b, a })
     }
+
+    fn parse(text: &str) -> Option<Self> {
+        let hex = text.trim().strip_prefix('#')?;
+        if !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        match hex.len() {
+            6 => Some(Color::rgb8(
+                (value >> 16) as u8,
+                (value >> 8) as u8,
+                value as u8,
+            )),
+            8 => Some(Color::rgba8(
+                (value >> 24) as u8,
+                (value >> 16) as u8,
+                (value >> 8) as u8,
+                value as u8,
+            )),
+            _ => None,
+        }
+    }
+
+    fn to_text(&self) -> Option<String> {
+        Some(if self.a == 255 {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        })
+    }
 }
 
 pub trait StyleClass: Default + Copy + 'static {
@@ -215,6 +653,61 @@ impl Debug for StyleClassRef {
     }
 }
 
+/// Registered classes by their full names.
+static CLASS_REGISTRY: parking_lot::Mutex<BTreeMap<&'static str, StyleClassRef>> =
+    parking_lot::const_mutex(BTreeMap::new());
+
+impl StyleClassRef {
+    /// The full name of the class type, including its module path.
+    pub fn name(&self) -> &'static str {
+        (self.info.name)()
+    }
+
+    /// The name of the class type without its module path.
+    pub fn short_name(&self) -> &'static str {
+        let name = self.name();
+        name.rsplit("::").next().unwrap_or(name)
+    }
+
+    /// Adds the class to the registry searched by [`StyleClassRef::by_name`]. Classes declared
+    /// with [`style_class!`](crate::style_class) are registered the first time their
+    /// [`class_ref`](StyleClass::class_ref) is called, which happens when they are used in a
+    /// style or on a view. The classes of the built-in widgets are always registered.
+    ///
+    /// If a class with the same full name is already registered, it's kept.
+    pub fn register(self) {
+        CLASS_REGISTRY.lock().entry(self.name()).or_insert(self);
+    }
+
+    fn registry() -> parking_lot::MutexGuard<'static, BTreeMap<&'static str, StyleClassRef>> {
+        static BUILTIN: std::sync::Once = std::sync::Once::new();
+        BUILTIN.call_once(crate::widgets::register_classes);
+        CLASS_REGISTRY.lock()
+    }
+
+    /// Looks up a registered class by its full name, like `my_app::sidebar::SidebarClass`, or by
+    /// its short name, like `SidebarClass`. When several classes have the same short name, the
+    /// one whose full name sorts first is returned, so use the full name to pick another.
+    pub fn by_name(name: &str) -> Option<StyleClassRef> {
+        let registry = Self::registry();
+        registry.get(name).copied().or_else(|| {
+            registry
+                .values()
+                .find(|class| class.short_name() == name)
+                .copied()
+        })
+    }
+
+    /// All registered classes, ordered by their full names.
+    pub fn registered() -> impl Iterator<Item = StyleClassRef> {
+        Self::registry()
+            .values()
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
 #[macro_export]
 macro_rules! style_class {
     ($v:vis $name:ident) => {
@@ -223,7 +716,10 @@ macro_rules! style_class {
         impl $crate::style::StyleClass for $name {
             fn class_ref() -> $crate::style::StyleClassRef {
                 static INFO: $crate::style::StyleClassInfo = $crate::style::StyleClassInfo::new::<$name>();
-                $crate::style::StyleClassRef { info: &INFO }
+                static REGISTER: std::sync::Once = std::sync::Once::new();
+                let class = $crate::style::StyleClassRef { info: &INFO };
+                REGISTER.call_once(|| class.register());
+                class
             }
         }
     };
@@ -242,6 +738,8 @@ pub struct StylePropInfo {
     pub(crate) default_as_any: fn() -> Rc<dyn Any>,
     pub(crate) debug_any: fn(val: &dyn Any) -> String,
     pub(crate) debug_view: fn(val: &dyn Any) -> Option<Box<dyn View>>,
+    pub(crate) parse_any: fn(text: &str) -> Option<Rc<dyn Any>>,
+    pub(crate) to_text_any: fn(val: &dyn Any) -> Option<String>,
 }
 
 impl StylePropInfo {
@@ -275,6 +773,8 @@ impl StylePropInfo {
                     )
                 }
             },
+            parse_any: |text| T::parse(text).map(|v| Rc::new(v) as Rc<dyn Any>),
+            to_text_any: |val| val.downcast_ref::<T>().and_then(|v| v.to_text()),
         }
     }
 }
@@ -339,7 +839,7 @@ impl<P: StyleProp> StylePropReader for P {
         );
         let changed = new != state.0;
         if changed {
-            state.1.transition(&Self::get(state), &new);
+            state.1.transition(&Self::get(state), &new, now);
             state.0 = new;
         }
         changed | state.1.step(now, request_transition)
@@ -517,6 +1017,9 @@ impl<T> StyleMapValue<T> {
 #[derive(Clone, Debug)]
 pub(crate) struct ActiveTransition<T: StylePropValue> {
     start: Instant,
+    /// The transition timing that was in effect when the value changed. This is kept for the
+    /// whole transition so that entering and leaving a selector can use different timings.
+    transition: Transition,
     before: T,
     current: T,
     after: T,
@@ -534,18 +1037,17 @@ impl<T: StylePropValue> TransitionState<T> {
         self.transition = transition;
     }
 
-    fn transition(&mut self, before: &T, after: &T) {
+    fn transition(&mut self, before: &T, after: &T, now: &Instant) {
         if !self.initial {
             return;
         }
-        if self.transition.is_some() {
-            self.active = Some(ActiveTransition {
-                start: Instant::now(),
-                before: before.clone(),
-                current: before.clone(),
-                after: after.clone(),
-            });
-        }
+        self.active = self.transition.clone().map(|transition| ActiveTransition {
+            start: *now,
+            transition,
+            before: before.clone(),
+            current: before.clone(),
+            after: after.clone(),
+        });
     }
 
     fn step(&mut self, now: &Instant, request_transition: &mut bool) -> bool {
@@ -554,16 +1056,14 @@ impl<T: StylePropValue> TransitionState<T> {
             self.initial = true;
         }
         if let Some(active) = &mut self.active {
-            if let Some(transition) = &self.transition {
-                let time = now.saturating_duration_since(active.start).as_secs_f64();
-                if time < transition.duration {
-                    if let Some(i) =
-                        T::interpolate(&active.before, &active.after, time / transition.duration)
-                    {
-                        active.current = i;
-                        *request_transition = true;
-                        return true;
-                    }
+            let transition = &active.transition;
+            let time = now.saturating_duration_since(active.start).as_secs_f64();
+            if time < transition.duration {
+                let progress = transition.easing.ease(time / transition.duration);
+                if let Some(i) = T::interpolate(&active.before, &active.after, progress) {
+                    active.current = i;
+                    *request_transition = true;
+                    return true;
                 }
             }
             self.active = None;
@@ -593,14 +1093,69 @@ impl<T: StylePropValue> Default for TransitionState<T> {
     }
 }
 
+/// Describes how a style property animates from its old value to its new value whenever
+/// it changes, similar to the CSS `transition` property.
+///
+/// Transitions are attached to a property with [`Style::transition`]:
+/// ```rust,ignore
+/// Style::new()
+///     .background(Color::WHITE)
+///     .transition(Background, Transition::linear(0.2).ease_out())
+///     .hover(|s| s.background(Color::LIGHT_GRAY))
+/// ```
+///
+/// The transition that applies is the one in effect for the state being entered. A transition
+/// set inside a selector is therefore used when entering that selector, while the base
+/// transition is used when leaving it. This allows e.g. a fast hover-in with a slow hover-out:
+/// ```rust,ignore
+/// Style::new()
+///     .transition(Background, Transition::linear(0.3))
+///     .hover(|s| {
+///         s.background(Color::LIGHT_GRAY)
+///             .transition(Background, Transition::linear(0.1))
+///     })
+/// ```
 #[derive(Clone, Debug)]
 pub struct Transition {
+    /// The duration of the transition in seconds
     duration: f64,
+    easing: Easing,
 }
 
 impl Transition {
+    /// A transition that runs for `duration` seconds with linear interpolation
     pub fn linear(duration: f64) -> Self {
-        Self { duration }
+        Self {
+            duration,
+            easing: Easing::default(),
+        }
+    }
+
+    pub fn easing_fn(mut self, easing_fn: EasingFn) -> Self {
+        self.easing.func = easing_fn;
+        self
+    }
+
+    pub fn ease_mode(mut self, mode: EasingMode) -> Self {
+        self.easing.mode = mode;
+        self
+    }
+
+    pub fn ease_in(self) -> Self {
+        self.ease_mode(EasingMode::In)
+    }
+
+    pub fn ease_out(self) -> Self {
+        self.ease_mode(EasingMode::Out)
+    }
+
+    pub fn ease_in_out(self) -> Self {
+        self.ease_mode(EasingMode::InOut)
+    }
+
+    /// The duration of the transition in seconds
+    pub fn duration(&self) -> f64 {
+        self.duration
     }
 }
 
@@ -611,6 +1166,16 @@ pub struct Style {
     pub(crate) map: ImHashMap<StylePropRef, StyleMapValue<Rc<dyn Any>>>,
     pub(crate) selectors: ImHashMap<StyleSelector, Style>,
     pub(crate) responsive: ImHashMap<ScreenSizeBp, Style>,
+    /// Styles applied when the window is at least as wide as the width they're paired with,
+    /// ordered by width.
+    pub(crate) breakpoints: im_rc::Vector<(f64, Style)>,
+    /// Styles applied when the view is at least as wide as the width they're paired with,
+    /// ordered by width.
+    pub(crate) container_queries: im_rc::Vector<(f64, Style)>,
+    /// Styles applied to the children at the positions they're paired with.
+    pub(crate) nth_child: im_rc::Vector<(NthChild, Style)>,
+    /// Styles applied when the view doesn't match the selector they're paired with.
+    pub(crate) negated: ImHashMap<StyleSelector, Style>,
     pub(crate) classes: ImHashMap<StyleClassRef, Style>,
     pub(crate) transitions: ImHashMap<StylePropRef, Transition>,
 }
@@ -654,7 +1219,12 @@ impl Style {
             .fold(StyleSelectors::default(), |s, (selector, map)| {
                 s.union(map.selectors()).set(*selector, true)
             });
-        result.responsive |= !self.responsive.is_empty();
+        // A negated selector depends on the same state as the selector
+        for (selector, map) in &self.negated {
+            result = result.union(map.selectors()).set(*selector, true);
+        }
+        result.responsive |= !self.responsive.is_empty() || !self.breakpoints.is_empty();
+        result.structural |= !self.nth_child.is_empty();
         result
     }
 
@@ -684,6 +1254,11 @@ impl Style {
                 self.apply_mut(map.apply_selectors(selectors));
             }
         }
+        for (selector, map) in std::mem::take(&mut self.negated) {
+            if !selectors.contains(&selector) {
+                self.apply_mut(map.apply_selectors(selectors));
+            }
+        }
         self
     }
 
@@ -691,33 +1266,152 @@ impl Style {
         &mut self,
         interact_state: &InteractionState,
         screen_size_bp: ScreenSizeBp,
+        window_width: f64,
+        container_width: f64,
     ) {
         if let Some(mut map) = self.responsive.remove(&screen_size_bp) {
-            map.apply_interact_state(interact_state, screen_size_bp);
+            map.apply_interact_state(
+                interact_state,
+                screen_size_bp,
+                window_width,
+                container_width,
+            );
             self.apply_mut(map);
         }
 
+        // Wider breakpoints come later, so they take precedence
+        for (min_width, mut map) in std::mem::take(&mut self.breakpoints) {
+            if window_width >= min_width {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+        for (min_width, mut map) in std::mem::take(&mut self.container_queries) {
+            if container_width >= min_width {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+
+        if let Some((index, count)) = interact_state.child_position {
+            let mut structural = Vec::new();
+            if index == 0 {
+                structural.extend(self.selectors.remove(&StyleSelector::FirstChild));
+            }
+            if index + 1 == count {
+                structural.extend(self.selectors.remove(&StyleSelector::LastChild));
+            }
+            for (nth, map) in std::mem::take(&mut self.nth_child) {
+                if nth.matches(index) {
+                    structural.push(map);
+                }
+            }
+            for mut map in structural {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+        if interact_state.is_empty {
+            if let Some(mut map) = self.selectors.remove(&StyleSelector::Empty) {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+        for (selector, mut map) in std::mem::take(&mut self.negated) {
+            if !selector.matches(interact_state) {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+
         if interact_state.is_hovered && !interact_state.is_disabled {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::Hover) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
         if interact_state.is_focused {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::Focus) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+        if interact_state.is_focus_within {
+            if let Some(mut map) = self.selectors.remove(&StyleSelector::FocusWithin) {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
         if interact_state.is_selected {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::Selected) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
         if interact_state.is_disabled {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::Disabled) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
+                self.apply_mut(map);
+            }
+        }
+        if interact_state.is_checked {
+            if let Some(mut map) = self.selectors.remove(&StyleSelector::Checked) {
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
@@ -727,7 +1421,12 @@ impl Style {
 
         if focused_keyboard {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::FocusVisible) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
@@ -735,7 +1434,12 @@ impl Style {
         let active_mouse = interact_state.is_hovered && !interact_state.using_keyboard_navigation;
         if interact_state.is_clicking && (active_mouse || focused_keyboard) {
             if let Some(mut map) = self.selectors.remove(&StyleSelector::Active) {
-                map.apply_interact_state(interact_state, screen_size_bp);
+                map.apply_interact_state(
+                    interact_state,
+                    screen_size_bp,
+                    window_width,
+                    container_width,
+                );
                 self.apply_mut(map);
             }
         }
@@ -761,7 +1465,7 @@ impl Style {
         }
     }
 
-    fn set_selector(&mut self, selector: StyleSelector, map: Style) {
+    pub(crate) fn set_selector(&mut self, selector: StyleSelector, map: Style) {
         match self.selectors.entry(selector) {
             Entry::Occupied(mut e) => e.get_mut().apply_mut(map),
             Entry::Vacant(e) => {
@@ -779,7 +1483,50 @@ impl Style {
         }
     }
 
-    fn set_class(&mut self, class: StyleClassRef, map: Style) {
+    /// Adds a style to a list of styles ordered by the minimum width they apply at.
+    fn insert_min_width(queries: &mut im_rc::Vector<(f64, Style)>, min_width: f64, map: Style) {
+        let index = queries.iter().position(|(width, _)| *width >= min_width);
+        match index {
+            Some(i) if queries[i].0 == min_width => queries[i].1.apply_mut(map),
+            Some(i) => queries.insert(i, (min_width, map)),
+            None => queries.push_back((min_width, map)),
+        }
+    }
+
+    fn set_negated(&mut self, selector: StyleSelector, map: Style) {
+        match self.negated.entry(selector) {
+            Entry::Occupied(mut e) => e.get_mut().apply_mut(map),
+            Entry::Vacant(e) => {
+                e.insert(map);
+            }
+        }
+    }
+
+    fn set_nth_child(&mut self, nth: NthChild, map: Style) {
+        match self.nth_child.iter().position(|(other, _)| *other == nth) {
+            Some(i) => self.nth_child[i].1.apply_mut(map),
+            None => self.nth_child.push_back((nth, map)),
+        }
+    }
+
+    /// Collects the widths the container queries of this style, and of the styles nested in it,
+    /// start applying at.
+    pub(crate) fn container_query_widths(&self, widths: &mut Vec<f64>) {
+        widths.extend(self.container_queries.iter().map(|(width, _)| *width));
+        let nested = self
+            .selectors
+            .values()
+            .chain(self.responsive.values())
+            .chain(self.breakpoints.iter().map(|(_, map)| map))
+            .chain(self.container_queries.iter().map(|(_, map)| map))
+            .chain(self.nth_child.iter().map(|(_, map)| map))
+            .chain(self.negated.values());
+        for map in nested {
+            map.container_query_widths(widths);
+        }
+    }
+
+    pub(crate) fn set_class(&mut self, class: StyleClassRef, map: Style) {
         match self.classes.entry(class) {
             Entry::Occupied(mut e) => e.get_mut().apply_mut(map),
             Entry::Vacant(e) => {
@@ -801,6 +1548,18 @@ impl Style {
         for (breakpoint, map) in over.responsive {
             self.set_breakpoint(breakpoint, map);
         }
+        for (min_width, map) in over.breakpoints {
+            Self::insert_min_width(&mut self.breakpoints, min_width, map);
+        }
+        for (min_width, map) in over.container_queries {
+            Self::insert_min_width(&mut self.container_queries, min_width, map);
+        }
+        for (nth, map) in over.nth_child {
+            self.set_nth_child(nth, map);
+        }
+        for (selector, map) in over.negated {
+            self.set_negated(selector, map);
+        }
         for (class, map) in over.classes {
             self.set_class(class, map);
         }
@@ -846,6 +1605,10 @@ impl Debug for Style {
             .field("selectors", &self.selectors)
             .field("classes", &self.classes)
             .field("responsive", &self.responsive)
+            .field("breakpoints", &self.breakpoints)
+            .field("container_queries", &self.container_queries)
+            .field("nth_child", &self.nth_child)
+            .field("negated", &self.negated)
             .field("transitions", &self.transitions)
             .finish()
     }
@@ -856,46 +1619,267 @@ pub enum StyleSelector {
     Hover,
     Focus,
     FocusVisible,
+    FocusWithin,
     Disabled,
     Active,
     Dragging,
     Selected,
+    Checked,
+    FirstChild,
+    LastChild,
+    Empty,
+    Placeholder,
+}
+
+impl StyleSelector {
+    const ALL: [StyleSelector; 13] = [
+        StyleSelector::Hover,
+        StyleSelector::Focus,
+        StyleSelector::FocusVisible,
+        StyleSelector::FocusWithin,
+        StyleSelector::Disabled,
+        StyleSelector::Active,
+        StyleSelector::Dragging,
+        StyleSelector::Selected,
+        StyleSelector::Checked,
+        StyleSelector::FirstChild,
+        StyleSelector::LastChild,
+        StyleSelector::Empty,
+        StyleSelector::Placeholder,
+    ];
+
+    /// The name of the selector in stylesheets and serialized styles, like `focus_visible`.
+    pub fn name(self) -> &'static str {
+        match self {
+            StyleSelector::Hover => "hover",
+            StyleSelector::Focus => "focus",
+            StyleSelector::FocusVisible => "focus_visible",
+            StyleSelector::FocusWithin => "focus_within",
+            StyleSelector::Disabled => "disabled",
+            StyleSelector::Active => "active",
+            StyleSelector::Dragging => "dragging",
+            StyleSelector::Selected => "selected",
+            StyleSelector::Checked => "checked",
+            StyleSelector::FirstChild => "first_child",
+            StyleSelector::LastChild => "last_child",
+            StyleSelector::Empty => "empty",
+            StyleSelector::Placeholder => "placeholder",
+        }
+    }
+
+    /// Whether a view in `state` matches the selector. The dragging selector is only applied
+    /// while painting the dragged view, and the placeholder selector to the placeholder text of
+    /// a text input, so they never match here.
+    pub(crate) fn matches(self, state: &InteractionState) -> bool {
+        let focused_keyboard = state.using_keyboard_navigation && state.is_focused;
+        match self {
+            StyleSelector::Hover => state.is_hovered && !state.is_disabled,
+            StyleSelector::Focus => state.is_focused,
+            StyleSelector::FocusVisible => focused_keyboard,
+            StyleSelector::FocusWithin => state.is_focus_within,
+            StyleSelector::Disabled => state.is_disabled,
+            StyleSelector::Active => {
+                let active_mouse = state.is_hovered && !state.using_keyboard_navigation;
+                state.is_clicking && (active_mouse || focused_keyboard)
+            }
+            StyleSelector::Dragging | StyleSelector::Placeholder => false,
+            StyleSelector::Selected => state.is_selected,
+            StyleSelector::Checked => state.is_checked,
+            StyleSelector::FirstChild => state.child_position.is_some_and(|(index, _)| index == 0),
+            StyleSelector::LastChild => state
+                .child_position
+                .is_some_and(|(index, count)| index + 1 == count),
+            StyleSelector::Empty => state.is_empty,
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<StyleSelector> {
+        Self::ALL
+            .into_iter()
+            .find(|selector| selector.name() == name)
+    }
+}
+
+/// The positions among its siblings a view matches with [`Style::nth_child`]: the positions
+/// `step * n + offset` for any `n` from 0, where the first child is at position 1, like
+/// `:nth-child(2n+1)` in css.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct NthChild {
+    pub step: i32,
+    pub offset: i32,
+}
+
+impl NthChild {
+    /// The children at odd positions: the first, the third and so on.
+    pub const ODD: NthChild = NthChild { step: 2, offset: 1 };
+    /// The children at even positions: the second, the fourth and so on.
+    pub const EVEN: NthChild = NthChild { step: 2, offset: 0 };
+
+    pub fn new(step: i32, offset: i32) -> Self {
+        Self { step, offset }
+    }
+
+    /// Only the child at `position`, counted from 1.
+    pub fn position(position: i32) -> Self {
+        Self::new(0, position)
+    }
+
+    /// Whether the child at `index`, counted from 0, matches.
+    pub fn matches(self, index: usize) -> bool {
+        let position = index as i64 + 1;
+        let (step, offset) = (self.step as i64, self.offset as i64);
+        if step == 0 {
+            return position == offset;
+        }
+        let distance = position - offset;
+        distance % step == 0 && distance / step >= 0
+    }
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Default)]
 pub(crate) struct StyleSelectors {
-    selectors: u8,
+    selectors: u16,
     responsive: bool,
+    /// Whether there are `nth_child` styles.
+    structural: bool,
 }
 
 impl StyleSelectors {
     pub(crate) fn set(mut self, selector: StyleSelector, value: bool) -> Self {
         let v = (selector as isize).try_into().unwrap();
-        let bit = 1_u8.checked_shl(v).unwrap();
-        self.selectors = (self.selectors & !bit) | ((value as u8) << v);
+        let bit = 1_u16.checked_shl(v).unwrap();
+        self.selectors = (self.selectors & !bit) | ((value as u16) << v);
         self
     }
     pub(crate) fn has(self, selector: StyleSelector) -> bool {
         let v = (selector as isize).try_into().unwrap();
-        let bit = 1_u8.checked_shl(v).unwrap();
+        let bit = 1_u16.checked_shl(v).unwrap();
         self.selectors & bit != 0
     }
     pub(crate) fn union(self, other: StyleSelectors) -> StyleSelectors {
         StyleSelectors {
             selectors: self.selectors | other.selectors,
             responsive: self.responsive | other.responsive,
+            structural: self.structural | other.structural,
         }
     }
     pub(crate) fn has_responsive(self) -> bool {
         self.responsive
     }
+    /// Whether the style depends on the position of the view among its siblings.
+    pub(crate) fn has_structural(self) -> bool {
+        self.structural || self.has(StyleSelector::FirstChild) || self.has(StyleSelector::LastChild)
+    }
+}
+
+/// The direction text is written in, which decides the side flex rows start from.
+///
+/// With [`Direction::Rtl`], rows are laid out from the right, the start and end alignments of
+/// columns are swapped, and text is shaped as a right-to-left paragraph. Physical properties,
+/// like `padding_left`, keep their side. Grid layouts aren't mirrored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Direction {
+    #[default]
+    Ltr,
+    Rtl,
+}
+
+impl Direction {
+    pub fn is_rtl(self) -> bool {
+        self == Direction::Rtl
+    }
 }
 
+// Set on the children of right-to-left columns, whose `align_self` is on the mirrored cross axis
+// of their parent.
+prop!(pub(crate) InRtlColumn: bool {} = false);
+
+/// The axes a scroll view snaps on, see [`Style::scroll_snap_type`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TextOverflow {
-    Wrap,
-    Clip,
-    Ellipsis,
+pub enum ScrollSnapAxis {
+    X,
+    Y,
+    Both,
+}
+
+/// Whether a scroll view always settles on a snap position, or only when it stops near one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollSnapStrictness {
+    Mandatory,
+    Proximity,
+}
+
+/// How a scroll view snaps, see [`Style::scroll_snap_type`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrollSnapType {
+    pub axis: ScrollSnapAxis,
+    pub strictness: ScrollSnapStrictness,
+}
+
+/// Which edge of a view a scroll view snaps to, see [`Style::scroll_snap_align`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollSnapAlign {
+    Start,
+    Center,
+    End,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextOverflow {
+    Wrap,
+    Clip,
+    Ellipsis,
+}
+
+/// How the case of text is changed when it's shown, see [`Style::text_transform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextTransform {
+    None,
+    Uppercase,
+    Lowercase,
+    /// Makes the first letter of each word uppercase, leaving the others as they are.
+    Capitalize,
+}
+
+impl TextTransform {
+    /// Changes the case of `text`, with the casing rules of the language `lang` if it's set,
+    /// like the dotted and dotless i of Turkish and Azerbaijani.
+    pub fn apply<'a>(self, text: &'a str, lang: Option<&str>) -> Cow<'a, str> {
+        // Turkic languages have a dotted and a dotless i, where others only have the first
+        let turkic = lang.is_some_and(|lang| {
+            let primary = lang.split(|c| c == '-' || c == '_').next().unwrap_or(lang);
+            primary.eq_ignore_ascii_case("tr") || primary.eq_ignore_ascii_case("az")
+        });
+        let uppercase = |text: &str| {
+            if turkic {
+                text.replace('i', "İ").to_uppercase()
+            } else {
+                text.to_uppercase()
+            }
+        };
+        match self {
+            TextTransform::None => Cow::Borrowed(text),
+            TextTransform::Uppercase => Cow::Owned(uppercase(text)),
+            TextTransform::Lowercase if turkic => {
+                Cow::Owned(text.replace('İ', "i").replace('I', "ı").to_lowercase())
+            }
+            TextTransform::Lowercase => Cow::Owned(text.to_lowercase()),
+            TextTransform::Capitalize => Cow::Owned(
+                text.split_word_bounds()
+                    .map(|word| {
+                        let mut chars = word.chars();
+                        match chars.next() {
+                            Some(first) if first.is_alphabetic() => {
+                                let first = &word[..first.len_utf8()];
+                                format!("{}{}", uppercase(first), chars.as_str())
+                            }
+                            _ => word.to_string(),
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -917,6 +1901,9 @@ pub enum CursorStyle {
     NwseResize,
 }
 
+/// A single shadow of the `box-shadow` property.
+///
+/// Multiple shadows can be stacked, the first shadow is painted on top.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct BoxShadow {
     pub blur_radius: PxPct,
@@ -924,6 +1911,8 @@ pub struct BoxShadow {
     pub spread: PxPct,
     pub h_offset: PxPct,
     pub v_offset: PxPct,
+    /// Paint the shadow inside the view's box instead of outside of it
+    pub inset: bool,
 }
 
 impl Default for BoxShadow {
@@ -934,10 +1923,218 @@ impl Default for BoxShadow {
             spread: PxPct::Px(0.),
             h_offset: PxPct::Px(0.),
             v_offset: PxPct::Px(0.),
+            inset: false,
         }
     }
 }
 
+/// The color space in which the colors between two gradient stops are interpolated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GradientColorSpace {
+    /// Interpolate the gamma encoded sRGB components. This matches the default of CSS gradients.
+    #[default]
+    Srgb,
+    /// Interpolate in linear light, which avoids the darkened midpoints between saturated colors.
+    LinearSrgb,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GradientShape {
+    /// A gradient along a line through the center of the view. The angle is in degrees where 0
+    /// points up and 90 points right, like CSS `linear-gradient`.
+    Linear { angle: f64 },
+    /// A circular gradient. `center` is relative to the view's size, so `(0.5, 0.5)` is the
+    /// center of the view. A percentage `radius` is relative to the distance from the center
+    /// to the farthest corner.
+    Radial { center: (f64, f64), radius: PxPct },
+}
+
+/// A gradient which is laid out relative to the view it's painted in.
+///
+/// The vger renderer can only draw linear gradients between two colors. It draws the colors of
+/// the first and last stops at their offsets, without the stops between them or the steps of
+/// [`GradientColorSpace::LinearSrgb`], and doesn't draw radial gradients. The tiny-skia renderer
+/// draws every gradient.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gradient {
+    pub shape: GradientShape,
+    pub stops: SmallVec<[(f32, Color); 4]>,
+    pub color_space: GradientColorSpace,
+}
+
+impl Gradient {
+    /// Number of stops inserted between two stops when interpolating in linear light.
+    const LINEAR_SRGB_STEPS: usize = 8;
+
+    pub fn linear(angle: f64) -> Self {
+        Self {
+            shape: GradientShape::Linear { angle },
+            stops: SmallVec::new(),
+            color_space: GradientColorSpace::default(),
+        }
+    }
+
+    pub fn radial(radius: impl Into<PxPct>) -> Self {
+        Self {
+            shape: GradientShape::Radial {
+                center: (0.5, 0.5),
+                radius: radius.into(),
+            },
+            stops: SmallVec::new(),
+            color_space: GradientColorSpace::default(),
+        }
+    }
+
+    /// Sets the center of a radial gradient, relative to the view's size.
+    pub fn center(mut self, x: f64, y: f64) -> Self {
+        if let GradientShape::Radial { center, .. } = &mut self.shape {
+            *center = (x, y);
+        }
+        self
+    }
+
+    /// Adds a color stop. `offset` ranges from 0.0 to 1.0.
+    pub fn stop(mut self, offset: f32, color: Color) -> Self {
+        self.stops.push((offset, color));
+        self
+    }
+
+    pub fn color_space(mut self, color_space: GradientColorSpace) -> Self {
+        self.color_space = color_space;
+        self
+    }
+
+    fn resolved_stops(&self) -> floem_peniko::ColorStops {
+        let stop = |offset, color| floem_peniko::ColorStop { offset, color };
+        match self.color_space {
+            GradientColorSpace::Srgb => self.stops.iter().map(|(o, c)| stop(*o, *c)).collect(),
+            GradientColorSpace::LinearSrgb => {
+                // Renderers interpolate in sRGB, so approximate linear interpolation with
+                // additional stops.
+                let mut stops = floem_peniko::ColorStops::new();
+                for pair in self.stops.windows(2) {
+                    let ((from_offset, from), (to_offset, to)) = (pair[0], pair[1]);
+                    for step in 0..Self::LINEAR_SRGB_STEPS {
+                        let t = step as f32 / Self::LINEAR_SRGB_STEPS as f32;
+                        let offset = from_offset + (to_offset - from_offset) * t;
+                        stops.push(stop(offset, lerp_linear_srgb(from, to, t)));
+                    }
+                }
+                if let Some((offset, color)) = self.stops.last() {
+                    stops.push(stop(*offset, *color));
+                }
+                stops
+            }
+        }
+    }
+
+    /// Creates the brush used to paint this gradient within `rect`.
+    pub fn to_brush(&self, rect: kurbo::Rect) -> floem_peniko::Brush {
+        let center = rect.center();
+        let kind = match self.shape {
+            GradientShape::Linear { angle } => {
+                let (sin, cos) = angle.to_radians().sin_cos();
+                let direction = kurbo::Vec2::new(sin, -cos);
+                // The gradient line is long enough for the corners to reach the first and last
+                // stops, like in CSS.
+                let half_length = (rect.width() * sin.abs() + rect.height() * cos.abs()) / 2.0;
+                floem_peniko::GradientKind::Linear {
+                    start: center - direction * half_length,
+                    end: center + direction * half_length,
+                }
+            }
+            GradientShape::Radial {
+                center: (x, y),
+                radius,
+            } => {
+                let center =
+                    kurbo::Point::new(rect.x0 + rect.width() * x, rect.y0 + rect.height() * y);
+                let radius = match radius {
+                    PxPct::Px(px) => px,
+                    PxPct::Pct(pct) => {
+                        let farthest = [
+                            rect.origin(),
+                            kurbo::Point::new(rect.x1, rect.y0),
+                            kurbo::Point::new(rect.x0, rect.y1),
+                            kurbo::Point::new(rect.x1, rect.y1),
+                        ]
+                        .into_iter()
+                        .map(|corner| corner.distance(center))
+                        .fold(0.0, f64::max);
+                        farthest * pct / 100.0
+                    }
+                };
+                floem_peniko::GradientKind::Radial {
+                    start_center: center,
+                    start_radius: 0.0,
+                    end_center: center,
+                    end_radius: radius as f32,
+                }
+            }
+        };
+        floem_peniko::Brush::Gradient(floem_peniko::Gradient {
+            kind,
+            extend: floem_peniko::Extend::Pad,
+            stops: self.resolved_stops(),
+        })
+    }
+}
+
+fn lerp_linear_srgb(from: Color, to: Color, t: f32) -> Color {
+    fn to_linear(c: u8) -> f32 {
+        let c = c as f32 / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    }
+    fn from_linear(c: f32) -> u8 {
+        let c = if c <= 0.0031308 {
+            c * 12.92
+        } else {
+            1.055 * c.powf(1.0 / 2.4) - 0.055
+        };
+        (c * 255.0).round().clamp(0.0, 255.0) as u8
+    }
+    let lerp = |a: u8, b: u8| from_linear(to_linear(a) + (to_linear(b) - to_linear(a)) * t);
+    Color {
+        r: lerp(from.r, to.r),
+        g: lerp(from.g, to.g),
+        b: lerp(from.b, to.b),
+        a: (from.a as f32 + (to.a as f32 - from.a as f32) * t).round() as u8,
+    }
+}
+
+/// What to fill an area such as the background of a view with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fill {
+    Solid(Color),
+    Gradient(Gradient),
+}
+
+impl Fill {
+    /// Creates the brush used to paint this fill within `rect`.
+    pub fn to_brush(&self, rect: kurbo::Rect) -> floem_peniko::Brush {
+        match self {
+            Fill::Solid(color) => floem_peniko::Brush::Solid(*color),
+            Fill::Gradient(gradient) => gradient.to_brush(rect),
+        }
+    }
+}
+
+impl From<Color> for Fill {
+    fn from(color: Color) -> Self {
+        Fill::Solid(color)
+    }
+}
+
+impl From<Gradient> for Fill {
+    fn from(gradient: Gradient) -> Self {
+        Fill::Gradient(gradient)
+    }
+}
+
 /// The value for a [`Style`] property
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum StyleValue<T> {
@@ -1018,6 +2215,28 @@ macro_rules! define_builtin_props {
                 }
             )*
         }
+
+        impl StylePropRef {
+            /// Looks up a built-in property by the name of its getter, like `padding_left`.
+            pub fn builtin(name: &str) -> Option<StylePropRef> {
+                match name {
+                    $(
+                        stringify!($name) => Some($type_name::prop_ref()),
+                    )*
+                    _ => None,
+                }
+            }
+
+            /// The name of a built-in property, which [`StylePropRef::builtin`] looks up.
+            pub fn builtin_name(&self) -> Option<&'static str> {
+                $(
+                    if *self == $type_name::prop_ref() {
+                        return Some(stringify!($name));
+                    }
+                )*
+                None
+            }
+        }
     };
     (decl: $type_name:ident $name:ident nocb: $typ:ty = $val:expr) => {};
     (decl: $type_name:ident $name:ident: $typ:ty = $val:expr) => {
@@ -1059,9 +2278,17 @@ define_builtin_props!(
     BorderRight border_right: Px {} = Px(0.0),
     BorderBottom border_bottom: Px {} = Px(0.0),
     BorderRadius border_radius: PxPct {} = PxPct::Px(0.0),
+    BorderTopLeftRadius border_top_left_radius nocb: Option<PxPct> {} = None,
+    BorderTopRightRadius border_top_right_radius nocb: Option<PxPct> {} = None,
+    BorderBottomLeftRadius border_bottom_left_radius nocb: Option<PxPct> {} = None,
+    BorderBottomRightRadius border_bottom_right_radius nocb: Option<PxPct> {} = None,
     OutlineColor outline_color: Color {} = Color::TRANSPARENT,
     Outline outline: Px {} = Px(0.0),
     BorderColor border_color: Color {} = Color::BLACK,
+    BorderLeftColor border_left_color nocb: Option<Color> {} = None,
+    BorderTopColor border_top_color nocb: Option<Color> {} = None,
+    BorderRightColor border_right_color nocb: Option<Color> {} = None,
+    BorderBottomColor border_bottom_color nocb: Option<Color> {} = None,
     PaddingLeft padding_left: PxPct {} = PxPct::Px(0.0),
     PaddingTop padding_top: PxPct {} = PxPct::Px(0.0),
     PaddingRight padding_right: PxPct {} = PxPct::Px(0.0),
@@ -1077,29 +2304,111 @@ define_builtin_props!(
     ZIndex z_index nocb: Option<i32> {} = None,
     Cursor cursor nocb: Option<CursorStyle> {} = None,
     TextColor color nocb: Option<Color> { inherited } = None,
-    Background background nocb: Option<Color> {} = None,
+    Background background nocb: Option<Fill> {} = None,
     Foreground foreground nocb: Option<Color> {} = None,
-    BoxShadowProp box_shadow nocb: Option<BoxShadow> {} = None,
+    BoxShadowProp box_shadow nocb: SmallVec<[BoxShadow; 3]> {} = SmallVec::new(),
     FontSize font_size nocb: Option<f32> { inherited } = None,
     FontFamily font_family nocb: Option<String> { inherited } = None,
+    FontFallback font_fallback nocb: Option<String> { inherited } = None,
     FontWeight font_weight nocb: Option<Weight> { inherited } = None,
     FontStyle font_style nocb: Option<cosmic_text::Style> { inherited } = None,
     CursorColor cursor_color nocb: Option<Color> {} = None,
     TextOverflowProp text_overflow: TextOverflow {} = TextOverflow::Wrap,
+    LineClamp line_clamp nocb: Option<usize> {} = None,
+    LetterSpacing letter_spacing nocb: f32 { inherited } = 0.0,
+    WordSpacing word_spacing nocb: f32 { inherited } = 0.0,
+    TextTransformProp text_transform nocb: TextTransform { inherited } = TextTransform::None,
+    Lang lang nocb: Option<String> { inherited } = None,
+    TextAlignProp text_align nocb: TextAlign { inherited } = TextAlign::Start,
+    DirectionProp direction: Direction { inherited } = Direction::Ltr,
+    PaddingInlineStart padding_inline_start nocb: Option<PxPct> {} = None,
+    PaddingInlineEnd padding_inline_end nocb: Option<PxPct> {} = None,
+    PaddingBlockStart padding_block_start nocb: Option<PxPct> {} = None,
+    PaddingBlockEnd padding_block_end nocb: Option<PxPct> {} = None,
+    MarginInlineStart margin_inline_start nocb: Option<PxPctAuto> {} = None,
+    MarginInlineEnd margin_inline_end nocb: Option<PxPctAuto> {} = None,
+    MarginBlockStart margin_block_start nocb: Option<PxPctAuto> {} = None,
+    MarginBlockEnd margin_block_end nocb: Option<PxPctAuto> {} = None,
     LineHeight line_height nocb: Option<LineHeightValue> { inherited } = None,
     AspectRatio aspect_ratio: Option<f32> {} = None,
     Gap gap nocb: Size<LengthPercentage> {} = Size::zero(),
+    ScrollSnapTypeProp scroll_snap_type nocb: Option<ScrollSnapType> {} = None,
+    ScrollSnapAlignProp scroll_snap_align nocb: Option<ScrollSnapAlign> {} = None,
+    PixelSnap pixel_snap nocb: bool { inherited } = false,
 );
 
 prop_extracter! {
     pub FontProps {
         pub size: FontSize,
         pub family: FontFamily,
+        pub fallback: FontFallback,
         pub weight: FontWeight,
         pub style: FontStyle,
     }
 }
 
+thread_local! {
+    static FONT_FALLBACK: RefCell<Vec<FamilyOwned>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Sets the fonts used for the characters which aren't in the font of a view, in the order
+/// they're tried, after the fallback of the view set with [`Style::font_fallback`]. Each
+/// character missing from a font is looked up in the next one, so text mixing scripts is shown
+/// with the first font having each of its characters, and an emoji font like
+/// `Noto Color Emoji` at the end of the list shows emojis in any text. The system fonts are
+/// still tried after the list, which only sets which fonts are preferred.
+///
+/// It should be set before the windows are created, as text which is already laid out keeps
+/// its fonts until it changes.
+/// ```rust
+/// use floem::cosmic_text::FamilyOwned;
+/// use floem::style::set_font_fallback;
+///
+/// set_font_fallback(vec![
+///     FamilyOwned::Name("Noto Sans CJK JP".to_string()),
+///     FamilyOwned::Name("Noto Sans Arabic".to_string()),
+///     FamilyOwned::Name("Noto Color Emoji".to_string()),
+/// ]);
+/// ```
+pub fn set_font_fallback(families: Vec<FamilyOwned>) {
+    FONT_FALLBACK.with(|fallback| *fallback.borrow_mut() = families);
+}
+
+impl FontProps {
+    /// The fonts to lay out text with, which are the font family, then the fallback of the view
+    /// and the one set with [`set_font_fallback`]. It's `None` when the default font is used
+    /// without any fallback.
+    pub fn families(&self) -> Option<Vec<FamilyOwned>> {
+        let mut families: Vec<FamilyOwned> = match self.family() {
+            Some(family) => FamilyOwned::parse_list(&family).collect(),
+            None => Vec::new(),
+        };
+        let has_family = !families.is_empty();
+        if let Some(fallback) = self.fallback() {
+            families.extend(FamilyOwned::parse_list(&fallback));
+        }
+        FONT_FALLBACK.with(|fallback| families.extend(fallback.borrow().iter().cloned()));
+        if families.is_empty() {
+            return None;
+        }
+        if !has_family {
+            // The fallback follows the default font
+            families.insert(0, FamilyOwned::SansSerif);
+        }
+        // A font listed twice is only tried the first time
+        let mut seen = Vec::with_capacity(families.len());
+        families.retain(|family| {
+            if seen.contains(family) {
+                false
+            } else {
+                seen.push(family.clone());
+                true
+            }
+        });
+        Some(families)
+    }
+}
+
 prop_extracter! {
     pub(crate) LayoutProps {
         pub border_left: BorderLeft,
@@ -1144,6 +2453,11 @@ impl Style {
         self
     }
 
+    /// Sets a property to a value of its type, like one returned by the parser of the property.
+    pub(crate) fn set_raw(&mut self, prop: StylePropRef, value: Rc<dyn Any>) {
+        self.map.insert(prop, StyleMapValue::Val(value));
+    }
+
     fn selector(mut self, selector: StyleSelector, style: impl FnOnce(Style) -> Style) -> Self {
         let over = style(Style::default());
         self.set_selector(selector, over);
@@ -1159,23 +2473,92 @@ impl Style {
         self.selector(StyleSelector::Focus, style)
     }
 
-    /// Similar to the `:focus-visible` css selector, this style only activates when tab navigation is used.
+    /// Similar to the `:focus-visible` css selector, this style only activates while the view is
+    /// focused and the last input came from the keyboard. It applies after moving focus with Tab,
+    /// or after pressing a key in a view which was focused by clicking it, but not after a click.
     pub fn focus_visible(self, style: impl FnOnce(Style) -> Style) -> Self {
         self.selector(StyleSelector::FocusVisible, style)
     }
 
+    /// Similar to the `:focus-within` css selector, this style activates while the view or one
+    /// of its descendants is focused, like the wrapper of a text input.
+    pub fn focus_within(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::FocusWithin, style)
+    }
+
     pub fn selected(self, style: impl FnOnce(Style) -> Style) -> Self {
         self.selector(StyleSelector::Selected, style)
     }
 
+    /// Applies `style` when the view or one of its ancestors is disabled.
     pub fn disabled(self, style: impl FnOnce(Style) -> Style) -> Self {
         self.selector(StyleSelector::Disabled, style)
     }
 
+    /// Applies `style` when the view is checked, like a checkbox.
+    /// See [`Id::update_checked`](crate::id::Id::update_checked).
+    pub fn checked(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::Checked, style)
+    }
+
     pub fn active(self, style: impl FnOnce(Style) -> Style) -> Self {
         self.selector(StyleSelector::Active, style)
     }
 
+    /// Applies `style` when the view is the first child of its parent.
+    pub fn first_child(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::FirstChild, style)
+    }
+
+    /// Applies `style` when the view is the last child of its parent, such as to remove the
+    /// separator after the last item of a list.
+    pub fn last_child(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::LastChild, style)
+    }
+
+    /// Styles the placeholder text of a text input, which is shown while the input is empty,
+    /// like the `::placeholder` css selector. It supports the text color, font weight and font
+    /// style.
+    pub fn placeholder(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::Placeholder, style)
+    }
+
+    /// Applies `style` when the view has no children, like the `:empty` css selector. The
+    /// style updates when children are added to or removed from the view.
+    pub fn empty(self, style: impl FnOnce(Style) -> Style) -> Self {
+        self.selector(StyleSelector::Empty, style)
+    }
+
+    /// Applies `style` when the view doesn't match `selector`, like the `:not()` css selector.
+    /// Negated styles take precedence over the structural selectors, and the other selectors
+    /// take precedence over them. Negating [`StyleSelector::Dragging`] applies `style` even
+    /// while dragging, as the dragging style is only applied while painting.
+    /// ```rust
+    /// # use floem::peniko::Color;
+    /// # use floem::style::{Style, StyleSelector};
+    /// let item = Style::new().not(StyleSelector::LastChild, |s| {
+    ///     s.border_bottom(1.0).border_color(Color::LIGHT_GRAY)
+    /// });
+    /// ```
+    pub fn not(mut self, selector: StyleSelector, style: impl FnOnce(Style) -> Style) -> Self {
+        let over = style(Style::default());
+        self.set_negated(selector, over);
+        self
+    }
+
+    /// Applies `style` when the position of the view among the children of its parent matches
+    /// `nth`, like the `:nth-child` css selector.
+    /// ```rust
+    /// # use floem::peniko::Color;
+    /// # use floem::style::{NthChild, Style};
+    /// let row = Style::new().nth_child(NthChild::EVEN, |s| s.background(Color::WHITE_SMOKE));
+    /// ```
+    pub fn nth_child(mut self, nth: NthChild, style: impl FnOnce(Style) -> Style) -> Self {
+        let over = style(Style::default());
+        self.set_nth_child(nth, over);
+        self
+    }
+
     pub fn responsive(mut self, size: ScreenSize, style: impl FnOnce(Style) -> Style) -> Self {
         let over = style(Style::default());
         for breakpoint in size.breakpoints() {
@@ -1184,6 +2567,45 @@ impl Style {
         self
     }
 
+    /// Applies `style` while the window is at least `min_width` pixels wide. Views using it are
+    /// restyled as the window is resized, before the next frame is laid out and painted.
+    ///
+    /// When several breakpoints apply, the ones with a larger `min_width` take precedence.
+    /// ```rust
+    /// # use floem::style::Style;
+    /// let sidebar = Style::new()
+    ///     .width(0.0)
+    ///     .at_breakpoint(600.0, |s| s.width(200.0))
+    ///     .at_breakpoint(1000.0, |s| s.width(300.0));
+    /// ```
+    pub fn at_breakpoint(mut self, min_width: f64, style: impl FnOnce(Style) -> Style) -> Self {
+        let over = style(Style::default());
+        Self::insert_min_width(&mut self.breakpoints, min_width, over);
+        self
+    }
+
+    /// Applies `style` while the view itself is at least `min_width` pixels wide, so a
+    /// component can adapt to the space it's placed in.
+    ///
+    /// The width of a view is only known after layout, which depends on its style, so the
+    /// style is resolved in two passes. The view is first styled with the width of its last
+    /// layout, which is zero when it's new. If its new layout crosses the `min_width` of a
+    /// query, the view is styled and laid out again, before the frame is painted.
+    ///
+    /// A query can change the width of the view back across its own `min_width`, like a query
+    /// which makes a view narrower once it's wide. Rather than switching between the two styles
+    /// forever, the view keeps the style which caused the switch back until its width settles
+    /// on the other side of the `min_width`, like when the window is resized.
+    pub fn at_container_width(
+        mut self,
+        min_width: f64,
+        style: impl FnOnce(Style) -> Style,
+    ) -> Self {
+        let over = style(Style::default());
+        Self::insert_min_width(&mut self.container_queries, min_width, over);
+        self
+    }
+
     pub fn class<C: StyleClass>(mut self, _class: C, style: impl FnOnce(Style) -> Style) -> Self {
         let over = style(Style::default());
         self.set_class(C::class_ref(), over);
@@ -1314,6 +2736,60 @@ impl Style {
         self.border_top(border).border_bottom(border)
     }
 
+    /// Sets the radius of the top left corner, overriding `border_radius`
+    pub fn border_top_left_radius(self, radius: impl Into<PxPct>) -> Self {
+        self.set(BorderTopLeftRadius, Some(radius.into()))
+    }
+
+    /// Sets the radius of the top right corner, overriding `border_radius`
+    pub fn border_top_right_radius(self, radius: impl Into<PxPct>) -> Self {
+        self.set(BorderTopRightRadius, Some(radius.into()))
+    }
+
+    /// Sets the radius of the bottom left corner, overriding `border_radius`
+    pub fn border_bottom_left_radius(self, radius: impl Into<PxPct>) -> Self {
+        self.set(BorderBottomLeftRadius, Some(radius.into()))
+    }
+
+    /// Sets the radius of the bottom right corner, overriding `border_radius`
+    pub fn border_bottom_right_radius(self, radius: impl Into<PxPct>) -> Self {
+        self.set(BorderBottomRightRadius, Some(radius.into()))
+    }
+
+    /// Sets `border_top_left_radius` and `border_top_right_radius` to `radius`
+    pub fn border_top_radius(self, radius: impl Into<PxPct>) -> Self {
+        let radius = radius.into();
+        self.border_top_left_radius(radius)
+            .border_top_right_radius(radius)
+    }
+
+    /// Sets `border_bottom_left_radius` and `border_bottom_right_radius` to `radius`
+    pub fn border_bottom_radius(self, radius: impl Into<PxPct>) -> Self {
+        let radius = radius.into();
+        self.border_bottom_left_radius(radius)
+            .border_bottom_right_radius(radius)
+    }
+
+    /// Sets the color of the left border, overriding `border_color`
+    pub fn border_left_color(self, color: Color) -> Self {
+        self.set(BorderLeftColor, Some(color))
+    }
+
+    /// Sets the color of the top border, overriding `border_color`
+    pub fn border_top_color(self, color: Color) -> Self {
+        self.set(BorderTopColor, Some(color))
+    }
+
+    /// Sets the color of the right border, overriding `border_color`
+    pub fn border_right_color(self, color: Color) -> Self {
+        self.set(BorderRightColor, Some(color))
+    }
+
+    /// Sets the color of the bottom border, overriding `border_color`
+    pub fn border_bottom_color(self, color: Color) -> Self {
+        self.set(BorderBottomColor, Some(color))
+    }
+
     pub fn padding_left_pct(self, padding: f64) -> Self {
         self.padding_left(padding.pct())
     }
@@ -1423,6 +2899,99 @@ impl Style {
         self.margin_top(margin).margin_bottom(margin)
     }
 
+    /// Sets the padding on the side text starts from, which is the left in left-to-right
+    /// [direction](Style::direction) and the right in right-to-left direction.
+    ///
+    /// Logical spacing properties are resolved to physical ones when the view is styled. If
+    /// the physical property of the same side is set too, including by a class or the theme,
+    /// the physical property is used.
+    pub fn padding_inline_start(self, padding: impl Into<PxPct>) -> Self {
+        self.set(PaddingInlineStart, Some(padding.into()))
+    }
+
+    /// Sets the padding on the side text ends on. See [`Style::padding_inline_start`].
+    pub fn padding_inline_end(self, padding: impl Into<PxPct>) -> Self {
+        self.set(PaddingInlineEnd, Some(padding.into()))
+    }
+
+    /// Sets the padding on the top. See [`Style::padding_inline_start`].
+    pub fn padding_block_start(self, padding: impl Into<PxPct>) -> Self {
+        self.set(PaddingBlockStart, Some(padding.into()))
+    }
+
+    /// Sets the padding on the bottom. See [`Style::padding_inline_start`].
+    pub fn padding_block_end(self, padding: impl Into<PxPct>) -> Self {
+        self.set(PaddingBlockEnd, Some(padding.into()))
+    }
+
+    /// Sets `padding_inline_start` and `padding_inline_end` to `padding`
+    pub fn padding_inline(self, padding: impl Into<PxPct>) -> Self {
+        let padding = padding.into();
+        self.padding_inline_start(padding)
+            .padding_inline_end(padding)
+    }
+
+    /// Sets the margin on the side text starts from. See [`Style::padding_inline_start`].
+    pub fn margin_inline_start(self, margin: impl Into<PxPctAuto>) -> Self {
+        self.set(MarginInlineStart, Some(margin.into()))
+    }
+
+    /// Sets the margin on the side text ends on. See [`Style::padding_inline_start`].
+    pub fn margin_inline_end(self, margin: impl Into<PxPctAuto>) -> Self {
+        self.set(MarginInlineEnd, Some(margin.into()))
+    }
+
+    /// Sets the margin on the top. See [`Style::padding_inline_start`].
+    pub fn margin_block_start(self, margin: impl Into<PxPctAuto>) -> Self {
+        self.set(MarginBlockStart, Some(margin.into()))
+    }
+
+    /// Sets the margin on the bottom. See [`Style::padding_inline_start`].
+    pub fn margin_block_end(self, margin: impl Into<PxPctAuto>) -> Self {
+        self.set(MarginBlockEnd, Some(margin.into()))
+    }
+
+    /// Sets `margin_inline_start` and `margin_inline_end` to `margin`
+    pub fn margin_inline(self, margin: impl Into<PxPctAuto>) -> Self {
+        let margin = margin.into();
+        self.margin_inline_start(margin).margin_inline_end(margin)
+    }
+
+    /// Sets the physical property `P` to the value of the logical property `L`, unless `P` is
+    /// set already.
+    fn resolve_logical<L, P>(&mut self, _logical: L, _physical: P)
+    where
+        P: StyleProp,
+        L: StyleProp<Type = Option<P::Type>>,
+    {
+        if self.map.contains_key(&P::prop_ref()) {
+            return;
+        }
+        if let Some(value) = self.get_prop::<L>().flatten() {
+            self.map
+                .insert(P::prop_ref(), StyleMapValue::Val(Rc::new(value)));
+        }
+    }
+
+    /// Resolves the logical spacing properties to physical ones for `direction`.
+    pub(crate) fn resolve_logical_spacing(&mut self, direction: Direction) {
+        self.resolve_logical(PaddingBlockStart, PaddingTop);
+        self.resolve_logical(PaddingBlockEnd, PaddingBottom);
+        self.resolve_logical(MarginBlockStart, MarginTop);
+        self.resolve_logical(MarginBlockEnd, MarginBottom);
+        if direction.is_rtl() {
+            self.resolve_logical(PaddingInlineStart, PaddingRight);
+            self.resolve_logical(PaddingInlineEnd, PaddingLeft);
+            self.resolve_logical(MarginInlineStart, MarginRight);
+            self.resolve_logical(MarginInlineEnd, MarginLeft);
+        } else {
+            self.resolve_logical(PaddingInlineStart, PaddingLeft);
+            self.resolve_logical(PaddingInlineEnd, PaddingRight);
+            self.resolve_logical(MarginInlineStart, MarginLeft);
+            self.resolve_logical(MarginInlineEnd, MarginRight);
+        }
+    }
+
     pub fn inset_left_pct(self, inset: f64) -> Self {
         self.inset_left(inset.pct())
     }
@@ -1464,37 +3033,62 @@ impl Style {
     }
 
     pub fn background(self, color: impl Into<StyleValue<Color>>) -> Self {
-        self.set_style_value(Background, color.into().map(Some))
+        self.set_style_value(
+            Background,
+            color.into().map(|color| Some(Fill::Solid(color))),
+        )
+    }
+
+    /// Sets the background to a [`Gradient`], which replaces a background color.
+    pub fn background_gradient(self, gradient: impl Into<StyleValue<Gradient>>) -> Self {
+        self.set_style_value(
+            Background,
+            gradient
+                .into()
+                .map(|gradient| Some(Fill::Gradient(gradient))),
+        )
+    }
+
+    /// Adds a shadow below the shadows added previously, like listing multiple shadows in the
+    /// CSS `box-shadow` property.
+    pub fn box_shadow(self, shadow: BoxShadow) -> Self {
+        let mut shadows = self.get(BoxShadowProp);
+        shadows.push(shadow);
+        self.set(BoxShadowProp, shadows)
+    }
+
+    /// Updates the most recently added shadow, adding a default one if there are none.
+    fn update_box_shadow(self, f: impl FnOnce(&mut BoxShadow)) -> Self {
+        let mut shadows = self.get(BoxShadowProp);
+        if shadows.is_empty() {
+            shadows.push(BoxShadow::default());
+        }
+        f(shadows.last_mut().unwrap());
+        self.set(BoxShadowProp, shadows)
     }
 
     pub fn box_shadow_blur(self, blur_radius: impl Into<PxPct>) -> Self {
-        let mut value = self.get(BoxShadowProp).unwrap_or_default();
-        value.blur_radius = blur_radius.into();
-        self.set(BoxShadowProp, Some(value))
+        self.update_box_shadow(|s| s.blur_radius = blur_radius.into())
     }
 
     pub fn box_shadow_color(self, color: Color) -> Self {
-        let mut value = self.get(BoxShadowProp).unwrap_or_default();
-        value.color = color;
-        self.set(BoxShadowProp, Some(value))
+        self.update_box_shadow(|s| s.color = color)
     }
 
     pub fn box_shadow_spread(self, spread: impl Into<PxPct>) -> Self {
-        let mut value = self.get(BoxShadowProp).unwrap_or_default();
-        value.spread = spread.into();
-        self.set(BoxShadowProp, Some(value))
+        self.update_box_shadow(|s| s.spread = spread.into())
     }
 
     pub fn box_shadow_h_offset(self, h_offset: impl Into<PxPct>) -> Self {
-        let mut value = self.get(BoxShadowProp).unwrap_or_default();
-        value.h_offset = h_offset.into();
-        self.set(BoxShadowProp, Some(value))
+        self.update_box_shadow(|s| s.h_offset = h_offset.into())
     }
 
     pub fn box_shadow_v_offset(self, v_offset: impl Into<PxPct>) -> Self {
-        let mut value = self.get(BoxShadowProp).unwrap_or_default();
-        value.v_offset = v_offset.into();
-        self.set(BoxShadowProp, Some(value))
+        self.update_box_shadow(|s| s.v_offset = v_offset.into())
+    }
+
+    pub fn box_shadow_inset(self, inset: bool) -> Self {
+        self.update_box_shadow(|s| s.inset = inset)
     }
 
     pub fn font_size(self, size: impl Into<StyleValue<f32>>) -> Self {
@@ -1505,6 +3099,15 @@ impl Style {
         self.set_style_value(FontFamily, family.into().map(Some))
     }
 
+    /// Sets the fonts used for the characters missing from the font family, written like the
+    /// family as a comma separated list, like `"Noto Sans Arabic, Noto Color Emoji"`. They're
+    /// tried in order for each missing character, before the fonts set with
+    /// [`set_font_fallback`]. It's inherited, so it can be set once for a part of the app, like
+    /// one showing a language which needs its own fonts.
+    pub fn font_fallback(self, fallback: impl Into<StyleValue<String>>) -> Self {
+        self.set_style_value(FontFallback, fallback.into().map(Some))
+    }
+
     pub fn font_weight(self, weight: impl Into<StyleValue<Weight>>) -> Self {
         self.set_style_value(FontWeight, weight.into().map(Some))
     }
@@ -1533,6 +3136,51 @@ impl Style {
         self.text_overflow(TextOverflow::Clip)
     }
 
+    /// Wraps the text of a [`label`](crate::views::label) to at most `lines` lines, ending the
+    /// last one with an ellipsis when the text is longer, like the `line-clamp` css property.
+    /// It's used instead of [`Style::text_overflow`], and a clamp of one line is the same as
+    /// [`Style::text_ellipsis`].
+    pub fn line_clamp(self, lines: usize) -> Self {
+        self.set(LineClamp, Some(lines))
+    }
+
+    /// Adds `spacing` pixels after each character of the text of a label, which can be negative
+    /// to move them closer, like the `letter-spacing` css property.
+    ///
+    /// The spacing is added when the text is drawn, after it's wrapped, so text which wraps can
+    /// be wider than the label by the spacing of a line.
+    pub fn letter_spacing(self, spacing: impl Into<StyleValue<f32>>) -> Self {
+        self.set_style_value(LetterSpacing, spacing.into())
+    }
+
+    /// Adds `spacing` pixels after each space in the text of a label, on top of the letter
+    /// spacing, like the `word-spacing` css property. It's added like
+    /// [`Style::letter_spacing`].
+    pub fn word_spacing(self, spacing: impl Into<StyleValue<f32>>) -> Self {
+        self.set_style_value(WordSpacing, spacing.into())
+    }
+
+    /// Aligns the lines of the text of a label or a [`rich_text`](crate::views::rich_text) in
+    /// the view, like the `text-align` css property. [`TextAlign::Start`] and
+    /// [`TextAlign::End`] follow the direction of each line, so they're on the right of
+    /// right-to-left text.
+    pub fn text_align(self, align: impl Into<StyleValue<TextAlign>>) -> Self {
+        self.set_style_value(TextAlignProp, align.into())
+    }
+
+    /// Changes the case of the text of a label when it's shown, without changing the text it
+    /// was given, like the `text-transform` css property. The casing rules of the language set
+    /// with [`Style::lang`] are used.
+    pub fn text_transform(self, transform: impl Into<StyleValue<TextTransform>>) -> Self {
+        self.set_style_value(TextTransformProp, transform.into())
+    }
+
+    /// Sets the language of the text, as a language tag like `"en"` or `"tr-TR"`, which some
+    /// text styles depend on, like the casing of [`Style::text_transform`].
+    pub fn lang(self, lang: impl Into<String>) -> Self {
+        self.set(Lang, Some(lang.into()))
+    }
+
     pub fn absolute(self) -> Self {
         self.position(taffy::style::Position::Absolute)
     }
@@ -1550,6 +3198,13 @@ impl Style {
         self.align_items(Some(taffy::style::AlignItems::FlexEnd))
     }
 
+    /// Aligns the children of a row so the baselines of the first lines of their text line up,
+    /// like labels with different font sizes. The baseline of a view is the one of its first
+    /// child, and views without text, like icons, have their bottom edge on the baseline.
+    pub fn items_baseline(self) -> Self {
+        self.align_items(Some(taffy::style::AlignItems::Baseline))
+    }
+
     /// Defines the alignment along the main axis as Centered
     pub fn justify_center(self) -> Self {
         self.justify_content(Some(taffy::style::JustifyContent::Center))
@@ -1587,6 +3242,42 @@ impl Style {
         self.set(ZIndex, Some(z_index))
     }
 
+    /// Rounds the position and size of the view, and the widths of its borders and outline, to
+    /// the physical pixels of the screen when it's painted, so thin borders and separators stay
+    /// sharp on fractional scale factors like 1.25 and at positions which aren't whole pixels.
+    /// It's inherited, so setting it on the root view snaps the whole window. Layout isn't
+    /// changed, so views can move by up to half a pixel from where they're laid out.
+    ///
+    /// The view's position in the window is snapped, so the views in a
+    /// [`scroll`](crate::views::scroll) stay sharp at any offset. What a view paints itself, like
+    /// the shapes of a [`scene`](crate::views::scene), is moved with its origin but isn't
+    /// snapped, and views under a transform which scales or rotates aren't snapped at all.
+    ///
+    /// Snapping moves views a whole pixel at a time, so views which move smoothly, like with
+    /// animations or transitions of their position, jitter. Set it to `false` on those views
+    /// while they move.
+    pub fn pixel_snap(self, snap: bool) -> Self {
+        self.set(PixelSnap, snap)
+    }
+
+    /// Makes a [`scroll`](crate::views::scroll) view settle on the snap positions of the
+    /// descendants with a [`Style::scroll_snap_align`] after it's scrolled, like the
+    /// `scroll-snap-type` css property. With [`ScrollSnapStrictness::Mandatory`] it always
+    /// settles on a snap position, and with [`ScrollSnapStrictness::Proximity`] only when it
+    /// stops near one.
+    pub fn scroll_snap_type(self, axis: ScrollSnapAxis, strictness: ScrollSnapStrictness) -> Self {
+        self.set(
+            ScrollSnapTypeProp,
+            Some(ScrollSnapType { axis, strictness }),
+        )
+    }
+
+    /// Makes the edge `align` of the view a snap position of the scroll view it's in, see
+    /// [`Style::scroll_snap_type`].
+    pub fn scroll_snap_align(self, align: ScrollSnapAlign) -> Self {
+        self.set(ScrollSnapAlignProp, Some(align))
+    }
+
     /// Allow the application of a function if the option exists.  
     /// This is useful for chaining together a bunch of optional style changes.  
     /// ```rust,ignore
@@ -1623,6 +3314,41 @@ impl Style {
 impl Style {
     pub fn to_taffy_style(&self) -> TaffyStyle {
         let style = self.builtin();
+        let rtl = style.direction().is_rtl();
+        // Taffy only lays out left-to-right, so right-to-left rows are reversed, and the cross
+        // axis alignment of columns is mirrored.
+        let flex_direction = match style.flex_direction() {
+            FlexDirection::Row if rtl => FlexDirection::RowReverse,
+            FlexDirection::RowReverse if rtl => FlexDirection::Row,
+            flex_direction => flex_direction,
+        };
+        let mirror_cross = rtl
+            && matches!(
+                flex_direction,
+                FlexDirection::Column | FlexDirection::ColumnReverse
+            );
+        let align_items = match style.align_items() {
+            Some(AlignItems::Start) if mirror_cross => Some(AlignItems::End),
+            Some(AlignItems::End) if mirror_cross => Some(AlignItems::Start),
+            Some(AlignItems::FlexStart) if mirror_cross => Some(AlignItems::FlexEnd),
+            Some(AlignItems::FlexEnd) if mirror_cross => Some(AlignItems::FlexStart),
+            align => align,
+        };
+        let align_content = match style.align_content() {
+            Some(AlignContent::Start) if mirror_cross => Some(AlignContent::End),
+            Some(AlignContent::End) if mirror_cross => Some(AlignContent::Start),
+            Some(AlignContent::FlexStart) if mirror_cross => Some(AlignContent::FlexEnd),
+            Some(AlignContent::FlexEnd) if mirror_cross => Some(AlignContent::FlexStart),
+            align => align,
+        };
+        let mirror_self = self.get(InRtlColumn);
+        let align_self = match style.align_self() {
+            Some(AlignItems::Start) if mirror_self => Some(AlignItems::End),
+            Some(AlignItems::End) if mirror_self => Some(AlignItems::Start),
+            Some(AlignItems::FlexStart) if mirror_self => Some(AlignItems::FlexEnd),
+            Some(AlignItems::FlexEnd) if mirror_self => Some(AlignItems::FlexStart),
+            align => align,
+        };
         TaffyStyle {
             display: style.display(),
             position: style.position(),
@@ -1638,16 +3364,16 @@ impl Style {
                 width: style.max_width().into(),
                 height: style.max_height().into(),
             },
-            flex_direction: style.flex_direction(),
+            flex_direction,
             flex_grow: style.flex_grow(),
             flex_shrink: style.flex_shrink(),
             flex_basis: style.flex_basis().into(),
             flex_wrap: style.flex_wrap(),
             justify_content: style.justify_content(),
             justify_self: style.justify_self(),
-            align_items: style.align_items(),
-            align_content: style.align_content(),
-            align_self: style.align_self(),
+            align_items,
+            align_content,
+            align_self,
             aspect_ratio: style.aspect_ratio(),
             border: Rect {
                 left: LengthPercentage::Points(style.border_left().0 as f32),
@@ -1683,12 +3409,196 @@ impl Style {
     }
 }
 
+/// Styles are serialized as a map of the names of built-in properties to their values, written
+/// as described in [`StylePropValue::parse`], with properties which are unset written as
+/// `unset`. Selector styles are nested under the names of the selectors, like `hover`.
+///
+/// Class styles are nested in a `classes` map, under the full names of the classes. They are
+/// looked up with [`StyleClassRef::by_name`] when deserializing, so the classes have to be
+/// registered.
+///
+/// All built-in properties are supported, except for `grid_template_rows`,
+/// `grid_template_columns`, `grid_row`, `grid_column`, `box_shadow`, `gap` and gradient
+/// backgrounds. Serializing a style which sets those, custom properties, breakpoints, container
+/// queries, `nth_child` or negated selectors, or transitions fails.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Style {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{Error, SerializeMap};
+
+        if !self.responsive.is_empty()
+            || !self.breakpoints.is_empty()
+            || !self.container_queries.is_empty()
+            || !self.nth_child.is_empty()
+            || !self.negated.is_empty()
+            || !self.transitions.is_empty()
+        {
+            return Err(S::Error::custom(
+                "styles with breakpoints, container queries, nth_child or negated selectors, or \
+                 transitions can't be serialized",
+            ));
+        }
+
+        let mut props = Vec::with_capacity(self.map.len());
+        for (prop, value) in &self.map {
+            let name = prop
+                .builtin_name()
+                .ok_or_else(|| S::Error::custom(format!("{prop:?} isn't a built-in property")))?;
+            let text = match value {
+                StyleMapValue::Val(value) => {
+                    (prop.info.to_text_any)(&**value).ok_or_else(|| {
+                        S::Error::custom(format!("the value of `{name}` can't be serialized"))
+                    })?
+                }
+                StyleMapValue::Unset => "unset".to_string(),
+            };
+            props.push((name, text));
+        }
+        // Sort the properties so the output is stable
+        props.sort_unstable_by_key(|(name, _)| *name);
+
+        let mut map = serializer.serialize_map(None)?;
+        for (name, text) in &props {
+            map.serialize_entry(name, text)?;
+        }
+        for selector in StyleSelector::ALL {
+            if let Some(style) = self.selectors.get(&selector) {
+                map.serialize_entry(selector.name(), style)?;
+            }
+        }
+        if !self.classes.is_empty() {
+            let classes: BTreeMap<_, _> = self
+                .classes
+                .iter()
+                .map(|(class, style)| (class.name(), style))
+                .collect();
+            map.serialize_entry("classes", &classes)?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Style {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::{Error, MapAccess, Visitor};
+
+        /// A property value, which can also be written as a number or a bool.
+        struct PropText(String);
+
+        impl<'de> serde::Deserialize<'de> for PropText {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct TextVisitor;
+
+                impl Visitor<'_> for TextVisitor {
+                    type Value = PropText;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("a style property value")
+                    }
+
+                    fn visit_str<E: Error>(self, v: &str) -> Result<PropText, E> {
+                        Ok(PropText(v.to_string()))
+                    }
+
+                    fn visit_i64<E: Error>(self, v: i64) -> Result<PropText, E> {
+                        Ok(PropText(v.to_string()))
+                    }
+
+                    fn visit_u64<E: Error>(self, v: u64) -> Result<PropText, E> {
+                        Ok(PropText(v.to_string()))
+                    }
+
+                    fn visit_f64<E: Error>(self, v: f64) -> Result<PropText, E> {
+                        Ok(PropText(v.to_string()))
+                    }
+
+                    fn visit_bool<E: Error>(self, v: bool) -> Result<PropText, E> {
+                        Ok(PropText(v.to_string()))
+                    }
+                }
+
+                deserializer.deserialize_any(TextVisitor)
+            }
+        }
+
+        struct StyleVisitor;
+
+        impl<'de> Visitor<'de> for StyleVisitor {
+            type Value = Style;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of style properties")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Style, A::Error> {
+                let mut style = Style::new();
+                while let Some(name) = map.next_key::<String>()? {
+                    if let Some(selector) = StyleSelector::from_name(&name) {
+                        let over: Style = map.next_value()?;
+                        style.set_selector(selector, over);
+                        continue;
+                    }
+                    if name == "classes" {
+                        let classes: BTreeMap<String, Style> = map.next_value()?;
+                        for (name, over) in classes {
+                            let class = StyleClassRef::by_name(&name).ok_or_else(|| {
+                                A::Error::custom(format!("unknown style class `{name}`"))
+                            })?;
+                            style.set_class(class, over);
+                        }
+                        continue;
+                    }
+                    let prop = StylePropRef::builtin(&name).ok_or_else(|| {
+                        A::Error::custom(format!("unknown style property `{name}`"))
+                    })?;
+                    let PropText(text) = map.next_value()?;
+                    if text == "unset" {
+                        style.map.insert(prop, StyleMapValue::Unset);
+                        continue;
+                    }
+                    let value = (prop.info.parse_any)(&text).ok_or_else(|| {
+                        A::Error::custom(format!("invalid value `{text}` for `{name}`"))
+                    })?;
+                    style.set_raw(prop, value);
+                }
+                Ok(style)
+            }
+        }
+
+        deserializer.deserialize_map(StyleVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Style, StyleValue};
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+        time::{Duration, Instant},
+    };
+
+    use floem_peniko::Color;
+    use floem_reactive::create_rw_signal;
+    use kurbo::{Point, Size};
+    use smallvec::SmallVec;
+
+    use super::{
+        set_font_fallback, BoxShadow, ExtratorField, Fill, FontProps, Gradient, GradientColorSpace,
+        NthChild, Style, StylePropValue, StyleSelector, StyleValue, TextTransform, Transition,
+        TransitionState,
+    };
     use crate::{
-        style::{PaddingBottom, PaddingLeft},
-        unit::PxPct,
+        animate::EasingFn,
+        context::InteractionState,
+        responsive::ScreenSizeBp,
+        style::{
+            Background, BorderColor, Outline, PaddingBottom, PaddingLeft, PaddingTop, TextColor,
+        },
+        test_support::HeadlessWindow,
+        unit::{Px, PxPct},
+        view::View,
+        views::{container, empty, h_stack, Decorators},
     };
 
     #[test]
@@ -1764,4 +3674,555 @@ mod tests {
             StyleValue::Val(PxPct::Px(100.0))
         );
     }
+
+    #[test]
+    fn transition_easing() {
+        let mut state = TransitionState::<f64>::default();
+        let mut request_transition = false;
+        state.read(Some(
+            Transition::linear(1.0)
+                .easing_fn(EasingFn::Quadratic)
+                .ease_out(),
+        ));
+        // The first step observes the initial value without animating.
+        state.step(&Instant::now(), &mut request_transition);
+
+        state.transition(&0.0, &1.0, &Instant::now());
+        let start = state.active.as_ref().unwrap().start;
+        assert!(state.step(
+            &(start + Duration::from_millis(500)),
+            &mut request_transition
+        ));
+        assert!(request_transition);
+        assert!((state.get(&1.0) - 0.75).abs() < 1e-9);
+
+        assert!(state.step(&(start + Duration::from_secs(1)), &mut request_transition));
+        assert!(state.active.is_none());
+        assert_eq!(state.get(&1.0), 1.0);
+    }
+
+    #[test]
+    fn nth_child_positions() {
+        let matching = |nth: NthChild| (0..8).filter(|&i| nth.matches(i)).collect::<Vec<_>>();
+        assert_eq!(matching(NthChild::ODD), vec![0, 2, 4, 6]);
+        assert_eq!(matching(NthChild::EVEN), vec![1, 3, 5, 7]);
+        assert_eq!(matching(NthChild::position(3)), vec![2]);
+        assert_eq!(matching(NthChild::new(3, 2)), vec![1, 4, 7]);
+        // The first three children, like `:nth-child(-n+3)`
+        assert_eq!(matching(NthChild::new(-1, 3)), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn structural_selectors() {
+        let row = Style::new()
+            .background(Color::WHITE)
+            .nth_child(NthChild::EVEN, |s| s.background(Color::WHITE_SMOKE))
+            .first_child(|s| s.padding_top(4.0))
+            .last_child(|s| s.padding_bottom(4.0));
+        let rows: Vec<Style> = (0..4)
+            .map(|index| {
+                let mut style = row.clone();
+                let state = InteractionState {
+                    child_position: Some((index, 4)),
+                    ..Default::default()
+                };
+                style.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+                style
+            })
+            .collect();
+
+        let backgrounds: Vec<_> = rows.iter().map(|s| s.get(Background)).collect();
+        assert_eq!(
+            backgrounds,
+            vec![
+                Some(Fill::Solid(Color::WHITE)),
+                Some(Fill::Solid(Color::WHITE_SMOKE)),
+                Some(Fill::Solid(Color::WHITE)),
+                Some(Fill::Solid(Color::WHITE_SMOKE)),
+            ]
+        );
+        assert_eq!(rows[0].get(PaddingTop), PxPct::Px(4.0));
+        assert_eq!(rows[1].get(PaddingTop), PxPct::Px(0.0));
+        assert_eq!(rows[3].get(PaddingBottom), PxPct::Px(4.0));
+        assert_eq!(rows[2].get(PaddingBottom), PxPct::Px(0.0));
+        assert!(row.selectors().has_structural());
+    }
+
+    #[test]
+    fn empty_and_negated_selectors() {
+        let list = Style::new()
+            .empty(|s| s.padding_top(8.0))
+            .not(StyleSelector::Hover, |s| s.background(Color::WHITE))
+            .not(StyleSelector::Empty, |s| s.padding_bottom(2.0));
+        let computed = |is_empty, is_hovered| {
+            let mut style = list.clone();
+            let state = InteractionState {
+                is_empty,
+                is_hovered,
+                ..Default::default()
+            };
+            style.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+            style
+        };
+
+        let empty = computed(true, false);
+        assert_eq!(empty.get(PaddingTop), PxPct::Px(8.0));
+        assert_eq!(empty.get(PaddingBottom), PxPct::Px(0.0));
+        assert_eq!(empty.get(Background), Some(Fill::Solid(Color::WHITE)));
+
+        let filled = computed(false, true);
+        assert_eq!(filled.get(PaddingTop), PxPct::Px(0.0));
+        assert_eq!(filled.get(PaddingBottom), PxPct::Px(2.0));
+        assert_eq!(filled.get(Background), None);
+
+        // Restyling on hover is needed for the negated hover style
+        assert!(list.selectors().has(StyleSelector::Hover));
+    }
+
+    #[test]
+    fn placeholder_selector() {
+        let mut input = Style::new()
+            .color(Color::BLACK)
+            .placeholder(|s| s.color(Color::GRAY));
+        let state = InteractionState {
+            is_focused: true,
+            is_empty: true,
+            ..Default::default()
+        };
+        input.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+        assert_eq!(input.get(TextColor), Some(Color::BLACK));
+
+        let placeholder = input.apply_selectors(&[StyleSelector::Placeholder]);
+        assert_eq!(placeholder.get(TextColor), Some(Color::GRAY));
+    }
+
+    #[test]
+    fn checked_selector() {
+        let style = Style::new()
+            .checked(|s| s.background(Color::BLUE))
+            .not(StyleSelector::Checked, |s| s.background(Color::WHITE));
+        let computed = |is_checked| {
+            let mut style = style.clone();
+            let state = InteractionState {
+                is_checked,
+                ..Default::default()
+            };
+            style.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+            style.get(Background)
+        };
+
+        assert_eq!(computed(true), Some(Fill::Solid(Color::BLUE)));
+        assert_eq!(computed(false), Some(Fill::Solid(Color::WHITE)));
+        assert_eq!(
+            StyleSelector::from_name("checked"),
+            Some(StyleSelector::Checked)
+        );
+    }
+
+    #[test]
+    fn focus_visible_needs_keyboard_input() {
+        let style = Style::new()
+            .focus(|s| s.border_color(Color::BLUE))
+            .focus_visible(|s| s.outline(3.0));
+        let computed = |is_focused, using_keyboard_navigation| {
+            let mut style = style.clone();
+            let state = InteractionState {
+                is_focused,
+                using_keyboard_navigation,
+                ..Default::default()
+            };
+            style.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+            style
+        };
+
+        let clicked = computed(true, false);
+        assert_eq!(clicked.get(BorderColor), Color::BLUE);
+        assert_eq!(clicked.get(Outline), Px(0.0));
+
+        let tabbed = computed(true, true);
+        assert_eq!(tabbed.get(BorderColor), Color::BLUE);
+        assert_eq!(tabbed.get(Outline), Px(3.0));
+
+        assert_eq!(computed(false, true).get(Outline), Px(0.0));
+    }
+
+    #[test]
+    fn transition_per_selector_timing() {
+        let style = Style::new()
+            .background(Color::WHITE)
+            .transition(Background, Transition::linear(0.3))
+            .hover(|s| {
+                s.background(Color::BLACK)
+                    .transition(Background, Transition::linear(0.1))
+            });
+        let computed = |is_hovered| {
+            let mut style = style.clone();
+            let state = InteractionState {
+                is_hovered,
+                ..Default::default()
+            };
+            style.apply_interact_state(&state, ScreenSizeBp::Xs, 0.0, 0.0);
+            style
+        };
+        let idle = computed(false);
+        let hovered = computed(true);
+
+        let mut field = ExtratorField::<Background>::new();
+        let mut request_transition = false;
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut read = |field: &mut ExtratorField<Background>, style: &Style, ms| {
+            field.read(style, style, &at(ms), &mut request_transition);
+            field.get()
+        };
+
+        assert_eq!(read(&mut field, &idle, 0), Some(Fill::Solid(Color::WHITE)));
+
+        // Hover in uses the transition from the hover selector.
+        assert_eq!(
+            read(&mut field, &hovered, 1000),
+            Some(Fill::Solid(Color::WHITE))
+        );
+        let mid = read(&mut field, &hovered, 1050);
+        assert_ne!(mid, Some(Fill::Solid(Color::WHITE)));
+        assert_ne!(mid, Some(Fill::Solid(Color::BLACK)));
+        assert_eq!(
+            read(&mut field, &hovered, 1100),
+            Some(Fill::Solid(Color::BLACK))
+        );
+
+        // Hover out uses the base transition.
+        assert_eq!(
+            read(&mut field, &idle, 2000),
+            Some(Fill::Solid(Color::BLACK))
+        );
+        let mid = read(&mut field, &idle, 2100);
+        assert_ne!(mid, Some(Fill::Solid(Color::WHITE)));
+        assert_ne!(mid, Some(Fill::Solid(Color::BLACK)));
+        assert_ne!(
+            read(&mut field, &idle, 2250),
+            Some(Fill::Solid(Color::WHITE))
+        );
+        assert_eq!(
+            read(&mut field, &idle, 2300),
+            Some(Fill::Solid(Color::WHITE))
+        );
+    }
+
+    #[test]
+    fn box_shadows_interpolate_pairwise() {
+        let shadow = |blur, spread, inset| BoxShadow {
+            blur_radius: PxPct::Px(blur),
+            color: Color::BLACK,
+            spread: PxPct::Px(spread),
+            inset,
+            ..Default::default()
+        };
+        let from: SmallVec<[BoxShadow; 3]> =
+            SmallVec::from_slice(&[shadow(0.0, 10.0, false), shadow(4.0, 0.0, true)]);
+        let to = SmallVec::from_slice(&[shadow(10.0, 20.0, false), shadow(8.0, 2.0, true)]);
+        let mid = from.interpolate(&to, 0.5).unwrap();
+        assert_eq!(mid[0], shadow(5.0, 15.0, false));
+        assert_eq!(mid[1], shadow(6.0, 1.0, true));
+
+        // Shadows which can't be matched up jump to the new value
+        let one_shadow = SmallVec::from_slice(&[shadow(10.0, 20.0, false)]);
+        assert_eq!(from.interpolate(&one_shadow, 0.5), None);
+        let swapped = SmallVec::from_slice(&[shadow(4.0, 0.0, true), shadow(0.0, 10.0, false)]);
+        assert_eq!(from.interpolate(&swapped, 0.5), None);
+    }
+
+    #[test]
+    fn linear_gradient_geometry() {
+        let gradient = Gradient::linear(90.0)
+            .stop(0.0, Color::BLACK)
+            .stop(1.0, Color::WHITE);
+        let rect = kurbo::Rect::new(0.0, 0.0, 100.0, 50.0);
+        let floem_peniko::Brush::Gradient(brush) = gradient.to_brush(rect) else {
+            panic!("expected a gradient brush");
+        };
+        let floem_peniko::GradientKind::Linear { start, end } = brush.kind else {
+            panic!("expected a linear gradient");
+        };
+        assert!((start - kurbo::Point::new(0.0, 25.0)).hypot() < 1e-9);
+        assert!((end - kurbo::Point::new(100.0, 25.0)).hypot() < 1e-9);
+        assert_eq!(brush.stops.len(), 2);
+
+        let linear = gradient.color_space(GradientColorSpace::LinearSrgb);
+        let floem_peniko::Brush::Gradient(brush) = linear.to_brush(rect) else {
+            panic!("expected a gradient brush");
+        };
+        assert_eq!(brush.stops.len(), Gradient::LINEAR_SRGB_STEPS + 1);
+        // The midpoint of black to white in linear light is lighter than in sRGB.
+        let mid = brush.stops[Gradient::LINEAR_SRGB_STEPS / 2].color;
+        assert!(mid.r > 128);
+    }
+
+    #[test]
+    fn breakpoints() {
+        let style = Style::new()
+            .padding_left(1.0)
+            .at_breakpoint(1000.0, |s| s.padding_left(3.0))
+            .at_breakpoint(500.0, |s| s.padding_left(2.0).padding_bottom(5.0))
+            .apply(Style::new().at_breakpoint(1000.0, |s| s.padding_bottom(6.0)));
+        let at_width = |width| {
+            let mut style = style.clone();
+            style.apply_interact_state(&InteractionState::default(), ScreenSizeBp::Xs, width, 0.0);
+            (style.get(PaddingLeft), style.get(PaddingBottom))
+        };
+        assert_eq!(at_width(499.0), (PxPct::Px(1.0), PxPct::Px(0.0)));
+        assert_eq!(at_width(500.0), (PxPct::Px(2.0), PxPct::Px(5.0)));
+        assert_eq!(at_width(1200.0), (PxPct::Px(3.0), PxPct::Px(6.0)));
+
+        // Container queries use the width of the view instead of the window
+        let mut style = Style::new().at_container_width(300.0, |s| s.padding_left(4.0));
+        style.apply_interact_state(&InteractionState::default(), ScreenSizeBp::Xs, 0.0, 300.0);
+        assert_eq!(style.get(PaddingLeft), PxPct::Px(4.0));
+    }
+
+    #[test]
+    fn rtl_mirrors_flex_layout() {
+        use super::Direction;
+        use taffy::style::{AlignItems, FlexDirection};
+
+        let row = Style::new().direction(Direction::Rtl).to_taffy_style();
+        assert_eq!(row.flex_direction, FlexDirection::RowReverse);
+
+        let column = Style::new()
+            .direction(Direction::Rtl)
+            .flex_col()
+            .align_items(Some(AlignItems::FlexStart))
+            .to_taffy_style();
+        assert_eq!(column.flex_direction, FlexDirection::Column);
+        assert_eq!(column.align_items, Some(AlignItems::FlexEnd));
+
+        // The children of the column are aligned on the same axis
+        let child = Style::new().align_self(Some(AlignItems::Start));
+        assert_eq!(child.to_taffy_style().align_self, Some(AlignItems::Start));
+        let child = child.set(super::InRtlColumn, true).to_taffy_style();
+        assert_eq!(child.align_self, Some(AlignItems::End));
+    }
+
+    #[test]
+    fn logical_spacing() {
+        use super::{Direction, PaddingRight, PaddingTop};
+
+        let style = Style::new()
+            .padding_inline_start(4.0)
+            .padding_inline_end(6.0)
+            .padding_block_start(2.0)
+            .padding_right(1.0);
+
+        let mut ltr = style.clone();
+        ltr.resolve_logical_spacing(Direction::Ltr);
+        assert_eq!(ltr.get(PaddingLeft), PxPct::Px(4.0));
+        assert_eq!(ltr.get(PaddingTop), PxPct::Px(2.0));
+        // Physical properties take precedence
+        assert_eq!(ltr.get(PaddingRight), PxPct::Px(1.0));
+
+        let mut rtl = style;
+        rtl.resolve_logical_spacing(Direction::Rtl);
+        assert_eq!(rtl.get(PaddingLeft), PxPct::Px(6.0));
+        assert_eq!(rtl.get(PaddingRight), PxPct::Px(1.0));
+    }
+
+    #[test]
+    fn class_registry() {
+        use crate::style::{StyleClass, StyleClassRef};
+        use crate::widgets::ButtonClass;
+
+        mod a {
+            crate::style_class!(pub DupClass);
+        }
+        mod b {
+            crate::style_class!(pub DupClass);
+        }
+        b::DupClass::class_ref();
+        a::DupClass::class_ref();
+
+        // Short names resolve to the first full name in order
+        assert_eq!(
+            StyleClassRef::by_name("DupClass"),
+            Some(a::DupClass::class_ref())
+        );
+        assert_eq!(
+            StyleClassRef::by_name(b::DupClass::class_ref().name()),
+            Some(b::DupClass::class_ref())
+        );
+        assert_eq!(StyleClassRef::by_name("MissingClass"), None);
+
+        // Built-in classes are registered without being used
+        assert_eq!(
+            StyleClassRef::by_name("floem::widgets::button::ButtonClass"),
+            Some(ButtonClass::class_ref())
+        );
+        let names: Vec<_> = StyleClassRef::registered().map(|c| c.name()).collect();
+        for name in [
+            "floem::widgets::slider::FocusedHandleClass",
+            "floem::widgets::data_table::DataTableClass",
+            "floem::views::scroll::Handle",
+            "floem::menu::MnemonicClass",
+        ] {
+            assert!(names.contains(&name), "{name} isn't registered");
+        }
+        assert!(names.contains(&a::DupClass::class_ref().name()));
+        assert!(names.contains(&b::DupClass::class_ref().name()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use crate::style::{AlignItems, CursorStyle, Display, ZIndex};
+        use crate::unit::PxPctAuto;
+        use floem_renderer::cosmic_text::Weight;
+
+        let style = Style::new()
+            .display(Display::Grid)
+            .padding_left(8.0)
+            .padding_right(PxPct::Pct(12.5))
+            .width(PxPctAuto::Auto)
+            .margin_top(-3.25)
+            .align_items(Some(AlignItems::FlexEnd))
+            .color(Color::rgba8(1, 2, 3, 4))
+            .background(Color::rgb8(0x10, 0x20, 0x30))
+            .font_weight(Weight::BOLD)
+            .font_size(13.5)
+            .font_family("Fira Sans".to_string())
+            .cursor(CursorStyle::NwseResize)
+            .set_style_value(ZIndex, StyleValue::Unset)
+            .hover(|s| s.border_color(Color::WHITE).flex_grow(2.0));
+
+        let json = serde_json::to_string(&style).unwrap();
+        let parsed: Style = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        assert_eq!(parsed.get(PaddingLeft), PxPct::Px(8.0));
+        assert_eq!(
+            parsed.get(Background),
+            Some(Color::rgb8(0x10, 0x20, 0x30).into())
+        );
+
+        let gradient = Style::new().background_gradient(Gradient::linear(0.0));
+        assert!(serde_json::to_string(&gradient).is_err());
+    }
+
+    #[test]
+    fn font_fallback_follows_the_family() {
+        use floem_renderer::cosmic_text::FamilyOwned;
+
+        let name = |name: &str| FamilyOwned::Name(name.to_string());
+        let read = |style: &Style| {
+            let mut font = FontProps::default();
+            font.read_explicit(style, style, &Instant::now(), &mut false);
+            font.families()
+        };
+        assert_eq!(read(&Style::new()), None);
+
+        let style = Style::new()
+            .font_family("Inter")
+            .font_fallback("Noto Sans Arabic, Inter");
+        set_font_fallback(vec![name("Noto Color Emoji")]);
+        assert_eq!(
+            read(&style),
+            Some(vec![
+                name("Inter"),
+                name("Noto Sans Arabic"),
+                name("Noto Color Emoji")
+            ])
+        );
+        assert_eq!(
+            read(&Style::new()),
+            Some(vec![FamilyOwned::SansSerif, name("Noto Color Emoji")])
+        );
+        set_font_fallback(Vec::new());
+    }
+
+    #[test]
+    fn text_transform_follows_the_language() {
+        let text = "istanbul IĞDIR";
+        assert_eq!(TextTransform::Uppercase.apply(text, None), "ISTANBUL IĞDIR");
+        assert_eq!(
+            TextTransform::Uppercase.apply(text, Some("tr-TR")),
+            "İSTANBUL IĞDIR"
+        );
+        assert_eq!(TextTransform::Lowercase.apply(text, None), "istanbul iğdir");
+        assert_eq!(
+            TextTransform::Lowercase.apply(text, Some("tr")),
+            "istanbul ığdır"
+        );
+        assert_eq!(
+            TextTransform::Capitalize.apply(text, Some("az")),
+            "İstanbul IĞDIR"
+        );
+        // The final sigma has its own lowercase
+        assert_eq!(
+            TextTransform::Lowercase.apply("ΟΔΟΣ", None),
+            "\u{3bf}\u{3b4}\u{3bf}\u{3c2}"
+        );
+    }
+
+    #[test]
+    fn focus_within_restyles_the_wrapper() {
+        let widths = Rc::new(RefCell::new(Vec::new()));
+        let wrapper_widths = widths.clone();
+        let mut window = HeadlessWindow::new(Size::new(200.0, 50.0), move || {
+            h_stack((
+                container(empty().keyboard_navigatable().style(|s| s.size(50.0, 50.0)))
+                    .on_resize(move |rect| wrapper_widths.borrow_mut().push(rect.width()))
+                    .style(|s| s.width(100.0).focus_within(|s| s.width(120.0))),
+                empty().keyboard_navigatable().style(|s| s.size(50.0, 50.0)),
+            ))
+        });
+        window.update();
+
+        window.click(Point::new(25.0, 25.0));
+        window.update();
+        window.click(Point::new(150.0, 25.0));
+        window.update();
+        assert_eq!(*widths.borrow(), [100.0, 120.0, 100.0]);
+    }
+
+    #[test]
+    fn disabled_applies_to_descendants_but_checked_does_not() {
+        let disabled = create_rw_signal(false);
+        let checked = create_rw_signal(false);
+        let mark_checked = create_rw_signal(false);
+        let sizes = Rc::new(RefCell::new(Vec::new()));
+        let mark_sizes = sizes.clone();
+        let mark = Rc::new(Cell::new(None));
+        let mark_id = mark.clone();
+        let mut window = HeadlessWindow::new(Size::new(200.0, 50.0), move || {
+            let view = empty()
+                .keyboard_navigatable()
+                .checked(move || mark_checked.get())
+                .on_resize(move |rect| mark_sizes.borrow_mut().push(rect.size()))
+                .style(|s| {
+                    s.size(10.0, 10.0)
+                        .checked(|s| s.width(20.0))
+                        .disabled(|s| s.height(5.0))
+                });
+            mark_id.set(Some(view.id()));
+            container(view)
+                .disabled(move || disabled.get())
+                .checked(move || checked.get())
+        });
+        window.update();
+        let mark = mark.get().unwrap();
+
+        // An unchecked view in a checked one stays unchecked
+        checked.set(true);
+        window.update();
+        disabled.set(true);
+        window.update();
+        assert!(!window.simulate_click(mark));
+        mark_checked.set(true);
+        window.update();
+        assert_eq!(
+            *sizes.borrow(),
+            [
+                Size::new(10.0, 10.0),
+                Size::new(10.0, 5.0),
+                Size::new(20.0, 5.0)
+            ]
+        );
+    }
 }
@@ -0,0 +1,476 @@
+//! # Stylesheets
+//!
+//! A stylesheet is a JSON file with styles for [style classes](crate::style::StyleClass), which
+//! is reloaded when it changes, so styles can be tweaked without recompiling the app.
+//! ```json
+//! {
+//!     "TooltipClass": {
+//!         "padding_left": "8px",
+//!         "padding_right": "8px",
+//!         "background": "#3c3c3c",
+//!         "hover": { "background": "#505050" }
+//!     }
+//! }
+//! ```
+//! Each rule is named after a registered class, with or without its module path, as looked up
+//! by [`StyleClassRef::by_name`](crate::style::StyleClassRef::by_name). It sets built-in
+//! properties by the name of their [`Style`] method. Values are written as described in
+//! [`StylePropValue::parse`](crate::style::StylePropValue::parse); numbers can be written
+//! without quotes. `padding` and `margin` set the four sides like in CSS, with one to four
+//! values such as `"4px 8px"`. A rule can contain the selectors `hover`, `focus`, `focus_visible`,
+//! `focus_within`, `disabled`, `active`, `dragging`, `selected`, `checked`, `first_child`,
+//! `last_child`, `empty` and `placeholder`, which hold styles in the same format.
+//!
+//! Stylesheet styles apply to the views of every window, with the same priority as the
+//! default theme. Styles set on views, and classes styled by ancestors, take precedence.
+//!
+//! When the file can't be read or has an error, the last valid version stays applied and the
+//! errors are reported through [`StylesheetWatcher::errors`], and logged as warnings.
+
+use std::{
+    any::Any,
+    cell::RefCell,
+    fmt,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, SystemTime},
+};
+
+use floem_reactive::{create_effect, create_rw_signal, ReadSignal, RwSignal};
+use indexmap::IndexMap;
+use serde_json::Value;
+
+use crate::{
+    app::{add_app_update_event, AppUpdateEvent},
+    ext_event::create_signal_from_channel,
+    style::{Style, StyleClassRef, StylePropRef, StyleSelector},
+};
+
+/// How often the file is checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+thread_local! {
+    static STYLESHEETS: RefCell<IndexMap<PathBuf, Style>> = Default::default();
+    /// The styles of all the stylesheets, until one of them changes.
+    static MERGED_STYLE: RefCell<Option<Rc<Style>>> = Default::default();
+}
+
+/// The styles of all loaded stylesheets, in the order they were first loaded. It's the same
+/// style until a stylesheet changes.
+pub(crate) fn stylesheet_style() -> Option<Rc<Style>> {
+    MERGED_STYLE.with(|merged| {
+        let mut merged = merged.borrow_mut();
+        if merged.is_none() {
+            *merged = STYLESHEETS.with(|sheets| {
+                let sheets = sheets.borrow();
+                if sheets.is_empty() {
+                    return None;
+                }
+                let style = sheets
+                    .values()
+                    .fold(Style::new(), |style, sheet| style.apply(sheet.clone()));
+                Some(Rc::new(style))
+            });
+        }
+        merged.clone()
+    })
+}
+
+fn update_stylesheets(f: impl FnOnce(&mut IndexMap<PathBuf, Style>)) {
+    STYLESHEETS.with(|sheets| f(&mut sheets.borrow_mut()));
+    MERGED_STYLE.with(|merged| merged.borrow_mut().take());
+    add_app_update_event(AppUpdateEvent::StylesheetChanged);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum StylesheetError {
+    /// The file couldn't be read.
+    Io { path: PathBuf, message: String },
+    /// The file isn't valid JSON, or isn't an object of rules.
+    Syntax {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// A rule doesn't match any registered class.
+    UnknownClass { rule: String },
+    /// A rule sets a property, or uses a selector, which doesn't exist.
+    UnknownProperty { rule: String, property: String },
+    /// A property is set to a value which isn't valid for its type.
+    InvalidValue {
+        rule: String,
+        property: String,
+        value: String,
+    },
+}
+
+impl fmt::Display for StylesheetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StylesheetError::Io { path, message } => {
+                write!(f, "failed to read {}: {message}", path.display())
+            }
+            // The message of the JSON parser includes the position
+            StylesheetError::Syntax { message, .. } => f.write_str(message),
+            StylesheetError::UnknownClass { rule } => {
+                write!(f, "rule `{rule}` doesn't match a class")
+            }
+            StylesheetError::UnknownProperty { rule, property } => {
+                write!(f, "unknown property `{property}` in rule `{rule}`")
+            }
+            StylesheetError::InvalidValue {
+                rule,
+                property,
+                value,
+            } => write!(
+                f,
+                "invalid value `{value}` for property `{property}` in rule `{rule}`"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StylesheetError {}
+
+fn parse_rule(
+    rule: &str,
+    props: &serde_json::Map<String, Value>,
+    nested: bool,
+    errors: &mut Vec<StylesheetError>,
+) -> Style {
+    let mut style = Style::new();
+    for (name, value) in props {
+        let text = match value {
+            Value::String(text) => text.clone(),
+            Value::Number(number) => number.to_string(),
+            Value::Bool(value) => value.to_string(),
+            Value::Object(props) if !nested => {
+                match StyleSelector::from_name(name) {
+                    Some(selector) => {
+                        let over = parse_rule(rule, props, true, errors);
+                        style.set_selector(selector, over);
+                    }
+                    None => errors.push(StylesheetError::UnknownProperty {
+                        rule: rule.to_string(),
+                        property: name.clone(),
+                    }),
+                }
+                continue;
+            }
+            value => value.to_string(),
+        };
+        if let Some(sides) = shorthand_sides(name) {
+            match parse_sides(sides, &text) {
+                Some(values) => {
+                    for (prop, value) in values {
+                        style.set_raw(prop, value);
+                    }
+                }
+                None => errors.push(StylesheetError::InvalidValue {
+                    rule: rule.to_string(),
+                    property: name.clone(),
+                    value: text,
+                }),
+            }
+            continue;
+        }
+        let Some(prop) = StylePropRef::builtin(name) else {
+            errors.push(StylesheetError::UnknownProperty {
+                rule: rule.to_string(),
+                property: name.clone(),
+            });
+            continue;
+        };
+        match (prop.info.parse_any)(&text) {
+            Some(value) => style.set_raw(prop, value),
+            None => errors.push(StylesheetError::InvalidValue {
+                rule: rule.to_string(),
+                property: name.clone(),
+                value: text,
+            }),
+        }
+    }
+    style
+}
+
+/// The properties set by a shorthand property, in the CSS order of top, right, bottom and left.
+fn shorthand_sides(name: &str) -> Option<[&'static str; 4]> {
+    match name {
+        "padding" => Some([
+            "padding_top",
+            "padding_right",
+            "padding_bottom",
+            "padding_left",
+        ]),
+        "margin" => Some(["margin_top", "margin_right", "margin_bottom", "margin_left"]),
+        _ => None,
+    }
+}
+
+/// Parses the one to four values of a shorthand like CSS: one value sets every side, two set
+/// the vertical and horizontal sides, and three set the top, the horizontal sides and the
+/// bottom.
+fn parse_sides(sides: [&'static str; 4], text: &str) -> Option<Vec<(StylePropRef, Rc<dyn Any>)>> {
+    let values: Vec<&str> = text.split_whitespace().collect();
+    let [top, right, bottom, left] = match values[..] {
+        [all] => [all, all, all, all],
+        [vertical, horizontal] => [vertical, horizontal, vertical, horizontal],
+        [top, horizontal, bottom] => [top, horizontal, bottom, horizontal],
+        [top, right, bottom, left] => [top, right, bottom, left],
+        _ => return None,
+    };
+    sides
+        .into_iter()
+        .zip([top, right, bottom, left])
+        .map(|(side, text)| {
+            let prop = StylePropRef::builtin(side)?;
+            Some((prop, (prop.info.parse_any)(text)?))
+        })
+        .collect()
+}
+
+/// Parses a stylesheet, returning a style with a class style for each rule, or all the errors
+/// in it.
+pub fn parse_stylesheet(source: &str) -> Result<Style, Vec<StylesheetError>> {
+    let rules: serde_json::Map<String, Value> = serde_json::from_str(source).map_err(|e| {
+        vec![StylesheetError::Syntax {
+            line: e.line(),
+            column: e.column(),
+            message: e.to_string(),
+        }]
+    })?;
+
+    let mut style = Style::new();
+    let mut errors = Vec::new();
+    for (rule, props) in &rules {
+        let Some(class) = StyleClassRef::by_name(rule) else {
+            errors.push(StylesheetError::UnknownClass { rule: rule.clone() });
+            continue;
+        };
+        let Value::Object(props) = props else {
+            errors.push(StylesheetError::InvalidValue {
+                rule: rule.clone(),
+                property: String::new(),
+                value: props.to_string(),
+            });
+            continue;
+        };
+        let class_style = parse_rule(rule, props, false, &mut errors);
+        style.set_class(class, class_style);
+    }
+
+    if errors.is_empty() {
+        Ok(style)
+    } else {
+        Err(errors)
+    }
+}
+
+/// A stylesheet loaded with [`watch_stylesheet`].
+pub struct StylesheetWatcher {
+    path: PathBuf,
+    errors: RwSignal<Vec<StylesheetError>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl StylesheetWatcher {
+    /// The errors of the last time the file was loaded, which is empty if it was valid.
+    pub fn errors(&self) -> ReadSignal<Vec<StylesheetError>> {
+        self.errors.read_only()
+    }
+
+    /// Stops watching the file and removes its styles.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+        update_stylesheets(|sheets| {
+            sheets.shift_remove(&self.path);
+        });
+    }
+}
+
+/// Dropping the watcher stops watching the file, but its styles stay applied.
+impl Drop for StylesheetWatcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// Loads the stylesheet at `path`, and reloads it whenever the file changes. Rules are matched
+/// to the [registered](StyleClassRef::register) classes by name.
+///
+/// Errors are logged as warnings and reported by [`StylesheetWatcher::errors`]. The file is
+/// watched until the watcher is dropped or stopped.
+/// ```rust,no_run
+/// # use floem::stylesheet::watch_stylesheet;
+/// let stylesheet = watch_stylesheet("styles.json");
+/// ```
+pub fn watch_stylesheet(path: impl Into<PathBuf>) -> StylesheetWatcher {
+    let path = path.into();
+    let stop = Arc::new(AtomicBool::new(false));
+    let errors = create_rw_signal(Vec::new());
+
+    let (tx, rx) = crossbeam_channel::unbounded();
+    std::thread::spawn({
+        let path = path.clone();
+        let stop = stop.clone();
+        move || {
+            let mut last_modified = None;
+            let mut first = true;
+            while !stop.load(Ordering::Relaxed) {
+                let modified = modified(&path);
+                if first || modified != last_modified {
+                    first = false;
+                    last_modified = modified;
+                    let source = std::fs::read_to_string(&path).map_err(|e| e.to_string());
+                    if tx.send(source).is_err() {
+                        return;
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    });
+
+    let source = create_signal_from_channel(rx);
+    let sheet_path = path.clone();
+    create_effect(move |_| {
+        let Some(source) = source.get() else {
+            return;
+        };
+        let result = source
+            .map_err(|message| {
+                vec![StylesheetError::Io {
+                    path: sheet_path.clone(),
+                    message,
+                }]
+            })
+            .and_then(|source| parse_stylesheet(&source));
+        match result {
+            Ok(style) => {
+                update_stylesheets(|sheets| {
+                    sheets.insert(sheet_path.clone(), style);
+                });
+                errors.set(Vec::new());
+            }
+            Err(new_errors) => {
+                for error in &new_errors {
+                    log::warn!("stylesheet {}: {error}", sheet_path.display());
+                }
+                errors.set(new_errors);
+            }
+        }
+    });
+
+    StylesheetWatcher { path, errors, stop }
+}
+
+#[cfg(test)]
+mod tests {
+    use floem_peniko::Color;
+
+    use std::rc::Rc;
+
+    use super::{parse_stylesheet, stylesheet_style, update_stylesheets, StylesheetError};
+    use crate::style::{
+        Background, MarginLeft, MarginTop, PaddingBottom, PaddingLeft, PaddingRight, PaddingTop,
+        Style, StyleClass, StyleSelector,
+    };
+    use crate::unit::{PxPct, PxPctAuto};
+
+    crate::style_class!(TestClass);
+
+    #[test]
+    fn parse_rules() {
+        let source = r##"{
+            "TestClass": {
+                "padding_left": 8,
+                "background": "#102030",
+                "hover": { "padding_left": "50%" }
+            }
+        }"##;
+        TestClass::class_ref();
+        let style = parse_stylesheet(source).unwrap();
+        let class = &style.classes[&TestClass::class_ref()];
+        assert_eq!(class.get(PaddingLeft), PxPct::Px(8.0));
+        assert_eq!(
+            class.get(Background),
+            Some(Color::rgb8(0x10, 0x20, 0x30).into())
+        );
+        let hover = &class.selectors[&StyleSelector::Hover];
+        assert_eq!(hover.get(PaddingLeft), PxPct::Pct(50.0));
+    }
+
+    #[test]
+    fn report_errors() {
+        let source = r#"{
+            "TestClass": { "pading": "8px", "background": "blue", "margin": "1px 2px 3px 4px 5px" },
+            "Missing": {}
+        }"#;
+        TestClass::class_ref();
+        let errors = parse_stylesheet(source).unwrap_err();
+        assert_eq!(errors.len(), 4);
+        assert!(errors.contains(&StylesheetError::UnknownProperty {
+            rule: "TestClass".to_string(),
+            property: "pading".to_string(),
+        }));
+        assert!(errors.contains(&StylesheetError::InvalidValue {
+            rule: "TestClass".to_string(),
+            property: "margin".to_string(),
+            value: "1px 2px 3px 4px 5px".to_string(),
+        }));
+        assert!(errors.contains(&StylesheetError::InvalidValue {
+            rule: "TestClass".to_string(),
+            property: "background".to_string(),
+            value: "blue".to_string(),
+        }));
+        assert!(errors.contains(&StylesheetError::UnknownClass {
+            rule: "Missing".to_string(),
+        }));
+
+        let errors = parse_stylesheet("{ \"TestClass\": ").unwrap_err();
+        assert!(matches!(
+            errors[..],
+            [StylesheetError::Syntax { line: 1, .. }]
+        ));
+    }
+
+    #[test]
+    fn parse_shorthands() {
+        let source = r#"{
+            "TestClass": { "padding": "1px 2px 3px", "margin": 4 }
+        }"#;
+        TestClass::class_ref();
+        let style = parse_stylesheet(source).unwrap();
+        let class = &style.classes[&TestClass::class_ref()];
+        assert_eq!(class.get(PaddingTop), PxPct::Px(1.0));
+        assert_eq!(class.get(PaddingRight), PxPct::Px(2.0));
+        assert_eq!(class.get(PaddingBottom), PxPct::Px(3.0));
+        assert_eq!(class.get(PaddingLeft), PxPct::Px(2.0));
+        assert_eq!(class.get(MarginTop), PxPctAuto::Px(4.0));
+        assert_eq!(class.get(MarginLeft), PxPctAuto::Px(4.0));
+    }
+
+    #[test]
+    fn merged_style_is_kept_until_a_stylesheet_changes() {
+        update_stylesheets(|sheets| {
+            sheets.insert("a.json".into(), Style::new().padding_left(1.0));
+        });
+        let merged = stylesheet_style().unwrap();
+        assert!(Rc::ptr_eq(&merged, &stylesheet_style().unwrap()));
+
+        update_stylesheets(|sheets| {
+            sheets.insert("b.json".into(), Style::new().padding_left(2.0));
+        });
+        let changed = stylesheet_style().unwrap();
+        assert!(!Rc::ptr_eq(&merged, &changed));
+        assert_eq!(changed.get(PaddingLeft), PxPct::Px(2.0));
+    }
+}
@@ -0,0 +1,579 @@
+//! # Visual regression tests
+//!
+//! This module needs the `test-support` feature, which can be enabled for the tests only:
+//! ```toml
+//! [dev-dependencies]
+//! floem = { version = "0.1", features = ["test-support"] }
+//! ```
+//!
+//! This module compares screenshots of views against golden PNG images.
+//! Use [`check_golden`] to capture a view with [`Id::capture_screenshot`] and compare it, or
+//! [`compare_with_golden`] to compare an image you already have.
+//!
+//! On a mismatch, the captured image is written next to the golden image as `<name>.actual.png`
+//! along with `<name>.diff.png`, which shows differing pixels in red over a faded copy of the
+//! golden image. Set the `FLOEM_UPDATE_GOLDENS` environment variable to write the captured
+//! images as the new golden images instead of comparing them.
+//!
+//! ## Determinism
+//!
+//! Screenshots are only comparable when they are rendered the same way on every machine:
+//! - Give the window a fixed size. [`check_golden`] renders the window at scale 1.0 whatever the
+//!   scale of the monitor, and screenshots taken with another scale are rendered at that scale.
+//! - Set `font_family` on the root view to a font which is installed on every machine running the
+//!   tests. System font fallback differs between platforms.
+//! - Use the same renderer everywhere. The vger and tiny-skia renderers anti-alias differently.
+//! - Disable animations and transitions, or wait for them to finish before capturing.
+//!
+//! Small differences in anti-aliasing can still occur between GPUs, which is what the tolerances
+//! in [`GoldenOptions`] are for.
+//!
+//! Snapshots which include ids, like [`Id::debug_tree_string`], are only stable when the views
+//! are built in a [`with_id_scope`](crate::id::with_id_scope).
+//!
+//! ## Headless windows
+//!
+//! A [`HeadlessWindow`] runs the full update, style, layout and paint pipeline without a window
+//! or display server, painting with the tiny-skia renderer to an offscreen image at scale 1.0.
+//! This makes it deterministic and usable on CI machines without a display.
+//!
+//! Headless windows have their own clock, which only moves forward with
+//! [`HeadlessWindow::advance`], and which gestures, transitions, scroll animations and progress
+//! indicators measure time with while the window exists. Advancing it runs the timers of
+//! [`exec_after`](crate::action::exec_after) which are due, and the
+//! [`ext_event`](crate::ext_event) actions sent from other threads, like
+//! the application event loop would. [`HeadlessWindow::update`] runs the pending actions without
+//! moving the clock. The actions sent from other threads are queued for the whole process, so
+//! tests using them shouldn't run in parallel with each other.
+//!
+//! Input can be injected with methods like [`HeadlessWindow::click`] and
+//! [`HeadlessWindow::send_event`]. These dispatch the events through the same pipeline as real
+//! input, including hover, active and focus handling, and then run updates, style and layout
+//! before returning. Keys are pressed with [`HeadlessWindow::send_key`].
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use floem_winit::event::{ElementState, MouseButton, MouseScrollDelta};
+use image::{Rgba, RgbaImage};
+use kurbo::{Point, Size, Vec2};
+
+use crate::{
+    action::{Timer, TimerToken},
+    app::{AppUpdateEvent, APP_UPDATE_EVENTS},
+    clock,
+    context::EventPropagation,
+    event::Event,
+    ext_event::{EXT_EVENT_HANDLER, PROXY_ACTIONS},
+    gesture::GesturePhase,
+    id::{record_debug_tree, Id},
+    keyboard::{Key, KeyEvent, KeyInput, ModifiersState},
+    pointer::{PointerButton, PointerType, TouchEvent},
+    view::View,
+    window_handle::WindowHandle,
+};
+
+/// The environment variable which makes golden comparisons update the golden images.
+pub const UPDATE_GOLDENS_VAR: &str = "FLOEM_UPDATE_GOLDENS";
+
+/// How close a screenshot has to be to the golden image to match it.
+#[derive(Clone, Copy, Debug)]
+pub struct GoldenOptions {
+    /// The largest difference allowed in any channel before a pixel counts as different.
+    pub channel_tolerance: u8,
+    /// The number of different pixels allowed.
+    pub max_different_pixels: usize,
+}
+
+impl Default for GoldenOptions {
+    fn default() -> Self {
+        Self {
+            channel_tolerance: 2,
+            max_different_pixels: 0,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    /// The view couldn't be captured.
+    CaptureFailed,
+    /// There is no golden image. The captured image was written to `actual`.
+    MissingGolden {
+        actual: PathBuf,
+    },
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    /// Too many pixels differ. A diff image was written to `diff`.
+    Mismatch {
+        different_pixels: usize,
+        diff: PathBuf,
+    },
+    Image(image::ImageError),
+}
+
+impl std::fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoldenError::CaptureFailed => write!(f, "the view couldn't be captured"),
+            GoldenError::MissingGolden { actual } => write!(
+                f,
+                "missing golden image, the captured image was written to {}",
+                actual.display()
+            ),
+            GoldenError::SizeMismatch { expected, actual } => write!(
+                f,
+                "expected a {}x{} image, but captured a {}x{} image",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            GoldenError::Mismatch {
+                different_pixels,
+                diff,
+            } => write!(
+                f,
+                "{} pixels differ from the golden image, see {}",
+                different_pixels,
+                diff.display()
+            ),
+            GoldenError::Image(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl From<image::ImageError> for GoldenError {
+    fn from(err: image::ImageError) -> Self {
+        GoldenError::Image(err)
+    }
+}
+
+fn sibling_path(golden: &Path, suffix: &str) -> PathBuf {
+    let stem = golden
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    golden.with_file_name(format!("{stem}.{suffix}.png"))
+}
+
+fn pixels_match(a: &Rgba<u8>, b: &Rgba<u8>, tolerance: u8) -> bool {
+    a.0.iter()
+        .zip(b.0.iter())
+        .all(|(a, b)| a.abs_diff(*b) <= tolerance)
+}
+
+/// Returns the number of different pixels and an image highlighting them.
+/// Both images must have the same size.
+fn diff_images(expected: &RgbaImage, actual: &RgbaImage, tolerance: u8) -> (usize, RgbaImage) {
+    let mut different_pixels = 0;
+    let diff = RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let expected = expected.get_pixel(x, y);
+        if pixels_match(expected, actual.get_pixel(x, y), tolerance) {
+            let [r, g, b, _] = expected.0;
+            let faded = |c: u8| 191 + c / 4;
+            Rgba([faded(r), faded(g), faded(b), 255])
+        } else {
+            different_pixels += 1;
+            Rgba([255, 0, 0, 255])
+        }
+    });
+    (different_pixels, diff)
+}
+
+/// Compares `image` against the golden PNG image at `golden`.
+///
+/// If the `FLOEM_UPDATE_GOLDENS` environment variable is set, `image` is written to `golden`
+/// instead.
+pub fn compare_with_golden(
+    image: &RgbaImage,
+    golden: impl AsRef<Path>,
+    options: GoldenOptions,
+) -> Result<(), GoldenError> {
+    let golden = golden.as_ref();
+    if std::env::var_os(UPDATE_GOLDENS_VAR).is_some() {
+        image.save(golden)?;
+        return Ok(());
+    }
+
+    if !golden.exists() {
+        let actual = sibling_path(golden, "actual");
+        image.save(&actual)?;
+        return Err(GoldenError::MissingGolden { actual });
+    }
+
+    let expected = image::open(golden)?.to_rgba8();
+    if expected.dimensions() != image.dimensions() {
+        image.save(sibling_path(golden, "actual"))?;
+        return Err(GoldenError::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: image.dimensions(),
+        });
+    }
+
+    let (different_pixels, diff_image) = diff_images(&expected, image, options.channel_tolerance);
+    if different_pixels > options.max_different_pixels {
+        let diff = sibling_path(golden, "diff");
+        diff_image.save(&diff)?;
+        image.save(sibling_path(golden, "actual"))?;
+        return Err(GoldenError::Mismatch {
+            different_pixels,
+            diff,
+        });
+    }
+    Ok(())
+}
+
+/// Captures the view `id` at scale 1.0 on the next frame and compares it with the golden PNG
+/// image at `golden`, calling `done` with the result. See [`compare_with_golden`].
+pub fn check_golden(
+    id: Id,
+    golden: impl Into<PathBuf>,
+    options: GoldenOptions,
+    done: impl FnOnce(Result<(), GoldenError>) + 'static,
+) {
+    let golden = golden.into();
+    id.capture_screenshot(Some(1.0), move |image| {
+        done(match image {
+            Some(image) => compare_with_golden(&image, &golden, options),
+            None => Err(GoldenError::CaptureFailed),
+        })
+    });
+}
+
+/// A window without a native window, which renders to an offscreen image. See the
+/// [module documentation](self#headless-windows).
+pub struct HeadlessWindow {
+    handle: WindowHandle,
+    timers: HashMap<TimerToken, Timer>,
+    now: Instant,
+}
+
+impl HeadlessWindow {
+    /// Creates a headless window of `size` logical pixels, with the default theme applied.
+    /// The views' debug info is recorded for [`Id::debug_tree_string`].
+    pub fn new<V: View + 'static>(size: Size, view: impl FnOnce() -> V + 'static) -> Self {
+        record_debug_tree(true);
+        let now = Instant::now();
+        clock::set_fixed_now(Some(now));
+        let handle = WindowHandle::new_headless(move |_| Box::new(view()), size, 1.0, true);
+        Self {
+            handle,
+            timers: HashMap::new(),
+            now,
+        }
+    }
+
+    pub fn set_size(&mut self, size: Size) {
+        self.handle.size(size);
+    }
+
+    /// Changes the scale factor of the window, like moving it to a monitor with another DPI.
+    pub fn set_scale_factor(&mut self, scale: f64) {
+        self.handle.scale(scale);
+    }
+
+    /// Runs the actions sent from other threads and the timers which are due, then processes
+    /// pending updates and runs style and layout if needed, without painting.
+    pub fn update(&mut self) {
+        self.run_until(self.now);
+    }
+
+    /// Moves the clock of the window forward by `duration`, running the timers which become due
+    /// in the order of their deadlines, with the clock at each deadline. Timers started by them
+    /// run in the same call if they're due before the end.
+    pub fn advance(&mut self, duration: Duration) {
+        self.run_until(self.now + duration);
+    }
+
+    fn run_until(&mut self, end: Instant) {
+        loop {
+            self.take_app_update_events();
+            let mut ran = false;
+            while let Some(action) = { PROXY_ACTIONS.lock().pop_front() } {
+                action();
+                ran = true;
+            }
+            while let Some(trigger) = { EXT_EVENT_HANDLER.queue.lock().pop_front() } {
+                trigger.notify();
+                ran = true;
+            }
+            if !ran {
+                let next = self
+                    .timers
+                    .values()
+                    .filter(|timer| timer.deadline <= end)
+                    .min_by_key(|timer| timer.deadline)
+                    .map(|timer| timer.token);
+                if let Some(timer) = next.and_then(|token| self.timers.remove(&token)) {
+                    self.now = self.now.max(timer.deadline);
+                    clock::set_fixed_now(Some(self.now));
+                    (timer.action)(timer.token);
+                    ran = true;
+                }
+            }
+            self.handle.process_update_no_paint();
+            if !ran {
+                break;
+            }
+        }
+        self.now = end;
+        clock::set_fixed_now(Some(end));
+    }
+
+    /// Handles the events for the application which matter to a headless window. The others
+    /// need the event loop, and are dropped.
+    fn take_app_update_events(&mut self) {
+        let events = APP_UPDATE_EVENTS.with(|events| std::mem::take(&mut *events.borrow_mut()));
+        for event in events {
+            match event {
+                AppUpdateEvent::RequestTimer { mut timer } => {
+                    // The deadline is on the system clock, the timer is due after the same
+                    // duration on the clock of the window
+                    let duration = timer.deadline.saturating_duration_since(Instant::now());
+                    timer.deadline = self.now + duration;
+                    self.timers.insert(timer.token, timer);
+                }
+                AppUpdateEvent::CancelTimer { token } => {
+                    self.timers.remove(&token);
+                }
+                #[cfg(feature = "stylesheet")]
+                AppUpdateEvent::StylesheetChanged => self.handle.restyle(),
+                _ => {}
+            }
+        }
+    }
+
+    /// Sets the modifiers used for the following pointer and key events.
+    pub fn set_modifiers(&mut self, modifiers: ModifiersState) {
+        self.handle.modifiers = modifiers;
+    }
+
+    /// Dispatches `event` to the views, then runs updates, style and layout.
+    /// Returns [`EventPropagation::Stop`] if a view handled the event.
+    pub fn send_event(&mut self, event: Event) -> EventPropagation {
+        self.handle.event(event)
+    }
+
+    /// Clicks the center of the view `id`, returning `true` if a view handled the click.
+    /// See [`Id::simulate_click`].
+    pub fn simulate_click(&mut self, id: Id) -> bool {
+        self.handle.process_update_no_paint();
+        self.handle.simulate_click(id)
+    }
+
+    /// Presses and releases the key which types `key` while `modifiers` are held, then restores
+    /// the modifiers. Returns [`EventPropagation::Stop`] if a view handled the press.
+    ///
+    /// The key is at its place on a US layout, which matters for shortcuts, see
+    /// [`KeyInput::pressed`].
+    pub fn send_key(&mut self, key: Key, modifiers: ModifiersState) -> EventPropagation {
+        let press = KeyInput::pressed(key);
+        let release = press.released();
+        let held = std::mem::replace(&mut self.handle.modifiers, modifiers);
+        let propagation = self.handle.event(Event::KeyDown(KeyEvent {
+            key: press,
+            modifiers,
+        }));
+        self.handle.event(Event::KeyUp(KeyEvent {
+            key: release,
+            modifiers,
+        }));
+        self.handle.modifiers = held;
+        propagation
+    }
+
+    /// Moves the pointer to `pos`, in logical pixels relative to the window.
+    pub fn pointer_move(&mut self, pos: Point) {
+        self.handle.pointer_move(pos);
+    }
+
+    /// Moves the pointer to `pos` and presses `button`.
+    pub fn pointer_down(&mut self, pos: Point, button: PointerButton) {
+        self.handle.pointer_move(pos);
+        self.handle
+            .mouse_input(mouse_button(button), ElementState::Pressed);
+    }
+
+    /// Moves the pointer to `pos` and releases `button`.
+    pub fn pointer_up(&mut self, pos: Point, button: PointerButton) {
+        self.handle.pointer_move(pos);
+        self.handle
+            .mouse_input(mouse_button(button), ElementState::Released);
+    }
+
+    /// Presses and releases the primary button at `pos`.
+    pub fn click(&mut self, pos: Point) {
+        self.pointer_down(pos, PointerButton::Primary);
+        self.pointer_up(pos, PointerButton::Primary);
+    }
+
+    /// Sends the `phase` of the touch `id` of a finger at `pos`, in logical pixels relative to
+    /// the window.
+    pub fn touch(&mut self, id: u64, phase: GesturePhase, pos: Point) {
+        let pressure = match phase {
+            GesturePhase::Ended | GesturePhase::Cancelled => 0.0,
+            GesturePhase::Began | GesturePhase::Changed => 0.5,
+        };
+        self.handle.event(Event::Touch(TouchEvent {
+            id,
+            phase,
+            pos,
+            pointer_type: PointerType::Touch,
+            pressure,
+            tilt: None,
+        }));
+    }
+
+    /// Sets the pressure of the pointer, from 0.0 to 1.0, like a pressure-sensitive touchpad.
+    pub fn set_pressure(&mut self, pressure: f64) {
+        self.handle.touchpad_pressure(pressure);
+    }
+
+    /// Scrolls by `delta` logical pixels with the pointer at `pos`.
+    pub fn wheel(&mut self, pos: Point, delta: Vec2) {
+        self.handle.pointer_move(pos);
+        // `mouse_wheel` negates the delta to match the platform scroll direction.
+        self.handle.mouse_wheel(MouseScrollDelta::PixelDelta(
+            floem_winit::dpi::PhysicalPosition::new(-delta.x, -delta.y),
+        ));
+    }
+
+    /// Drags the file at `path` from outside the app over the window, with the pointer at `pos`.
+    pub fn hover_file(&mut self, path: impl Into<PathBuf>, pos: Point) {
+        self.handle.pointer_move(pos);
+        self.handle.hovered_file(path.into());
+    }
+
+    /// Drops the file at `path`, which was dragged over the window, with the pointer at `pos`.
+    pub fn drop_file(&mut self, path: impl Into<PathBuf>, pos: Point) {
+        self.handle.pointer_move(pos);
+        self.handle.dropped_file(path.into());
+    }
+
+    /// Sets whether only the parts of the window which changed are repainted, see
+    /// [`WindowConfig::partial_present`](crate::window::WindowConfig::partial_present).
+    pub fn set_partial_present(&mut self, partial_present: bool) {
+        self.handle.set_partial_present(partial_present);
+    }
+
+    /// Processes pending updates and renders a frame, returning the rendered image.
+    pub fn render(&mut self) -> Option<RgbaImage> {
+        self.handle
+            .render_frame_image()
+            .map(|image| image.to_rgba8())
+    }
+}
+
+fn mouse_button(button: PointerButton) -> MouseButton {
+    match button {
+        PointerButton::Primary => MouseButton::Left,
+        PointerButton::Secondary => MouseButton::Right,
+        PointerButton::Auxiliary => MouseButton::Middle,
+        PointerButton::X1 => MouseButton::Back,
+        PointerButton::X2 => MouseButton::Forward,
+        PointerButton::None => MouseButton::Other(0),
+    }
+}
+
+impl Drop for HeadlessWindow {
+    fn drop(&mut self) {
+        self.handle.destroy();
+        clock::set_fixed_now(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use floem_reactive::create_rw_signal;
+    use image::{Rgba, RgbaImage};
+    use kurbo::Size;
+
+    use super::{diff_images, HeadlessWindow};
+    use crate::{
+        action::{cancel_timer, exec_after, set_interval},
+        view::View,
+        views::{dyn_container, empty},
+    };
+
+    #[test]
+    fn diff_counts_pixels_outside_tolerance() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([102, 100, 100, 255]));
+        actual.put_pixel(1, 0, Rgba([110, 100, 100, 255]));
+
+        let (different_pixels, diff) = diff_images(&expected, &actual, 2);
+        assert_eq!(different_pixels, 1);
+        assert_eq!(diff.get_pixel(1, 0), &Rgba([255, 0, 0, 255]));
+        assert_ne!(diff.get_pixel(0, 0), &Rgba([255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn timers_run_when_the_clock_advances() {
+        let fired = create_rw_signal(Vec::new());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            exec_after(Duration::from_millis(200), move |_| {
+                fired.update(|fired| fired.push("late"))
+            });
+            exec_after(Duration::from_millis(100), move |_| {
+                fired.update(|fired| fired.push("early"));
+                // Due 50ms after this timer ran, not after the clock moved
+                exec_after(Duration::from_millis(50), move |_| {
+                    fired.update(|fired| fired.push("chained"))
+                });
+            });
+            let cancelled = exec_after(Duration::from_millis(100), move |_| {
+                fired.update(|fired| fired.push("cancelled"))
+            });
+            cancel_timer(cancelled);
+            empty()
+        });
+
+        window.update();
+        assert!(fired.get_untracked().is_empty());
+        window.advance(Duration::from_millis(120));
+        assert_eq!(fired.get_untracked(), ["early"]);
+        window.advance(Duration::from_millis(100));
+        assert_eq!(fired.get_untracked(), ["early", "chained", "late"]);
+    }
+
+    #[test]
+    fn intervals_stop_when_their_owner_is_removed() {
+        let ticks = create_rw_signal(0);
+        let shown = create_rw_signal(true);
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            dyn_container(
+                move || shown.get(),
+                move |shown| {
+                    let view = empty();
+                    if shown {
+                        set_interval(Duration::from_millis(100), move || {
+                            ticks.update(|ticks| *ticks += 1)
+                        })
+                        .owner(view.id());
+                    }
+                    Box::new(view)
+                },
+            )
+        });
+
+        window.update();
+        window.advance(Duration::from_millis(250));
+        assert_eq!(ticks.get_untracked(), 2);
+
+        shown.set(false);
+        window.update();
+        // The timer is cancelled before it's due, instead of being skipped when it's due
+        window.advance(Duration::from_millis(10));
+        assert!(window.timers.is_empty());
+        window.advance(Duration::from_millis(200));
+        assert_eq!(ticks.get_untracked(), 2);
+    }
+}
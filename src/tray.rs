@@ -0,0 +1,296 @@
+//! # System tray icons
+//!
+//! A tray icon shows an icon with a tooltip in the system tray, and can show a [`Menu`] and run
+//! callbacks when it's clicked. Together with [`set_window_visible`](crate::window::set_window_visible)
+//! and [`focus_window_with_id`](crate::window::focus_window_with_id) this can be used to show and
+//! hide the windows of an app running in the background.
+//!
+//! Tray icons need the `tray` feature and are supported on Windows and macOS. On other platforms
+//! [`TrayIcon::build`] returns [`TrayError::Unsupported`].
+//!
+//! Tray icons must be built after the application is launched, for example when building the
+//! view of the first window. The icon is removed when its [`TrayHandle`] is dropped.
+//! ```rust,no_run
+//! use floem::menu::{Menu, MenuItem};
+//! use floem::tray::TrayIcon;
+//! use floem::window::{open_windows, set_window_visible};
+//!
+//! let tray = TrayIcon::new()
+//!     .tooltip("My App")
+//!     .menu(Menu::new("").entry(MenuItem::new("Quit").action(floem::quit_app)))
+//!     .on_click(|| {
+//!         for window_id in open_windows() {
+//!             set_window_visible(window_id, true);
+//!         }
+//!     })
+//!     .build();
+//! ```
+
+use std::fmt;
+
+use image::RgbaImage;
+
+use crate::menu::Menu;
+
+/// An error building or updating a tray icon.
+#[derive(Debug)]
+pub enum TrayError {
+    /// Tray icons aren't supported on this platform.
+    Unsupported,
+    /// The icon image couldn't be used.
+    Icon(String),
+    /// The platform failed to create the tray icon.
+    Os(String),
+}
+
+impl fmt::Display for TrayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrayError::Unsupported => write!(f, "tray icons are not supported on this platform"),
+            TrayError::Icon(err) => write!(f, "invalid tray icon: {err}"),
+            TrayError::Os(err) => write!(f, "failed to create the tray icon: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TrayError {}
+
+/// The configuration of a tray icon. Call [`TrayIcon::build`] to show it.
+#[derive(Default)]
+pub struct TrayIcon {
+    icon: Option<RgbaImage>,
+    tooltip: Option<String>,
+    menu: Option<Menu>,
+    on_click: Option<Box<dyn Fn()>>,
+    on_secondary_click: Option<Box<dyn Fn()>>,
+}
+
+impl TrayIcon {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn icon(mut self, icon: RgbaImage) -> Self {
+        self.icon = Some(icon);
+        self
+    }
+
+    pub fn tooltip(mut self, tooltip: impl Into<String>) -> Self {
+        self.tooltip = Some(tooltip.into());
+        self
+    }
+
+    /// The menu shown when the icon is clicked with the secondary button. The title of the
+    /// menu itself isn't shown.
+    pub fn menu(mut self, menu: Menu) -> Self {
+        self.menu = Some(menu);
+        self
+    }
+
+    /// Called when the icon is clicked with the primary button.
+    pub fn on_click(mut self, action: impl Fn() + 'static) -> Self {
+        self.on_click = Some(Box::new(action));
+        self
+    }
+
+    /// Called when the icon is clicked with the secondary button, in addition to showing the
+    /// menu if there is one.
+    pub fn on_secondary_click(mut self, action: impl Fn() + 'static) -> Self {
+        self.on_secondary_click = Some(Box::new(action));
+        self
+    }
+
+    /// Shows the tray icon.
+    pub fn build(self) -> Result<TrayHandle, TrayError> {
+        platform::build(self)
+    }
+}
+
+/// A tray icon which is shown until this is dropped.
+pub struct TrayHandle {
+    inner: platform::Tray,
+}
+
+impl TrayHandle {
+    pub fn set_icon(&self, icon: RgbaImage) -> Result<(), TrayError> {
+        self.inner.set_icon(icon)
+    }
+
+    pub fn set_tooltip(&self, tooltip: Option<&str>) {
+        self.inner.set_tooltip(tooltip);
+    }
+
+    pub fn set_menu(&self, menu: Option<Menu>) {
+        self.inner.set_menu(menu);
+    }
+
+    pub fn set_visible(&self, visible: bool) {
+        self.inner.set_visible(visible);
+    }
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos"))]
+mod platform {
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        rc::Rc,
+    };
+
+    use floem_reactive::Scope;
+    use image::RgbaImage;
+    use tray_icon::{menu::MenuId, ClickType, TrayIconBuilder, TrayIconEvent, TrayIconId};
+
+    use super::{TrayError, TrayIcon};
+    use crate::{
+        ext_event::create_signal_from_channel,
+        menu::native::{platform_menu, remove_menu_actions},
+        menu::Menu,
+    };
+
+    struct TrayCallbacks {
+        on_click: Option<Rc<dyn Fn()>>,
+        on_secondary_click: Option<Rc<dyn Fn()>>,
+        menu_items: Vec<MenuId>,
+    }
+
+    thread_local! {
+        static TRAYS: RefCell<HashMap<TrayIconId, TrayCallbacks>> = Default::default();
+        static LISTENING: Cell<bool> = const { Cell::new(false) };
+    }
+
+    pub(super) struct Tray {
+        tray: tray_icon::TrayIcon,
+    }
+
+    pub(super) fn build(config: TrayIcon) -> Result<Tray, TrayError> {
+        listen();
+
+        let mut builder = TrayIconBuilder::new();
+        if let Some(icon) = config.icon {
+            builder = builder.with_icon(platform_icon(icon)?);
+        }
+        if let Some(tooltip) = config.tooltip {
+            builder = builder.with_tooltip(tooltip);
+        }
+        let mut menu_items = Vec::new();
+        if let Some(menu) = config.menu {
+            builder = builder.with_menu(Box::new(platform_menu(menu, &mut menu_items)));
+        }
+        let tray = builder
+            .build()
+            .map_err(|err| TrayError::Os(err.to_string()))?;
+
+        TRAYS.with(|trays| {
+            trays.borrow_mut().insert(
+                tray.id().clone(),
+                TrayCallbacks {
+                    on_click: config.on_click.map(Rc::from),
+                    on_secondary_click: config.on_secondary_click.map(Rc::from),
+                    menu_items,
+                },
+            )
+        });
+        Ok(Tray { tray })
+    }
+
+    impl Tray {
+        pub(super) fn set_icon(&self, icon: RgbaImage) -> Result<(), TrayError> {
+            self.tray
+                .set_icon(Some(platform_icon(icon)?))
+                .map_err(|err| TrayError::Os(err.to_string()))
+        }
+
+        pub(super) fn set_tooltip(&self, tooltip: Option<&str>) {
+            let _ = self.tray.set_tooltip(tooltip);
+        }
+
+        pub(super) fn set_menu(&self, menu: Option<Menu>) {
+            let mut menu_items = Vec::new();
+            let menu = menu.map(|menu| {
+                Box::new(platform_menu(menu, &mut menu_items))
+                    as Box<dyn tray_icon::menu::ContextMenu>
+            });
+            self.tray.set_menu(menu);
+            TRAYS.with(|trays| {
+                if let Some(callbacks) = trays.borrow_mut().get_mut(self.tray.id()) {
+                    let old_items = std::mem::replace(&mut callbacks.menu_items, menu_items);
+                    remove_menu_actions(&old_items);
+                }
+            });
+        }
+
+        pub(super) fn set_visible(&self, visible: bool) {
+            let _ = self.tray.set_visible(visible);
+        }
+    }
+
+    impl Drop for Tray {
+        fn drop(&mut self) {
+            let callbacks = TRAYS.with(|trays| trays.borrow_mut().remove(self.tray.id()));
+            if let Some(callbacks) = callbacks {
+                remove_menu_actions(&callbacks.menu_items);
+            }
+        }
+    }
+
+    /// Forwards the tray events, which are sent from the platform event handlers, to the
+    /// callbacks on the main thread.
+    fn listen() {
+        if LISTENING.with(|listening| listening.replace(true)) {
+            return;
+        }
+
+        let tray_events = create_signal_from_channel(TrayIconEvent::receiver().clone());
+        let cx = Scope::new();
+        cx.create_effect(move |_| {
+            let action = tray_events.with(|event| {
+                let event = event.as_ref()?;
+                TRAYS.with(|trays| {
+                    let trays = trays.borrow();
+                    let callbacks = trays.get(&event.id)?;
+                    match event.click_type {
+                        ClickType::Left => callbacks.on_click.clone(),
+                        ClickType::Right => callbacks.on_secondary_click.clone(),
+                        ClickType::Double => None,
+                    }
+                })
+            });
+            if let Some(action) = action {
+                action();
+            }
+        });
+    }
+
+    fn platform_icon(icon: RgbaImage) -> Result<tray_icon::Icon, TrayError> {
+        let (width, height) = icon.dimensions();
+        tray_icon::Icon::from_rgba(icon.into_raw(), width, height)
+            .map_err(|err| TrayError::Icon(err.to_string()))
+    }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+mod platform {
+    use image::RgbaImage;
+
+    use super::{TrayError, TrayIcon};
+    use crate::menu::Menu;
+
+    pub(super) struct Tray;
+
+    pub(super) fn build(_config: TrayIcon) -> Result<Tray, TrayError> {
+        Err(TrayError::Unsupported)
+    }
+
+    impl Tray {
+        pub(super) fn set_icon(&self, _icon: RgbaImage) -> Result<(), TrayError> {
+            Err(TrayError::Unsupported)
+        }
+
+        pub(super) fn set_tooltip(&self, _tooltip: Option<&str>) {}
+
+        pub(super) fn set_menu(&self, _menu: Option<Menu>) {}
+
+        pub(super) fn set_visible(&self, _visible: bool) {}
+    }
+}
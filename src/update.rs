@@ -1,17 +1,21 @@
 use std::{any::Any, cell::RefCell, collections::HashMap};
 
+use floem_reactive::Scope;
 use floem_winit::window::ResizeDirection;
+use image::RgbaImage;
 use kurbo::{Point, Rect, Size, Vec2};
 
 use crate::{
     animate::{AnimUpdateMsg, Animation},
     context::{EventCallback, ResizeCallback},
-    event::EventListener,
+    event::{EventListener, FileDropEvent},
     id::Id,
     menu::Menu,
-    style::{Style, StyleClassRef, StyleSelector},
+    pointer::{PointerWheelEvent, TouchEvent},
+    style::{Display, Style, StyleClassRef, StyleSelector},
     view::View,
     view_data::{ChangeFlags, StackOffset},
+    window::{BlurStyle, WindowRegion},
 };
 
 thread_local! {
@@ -29,6 +33,7 @@ thread_local! {
 
 // pub type FileDialogs = HashMap<FileDialogToken, Box<dyn Fn(Option<FileInfo>)>>;
 type DeferredUpdateMessages = HashMap<Id, Vec<(Id, Box<dyn Any>)>>;
+pub(crate) type ScreenshotCallback = dyn FnOnce(Option<RgbaImage>);
 
 pub(crate) enum UpdateMessage {
     Focus(Id),
@@ -39,11 +44,27 @@ pub(crate) enum UpdateMessage {
         id: Id,
         is_disabled: bool,
     },
+    Checked {
+        id: Id,
+        is_checked: bool,
+    },
+    Display {
+        id: Id,
+        display: Display,
+    },
+    BringToFront {
+        id: Id,
+    },
     RequestChange {
         id: Id,
         flags: ChangeFlags,
     },
-    RequestPaint,
+    RequestPaint {
+        id: Id,
+    },
+    /// Requests the next frame of the window, after it was delayed to keep to its maximum
+    /// frame rate.
+    RequestFrame,
     State {
         id: Id,
         state: Box<dyn Any>,
@@ -57,6 +78,14 @@ pub(crate) enum UpdateMessage {
         id: Id,
         class: StyleClassRef,
     },
+    MinSize {
+        id: Id,
+        size: Size,
+    },
+    MaxSize {
+        id: Id,
+        size: Size,
+    },
     StyleSelector {
         id: Id,
         selector: StyleSelector,
@@ -73,21 +102,77 @@ pub(crate) enum UpdateMessage {
         listener: EventListener,
         action: Box<EventCallback>,
     },
+    CaptureListener {
+        id: Id,
+        listener: EventListener,
+        action: Box<EventCallback>,
+    },
     ResizeListener {
         id: Id,
         action: Box<ResizeCallback>,
     },
+    SizeListener {
+        id: Id,
+        action: Box<dyn Fn(Size)>,
+    },
     MoveListener {
         id: Id,
         action: Box<dyn Fn(Point)>,
     },
+    VisibilityListener {
+        id: Id,
+        threshold: f64,
+        action: Box<dyn Fn(bool)>,
+    },
+    IntersectionListener {
+        id: Id,
+        thresholds: Vec<f64>,
+        action: Box<dyn Fn(f64)>,
+    },
+    FileDropListener {
+        id: Id,
+        action: Box<dyn Fn(FileDropEvent)>,
+    },
     CleanupListener {
         id: Id,
         action: Box<dyn Fn()>,
     },
+    Autofocus {
+        id: Id,
+        force: bool,
+    },
+    MountListener {
+        id: Id,
+        action: Box<dyn Fn()>,
+    },
+    UnmountListener {
+        id: Id,
+        action: Box<dyn Fn()>,
+    },
+    TouchListener {
+        id: Id,
+        action: Box<dyn Fn(&TouchEvent)>,
+    },
+    WheelListener {
+        id: Id,
+        action: Box<dyn Fn(&PointerWheelEvent) -> bool>,
+    },
+    FocusListener {
+        id: Id,
+        action: Box<dyn Fn(bool)>,
+    },
+    FocusWithinListener {
+        id: Id,
+        action: Box<dyn Fn(bool)>,
+    },
     ToggleWindowMaximized,
     SetWindowMaximized(bool),
     MinimizeWindow,
+    RequestCloseWindow,
+    SetWindowDecorations(bool),
+    SetWindowBlur(BlurStyle),
+    SetWindowClickThrough(bool),
+    WindowHitTest(Box<dyn Fn(Point) -> WindowRegion>),
     DragWindow,
     DragResizeWindow(ResizeDirection),
     SetWindowDelta(Vec2),
@@ -121,6 +206,13 @@ pub(crate) enum UpdateMessage {
     RemoveOverlay {
         id: Id,
     },
+    AddPortal {
+        view: Box<dyn View>,
+        scope: Scope,
+    },
+    RemovePortal {
+        id: Id,
+    },
     Inspect,
     ScrollTo {
         id: Id,
@@ -134,4 +226,13 @@ pub(crate) enum UpdateMessage {
         position: Point,
         size: Size,
     },
+    CaptureScreenshot {
+        id: Id,
+        scale: Option<f64>,
+        callback: Box<ScreenshotCallback>,
+    },
+    SimulateClick {
+        id: Id,
+        callback: Box<dyn FnOnce(bool)>,
+    },
 }
@@ -84,15 +84,19 @@
 //!
 
 use floem_renderer::Renderer;
-use kurbo::{Circle, Insets, Line, Point, Rect, RoundedRect, Size};
+use kurbo::{
+    Arc, BezPath, Circle, Insets, Line, Point, Rect, RoundedRect, RoundedRectRadii, Shape, Size,
+    Vec2,
+};
 use std::any::Any;
+use std::f64::consts::PI;
 use taffy::prelude::Node;
 
 use crate::{
     context::{AppState, ComputeLayoutCx, EventCx, LayoutCx, PaintCx, StyleCx, UpdateCx},
     event::Event,
     id::Id,
-    style::{BoxShadowProp, Style, StyleClassRef},
+    style::{BoxShadow, BoxShadowProp, Style, StyleClassRef},
     view_data::ViewStyleProps,
     EventPropagation,
 };
@@ -228,10 +232,9 @@ pub trait View {
     /// Usually you'll call `paint_view` for every child view. But you might also draw text, adjust the offset, clip
     /// or draw text.
     fn paint(&mut self, cx: &mut PaintCx) {
-        self.for_each_child_mut(&mut |child| {
+        for child in stacked_children(self, cx.app_state) {
             cx.paint_view(child);
-            false
-        });
+        }
     }
 
     /// Scrolls the view and all direct and indirect children to bring the `target` view to be
@@ -276,10 +279,13 @@ pub fn default_event<V: View + ?Sized>(
     event: Event,
 ) -> EventPropagation {
     let mut handled = false;
-    view.for_each_child_rev_mut(&mut |child| {
-        handled |= cx.view_event(child, id_path, event.clone()).is_processed();
-        handled
-    });
+    // the child on top gets the event first, so events go where they're painted
+    for child in stacked_children(view, cx.app_state).into_iter().rev() {
+        if cx.view_event(child, id_path, event.clone()).is_processed() {
+            handled = true;
+            break;
+        }
+    }
     if handled {
         EventPropagation::Stop
     } else {
@@ -287,75 +293,147 @@ pub fn default_event<V: View + ?Sized>(
     }
 }
 
+/// Returns the children of `view` from the bottom to the top, which is the order they're
+/// painted in. Children with a higher [`ZIndex`](crate::style::ZIndex) are above the others.
+/// Between children with the same z-index, the ones brought to the front with
+/// [`Id::bring_to_front`] are above the others, and otherwise later children are above.
+pub fn stacked_children<'a, V: View + ?Sized>(
+    view: &'a mut V,
+    app_state: &AppState,
+) -> Vec<&'a mut dyn View> {
+    let mut children = Vec::new();
+    view.for_each_child_mut(&mut |child| {
+        children.push(child);
+        false
+    });
+    children.sort_by_key(|child| app_state.stacking_order(child.id()));
+    children
+}
+
+/// Resolves the radius of each corner, falling back to `border_radius` for corners without
+/// their own radius.
+pub(crate) fn border_radii(style: &ViewStyleProps, size: Size) -> RoundedRectRadii {
+    let pixels = |px_pct| match px_pct {
+        crate::unit::PxPct::Px(px) => px,
+        crate::unit::PxPct::Pct(pct) => size.min_side() * (pct / 100.),
+    };
+    let radius = style.border_radius();
+    RoundedRectRadii::new(
+        pixels(style.border_top_left_radius().unwrap_or(radius)),
+        pixels(style.border_top_right_radius().unwrap_or(radius)),
+        pixels(style.border_bottom_right_radius().unwrap_or(radius)),
+        pixels(style.border_bottom_left_radius().unwrap_or(radius)),
+    )
+}
+
+pub(crate) fn has_radius(radii: &RoundedRectRadii) -> bool {
+    radii.top_left > 0.0
+        || radii.top_right > 0.0
+        || radii.bottom_right > 0.0
+        || radii.bottom_left > 0.0
+}
+
 pub(crate) fn paint_bg(
     cx: &mut PaintCx,
     computed_style: &Style,
     style: &ViewStyleProps,
     size: Size,
 ) {
-    let radius = match style.border_radius() {
-        crate::unit::PxPct::Px(px) => px,
-        crate::unit::PxPct::Pct(pct) => size.min_side() * (pct / 100.),
-    };
-    if radius > 0.0 {
-        let rect = size.to_rect();
+    let radii = border_radii(style, size);
+    let rect = size.to_rect();
+    if has_radius(&radii) {
         let width = rect.width();
         let height = rect.height();
-        if width > 0.0 && height > 0.0 && radius > width.max(height) / 2.0 {
+        // A single radius larger than the view makes a circle.
+        let is_circle = radii
+            .as_single_radius()
+            .is_some_and(|radius| radius > width.max(height) / 2.0);
+        if width > 0.0 && height > 0.0 && is_circle {
             let radius = width.max(height) / 2.0;
             let circle = Circle::new(rect.center(), radius);
             let bg = match style.background() {
-                Some(color) => color,
+                Some(fill) => fill.to_brush(rect),
                 None => return,
             };
-            cx.fill(&circle, bg, 0.0);
+            cx.fill(&circle, &bg, 0.0);
         } else {
-            paint_box_shadow(cx, computed_style, rect, Some(radius));
+            paint_box_shadow(cx, computed_style, rect, Some(radii));
             let bg = match style.background() {
-                Some(color) => color,
+                Some(fill) => fill.to_brush(rect),
                 None => return,
             };
-            let rounded_rect = rect.to_rounded_rect(radius);
-            cx.fill(&rounded_rect, bg, 0.0);
+            let rounded_rect = rect.to_rounded_rect(radii);
+            cx.fill(&rounded_rect, &bg, 0.0);
         }
     } else {
-        paint_box_shadow(cx, computed_style, size.to_rect(), None);
+        paint_box_shadow(cx, computed_style, rect, None);
         let bg = match style.background() {
-            Some(color) => color,
+            Some(fill) => fill.to_brush(rect),
             None => return,
         };
-        cx.fill(&size.to_rect(), bg, 0.0);
+        cx.fill(&rect, &bg, 0.0);
     }
 }
 
-fn paint_box_shadow(cx: &mut PaintCx, style: &Style, rect: Rect, rect_radius: Option<f64>) {
-    if let Some(shadow) = style.get(BoxShadowProp).as_ref() {
-        let min = rect.size().min_side();
-        let h_offset = match shadow.h_offset {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => min * (pct / 100.),
-        };
-        let v_offset = match shadow.v_offset {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => min * (pct / 100.),
-        };
-        let spread = match shadow.spread {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => min * (pct / 100.),
-        };
-        let blur_radius = match shadow.blur_radius {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => min * (pct / 100.),
-        };
+fn resolve_box_shadow(shadow: &BoxShadow, size: Size) -> (Vec2, f64, f64) {
+    let min = size.min_side();
+    let pixels = |px_pct| match px_pct {
+        crate::unit::PxPct::Px(px) => px,
+        crate::unit::PxPct::Pct(pct) => min * (pct / 100.),
+    };
+    (
+        Vec2::new(pixels(shadow.h_offset), pixels(shadow.v_offset)),
+        pixels(shadow.spread),
+        pixels(shadow.blur_radius),
+    )
+}
+
+/// Returns the rect the view paints to, relative to it, which is larger than its box with shadows
+/// and outlines.
+pub(crate) fn paint_bounds(computed_style: &Style, style: &ViewStyleProps, size: Size) -> Rect {
+    let rect = size.to_rect();
+    let outline = style.outline().0;
+    let mut bounds = rect.inflate(outline, outline);
+    for shadow in computed_style.get(BoxShadowProp).iter() {
+        if shadow.inset {
+            continue;
+        }
+        let (offset, spread, blur_radius) = resolve_box_shadow(shadow, size);
+        // The blurred edges reach past the blur radius, like in `paint_inset_box_shadow`
+        let extent = spread + blur_radius * 2.0 + 1.0;
+        bounds = bounds.union(rect.inflate(extent, extent) + offset / 2.0);
+    }
+    bounds
+}
+
+fn paint_box_shadow(
+    cx: &mut PaintCx,
+    style: &Style,
+    rect: Rect,
+    rect_radii: Option<RoundedRectRadii>,
+) {
+    // The first shadow is on top, so paint them back to front.
+    for shadow in style.get(BoxShadowProp).iter().rev() {
+        if shadow.inset {
+            continue;
+        }
+        let (offset, spread, blur_radius) = resolve_box_shadow(shadow, rect.size());
+        // Outer shadows are moved by half of their offset
         let inset = Insets::new(
-            -h_offset / 2.0,
-            -v_offset / 2.0,
-            h_offset / 2.0,
-            v_offset / 2.0,
+            -offset.x / 2.0,
+            -offset.y / 2.0,
+            offset.x / 2.0,
+            offset.y / 2.0,
         );
         let rect = rect.inflate(spread, spread).inset(inset);
-        if let Some(radii) = rect_radius {
-            let rounded_rect = RoundedRect::from_rect(rect, radii + spread);
+        if let Some(radii) = rect_radii {
+            let radii = RoundedRectRadii::new(
+                radii.top_left + spread,
+                radii.top_right + spread,
+                radii.bottom_right + spread,
+                radii.bottom_left + spread,
+            );
+            let rounded_rect = RoundedRect::from_rect(rect, radii);
             cx.fill(&rounded_rect, shadow.color, blur_radius);
         } else {
             cx.fill(&rect, shadow.color, blur_radius);
@@ -363,50 +441,147 @@ fn paint_box_shadow(cx: &mut PaintCx, style: &Style, rect: Rect, rect_radius: Op
     }
 }
 
-pub(crate) fn paint_outline(cx: &mut PaintCx, style: &ViewStyleProps, size: Size) {
-    let outline = style.outline().0;
+/// Paints the shadows with `inset` set. These are painted above the background, clipped to the
+/// view's box.
+pub(crate) fn paint_inset_box_shadow(
+    cx: &mut PaintCx,
+    computed_style: &Style,
+    style: &ViewStyleProps,
+    size: Size,
+) {
+    let shadows = computed_style.get(BoxShadowProp);
+    if !shadows.iter().any(|shadow| shadow.inset) {
+        return;
+    }
+    let rect = size.to_rect();
+    let radii = border_radii(style, size);
+
+    cx.save();
+    cx.clip(&rect.to_rounded_rect(radii));
+    for shadow in shadows.iter().rev() {
+        if !shadow.inset {
+            continue;
+        }
+        let (offset, spread, blur_radius) = resolve_box_shadow(shadow, size);
+        cx.fill(
+            &inset_shadow_ring(rect, radii, offset, spread, blur_radius),
+            shadow.color,
+            blur_radius,
+        );
+    }
+    cx.restore();
+}
+
+/// The shape of an inset shadow of the view's box `rect`: a ring around the rounded rect the
+/// shadow isn't cast on, which is `rect` shrunk by `spread` and moved by `offset`.
+fn inset_shadow_ring(
+    rect: Rect,
+    radii: RoundedRectRadii,
+    offset: Vec2,
+    spread: f64,
+    blur_radius: f64,
+) -> BezPath {
+    // The outer edge of the ring stays outside of the view, where its blur is clipped
+    let extent = spread.abs() + offset.hypot() + blur_radius * 2.0 + 1.0;
+    let outer = rect.inflate(extent, extent);
+    // Counterclockwise, so that the hole drawn clockwise isn't filled with the non-zero rule
+    let mut ring = BezPath::new();
+    ring.move_to((outer.x0, outer.y0));
+    ring.line_to((outer.x0, outer.y1));
+    ring.line_to((outer.x1, outer.y1));
+    ring.line_to((outer.x1, outer.y0));
+    ring.close_path();
+
+    let hole = rect.inflate(-spread, -spread) + offset;
+    if hole.width() > 0.0 && hole.height() > 0.0 {
+        let shrink = |radius: f64| (radius - spread).max(0.0);
+        let radii = RoundedRectRadii::new(
+            shrink(radii.top_left),
+            shrink(radii.top_right),
+            shrink(radii.bottom_right),
+            shrink(radii.bottom_left),
+        );
+        ring.extend(hole.to_rounded_rect(radii).path_elements(0.1));
+    }
+    ring
+}
+
+pub(crate) fn paint_outline(cx: &mut PaintCx, style: &ViewStyleProps, size: Size, snap: bool) {
+    let mut outline = style.outline().0;
+    if snap {
+        outline = cx.snap_width(outline);
+    }
     if outline == 0. {
         // TODO: we should warn! when outline is < 0
         return;
     }
     let half = outline / 2.0;
     let rect = size.to_rect().inflate(half, half);
-    let border_radius = match style.border_radius() {
-        crate::unit::PxPct::Px(px) => px,
-        crate::unit::PxPct::Pct(pct) => size.min_side() * (pct / 100.),
-    };
-    cx.stroke(
-        &rect.to_rounded_rect(border_radius + half),
-        style.outline_color(),
-        outline,
+    let radii = border_radii(style, size);
+    let radii = RoundedRectRadii::new(
+        radii.top_left + half,
+        radii.top_right + half,
+        radii.bottom_right + half,
+        radii.bottom_left + half,
     );
+    cx.stroke(&rect.to_rounded_rect(radii), style.outline_color(), outline);
 }
 
-pub(crate) fn paint_border(cx: &mut PaintCx, style: &ViewStyleProps, size: Size) {
-    let left = style.border_left().0;
-    let top = style.border_top().0;
-    let right = style.border_right().0;
-    let bottom = style.border_bottom().0;
+pub(crate) fn paint_border(cx: &mut PaintCx, style: &ViewStyleProps, size: Size, snap: bool) {
+    let mut left = style.border_left().0;
+    let mut top = style.border_top().0;
+    let mut right = style.border_right().0;
+    let mut bottom = style.border_bottom().0;
+    if snap {
+        left = cx.snap_width(left);
+        top = cx.snap_width(top);
+        right = cx.snap_width(right);
+        bottom = cx.snap_width(bottom);
+    }
 
     let border_color = style.border_color();
-    if left == top && top == right && right == bottom && bottom == left && left > 0.0 {
+    let left_color = style.border_left_color().unwrap_or(border_color);
+    let top_color = style.border_top_color().unwrap_or(border_color);
+    let right_color = style.border_right_color().unwrap_or(border_color);
+    let bottom_color = style.border_bottom_color().unwrap_or(border_color);
+
+    let radii = border_radii(style, size);
+    let same_width = left == top && top == right && right == bottom;
+    let same_color =
+        left_color == top_color && top_color == right_color && right_color == bottom_color;
+    if same_width && same_color && left > 0.0 {
         let half = left / 2.0;
         let rect = size.to_rect().inflate(-half, -half);
-        let radius = match style.border_radius() {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => size.min_side() * (pct / 100.),
-        };
-        if radius > 0.0 {
-            cx.stroke(&rect.to_rounded_rect(radius), border_color, left);
+        if has_radius(&radii) {
+            cx.stroke(&rect.to_rounded_rect(radii), left_color, left);
         } else {
-            cx.stroke(&rect, border_color, left);
+            cx.stroke(&rect, left_color, left);
         }
     } else {
+        // Paint each side separately. The sides stop where the rounded corners begin.
+        let (width, height) = (size.width, size.height);
+        let start = |radius: f64, other_side: f64| {
+            if radius > 0.0 {
+                other_side / 2.0 + radius
+            } else {
+                0.0
+            }
+        };
+        let end = |length: f64, radius: f64, other_side: f64| {
+            if radius > 0.0 {
+                length - other_side / 2.0 - radius
+            } else {
+                length
+            }
+        };
         if left > 0.0 {
             let half = left / 2.0;
             cx.stroke(
-                &Line::new(Point::new(half, 0.0), Point::new(half, size.height)),
-                border_color,
+                &Line::new(
+                    Point::new(half, start(radii.top_left, top)),
+                    Point::new(half, end(height, radii.bottom_left, bottom)),
+                ),
+                left_color,
                 left,
             );
         }
@@ -414,18 +589,21 @@ pub(crate) fn paint_border(cx: &mut PaintCx, style: &ViewStyleProps, size: Size)
             let half = right / 2.0;
             cx.stroke(
                 &Line::new(
-                    Point::new(size.width - half, 0.0),
-                    Point::new(size.width - half, size.height),
+                    Point::new(width - half, start(radii.top_right, top)),
+                    Point::new(width - half, end(height, radii.bottom_right, bottom)),
                 ),
-                border_color,
+                right_color,
                 right,
             );
         }
         if top > 0.0 {
             let half = top / 2.0;
             cx.stroke(
-                &Line::new(Point::new(0.0, half), Point::new(size.width, half)),
-                border_color,
+                &Line::new(
+                    Point::new(start(radii.top_left, left), half),
+                    Point::new(end(width, radii.top_right, right), half),
+                ),
+                top_color,
                 top,
             );
         }
@@ -433,13 +611,64 @@ pub(crate) fn paint_border(cx: &mut PaintCx, style: &ViewStyleProps, size: Size)
             let half = bottom / 2.0;
             cx.stroke(
                 &Line::new(
-                    Point::new(0.0, size.height - half),
-                    Point::new(size.width, size.height - half),
+                    Point::new(start(radii.bottom_left, left), height - half),
+                    Point::new(end(width, radii.bottom_right, right), height - half),
                 ),
-                border_color,
+                bottom_color,
                 bottom,
             );
         }
+
+        // The corners use the top or bottom border, falling back to the left or right border.
+        let corners = [
+            (
+                radii.top_left,
+                Point::new(left / 2.0, top / 2.0),
+                PI,
+                (top, top_color),
+                (left, left_color),
+            ),
+            (
+                radii.top_right,
+                Point::new(width - right / 2.0, top / 2.0),
+                PI * 1.5,
+                (top, top_color),
+                (right, right_color),
+            ),
+            (
+                radii.bottom_right,
+                Point::new(width - right / 2.0, height - bottom / 2.0),
+                0.0,
+                (bottom, bottom_color),
+                (right, right_color),
+            ),
+            (
+                radii.bottom_left,
+                Point::new(left / 2.0, height - bottom / 2.0),
+                PI * 0.5,
+                (bottom, bottom_color),
+                (left, left_color),
+            ),
+        ];
+        for (radius, corner, start_angle, horiz, vert) in corners {
+            let (stroke_width, color) = if horiz.0 > 0.0 { horiz } else { vert };
+            if radius <= 0.0 || stroke_width <= 0.0 {
+                continue;
+            }
+            // Move from the corner towards the center of the view by the radius
+            let center = Point::new(
+                corner.x + radius * if corner.x < width / 2.0 { 1.0 } else { -1.0 },
+                corner.y + radius * if corner.y < height / 2.0 { 1.0 } else { -1.0 },
+            );
+            let arc = Arc {
+                center,
+                radii: Vec2::new(radius, radius),
+                start_angle,
+                sweep_angle: PI / 2.0,
+                x_rotation: 0.0,
+            };
+            cx.stroke(&arc, color, stroke_width);
+        }
     }
 }
 
@@ -705,3 +934,79 @@ impl View for Box<dyn View> {
         (**self).scroll_to(cx, target, rect)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use floem_peniko::Color;
+    use image::Rgba;
+    use kurbo::{Point, Rect, RoundedRectRadii, Shape, Size, Vec2};
+
+    use super::inset_shadow_ring;
+    use crate::{
+        style::BoxShadow,
+        test_support::HeadlessWindow,
+        unit::PxPct,
+        views::{container, empty, Decorators},
+    };
+
+    #[test]
+    fn inset_shadow_ring_has_a_rounded_hole() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+        let ring = inset_shadow_ring(
+            rect,
+            RoundedRectRadii::from_single_radius(30.0),
+            Vec2::new(10.0, 0.0),
+            10.0,
+            0.0,
+        );
+        // The hole is 20 to 100 wide, with corners of radius 20
+        assert!(ring.contains(Point::new(15.0, 50.0)));
+        assert!(!ring.contains(Point::new(25.0, 50.0)));
+        assert!(!ring.contains(Point::new(95.0, 50.0)));
+        assert!(ring.contains(Point::new(22.0, 12.0)));
+        assert!(!ring.contains(Point::new(40.0, 12.0)));
+        // The ring reaches past the view, where it's clipped
+        assert!(ring.contains(Point::new(-5.0, -5.0)));
+
+        let covered = inset_shadow_ring(rect, RoundedRectRadii::default(), Vec2::ZERO, 60.0, 0.0);
+        assert!(covered.contains(Point::new(50.0, 50.0)));
+    }
+
+    #[test]
+    fn box_shadows_are_painted_around_and_inside_the_view() {
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), || {
+            container(empty().style(|s| {
+                s.size(40.0, 40.0)
+                    .margin_left(30.0)
+                    .margin_top(30.0)
+                    .background(Color::WHITE)
+                    .box_shadow(BoxShadow {
+                        color: Color::BLACK,
+                        spread: PxPct::Pct(5.0),
+                        inset: true,
+                        ..Default::default()
+                    })
+                    .box_shadow(BoxShadow {
+                        color: Color::RED,
+                        h_offset: PxPct::Px(20.0),
+                        ..Default::default()
+                    })
+            }))
+            .style(|s| s.size_full())
+        });
+        let image = window.render().unwrap();
+        let black = Rgba([0, 0, 0, 255]);
+        let red = Rgba([255, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        // The inset shadow is 2 pixels wide
+        assert_eq!(*image.get_pixel(30, 50), black);
+        assert_eq!(*image.get_pixel(31, 50), black);
+        assert_eq!(*image.get_pixel(32, 50), white);
+        assert_eq!(*image.get_pixel(67, 50), white);
+        assert_eq!(*image.get_pixel(68, 50), black);
+        // The outer shadow is moved by half of its offset
+        assert_eq!(*image.get_pixel(75, 50), red);
+        assert_eq!(*image.get_pixel(80, 50), white);
+        assert_eq!(*image.get_pixel(29, 50), white);
+    }
+}
@@ -1,19 +1,25 @@
 use crate::{
     animate::{AnimPropKind, Animation},
-    context::{EventCallback, InteractionState, MenuCallback, MoveListener, ResizeListener},
-    event::EventListener,
+    context::{
+        Autofocus, EventCallback, InteractionState, IntersectionListener, MenuCallback,
+        MoveListener, ResizeListener, SizeListener, VisibilityListener,
+    },
+    event::{EventListener, FileDropEvent},
     id::{Id, ID_PATHS},
-    pointer::PointerInputEvent,
+    pointer::{PointerInputEvent, PointerWheelEvent, TouchEvent},
     prop_extracter,
     responsive::ScreenSizeBp,
     style::{
-        Background, BorderBottom, BorderColor, BorderLeft, BorderRadius, BorderRight, BorderTop,
-        LayoutProps, Outline, OutlineColor, Style, StyleClassRef, StyleSelectors,
+        Background, BorderBottom, BorderBottomColor, BorderBottomLeftRadius,
+        BorderBottomRightRadius, BorderColor, BorderLeft, BorderLeftColor, BorderRadius,
+        BorderRight, BorderRightColor, BorderTop, BorderTopColor, BorderTopLeftRadius,
+        BorderTopRightRadius, Display, LayoutProps, Outline, OutlineColor, Style, StyleClassRef,
+        StyleSelectors,
     },
     view::View,
 };
 use bitflags::bitflags;
-use kurbo::Rect;
+use kurbo::{Rect, Size};
 use smallvec::SmallVec;
 use std::{collections::HashMap, marker::PhantomData, time::Duration};
 use taffy::node::Node;
@@ -114,10 +120,18 @@ prop_extracter! {
         pub border_right: BorderRight,
         pub border_bottom: BorderBottom,
         pub border_radius: BorderRadius,
+        pub border_top_left_radius: BorderTopLeftRadius,
+        pub border_top_right_radius: BorderTopRightRadius,
+        pub border_bottom_left_radius: BorderBottomLeftRadius,
+        pub border_bottom_right_radius: BorderBottomRightRadius,
 
         pub outline: Outline,
         pub outline_color: OutlineColor,
         pub border_color: BorderColor,
+        pub border_left_color: BorderLeftColor,
+        pub border_top_color: BorderTopColor,
+        pub border_right_color: BorderRightColor,
+        pub border_bottom_color: BorderBottomColor,
         pub background: Background,
     }
 }
@@ -138,46 +152,151 @@ pub struct ViewState {
     /// Layout is requested on all direct and indirect children.
     pub(crate) request_style_recursive: bool,
     pub(crate) has_style_selectors: StyleSelectors,
+    /// The index of the view among its siblings when it was last styled, and their number.
+    pub(crate) child_position: Option<(usize, usize)>,
     pub(crate) viewport: Option<Rect>,
     pub(crate) layout_rect: Rect,
+    /// The rect the view and its descendants paint to, in window coordinates. It's larger than
+    /// `layout_rect` with shadows and outlines.
+    pub(crate) paint_rect: Rect,
     pub(crate) layout_props: LayoutProps,
     pub(crate) view_style_props: ViewStyleProps,
     pub(crate) animation: Option<Animation>,
     pub(crate) class: Option<StyleClassRef>,
     pub(crate) dragging_style: Option<Style>,
     pub(crate) combined_style: Style,
+    /// The display set with [`Id::set_display`], which overrides the one of the style.
+    pub(crate) display: Option<Display>,
+    /// When the view was last brought to the front, see [`Id::bring_to_front`].
+    pub(crate) stack_order: u64,
+    pub(crate) container_queries: ContainerQueries,
+    /// The size constraints set by [`Id::set_min_size`] and [`Id::set_max_size`].
+    pub(crate) min_size: Size,
+    pub(crate) max_size: Size,
     pub(crate) taffy_style: taffy::style::Style,
     pub(crate) event_listeners: HashMap<EventListener, Box<EventCallback>>,
+    pub(crate) capture_listeners: HashMap<EventListener, Box<EventCallback>>,
     pub(crate) context_menu: Option<Box<MenuCallback>>,
     pub(crate) popout_menu: Option<Box<MenuCallback>>,
     pub(crate) resize_listener: Option<ResizeListener>,
+    pub(crate) size_listener: Option<SizeListener>,
     pub(crate) move_listener: Option<MoveListener>,
+    pub(crate) visibility_listener: Option<VisibilityListener>,
+    pub(crate) intersection_listener: Option<IntersectionListener>,
+    pub(crate) file_drop_listener: Option<Box<dyn Fn(FileDropEvent)>>,
     pub(crate) cleanup_listener: Option<Box<dyn Fn()>>,
+    pub(crate) mount_listener: Option<Box<dyn Fn()>>,
+    pub(crate) unmount_listener: Option<Box<dyn Fn()>>,
+    pub(crate) touch_listener: Option<Box<dyn Fn(&TouchEvent)>>,
+    pub(crate) wheel_listener: Option<Box<dyn Fn(&PointerWheelEvent) -> bool>>,
+    pub(crate) focus_listener: Option<Box<dyn Fn(bool)>>,
+    pub(crate) focus_within_listener: Option<Box<dyn Fn(bool)>>,
+    /// Whether the view was laid out since it was added or last hidden.
+    pub(crate) mounted: bool,
+    pub(crate) autofocus: Option<Autofocus>,
     pub(crate) last_pointer_down: Option<PointerInputEvent>,
 }
 
+/// Tracks which container queries apply to a view, as described in
+/// [`Style::at_container_width`].
+#[derive(Default)]
+pub(crate) struct ContainerQueries {
+    /// The widths the queries of the view start applying at, in order.
+    thresholds: Vec<f64>,
+    /// The width of the view its style was resolved with.
+    pub(crate) width: f64,
+    /// The width the style was resolved with before it was last changed by layout.
+    previous_width: Option<f64>,
+    /// The width layout moved the view back to, across a threshold, which was ignored to
+    /// avoid alternating between two styles.
+    suppressed_width: Option<f64>,
+}
+
+impl ContainerQueries {
+    fn set_style(&mut self, style: &Style) {
+        self.thresholds.clear();
+        style.container_query_widths(&mut self.thresholds);
+        self.thresholds.sort_by(f64::total_cmp);
+        self.thresholds.dedup();
+    }
+
+    /// The number of thresholds `width` reaches, which decides the queries that apply.
+    fn level(&self, width: f64) -> usize {
+        self.thresholds
+            .partition_point(|min_width| *min_width <= width)
+    }
+
+    /// Updates the width of the view after layout. Returns `true` if it needs to be restyled.
+    pub(crate) fn resize(&mut self, width: f64) -> bool {
+        let level = self.level(width);
+        if level == self.level(self.width) {
+            self.width = width;
+            self.previous_width = None;
+            self.suppressed_width = None;
+            return false;
+        }
+        if let Some(previous_width) = self.previous_width {
+            // The last change of the style moved the width back across a threshold, so
+            // applying the new width would alternate between two styles. Once that's ignored,
+            // only the same width is, as any other one comes from an actual resize.
+            let flips_back = match self.suppressed_width {
+                Some(suppressed_width) => suppressed_width == width,
+                None => self.level(previous_width) == level,
+            };
+            if flips_back {
+                self.suppressed_width = Some(width);
+                return false;
+            }
+        }
+        self.previous_width = Some(self.width);
+        self.suppressed_width = None;
+        self.width = width;
+        true
+    }
+}
+
 impl ViewState {
     pub(crate) fn new(taffy: &mut taffy::Taffy) -> Self {
         Self {
             node: taffy.new_leaf(taffy::style::Style::DEFAULT).unwrap(),
             viewport: None,
             layout_rect: Rect::ZERO,
+            paint_rect: Rect::ZERO,
             layout_props: Default::default(),
             view_style_props: Default::default(),
             requested_changes: ChangeFlags::all(),
             request_style_recursive: false,
             has_style_selectors: StyleSelectors::default(),
+            child_position: None,
             animation: None,
             class: None,
             combined_style: Style::new(),
+            display: None,
+            stack_order: 0,
+            container_queries: ContainerQueries::default(),
+            min_size: Size::ZERO,
+            max_size: Size::new(f64::INFINITY, f64::INFINITY),
             taffy_style: taffy::style::Style::DEFAULT,
             dragging_style: None,
             event_listeners: HashMap::new(),
+            capture_listeners: HashMap::new(),
             context_menu: None,
             popout_menu: None,
             resize_listener: None,
+            size_listener: None,
             move_listener: None,
+            visibility_listener: None,
+            intersection_listener: None,
+            file_drop_listener: None,
             cleanup_listener: None,
+            mount_listener: None,
+            unmount_listener: None,
+            touch_listener: None,
+            wheel_listener: None,
+            focus_listener: None,
+            focus_within_listener: None,
+            mounted: false,
+            autofocus: None,
             last_pointer_down: None,
         }
     }
@@ -190,6 +309,7 @@ impl ViewState {
         view_style: Option<Style>,
         interact_state: InteractionState,
         screen_size_bp: ScreenSizeBp,
+        window_width: f64,
         view_class: Option<StyleClassRef>,
         classes: &[StyleClassRef],
         context: &Style,
@@ -241,11 +361,85 @@ impl ViewState {
         }
 
         self.has_style_selectors = computed_style.selectors();
+        self.container_queries.set_style(&computed_style);
 
-        computed_style.apply_interact_state(&interact_state, screen_size_bp);
+        computed_style.apply_interact_state(
+            &interact_state,
+            screen_size_bp,
+            window_width,
+            self.container_queries.width,
+        );
+
+        if self.min_size.width > 0.0 {
+            computed_style = computed_style.min_width(self.min_size.width);
+        }
+        if self.min_size.height > 0.0 {
+            computed_style = computed_style.min_height(self.min_size.height);
+        }
+        if self.max_size.width.is_finite() {
+            computed_style = computed_style.max_width(self.max_size.width);
+        }
+        if self.max_size.height.is_finite() {
+            computed_style = computed_style.max_height(self.max_size.height);
+        }
 
         self.combined_style = computed_style;
 
         new_frame
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::ContainerQueries;
+
+    fn with_thresholds(thresholds: &[f64], width: f64) -> ContainerQueries {
+        ContainerQueries {
+            thresholds: thresholds.to_vec(),
+            width,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn resizing_across_a_threshold_restyles() {
+        let mut queries = with_thresholds(&[300.0, 600.0], 200.0);
+        assert!(!queries.resize(250.0));
+        assert!(queries.resize(400.0));
+        assert!(!queries.resize(500.0));
+        assert!(queries.resize(700.0));
+        assert!(queries.resize(100.0));
+    }
+
+    #[test]
+    fn flipping_back_across_a_threshold_is_ignored() {
+        let mut queries = with_thresholds(&[300.0], 280.0);
+        // The narrow style makes the view wider, and the wide style makes it narrower again
+        assert!(queries.resize(320.0));
+        assert!(!queries.resize(290.0));
+        assert_eq!(queries.width, 320.0);
+        // Layout keeps producing the same width with the wide style
+        assert!(!queries.resize(290.0));
+        assert_eq!(queries.width, 320.0);
+    }
+
+    #[test]
+    fn resizing_after_a_flip_back_restyles() {
+        let mut queries = with_thresholds(&[300.0, 600.0], 280.0);
+        assert!(queries.resize(320.0));
+        assert!(!queries.resize(290.0));
+
+        // The window was made narrower, to another width below the threshold
+        assert!(queries.resize(150.0));
+        assert_eq!(queries.width, 150.0);
+
+        // And then much wider
+        let mut queries = with_thresholds(&[300.0, 600.0], 280.0);
+        assert!(queries.resize(320.0));
+        assert!(!queries.resize(290.0));
+        assert!(queries.resize(800.0));
+        assert_eq!(queries.width, 800.0);
+        // Widths in the same range don't restyle
+        assert!(!queries.resize(900.0));
+    }
+}
@@ -0,0 +1,261 @@
+use std::{cell::Cell, io::Cursor, rc::Rc, sync::Arc, time::Duration};
+
+use floem_reactive::{create_effect, Scope};
+use floem_renderer::Renderer;
+use image::{codecs::gif::GifDecoder, AnimationDecoder, DynamicImage, GenericImageView};
+use sha2::{Digest, Sha256};
+use taffy::prelude::Node;
+
+use crate::{
+    action::{exec_after, TimerToken},
+    executor,
+    ext_event::create_ext_action,
+    id::Id,
+    style::Style,
+    unit::UnitExt,
+    view::{View, ViewData},
+};
+
+use super::{fit_rect, ObjectFit};
+
+/// How many times an [`AnimatedImg`] plays its frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LoopCount {
+    Infinite,
+    Count(u32),
+}
+
+struct Frame {
+    img: Arc<DynamicImage>,
+    hash: Vec<u8>,
+    delay: Duration,
+}
+
+/// The shortest frame delay. Like browsers, smaller delays are treated as this delay.
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+fn decode_frames(data: &[u8]) -> Vec<Frame> {
+    let images: Vec<(DynamicImage, Duration)> = match GifDecoder::new(Cursor::new(data))
+        .and_then(|decoder| decoder.into_frames().collect_frames())
+    {
+        Ok(frames) => frames
+            .into_iter()
+            .map(|frame| {
+                let delay = Duration::from(frame.delay()).max(MIN_FRAME_DELAY);
+                (DynamicImage::ImageRgba8(frame.into_buffer()), delay)
+            })
+            .collect(),
+        // Images which aren't gifs are shown as a single frame.
+        Err(_) => image::load_from_memory(data)
+            .map(|img| vec![(img, Duration::ZERO)])
+            .unwrap_or_default(),
+    };
+    images
+        .into_iter()
+        .map(|(img, delay)| {
+            let mut hasher = Sha256::new();
+            hasher.update(img.as_bytes());
+            Frame {
+                hash: hasher.finalize().to_vec(),
+                img: Arc::new(img),
+                delay,
+            }
+        })
+        .collect()
+}
+
+enum AnimatedImgMsg {
+    Frames(Vec<Frame>),
+    Playing(bool),
+    Tick(TimerToken),
+}
+
+/// An image view which plays the frames of an animated gif. See [`animated_img`].
+pub struct AnimatedImg {
+    data: ViewData,
+    frames: Vec<Frame>,
+    current: usize,
+    loops_done: u32,
+    loop_count: LoopCount,
+    playing: bool,
+    timer: Option<TimerToken>,
+    fit: ObjectFit,
+    content_node: Option<Node>,
+}
+
+/// An image view which plays the animated gif in `data`, honoring the delay of each frame.
+///
+/// The animation plays while `playing` returns `true`. The next frame is only scheduled when the
+/// view is painted, so the animation pauses while the view is hidden or scrolled out of view.
+/// Images in other formats are shown as a still image. The frames are decoded on the
+/// [executor](crate::executor), and nothing is shown until they're decoded.
+pub fn animated_img(
+    data: impl Fn() -> Vec<u8> + 'static,
+    playing: impl Fn() -> bool + 'static,
+) -> AnimatedImg {
+    let id = Id::next();
+    let cx = Scope::current();
+    let latest = Rc::new(Cell::new(0u64));
+    create_effect(move |_| {
+        let data = data();
+        let generation = latest.get() + 1;
+        latest.set(generation);
+        let latest = latest.clone();
+        let send = create_ext_action(cx, move |frames: Vec<Frame>| {
+            // The data could have changed while these frames were decoded
+            if latest.get() == generation {
+                id.update_state(AnimatedImgMsg::Frames(frames));
+            }
+        });
+        executor::spawn(async move { send(decode_frames(&data)) });
+    });
+    create_effect(move |_| {
+        id.update_state(AnimatedImgMsg::Playing(playing()));
+    });
+    AnimatedImg {
+        data: ViewData::new(id),
+        frames: Vec::new(),
+        current: 0,
+        loops_done: 0,
+        loop_count: LoopCount::Infinite,
+        playing: true,
+        timer: None,
+        fit: ObjectFit::Fill,
+        content_node: None,
+    }
+}
+
+impl AnimatedImg {
+    /// Sets how many times the animation is played. Defaults to [`LoopCount::Infinite`].
+    pub fn loop_count(mut self, loop_count: LoopCount) -> Self {
+        self.loop_count = loop_count;
+        self
+    }
+
+    /// Sets how the frames are resized to fit the content box of the view.
+    pub fn fit(mut self, fit: ObjectFit) -> Self {
+        self.fit = fit;
+        self
+    }
+
+    fn is_finished(&self) -> bool {
+        match self.loop_count {
+            LoopCount::Infinite => false,
+            LoopCount::Count(count) => self.loops_done >= count,
+        }
+    }
+
+    fn advance(&mut self) {
+        if self.current + 1 < self.frames.len() {
+            self.current += 1;
+            return;
+        }
+        self.loops_done += 1;
+        if !self.is_finished() {
+            self.current = 0;
+        }
+    }
+}
+
+impl View for AnimatedImg {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "AnimatedImg".into()
+    }
+
+    fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(msg) = state.downcast::<AnimatedImgMsg>() {
+            match *msg {
+                AnimatedImgMsg::Frames(frames) => {
+                    self.frames = frames;
+                    self.current = 0;
+                    self.loops_done = 0;
+                    self.timer = None;
+                    cx.request_layout(self.id());
+                }
+                AnimatedImgMsg::Playing(playing) => {
+                    self.playing = playing;
+                    if !playing {
+                        self.timer = None;
+                    }
+                    cx.app_state_mut().request_paint(self.id());
+                }
+                AnimatedImgMsg::Tick(token) => {
+                    // Ignore timers which were cancelled by pausing or changing the frames.
+                    if self.timer == Some(token) {
+                        self.timer = None;
+                        self.advance();
+                        cx.app_state_mut().request_paint(self.id());
+                    }
+                }
+            }
+        }
+    }
+
+    fn layout(&mut self, cx: &mut crate::context::LayoutCx) -> taffy::prelude::Node {
+        cx.layout_node(self.id(), true, |cx| {
+            if self.content_node.is_none() {
+                self.content_node = Some(
+                    cx.app_state_mut()
+                        .taffy
+                        .new_leaf(taffy::style::Style::DEFAULT)
+                        .unwrap(),
+                );
+            }
+            let content_node = self.content_node.unwrap();
+
+            let (width, height) = self
+                .frames
+                .first()
+                .map(|frame| frame.img.dimensions())
+                .unwrap_or((0, 0));
+
+            let style = Style::new()
+                .width((width as f64).px())
+                .height((height as f64).px())
+                .to_taffy_style();
+            let _ = cx.app_state_mut().taffy.set_style(content_node, style);
+
+            vec![content_node]
+        })
+    }
+
+    fn paint(&mut self, cx: &mut crate::context::PaintCx) {
+        let frame = match self.frames.get(self.current) {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        let content_rect = cx.get_content_rect(self.id());
+        let (rect, clip) = fit_rect(self.fit, content_rect, frame.img.dimensions());
+        if clip {
+            cx.save();
+            cx.clip(&content_rect);
+        }
+        cx.draw_img(
+            floem_renderer::Img {
+                img: &frame.img,
+                data: frame.img.as_bytes(),
+                hash: &frame.hash,
+            },
+            rect,
+        );
+        if clip {
+            cx.restore();
+        }
+
+        if self.playing && self.timer.is_none() && self.frames.len() > 1 && !self.is_finished() {
+            let id = self.id();
+            self.timer = Some(exec_after(frame.delay, move |token| {
+                id.update_state(AnimatedImgMsg::Tick(token));
+            }));
+        }
+    }
+}
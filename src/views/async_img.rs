@@ -0,0 +1,260 @@
+use std::{cell::RefCell, path::PathBuf, rc::Rc, sync::Arc};
+
+use floem_reactive::{create_effect, create_rw_signal, ReadSignal, RwSignal, Scope};
+use image::DynamicImage;
+use indexmap::IndexMap;
+use once_cell::sync::Lazy;
+use parking_lot::Mutex;
+
+use crate::{
+    executor,
+    ext_event::create_ext_action,
+    id::Id,
+    view::{View, ViewData},
+};
+
+use super::{dyn_container, img_dynamic, DynamicContainer, ObjectFit};
+
+/// Fetches the bytes of an image from a url. See [`set_url_fetcher`].
+pub type UrlFetcher = dyn Fn(&str) -> Result<Vec<u8>, String> + Send + Sync;
+
+static URL_FETCHER: Lazy<Mutex<Option<Arc<UrlFetcher>>>> = Lazy::new(|| Mutex::new(None));
+
+/// The size of the decoded images kept by the image cache, in bytes.
+const IMAGE_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+thread_local! {
+    static IMAGE_CACHE: RefCell<ImageCache> = RefCell::new(ImageCache::new(IMAGE_CACHE_BYTES));
+}
+
+/// The decoded images by source, from the least to the most recently used. The least recently
+/// used images are dropped when they take more than `limit` bytes, but the images which are
+/// shown stay loaded by their views.
+struct ImageCache {
+    images: IndexMap<ImageSource, Rc<DynamicImage>>,
+    bytes: usize,
+    limit: usize,
+}
+
+impl ImageCache {
+    fn new(limit: usize) -> Self {
+        Self {
+            images: IndexMap::new(),
+            bytes: 0,
+            limit,
+        }
+    }
+
+    fn get(&mut self, source: &ImageSource) -> Option<Rc<DynamicImage>> {
+        let image = self.images.shift_remove(source)?;
+        self.images.insert(source.clone(), image.clone());
+        Some(image)
+    }
+
+    fn insert(&mut self, source: ImageSource, image: Rc<DynamicImage>) {
+        self.bytes += image.as_bytes().len();
+        if let Some(old) = self.images.shift_remove(&source) {
+            self.bytes -= old.as_bytes().len();
+        }
+        self.images.insert(source, image);
+        // The newest image is kept even if it's larger than the limit on its own
+        while self.bytes > self.limit && self.images.len() > 1 {
+            if let Some((_, evicted)) = self.images.shift_remove_index(0) {
+                self.bytes -= evicted.as_bytes().len();
+            }
+        }
+    }
+}
+
+/// Sets the function used by [`async_img`] to fetch images from urls.
+///
+/// Floem doesn't include an http client, so loading an [`ImageSource::Url`] fails until one is
+/// set. The fetcher is called off the UI thread, on the [executor](crate::executor).
+pub fn set_url_fetcher(fetcher: impl Fn(&str) -> Result<Vec<u8>, String> + Send + Sync + 'static) {
+    *URL_FETCHER.lock() = Some(Arc::new(fetcher));
+}
+
+/// Where [`async_img`] loads an image from.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ImageSource {
+    Path(PathBuf),
+    Url(String),
+}
+
+impl ImageSource {
+    fn load(&self) -> Result<DynamicImage, String> {
+        let bytes = match self {
+            ImageSource::Path(path) => std::fs::read(path).map_err(|err| err.to_string())?,
+            ImageSource::Url(url) => {
+                let fetcher = URL_FETCHER
+                    .lock()
+                    .clone()
+                    .ok_or_else(|| "no url fetcher has been set".to_string())?;
+                fetcher(url)?
+            }
+        };
+        image::load_from_memory(&bytes).map_err(|err| err.to_string())
+    }
+}
+
+#[derive(Clone)]
+pub enum ImageLoadState {
+    Loading,
+    Loaded(Rc<DynamicImage>),
+    Failed(String),
+}
+
+/// A view which loads an image in the background. See [`async_img`].
+pub struct AsyncImg {
+    data: ViewData,
+    child: DynamicContainer<ImageLoadState>,
+    load_state: RwSignal<ImageLoadState>,
+    fit: RwSignal<ObjectFit>,
+}
+
+/// A view which loads the image at `source` off the UI thread, on the
+/// [executor](crate::executor).
+///
+/// The view returned by `placeholder` is shown while the image is loading, and the view returned
+/// by `error` is shown with the error message if loading fails. Decoded images are cached by
+/// source, so showing the same image again doesn't load it again, until the recently used
+/// images take more than 64 MiB.
+///
+/// Loading images from urls requires a fetcher to be set with [`set_url_fetcher`].
+pub fn async_img(
+    source: impl Fn() -> ImageSource + 'static,
+    placeholder: impl Fn() -> Box<dyn View> + 'static,
+    error: impl Fn(String) -> Box<dyn View> + 'static,
+) -> AsyncImg {
+    let cx = Scope::current();
+    let load_state = create_rw_signal(ImageLoadState::Loading);
+    let current_source = create_rw_signal(None);
+    let fit = create_rw_signal(ObjectFit::Fill);
+
+    create_effect(move |_| {
+        let source = source();
+        current_source.set(Some(source.clone()));
+
+        let cached = IMAGE_CACHE.with(|cache| cache.borrow_mut().get(&source));
+        if let Some(image) = cached {
+            load_state.set(ImageLoadState::Loaded(image));
+            return;
+        }
+
+        load_state.set(ImageLoadState::Loading);
+        let loaded_source = source.clone();
+        let send = create_ext_action(cx, move |result: Result<DynamicImage, String>| {
+            let state = match result {
+                Ok(image) => {
+                    let image = Rc::new(image);
+                    IMAGE_CACHE.with(|cache| {
+                        cache
+                            .borrow_mut()
+                            .insert(loaded_source.clone(), image.clone())
+                    });
+                    ImageLoadState::Loaded(image)
+                }
+                Err(err) => ImageLoadState::Failed(err),
+            };
+            // The source could have changed while this image was loading.
+            if current_source.get_untracked().as_ref() == Some(&loaded_source) {
+                load_state.set(state);
+            }
+        });
+        executor::spawn(async move { send(source.load()) });
+    });
+
+    let child = dyn_container(
+        move || load_state.get(),
+        move |state| match state {
+            ImageLoadState::Loading => placeholder(),
+            ImageLoadState::Loaded(image) => {
+                let img = img_dynamic(move || Some(image.clone()));
+                let id = img.id();
+                create_effect(move |_| id.update_state(fit.get()));
+                Box::new(img)
+            }
+            ImageLoadState::Failed(err) => error(err),
+        },
+    );
+
+    AsyncImg {
+        data: ViewData::new(Id::next()),
+        child,
+        load_state,
+        fit,
+    }
+}
+
+impl AsyncImg {
+    /// Sets how the loaded image is resized to fit the view.
+    pub fn fit(self, fit: ObjectFit) -> Self {
+        self.fit.set(fit);
+        self
+    }
+
+    /// A signal with the loading state of the current image.
+    pub fn load_state(&self) -> ReadSignal<ImageLoadState> {
+        self.load_state.read_only()
+    }
+}
+
+impl View for AsyncImg {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "AsyncImg".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use image::DynamicImage;
+
+    use super::{ImageCache, ImageSource};
+
+    #[test]
+    fn least_recently_used_images_are_evicted() {
+        let source = |name: &str| ImageSource::Path(name.into());
+        // Each image takes 4 bytes
+        let image = || Rc::new(DynamicImage::new_rgba8(1, 1));
+        let mut cache = ImageCache::new(8);
+        cache.insert(source("a"), image());
+        cache.insert(source("b"), image());
+        assert!(cache.get(&source("a")).is_some());
+
+        cache.insert(source("c"), image());
+        assert!(cache.get(&source("b")).is_none());
+        assert!(cache.get(&source("a")).is_some());
+        assert!(cache.get(&source("c")).is_some());
+        assert_eq!(cache.bytes, 8);
+
+        // An image larger than the limit only replaces the others
+        cache.insert(source("d"), Rc::new(DynamicImage::new_rgba8(2, 2)));
+        assert_eq!(cache.images.len(), 1);
+        assert_eq!(cache.bytes, 16);
+    }
+}
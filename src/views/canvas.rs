@@ -0,0 +1,72 @@
+use floem_renderer::Renderer;
+use kurbo::Size;
+
+use crate::{
+    context::PaintCx,
+    id::Id,
+    view::{View, ViewData},
+};
+
+/// A view which paints itself with a user supplied closure. See [`canvas`].
+pub struct Canvas {
+    data: ViewData,
+    paint: Box<dyn Fn(&mut PaintCx, Size)>,
+}
+
+/// A view which hands a [`PaintCx`] to `paint` each time it is painted, for charts and other
+/// custom graphics.
+///
+/// `paint` receives the size of the view and draws with the [`Renderer`] methods, in coordinates
+/// relative to the view's origin. Drawing is clipped to the view. The canvas is only repainted
+/// when Floem needs to, so call [`Id::request_paint`] on the canvas id when the data it draws
+/// changes, for example from an effect.
+///
+/// ```rust
+/// # use floem::peniko::Color;
+/// # use floem::kurbo::Circle;
+/// # use floem::reactive::{create_effect, create_rw_signal};
+/// # use floem::views::canvas;
+/// # use floem::view::View;
+/// # use floem::Renderer;
+/// let radius = create_rw_signal(10.0);
+/// let view = canvas(move |cx, size| {
+///     let circle = Circle::new(size.to_rect().center(), radius.get_untracked());
+///     cx.fill(&circle, Color::RED, 0.0);
+/// });
+/// let id = view.id();
+/// create_effect(move |_| {
+///     radius.track();
+///     id.request_paint();
+/// });
+/// ```
+pub fn canvas(paint: impl Fn(&mut PaintCx, Size) + 'static) -> Canvas {
+    Canvas {
+        data: ViewData::new(Id::next()),
+        paint: Box::new(paint),
+    }
+}
+
+impl View for Canvas {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Canvas".into()
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        let size = cx
+            .get_layout(self.id())
+            .map(|layout| Size::new(layout.size.width as f64, layout.size.height as f64))
+            .unwrap_or_default();
+        cx.save();
+        cx.clip(&size.to_rect());
+        (self.paint)(cx, size);
+        cx.restore();
+    }
+}
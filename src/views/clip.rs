@@ -2,7 +2,7 @@ use kurbo::Size;
 
 use crate::{
     id::Id,
-    view::{View, ViewData},
+    view::{border_radii, has_radius, View, ViewData},
 };
 
 pub struct Clip {
@@ -47,20 +47,14 @@ impl View for Clip {
 
     fn paint(&mut self, cx: &mut crate::context::PaintCx) {
         cx.save();
-        let style = cx.get_builtin_style(self.id());
-        let border_radius = style.border_radius();
         let size = cx
             .get_layout(self.id())
             .map(|layout| Size::new(layout.size.width as f64, layout.size.height as f64))
             .unwrap_or_default();
-
-        let radius = match border_radius {
-            crate::unit::PxPct::Px(px) => px,
-            crate::unit::PxPct::Pct(pct) => size.min_side() * (pct / 100.),
-        };
-        if radius > 0.0 {
-            let rect = size.to_rect().to_rounded_rect(radius);
-            cx.clip(&rect);
+        let style = &cx.app_state.view_state(self.id()).view_style_props;
+        let radii = border_radii(style, size);
+        if has_radius(&radii) {
+            cx.clip(&size.to_rect().to_rounded_rect(radii));
         } else {
             cx.clip(&size.to_rect());
         }
@@ -68,3 +62,55 @@ impl View for Clip {
         cx.restore();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use floem_peniko::Color;
+    use image::Rgba;
+    use kurbo::Size;
+
+    use super::clip;
+    use crate::{
+        style::Style,
+        test_support::HeadlessWindow,
+        unit::UnitExt,
+        views::{container, empty, Decorators},
+    };
+
+    fn clipped_square(radius: impl Fn(Style) -> Style + 'static) -> HeadlessWindow {
+        HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            container(
+                clip(empty().style(|s| s.size_full().background(Color::RED)))
+                    .style(move |s| radius(s.size(40.0, 40.0).margin_left(30.0).margin_top(30.0))),
+            )
+            .style(|s| s.size_full().background(Color::WHITE))
+        })
+    }
+
+    #[test]
+    fn children_are_clipped_to_the_rounded_corners() {
+        let mut window = clipped_square(|s| s.border_radius(10.0).border_top_right_radius(0.0));
+        let image = window.render().unwrap();
+        let red = Rgba([255, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(*image.get_pixel(50, 50), red);
+        // The rounded corners are cut out, but not the square one
+        assert_eq!(*image.get_pixel(30, 30), white);
+        assert_eq!(*image.get_pixel(30, 69), white);
+        assert_eq!(*image.get_pixel(69, 69), white);
+        assert_eq!(*image.get_pixel(69, 30), red);
+    }
+
+    #[test]
+    fn percentage_radii_are_resolved_like_the_background() {
+        // A radius of half the view clips its child to a circle
+        let mut window = clipped_square(|s| s.border_radius(50.pct()));
+        let image = window.render().unwrap();
+        let red = Rgba([255, 0, 0, 255]);
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(*image.get_pixel(50, 50), red);
+        assert_eq!(*image.get_pixel(50, 31), red);
+        assert_eq!(*image.get_pixel(33, 33), white);
+        assert_eq!(*image.get_pixel(66, 66), white);
+    }
+}
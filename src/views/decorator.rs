@@ -1,12 +1,17 @@
+use std::time::Duration;
+
 use floem_reactive::{create_effect, create_updater};
 use floem_winit::keyboard::{Key, ModifiersState};
-use kurbo::{Point, Rect};
+use kurbo::{Point, Rect, Size};
 
 use crate::{
     action::{set_window_menu, set_window_title, update_window_scale},
     animate::Animation,
-    event::{Event, EventListener},
+    command::Command,
+    event::{Event, EventListener, FileDropEvent},
+    gesture::{Gestures, PanEvent, PinchEvent, Recognizer, SwipeDirection},
     menu::Menu,
+    pointer::{PointerWheelEvent, TouchEvent},
     style::{Style, StyleClass, StyleSelector},
     view::View,
     EventPropagation,
@@ -81,6 +86,19 @@ pub trait Decorators: View + Sized {
         self
     }
 
+    /// Sets whether the view is checked, for the `checked` style selector.
+    /// See [`Id::update_checked`](crate::id::Id::update_checked).
+    fn checked(self, checked_fn: impl Fn() -> bool + 'static) -> Self {
+        let id = self.id();
+
+        create_effect(move |_| {
+            let is_checked = checked_fn();
+            id.update_checked(is_checked);
+        });
+
+        self
+    }
+
     /// Add an event handler for the given [EventListener].
     fn on_event(
         self,
@@ -92,6 +110,19 @@ pub trait Decorators: View + Sized {
         self
     }
 
+    /// Add an event handler for the given [EventListener] which gets the event before the
+    /// descendants of the view. See
+    /// [`Id::update_capture_listener`](crate::id::Id::update_capture_listener).
+    fn on_event_capture(
+        self,
+        listener: EventListener,
+        action: impl Fn(&Event) -> EventPropagation + 'static,
+    ) -> Self {
+        let id = self.id();
+        id.update_capture_listener(listener, Box::new(action));
+        self
+    }
+
     /// Add an handler for pressing down a specific key.
     fn on_key_down(
         mut self,
@@ -229,12 +260,129 @@ pub trait Decorators: View + Sized {
         self
     }
 
+    /// Add a listener for the size the content of the view takes, which can be larger or
+    /// smaller than the size the view is given. See [`Id::update_size_listener`].
+    fn on_content_resize(self, action: impl Fn(Size) + 'static) -> Self {
+        let id = self.id();
+        id.update_size_listener(Box::new(action));
+        self
+    }
+
     fn on_move(self, action: impl Fn(Point) + 'static) -> Self {
         let id = self.id();
         id.update_move_listener(Box::new(action));
         self
     }
 
+    /// Add a handler for the view being scrolled into and out of view, which is called with
+    /// whether at least `threshold` of it is visible. See
+    /// [`Id::update_visibility_listener`](crate::id::Id::update_visibility_listener).
+    fn on_visibility_change(self, threshold: f64, action: impl Fn(bool) + 'static) -> Self {
+        let id = self.id();
+        id.update_visibility_listener(threshold, Box::new(action));
+        self
+    }
+
+    /// Add a handler for the visible fraction of the view crossing one of `thresholds`. See
+    /// [`Id::update_intersection_listener`](crate::id::Id::update_intersection_listener).
+    fn on_intersection(self, thresholds: Vec<f64>, action: impl Fn(f64) + 'static) -> Self {
+        let id = self.id();
+        id.update_intersection_listener(thresholds, Box::new(action));
+        self
+    }
+
+    /// Recognize `gestures` from the pointer events of the view. See the
+    /// [`gesture`](crate::gesture) module.
+    fn gestures(mut self, gestures: Gestures) -> Self {
+        let id = self.id();
+        self.view_data_mut()
+            .event_handlers
+            .push(Box::new(Recognizer::handler(id, gestures)));
+        self
+    }
+
+    /// Add a handler for the pointer being pressed and dragged over the view.
+    fn on_pan(self, action: impl Fn(&PanEvent) + 'static) -> Self {
+        self.gestures(Gestures::new().on_pan(action))
+    }
+
+    /// Add a handler for two fingers pinching or rotating on a touchpad over the view.
+    fn on_pinch(self, action: impl Fn(&PinchEvent) + 'static) -> Self {
+        self.gestures(Gestures::new().on_pinch(action))
+    }
+
+    /// Add a handler for the pointer being pressed on the view for `duration` without moving.
+    fn on_long_press(self, duration: Duration, action: impl Fn(Point) + 'static) -> Self {
+        self.gestures(Gestures::new().on_long_press(duration, action))
+    }
+
+    /// Add a handler for the pointer being dragged quickly over the view and released.
+    fn on_swipe(self, action: impl Fn(SwipeDirection) + 'static) -> Self {
+        self.gestures(Gestures::new().on_swipe(action))
+    }
+
+    /// Add a handler for files dragged from outside the app over this view.
+    /// See [`FileDropEvent`](crate::event::FileDropEvent).
+    fn on_file_drop(self, action: impl Fn(FileDropEvent) + 'static) -> Self {
+        let id = self.id();
+        id.update_file_drop_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for the view being mounted, after it's first laid out. See
+    /// [`Id::update_mount_listener`](crate::id::Id::update_mount_listener).
+    fn on_mount(self, action: impl Fn() + 'static) -> Self {
+        let id = self.id();
+        id.update_mount_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for the view being unmounted, when it's hidden or removed. See
+    /// [`Id::update_unmount_listener`](crate::id::Id::update_unmount_listener).
+    fn on_unmount(self, action: impl Fn() + 'static) -> Self {
+        let id = self.id();
+        id.update_unmount_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for the touches on the view. See
+    /// [`Id::update_touch_listener`](crate::id::Id::update_touch_listener).
+    fn on_touch(self, action: impl Fn(&TouchEvent) + 'static) -> Self {
+        let id = self.id();
+        id.update_touch_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for the wheel events over the view, which gets them before its children.
+    /// See [`Id::update_wheel_listener`](crate::id::Id::update_wheel_listener).
+    fn on_wheel(self, action: impl Fn(&PointerWheelEvent) -> bool + 'static) -> Self {
+        let id = self.id();
+        id.update_wheel_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for the view gaining and losing focus.
+    /// See [`Id::update_focus_listener`](crate::id::Id::update_focus_listener).
+    fn on_focus_change(self, action: impl Fn(bool) + 'static) -> Self {
+        let id = self.id();
+        id.update_focus_listener(Box::new(action));
+        self
+    }
+
+    /// Add a handler for focus moving into and out of the view and its descendants.
+    /// See [`Id::update_focus_within_listener`](crate::id::Id::update_focus_within_listener).
+    fn on_focus_within_change(self, action: impl Fn(bool) + 'static) -> Self {
+        let id = self.id();
+        id.update_focus_within_listener(Box::new(action));
+        self
+    }
+
+    /// Focuses the view once it's mounted. See [`Id::autofocus`](crate::id::Id::autofocus).
+    fn autofocus(self) -> Self {
+        self.id().autofocus();
+        self
+    }
+
     fn on_cleanup(self, action: impl Fn() + 'static) -> Self {
         let id = self.id();
         id.update_cleanup_listener(Box::new(action));
@@ -304,6 +452,182 @@ pub trait Decorators: View + Sized {
         id.update_popout_menu(Box::new(menu));
         self
     }
+
+    /// Executes `command` when the view is clicked, and disables the view while the command
+    /// is disabled.
+    fn command(self, command: &Command) -> Self {
+        let enabled = command.clone();
+        let command = command.clone();
+        self.disabled(move || !enabled.is_enabled())
+            .on_click_stop(move |_| {
+                command.execute();
+            })
+    }
 }
 
 impl<V: View> Decorators for V {}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use kurbo::{Point, Size, Vec2};
+
+    use super::Decorators;
+    use crate::{
+        keyboard::ModifiersState,
+        style::Display,
+        test_support::HeadlessWindow,
+        view::View,
+        views::{container, empty, h_stack, scroll, v_stack},
+    };
+
+    #[test]
+    fn mount_listeners_follow_display() {
+        let mounts = Rc::new(Cell::new(0));
+        let unmounts = Rc::new(Cell::new(0));
+        let id = Rc::new(Cell::new(None));
+        let (view_mounts, view_unmounts, view_id) = (mounts.clone(), unmounts.clone(), id.clone());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let view = empty()
+                .on_mount(move || view_mounts.set(view_mounts.get() + 1))
+                .on_unmount(move || view_unmounts.set(view_unmounts.get() + 1));
+            view_id.set(Some(view.id()));
+            view
+        });
+        window.update();
+        window.update();
+        assert_eq!((mounts.get(), unmounts.get()), (1, 0));
+
+        let id = id.get().unwrap();
+        id.set_display(Display::None);
+        window.update();
+        assert_eq!((mounts.get(), unmounts.get()), (1, 1));
+
+        id.set_display(Display::Flex);
+        window.update();
+        assert_eq!((mounts.get(), unmounts.get()), (2, 1));
+    }
+
+    #[test]
+    fn wheel_listener_runs_before_scrolling() {
+        let zoom = Rc::new(Cell::new(0.0));
+        let (view_zoom, scroll_id) = (zoom.clone(), Rc::new(Cell::new(None)));
+        let view_scroll_id = scroll_id.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let view = scroll(empty().style(|s| s.size(100.0, 400.0)))
+                .on_wheel(move |event| {
+                    if event.modifiers.control_key() {
+                        view_zoom.set(view_zoom.get() - event.delta.y);
+                    }
+                    event.modifiers.control_key()
+                })
+                .style(|s| s.size_full());
+            view_scroll_id.set(Some(view.id()));
+            view
+        });
+        window.update();
+        let scroll_id = scroll_id.get().unwrap();
+        let offset = || scroll_id.save_scroll_state().unwrap_or_default().offset;
+
+        window.set_modifiers(ModifiersState::CONTROL);
+        window.wheel(Point::new(50.0, 50.0), Vec2::new(0.0, 20.0));
+        assert_eq!((zoom.get(), offset()), (-20.0, Vec2::ZERO));
+
+        window.set_modifiers(ModifiersState::empty());
+        window.wheel(Point::new(50.0, 50.0), Vec2::new(0.0, 20.0));
+        assert_eq!((zoom.get(), offset()), (-20.0, Vec2::new(0.0, 20.0)));
+    }
+
+    #[test]
+    fn focus_listeners_fire_once_per_change() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let (field, other, form) = (changes.clone(), changes.clone(), changes.clone());
+        let mut window = HeadlessWindow::new(Size::new(150.0, 50.0), move || {
+            h_stack((
+                h_stack((
+                    empty()
+                        .keyboard_navigatable()
+                        .on_focus_change(move |focused| field.borrow_mut().push(("a", focused)))
+                        .style(|s| s.size(50.0, 50.0)),
+                    empty().keyboard_navigatable().style(|s| s.size(50.0, 50.0)),
+                ))
+                .on_focus_within_change(move |focused| form.borrow_mut().push(("form", focused))),
+                empty()
+                    .keyboard_navigatable()
+                    .on_focus_change(move |focused| other.borrow_mut().push(("c", focused)))
+                    .style(|s| s.size(50.0, 50.0)),
+            ))
+        });
+        window.update();
+
+        window.click(Point::new(25.0, 25.0));
+        window.click(Point::new(25.0, 25.0));
+        window.click(Point::new(75.0, 25.0));
+        window.click(Point::new(125.0, 25.0));
+        assert_eq!(
+            *changes.borrow(),
+            [
+                ("a", true),
+                ("form", true),
+                ("a", false),
+                ("c", true),
+                ("form", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn content_size_includes_overflow() {
+        let size = Rc::new(Cell::new(Size::ZERO));
+        let content_size = Rc::new(Cell::new(Size::ZERO));
+        let (resized, content_resized) = (size.clone(), content_size.clone());
+        let mut window = HeadlessWindow::new(Size::new(200.0, 200.0), move || {
+            container(empty().style(|s| s.size(80.0, 120.0)))
+                .on_resize(move |rect| resized.set(rect.size()))
+                .on_content_resize(move |size| content_resized.set(size))
+                .style(|s| s.padding(5.0).width(100.0).max_height(50.0))
+        });
+        window.update();
+        assert_eq!(size.get(), Size::new(100.0, 50.0));
+        assert_eq!(content_size.get(), Size::new(90.0, 130.0));
+    }
+
+    #[test]
+    fn intersection_listener_fires_when_crossing_thresholds() {
+        let fractions = Rc::new(RefCell::new(Vec::new()));
+        let view_fractions = fractions.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let target = empty()
+                .on_intersection(vec![1.0, 0.25, 0.5], move |fraction| {
+                    view_fractions.borrow_mut().push(fraction)
+                })
+                .style(|s| s.size(100.0, 100.0));
+            scroll(v_stack((empty().style(|s| s.size(100.0, 100.0)), target)))
+                .style(|s| s.size_full())
+        });
+        window.update();
+        // The listener is called after the first layout, even when it's out of view
+        assert_eq!(*fractions.borrow(), [0.0]);
+
+        let mut scroll_by = |y: f64| {
+            window.wheel(Point::new(50.0, 50.0), Vec2::new(0.0, y));
+            window.update();
+        };
+        // Below the first threshold
+        scroll_by(20.0);
+        assert_eq!(*fractions.borrow(), [0.0]);
+        scroll_by(20.0);
+        scroll_by(40.0);
+        scroll_by(10.0);
+        scroll_by(10.0);
+        assert_eq!(*fractions.borrow(), [0.0, 0.4, 0.8, 1.0]);
+
+        scroll_by(-10.0);
+        scroll_by(-100.0);
+        assert_eq!(*fractions.borrow(), [0.0, 0.4, 0.8, 1.0, 0.9, 0.0]);
+    }
+}
@@ -12,6 +12,22 @@ pub struct DragWindowArea {
     child: Box<dyn View>,
 }
 
+/// A view which moves the window when dragged and toggles maximizing it when double clicked.
+///
+/// Together with [`set_window_decorations`](crate::action::set_window_decorations) or
+/// [`WindowConfig::show_titlebar`](crate::window::WindowConfig::show_titlebar) this can be used
+/// to build a custom title bar:
+/// ```rust
+/// use floem::action::{minimize_window, request_close_window, toggle_window_maximized};
+/// use floem::views::{drag_window_area, h_stack, label, Decorators};
+///
+/// let title_bar = h_stack((
+///     drag_window_area(label(|| "My App")).style(|s| s.flex_grow(1.0)),
+///     label(|| "-").on_click_stop(|_| minimize_window()),
+///     label(|| "+").on_click_stop(|_| toggle_window_maximized()),
+///     label(|| "x").on_click_stop(|_| request_close_window()),
+/// ));
+/// ```
 pub fn drag_window_area<V: View + 'static>(child: V) -> DragWindowArea {
     let id = Id::next();
     DragWindowArea {
@@ -1,18 +1,146 @@
+use std::time::{Duration, Instant};
+
 use floem_reactive::{as_child_of_current_scope, create_updater, Scope};
+use floem_renderer::Renderer;
+use kurbo::{Size, Vec2};
 
 use crate::{
+    action::{cancel_timer, exec_after, TimerToken},
+    animate::{Easing, EasingFn, EasingMode},
+    clock,
+    context::PaintCx,
     id::Id,
     view::{view_children_set_parent_id, View, ViewData},
 };
 
 type ChildFn<T> = dyn Fn(T) -> (Box<dyn View>, Scope);
 
+const FRAME: Duration = Duration::from_millis(16);
+
+/// The direction the children of a [`DynamicContainer`] move in during a
+/// [`ContainerTransition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SlideDirection {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+impl SlideDirection {
+    /// The offset of a child which moved by a whole container of `size` in this direction.
+    fn offset(self, size: Size) -> Vec2 {
+        match self {
+            SlideDirection::Left => Vec2::new(-size.width, 0.0),
+            SlideDirection::Right => Vec2::new(size.width, 0.0),
+            SlideDirection::Up => Vec2::new(0.0, -size.height),
+            SlideDirection::Down => Vec2::new(0.0, size.height),
+        }
+    }
+
+    fn opposite(self) -> SlideDirection {
+        match self {
+            SlideDirection::Left => SlideDirection::Right,
+            SlideDirection::Right => SlideDirection::Left,
+            SlideDirection::Up => SlideDirection::Down,
+            SlideDirection::Down => SlideDirection::Up,
+        }
+    }
+}
+
+/// How a [`DynamicContainer`] goes from its old child to its new one, see
+/// [`DynamicContainer::transition`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerTransition {
+    /// The new child slides in and pushes the old one out, like pages of a navigation stack.
+    Push(SlideDirection),
+    /// The new child slides in over the old one.
+    Cover(SlideDirection),
+    /// The old child slides out, uncovering the new one.
+    Reveal(SlideDirection),
+}
+
+impl ContainerTransition {
+    /// The transition which undoes this one, like going back after pushing a page.
+    pub(crate) fn reversed(self) -> ContainerTransition {
+        match self {
+            ContainerTransition::Push(direction) => ContainerTransition::Push(direction.opposite()),
+            ContainerTransition::Cover(direction) => {
+                ContainerTransition::Reveal(direction.opposite())
+            }
+            ContainerTransition::Reveal(direction) => {
+                ContainerTransition::Cover(direction.opposite())
+            }
+        }
+    }
+
+    /// Paints the `old` and `new` views of a container of `size` when the transition is at
+    /// `progress`, from 0.0 to 1.0.
+    pub(crate) fn paint(
+        self,
+        cx: &mut PaintCx,
+        size: Size,
+        progress: f64,
+        old: &mut dyn View,
+        new: &mut dyn View,
+    ) {
+        let easing = Easing {
+            mode: EasingMode::InOut,
+            func: EasingFn::Cubic,
+        };
+        let progress = easing.ease(progress);
+        // The offsets of the old and new view, and whether the new view is painted on top
+        let (old_offset, new_offset, new_on_top) = match self {
+            ContainerTransition::Push(direction) => {
+                let offset = direction.offset(size);
+                (offset * progress, offset * (progress - 1.0), true)
+            }
+            ContainerTransition::Cover(direction) => {
+                (Vec2::ZERO, direction.offset(size) * (progress - 1.0), true)
+            }
+            ContainerTransition::Reveal(direction) => {
+                (direction.offset(size) * progress, Vec2::ZERO, false)
+            }
+        };
+
+        cx.save();
+        cx.clip(&size.to_rect());
+        let paint = |cx: &mut PaintCx, view: &mut dyn View, offset: Vec2| {
+            cx.save();
+            cx.offset((offset.x, offset.y));
+            cx.paint_view(view);
+            cx.restore();
+        };
+        if new_on_top {
+            paint(cx, old, old_offset);
+            paint(cx, new, new_offset);
+        } else {
+            paint(cx, new, new_offset);
+            paint(cx, old, old_offset);
+        }
+        cx.restore();
+    }
+}
+
+/// The old child of a [`DynamicContainer`], which is kept while it leaves.
+struct Leaving {
+    child: Box<dyn View>,
+    scope: Scope,
+    start: Instant,
+}
+
+/// A frame of the transition of a [`DynamicContainer`].
+struct TransitionFrame(TimerToken);
+
 /// A container for a dynamically updating View. See [`dyn_container`]
 pub struct DynamicContainer<T: 'static> {
     data: ViewData,
     child: Box<dyn View>,
     child_scope: Scope,
     child_fn: Box<ChildFn<T>>,
+    transition: Option<(ContainerTransition, Duration)>,
+    leaving: Option<Leaving>,
+    frame_timer: TimerToken,
 }
 
 /// A container for a dynamically updating View
@@ -66,6 +194,10 @@ pub struct DynamicContainer<T: 'static> {
 /// }
 /// ```
 ///
+/// The child is rebuilt each time `update_view` reruns, so when it reads more than what picks the
+/// child, it should be wrapped in a [`create_memo`](floem_reactive::create_memo) to only
+/// rebuild the child when the result changes.
+///
 /// See [container_box](crate::views::container_box()) for more documentation on a general container
 pub fn dyn_container<CF: Fn(T) -> Box<dyn View> + 'static, T: 'static>(
     update_view: impl Fn() -> T + 'static,
@@ -82,6 +214,48 @@ pub fn dyn_container<CF: Fn(T) -> Box<dyn View> + 'static, T: 'static>(
         child,
         child_scope,
         child_fn,
+        transition: None,
+        leaving: None,
+        frame_timer: TimerToken::INVALID,
+    }
+}
+
+impl<T: 'static> DynamicContainer<T> {
+    /// Animates changes of the child with `transition`, which lasts `duration`. The old child
+    /// is kept, and its effects keep running, until it's gone, but it no longer gets events.
+    pub fn transition(mut self, transition: ContainerTransition, duration: Duration) -> Self {
+        self.transition = Some((transition, duration));
+        self
+    }
+
+    /// How far the transition is, from 0.0 to 1.0, or `None` if there's no transition running.
+    fn progress(&self) -> Option<f64> {
+        let (leaving, (_, duration)) = self.leaving.as_ref().zip(self.transition)?;
+        let elapsed = clock::now().duration_since(leaving.start);
+        Some((elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0))
+    }
+
+    fn request_frame(&mut self) {
+        let id = self.id();
+        self.frame_timer = exec_after(FRAME, move |token| {
+            id.update_state(TransitionFrame(token));
+        });
+    }
+
+    fn finish_leaving(&mut self, cx: &mut crate::context::UpdateCx) {
+        cancel_timer(self.frame_timer);
+        if let Some(mut leaving) = self.leaving.take() {
+            cx.app_state_mut().remove_view(&mut leaving.child);
+            leaving.scope.dispose();
+        }
+    }
+
+    fn paint_transition(&mut self, cx: &mut PaintCx, progress: f64) {
+        let Some(((transition, _), leaving)) = self.transition.zip(self.leaving.as_mut()) else {
+            return;
+        };
+        let size = cx.app_state.get_layout_rect(self.data.id()).size();
+        transition.paint(cx, size, progress, &mut leaving.child, &mut self.child);
     }
 }
 
@@ -114,14 +288,140 @@ impl<T: 'static> View for DynamicContainer<T> {
     }
 
     fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
-        if let Ok(val) = state.downcast::<T>() {
-            let old_child_scope = self.child_scope;
-            cx.app_state_mut().remove_view(&mut self.child);
-            (self.child, self.child_scope) = (self.child_fn)(*val);
-            old_child_scope.dispose();
-            self.child.id().set_parent(self.id());
-            view_children_set_parent_id(&*self.child);
-            cx.request_all(self.id());
+        let state = match state.downcast::<T>() {
+            Ok(val) => {
+                // A child which is still leaving is dropped for the new one
+                self.finish_leaving(cx);
+                let old_child_scope = self.child_scope;
+                let (child, child_scope) = (self.child_fn)(*val);
+                let mut old_child = std::mem::replace(&mut self.child, child);
+                self.child_scope = child_scope;
+                if self.transition.is_some() {
+                    self.leaving = Some(Leaving {
+                        child: old_child,
+                        scope: old_child_scope,
+                        start: clock::now(),
+                    });
+                    self.request_frame();
+                } else {
+                    cx.app_state_mut().remove_view(&mut old_child);
+                    old_child_scope.dispose();
+                }
+                self.child.id().set_parent(self.id());
+                view_children_set_parent_id(&*self.child);
+                cx.request_all(self.id());
+                return;
+            }
+            Err(state) => state,
+        };
+        if let Ok(frame) = state.downcast::<TransitionFrame>() {
+            if frame.0 != self.frame_timer {
+                return;
+            }
+            if self.progress().is_some_and(|progress| progress >= 1.0) {
+                self.finish_leaving(cx);
+            } else {
+                self.request_frame();
+            }
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        match self.progress() {
+            Some(progress) => self.paint_transition(cx, progress),
+            None => cx.paint_view(&mut self.child),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use floem_peniko::Color;
+    use floem_reactive::{create_rw_signal, RwSignal};
+    use image::Rgba;
+    use kurbo::Size;
+
+    use super::{dyn_container, ContainerTransition, SlideDirection};
+    use crate::{
+        test_support::HeadlessWindow,
+        views::{empty, Decorators},
+    };
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+    const BLUE: Rgba<u8> = Rgba([0, 0, 255, 255]);
+
+    /// A window with a container of 100x100 which pushes in the child at `index` from the
+    /// right over 300ms, and the indices of the children alive. The children are red, green
+    /// and blue.
+    fn pushing_window(index: RwSignal<usize>) -> (HeadlessWindow, Rc<RefCell<Vec<usize>>>) {
+        let alive = Rc::new(RefCell::new(Vec::new()));
+        let children = alive.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            dyn_container(
+                move || index.get(),
+                move |index| {
+                    let color = [Color::RED, Color::rgb8(0, 255, 0), Color::BLUE][index];
+                    children.borrow_mut().push(index);
+                    let children = children.clone();
+                    Box::new(
+                        empty()
+                            .style(move |s| s.size_full().background(color))
+                            .on_cleanup(move || children.borrow_mut().retain(|i| *i != index)),
+                    )
+                },
+            )
+            .transition(
+                ContainerTransition::Push(SlideDirection::Left),
+                Duration::from_millis(300),
+            )
+            .style(|s| s.size_full())
+        });
+        window.update();
+        (window, alive)
+    }
+
+    #[test]
+    fn the_old_child_leaves_during_the_transition() {
+        let index = create_rw_signal(0);
+        let (mut window, alive) = pushing_window(index);
+        index.set(1);
+        window.update();
+        assert_eq!(*alive.borrow(), [0, 1]);
+
+        // Halfway, the old child was pushed out of the left half
+        window.advance(Duration::from_millis(150));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(25, 50), RED);
+        assert_eq!(*image.get_pixel(75, 50), GREEN);
+
+        window.advance(Duration::from_millis(200));
+        assert_eq!(*alive.borrow(), [1]);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(25, 50), GREEN);
+        assert_eq!(*image.get_pixel(75, 50), GREEN);
+    }
+
+    #[test]
+    fn a_new_change_drops_the_child_still_leaving() {
+        let index = create_rw_signal(0);
+        let (mut window, alive) = pushing_window(index);
+        index.set(1);
+        window.update();
+        window.advance(Duration::from_millis(100));
+        index.set(2);
+        window.update();
+        // The transition starts again, from the child which was entering
+        assert_eq!(*alive.borrow(), [1, 2]);
+        window.advance(Duration::from_millis(150));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(25, 50), GREEN);
+        assert_eq!(*image.get_pixel(75, 50), BLUE);
+
+        window.advance(Duration::from_millis(200));
+        assert_eq!(*alive.borrow(), [2]);
+    }
+}
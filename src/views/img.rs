@@ -3,6 +3,7 @@ use std::rc::Rc;
 use floem_reactive::create_effect;
 use floem_renderer::Renderer;
 use image::{DynamicImage, GenericImageView};
+use kurbo::{Rect, Size};
 use sha2::{Digest, Sha256};
 
 use crate::{
@@ -21,6 +22,7 @@ pub struct ImageStyle {
 
 /// How the content of a replaced element, such as an img or video, should be resized to fit its container.
 /// See <https://developer.mozilla.org/en-US/docs/Web/CSS/object-fit>.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ObjectFit {
     /// The replaced content is sized to fill the element's content box.
     /// The entire object will completely fill the box.
@@ -91,6 +93,7 @@ pub struct Img {
     img: Option<Rc<DynamicImage>>,
     img_hash: Option<Vec<u8>>,
     img_dimensions: Option<(u32, u32)>,
+    fit: ObjectFit,
     content_node: Option<Node>,
 }
 
@@ -108,10 +111,42 @@ pub(crate) fn img_dynamic(image: impl Fn() -> Option<Rc<DynamicImage>> + 'static
         img: None,
         img_hash: None,
         img_dimensions: None,
+        fit: ObjectFit::Fill,
         content_node: None,
     }
 }
 
+impl Img {
+    /// Sets how the image is resized to fit the content box of the view.
+    pub fn fit(mut self, fit: ObjectFit) -> Self {
+        self.fit = fit;
+        self
+    }
+}
+
+/// Returns the rect the image is drawn in, and whether it has to be clipped to `rect`.
+pub(crate) fn fit_rect(fit: ObjectFit, rect: Rect, (width, height): (u32, u32)) -> (Rect, bool) {
+    let (width, height) = (width as f64, height as f64);
+    if width == 0.0 || height == 0.0 {
+        return (rect, false);
+    }
+    let contain = (rect.width() / width).min(rect.height() / height);
+    let scale = match fit {
+        ObjectFit::Fill => return (rect, false),
+        ObjectFit::Contain => contain,
+        ObjectFit::Cover => (rect.width() / width).max(rect.height() / height),
+        ObjectFit::ScaleDown => contain.min(1.0),
+        ObjectFit::None => 1.0,
+    };
+    let size = Size::new(width * scale, height * scale);
+    let origin = rect.center() - size.to_vec2() / 2.0;
+    let fitted = size.to_rect().with_origin(origin);
+    (
+        fitted,
+        fitted.width() > rect.width() || fitted.height() > rect.height(),
+    )
+}
+
 impl View for Img {
     fn view_data(&self) -> &ViewData {
         &self.data
@@ -135,6 +170,9 @@ impl View for Img {
             self.img = *img;
             self.img_dimensions = self.img.as_ref().map(|img| img.dimensions());
             cx.request_layout(self.id());
+        } else if let Ok(fit) = state.downcast::<ObjectFit>() {
+            self.fit = *fit;
+            cx.app_state_mut().request_paint(self.id());
         }
     }
 
@@ -164,7 +202,16 @@ impl View for Img {
 
     fn paint(&mut self, cx: &mut crate::context::PaintCx) {
         if let Some(img) = self.img.as_ref() {
-            let rect = cx.get_content_rect(self.id());
+            let content_rect = cx.get_content_rect(self.id());
+            let (rect, clip) = fit_rect(
+                self.fit,
+                content_rect,
+                self.img_dimensions.unwrap_or_default(),
+            );
+            if clip {
+                cx.save();
+                cx.clip(&content_rect);
+            }
             cx.draw_img(
                 floem_renderer::Img {
                     img,
@@ -173,6 +220,9 @@ impl View for Img {
                 },
                 rect,
             );
+            if clip {
+                cx.restore();
+            }
         }
     }
 }
@@ -1,43 +1,79 @@
-use std::{any::Any, fmt::Display};
+use std::{
+    any::Any,
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    fmt::Display,
+    rc::Rc,
+};
 
 use crate::{
     context::UpdateCx,
-    cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout},
+    cosmic_text::{Attrs, AttrsList, TextLayout},
     id::Id,
     prop_extracter,
     style::Style,
-    style::{FontProps, LineHeight, TextColor, TextOverflow, TextOverflowProp},
+    style::{
+        DirectionProp, FontProps, Lang, LetterSpacing, LineClamp, LineHeight, TextAlignProp,
+        TextColor, TextOverflow, TextOverflowProp, TextTransformProp, WordSpacing,
+    },
     unit::PxPct,
     view::{View, ViewData},
+    views::{container, tooltip, Decorators, Tooltip},
+    widgets::TooltipClass,
 };
 use floem_peniko::Color;
 use floem_reactive::create_updater;
-use floem_renderer::Renderer;
+use floem_renderer::{Renderer, TextLines};
 use kurbo::{Point, Rect};
 use taffy::prelude::Node;
+use unicode_segmentation::UnicodeSegmentation;
 
 prop_extracter! {
     Extracter {
         color: TextColor,
         text_overflow: TextOverflowProp,
+        line_clamp: LineClamp,
         line_height: LineHeight,
+        direction: DirectionProp,
+        letter_spacing: LetterSpacing,
+        word_spacing: WordSpacing,
+        text_transform: TextTransformProp,
+        lang: Lang,
+        text_align: TextAlignProp,
     }
 }
 
+const ELLIPSIS: &str = "...";
+
+/// An invisible right-to-left character. Bidi reordering takes the direction of a paragraph
+/// from its first strong character, so starting with this makes it a right-to-left paragraph
+/// even when it begins with left-to-right text.
+pub(crate) const RTL_MARK: &str = "\u{200F}";
+
 struct TextOverflowListener {
     last_is_overflown: Option<bool>,
     on_change_fn: Box<dyn Fn(bool) + 'static>,
 }
 
+/// The text shown by the tooltip of [`Label::tooltip_when_truncated`].
+#[derive(Default)]
+struct TruncatedText {
+    text: RefCell<String>,
+    is_truncated: Cell<bool>,
+}
+
 pub struct Label {
     data: ViewData,
     label: String,
     text_layout: Option<TextLayout>,
     text_node: Option<Node>,
+    baseline_node: Option<Node>,
     available_text: Option<String>,
     available_width: Option<f32>,
     available_text_layout: Option<TextLayout>,
     text_overflow_listener: Option<TextOverflowListener>,
+    is_truncated: bool,
+    truncated_text: Option<Rc<TruncatedText>>,
     font: FontProps,
     style: Extracter,
 }
@@ -49,10 +85,13 @@ impl Label {
             label,
             text_layout: None,
             text_node: None,
+            baseline_node: None,
             available_text: None,
             available_width: None,
             available_text_layout: None,
             text_overflow_listener: None,
+            is_truncated: false,
+            truncated_text: None,
             font: FontProps::default(),
             style: Default::default(),
         }
@@ -85,6 +124,23 @@ impl Label {
         self
     }
 
+    /// Shows the whole text in a tooltip when the label is hovered while its text is cut with an
+    /// ellipsis, by [`Style::text_ellipsis`] or [`Style::line_clamp`]. Nothing is shown when the
+    /// text fits.
+    pub fn tooltip_when_truncated(mut self) -> Tooltip {
+        let truncated = Rc::new(TruncatedText {
+            text: RefCell::new(self.label.clone()),
+            is_truncated: Cell::new(false),
+        });
+        self.truncated_text = Some(truncated.clone());
+        let tip = truncated.clone();
+        tooltip(self, move || {
+            let text = tip.text.borrow().clone();
+            container(static_label(text)).class(TooltipClass)
+        })
+        .show_when(move || truncated.is_truncated.get())
+    }
+
     fn get_attrs_list(&self) -> AttrsList {
         let mut attrs = Attrs::new().color(self.style.color().unwrap_or(Color::BLACK));
         if let Some(font_size) = self.font.size() {
@@ -93,10 +149,7 @@ impl Label {
         if let Some(font_style) = self.font.style() {
             attrs = attrs.style(font_style);
         }
-        let font_family = self.font.family().as_ref().map(|font_family| {
-            let family: Vec<FamilyOwned> = FamilyOwned::parse_list(font_family).collect();
-            family
-        });
+        let font_family = self.font.families();
         if let Some(font_family) = font_family.as_ref() {
             attrs = attrs.family(font_family);
         }
@@ -109,18 +162,120 @@ impl Label {
         AttrsList::new(attrs)
     }
 
+    /// The text shown, with its case changed by the text transform.
+    fn shown_text(&self) -> Cow<'_, str> {
+        self.style
+            .text_transform()
+            .apply(&self.label, self.style.lang().as_deref())
+    }
+
+    /// How the lines of the text are placed in `width`.
+    fn text_lines(&self, width: f32) -> TextLines {
+        TextLines {
+            letter_spacing: self.style.letter_spacing(),
+            word_spacing: self.style.word_spacing(),
+            align: self.style.text_align(),
+            width,
+        }
+    }
+
+    /// The extra width of the text with the letter and word spacing.
+    fn spacing_width(&self, text_layout: &TextLayout) -> f32 {
+        if self.style.letter_spacing() == 0.0 && self.style.word_spacing() == 0.0 {
+            return 0.0;
+        }
+        self.text_lines(0.0).spacing_width(text_layout)
+    }
+
+    /// The offset of the label text in the text layouts.
+    fn text_offset(&self) -> usize {
+        if self.style.direction().is_rtl() {
+            RTL_MARK.len()
+        } else {
+            0
+        }
+    }
+
     fn set_text_layout(&mut self) {
+        let mark = &RTL_MARK[..self.text_offset()];
         let mut text_layout = TextLayout::new();
         let attrs_list = self.get_attrs_list();
-        text_layout.set_text(self.label.as_str(), attrs_list.clone());
+        text_layout.set_text(&format!("{mark}{}", self.shown_text()), attrs_list.clone());
         self.text_layout = Some(text_layout);
 
         if let Some(new_text) = self.available_text.as_ref() {
             let mut text_layout = TextLayout::new();
-            text_layout.set_text(new_text, attrs_list);
+            text_layout.set_text(&format!("{mark}{new_text}"), attrs_list);
             self.available_text_layout = Some(text_layout);
         }
     }
+
+    /// Wraps the text to `available_width`, cutting it with an ellipsis after `lines` lines.
+    /// Returns `true` if the text was cut.
+    fn clamp_lines(&mut self, available_width: f32, lines: usize) -> bool {
+        let mut wrapped = self.text_layout.clone().unwrap();
+        wrapped.set_size(available_width, f32::MAX);
+        let runs: Vec<(usize, f32, f32, usize)> = wrapped
+            .layout_runs()
+            .map(|run| {
+                // The glyphs are in visual order, so the last one isn't the end of the line when
+                // it's right-to-left
+                let end = run.glyphs.iter().map(|glyph| glyph.end).max().unwrap_or(0);
+                (run.line_i, run.line_y, run.line_w, end)
+            })
+            .collect();
+        if runs.len() <= lines {
+            self.available_text = None;
+            self.available_text_layout = Some(wrapped);
+            return false;
+        }
+        let (paragraph, line_y, line_width, line_end) = runs[lines - 1];
+
+        let mut dots_text = TextLayout::new();
+        dots_text.set_text(ELLIPSIS, self.get_attrs_list());
+        let dots_width = dots_text.size().width as f32;
+        let end = if line_width + dots_width <= available_width {
+            // The ellipsis fits after the whole line
+            line_end
+        } else {
+            let hit_point = wrapped.hit_point(Point::new(
+                (available_width - dots_width).max(0.0) as f64,
+                line_y as f64,
+            ));
+            hit_point.index.min(line_end)
+        };
+        // The end is relative to the paragraph the line is in
+        let mark = &RTL_MARK[..self.text_offset()];
+        let text = self.shown_text();
+        let paragraph_start: usize = format!("{mark}{text}")
+            .split('\n')
+            .take(paragraph)
+            .map(|paragraph| paragraph.len() + 1)
+            .sum();
+        let index = (paragraph_start + end).saturating_sub(self.text_offset());
+        let index = grapheme_floor(&text, index);
+
+        let mut text_layout = TextLayout::new();
+        let new_text = format!("{}{ELLIPSIS}", text[..index].trim_end());
+        text_layout.set_text(&format!("{mark}{new_text}"), self.get_attrs_list());
+        text_layout.set_size(available_width, f32::MAX);
+        self.available_text = Some(new_text);
+        self.available_text_layout = Some(text_layout);
+        true
+    }
+}
+
+/// Moves `index` back to the start of the grapheme it's in, so text cut there doesn't end in the
+/// middle of a character.
+fn grapheme_floor(text: &str, index: usize) -> usize {
+    if index >= text.len() {
+        return text.len();
+    }
+    text.grapheme_indices(true)
+        .map(|(start, _)| start)
+        .take_while(|start| *start <= index)
+        .last()
+        .unwrap_or(0)
 }
 
 impl View for Label {
@@ -139,6 +294,9 @@ impl View for Label {
     fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn Any>) {
         if let Ok(state) = state.downcast() {
             self.label = *state;
+            if let Some(truncated) = self.truncated_text.as_ref() {
+                truncated.text.replace(self.label.clone());
+            }
             self.text_layout = None;
             self.available_text = None;
             self.available_width = None;
@@ -159,24 +317,31 @@ impl View for Label {
 
     fn layout(&mut self, cx: &mut crate::context::LayoutCx) -> taffy::prelude::Node {
         cx.layout_node(self.id(), true, |cx| {
-            let (width, height) = if self.label.is_empty() {
-                (0.0, self.font.size().unwrap_or(14.0))
+            let (width, height, baseline) = if self.label.is_empty() {
+                let height = self.font.size().unwrap_or(14.0);
+                (0.0, height, height)
             } else {
                 if self.text_layout.is_none() {
                     self.set_text_layout();
                 }
                 let text_layout = self.text_layout.as_ref().unwrap();
                 let size = text_layout.size();
-                let width = size.width.ceil() as f32;
+                let width = (size.width as f32 + self.spacing_width(text_layout)).ceil();
                 let mut height = size.height as f32;
 
-                if self.style.text_overflow() == TextOverflow::Wrap {
+                if self.style.text_overflow() == TextOverflow::Wrap
+                    || self.style.line_clamp().is_some_and(|lines| lines > 1)
+                {
                     if let Some(t) = self.available_text_layout.as_ref() {
                         height = height.max(t.size().height as f32);
                     }
                 }
 
-                (width, height)
+                let baseline = text_layout
+                    .layout_runs()
+                    .next()
+                    .map_or(height, |run| run.line_y);
+                (width, height, baseline)
             };
 
             if self.text_node.is_none() {
@@ -191,8 +356,9 @@ impl View for Label {
 
             let style = Style::new().width(width).height(height).to_taffy_style();
             let _ = cx.app_state_mut().taffy.set_style(text_node, style);
+            let baseline_node = cx.baseline_node(self.id(), &mut self.baseline_node, baseline);
 
-            vec![text_node]
+            vec![baseline_node, text_node]
         })
     }
 
@@ -215,21 +381,40 @@ impl View for Label {
         let padding = padding_left + padding_right;
 
         let text_layout = self.text_layout.as_ref().unwrap();
-        let width = text_layout.size().width as f32;
+        let width = text_layout.size().width as f32 + self.spacing_width(text_layout);
         let available_width = layout.size.width - padding;
-        if text_overflow == TextOverflow::Ellipsis {
+        let line_clamp = self.style.line_clamp().filter(|lines| *lines > 0);
+        if let Some(lines) = line_clamp.filter(|lines| *lines > 1) {
+            if self.available_width != Some(available_width) {
+                let old_height = self
+                    .available_text_layout
+                    .as_ref()
+                    .map(|text_layout| text_layout.size().height);
+                self.is_truncated = self.clamp_lines(available_width, lines);
+                self.available_width = Some(available_width);
+                let height = self
+                    .available_text_layout
+                    .as_ref()
+                    .map(|text_layout| text_layout.size().height);
+                if height != old_height {
+                    cx.app_state_mut().request_layout(self.id());
+                }
+            }
+        } else if text_overflow == TextOverflow::Ellipsis || line_clamp.is_some() {
             if width > available_width {
                 if self.available_width != Some(available_width) {
                     let mut dots_text = TextLayout::new();
-                    dots_text.set_text("...", self.get_attrs_list());
+                    dots_text.set_text(ELLIPSIS, self.get_attrs_list());
 
                     let dots_width = dots_text.size().width as f32;
                     let width_left = available_width - dots_width;
                     let hit_point = text_layout.hit_point(Point::new(width_left as f64, 0.0));
-                    let index = hit_point.index;
+                    let index = hit_point.index.saturating_sub(self.text_offset());
+                    let text = self.shown_text();
+                    let index = grapheme_floor(&text, index);
 
                     let new_text = if index > 0 {
-                        format!("{}...", &self.label[..index])
+                        format!("{}{ELLIPSIS}", &text[..index])
                     } else {
                         "".to_string()
                     };
@@ -237,12 +422,15 @@ impl View for Label {
                     self.available_width = Some(available_width);
                     self.set_text_layout();
                 }
+                self.is_truncated = true;
             } else {
                 self.available_text = None;
                 self.available_width = None;
                 self.available_text_layout = None;
+                self.is_truncated = false;
             }
         } else if text_overflow == TextOverflow::Wrap {
+            self.is_truncated = false;
             if width > available_width {
                 if self.available_width != Some(available_width) {
                     let mut text_layout = text_layout.clone();
@@ -259,6 +447,12 @@ impl View for Label {
                 self.available_width = None;
                 self.available_text_layout = None;
             }
+        } else {
+            self.is_truncated = false;
+        }
+
+        if let Some(truncated) = self.truncated_text.as_ref() {
+            truncated.is_truncated.set(self.is_truncated);
         }
 
         if let Some(listener) = self.text_overflow_listener.as_mut() {
@@ -281,10 +475,183 @@ impl View for Label {
         let text_node = self.text_node.unwrap();
         let location = cx.app_state.taffy.layout(text_node).unwrap().location;
         let point = Point::new(location.x as f64, location.y as f64);
-        if let Some(text_layout) = self.available_text_layout.as_ref() {
-            cx.draw_text(text_layout, point);
-        } else {
-            cx.draw_text(self.text_layout.as_ref().unwrap(), point);
-        }
+        let text_layout = self
+            .available_text_layout
+            .as_ref()
+            .unwrap_or_else(|| self.text_layout.as_ref().unwrap());
+        // The lines are aligned in the content box, which the text starts at
+        let width = cx.get_content_rect(self.id()).x1 - point.x;
+        cx.draw_text_lines(text_layout, point, &self.text_lines(width as f32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Instant};
+
+    use floem_peniko::Color;
+    use image::RgbaImage;
+    use kurbo::{Rect, Size};
+
+    use super::{grapheme_floor, text, Label};
+    use crate::{
+        id::Id,
+        style::{Direction, Style, TextAlign},
+        test_support::HeadlessWindow,
+        view::View,
+        views::{empty, h_stack_from_iter, Decorators},
+    };
+
+    /// The text of a label with `style` clamped to `lines` lines of 1000px, or `None` when it
+    /// isn't cut.
+    fn clamped(text: &str, style: Style, lines: usize) -> Option<String> {
+        let mut label = Label::new(Id::next(), text.to_string());
+        label
+            .style
+            .read_explicit(&style, &style, &Instant::now(), &mut false);
+        label.set_text_layout();
+        let cut = label.clamp_lines(1000.0, lines);
+        assert_eq!(cut, label.available_text.is_some());
+        label.available_text
+    }
+
+    #[test]
+    fn clamping_cuts_after_the_last_paragraph_shown() {
+        let text = "one\ntwo \nthree";
+        assert_eq!(clamped(text, Style::new(), 3), None);
+        assert_eq!(clamped(text, Style::new(), 2).unwrap(), "one\ntwo...");
+        assert_eq!(clamped(text, Style::new(), 1).unwrap(), "one...");
+    }
+
+    #[test]
+    fn clamping_keeps_combining_marks_at_the_cut() {
+        let text = "noe\u{308}l\ncafe\u{301}\nthe\u{301}";
+        assert_eq!(
+            clamped(text, Style::new(), 2).unwrap(),
+            "noe\u{308}l\ncafe\u{301}..."
+        );
+        assert_eq!(clamped(text, Style::new(), 1).unwrap(), "noe\u{308}l...");
+    }
+
+    #[test]
+    fn clamping_right_to_left_text_skips_the_direction_mark() {
+        let rtl = || Style::new().direction(Direction::Rtl);
+        assert_eq!(clamped("one\ntwo\nthree", rtl(), 2).unwrap(), "one\ntwo...");
+        assert_eq!(clamped("one\ntwo\nthree", rtl(), 1).unwrap(), "one...");
+        assert_eq!(
+            clamped("שלום\nעולם\nטוב", rtl(), 2).unwrap(),
+            "שלום\nעולם..."
+        );
+        assert_eq!(clamped("שלום\nעולם\nטוב", rtl(), 1).unwrap(), "שלום...");
+    }
+
+    #[test]
+    fn cuts_move_to_the_start_of_the_grapheme() {
+        // The acute accent is at 4..6
+        let text = "cafe\u{301}s";
+        assert_eq!(grapheme_floor(text, 0), 0);
+        assert_eq!(grapheme_floor(text, 3), 3);
+        assert_eq!(grapheme_floor(text, 4), 3);
+        assert_eq!(grapheme_floor(text, 5), 3);
+        assert_eq!(grapheme_floor(text, 6), 6);
+        assert_eq!(grapheme_floor(text, 100), text.len());
+
+        // Emoji joined into one grapheme, and a flag made of two regional indicators
+        let family = "\u{1F469}\u{200D}\u{1F467}x";
+        assert_eq!(grapheme_floor(family, 5), 0);
+        assert_eq!(grapheme_floor(family, family.len() - 1), family.len() - 1);
+        assert_eq!(grapheme_floor("\u{1F1EB}\u{1F1F7}", 4), 0);
+    }
+
+    /// The first and last columns of `image` with dark pixels.
+    fn inked_columns(image: &RgbaImage) -> (u32, u32) {
+        let inked = |x| (0..image.height()).any(|y| image.get_pixel(x, y)[0] < 128);
+        let first = (0..image.width()).find(|x| inked(*x)).unwrap();
+        let last = (0..image.width()).rev().find(|x| inked(*x)).unwrap();
+        (first, last)
+    }
+
+    #[test]
+    fn text_is_aligned_in_the_content_box() {
+        let aligned = |align| {
+            let mut window = HeadlessWindow::new(Size::new(200.0, 30.0), move || {
+                text("abc").style(move |s| {
+                    s.size_full()
+                        .padding_horiz(10.0)
+                        .background(Color::WHITE)
+                        .text_align(align)
+                })
+            });
+            inked_columns(&window.render().unwrap())
+        };
+        let (left_start, left_end) = aligned(TextAlign::Left);
+        assert!((10..15).contains(&left_start), "{left_start}");
+        let (right_start, right_end) = aligned(TextAlign::Right);
+        assert!((186..190).contains(&right_end), "{right_end}");
+        // The line keeps its width, up to the rounding of the glyphs to pixels
+        assert!((right_start - left_start).abs_diff(right_end - left_end) <= 1);
+        let (center_start, center_end) = aligned(TextAlign::Center);
+        assert!((center_start + center_end).abs_diff(200) <= 2);
+    }
+
+    /// The layouts of `views` in a row which aligns them on their baselines.
+    fn baseline_row(views: impl FnOnce() -> Vec<Box<dyn View>> + 'static) -> Vec<Rect> {
+        let rects = Rc::new(RefCell::new(Vec::new()));
+        let view_rects = rects.clone();
+        let mut window = HeadlessWindow::new(Size::new(300.0, 100.0), move || {
+            let views = views().into_iter().enumerate().map(move |(index, view)| {
+                let rects = view_rects.clone();
+                view.on_resize(move |rect| {
+                    let mut rects = rects.borrow_mut();
+                    if rects.len() <= index {
+                        rects.resize(index + 1, Rect::ZERO);
+                    }
+                    rects[index] = rect;
+                })
+            });
+            h_stack_from_iter(views).style(|s| s.items_baseline())
+        });
+        window.update();
+        rects.take()
+    }
+
+    #[test]
+    fn baselines_of_different_font_sizes_line_up() {
+        let rects = baseline_row(|| {
+            vec![
+                Box::new(text("Big").style(|s| s.font_size(30.0))),
+                Box::new(text("small").style(|s| s.font_size(12.0))),
+            ]
+        });
+        // The bigger text has the highest baseline, so the smaller one moves down
+        assert_eq!(rects[0].y0, 0.0);
+        assert!(rects[1].y0 > 0.0);
+        assert!(rects[1].y1 < rects[0].y1);
+    }
+
+    #[test]
+    fn baselines_are_below_the_padding() {
+        let rects = baseline_row(|| {
+            vec![
+                Box::new(text("a")),
+                Box::new(text("b").style(|s| s.padding_top(20.0).border_top(2.0))),
+            ]
+        });
+        assert_eq!(rects[0].y0, 22.0);
+        assert_eq!(rects[1].y0, 0.0);
+    }
+
+    #[test]
+    fn views_without_text_sit_on_the_baseline() {
+        let rects = baseline_row(|| {
+            vec![
+                Box::new(text("text").style(|s| s.font_size(20.0))),
+                Box::new(empty().style(|s| s.size(10.0, 10.0))),
+            ]
+        });
+        // The bottom of the view is on the baseline, above the descent of the text
+        assert_eq!(rects[0].y0, 0.0);
+        assert!(rects[1].y0 > 0.0);
+        assert!(rects[1].y1 < rects[0].y1);
     }
 }
@@ -27,6 +27,9 @@ pub use container_box::*;
 mod dyn_container;
 pub use dyn_container::*;
 
+mod navigator;
+pub use navigator::*;
+
 mod decorator;
 pub use decorator::*;
 
@@ -40,7 +43,7 @@ mod virtual_stack;
 pub use virtual_stack::*;
 
 pub mod scroll;
-pub use scroll::{scroll, Scroll};
+pub use scroll::{scroll, Scroll, ScrollState};
 
 mod tab;
 pub use tab::*;
@@ -48,15 +51,24 @@ pub use tab::*;
 mod tooltip;
 pub use tooltip::*;
 
+mod portal;
+pub use portal::*;
+
 mod stack;
 pub use stack::*;
 
 mod text_input;
 pub use text_input::*;
 
+mod text_editor;
+pub use text_editor::*;
+
 mod empty;
 pub use empty::*;
 
+mod spacer;
+pub use spacer::*;
+
 mod drag_window_area;
 pub use drag_window_area::*;
 
@@ -65,3 +77,15 @@ pub use drag_resize_window_area::*;
 
 mod img;
 pub use img::*;
+
+mod async_img;
+pub use async_img::*;
+
+mod animated_img;
+pub use animated_img::*;
+
+mod canvas;
+pub use canvas::*;
+
+mod scene;
+pub use scene::*;
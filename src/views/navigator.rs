@@ -0,0 +1,525 @@
+use std::time::{Duration, Instant};
+
+use floem_reactive::{
+    as_child_of_current_scope, create_rw_signal, create_updater, ReadSignal, RwSignal, Scope,
+};
+use floem_winit::keyboard::{Key, ModifiersState, NamedKey};
+use taffy::style::Display;
+
+use crate::{
+    action::{cancel_timer, exec_after, TimerToken},
+    clock,
+    context::{PaintCx, StyleCx, UpdateCx},
+    event::{Event, EventListener},
+    gesture::SwipeDirection,
+    id::Id,
+    pointer::PointerButton,
+    style::DisplayProp,
+    view::{view_children_set_parent_id, View, ViewData},
+    EventPropagation,
+};
+
+use super::{ContainerTransition, Decorators};
+
+const FRAME: Duration = Duration::from_millis(16);
+
+/// The routes of a [`navigator`], from the root route to the current one. It's a handle to a
+/// signal, so it can be copied into the views of the routes to navigate from them.
+pub struct NavStack<R: 'static> {
+    routes: RwSignal<Vec<R>>,
+}
+
+impl<R: 'static> Copy for NavStack<R> {}
+
+impl<R: 'static> Clone for NavStack<R> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<R: 'static> NavStack<R> {
+    /// Creates a stack with `root` as its only route.
+    pub fn new(root: R) -> Self {
+        Self {
+            routes: create_rw_signal(vec![root]),
+        }
+    }
+
+    /// Goes to `route`, keeping the current route to go back to.
+    pub fn push(&self, route: R) {
+        self.routes.update(|routes| routes.push(route));
+    }
+
+    /// Goes back to the previous route, and returns whether there was one. The root route is
+    /// never popped.
+    pub fn pop(&self) -> bool {
+        let can_pop = self.routes.with_untracked(|routes| routes.len() > 1);
+        if can_pop {
+            self.routes.update(|routes| {
+                routes.pop();
+            });
+        }
+        can_pop
+    }
+
+    /// Goes back to the root route.
+    pub fn pop_to_root(&self) {
+        if self.routes.with_untracked(|routes| routes.len() > 1) {
+            self.routes.update(|routes| routes.truncate(1));
+        }
+    }
+
+    /// Goes to `route` instead of the current route, which can't be gone back to.
+    pub fn replace(&self, route: R) {
+        self.routes.update(|routes| {
+            if let Some(current) = routes.last_mut() {
+                *current = route;
+            }
+        });
+    }
+
+    /// Whether there's a route to go back to.
+    pub fn can_go_back(&self) -> bool {
+        self.routes.with(|routes| routes.len() > 1)
+    }
+
+    /// The routes, from the root route to the current one.
+    pub fn routes(&self) -> ReadSignal<Vec<R>> {
+        self.routes.read_only()
+    }
+}
+
+impl<R: Clone + 'static> NavStack<R> {
+    /// The current route.
+    pub fn current(&self) -> R {
+        self.routes
+            .with(|routes| routes.last().cloned())
+            .expect("the root route is never popped")
+    }
+}
+
+/// The page a [`Navigator`] is leaving.
+enum Outgoing {
+    /// A page which is kept under the new one, at this index.
+    Kept(usize),
+    /// A page which was popped, and is removed once the transition is done.
+    Removed(Box<dyn View>, Scope),
+}
+
+struct Navigation {
+    outgoing: Outgoing,
+    transition: ContainerTransition,
+    start: Instant,
+}
+
+/// A frame of the transition of a [`Navigator`].
+struct NavigationFrame(TimerToken);
+
+/// A view showing the current route of a [`NavStack`], see [`navigator`].
+pub struct Navigator<R: 'static> {
+    data: ViewData,
+    pages: Vec<(R, Box<dyn View>, Scope)>,
+    view_fn: Box<dyn Fn(R) -> (Box<dyn View>, Scope)>,
+    transition: Option<(ContainerTransition, Duration)>,
+    navigation: Option<Navigation>,
+    frame_timer: TimerToken,
+}
+
+/// A view showing the current route of `stack`, with the view built for it by `view_fn`.
+///
+/// The views of the routes under the current one are kept, hidden, so they have the same state
+/// when they're gone back to, like the position of a scroll view. A view is built again only
+/// when its route is pushed again after being popped or replaced.
+///
+/// Going back pops the stack, which is done by swiping right on the navigator, by the back
+/// button of a mouse, and by the back key of a keyboard.
+/// ```rust
+/// use floem::view::View;
+/// use floem::views::{label, navigator, Decorators, NavStack};
+/// use floem::widgets::button;
+///
+/// #[derive(Clone, PartialEq)]
+/// enum Route {
+///     Inbox,
+///     Message(usize),
+/// }
+///
+/// let stack = NavStack::new(Route::Inbox);
+/// navigator(stack, move |route| -> Box<dyn View> {
+///     match route {
+///         Route::Inbox => Box::new(
+///             button(|| "Open").on_click_stop(move |_| stack.push(Route::Message(1))),
+///         ),
+///         Route::Message(id) => Box::new(label(move || format!("Message {id}"))),
+///     }
+/// });
+/// ```
+pub fn navigator<R, V>(stack: NavStack<R>, view_fn: impl Fn(R) -> V + 'static) -> Navigator<R>
+where
+    R: Clone + PartialEq + 'static,
+    V: View + 'static,
+{
+    let id = Id::next();
+    let routes = create_updater(
+        move || stack.routes.get(),
+        move |routes| id.update_state(routes),
+    );
+    let view_fn = Box::new(as_child_of_current_scope(move |route| {
+        Box::new(view_fn(route)) as Box<dyn View>
+    }));
+    let pages = routes
+        .into_iter()
+        .map(|route| {
+            let (view, scope) = view_fn(route.clone());
+            (route, view, scope)
+        })
+        .collect();
+    Navigator {
+        data: ViewData::new(id),
+        pages,
+        view_fn,
+        transition: None,
+        navigation: None,
+        frame_timer: TimerToken::INVALID,
+    }
+    .on_swipe(move |direction| {
+        if direction == SwipeDirection::Right {
+            stack.pop();
+        }
+    })
+    .on_event(EventListener::PointerDown, move |event| {
+        if let Event::PointerDown(event) = event {
+            if event.button == PointerButton::X1 && stack.pop() {
+                return EventPropagation::Stop;
+            }
+        }
+        EventPropagation::Continue
+    })
+    .on_key_down(
+        Key::Named(NamedKey::BrowserBack),
+        ModifiersState::empty(),
+        move |_| {
+            stack.pop();
+        },
+    )
+}
+
+impl<R: PartialEq + Clone + 'static> Navigator<R> {
+    /// Animates going to a route with `transition`, and going back with the opposite one. The
+    /// transition lasts `duration`.
+    pub fn transition(mut self, transition: ContainerTransition, duration: Duration) -> Self {
+        self.transition = Some((transition, duration));
+        self
+    }
+
+    /// Builds the views of the routes which changed, keeping the views of the routes they start
+    /// with.
+    fn navigate(&mut self, cx: &mut UpdateCx, routes: Vec<R>) {
+        self.finish_navigation(cx);
+
+        let kept = self
+            .pages
+            .iter()
+            .zip(routes.iter())
+            .take_while(|((page, _, _), route)| page == *route)
+            .count();
+        let old_len = self.pages.len();
+        let going_back = routes.len() < old_len;
+        let mut outgoing = None;
+        for (i, (_, mut view, scope)) in self.pages.drain(kept..).enumerate().rev() {
+            if i + kept == old_len - 1 && self.transition.is_some() {
+                outgoing = Some(Outgoing::Removed(view, scope));
+            } else {
+                cx.app_state_mut().remove_view(&mut view);
+                scope.dispose();
+            }
+        }
+        for route in routes.into_iter().skip(kept) {
+            let (view, scope) = (self.view_fn)(route.clone());
+            view.id().set_parent(self.id());
+            view_children_set_parent_id(&*view);
+            self.pages.push((route, view, scope));
+        }
+
+        if kept > 0 && kept == old_len && kept < self.pages.len() {
+            outgoing = Some(Outgoing::Kept(kept - 1));
+        }
+        if let (Some(outgoing), Some((transition, _))) = (outgoing, self.transition) {
+            self.navigation = Some(Navigation {
+                outgoing,
+                transition: if going_back {
+                    transition.reversed()
+                } else {
+                    transition
+                },
+                start: clock::now(),
+            });
+            self.request_frame();
+        }
+        cx.request_all(self.id());
+        for (_, view, _) in &self.pages {
+            cx.request_all(view.id());
+        }
+    }
+
+    /// How far the transition is, from 0.0 to 1.0, or `None` if there's no transition running.
+    fn progress(&self) -> Option<f64> {
+        let (navigation, (_, duration)) = self.navigation.as_ref().zip(self.transition)?;
+        let elapsed = clock::now().duration_since(navigation.start);
+        Some((elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0))
+    }
+
+    fn request_frame(&mut self) {
+        let id = self.id();
+        self.frame_timer = exec_after(FRAME, move |token| {
+            id.update_state(NavigationFrame(token));
+        });
+    }
+
+    fn finish_navigation(&mut self, cx: &mut UpdateCx) {
+        cancel_timer(self.frame_timer);
+        if let Some(navigation) = self.navigation.take() {
+            match navigation.outgoing {
+                Outgoing::Kept(_) => cx.request_all(self.id()),
+                Outgoing::Removed(mut view, scope) => {
+                    cx.app_state_mut().remove_view(&mut view);
+                    scope.dispose();
+                }
+            }
+        }
+    }
+
+    /// The index of the page which is kept under the new one while it's shown, if any.
+    fn kept_outgoing(&self) -> Option<usize> {
+        match self.navigation.as_ref()?.outgoing {
+            Outgoing::Kept(index) => Some(index),
+            Outgoing::Removed(..) => None,
+        }
+    }
+}
+
+impl<R: PartialEq + Clone + 'static> View for Navigator<R> {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for (_, view, _) in &self.pages {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for (_, view, _) in &mut self.pages {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for (_, view, _) in self.pages.iter_mut().rev() {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        format!("Navigator: {} routes", self.pages.len()).into()
+    }
+
+    fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn std::any::Any>) {
+        let state = match state.downcast::<Vec<R>>() {
+            Ok(routes) => {
+                self.navigate(cx, *routes);
+                return;
+            }
+            Err(state) => state,
+        };
+        if let Ok(frame) = state.downcast::<NavigationFrame>() {
+            if frame.0 != self.frame_timer {
+                return;
+            }
+            if self.progress().is_some_and(|progress| progress >= 1.0) {
+                self.finish_navigation(cx);
+            } else {
+                self.request_frame();
+            }
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn style(&mut self, cx: &mut StyleCx<'_>) {
+        let current = self.pages.len().saturating_sub(1);
+        let kept_outgoing = self.kept_outgoing();
+        for (i, (_, view, _)) in self.pages.iter_mut().enumerate() {
+            cx.style_view(view);
+            let view_state = cx.app_state_mut().view_state(view.id());
+            let style = view_state.combined_style.clone();
+            view_state.combined_style = if i == current {
+                style.set(DisplayProp, Display::Flex)
+            } else if Some(i) == kept_outgoing {
+                // It stays where it is, under the new page, until the transition is done
+                style.set(DisplayProp, Display::Flex).absolute().inset(0.0)
+            } else {
+                style.set(DisplayProp, Display::None)
+            };
+        }
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        let Some(progress) = self.progress() else {
+            if let Some((_, view, _)) = self.pages.last_mut() {
+                cx.paint_view(view);
+            }
+            return;
+        };
+        let size = cx.app_state.get_layout_rect(self.data.id()).size();
+        let Some(navigation) = self.navigation.as_mut() else {
+            return;
+        };
+        let transition = navigation.transition;
+        match &mut navigation.outgoing {
+            Outgoing::Kept(index) => {
+                let (below, above) = self.pages.split_at_mut(*index + 1);
+                if let (Some((_, old, _)), Some((_, new, _))) = (below.last_mut(), above.last_mut())
+                {
+                    transition.paint(cx, size, progress, old, new);
+                }
+            }
+            Outgoing::Removed(old, _) => {
+                if let Some((_, new, _)) = self.pages.last_mut() {
+                    transition.paint(cx, size, progress, old, new);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, time::Duration};
+
+    use floem_peniko::Color;
+    use image::Rgba;
+    use kurbo::Size;
+
+    use super::{navigator, NavStack};
+    use crate::{
+        test_support::HeadlessWindow,
+        views::{empty, ContainerTransition, Decorators, SlideDirection},
+    };
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const GREEN: Rgba<u8> = Rgba([0, 255, 0, 255]);
+
+    /// A window with a navigator of 100x100 where the root page is red and the other pages are
+    /// green, which covers pages with the new one from the right over 300ms. Also returns the
+    /// routes whose pages are alive.
+    fn covering_window(
+        stack: NavStack<&'static str>,
+    ) -> (HeadlessWindow, Rc<RefCell<Vec<&'static str>>>) {
+        let alive = Rc::new(RefCell::new(Vec::new()));
+        let pages = alive.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            navigator(stack, move |route| {
+                pages.borrow_mut().push(route);
+                let pages = pages.clone();
+                let color = if route == "root" {
+                    Color::RED
+                } else {
+                    Color::rgb8(0, 255, 0)
+                };
+                empty()
+                    .style(move |s| s.size_full().background(color))
+                    .on_cleanup(move || pages.borrow_mut().retain(|page| *page != route))
+            })
+            .transition(
+                ContainerTransition::Cover(SlideDirection::Left),
+                Duration::from_millis(300),
+            )
+            .style(|s| s.size_full())
+        });
+        window.update();
+        (window, alive)
+    }
+
+    #[test]
+    fn navigator_keeps_the_routes_below() {
+        let built = Rc::new(RefCell::new(Vec::new()));
+        let built_routes = built.clone();
+        let stack = NavStack::new("root");
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            navigator(stack, move |route| {
+                built_routes.borrow_mut().push(route);
+                empty()
+            })
+        });
+        window.update();
+
+        stack.push("details");
+        window.update();
+        assert!(stack.pop());
+        window.update();
+        assert!(!stack.pop());
+        stack.push("details");
+        window.update();
+        stack.replace("settings");
+        window.update();
+        assert_eq!(stack.current(), "settings");
+        assert_eq!(*built.borrow(), ["root", "details", "details", "settings"]);
+    }
+
+    #[test]
+    fn pushed_pages_cover_the_current_one() {
+        let stack = NavStack::new("root");
+        let (mut window, _) = covering_window(stack);
+        stack.push("details");
+        window.update();
+
+        // A quarter of the way, the eased transition only moved the new page in a little
+        window.advance(Duration::from_millis(75));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 50), RED);
+        assert_eq!(*image.get_pixel(98, 50), GREEN);
+
+        window.advance(Duration::from_millis(250));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(2, 50), GREEN);
+        assert_eq!(*image.get_pixel(50, 50), GREEN);
+    }
+
+    #[test]
+    fn going_back_during_a_transition_reveals_the_page_below() {
+        let stack = NavStack::new("root");
+        let (mut window, alive) = covering_window(stack);
+        stack.push("details");
+        window.update();
+        window.advance(Duration::from_millis(150));
+
+        // The push is finished, and the popped page is revealed from the start
+        assert!(stack.pop());
+        window.update();
+        window.advance(Duration::from_millis(75));
+        assert_eq!(*alive.borrow(), ["root", "details"]);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(2, 50), RED);
+        assert_eq!(*image.get_pixel(50, 50), GREEN);
+
+        window.advance(Duration::from_millis(250));
+        assert_eq!(*alive.borrow(), ["root"]);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 50), RED);
+        assert_eq!(*image.get_pixel(98, 50), RED);
+    }
+}
@@ -0,0 +1,277 @@
+use floem_reactive::{with_scope, Scope};
+
+use crate::{
+    action::{add_portal, remove_portal},
+    context::{AppState, UpdateCx},
+    id::Id,
+    style::Style,
+    view::{view_children_set_parent_id, View, ViewData},
+};
+
+/// Where the content of a [`portal`] is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PortalTarget {
+    /// Over the whole window, above its views but below its overlays, like modal dialogs.
+    Window,
+    /// In the [`portal_host`] with this id.
+    Host(Id),
+}
+
+pub(crate) enum PortalUpdate {
+    Add(Box<dyn View>, Scope),
+    Remove(Id),
+}
+
+/// A view which shows its content elsewhere in the tree, see [`portal`].
+pub struct Portal {
+    data: ViewData,
+    content: Id,
+    target: PortalTarget,
+}
+
+/// Shows `content` at `target` instead of where the portal is, which is in an empty view. It's
+/// for views which have to be above the others, like modal dialogs, without ordering views with
+/// z-indexes.
+///
+/// The content still belongs to the portal: it's built in a child of the current scope, so it
+/// sees the [contexts](floem_reactive::use_context) provided around the portal, and it's
+/// removed when the portal is, with its scope disposed, so a portal in a
+/// [`dyn_container`](super::dyn_container) is shown with it. The content fills the target, and
+/// views which don't take the whole target can be positioned with styles, like `absolute` and
+/// `inset`.
+/// ```rust
+/// use floem::peniko::Color;
+/// use floem::views::{label, portal, v_stack, Decorators, PortalTarget};
+///
+/// v_stack((
+///     label(|| "Page"),
+///     portal(PortalTarget::Window, || {
+///         label(|| "Dialog").style(|s| {
+///             s.size_full()
+///                 .items_center()
+///                 .justify_center()
+///                 .background(Color::BLACK.with_alpha_factor(0.5))
+///         })
+///     }),
+/// ));
+/// ```
+pub fn portal<V: View + 'static>(target: PortalTarget, content: impl FnOnce() -> V) -> Portal {
+    let content_id = Id::next();
+    let scope = Scope::current().create_child();
+    let view: Box<dyn View> = Box::new(PortalView {
+        data: ViewData::new(content_id),
+        child: Box::new(with_scope(scope, content)),
+    });
+    match target {
+        PortalTarget::Window => add_portal(view, scope),
+        PortalTarget::Host(host) => host.update_state(PortalUpdate::Add(view, scope)),
+    }
+    Portal {
+        data: ViewData::new(Id::next()),
+        content: content_id,
+        target,
+    }
+}
+
+impl View for Portal {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Portal".into()
+    }
+}
+
+impl Drop for Portal {
+    fn drop(&mut self) {
+        match self.target {
+            PortalTarget::Window => remove_portal(self.content),
+            PortalTarget::Host(host) => host.update_state(PortalUpdate::Remove(self.content)),
+        }
+    }
+}
+
+/// The content of a [`Portal`] where it's shown, filling the target.
+struct PortalView {
+    data: ViewData,
+    child: Box<dyn View>,
+}
+
+impl View for PortalView {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn view_style(&self) -> Option<Style> {
+        Some(Style::new().absolute().inset(0.0))
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "PortalContent".into()
+    }
+}
+
+/// A view showing the content of the [`portal`]s targeting it, see [`portal_host`].
+pub struct PortalHost {
+    data: ViewData,
+    portals: Vec<(Box<dyn View>, Scope)>,
+}
+
+/// A view which shows the content of the [`portal`]s with [`PortalTarget::Host`] and its id, in
+/// the order they were created. It fills its parent and is positioned over it, so it should
+/// be after the views it covers.
+pub fn portal_host() -> PortalHost {
+    PortalHost {
+        data: ViewData::new(Id::next()),
+        portals: Vec::new(),
+    }
+}
+
+impl PortalHost {
+    pub(crate) fn add(&mut self, app_state: &mut AppState, view: Box<dyn View>, scope: Scope) {
+        view.id().set_parent(self.id());
+        view_children_set_parent_id(&*view);
+        self.portals.push((view, scope));
+        app_state.request_all(self.id());
+    }
+
+    pub(crate) fn remove(&mut self, app_state: &mut AppState, id: Id) {
+        if let Some(index) = self.portals.iter().position(|(view, _)| view.id() == id) {
+            let (mut view, scope) = self.portals.remove(index);
+            app_state.remove_view(&mut view);
+            scope.dispose();
+            app_state.request_all(self.id());
+        }
+    }
+}
+
+impl View for PortalHost {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn view_style(&self) -> Option<Style> {
+        Some(Style::new().absolute().inset(0.0))
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for (view, _) in &self.portals {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for (view, _) in &mut self.portals {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for (view, _) in self.portals.iter_mut().rev() {
+            if for_each(view) {
+                break;
+            }
+        }
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "PortalHost".into()
+    }
+
+    fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(update) = state.downcast::<PortalUpdate>() {
+            match *update {
+                PortalUpdate::Add(view, scope) => self.add(cx.app_state, view, scope),
+                PortalUpdate::Remove(id) => self.remove(cx.app_state, id),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use floem_reactive::{create_rw_signal, provide_context, use_context};
+    use kurbo::Size;
+
+    use super::{portal, PortalTarget};
+    use crate::{
+        test_support::HeadlessWindow,
+        views::{container, dyn_container, empty, Decorators},
+    };
+
+    #[test]
+    fn portal_content_follows_the_portal() {
+        let context = Rc::new(Cell::new(None));
+        let size = Rc::new(Cell::new(Size::ZERO));
+        let cleanups = Rc::new(Cell::new(0));
+        let shown = create_rw_signal(true);
+        let (seen, resized, cleaned) = (context.clone(), size.clone(), cleanups.clone());
+        let mut window = HeadlessWindow::new(Size::new(200.0, 100.0), move || {
+            provide_context(7u32);
+            container(
+                dyn_container(
+                    move || shown.get(),
+                    move |shown| {
+                        if !shown {
+                            return Box::new(empty());
+                        }
+                        let (seen, resized, cleaned) =
+                            (seen.clone(), resized.clone(), cleaned.clone());
+                        Box::new(portal(PortalTarget::Window, move || {
+                            seen.set(use_context::<u32>());
+                            empty()
+                                .on_resize(move |rect| resized.set(rect.size()))
+                                .on_cleanup(move || cleaned.set(cleaned.get() + 1))
+                                .style(|s| s.size_full())
+                        }))
+                    },
+                )
+                .style(|s| s.size(20.0, 20.0)),
+            )
+        });
+        window.update();
+        assert_eq!(context.get(), Some(7));
+        assert_eq!(size.get(), Size::new(200.0, 100.0));
+
+        shown.set(false);
+        window.update();
+        assert_eq!(cleanups.get(), 1);
+    }
+}
@@ -1,22 +1,39 @@
 use std::any::Any;
 
 use floem_reactive::create_effect;
-use floem_renderer::{cosmic_text::TextLayout, Renderer};
+use floem_renderer::{
+    cosmic_text::{AttrsList, TextLayout},
+    Renderer, TextLines,
+};
 use kurbo::{Point, Rect};
 use taffy::prelude::Node;
 
 use crate::{
     context::UpdateCx,
     id::Id,
-    style::{Style, TextOverflow},
+    prop_extracter,
+    style::{DirectionProp, Style, TextOverflow},
     unit::PxPct,
     view::{View, ViewData},
 };
 
+use super::label::RTL_MARK;
+
+prop_extracter! {
+    Extracter {
+        direction: DirectionProp,
+    }
+}
+
 pub struct RichText {
     data: ViewData,
+    /// The text layout given to the view.
+    source: TextLayout,
+    /// The text layout shown, which has right-to-left paragraphs in a right-to-left view.
     text_layout: TextLayout,
+    style: Extracter,
     text_node: Option<Node>,
+    baseline_node: Option<Node>,
     text_overflow: TextOverflow,
     available_width: f32,
 }
@@ -30,13 +47,55 @@ pub fn rich_text(text_layout: impl Fn() -> TextLayout + 'static) -> RichText {
     });
     RichText {
         data: ViewData::new(id),
+        source: text.clone(),
         text_layout: text,
+        style: Default::default(),
         text_node: None,
+        baseline_node: None,
         text_overflow: TextOverflow::Wrap,
         available_width: 0.0,
     }
 }
 
+/// Copies `text_layout` with each of its paragraphs starting with a right-to-left mark, so that
+/// they're right-to-left paragraphs even when they begin with left-to-right text.
+fn with_rtl_marks(text_layout: &TextLayout) -> TextLayout {
+    let Some(first_line) = text_layout.lines.first() else {
+        return text_layout.clone();
+    };
+    let mut text = String::new();
+    let mut attrs_list = AttrsList::new(first_line.attrs_list().defaults());
+    for (i, line) in text_layout.lines.iter().enumerate() {
+        if i > 0 {
+            text.push('\n');
+        }
+        text.push_str(RTL_MARK);
+        let start = text.len();
+        text.push_str(line.text());
+        // The lines can have different default attributes
+        attrs_list.add_span(start..text.len(), line.attrs_list().defaults());
+        for (range, attrs) in line.attrs_list().spans() {
+            attrs_list.add_span(start + range.start..start + range.end, attrs.as_attrs());
+        }
+    }
+    let mut rtl_layout = TextLayout::new();
+    rtl_layout.set_text(&text, attrs_list);
+    rtl_layout
+}
+
+impl RichText {
+    fn set_text_layout(&mut self) {
+        self.text_layout = if self.style.direction().is_rtl() {
+            with_rtl_marks(&self.source)
+        } else {
+            self.source.clone()
+        };
+        if self.text_overflow == TextOverflow::Wrap && self.available_width > 0.0 {
+            self.text_layout.set_size(self.available_width, f32::MAX);
+        }
+    }
+}
+
 impl View for RichText {
     fn view_data(&self) -> &ViewData {
         &self.data
@@ -49,7 +108,7 @@ impl View for RichText {
     fn debug_name(&self) -> std::borrow::Cow<'static, str> {
         format!(
             "RichText: {:?}",
-            self.text_layout
+            self.source
                 .lines
                 .iter()
                 .map(|text| text.text())
@@ -60,16 +119,19 @@ impl View for RichText {
 
     fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn Any>) {
         if let Ok(state) = state.downcast() {
-            let mut text_layout: TextLayout = *state;
-            if self.text_overflow == TextOverflow::Wrap && self.available_width > 0.0 {
-                text_layout.set_size(self.available_width, f32::MAX);
-            }
-
-            self.text_layout = text_layout;
+            self.source = *state;
+            self.set_text_layout();
             cx.request_layout(self.id());
         }
     }
 
+    fn style(&mut self, cx: &mut crate::context::StyleCx<'_>) {
+        if self.style.read(cx) {
+            self.set_text_layout();
+            cx.app_state_mut().request_layout(self.id());
+        }
+    }
+
     fn layout(&mut self, cx: &mut crate::context::LayoutCx) -> taffy::prelude::Node {
         cx.layout_node(self.id(), true, |cx| {
             let size = self.text_layout.size();
@@ -88,7 +150,13 @@ impl View for RichText {
 
             let style = Style::new().width(width).height(height).to_taffy_style();
             let _ = cx.app_state_mut().taffy.set_style(text_node, style);
-            vec![text_node]
+            let baseline = self
+                .text_layout
+                .layout_runs()
+                .next()
+                .map_or(height, |run| run.line_y);
+            let baseline_node = cx.baseline_node(self.id(), &mut self.baseline_node, baseline);
+            vec![baseline_node, text_node]
         })
     }
 
@@ -119,6 +187,14 @@ impl View for RichText {
         let text_node = self.text_node.unwrap();
         let location = cx.app_state.taffy.layout(text_node).unwrap().location;
         let point = Point::new(location.x as f64, location.y as f64);
-        cx.draw_text(&self.text_layout, point);
+        let align = cx.get_builtin_style(self.id()).text_align();
+        // The lines are aligned in the content box, which the text starts at
+        let width = cx.get_content_rect(self.id()).x1 - point.x;
+        let lines = TextLines {
+            align,
+            width: width as f32,
+            ..Default::default()
+        };
+        cx.draw_text_lines(&self.text_layout, point, &lines);
     }
 }
@@ -0,0 +1,167 @@
+use floem_peniko::Brush;
+use floem_reactive::create_effect;
+use floem_renderer::Renderer;
+use kurbo::{Affine, BezPath, Shape, Size};
+
+use crate::{
+    context::{PaintCx, UpdateCx},
+    id::Id,
+    view::{View, ViewData},
+};
+
+/// The tolerance used when converting recorded shapes to paths.
+const PATH_TOLERANCE: f64 = 0.1;
+
+#[derive(Clone)]
+enum SceneItem {
+    Fill {
+        path: BezPath,
+        brush: Brush,
+        blur_radius: f64,
+    },
+    Stroke {
+        path: BezPath,
+        brush: Brush,
+        width: f64,
+    },
+}
+
+/// A retained list of fills and strokes which can be painted many times without being rebuilt.
+///
+/// Shapes are converted to paths, with the current transform applied, when they are recorded.
+/// The transform set with [`Scene::set_transform`] does not scale stroke widths.
+#[derive(Clone, Default)]
+pub struct Scene {
+    items: Vec<SceneItem>,
+    transform: Affine,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the transform applied to shapes recorded after this call.
+    pub fn set_transform(&mut self, transform: Affine) {
+        self.transform = transform;
+    }
+
+    pub fn transform(&self) -> Affine {
+        self.transform
+    }
+
+    pub fn fill(&mut self, shape: &impl Shape, brush: impl Into<Brush>, blur_radius: f64) {
+        self.items.push(SceneItem::Fill {
+            path: self.transform * shape.to_path(PATH_TOLERANCE),
+            brush: brush.into(),
+            blur_radius,
+        });
+    }
+
+    pub fn stroke(&mut self, shape: &impl Shape, brush: impl Into<Brush>, width: f64) {
+        self.items.push(SceneItem::Stroke {
+            path: self.transform * shape.to_path(PATH_TOLERANCE),
+            brush: brush.into(),
+            width,
+        });
+    }
+
+    /// Appends the items of `other`, with the current transform applied to them.
+    pub fn append(&mut self, other: &Scene) {
+        let transform = self.transform;
+        self.items.extend(other.items.iter().map(|item| match item {
+            SceneItem::Fill {
+                path,
+                brush,
+                blur_radius,
+            } => SceneItem::Fill {
+                path: transform * path.clone(),
+                brush: brush.clone(),
+                blur_radius: *blur_radius,
+            },
+            SceneItem::Stroke { path, brush, width } => SceneItem::Stroke {
+                path: transform * path.clone(),
+                brush: brush.clone(),
+                width: *width,
+            },
+        }));
+    }
+
+    /// Removes all recorded items and resets the transform.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.transform = Affine::IDENTITY;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Submits the recorded items to `renderer`, in the order they were recorded.
+    pub fn paint(&self, renderer: &mut impl Renderer) {
+        for item in &self.items {
+            match item {
+                SceneItem::Fill {
+                    path,
+                    brush,
+                    blur_radius,
+                } => renderer.fill(path, brush, *blur_radius),
+                SceneItem::Stroke { path, brush, width } => renderer.stroke(path, brush, *width),
+            }
+        }
+    }
+}
+
+/// A view which paints a retained [`Scene`]. See [`scene`].
+pub struct SceneView {
+    data: ViewData,
+    scene: Scene,
+}
+
+/// A view which paints the [`Scene`] returned by `scene`.
+///
+/// `scene` is run in an effect, so the scene is only rebuilt when a signal it reads changes, and
+/// the view is then repainted. Between changes the recorded scene is painted as is, which is much
+/// cheaper than rebuilding complex graphics every frame with a [`canvas`](super::canvas).
+pub fn scene(scene: impl Fn() -> Scene + 'static) -> SceneView {
+    let id = Id::next();
+    create_effect(move |_| {
+        id.update_state(scene());
+    });
+    SceneView {
+        data: ViewData::new(id),
+        scene: Scene::new(),
+    }
+}
+
+impl View for SceneView {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Scene".into()
+    }
+
+    fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(scene) = state.downcast::<Scene>() {
+            self.scene = *scene;
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        let size = cx
+            .get_layout(self.id())
+            .map(|layout| Size::new(layout.size.width as f64, layout.size.height as f64))
+            .unwrap_or_default();
+        cx.save();
+        cx.clip(&size.to_rect());
+        self.scene.paint(&mut **cx);
+        cx.restore();
+    }
+}
@@ -1,21 +1,56 @@
-use floem_peniko::Color;
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use floem_peniko::{Brush, Color, ColorStop, Extend, Gradient, GradientKind};
 use floem_reactive::create_effect;
 use floem_renderer::Renderer;
-use kurbo::{Point, Rect, Size, Vec2};
+use kurbo::{Ellipse, Point, Rect, Size, Vec2};
 
 use crate::{
+    action::{cancel_timer, exec_after, TimerToken},
+    animate::{Easing, EasingFn, EasingMode},
+    clock,
     context::{AppState, ComputeLayoutCx, PaintCx},
     event::Event,
+    hot_reload,
     id::Id,
     prop, prop_extracter,
-    style::{Background, BorderColor, BorderRadius, Style, StyleSelector},
+    style::{
+        Background, BorderColor, BorderRadius, Fill, ScrollSnapAlign, ScrollSnapAlignProp,
+        ScrollSnapAxis, ScrollSnapStrictness, ScrollSnapType, ScrollSnapTypeProp, Style,
+        StyleSelector,
+    },
     style_class,
     unit::Px,
     view::{View, ViewData},
     EventPropagation,
 };
 
-enum ScrollState {
+use super::Decorators;
+
+thread_local! {
+    /// The scroll state of each scroll view, for [`Id::save_scroll_state`].
+    static SCROLL_STATES: RefCell<HashMap<Id, ScrollState>> = Default::default();
+}
+
+/// The scroll position of a [`Scroll`], which is saved with [`Id::save_scroll_state`] and
+/// restored with [`Id::restore_scroll_state`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ScrollState {
+    /// How far the content is scrolled.
+    pub offset: Vec2,
+}
+
+/// The scroll state of the scroll view with `id`.
+pub(crate) fn scroll_state(id: Id) -> Option<ScrollState> {
+    SCROLL_STATES.with(|states| states.borrow().get(&id).copied())
+}
+
+enum ScrollUpdate {
     EnsureVisible(Rect),
     ScrollDelta(Vec2),
     ScrollTo(Point),
@@ -24,6 +59,239 @@ enum ScrollState {
     HiddenBar(bool),
     PropagatePointerWheel(bool),
     VerticalScrollAsHorizontal(bool),
+    Overscroll(OverscrollMode),
+    /// The wheel stopped, so the scroll view springs back and settles on a snap position.
+    Settle(TimerToken),
+    Frame(TimerToken),
+    Visibility(ScrollbarVisibility),
+    /// The overlay scrollbars haven't been used for a while, so they fade out.
+    HideBars(TimerToken),
+}
+
+/// What a scroll view shows when it's scrolled past the edges of its content with the wheel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverscrollMode {
+    /// The content stops at its edges.
+    #[default]
+    HardStop,
+    /// The content follows the wheel past its edges with growing resistance, like a rubber
+    /// band, and springs back when the wheel stops, like on iOS and macOS.
+    Bounce,
+    /// A glow at the edge grows while scrolling past it, and fades when the wheel stops, like
+    /// on Android.
+    Glow,
+    /// What the platform does for native scroll views: [`OverscrollMode::Bounce`] on macOS,
+    /// and [`OverscrollMode::HardStop`] on Windows and Linux, where they don't overscroll.
+    Platform,
+}
+
+impl OverscrollMode {
+    fn resolve(self) -> OverscrollMode {
+        match self {
+            OverscrollMode::Platform if cfg!(target_os = "macos") => OverscrollMode::Bounce,
+            OverscrollMode::Platform => OverscrollMode::HardStop,
+            mode => mode,
+        }
+    }
+}
+
+/// When the scrollbars of a scroll view are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScrollbarVisibility {
+    /// The scrollbars are always shown, even when the content fits, like `overflow: scroll` in
+    /// CSS.
+    Always,
+    /// The scrollbars fade in while scrolling or when the pointer is over them, and fade out
+    /// once they haven't been used for a while, like on macOS and mobile platforms.
+    Overlay,
+    /// The scrollbar of an axis is shown when the content doesn't fit on it.
+    #[default]
+    Auto,
+}
+
+/// The look of the scrollbars of a scroll view, set with [`Scroll::scrollbar_style`]. It's a
+/// shorthand for styling the [`Handle`] and [`Track`] classes, which can also be styled
+/// directly, for instance to change the look on hover. The values which aren't set are left
+/// to the theme.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ScrollbarStyle {
+    thumb_color: Option<Color>,
+    track_color: Option<Color>,
+    thickness: Option<f64>,
+    radius: Option<f64>,
+}
+
+impl ScrollbarStyle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The color of the thumb, which is the part of the scrollbar which is dragged.
+    pub fn thumb_color(mut self, color: Color) -> Self {
+        self.thumb_color = Some(color);
+        self
+    }
+
+    /// The color of the track, along which the thumb moves.
+    pub fn track_color(mut self, color: Color) -> Self {
+        self.track_color = Some(color);
+        self
+    }
+
+    /// How wide a vertical scrollbar is, and how tall a horizontal one is.
+    pub fn thickness(mut self, thickness: f64) -> Self {
+        self.thickness = Some(thickness);
+        self
+    }
+
+    /// The radius of the corners of the thumb, instead of the fully rounded thumb of [`Rounded`].
+    pub fn radius(mut self, radius: f64) -> Self {
+        self.radius = Some(radius);
+        self
+    }
+
+    fn apply(self, style: Style) -> Style {
+        style
+            .class(Handle, |s| {
+                let s = s.apply_opt(self.thumb_color, Style::background);
+                let s = s.apply_opt(self.thickness, |s, thickness| {
+                    s.set(Thickness, Px(thickness))
+                });
+                s.apply_opt(self.radius, |s, radius| {
+                    s.border_radius(radius).set(Rounded, false)
+                })
+            })
+            .class(Track, |s| s.apply_opt(self.track_color, Style::background))
+    }
+}
+
+/// How long the wheel has to stop before the scroll view settles. The inertia of a touchpad
+/// keeps sending wheel events, so this waits for it to end too.
+const SETTLE_DELAY: Duration = Duration::from_millis(120);
+/// How far ahead the velocity of the last wheel events is projected when picking the snap
+/// position, so a quick flick settles on the next position instead of going back.
+const SNAP_MOMENTUM: f64 = 0.15;
+/// With proximity snapping, the part of the viewport within which a snap position is snapped to.
+const SNAP_PROXIMITY: f64 = 0.3;
+const SETTLE_DURATION: Duration = Duration::from_millis(250);
+const FRAME: Duration = Duration::from_millis(16);
+/// How quickly the resistance of rubber-band overscrolling grows, see [`rubber_band`].
+const RUBBER_BAND_STIFFNESS: f64 = 0.55;
+const GLOW_COLOR: Color = Color::rgba8(128, 128, 128, 120);
+/// How long overlay scrollbars stay after they were last used, before fading out.
+const OVERLAY_BAR_DELAY: Duration = Duration::from_millis(1000);
+const BAR_FADE_DURATION: Duration = Duration::from_millis(200);
+
+/// An animation of the scroll view settling, to a snap position or back from overscrolling.
+struct SettleAnimation {
+    from: Vec2,
+    to: Vec2,
+    start: Instant,
+}
+
+impl SettleAnimation {
+    fn new(from: Vec2, to: Vec2) -> Self {
+        Self {
+            from,
+            to,
+            start: clock::now(),
+        }
+    }
+
+    /// The value at the current time, and whether the animation is done.
+    fn value(&self) -> (Vec2, bool) {
+        let (progress, done) = eased_progress(self.start, SETTLE_DURATION);
+        (self.from.lerp(self.to, progress), done)
+    }
+}
+
+/// An animation of overlay scrollbars fading in or out.
+struct BarFade {
+    from: f64,
+    to: f64,
+    start: Instant,
+}
+
+impl BarFade {
+    fn new(from: f64, to: f64) -> Self {
+        Self {
+            from,
+            to,
+            start: clock::now(),
+        }
+    }
+
+    /// The opacity at the current time, and whether the fade is done.
+    fn value(&self) -> (f64, bool) {
+        let (progress, done) = eased_progress(self.start, BAR_FADE_DURATION);
+        (self.from + (self.to - self.from) * progress, done)
+    }
+}
+
+/// How far an animation which started at `start` and lasts `duration` is, eased, and whether
+/// it's done.
+fn eased_progress(start: Instant, duration: Duration) -> (f64, bool) {
+    let elapsed = clock::now().duration_since(start);
+    let t = (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0);
+    let easing = Easing {
+        mode: EasingMode::Out,
+        func: EasingFn::Cubic,
+    };
+    (easing.ease(t), t >= 1.0)
+}
+
+/// Paints `brush` at `opacity`.
+fn brush_with_opacity(brush: Brush, opacity: f64) -> Brush {
+    match brush {
+        Brush::Solid(color) => Brush::Solid(color.with_alpha_factor(opacity as f32)),
+        Brush::Gradient(mut gradient) => {
+            for stop in gradient.stops.iter_mut() {
+                stop.color = stop.color.with_alpha_factor(opacity as f32);
+            }
+            Brush::Gradient(gradient)
+        }
+        brush => brush,
+    }
+}
+
+/// How far the content is moved when it's overscrolled by `overscroll` on an axis where the
+/// viewport is `length` long. The content follows ever slower, and never moves by `length`.
+fn rubber_band(overscroll: f64, length: f64) -> f64 {
+    if length <= 0.0 {
+        return 0.0;
+    }
+    let moved = (1.0 - 1.0 / (overscroll.abs() * RUBBER_BAND_STIFFNESS / length + 1.0)) * length;
+    moved.copysign(overscroll)
+}
+
+/// Moves the part of `delta` which scrolls back from overscrolling into `overscroll`.
+fn take_overscroll(overscroll: &mut f64, delta: &mut f64) {
+    if *overscroll * *delta < 0.0 {
+        let taken = delta.abs().min(overscroll.abs()).copysign(*delta);
+        *overscroll += taken;
+        *delta -= taken;
+    }
+}
+
+/// The snap position a scroll view settles at on an axis when it stops at `offset`, where the
+/// momentum of the scrolling would have carried it to `projected`.
+fn snap_offset(
+    offset: f64,
+    projected: f64,
+    viewport: f64,
+    positions: &[f64],
+    strictness: ScrollSnapStrictness,
+) -> Option<f64> {
+    let closest = positions
+        .iter()
+        .copied()
+        .min_by(|a, b| (a - projected).abs().total_cmp(&(b - projected).abs()))?;
+    match strictness {
+        ScrollSnapStrictness::Mandatory => Some(closest),
+        ScrollSnapStrictness::Proximity => {
+            ((closest - offset).abs() <= viewport * SNAP_PROXIMITY).then_some(closest)
+        }
+    }
 }
 
 /// Minimum length for any scrollbar to be when measured on that
@@ -49,6 +317,10 @@ style_class!(pub Track);
 prop!(pub Rounded: bool {} = cfg!(target_os = "macos"));
 prop!(pub Thickness: Px {} = Px(10.0));
 prop!(pub Border: Px {} = Px(0.0));
+/// The color of the fades of [`Scroll::with_scroll_fade`], where they're most opaque.
+prop!(pub FadeColor: Color {} = Color::rgba8(0, 0, 0, 60));
+/// How far the fades of [`Scroll::with_scroll_fade`] reach into the scroll view.
+prop!(pub FadeSize: Px {} = Px(16.0));
 
 prop_extracter! {
     ScrollStyle {
@@ -94,6 +366,27 @@ pub struct Scroll {
     track_style: ScrollStyle,
     track_hover_style: ScrollStyle,
     hide: bool,
+    /// The key the scroll state is persisted with.
+    persist_key: Option<String>,
+    snap_type: Option<ScrollSnapType>,
+    /// The velocity of the last wheel events, in pixels per second.
+    wheel_velocity: Vec2,
+    last_wheel: Option<Instant>,
+    settle_timer: TimerToken,
+    frame_timer: TimerToken,
+    snap_animation: Option<SettleAnimation>,
+    overscroll_mode: OverscrollMode,
+    /// How far the wheel scrolled past the edges of the content.
+    overscroll: Vec2,
+    overscroll_animation: Option<SettleAnimation>,
+    scroll_fade: bool,
+    fade_color: Color,
+    fade_size: f64,
+    visibility: ScrollbarVisibility,
+    /// The opacity of the scrollbars, which only changes for overlay scrollbars.
+    bar_opacity: f64,
+    bar_fade: Option<BarFade>,
+    hide_bars_timer: TimerToken,
 }
 
 pub fn scroll<V: View + 'static>(child: V) -> Scroll {
@@ -118,6 +411,23 @@ pub fn scroll<V: View + 'static>(child: V) -> Scroll {
         handle_hover_style: Default::default(),
         track_style: Default::default(),
         track_hover_style: Default::default(),
+        persist_key: None,
+        snap_type: None,
+        wheel_velocity: Vec2::ZERO,
+        last_wheel: None,
+        settle_timer: TimerToken::INVALID,
+        frame_timer: TimerToken::INVALID,
+        snap_animation: None,
+        overscroll_mode: OverscrollMode::default(),
+        overscroll: Vec2::ZERO,
+        overscroll_animation: None,
+        scroll_fade: false,
+        fade_color: Color::TRANSPARENT,
+        fade_size: 0.0,
+        visibility: ScrollbarVisibility::default(),
+        bar_opacity: 1.0,
+        bar_fade: None,
+        hide_bars_timer: TimerToken::INVALID,
     }
 }
 
@@ -127,11 +437,27 @@ impl Scroll {
         self
     }
 
+    /// Saves the scroll position under `key` whenever it changes, and scrolls back to the
+    /// position saved under `key` when the scroll view is created. A scroll view rebuilt with
+    /// the same key, such as when switching back to a tab, keeps its position.
+    ///
+    /// The key should identify the content, so a scroll view showing other content starts at
+    /// the top. The position is kept across rebuilds of the app like the values of
+    /// [`persisted`](crate::hot_reload::persisted), and the key is scoped the same way.
+    pub fn persist_scroll(mut self, key: impl Display) -> Self {
+        let key = hot_reload::full_key(format!("scroll:{key}"));
+        if let Some(offset) = hot_reload::load_value(&key) {
+            self.id().restore_scroll_state(ScrollState { offset });
+        }
+        self.persist_key = Some(key);
+        self
+    }
+
     pub fn ensure_visible(self, to: impl Fn() -> Rect + 'static) -> Self {
         let id = self.id();
         create_effect(move |_| {
             let rect = to();
-            id.update_state_deferred(ScrollState::EnsureVisible(rect));
+            id.update_state_deferred(ScrollUpdate::EnsureVisible(rect));
         });
 
         self
@@ -141,7 +467,7 @@ impl Scroll {
         let id = self.id();
         create_effect(move |_| {
             let delta = delta();
-            id.update_state(ScrollState::ScrollDelta(delta));
+            id.update_state(ScrollUpdate::ScrollDelta(delta));
         });
 
         self
@@ -151,7 +477,7 @@ impl Scroll {
         let id = self.id();
         create_effect(move |_| {
             if let Some(origin) = origin() {
-                id.update_state_deferred(ScrollState::ScrollTo(origin));
+                id.update_state_deferred(ScrollUpdate::ScrollTo(origin));
             }
         });
 
@@ -163,7 +489,7 @@ impl Scroll {
         let id = self.id();
         create_effect(move |_| {
             let percent = percent() / 100.;
-            id.update_state_deferred(ScrollState::ScrollToPercent(percent));
+            id.update_state_deferred(ScrollUpdate::ScrollToPercent(percent));
         });
         self
     }
@@ -172,7 +498,7 @@ impl Scroll {
         let id = self.id();
         create_effect(move |_| {
             if let Some(view) = view() {
-                id.update_state_deferred(ScrollState::ScrollToView(view));
+                id.update_state_deferred(ScrollUpdate::ScrollToView(view));
             }
         });
 
@@ -182,15 +508,33 @@ impl Scroll {
     pub fn hide_bar(self, hide: impl Fn() -> bool + 'static) -> Self {
         let id = self.id();
         create_effect(move |_| {
-            id.update_state(ScrollState::HiddenBar(hide()));
+            id.update_state(ScrollUpdate::HiddenBar(hide()));
+        });
+        self
+    }
+
+    /// Sets when the scrollbars are shown, see [`ScrollbarVisibility`]. They're shown when the
+    /// content doesn't fit by default.
+    pub fn scrollbar_visibility(
+        self,
+        visibility: impl Fn() -> ScrollbarVisibility + 'static,
+    ) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            id.update_state(ScrollUpdate::Visibility(visibility()));
         });
         self
     }
 
+    /// Sets the look of the scrollbars, see [`ScrollbarStyle`].
+    pub fn scrollbar_style(self, style: impl Fn() -> ScrollbarStyle + 'static) -> Self {
+        self.style(move |s| style().apply(s))
+    }
+
     pub fn propagate_pointer_wheel(self, value: impl Fn() -> bool + 'static) -> Self {
         let id = self.id();
         create_effect(move |_| {
-            id.update_state(ScrollState::PropagatePointerWheel(value()));
+            id.update_state(ScrollUpdate::PropagatePointerWheel(value()));
         });
         self
     }
@@ -198,7 +542,25 @@ impl Scroll {
     pub fn vertical_scroll_as_horizontal(self, value: impl Fn() -> bool + 'static) -> Self {
         let id = self.id();
         create_effect(move |_| {
-            id.update_state(ScrollState::VerticalScrollAsHorizontal(value()));
+            id.update_state(ScrollUpdate::VerticalScrollAsHorizontal(value()));
+        });
+        self
+    }
+
+    /// Shows a fade at the edges of the scroll view which have more content to scroll to, which
+    /// disappears when scrolled to the end. Its color and size are set with the [`FadeColor`]
+    /// and [`FadeSize`] properties, like `.style(|s| s.set(FadeSize, Px(24.0)))`.
+    pub fn with_scroll_fade(mut self) -> Self {
+        self.scroll_fade = true;
+        self
+    }
+
+    /// Sets what the scroll view shows when it's scrolled past the edges of its content with
+    /// the wheel, see [`OverscrollMode`]. It stops at the edges by default.
+    pub fn overscroll(self, mode: impl Fn() -> OverscrollMode + 'static) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            id.update_state(ScrollUpdate::Overscroll(mode()));
         });
         self
     }
@@ -212,6 +574,319 @@ impl Scroll {
         self.clamp_child_viewport(app_state, self.child_viewport.with_origin(origin));
     }
 
+    /// Scrolls by `delta` from the wheel, past the edges of the content if the overscroll mode
+    /// allows it, and restarts the timer which settles the scroll view once the wheel, and the
+    /// inertia of the touchpad, stop.
+    fn scroll_by_wheel(&mut self, app_state: &mut AppState, delta: Vec2) {
+        self.stop_settling();
+        let overscrolls = self.overscroll_mode.resolve() != OverscrollMode::HardStop;
+        let mut scroll_delta = delta;
+        if overscrolls {
+            take_overscroll(&mut self.overscroll.x, &mut scroll_delta.x);
+            take_overscroll(&mut self.overscroll.y, &mut scroll_delta.y);
+        }
+        let target = self.child_viewport.origin() + scroll_delta;
+        self.clamp_child_viewport(app_state, self.child_viewport.with_origin(target));
+        if overscrolls {
+            self.overscroll += target - self.child_viewport.origin();
+            app_state.request_paint(self.id());
+        }
+
+        let now = clock::now();
+        self.wheel_velocity = match self.last_wheel {
+            Some(last) if now - last < SETTLE_DELAY => {
+                let elapsed = (now - last).as_secs_f64().max(0.001);
+                (self.wheel_velocity + delta / elapsed) / 2.0
+            }
+            _ => Vec2::ZERO,
+        };
+        self.last_wheel = Some(now);
+        if self.snap_type.is_some() || self.overscroll != Vec2::ZERO {
+            let id = self.id();
+            self.settle_timer = exec_after(SETTLE_DELAY, move |token| {
+                id.update_state(ScrollUpdate::Settle(token));
+            });
+        }
+    }
+
+    /// Stops settling, leaving the scroll view where it is.
+    fn stop_settling(&mut self) {
+        cancel_timer(self.settle_timer);
+        cancel_timer(self.frame_timer);
+        self.snap_animation = None;
+        self.overscroll_animation = None;
+        // Overlay scrollbars keep fading
+        self.request_frame();
+    }
+
+    /// Springs back from overscrolling, and animates the scroll view to the snap position
+    /// picked for where it stopped.
+    fn settle(&mut self, app_state: &mut AppState) {
+        let offset = self.child_viewport.origin();
+        let projected = offset + self.wheel_velocity * SNAP_MOMENTUM;
+        self.wheel_velocity = Vec2::ZERO;
+        self.last_wheel = None;
+
+        if self.overscroll != Vec2::ZERO {
+            self.overscroll_animation = Some(SettleAnimation::new(self.overscroll, Vec2::ZERO));
+        }
+        if let Some(snap_type) = self.snap_type {
+            let (xs, ys) = self.snap_positions(app_state, snap_type.axis);
+            let viewport = self.child_viewport.size();
+            let strictness = snap_type.strictness;
+            let target = Point::new(
+                snap_offset(offset.x, projected.x, viewport.width, &xs, strictness)
+                    .unwrap_or(offset.x),
+                snap_offset(offset.y, projected.y, viewport.height, &ys, strictness)
+                    .unwrap_or(offset.y),
+            );
+            if target != offset {
+                self.snap_animation =
+                    Some(SettleAnimation::new(offset.to_vec2(), target.to_vec2()));
+            }
+        }
+        self.request_frame();
+    }
+
+    fn request_frame(&mut self) {
+        if self.snap_animation.is_some()
+            || self.overscroll_animation.is_some()
+            || self.bar_fade.is_some()
+        {
+            let id = self.id();
+            self.frame_timer = exec_after(FRAME, move |token| {
+                id.update_state(ScrollUpdate::Frame(token));
+            });
+        }
+    }
+
+    fn frame(&mut self, app_state: &mut AppState, token: TimerToken) {
+        if token != self.frame_timer {
+            return;
+        }
+        if let Some(animation) = &self.overscroll_animation {
+            let (overscroll, done) = animation.value();
+            self.overscroll = overscroll;
+            if done {
+                self.overscroll_animation = None;
+            }
+            app_state.request_paint(self.id());
+        }
+        if let Some(animation) = &self.snap_animation {
+            let (origin, done) = animation.value();
+            if done {
+                self.snap_animation = None;
+            }
+            self.do_scroll_to(app_state, origin.to_point());
+        }
+        if let Some(fade) = &self.bar_fade {
+            let (opacity, done) = fade.value();
+            self.bar_opacity = opacity;
+            if done {
+                self.bar_fade = None;
+            }
+            app_state.request_paint(self.id());
+        }
+        self.request_frame();
+    }
+
+    /// Fades the overlay scrollbars in, and restarts the timer which fades them out.
+    fn show_bars(&mut self) {
+        if self.visibility != ScrollbarVisibility::Overlay {
+            return;
+        }
+        self.fade_bars(1.0);
+        cancel_timer(self.hide_bars_timer);
+        let id = self.id();
+        self.hide_bars_timer = exec_after(OVERLAY_BAR_DELAY, move |token| {
+            id.update_state(ScrollUpdate::HideBars(token));
+        });
+    }
+
+    fn hide_bars(&mut self) {
+        if self.are_bars_held() || self.v_track_hover || self.h_track_hover {
+            // They're still used, so they stay until the pointer leaves them
+            self.show_bars();
+        } else {
+            self.fade_bars(0.0);
+        }
+    }
+
+    fn fade_bars(&mut self, to: f64) {
+        let target = self
+            .bar_fade
+            .as_ref()
+            .map_or(self.bar_opacity, |fade| fade.to);
+        if target != to {
+            self.bar_fade = Some(BarFade::new(self.bar_opacity, to));
+            self.request_frame();
+        }
+    }
+
+    /// Whether the scrollbar of an axis on which the viewport is `viewport` long and the
+    /// content is `content` long is shown.
+    fn shows_bar(&self, viewport: f64, content: f64) -> bool {
+        content > viewport || self.visibility == ScrollbarVisibility::Always
+    }
+
+    /// How far the content is moved by overscrolling, see [`rubber_band`].
+    fn overscroll_offset(&self) -> Vec2 {
+        Vec2::new(
+            rubber_band(self.overscroll.x, self.child_viewport.width()),
+            rubber_band(self.overscroll.y, self.child_viewport.height()),
+        )
+    }
+
+    /// Paints a glow at the edges the content is overscrolled past, which grows with the
+    /// overscroll.
+    fn draw_glow(&self, cx: &mut PaintCx, offset: Vec2) {
+        let rect = self.actual_rect;
+        let center = rect.center();
+        // The center of the edge, the radii of the glow, and how far it's grown
+        let mut glows = Vec::new();
+        if offset.x != 0.0 {
+            let x = if offset.x < 0.0 { rect.x0 } else { rect.x1 };
+            let radii = Vec2::new(offset.x.abs(), rect.height() * 0.75);
+            glows.push((
+                Point::new(x, center.y),
+                radii,
+                offset.x.abs() / rect.width(),
+            ));
+        }
+        if offset.y != 0.0 {
+            let y = if offset.y < 0.0 { rect.y0 } else { rect.y1 };
+            let radii = Vec2::new(rect.width() * 0.75, offset.y.abs());
+            glows.push((
+                Point::new(center.x, y),
+                radii,
+                offset.y.abs() / rect.height(),
+            ));
+        }
+        for (edge, radii, grown) in glows {
+            let color = GLOW_COLOR.with_alpha_factor((grown * 2.0).min(1.0) as f32);
+            let blur = radii.x.min(radii.y) / 2.0;
+            cx.fill(&Ellipse::new(edge, radii, 0.0), color, blur);
+        }
+    }
+
+    /// Paints a fade at each edge with more content past it, which fades in as the content
+    /// moves away from the edge.
+    fn draw_fades(&self, cx: &mut PaintCx) {
+        let rect = self.actual_rect;
+        let viewport = self.child_viewport;
+        let size = self.fade_size;
+        // How much content is past each edge, where the fade starts and where it ends
+        let edges = [
+            (
+                viewport.y0,
+                rect.origin(),
+                Point::new(rect.x1, rect.y0 + size),
+            ),
+            (
+                self.child_size.height - viewport.y1,
+                Point::new(rect.x0, rect.y1),
+                Point::new(rect.x1, rect.y1 - size),
+            ),
+            (
+                viewport.x0,
+                rect.origin(),
+                Point::new(rect.x0 + size, rect.y1),
+            ),
+            (
+                self.child_size.width - viewport.x1,
+                Point::new(rect.x1, rect.y0),
+                Point::new(rect.x1 - size, rect.y1),
+            ),
+        ];
+        for (i, (hidden, from, to)) in edges.into_iter().enumerate() {
+            if hidden <= 0.0 || size <= 0.0 {
+                continue;
+            }
+            let color = self
+                .fade_color
+                .with_alpha_factor((hidden / size).min(1.0) as f32);
+            // The gradient runs across the edge, from the corner at `from`
+            let end = if i < 2 {
+                Point::new(from.x, to.y)
+            } else {
+                Point::new(to.x, from.y)
+            };
+            let stops = [(0.0, color), (1.0, color.with_alpha_factor(0.0))]
+                .into_iter()
+                .map(|(offset, color)| ColorStop { offset, color })
+                .collect();
+            let brush = Brush::Gradient(Gradient {
+                kind: GradientKind::Linear { start: from, end },
+                extend: Extend::Pad,
+                stops,
+            });
+            cx.fill(&Rect::from_points(from, to), &brush, 0.0);
+        }
+    }
+
+    /// The offsets on each snapping axis which align the content views with a
+    /// [`ScrollSnapAlign`] to the viewport, limited to the offsets the content can scroll to.
+    fn snap_positions(
+        &self,
+        app_state: &mut AppState,
+        axis: ScrollSnapAxis,
+    ) -> (Vec<f64>, Vec<f64>) {
+        let viewport = self.child_viewport.size();
+        let max_x = (self.child_size.width - viewport.width).max(0.0);
+        let max_y = (self.child_size.height - viewport.height).max(0.0);
+        let mut xs = Vec::new();
+        let mut ys = Vec::new();
+        // The views with their origin relative to the content
+        let mut views: Vec<(&dyn View, Point)> = vec![(&*self.child, Point::ZERO)];
+        while let Some((view, origin)) = views.pop() {
+            let id = view.id();
+            if app_state.is_hidden(id) {
+                continue;
+            }
+            if let Some(align) = app_state.get_computed_style(id).get(ScrollSnapAlignProp) {
+                let size = app_state
+                    .get_layout(id)
+                    .map(|layout| Size::new(layout.size.width as f64, layout.size.height as f64))
+                    .unwrap_or_default();
+                let rect = Rect::from_origin_size(origin, size);
+                let (x, y) = match align {
+                    ScrollSnapAlign::Start => (rect.x0, rect.y0),
+                    ScrollSnapAlign::Center => (
+                        rect.center().x - viewport.width / 2.0,
+                        rect.center().y - viewport.height / 2.0,
+                    ),
+                    ScrollSnapAlign::End => (rect.x1 - viewport.width, rect.y1 - viewport.height),
+                };
+                xs.push(x.clamp(0.0, max_x));
+                ys.push(y.clamp(0.0, max_y));
+            }
+            view.for_each_child(&mut |child| {
+                let location = app_state
+                    .get_layout(child.id())
+                    .map(|layout| Vec2::new(layout.location.x as f64, layout.location.y as f64))
+                    .unwrap_or_default();
+                views.push((child, origin + location));
+                false
+            });
+        }
+        match axis {
+            ScrollSnapAxis::X => ys.clear(),
+            ScrollSnapAxis::Y => xs.clear(),
+            ScrollSnapAxis::Both => {}
+        }
+        (xs, ys)
+    }
+
+    fn save_state(&self) {
+        let state = ScrollState {
+            offset: self.child_viewport.origin().to_vec2(),
+        };
+        SCROLL_STATES.with(|states| states.borrow_mut().insert(self.id(), state));
+        if let Some(key) = &self.persist_key {
+            hot_reload::store_value(key.clone(), &state.offset);
+        }
+    }
+
     /// Pan the smallest distance that makes the target [`Rect`] visible.
     ///
     /// If the target rect is larger than viewport size, we will prioritize
@@ -304,10 +979,14 @@ impl Scroll {
         child_viewport = child_viewport.with_size(actual_size);
 
         if child_viewport != self.child_viewport {
+            if child_viewport.origin() != self.child_viewport.origin() {
+                self.show_bars();
+            }
             app_state.set_viewport(self.child.id(), child_viewport);
             app_state.request_compute_layout_recursive(self.id());
             app_state.request_paint(self.id());
             self.child_viewport = child_viewport;
+            self.save_state();
             if let Some(onscroll) = &self.onscroll {
                 onscroll(child_viewport);
             }
@@ -368,18 +1047,24 @@ impl Scroll {
                     &self.track_style
                 };
 
-            if let Some(color) = track_style.color() {
+            if let Some(fill) = track_style.color() {
                 let mut bounds = bounds - scroll_offset;
                 bounds.y0 = self.actual_rect.y0;
                 bounds.y1 = self.actual_rect.y1;
-                cx.fill(&bounds, color, 0.0);
+                let brush = brush_with_opacity(fill.to_brush(bounds), self.bar_opacity);
+                cx.fill(&bounds, &brush, 0.0);
             }
             let edge_width = style.border().0;
             let rect = (bounds - scroll_offset).inset(-edge_width / 2.0);
             let rect = rect.to_rounded_rect(radius(style, rect, true));
-            cx.fill(&rect, style.color().unwrap_or(HANDLE_COLOR), 0.0);
+            let fill = style.color().unwrap_or(Fill::Solid(HANDLE_COLOR));
+            let brush = brush_with_opacity(fill.to_brush(rect.rect()), self.bar_opacity);
+            cx.fill(&rect, &brush, 0.0);
             if edge_width > 0.0 {
-                cx.stroke(&rect, style.border_color(), edge_width);
+                let color = style
+                    .border_color()
+                    .with_alpha_factor(self.bar_opacity as f32);
+                cx.stroke(&rect, color, edge_width);
             }
         }
 
@@ -393,18 +1078,24 @@ impl Scroll {
                     &self.track_style
                 };
 
-            if let Some(color) = track_style.color() {
+            if let Some(fill) = track_style.color() {
                 let mut bounds = bounds - scroll_offset;
                 bounds.x0 = self.actual_rect.x0;
                 bounds.x1 = self.actual_rect.x1;
-                cx.fill(&bounds, color, 0.0);
+                let brush = brush_with_opacity(fill.to_brush(bounds), self.bar_opacity);
+                cx.fill(&bounds, &brush, 0.0);
             }
             let edge_width = style.border().0;
             let rect = (bounds - scroll_offset).inset(-edge_width / 2.0);
             let rect = rect.to_rounded_rect(radius(style, rect, false));
-            cx.fill(&rect, style.color().unwrap_or(HANDLE_COLOR), 0.0);
+            let fill = style.color().unwrap_or(Fill::Solid(HANDLE_COLOR));
+            let brush = brush_with_opacity(fill.to_brush(rect.rect()), self.bar_opacity);
+            cx.fill(&rect, &brush, 0.0);
             if edge_width > 0.0 {
-                cx.stroke(&rect, style.border_color(), edge_width);
+                let color = style
+                    .border_color()
+                    .with_alpha_factor(self.bar_opacity as f32);
+                cx.stroke(&rect, color, edge_width);
             }
         }
     }
@@ -415,7 +1106,7 @@ impl Scroll {
         let scroll_offset = self.child_viewport.origin().to_vec2();
 
         // dbg!(viewport_size.height, content_size.height);
-        if viewport_size.height >= content_size.height {
+        if !self.shows_bar(viewport_size.height, content_size.height) {
             return None;
         }
 
@@ -424,8 +1115,12 @@ impl Scroll {
         let bar_width = style.thickness().0;
         let bar_pad = 0.0;
 
-        let percent_visible = viewport_size.height / content_size.height;
-        let percent_scrolled = scroll_offset.y / (content_size.height - viewport_size.height);
+        let percent_visible = (viewport_size.height / content_size.height).min(1.0);
+        let percent_scrolled = if content_size.height > viewport_size.height {
+            scroll_offset.y / (content_size.height - viewport_size.height)
+        } else {
+            0.0
+        };
 
         let length = (percent_visible * viewport_size.height).ceil();
         // Vertical scroll bar must have ast least the same height as it's width
@@ -448,7 +1143,7 @@ impl Scroll {
         let content_size = self.child_size;
         let scroll_offset = self.child_viewport.origin().to_vec2();
 
-        if viewport_size.width >= content_size.width {
+        if !self.shows_bar(viewport_size.width, content_size.width) {
             return None;
         }
 
@@ -457,13 +1152,17 @@ impl Scroll {
         let bar_width = style.thickness().0;
         let bar_pad = 0.0;
 
-        let percent_visible = viewport_size.width / content_size.width;
-        let percent_scrolled = scroll_offset.x / (content_size.width - viewport_size.width);
+        let percent_visible = (viewport_size.width / content_size.width).min(1.0);
+        let percent_scrolled = if content_size.width > viewport_size.width {
+            scroll_offset.x / (content_size.width - viewport_size.width)
+        } else {
+            0.0
+        };
 
         let length = (percent_visible * viewport_size.width).ceil();
         let length = length.max(SCROLLBAR_MIN_SIZE);
 
-        let horizontal_padding = if viewport_size.height >= content_size.height {
+        let horizontal_padding = if !self.shows_bar(viewport_size.height, content_size.height) {
             0.0
         } else {
             bar_pad + bar_pad + bar_width
@@ -482,20 +1181,22 @@ impl Scroll {
         Some(Rect::new(x0, y0, x1, y1))
     }
 
+    /// Scrolls by a page towards `pos`, which is on the track of the vertical scrollbar.
     fn click_vertical_bar_area(&mut self, app_state: &mut AppState, pos: Point) {
-        let new_y = (pos.y / self.actual_rect.height()) * self.child_size.height
-            - self.actual_rect.height() / 2.0;
-        let mut new_origin = self.child_viewport.origin();
-        new_origin.y = new_y;
-        self.do_scroll_to(app_state, new_origin);
+        if let Some(bounds) = self.calc_vertical_bar_bounds(app_state) {
+            let page = self.child_viewport.height();
+            let delta = if pos.y < bounds.y0 { -page } else { page };
+            self.do_scroll_delta(app_state, Vec2::new(0.0, delta));
+        }
     }
 
+    /// Scrolls by a page towards `pos`, which is on the track of the horizontal scrollbar.
     fn click_horizontal_bar_area(&mut self, app_state: &mut AppState, pos: Point) {
-        let new_x = (pos.x / self.actual_rect.width()) * self.child_size.width
-            - self.actual_rect.width() / 2.0;
-        let mut new_origin = self.child_viewport.origin();
-        new_origin.x = new_x;
-        self.do_scroll_to(app_state, new_origin);
+        if let Some(bounds) = self.calc_horizontal_bar_bounds(app_state) {
+            let page = self.child_viewport.width();
+            let delta = if pos.x < bounds.x0 { -page } else { page };
+            self.do_scroll_delta(app_state, Vec2::new(delta, 0.0));
+        }
     }
 
     fn point_within_vertical_bar(&self, app_state: &mut AppState, pos: Point) -> bool {
@@ -571,11 +1272,17 @@ impl Scroll {
         let hover = self.point_within_vertical_bar(app_state, pos);
         if self.v_track_hover != hover {
             self.v_track_hover = hover;
+            if hover {
+                self.show_bars();
+            }
             app_state.request_paint(self.id());
         }
         let hover = self.point_within_horizontal_bar(app_state, pos);
         if self.h_track_hover != hover {
             self.h_track_hover = hover;
+            if hover {
+                self.show_bars();
+            }
             app_state.request_paint(self.id());
         }
     }
@@ -649,35 +1356,73 @@ impl View for Scroll {
     }
 
     fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
-        if let Ok(state) = state.downcast::<ScrollState>() {
+        let state = match state.downcast::<ScrollState>() {
+            Ok(state) => {
+                self.do_scroll_to(cx.app_state, state.offset.to_point());
+                cx.request_layout(self.id());
+                return;
+            }
+            Err(state) => state,
+        };
+        if let Ok(state) = state.downcast::<ScrollUpdate>() {
             match *state {
-                ScrollState::EnsureVisible(rect) => {
+                ScrollUpdate::EnsureVisible(rect) => {
                     self.pan_to_visible(cx.app_state, rect);
                 }
-                ScrollState::ScrollDelta(delta) => {
+                ScrollUpdate::ScrollDelta(delta) => {
                     self.do_scroll_delta(cx.app_state, delta);
                 }
-                ScrollState::ScrollTo(origin) => {
+                ScrollUpdate::ScrollTo(origin) => {
                     self.do_scroll_to(cx.app_state, origin);
                 }
-                ScrollState::ScrollToPercent(percent) => {
+                ScrollUpdate::ScrollToPercent(percent) => {
                     let mut child_size = self.child_size;
                     child_size *= percent as f64;
                     let point = child_size.to_vec2().to_point();
                     self.do_scroll_to(cx.app_state, point);
                 }
-                ScrollState::ScrollToView(id) => {
+                ScrollUpdate::ScrollToView(id) => {
                     self.do_scroll_to_view(cx.app_state, id, None);
                 }
-                ScrollState::HiddenBar(hide) => {
+                ScrollUpdate::HiddenBar(hide) => {
                     self.hide = hide;
                 }
-                ScrollState::PropagatePointerWheel(value) => {
+                ScrollUpdate::PropagatePointerWheel(value) => {
                     self.propagate_pointer_wheel = value;
                 }
-                ScrollState::VerticalScrollAsHorizontal(value) => {
+                ScrollUpdate::VerticalScrollAsHorizontal(value) => {
                     self.vertical_scroll_as_horizontal = value;
                 }
+                ScrollUpdate::Overscroll(mode) => {
+                    self.overscroll_mode = mode;
+                    if mode.resolve() == OverscrollMode::HardStop {
+                        self.overscroll = Vec2::ZERO;
+                        self.overscroll_animation = None;
+                    }
+                }
+                ScrollUpdate::Settle(token) => {
+                    if token == self.settle_timer {
+                        self.settle(cx.app_state);
+                    }
+                }
+                ScrollUpdate::Frame(token) => {
+                    self.frame(cx.app_state, token);
+                }
+                ScrollUpdate::Visibility(visibility) => {
+                    self.visibility = visibility;
+                    cancel_timer(self.hide_bars_timer);
+                    self.bar_fade = None;
+                    self.bar_opacity = if visibility == ScrollbarVisibility::Overlay {
+                        0.0
+                    } else {
+                        1.0
+                    };
+                }
+                ScrollUpdate::HideBars(token) => {
+                    if token == self.hide_bars_timer {
+                        self.hide_bars();
+                    }
+                }
             }
             cx.request_layout(self.id());
         }
@@ -693,6 +1438,9 @@ impl View for Scroll {
 
     fn style(&mut self, cx: &mut crate::context::StyleCx<'_>) {
         let style = cx.style();
+        self.snap_type = style.get(ScrollSnapTypeProp);
+        self.fade_color = style.get(FadeColor);
+        self.fade_size = style.get(FadeSize).0;
 
         let handle_style = style.clone().apply_class(Handle);
         self.handle_style.read_style(cx, &handle_style);
@@ -733,12 +1481,13 @@ impl View for Scroll {
 
         match &event {
             Event::PointerDown(event) => {
-                if !self.hide && event.button.is_primary() {
+                if !self.hide && self.bar_opacity > 0.0 && event.button.is_primary() {
                     self.held = BarHeldState::None;
 
                     let pos = event.pos + scroll_offset;
 
                     if self.point_within_vertical_bar(cx.app_state, pos) {
+                        self.stop_settling();
                         if self.point_hits_vertical_bar(cx.app_state, pos) {
                             self.held = BarHeldState::Vertical(
                                 // The bounds must be non-empty, because the point hits the scrollbar.
@@ -750,7 +1499,7 @@ impl View for Scroll {
                             cx.request_paint(self.id());
                             return EventPropagation::Stop;
                         }
-                        self.click_vertical_bar_area(cx.app_state, event.pos);
+                        self.click_vertical_bar_area(cx.app_state, pos);
                         let scroll_offset = self.child_viewport.origin().to_vec2();
                         self.held = BarHeldState::Vertical(
                             // The bounds must be non-empty, because the point hits the scrollbar.
@@ -760,6 +1509,7 @@ impl View for Scroll {
                         cx.update_active(self.id());
                         return EventPropagation::Stop;
                     } else if self.point_within_horizontal_bar(cx.app_state, pos) {
+                        self.stop_settling();
                         if self.point_hits_horizontal_bar(cx.app_state, pos) {
                             self.held = BarHeldState::Horizontal(
                                 // The bounds must be non-empty, because the point hits the scrollbar.
@@ -771,7 +1521,7 @@ impl View for Scroll {
                             cx.request_paint(self.id());
                             return EventPropagation::Stop;
                         }
-                        self.click_horizontal_bar_area(cx.app_state, event.pos);
+                        self.click_horizontal_bar_area(cx.app_state, pos);
                         let scroll_offset = self.child_viewport.origin().to_vec2();
                         self.held = BarHeldState::Horizontal(
                             // The bounds must be non-empty, because the point hits the scrollbar.
@@ -786,6 +1536,8 @@ impl View for Scroll {
             Event::PointerUp(_event) => {
                 if self.are_bars_held() {
                     self.held = BarHeldState::None;
+                    self.settle(cx.app_state);
+                    self.show_bars();
                     // Force a repaint.
                     cx.request_paint(self.id());
                 }
@@ -841,7 +1593,7 @@ impl View for Scroll {
             return EventPropagation::Stop;
         }
 
-        if let Event::PointerWheel(pointer_event) = &event {
+        if let (false, Event::PointerWheel(pointer_event)) = (cx.is_default_prevented(), &event) {
             if let Some(listener) = event.listener() {
                 if let Some(action) = cx.get_event_listener(self.id(), &listener) {
                     if (*action)(&event).is_processed() {
@@ -855,7 +1607,7 @@ impl View for Scroll {
             } else {
                 delta
             };
-            self.clamp_child_viewport(cx.app_state, self.child_viewport + delta);
+            self.scroll_by_wheel(cx.app_state, delta);
 
             // Check if the scroll bars now hover
             self.update_hover_states(cx.app_state, pointer_event.pos);
@@ -883,12 +1635,372 @@ impl View for Scroll {
         } else {
             cx.clip(&self.actual_rect);
         }
-        cx.offset((-self.child_viewport.x0, -self.child_viewport.y0));
+        let overscroll = self.overscroll_offset();
+        let mode = self.overscroll_mode.resolve();
+        let content_offset = if mode == OverscrollMode::Bounce {
+            self.child_viewport.origin().to_vec2() + overscroll
+        } else {
+            self.child_viewport.origin().to_vec2()
+        };
+        cx.offset((-content_offset.x, -content_offset.y));
         cx.paint_view(&mut self.child);
         cx.restore();
 
-        if !self.hide {
+        if mode == OverscrollMode::Glow && overscroll != Vec2::ZERO {
+            cx.save();
+            cx.clip(&self.actual_rect);
+            self.draw_glow(cx, overscroll);
+            cx.restore();
+        }
+        if self.scroll_fade {
+            self.draw_fades(cx);
+        }
+
+        if !self.hide && self.bar_opacity > 0.0 {
             self.draw_bars(cx);
         }
     }
 }
+
+impl Drop for Scroll {
+    fn drop(&mut self) {
+        SCROLL_STATES.with(|states| states.borrow_mut().remove(&self.id()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use kurbo::{Point, Size, Vec2};
+
+    use floem_peniko::Color;
+    use image::Rgba;
+
+    use super::{
+        rubber_band, scroll, snap_offset, take_overscroll, FadeColor, FadeSize, OverscrollMode,
+        ScrollbarStyle, ScrollbarVisibility,
+    };
+    use crate::{
+        id::Id,
+        style::{ScrollSnapAlign, ScrollSnapAxis, ScrollSnapStrictness},
+        test_support::HeadlessWindow,
+        unit::Px,
+        view::View,
+        views::{container, empty, v_stack_from_iter, Decorators},
+    };
+
+    /// A window with a scroll view of 100x100 over five pages of 100x100, which it snaps to
+    /// with `strictness`. Also returns the id of the scroll view.
+    fn paging_window(strictness: ScrollSnapStrictness) -> (HeadlessWindow, Id) {
+        let id = Rc::new(Cell::new(None));
+        let scroll_id = id.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let pages = (0..5).map(|_| {
+                empty().style(|s| {
+                    s.size(100.0, 100.0)
+                        .scroll_snap_align(ScrollSnapAlign::Start)
+                })
+            });
+            let view = scroll(v_stack_from_iter(pages)).style(move |s| {
+                s.size_full()
+                    .scroll_snap_type(ScrollSnapAxis::Y, strictness)
+            });
+            scroll_id.set(Some(view.id()));
+            view
+        });
+        window.update();
+        (window, id.get().unwrap())
+    }
+
+    fn offset(id: Id) -> f64 {
+        id.save_scroll_state().unwrap_or_default().offset.y
+    }
+
+    fn scroll_down(window: &mut HeadlessWindow, distance: f64) {
+        window.wheel(Point::new(50.0, 50.0), Vec2::new(0.0, distance));
+    }
+
+    #[test]
+    fn snapping_picks_the_position_the_scroll_was_heading_to() {
+        let mandatory = ScrollSnapStrictness::Mandatory;
+        let proximity = ScrollSnapStrictness::Proximity;
+        let positions = [0.0, 100.0, 200.0];
+        assert_eq!(
+            snap_offset(40.0, 40.0, 100.0, &positions, mandatory),
+            Some(0.0)
+        );
+        assert_eq!(
+            snap_offset(40.0, 160.0, 100.0, &positions, mandatory),
+            Some(200.0)
+        );
+        assert_eq!(snap_offset(40.0, 40.0, 100.0, &[], mandatory), None);
+        // Only positions within 30% of the viewport are snapped to
+        assert_eq!(
+            snap_offset(25.0, 25.0, 100.0, &positions, proximity),
+            Some(0.0)
+        );
+        assert_eq!(snap_offset(40.0, 40.0, 100.0, &positions, proximity), None);
+    }
+
+    #[test]
+    fn the_scroll_settles_on_a_snap_position_once_the_wheel_stops() {
+        let (mut window, id) = paging_window(ScrollSnapStrictness::Mandatory);
+        scroll_down(&mut window, 40.0);
+        assert_eq!(offset(id), 40.0);
+        window.advance(Duration::from_millis(100));
+        assert_eq!(offset(id), 40.0);
+
+        // It settles 120ms after the wheel stopped, and moves there over 250ms
+        window.advance(Duration::from_millis(150));
+        let settling = offset(id);
+        assert!(settling > 0.0 && settling < 40.0, "{settling}");
+        window.advance(Duration::from_millis(250));
+        assert_eq!(offset(id), 0.0);
+    }
+
+    #[test]
+    fn quick_scrolls_settle_further_along() {
+        let (mut window, id) = paging_window(ScrollSnapStrictness::Mandatory);
+        // 40px in 10ms is heading past the second page
+        scroll_down(&mut window, 20.0);
+        window.advance(Duration::from_millis(10));
+        scroll_down(&mut window, 20.0);
+        window.advance(Duration::from_millis(500));
+        assert_eq!(offset(id), 200.0);
+    }
+
+    #[test]
+    fn proximity_snapping_only_settles_near_a_snap_position() {
+        let (mut window, id) = paging_window(ScrollSnapStrictness::Proximity);
+        scroll_down(&mut window, 20.0);
+        window.advance(Duration::from_millis(500));
+        assert_eq!(offset(id), 0.0);
+
+        scroll_down(&mut window, 140.0);
+        window.advance(Duration::from_millis(500));
+        assert_eq!(offset(id), 140.0);
+        scroll_down(&mut window, 35.0);
+        window.advance(Duration::from_millis(500));
+        assert_eq!(offset(id), 200.0);
+    }
+
+    #[test]
+    fn scrolling_while_settling_stops_it() {
+        let (mut window, id) = paging_window(ScrollSnapStrictness::Mandatory);
+        scroll_down(&mut window, 40.0);
+        window.advance(Duration::from_millis(170));
+        let settling = offset(id);
+        assert!(settling < 40.0, "{settling}");
+
+        scroll_down(&mut window, 10.0);
+        assert_eq!(offset(id), settling + 10.0);
+        window.advance(Duration::from_millis(100));
+        assert_eq!(offset(id), settling + 10.0);
+        // Then it settles again from where the wheel left it
+        window.advance(Duration::from_millis(500));
+        assert_eq!(offset(id), 0.0);
+    }
+
+    const RED: Rgba<u8> = Rgba([255, 0, 0, 255]);
+    const WHITE: Rgba<u8> = Rgba([255, 255, 255, 255]);
+
+    /// A window with a scroll view of 100x100 over red content of 100x300, on white, which
+    /// overscrolls with `mode`.
+    fn overscrolling_window(mode: OverscrollMode) -> HeadlessWindow {
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let content = empty().style(|s| s.size(100.0, 300.0).background(Color::RED));
+            container(
+                scroll(content)
+                    .overscroll(move || mode)
+                    .style(|s| s.size_full()),
+            )
+            .style(|s| s.size_full().background(Color::WHITE))
+        });
+        window.update();
+        window
+    }
+
+    #[test]
+    fn overscrolling_resists_more_the_further_it_goes() {
+        assert_eq!(rubber_band(0.0, 100.0), 0.0);
+        let moved = rubber_band(30.0, 100.0);
+        assert!(moved > 0.0 && moved < 30.0, "{moved}");
+        assert_eq!(rubber_band(-30.0, 100.0), -moved);
+        assert!(rubber_band(30.0, 100.0) - rubber_band(20.0, 100.0) < rubber_band(10.0, 100.0));
+        assert!(rubber_band(10_000.0, 100.0) < 100.0);
+        assert_eq!(rubber_band(30.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn scrolling_back_takes_up_the_overscroll_first() {
+        let (mut overscroll, mut delta) = (-30.0, 20.0);
+        take_overscroll(&mut overscroll, &mut delta);
+        assert_eq!((overscroll, delta), (-10.0, 0.0));
+        let (mut overscroll, mut delta) = (-30.0, 50.0);
+        take_overscroll(&mut overscroll, &mut delta);
+        assert_eq!((overscroll, delta), (0.0, 20.0));
+        // Scrolling further past the edge is left to the wheel
+        let (mut overscroll, mut delta) = (-30.0, -10.0);
+        take_overscroll(&mut overscroll, &mut delta);
+        assert_eq!((overscroll, delta), (-30.0, -10.0));
+    }
+
+    #[test]
+    fn bouncing_content_springs_back_when_the_wheel_stops() {
+        let mut window = overscrolling_window(OverscrollMode::Bounce);
+        scroll_down(&mut window, -30.0);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 5), WHITE);
+        assert_eq!(*image.get_pixel(50, 20), RED);
+
+        window.advance(Duration::from_millis(500));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 1), RED);
+    }
+
+    #[test]
+    fn glowing_content_stays_in_place() {
+        let mut window = overscrolling_window(OverscrollMode::Glow);
+        scroll_down(&mut window, -30.0);
+        let image = window.render().unwrap();
+        assert_ne!(*image.get_pixel(50, 1), WHITE);
+        assert_ne!(*image.get_pixel(50, 1), RED);
+        assert_eq!(*image.get_pixel(50, 60), RED);
+
+        window.advance(Duration::from_millis(500));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 1), RED);
+    }
+
+    #[test]
+    fn hard_stops_dont_overscroll() {
+        let mut window = overscrolling_window(OverscrollMode::HardStop);
+        scroll_down(&mut window, -30.0);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(50, 1), RED);
+    }
+
+    #[test]
+    fn fades_show_at_the_edges_with_more_content() {
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), || {
+            let content = empty().style(|s| s.size(100.0, 300.0).background(Color::WHITE));
+            scroll(content).with_scroll_fade().style(|s| {
+                s.size_full()
+                    .set(FadeColor, Color::BLACK)
+                    .set(FadeSize, Px(20.0))
+            })
+        });
+        window.update();
+        let brightness =
+            |window: &mut HeadlessWindow, y| window.render().unwrap().get_pixel(50, y)[0];
+
+        // Only the bottom fades at the start, and only near the edge
+        assert_eq!(brightness(&mut window, 0), 255);
+        assert!(brightness(&mut window, 99) < 20);
+        assert_eq!(brightness(&mut window, 70), 255);
+
+        // The top fades in as the content moves away from it
+        scroll_down(&mut window, 10.0);
+        let top = brightness(&mut window, 0);
+        assert!(top > 100 && top < 160, "{top}");
+        scroll_down(&mut window, 20.0);
+        assert!(brightness(&mut window, 0) < 20);
+
+        // The bottom fade disappears at the end
+        scroll_down(&mut window, 500.0);
+        assert!(brightness(&mut window, 0) < 20);
+        assert_eq!(brightness(&mut window, 99), 255);
+    }
+
+    /// A window with a scroll view of 100x100 over white content which is `height` tall, with
+    /// the scrollbars shown by `visibility` and styled by `style`. Also returns the id of the
+    /// scroll view.
+    fn scrollbar_window(
+        height: f64,
+        visibility: ScrollbarVisibility,
+        style: ScrollbarStyle,
+    ) -> (HeadlessWindow, Id) {
+        let id = Rc::new(Cell::new(None));
+        let scroll_id = id.clone();
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            let content = empty().style(move |s| s.size(100.0, height));
+            let view = scroll(content)
+                .scrollbar_visibility(move || visibility)
+                .scrollbar_style(move || style)
+                .style(|s| s.size_full());
+            scroll_id.set(Some(view.id()));
+            container(view).style(|s| s.size_full().background(Color::WHITE))
+        });
+        window.update();
+        (window, id.get().unwrap())
+    }
+
+    #[test]
+    fn clicking_the_track_scrolls_by_a_page() {
+        let style = ScrollbarStyle::new();
+        let (mut window, id) = scrollbar_window(500.0, ScrollbarVisibility::Auto, style);
+        // The thumb is 20px long, from the top
+        window.click(Point::new(92.0, 80.0));
+        assert_eq!(offset(id), 100.0);
+        window.click(Point::new(92.0, 80.0));
+        assert_eq!(offset(id), 200.0);
+        window.click(Point::new(92.0, 5.0));
+        assert_eq!(offset(id), 100.0);
+        // Clicking the thumb doesn't scroll
+        window.click(Point::new(92.0, 30.0));
+        assert_eq!(offset(id), 100.0);
+    }
+
+    #[test]
+    fn bars_are_shown_when_the_content_overflows_unless_they_always_are() {
+        let style = ScrollbarStyle::new().thumb_color(Color::RED);
+        let (mut window, _) = scrollbar_window(500.0, ScrollbarVisibility::Auto, style);
+        assert_eq!(*window.render().unwrap().get_pixel(92, 10), RED);
+        let (mut window, _) = scrollbar_window(50.0, ScrollbarVisibility::Auto, style);
+        assert_eq!(*window.render().unwrap().get_pixel(92, 10), WHITE);
+
+        // Always shown bars span the whole track when the content fits
+        let (mut window, _) = scrollbar_window(50.0, ScrollbarVisibility::Always, style);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(92, 10), RED);
+        assert_eq!(*image.get_pixel(92, 70), RED);
+    }
+
+    #[test]
+    fn overlay_bars_fade_in_while_scrolling_then_out() {
+        let style = ScrollbarStyle::new().thumb_color(Color::RED);
+        let (mut window, id) = scrollbar_window(500.0, ScrollbarVisibility::Overlay, style);
+        assert_eq!(*window.render().unwrap().get_pixel(92, 10), WHITE);
+        // The hidden bars can't be clicked
+        window.click(Point::new(92.0, 80.0));
+        assert_eq!(offset(id), 0.0);
+
+        scroll_down(&mut window, 100.0);
+        window.advance(Duration::from_millis(300));
+        assert_eq!(*window.render().unwrap().get_pixel(92, 30), RED);
+
+        // They stay while the pointer is over them, then fade out once it leaves
+        window.pointer_move(Point::new(92.0, 30.0));
+        window.advance(Duration::from_millis(1500));
+        assert_ne!(*window.render().unwrap().get_pixel(92, 30), WHITE);
+        window.pointer_move(Point::new(50.0, 50.0));
+        window.advance(Duration::from_millis(1500));
+        assert_eq!(*window.render().unwrap().get_pixel(92, 30), WHITE);
+    }
+
+    #[test]
+    fn scrollbar_styles_set_the_look_of_the_bars() {
+        let style = ScrollbarStyle::new()
+            .thumb_color(Color::RED)
+            .track_color(Color::BLACK)
+            .thickness(10.0)
+            .radius(0.0);
+        let (mut window, _) = scrollbar_window(500.0, ScrollbarVisibility::Auto, style);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(85, 10), WHITE);
+        assert_eq!(*image.get_pixel(90, 0), RED);
+        assert_eq!(*image.get_pixel(99, 19), RED);
+        assert_eq!(*image.get_pixel(95, 50), Rgba([0, 0, 0, 255]));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{
+    id::Id,
+    style::Style,
+    view::{View, ViewData},
+};
+
+/// A view which takes the space left in a stack, see [`spacer`].
+pub struct Spacer {
+    data: ViewData,
+    weight: f64,
+}
+
+/// An empty view which grows to take the space left in the stack it's in, pushing the views
+/// after it to the end. It's a shorthand for an [`empty`](super::empty) view with
+/// `flex_grow(1.0)`, which reads better in layouts like toolbars:
+/// ```rust
+/// use floem::views::{h_stack, label, spacer};
+///
+/// let toolbar = h_stack((
+///     label(|| "Back"),
+///     label(|| "Forward"),
+///     spacer(),
+///     label(|| "Settings"),
+/// ));
+/// ```
+pub fn spacer() -> Spacer {
+    spacer_weighted(1.0)
+}
+
+/// A [`spacer`] which shares the space left in the stack with the other spacers in proportion
+/// to its `weight`, so a spacer with a weight of 2.0 is twice as long as one with a weight
+/// of 1.0.
+pub fn spacer_weighted(weight: f64) -> Spacer {
+    Spacer {
+        data: ViewData::new(Id::next()),
+        weight,
+    }
+}
+
+impl View for Spacer {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Spacer".into()
+    }
+
+    fn view_style(&self) -> Option<Style> {
+        // Spacers start from nothing so the space is split by weight only
+        Some(Style::new().flex_grow(self.weight as f32).flex_basis(0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use kurbo::Size;
+
+    use super::{spacer, spacer_weighted};
+    use crate::{
+        test_support::HeadlessWindow,
+        views::{empty, h_stack, Decorators},
+    };
+
+    #[test]
+    fn spacers_split_the_space_by_weight() {
+        let widths = Rc::new([Cell::new(0.0), Cell::new(0.0), Cell::new(0.0)]);
+        let spacer_widths = widths.clone();
+        let mut window = HeadlessWindow::new(Size::new(300.0, 50.0), move || {
+            let on_resize = |i: usize| {
+                let widths = spacer_widths.clone();
+                move |rect: kurbo::Rect| widths[i].set(rect.width())
+            };
+            h_stack((
+                spacer().on_resize(on_resize(0)),
+                empty().style(|s| s.width(60.0)),
+                spacer_weighted(2.0).on_resize(on_resize(1)),
+                spacer_weighted(3.0).on_resize(on_resize(2)),
+            ))
+            .style(|s| s.width_full())
+        });
+        window.update();
+        let widths: Vec<f64> = widths.iter().map(Cell::get).collect();
+        assert_eq!(widths, [40.0, 80.0, 120.0]);
+    }
+}
@@ -6,6 +6,7 @@ use floem_renderer::{
 };
 use kurbo::Size;
 use sha2::{Digest, Sha256};
+use std::path::PathBuf;
 
 use crate::{
     id::Id,
@@ -18,6 +19,14 @@ pub struct Svg {
     svg_hash: Option<Vec<u8>>,
 }
 
+/// A view which renders the svg document returned by `svg_str`, scaled to fit the view and
+/// centered in it.
+///
+/// `svg_str` is run in an effect, and the document is parsed again whenever it changes.
+/// The rasterized svg is cached by the renderer for the document and the size it's drawn at.
+///
+/// If the `color` style is set, the svg is painted with that color, which is useful for
+/// recoloring monochrome icons.
 pub fn svg(svg_str: impl Fn() -> String + 'static) -> Svg {
     let id = Id::next();
     create_effect(move |_| {
@@ -31,6 +40,14 @@ pub fn svg(svg_str: impl Fn() -> String + 'static) -> Svg {
     }
 }
 
+/// A view which renders the svg file at the path returned by `path`.
+///
+/// The file is read again whenever `path` changes. See [`svg`] for how the svg is drawn.
+/// Nothing is drawn if the file can't be read or parsed.
+pub fn svg_file(path: impl Fn() -> PathBuf + 'static) -> Svg {
+    svg(move || std::fs::read_to_string(path()).unwrap_or_default())
+}
+
 impl View for Svg {
     fn view_data(&self) -> &ViewData {
         &self.data
@@ -47,11 +64,15 @@ impl View for Svg {
     fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
         if let Ok(state) = state.downcast::<String>() {
             let text = &*state;
-            self.svg_tree = Tree::from_str(text, &usvg::Options::default()).ok();
 
             let mut hasher = Sha256::new();
             hasher.update(text);
             let hash = hasher.finalize().to_vec();
+            if self.svg_hash.as_ref() == Some(&hash) {
+                return;
+            }
+
+            self.svg_tree = Tree::from_str(text, &usvg::Options::default()).ok();
             self.svg_hash = Some(hash);
 
             cx.request_layout(self.id());
@@ -0,0 +1,1425 @@
+//! A multi-line text editor view.
+
+use std::{
+    any::Any,
+    ops::Range,
+    time::{Duration, Instant},
+};
+
+use floem_reactive::{create_effect, RwSignal, UndoStack};
+use floem_renderer::{
+    cosmic_text::{Attrs, AttrsList, FamilyOwned, Style as FontStyle, TextLayout, Weight},
+    Renderer,
+};
+use kurbo::{Circle, Point, Rect, Vec2};
+use taffy::prelude::Node;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    action::{exec_after, set_ime_allowed},
+    context::{ComputeLayoutCx, EventCx, LayoutCx, PaintCx, StyleCx, UpdateCx},
+    event::{Event, EventListener},
+    id::Id,
+    keyboard::{Key, KeyEvent, ModifiersState, NamedKey},
+    peniko::Color,
+    prop, prop_extracter,
+    style::{CursorColor, FontProps, Style, TextColor},
+    style_class,
+    view::{View, ViewData},
+    views::Decorators,
+    widgets::{find_matches, FindAction, FindState},
+    Clipboard, EventPropagation,
+};
+
+style_class!(pub TextEditorClass);
+
+prop!(pub SelectionColor: Color {} = Color::rgba8(0, 120, 215, 80));
+prop!(pub LineNumberColor: Color {} = Color::GRAY);
+prop!(pub FindMatchColor: Color {} = Color::rgba8(255, 200, 0, 90));
+prop!(pub CurrentMatchColor: Color {} = Color::rgba8(255, 140, 0, 160));
+
+prop_extracter! {
+    EditorStyle {
+        color: TextColor,
+        cursor_color: CursorColor,
+        selection_color: SelectionColor,
+        line_number_color: LineNumberColor,
+        find_match_color: FindMatchColor,
+        current_match_color: CurrentMatchColor,
+    }
+}
+
+const DEFAULT_FONT_SIZE: f32 = 14.0;
+const CURSOR_BLINK_INTERVAL_MS: u64 = 500;
+/// How long after the last edit typing is still merged into the same undo step.
+const UNDO_COALESCE_WINDOW: Duration = Duration::from_millis(750);
+const UNDO_COALESCE_EDITS: usize = 50;
+const TYPING: &str = "Typing";
+/// The space between the gutter and the text.
+const GUTTER_PADDING: f64 = 8.0;
+
+enum EditorUpdate {
+    TextChanged,
+    ReadOnly(bool),
+    ScrollToCaret,
+    Markers(Vec<GutterMarker>),
+    MatchesChanged,
+}
+
+/// A line of the text, which is wrapped into one or more rows.
+struct Line {
+    text: String,
+    /// The offset of the line in the text.
+    start: usize,
+    /// The distance from the top of the text to the top of the line.
+    top: f64,
+    height: f64,
+    layout: TextLayout,
+}
+
+impl Line {
+    fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+}
+
+/// An edit of the text, in byte offsets, like the `InputEdit` of tree-sitter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    /// Where the edit starts, in both the old and the new text.
+    pub start: usize,
+    /// Where the replaced text ended in the old text.
+    pub old_end: usize,
+    /// Where the inserted text ends in the new text.
+    pub new_end: usize,
+}
+
+/// A styled range of the text of a [`TextEditor`]. The properties which aren't set keep the
+/// style of the editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HighlightSpan {
+    pub range: Range<usize>,
+    pub color: Option<Color>,
+    pub weight: Option<Weight>,
+    pub font_style: Option<FontStyle>,
+}
+
+impl HighlightSpan {
+    pub fn new(range: Range<usize>) -> Self {
+        Self {
+            range,
+            color: None,
+            weight: None,
+            font_style: None,
+        }
+    }
+
+    pub fn color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn weight(mut self, weight: Weight) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    pub fn font_style(mut self, font_style: FontStyle) -> Self {
+        self.font_style = Some(font_style);
+        self
+    }
+
+    fn apply<'a>(&self, mut attrs: Attrs<'a>) -> Attrs<'a> {
+        if let Some(color) = self.color {
+            attrs = attrs.color(color);
+        }
+        if let Some(weight) = self.weight {
+            attrs = attrs.weight(weight);
+        }
+        if let Some(font_style) = self.font_style {
+            attrs = attrs.style(font_style);
+        }
+        attrs
+    }
+}
+
+/// Highlights the text of a [`TextEditor`], such as with tree-sitter or syntect.
+///
+/// The highlighter is told about every edit, so it can parse the text incrementally, and is
+/// only asked for the spans of the lines it reports as changed and the lines which were
+/// edited.
+/// ```rust
+/// # use std::ops::Range;
+/// # use floem::peniko::Color;
+/// # use floem::views::{HighlightSpan, Highlighter, TextEdit};
+/// /// Highlights the digits of the text.
+/// struct Digits;
+///
+/// impl Highlighter for Digits {
+///     fn update(&mut self, _text: &str, edit: TextEdit) -> Range<usize> {
+///         edit.start..edit.new_end
+///     }
+///
+///     fn spans(&self, text: &str, range: Range<usize>) -> Vec<HighlightSpan> {
+///         text[range.clone()]
+///             .char_indices()
+///             .filter(|(_, c)| c.is_ascii_digit())
+///             .map(|(i, c)| {
+///                 let start = range.start + i;
+///                 HighlightSpan::new(start..start + c.len_utf8()).color(Color::BLUE)
+///             })
+///             .collect()
+///     }
+/// }
+/// ```
+pub trait Highlighter {
+    /// Updates the highlighter after `edit` turned the text into `text`. Returns the range of
+    /// `text` whose spans changed beyond the edited lines, like the rest of a string whose
+    /// opening quote was typed, or an empty range.
+    fn update(&mut self, text: &str, edit: TextEdit) -> Range<usize>;
+
+    /// The spans in `range` of `text`, with offsets in `text`. Spans may extend past the range.
+    fn spans(&self, text: &str, range: Range<usize>) -> Vec<HighlightSpan>;
+}
+
+/// A marker shown in the gutter of a [`TextEditor`], next to the line at `line`, counted
+/// from 0. See [`TextEditor::gutter_markers`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GutterMarker {
+    pub line: usize,
+    pub color: Color,
+}
+
+/// A multi-line text editor. See [`text_editor`].
+pub struct TextEditor {
+    data: ViewData,
+    text: RwSignal<String>,
+    undo: UndoStack,
+    read_only: bool,
+    /// The text the lines are laid out for.
+    content: String,
+    lines: Vec<Line>,
+    /// The offset of the caret in the text.
+    cursor: usize,
+    /// The other end of the selection, which is at the caret when nothing is selected.
+    anchor: usize,
+    /// The horizontal position the caret keeps while it's moved up and down.
+    preferred_x: Option<f64>,
+    /// The width lines are wrapped at, which is 0 until the editor is laid out.
+    wrap_width: f32,
+    /// The height of a row of text, which empty lines have.
+    row_height: f64,
+    text_node: Option<Node>,
+    /// Where the text starts in the editor, after the padding.
+    text_origin: Point,
+    /// The visible part of the editor.
+    viewport: Rect,
+    font: FontProps,
+    style: EditorStyle,
+    highlighter: Option<Box<dyn Highlighter>>,
+    line_numbers: bool,
+    /// The gutter markers, which are `None` until markers are set.
+    markers: Option<Vec<GutterMarker>>,
+    on_gutter_click: Option<Box<dyn Fn(usize)>>,
+    /// The width of the gutter, which is 0 without line numbers and markers.
+    gutter_width: f64,
+    /// The width of a digit of the line numbers.
+    digit_width: f64,
+    find: Option<FindState>,
+    /// Whether an input method is composing text.
+    composing: bool,
+    last_cursor_action_on: Instant,
+}
+
+/// A multi-line editor for `text`, which is updated as it's edited and can be changed from
+/// outside of the editor.
+///
+/// Lines are wrapped at the width of the editor, so it should be given a width, like with
+/// `width_full`. Only the lines which are scrolled into view are painted, so it's meant to be
+/// put in a [`scroll`](crate::views::scroll), which follows the caret.
+///
+/// The editor supports moving the caret by character, word, line and page, selecting with the
+/// keyboard and the pointer, the clipboard, and undo and redo with the [`UndoStack`] returned by
+/// [`TextEditor::undo_stack`]. Consecutive typing is undone in one step.
+///
+/// Syntax highlighting is added with [`TextEditor::highlighter`], and a gutter with
+/// [`TextEditor::line_numbers`] and [`TextEditor::gutter_markers`]. It's searched with a
+/// [`find_bar`](crate::widgets::find_bar) connected with [`TextEditor::find`].
+///
+/// The editor has the [`TextEditorClass`]. Its text is painted with the [`TextColor`], the caret
+/// with the [`CursorColor`] and the selection with the [`SelectionColor`].
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::views::{scroll, text_editor, Decorators};
+/// let text = create_rw_signal(String::from("Hello\nworld"));
+/// scroll(text_editor(text).style(|s| s.width_full()));
+/// ```
+pub fn text_editor(text: RwSignal<String>) -> TextEditor {
+    let id = Id::next();
+    create_effect(move |_| {
+        text.track();
+        id.update_state(EditorUpdate::TextChanged);
+    });
+    let undo = UndoStack::new().coalesce(UNDO_COALESCE_WINDOW, UNDO_COALESCE_EDITS);
+    undo.track(text);
+
+    TextEditor {
+        data: ViewData::new(id),
+        text,
+        undo,
+        read_only: false,
+        content: String::new(),
+        lines: Vec::new(),
+        cursor: 0,
+        anchor: 0,
+        preferred_x: None,
+        wrap_width: 0.0,
+        row_height: 0.0,
+        text_node: None,
+        text_origin: Point::ZERO,
+        viewport: Rect::ZERO,
+        font: FontProps::default(),
+        style: Default::default(),
+        highlighter: None,
+        line_numbers: false,
+        markers: None,
+        on_gutter_click: None,
+        gutter_width: 0.0,
+        digit_width: 0.0,
+        find: None,
+        composing: false,
+        last_cursor_action_on: Instant::now(),
+    }
+    .class(TextEditorClass)
+    .keyboard_navigatable()
+    .on_event_cont(EventListener::FocusGained, |_| set_ime_allowed(true))
+    .on_event_cont(EventListener::FocusLost, |_| set_ime_allowed(false))
+}
+
+impl TextEditor {
+    /// Prevents the text from being edited while `read_only` returns `true`. It can still be
+    /// selected and copied.
+    pub fn read_only(self, read_only: impl Fn() -> bool + 'static) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            id.update_state(EditorUpdate::ReadOnly(read_only()));
+        });
+        self
+    }
+
+    /// The undo history of the text, for binding undo and redo to menu items.
+    pub fn undo_stack(&self) -> UndoStack {
+        self.undo.clone()
+    }
+
+    /// Highlights the text with `highlighter`, which is updated as the text is edited.
+    pub fn highlighter(mut self, highlighter: impl Highlighter + 'static) -> Self {
+        self.highlighter = Some(Box::new(highlighter));
+        self
+    }
+
+    /// Shows the number of each line in a gutter, in the [`LineNumberColor`].
+    pub fn line_numbers(mut self) -> Self {
+        self.line_numbers = true;
+        self
+    }
+
+    /// Shows the markers returned by `markers` in a gutter, as dots next to their lines, like
+    /// breakpoints or diagnostics.
+    pub fn gutter_markers(self, markers: impl Fn() -> Vec<GutterMarker> + 'static) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            id.update_state(EditorUpdate::Markers(markers()));
+        });
+        self
+    }
+
+    /// Calls `on_click` with the index of the line, counted from 0, when the gutter is clicked
+    /// next to it.
+    pub fn on_gutter_click(mut self, on_click: impl Fn(usize) + 'static) -> Self {
+        self.on_gutter_click = Some(Box::new(on_click));
+        self
+    }
+
+    /// Searches the text for the query of `state`, which is usually edited with a
+    /// [`find_bar`](crate::widgets::find_bar). The matches are highlighted with the
+    /// [`FindMatchColor`], and the selected match with the [`CurrentMatchColor`].
+    ///
+    /// In the editor, the primary modifier with F shows the find bar, and F3 selects the next
+    /// match, or the previous one with Shift.
+    pub fn find(mut self, state: FindState) -> Self {
+        let id = self.id();
+        let text = self.text;
+        state.target.set(Some(id));
+        create_effect(move |_| {
+            let matches = match state.pattern() {
+                Some(pattern) => text.with(|text| find_matches(text, &pattern)),
+                None => Vec::new(),
+            };
+            state.matches.set(matches);
+        });
+        create_effect(move |_| {
+            state.matches.track();
+            id.update_state(EditorUpdate::MatchesChanged);
+        });
+        self.find = Some(state);
+        self
+    }
+
+    /// Sets the current match of the search to the one which is selected.
+    fn update_current_match(&self) {
+        let Some(find) = self.find else {
+            return;
+        };
+        let selection = self.selection();
+        let current = find
+            .matches
+            .with_untracked(|matches| matches.iter().position(|found| *found == selection));
+        if find.current.get_untracked() != current {
+            find.current.set(current);
+        }
+    }
+
+    fn find_action(&mut self, find: FindState, action: FindAction) {
+        let Some(pattern) = find.pattern() else {
+            return;
+        };
+        let selection = self.selection();
+        let matches = match action {
+            FindAction::Next | FindAction::Previous => find.matches.get_untracked(),
+            FindAction::Replace => {
+                if find
+                    .matches
+                    .with_untracked(|matches| matches.contains(&selection))
+                {
+                    let replacement = find.replacement_for(&pattern, &self.content[selection]);
+                    self.edit("Replace", &replacement);
+                }
+                // The matches after the replaced one moved, so they're searched for again
+                find_matches(&self.content, &pattern)
+            }
+            FindAction::ReplaceAll => {
+                let matches = find.matches.get_untracked();
+                if self.read_only || matches.is_empty() {
+                    return;
+                }
+                let mut content = self.content.clone();
+                for found in matches.into_iter().rev() {
+                    let replacement = find.replacement_for(&pattern, &content[found.clone()]);
+                    content.replace_range(found, &replacement);
+                }
+                let edit = self.set_content(content.clone());
+                self.cursor = edit.new_end;
+                self.anchor = edit.new_end;
+                self.preferred_x = None;
+                let signal = self.text;
+                self.undo.transaction("Replace All", || signal.set(content));
+                return;
+            }
+        };
+
+        let selection = self.selection();
+        let found = if matches!(action, FindAction::Previous) {
+            let before = matches
+                .iter()
+                .rev()
+                .find(|found| found.end <= selection.start);
+            before.or(matches.last())
+        } else {
+            let after = matches.iter().find(|found| found.start >= selection.end);
+            after.or(matches.first())
+        };
+        if let Some(found) = found {
+            self.anchor = found.start;
+            self.cursor = found.end;
+            self.preferred_x = None;
+        }
+    }
+
+    /// The width of the gutter for the current number of lines. The gutter has a column for
+    /// the markers, which is as wide as a row is high, and a column for the line numbers.
+    fn compute_gutter_width(&self) -> f64 {
+        let markers = if self.markers.is_some() {
+            self.row_height
+        } else {
+            0.0
+        };
+        let numbers = if self.line_numbers {
+            self.lines.len().max(1).to_string().len() as f64 * self.digit_width
+        } else {
+            0.0
+        };
+        if markers + numbers > 0.0 {
+            markers + numbers + GUTTER_PADDING
+        } else {
+            0.0
+        }
+    }
+
+    fn paint_gutter(&self, cx: &mut PaintCx, lines: impl Iterator<Item = &Line>) {
+        let left = self.text_origin.x - self.gutter_width;
+        let numbers_right = left + self.gutter_width - GUTTER_PADDING;
+        let font_family = self.font_family();
+        let font_family = font_family.as_deref();
+        for line in lines {
+            let index = self.line_at(line.start);
+            let top = self.text_origin.y + line.top;
+            if let Some(marker) = self
+                .markers
+                .iter()
+                .flatten()
+                .find(|marker| marker.line == index)
+            {
+                let center = Point::new(left + self.row_height / 2.0, top + self.row_height / 2.0);
+                cx.fill(
+                    &Circle::new(center, self.row_height * 0.3),
+                    marker.color,
+                    0.0,
+                );
+            }
+            if self.line_numbers {
+                let attrs = self
+                    .attrs(font_family)
+                    .color(self.style.line_number_color());
+                let mut number = TextLayout::new();
+                number.set_text(&(index + 1).to_string(), AttrsList::new(attrs));
+                cx.draw_text(
+                    &number,
+                    Point::new(numbers_right - number.size().width, top),
+                );
+            }
+        }
+    }
+
+    fn attrs<'a>(&self, font_family: Option<&'a [FamilyOwned]>) -> Attrs<'a> {
+        let mut attrs = Attrs::new()
+            .color(self.style.color().unwrap_or(Color::BLACK))
+            .font_size(self.font.size().unwrap_or(DEFAULT_FONT_SIZE));
+        if let Some(font_style) = self.font.style() {
+            attrs = attrs.style(font_style);
+        }
+        if let Some(font_family) = font_family {
+            attrs = attrs.family(font_family);
+        }
+        if let Some(font_weight) = self.font.weight() {
+            attrs = attrs.weight(font_weight);
+        }
+        attrs
+    }
+
+    fn font_family(&self) -> Option<Vec<FamilyOwned>> {
+        self.font.families()
+    }
+
+    /// Lays out `line` with the spans of the highlighter.
+    fn layout_line(&self, line: &Line) -> TextLayout {
+        let font_family = self.font_family();
+        let font_family = font_family.as_deref();
+        let mut attrs_list = AttrsList::new(self.attrs(font_family));
+        if let Some(highlighter) = &self.highlighter {
+            for span in highlighter.spans(&self.content, line.start..line.end()) {
+                let start = span.range.start.clamp(line.start, line.end()) - line.start;
+                let end = span.range.end.clamp(line.start, line.end()) - line.start;
+                if start < end {
+                    attrs_list.add_span(start..end, span.apply(self.attrs(font_family)));
+                }
+            }
+        }
+
+        let mut layout = TextLayout::new();
+        layout.set_text(&line.text, attrs_list);
+        if self.wrap_width > 0.0 {
+            layout.set_size(self.wrap_width, f32::MAX);
+        }
+        layout
+    }
+
+    /// Lays out `content`, reusing the lines which didn't change before and after the edited
+    /// lines, unless the highlighting changed. Returns the edit from the previous content.
+    fn set_content(&mut self, content: String) -> TextEdit {
+        let edit = TextEdit::between(&self.content, &content);
+        let new_lines: Vec<&str> = content.split('\n').collect();
+        let mut lines = std::mem::take(&mut self.lines);
+        let prefix = lines
+            .iter()
+            .zip(&new_lines)
+            .take_while(|(line, text)| line.text == **text)
+            .count();
+        let suffix = lines[prefix..]
+            .iter()
+            .rev()
+            .zip(new_lines[prefix..].iter().rev())
+            .take_while(|(line, text)| line.text == **text)
+            .count();
+
+        let kept = lines.split_off(lines.len() - suffix);
+        lines.truncate(prefix);
+        let changed = prefix..new_lines.len() - suffix;
+        for text in &new_lines[changed.clone()] {
+            lines.push(Line {
+                text: text.to_string(),
+                start: 0,
+                top: 0.0,
+                height: 0.0,
+                layout: TextLayout::new(),
+            });
+        }
+        lines.extend(kept);
+        self.lines = lines;
+        self.content = content;
+        self.position_lines();
+
+        let highlighted = match &mut self.highlighter {
+            Some(highlighter) => highlighter.update(&self.content, edit),
+            None => 0..0,
+        };
+        for index in 0..self.lines.len() {
+            let line = &self.lines[index];
+            let rehighlight = !highlighted.is_empty()
+                && line.start < highlighted.end
+                && line.end() >= highlighted.start;
+            if changed.contains(&index) || rehighlight {
+                let layout = self.layout_line(line);
+                self.lines[index].layout = layout;
+            }
+        }
+        self.position_lines();
+        edit
+    }
+
+    /// Lays out every line again, after the font changed.
+    fn relayout(&mut self) {
+        self.lines.clear();
+        let content = self.text.get_untracked();
+        self.set_content(content);
+    }
+
+    fn position_lines(&mut self) {
+        let (mut start, mut top) = (0, 0.0);
+        for line in &mut self.lines {
+            line.start = start;
+            line.top = top;
+            line.height = if line.text.is_empty() {
+                self.row_height
+            } else {
+                line.layout.size().height
+            };
+            start = line.end() + 1;
+            top += line.height;
+        }
+    }
+
+    fn text_height(&self) -> f64 {
+        self.lines
+            .last()
+            .map_or(self.row_height, |line| line.top + line.height)
+    }
+
+    /// Updates the lines after the text was changed outside of the editor, such as by undo,
+    /// and moves the caret to the end of the change.
+    fn sync_text(&mut self) {
+        let text = self
+            .text
+            .with_untracked(|text| (*text != self.content).then(|| text.clone()));
+        if let Some(text) = text {
+            let edit = self.set_content(text);
+            self.cursor = edit.new_end;
+            self.anchor = edit.new_end;
+            self.preferred_x = None;
+        }
+    }
+
+    fn selection(&self) -> Range<usize> {
+        self.cursor.min(self.anchor)..self.cursor.max(self.anchor)
+    }
+
+    /// The index of the line containing `offset`.
+    fn line_at(&self, offset: usize) -> usize {
+        self.lines
+            .partition_point(|line| line.start <= offset)
+            .saturating_sub(1)
+    }
+
+    /// The horizontal position of `index` in `line`, with the top and bottom of its row,
+    /// relative to the line.
+    fn position_in_line(&self, line: &Line, index: usize) -> (f64, f64, f64) {
+        if line.text.is_empty() {
+            return (0.0, 0.0, self.row_height);
+        }
+        let hit = line.layout.hit_position(index);
+        (
+            hit.point.x,
+            hit.point.y - hit.glyph_ascent,
+            hit.point.y + hit.glyph_descent,
+        )
+    }
+
+    /// The offset in `line` closest to `point`, relative to the line.
+    fn index_in_line(&self, line: &Line, point: Point) -> usize {
+        if line.text.is_empty() {
+            return 0;
+        }
+        let mut index = line.layout.hit_point(point).index.min(line.text.len());
+        while !line.text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
+    /// The rect of the caret at `offset`, relative to the text.
+    fn caret_rect(&self, offset: usize) -> Rect {
+        let Some(line) = self.lines.get(self.line_at(offset)) else {
+            return Rect::new(0.0, 0.0, 1.0, self.row_height);
+        };
+        let (x, top, bottom) = self.position_in_line(line, offset - line.start);
+        Rect::new(x, line.top + top, x + 1.0, line.top + bottom)
+    }
+
+    /// The offset closest to `point`, relative to the text.
+    fn offset_at(&self, point: Point) -> usize {
+        if self.lines.is_empty() {
+            return 0;
+        }
+        let y = point.y.max(0.0);
+        let line = &self.lines[self
+            .lines
+            .partition_point(|line| line.top <= y)
+            .saturating_sub(1)];
+        if y >= line.top + line.height {
+            return self.content.len();
+        }
+        line.start + self.index_in_line(line, Point::new(point.x, y - line.top))
+    }
+
+    /// The offset a row above or below the caret, at the horizontal position the caret had
+    /// before it was moved vertically.
+    fn vertical_offset(&mut self, down: bool) -> usize {
+        let line_index = self.line_at(self.cursor);
+        let line = &self.lines[line_index];
+        let (x, top, bottom) = self.position_in_line(line, self.cursor - line.start);
+        let x = *self.preferred_x.get_or_insert(x);
+        let target = if down {
+            Point::new(x, line.top + bottom + 1.0)
+        } else {
+            Point::new(x, line.top + top - 1.0)
+        };
+        if target.y < 0.0 {
+            0
+        } else {
+            self.offset_at(target)
+        }
+    }
+
+    fn prev_grapheme(&self, offset: usize) -> usize {
+        self.content[..offset]
+            .graphemes(true)
+            .next_back()
+            .map_or(offset, |grapheme| offset - grapheme.len())
+    }
+
+    fn next_grapheme(&self, offset: usize) -> usize {
+        self.content[offset..]
+            .graphemes(true)
+            .next()
+            .map_or(offset, |grapheme| offset + grapheme.len())
+    }
+
+    fn prev_word(&self, offset: usize) -> usize {
+        self.content
+            .unicode_word_indices()
+            .map(|(start, _)| start)
+            .take_while(|start| *start < offset)
+            .last()
+            .unwrap_or(0)
+    }
+
+    fn next_word(&self, offset: usize) -> usize {
+        self.content
+            .unicode_word_indices()
+            .map(|(start, word)| start + word.len())
+            .find(|end| *end > offset)
+            .unwrap_or(self.content.len())
+    }
+
+    /// The word, or the run of whitespace or punctuation, at `offset`.
+    fn word_at(&self, offset: usize) -> Range<usize> {
+        self.content
+            .split_word_bound_indices()
+            .map(|(start, word)| start..start + word.len())
+            .find(|range| range.contains(&offset))
+            .unwrap_or(offset..offset)
+    }
+
+    fn move_caret(&mut self, offset: usize, extend: bool) {
+        self.cursor = offset;
+        if !extend {
+            self.anchor = offset;
+        }
+        self.undo.break_coalescing();
+    }
+
+    /// Replaces the selection with `text`, as an undo step named `label`.
+    fn edit(&mut self, label: &str, text: &str) {
+        if self.read_only {
+            return;
+        }
+        let selection = self.selection();
+        let mut content = self.content.clone();
+        content.replace_range(selection.clone(), text);
+        self.cursor = selection.start + text.len();
+        self.anchor = self.cursor;
+        self.preferred_x = None;
+        self.set_content(content.clone());
+        let signal = self.text;
+        self.undo.transaction(label, || signal.set(content));
+    }
+
+    /// Deletes the selection, or the text between the caret and `offset` when nothing is
+    /// selected.
+    fn delete_to(&mut self, offset: usize) {
+        if self.read_only {
+            return;
+        }
+        if self.cursor == self.anchor {
+            self.anchor = offset;
+        }
+        if self.cursor != self.anchor {
+            self.edit("Delete", "");
+        }
+    }
+
+    fn copy(&self) {
+        let selection = self.selection();
+        if !selection.is_empty() {
+            let _ = Clipboard::set_contents(self.content[selection].to_string());
+        }
+    }
+
+    fn paste(&mut self) {
+        if let Ok(text) = Clipboard::get_contents() {
+            if !text.is_empty() {
+                self.edit("Paste", &text.replace("\r\n", "\n"));
+            }
+        }
+    }
+
+    /// Handles a shortcut with the primary modifier, Cmd on macOS and Ctrl elsewhere.
+    fn shortcut(&mut self, character: &str, shift: bool) -> bool {
+        match character {
+            "a" => {
+                self.anchor = 0;
+                self.cursor = self.content.len();
+            }
+            "c" => self.copy(),
+            "x" => {
+                self.copy();
+                if !self.selection().is_empty() {
+                    self.edit("Cut", "");
+                }
+            }
+            "v" => self.paste(),
+            "f" if self.find.is_some() => self.find.unwrap().visible.set(true),
+            "z" if shift => {
+                self.undo.redo();
+            }
+            "z" => {
+                self.undo.undo();
+            }
+            #[cfg(not(target_os = "macos"))]
+            "y" => {
+                self.undo.redo();
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    fn key_down(&mut self, event: &KeyEvent) -> bool {
+        let modifiers = event.modifiers;
+        let shift = modifiers.shift_key();
+        let primary = is_primary(modifiers);
+        let word = is_word_motion(modifiers);
+        let line = &self.lines[self.line_at(self.cursor)];
+        let (line_start, line_end) = (line.start, line.end());
+        let selection = self.selection();
+
+        match &event.key.logical_key {
+            Key::Character(character) if primary => {
+                return self.shortcut(&character.to_lowercase(), shift)
+            }
+            Key::Character(_) if modifiers.control_key() || modifiers.super_key() => return false,
+            Key::Character(character) => self.edit(TYPING, character),
+            Key::Named(NamedKey::Space) => self.edit(TYPING, " "),
+            Key::Named(NamedKey::Enter) => self.edit(TYPING, "\n"),
+            Key::Named(NamedKey::Backspace) => {
+                let offset = if word {
+                    self.prev_word(self.cursor)
+                } else {
+                    self.prev_grapheme(self.cursor)
+                };
+                self.delete_to(offset);
+            }
+            Key::Named(NamedKey::Delete) => {
+                let offset = if word {
+                    self.next_word(self.cursor)
+                } else {
+                    self.next_grapheme(self.cursor)
+                };
+                self.delete_to(offset);
+            }
+            Key::Named(NamedKey::ArrowLeft) => {
+                let offset = if cfg!(target_os = "macos") && modifiers.super_key() {
+                    line_start
+                } else if word {
+                    self.prev_word(self.cursor)
+                } else if !shift && !selection.is_empty() {
+                    selection.start
+                } else {
+                    self.prev_grapheme(self.cursor)
+                };
+                self.move_caret(offset, shift);
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                let offset = if cfg!(target_os = "macos") && modifiers.super_key() {
+                    line_end
+                } else if word {
+                    self.next_word(self.cursor)
+                } else if !shift && !selection.is_empty() {
+                    selection.end
+                } else {
+                    self.next_grapheme(self.cursor)
+                };
+                self.move_caret(offset, shift);
+            }
+            Key::Named(NamedKey::ArrowUp) | Key::Named(NamedKey::ArrowDown) => {
+                let down = event.key.logical_key == Key::Named(NamedKey::ArrowDown);
+                let offset = match (primary, down) {
+                    (true, false) => 0,
+                    (true, true) => self.content.len(),
+                    (false, down) => self.vertical_offset(down),
+                };
+                let preferred_x = self.preferred_x;
+                self.move_caret(offset, shift);
+                self.preferred_x = preferred_x;
+                return true;
+            }
+            Key::Named(NamedKey::Home) => {
+                let offset = if primary { 0 } else { line_start };
+                self.move_caret(offset, shift);
+            }
+            Key::Named(NamedKey::End) => {
+                let offset = if primary {
+                    self.content.len()
+                } else {
+                    line_end
+                };
+                self.move_caret(offset, shift);
+            }
+            Key::Named(NamedKey::F3) if self.find.is_some() => {
+                let action = if shift {
+                    FindAction::Previous
+                } else {
+                    FindAction::Next
+                };
+                self.find_action(self.find.unwrap(), action);
+            }
+            Key::Named(NamedKey::PageUp) | Key::Named(NamedKey::PageDown) => {
+                let caret = self.caret_rect(self.cursor);
+                let x = *self.preferred_x.get_or_insert(caret.x0);
+                let page = self.viewport.height().max(self.row_height);
+                let offset = if event.key.logical_key == Key::Named(NamedKey::PageUp) {
+                    self.offset_at(Point::new(x, caret.y0 - page))
+                } else {
+                    self.offset_at(Point::new(x, caret.y0 + page))
+                };
+                let preferred_x = self.preferred_x;
+                self.move_caret(offset, shift);
+                self.preferred_x = preferred_x;
+                return true;
+            }
+            _ => return false,
+        }
+        self.preferred_x = None;
+        true
+    }
+
+    /// The rects covering the text from `start` to `end` in `line`, relative to the line.
+    /// When the selection continues on the next line, it covers the rest of the last row.
+    fn selection_rects(&self, line: &Line, start: usize, end: usize, to_edge: bool) -> Vec<Rect> {
+        let right = if self.wrap_width > 0.0 {
+            self.wrap_width as f64
+        } else {
+            line.layout.size().width
+        };
+        let (x0, top0, bottom0) = self.position_in_line(line, start);
+        let (x1, top1, bottom1) = self.position_in_line(line, end);
+        let x1 = if to_edge { right.max(x1) } else { x1 };
+        if top0 == top1 {
+            return vec![Rect::new(x0, top0, x1, bottom0)];
+        }
+        let mut rects = vec![Rect::new(x0, top0, right, bottom0)];
+        if top1 > bottom0 {
+            rects.push(Rect::new(0.0, bottom0, right, top1));
+        }
+        rects.push(Rect::new(0.0, top1, x1, bottom1));
+        rects
+    }
+}
+
+impl TextEdit {
+    /// The smallest edit which turns `old` into `new`.
+    pub fn between(old: &str, new: &str) -> Self {
+        let prefix = old
+            .char_indices()
+            .zip(new.chars())
+            .find(|((_, a), b)| a != b)
+            .map_or(old.len().min(new.len()), |((index, _), _)| index);
+        let suffix: usize = old[prefix..]
+            .chars()
+            .rev()
+            .zip(new[prefix..].chars().rev())
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len_utf8())
+            .sum();
+        Self {
+            start: prefix,
+            old_end: old.len() - suffix,
+            new_end: new.len() - suffix,
+        }
+    }
+}
+
+/// Whether the modifiers are those of shortcuts, Cmd on macOS and Ctrl elsewhere.
+fn is_primary(modifiers: ModifiersState) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.super_key()
+    } else {
+        modifiers.control_key()
+    }
+}
+
+/// Whether the modifiers move the caret by words, Alt on macOS and Ctrl elsewhere.
+fn is_word_motion(modifiers: ModifiersState) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.alt_key()
+    } else {
+        modifiers.control_key()
+    }
+}
+
+impl View for TextEditor {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "TextEditor".into()
+    }
+
+    fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn Any>) {
+        let state = match state.downcast::<FindAction>() {
+            Ok(action) => {
+                if let Some(find) = self.find {
+                    self.find_action(find, *action);
+                    self.update_current_match();
+                    self.last_cursor_action_on = Instant::now();
+                    cx.request_layout(self.id());
+                    self.id().update_state_deferred(EditorUpdate::ScrollToCaret);
+                }
+                return;
+            }
+            Err(state) => state,
+        };
+        if let Ok(update) = state.downcast::<EditorUpdate>() {
+            match *update {
+                EditorUpdate::TextChanged => {
+                    self.sync_text();
+                    cx.request_layout(self.id());
+                }
+                EditorUpdate::ReadOnly(read_only) => self.read_only = read_only,
+                EditorUpdate::ScrollToCaret => {
+                    let caret = self.caret_rect(self.cursor) + self.text_origin.to_vec2();
+                    self.id().scroll_to(Some(caret));
+                }
+                EditorUpdate::Markers(markers) => {
+                    self.markers = Some(markers);
+                    cx.request_layout(self.id());
+                }
+                EditorUpdate::MatchesChanged => {
+                    self.update_current_match();
+                    cx.app_state_mut().request_paint(self.id());
+                }
+            }
+        }
+    }
+
+    fn event(
+        &mut self,
+        cx: &mut EventCx,
+        _id_path: Option<&[Id]>,
+        event: Event,
+    ) -> EventPropagation {
+        let origin = self.text_origin.to_vec2();
+        let handled = match &event {
+            Event::PointerDown(pointer)
+                if pointer.button.is_primary()
+                    && self.gutter_width > 0.0
+                    && pointer.pos.x < self.text_origin.x =>
+            {
+                let index = self.line_at(self.offset_at(pointer.pos - origin));
+                if let Some(on_gutter_click) = &self.on_gutter_click {
+                    on_gutter_click(index);
+                }
+                false
+            }
+            Event::PointerDown(pointer) if pointer.button.is_primary() => {
+                cx.update_active(self.id());
+                let offset = self.offset_at(pointer.pos - origin);
+                match pointer.count {
+                    2 => {
+                        let word = self.word_at(offset);
+                        self.anchor = word.start;
+                        self.cursor = word.end;
+                    }
+                    3 => {
+                        let line = &self.lines[self.line_at(offset)];
+                        self.anchor = line.start;
+                        self.cursor = (line.end() + 1).min(self.content.len());
+                    }
+                    _ => self.move_caret(offset, pointer.modifiers.shift_key()),
+                }
+                self.preferred_x = None;
+                true
+            }
+            Event::PointerMove(pointer) if cx.is_active(self.id()) => {
+                self.cursor = self.offset_at(pointer.pos - origin);
+                true
+            }
+            Event::KeyDown(key) => self.key_down(key),
+            Event::ImePreedit { text, .. } => {
+                self.composing = !text.is_empty();
+                true
+            }
+            Event::ImeCommit(text) => {
+                self.composing = false;
+                self.edit(TYPING, text);
+                true
+            }
+            _ => false,
+        };
+
+        if !handled {
+            return EventPropagation::Continue;
+        }
+        self.last_cursor_action_on = Instant::now();
+        self.update_current_match();
+        cx.app_state_mut().request_layout(self.id());
+        self.id().update_state_deferred(EditorUpdate::ScrollToCaret);
+        // Pointer events continue, so clicking focuses the editor
+        if matches!(event, Event::KeyDown(_)) {
+            EventPropagation::Stop
+        } else {
+            EventPropagation::Continue
+        }
+    }
+
+    fn style(&mut self, cx: &mut StyleCx<'_>) {
+        let font_changed = self.font.read(cx);
+        if self.style.read(cx) || font_changed || self.lines.is_empty() {
+            let font_family = self.font_family();
+            let mut sample = TextLayout::new();
+            sample.set_text("W", AttrsList::new(self.attrs(font_family.as_deref())));
+            self.row_height = sample.size().height;
+            sample.set_text("0", AttrsList::new(self.attrs(font_family.as_deref())));
+            self.digit_width = sample.size().width;
+            self.relayout();
+            cx.app_state_mut().request_layout(self.id());
+        }
+    }
+
+    fn layout(&mut self, cx: &mut LayoutCx) -> Node {
+        cx.layout_node(self.id(), true, |cx| {
+            if self.text_node.is_none() {
+                self.text_node = Some(
+                    cx.app_state_mut()
+                        .taffy
+                        .new_leaf(taffy::style::Style::DEFAULT)
+                        .unwrap(),
+                );
+            }
+            let text_node = self.text_node.unwrap();
+            let style = Style::new()
+                .width_full()
+                .height(self.text_height())
+                .to_taffy_style();
+            let _ = cx.app_state_mut().taffy.set_style(text_node, style);
+            vec![text_node]
+        })
+    }
+
+    fn compute_layout(&mut self, cx: &mut ComputeLayoutCx) -> Option<Rect> {
+        self.viewport = cx.current_viewport();
+        let text_layout = *cx.app_state.taffy.layout(self.text_node.unwrap()).unwrap();
+        // The gutter is at the start of the text node, and the text after it
+        self.gutter_width = self.compute_gutter_width();
+        self.text_origin = Point::new(
+            text_layout.location.x as f64 + self.gutter_width,
+            text_layout.location.y as f64,
+        );
+        let width = (text_layout.size.width as f64 - self.gutter_width) as f32;
+        if width > 0.0 && width != self.wrap_width {
+            self.wrap_width = width;
+            for line in &mut self.lines {
+                line.layout.set_size(width, f32::MAX);
+            }
+            self.position_lines();
+            cx.app_state_mut().request_layout(self.id());
+        }
+        None
+    }
+
+    fn paint(&mut self, cx: &mut PaintCx) {
+        let origin = self.text_origin.to_vec2();
+        let viewport = self.viewport;
+        // Only the lines which are scrolled into view are painted
+        let visible = self.lines.iter().filter(|line| {
+            origin.y + line.top + line.height >= viewport.y0 && origin.y + line.top <= viewport.y1
+        });
+
+        let selection = self.selection();
+        let selection_color = self.style.selection_color();
+        for line in visible.clone() {
+            if selection.is_empty() || selection.start > line.end() || selection.end < line.start {
+                continue;
+            }
+            let start = selection.start.max(line.start) - line.start;
+            let end = selection.end.min(line.end()) - line.start;
+            let to_edge = selection.end > line.end();
+            let offset = origin + Vec2::new(0.0, line.top);
+            for rect in self.selection_rects(line, start, end, to_edge) {
+                cx.fill(&(rect + offset), selection_color, 0.0);
+            }
+        }
+
+        if let Some(find) = self.find {
+            let current = find.current.get_untracked();
+            find.matches.with_untracked(|matches| {
+                for line in visible.clone() {
+                    let first = matches.partition_point(|found| found.end < line.start);
+                    for (index, found) in matches.iter().enumerate().skip(first) {
+                        if found.start > line.end() {
+                            break;
+                        }
+                        let start = found.start.max(line.start) - line.start;
+                        let end = found.end.min(line.end()) - line.start;
+                        let color = if current == Some(index) {
+                            self.style.current_match_color()
+                        } else {
+                            self.style.find_match_color()
+                        };
+                        let offset = origin + Vec2::new(0.0, line.top);
+                        for rect in self.selection_rects(line, start, end, found.end > line.end()) {
+                            cx.fill(&(rect + offset), color, 0.0);
+                        }
+                    }
+                }
+            });
+        }
+
+        for line in visible.clone() {
+            cx.draw_text(&line.layout, (origin + Vec2::new(0.0, line.top)).to_point());
+        }
+        if self.gutter_width > 0.0 {
+            self.paint_gutter(cx, visible);
+        }
+
+        let is_focused = cx.app_state.is_focused(&self.id());
+        let blink_on = (self.last_cursor_action_on.elapsed().as_millis()
+            / CURSOR_BLINK_INTERVAL_MS as u128)
+            % 2
+            == 0;
+        if is_focused && !self.composing && blink_on {
+            let caret = self.caret_rect(self.cursor) + origin;
+            let color = self.style.cursor_color().unwrap_or(Color::BLACK);
+            cx.fill(&caret, color, 0.0);
+        }
+        if is_focused {
+            let id = self.id();
+            exec_after(
+                Duration::from_millis(CURSOR_BLINK_INTERVAL_MS),
+                Box::new(move |_| {
+                    id.request_paint();
+                }),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use floem_reactive::{create_rw_signal, RwSignal};
+    use kurbo::{Point, Size};
+
+    use super::{text_editor, TextEdit};
+    use crate::{
+        keyboard::{Key, ModifiersState, NamedKey},
+        test_support::HeadlessWindow,
+        views::Decorators,
+        widgets::FindState,
+    };
+
+    const PRIMARY: ModifiersState = if cfg!(target_os = "macos") {
+        ModifiersState::SUPER
+    } else {
+        ModifiersState::CONTROL
+    };
+
+    /// A window with a focused editor of `text`, with the caret at the start.
+    fn editor_window(text: RwSignal<String>, find: Option<FindState>) -> HeadlessWindow {
+        let mut window = HeadlessWindow::new(Size::new(200.0, 100.0), move || {
+            let editor = text_editor(text);
+            let editor = match find {
+                Some(find) => editor.find(find),
+                None => editor,
+            };
+            editor.style(|s| s.size(200.0, 100.0))
+        });
+        window.click(Point::new(100.0, 50.0));
+        window.send_key(Key::Named(NamedKey::Home), PRIMARY);
+        window
+    }
+
+    fn type_text(window: &mut HeadlessWindow, text: &str) {
+        for character in text.chars() {
+            window.send_key(
+                Key::Character(character.to_string().into()),
+                ModifiersState::empty(),
+            );
+        }
+    }
+
+    #[test]
+    fn typing_is_undone_in_one_step() {
+        let text = create_rw_signal(String::new());
+        let mut window = editor_window(text, None);
+        type_text(&mut window, "abc");
+        window.send_key(Key::Named(NamedKey::Backspace), ModifiersState::empty());
+        assert_eq!(text.get_untracked(), "ab");
+
+        let z = || Key::Character("z".into());
+        window.send_key(z(), PRIMARY);
+        assert_eq!(text.get_untracked(), "abc");
+        window.send_key(z(), PRIMARY);
+        assert_eq!(text.get_untracked(), "");
+        window.send_key(z(), PRIMARY | ModifiersState::SHIFT);
+        assert_eq!(text.get_untracked(), "abc");
+    }
+
+    #[test]
+    fn typing_replaces_the_selection() {
+        let text = create_rw_signal("hello world".to_string());
+        let mut window = editor_window(text, None);
+        window.send_key(Key::Named(NamedKey::End), ModifiersState::empty());
+        for _ in 0.."world".len() {
+            window.send_key(Key::Named(NamedKey::ArrowLeft), ModifiersState::SHIFT);
+        }
+        type_text(&mut window, "there");
+        assert_eq!(text.get_untracked(), "hello there");
+
+        window.send_key(Key::Character("a".into()), PRIMARY);
+        window.send_key(Key::Named(NamedKey::Backspace), ModifiersState::empty());
+        assert_eq!(text.get_untracked(), "");
+    }
+
+    #[test]
+    fn find_and_replace() {
+        let text = create_rw_signal("hello world".to_string());
+        let find = FindState::new();
+        let mut window = editor_window(text, Some(find));
+        find.query.set("O".to_string());
+        // The query is searched for once it stops changing
+        assert_eq!(find.match_count(), 0);
+        window.advance(Duration::from_millis(200));
+        assert_eq!(find.match_count(), 2);
+
+        find.next();
+        window.update();
+        assert_eq!(find.current_match(), Some(0));
+
+        find.replacement.set("0".to_string());
+        find.replace();
+        window.update();
+        assert_eq!(text.get_untracked(), "hell0 world");
+        assert_eq!(find.match_count(), 1);
+        assert_eq!(find.current_match(), Some(0));
+
+        find.replace_all();
+        window.update();
+        assert_eq!(text.get_untracked(), "hell0 w0rld");
+        assert_eq!(find.match_count(), 0);
+
+        // Replacing all the matches is a single undo step
+        window.send_key(Key::Character("z".into()), PRIMARY);
+        assert_eq!(text.get_untracked(), "hell0 world");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_replacements_expand_groups() {
+        let text = create_rw_signal("hello world".to_string());
+        let find = FindState::new();
+        let mut window = editor_window(text, Some(find));
+        find.regex.set(true);
+        find.query.set("(l+)".to_string());
+        find.replacement.set("<$1>".to_string());
+        window.advance(Duration::from_millis(200));
+        find.replace_all();
+        window.update();
+        assert_eq!(text.get_untracked(), "he<ll>o wor<l>d");
+    }
+
+    #[test]
+    fn deleting_in_a_read_only_editor_keeps_the_caret() {
+        let text = create_rw_signal("abc".to_string());
+        let read_only = create_rw_signal(true);
+        let mut window = HeadlessWindow::new(Size::new(200.0, 100.0), move || {
+            text_editor(text)
+                .read_only(move || read_only.get())
+                .style(|s| s.size(200.0, 100.0))
+        });
+        window.click(Point::new(100.0, 50.0));
+        window.send_key(Key::Named(NamedKey::End), PRIMARY);
+        window.send_key(Key::Named(NamedKey::Backspace), ModifiersState::empty());
+        window.send_key(Key::Named(NamedKey::Home), PRIMARY);
+        window.send_key(Key::Named(NamedKey::Delete), ModifiersState::empty());
+        assert_eq!(text.get_untracked(), "abc");
+
+        // Nothing was selected, so typing inserts at the caret instead of replacing a character
+        read_only.set(false);
+        window.update();
+        type_text(&mut window, "x");
+        assert_eq!(text.get_untracked(), "xabc");
+    }
+
+    #[test]
+    fn edit_between_texts() {
+        let edit = |old, new| {
+            let edit = TextEdit::between(old, new);
+            (edit.start, edit.old_end, edit.new_end)
+        };
+        assert_eq!(edit("hello world", "hello brave world"), (6, 6, 12));
+        assert_eq!(edit("hello world", "hello"), (5, 11, 5));
+        assert_eq!(edit("abc", "abc"), (3, 3, 3));
+        assert_eq!(edit("", "new"), (0, 0, 3));
+        // A repeated character is matched by the prefix first
+        assert_eq!(edit("aa", "aaa"), (2, 2, 3));
+        // Offsets stay on char boundaries
+        assert_eq!(edit("née", "nüe"), (1, 3, 3));
+    }
+}
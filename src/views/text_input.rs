@@ -1,9 +1,9 @@
-use crate::action::exec_after;
+use crate::action::{exec_after, set_ime_allowed, set_ime_cursor_area};
 use crate::event::EventListener;
 use crate::keyboard::{self, KeyEvent};
 use crate::pointer::{PointerButton, PointerInputEvent};
 use crate::reactive::{create_effect, RwSignal};
-use crate::style::{CursorColor, FontProps, PaddingLeft};
+use crate::style::{CursorColor, DirectionProp, FontProps, PaddingLeft, StyleSelector};
 use crate::style::{FontStyle, FontWeight, TextColor};
 use crate::unit::{PxPct, PxPctAuto};
 use crate::view::ViewData;
@@ -24,8 +24,8 @@ use std::{
     time::{Duration, Instant},
 };
 
-use crate::cosmic_text::{Attrs, AttrsList, FamilyOwned, TextLayout};
-use kurbo::{Point, Rect, Size};
+use crate::cosmic_text::{Attrs, AttrsList, TextLayout};
+use kurbo::{Line, Point, Rect, Size};
 
 use crate::{
     context::{EventCx, UpdateCx},
@@ -33,11 +33,12 @@ use crate::{
     id::Id,
 };
 
-use super::Decorators;
+use super::{label::RTL_MARK, Decorators};
 
 prop_extracter! {
     Extracter {
         color: TextColor,
+        direction: DirectionProp,
     }
 }
 
@@ -93,9 +94,22 @@ pub struct TextInput {
     font: FontProps,
     cursor_width: f64, // TODO: make this configurable
     is_focused: bool,
+    /// The text an input method is composing, which hides the placeholder.
+    preedit: Option<Preedit>,
+    /// The cursor area last given to the input method, in window coordinates.
+    ime_area: Option<Rect>,
     last_cursor_action_on: Instant,
 }
 
+/// Text composed by an input method, which is shown at the cursor until it's committed.
+struct Preedit {
+    /// Where the text is inserted in the buffer.
+    start: usize,
+    len: usize,
+    /// The text of the buffer with the composed text inserted.
+    layout: TextLayout,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub enum Movement {
     Glyph,
@@ -145,14 +159,18 @@ pub fn text_input(buffer: RwSignal<String>) -> TextInput {
         width: 0.0,
         height: 0.0,
         is_focused: false,
+        preedit: None,
+        ime_area: None,
         last_cursor_action_on: Instant::now(),
     }
     .keyboard_navigatable()
     .on_event_stop(EventListener::FocusGained, move |_| {
         is_focused.set(true);
+        set_ime_allowed(true);
     })
     .on_event_stop(EventListener::FocusLost, move |_| {
         is_focused.set(false);
+        set_ime_allowed(false);
     })
 }
 
@@ -290,7 +308,8 @@ impl TextInput {
     fn clip_text(&mut self, node_layout: &Layout) {
         let virt_text = self.text_buf.as_ref().unwrap();
         let node_width = node_layout.size.width as f64;
-        let cursor_text_loc = Cursor::new(0, self.cursor_glyph_idx);
+        let offset = self.text_offset();
+        let cursor_text_loc = Cursor::new(0, self.cursor_glyph_idx + offset);
         let layout_cursor = virt_text.layout_cursor(&cursor_text_loc);
         let cursor_glyph_pos = virt_text.hit_position(layout_cursor.glyph);
         let cursor_x = cursor_glyph_pos.point.x;
@@ -311,10 +330,14 @@ impl TextInput {
         }
         self.cursor_x = cursor_x;
 
-        let clip_start = virt_text.hit_point(Point::new(clip_start_x, 0.0)).index;
+        let clip_start = virt_text
+            .hit_point(Point::new(clip_start_x, 0.0))
+            .index
+            .saturating_sub(offset);
         let clip_end = virt_text
             .hit_point(Point::new(clip_start_x + node_width, 0.0))
-            .index;
+            .index
+            .saturating_sub(offset);
 
         let new_text = self
             .buffer
@@ -390,6 +413,7 @@ impl TextInput {
                 pos_y - padding_top as f64,
             ))
             .index
+            .saturating_sub(self.text_offset())
     }
 
     fn get_selection_rect(&self, node_layout: &Layout, left_padding: f64) -> Rect {
@@ -402,12 +426,14 @@ impl TextInput {
         let virtual_text = self.text_buf.as_ref().unwrap();
         let text_height = virtual_text.size().height;
 
+        let offset = self.text_offset();
         let selection_start_x =
-            virtual_text.hit_position(selection.start).point.x - self.clip_start_x;
+            virtual_text.hit_position(selection.start + offset).point.x - self.clip_start_x;
         let selection_start_x = selection_start_x.max(node_layout.location.x as f64 - left_padding);
 
-        let selection_end_x =
-            virtual_text.hit_position(selection.end).point.x + left_padding - self.clip_start_x;
+        let selection_end_x = virtual_text.hit_position(selection.end + offset).point.x
+            + left_padding
+            - self.clip_start_x;
         let selection_end_x =
             selection_end_x.min(selection_start_x + self.width as f64 + left_padding);
 
@@ -446,12 +472,23 @@ impl TextInput {
         }
     }
 
+    /// The offset of the buffer text in the text layouts.
+    fn text_offset(&self) -> usize {
+        if self.style.direction().is_rtl() {
+            RTL_MARK.len()
+        } else {
+            0
+        }
+    }
+
     fn update_text_layout(&mut self) {
         let mut text_layout = TextLayout::new();
         let attrs_list = self.get_text_attrs();
+        let mark = &RTL_MARK[..self.text_offset()];
 
-        self.buffer
-            .with_untracked(|buff| text_layout.set_text(buff, attrs_list.clone()));
+        self.buffer.with_untracked(|buff| {
+            text_layout.set_text(&format!("{mark}{buff}"), attrs_list.clone())
+        });
 
         let glyph_max_size = self.get_font_glyph_max_size();
         self.height = glyph_max_size.height as f32;
@@ -462,7 +499,7 @@ impl TextInput {
 
         if let Some(cr_text) = self.clipped_text.clone().as_ref() {
             let mut clp_txt_lay = text_layout;
-            clp_txt_lay.set_text(cr_text, attrs_list);
+            clp_txt_lay.set_text(&format!("{mark}{cr_text}"), attrs_list);
 
             self.clip_txt_buf = Some(clp_txt_lay);
         }
@@ -503,10 +540,7 @@ impl TextInput {
         if let Some(font_style) = self.font.style() {
             attrs = attrs.style(font_style);
         }
-        let font_family = self.font.family().as_ref().map(|font_family| {
-            let family: Vec<FamilyOwned> = FamilyOwned::parse_list(font_family).collect();
-            family
-        });
+        let font_family = self.font.families();
         if let Some(font_family) = font_family.as_ref() {
             attrs = attrs.family(font_family);
         }
@@ -615,6 +649,18 @@ impl TextInput {
         }
     }
 
+    /// Inserts `text` at the cursor, replacing the selection.
+    fn insert_text(&mut self, text: &str) {
+        if let Some(selection) = self.selection.take() {
+            self.buffer
+                .update(|buf| replace_range(buf, selection.clone(), None));
+            self.cursor_glyph_idx = selection.start;
+        }
+        self.buffer
+            .update(|buf| buf.insert_str(self.cursor_glyph_idx, text));
+        self.cursor_glyph_idx += text.len();
+    }
+
     fn handle_key_down(&mut self, cx: &mut EventCx, event: &KeyEvent) -> bool {
         match event.key.logical_key {
             Key::Character(ref ch) => {
@@ -837,6 +883,60 @@ impl TextInput {
         cx.draw_text(placeholder_buff, text_start_point);
     }
 
+    /// Lays out the text of the buffer with `text` being composed at the cursor.
+    fn preedit(&self, text: &str) -> Preedit {
+        let mut composed = self.buffer.get_untracked();
+        let start = if composed.is_char_boundary(self.cursor_glyph_idx) {
+            self.cursor_glyph_idx
+        } else {
+            composed.len()
+        };
+        composed.insert_str(start, text);
+        composed.insert_str(0, &RTL_MARK[..self.text_offset()]);
+        let mut layout = TextLayout::new();
+        layout.set_text(&composed, self.get_text_attrs());
+        Preedit {
+            start,
+            len: text.len(),
+            layout,
+        }
+    }
+
+    /// Paints the text with the composed text, which is underlined, in place of the text.
+    fn paint_preedit(
+        &self,
+        preedit: &Preedit,
+        node_layout: &Layout,
+        cx: &mut crate::context::PaintCx,
+    ) {
+        let location = Point::new(node_layout.location.x as f64, node_layout.location.y as f64);
+        // The composed text isn't clipped, so scroll it like the clipped text
+        let scroll_x = match &self.clip_txt_buf {
+            Some(_) => {
+                let text_buf = self.text_buf.as_ref().unwrap();
+                let index = self.clip_start_idx + self.text_offset();
+                text_buf.hit_position(index).point.x + self.clip_offset_x
+            }
+            None => 0.0,
+        };
+        let origin = Point::new(location.x - scroll_x, location.y);
+        let size = Size::new(
+            node_layout.size.width as f64,
+            node_layout.size.height.max(self.height) as f64,
+        );
+
+        cx.save();
+        cx.clip(&Rect::from_origin_size(location, size));
+        cx.draw_text(&preedit.layout, origin);
+        let start = preedit.start + self.text_offset();
+        let end = preedit.layout.hit_position(start + preedit.len).point.x;
+        let start = preedit.layout.hit_position(start).point.x;
+        let y = origin.y + self.height as f64 - 0.5;
+        let underline = Line::new((origin.x + start, y), (origin.x + end, y));
+        cx.stroke(&underline, self.style.color().unwrap_or(Color::BLACK), 1.0);
+        cx.restore();
+    }
+
     fn paint_selection_rect(&self, &node_layout: &Layout, cx: &mut crate::context::PaintCx<'_>) {
         let style = cx.app_state.get_computed_style(self.id());
         let cursor_color = style.get(CursorColor);
@@ -996,6 +1096,19 @@ impl View for TextInput {
                 false
             }
             Event::KeyDown(event) => self.handle_key_down(cx, event),
+            Event::ImePreedit { text, .. } => {
+                self.preedit = (!text.is_empty()).then(|| self.preedit(text));
+                true
+            }
+            Event::ImeCommit(text) => {
+                self.preedit = None;
+                self.insert_text(text);
+                true
+            }
+            Event::ImeDisabled => {
+                self.preedit = None;
+                true
+            }
             _ => false,
         };
 
@@ -1011,16 +1124,29 @@ impl View for TextInput {
         let style = cx.style();
         if self.font.read(cx) || self.text_buf.is_none() {
             self.update_text_layout();
+            self.placeholder_buff = None;
             cx.app_state_mut().request_layout(self.id());
         }
+        let was_rtl = self.style.direction().is_rtl();
         if self.style.read(cx) {
+            if self.style.direction().is_rtl() != was_rtl {
+                self.update_text_layout();
+                self.placeholder_buff = None;
+                cx.app_state_mut().request_layout(self.id());
+            }
             cx.app_state_mut().request_paint(self.id());
         }
 
         self.selection_style.read_style(cx, &style);
 
-        let placeholder_style = style.clone().apply_class(PlaceholderTextClass);
-        self.placeholder_style.read_style(cx, &placeholder_style);
+        let placeholder_style = style
+            .clone()
+            .apply_class(PlaceholderTextClass)
+            .apply_selectors(&[StyleSelector::Placeholder]);
+        if self.placeholder_style.read_style(cx, &placeholder_style) {
+            self.placeholder_buff = None;
+            cx.app_state_mut().request_layout(self.id());
+        }
     }
 
     fn layout(&mut self, cx: &mut crate::context::LayoutCx) -> taffy::prelude::Node {
@@ -1051,7 +1177,8 @@ impl View for TextInput {
                 if let Some(placeholder_text) = &self.placeholder_text {
                     let mut placeholder_buff = TextLayout::new();
                     let attrs_list = self.get_placeholder_text_attrs();
-                    placeholder_buff.set_text(placeholder_text, attrs_list);
+                    let mark = &RTL_MARK[..self.text_offset()];
+                    placeholder_buff.set_text(&format!("{mark}{placeholder_text}"), attrs_list);
                     self.placeholder_buff = Some(placeholder_buff);
                 }
             }
@@ -1118,7 +1245,7 @@ impl View for TextInput {
                 .text_buf
                 .as_ref()
                 .unwrap()
-                .hit_position(self.cursor_glyph_idx);
+                .hit_position(self.cursor_glyph_idx + self.text_offset());
             self.cursor_x = hit_pos.point.x;
         }
 
@@ -1126,13 +1253,14 @@ impl View for TextInput {
     }
 
     fn paint(&mut self, cx: &mut crate::context::PaintCx) {
-        if !cx.app_state.is_focused(&self.id())
-            && self.buffer.with_untracked(|buff| buff.is_empty())
-        {
+        // The placeholder is only painted, so it can't be selected or copied
+        if self.preedit.is_none() && self.buffer.with_untracked(|buff| buff.is_empty()) {
             if let Some(placeholder_buff) = &self.placeholder_buff {
                 self.paint_placeholder_text(placeholder_buff, cx);
             }
-            return;
+            if !cx.app_state.is_focused(&self.id()) {
+                return;
+            }
         }
 
         let text_node = self.text_node.unwrap();
@@ -1141,7 +1269,9 @@ impl View for TextInput {
         let location = node_layout.location;
         let text_start_point = Point::new(location.x as f64, location.y as f64);
 
-        if let Some(clip_txt) = self.clip_txt_buf.as_mut() {
+        if let Some(preedit) = &self.preedit {
+            self.paint_preedit(preedit, &node_layout, cx);
+        } else if let Some(clip_txt) = self.clip_txt_buf.as_mut() {
             cx.draw_text(
                 clip_txt,
                 Point::new(text_start_point.x - self.clip_offset_x, text_start_point.y),
@@ -1150,8 +1280,21 @@ impl View for TextInput {
             cx.draw_text(self.text_buf.as_ref().unwrap(), text_start_point);
         }
 
+        // The input method shows its candidates next to the cursor
+        if cx.app_state.is_focused(&self.id()) {
+            let origin = cx.app_state.get_layout_rect(self.id()).origin();
+            let area = self.get_cursor_rect(&node_layout) + origin.to_vec2();
+            if self.ime_area != Some(area) {
+                self.ime_area = Some(area);
+                set_ime_cursor_area(area.origin(), area.size());
+            }
+        } else {
+            self.ime_area = None;
+        }
+
         let is_cursor_visible = cx.app_state.is_focused(&self.id())
             && self.selection.is_none()
+            && self.preedit.is_none()
             && (self.last_cursor_action_on.elapsed().as_millis()
                 / CURSOR_BLINK_INTERVAL_MS as u128)
                 % 2
@@ -1183,9 +1326,13 @@ impl View for TextInput {
 
 #[cfg(test)]
 mod tests {
+    use floem_reactive::create_rw_signal;
+    use kurbo::{Point, Size};
+
     use crate::views::text_input::get_dbl_click_selection;
+    use crate::{event::Event, test_support::HeadlessWindow, views::Decorators};
 
-    use super::replace_range;
+    use super::{replace_range, text_input};
 
     #[test]
     fn replace_range_start() {
@@ -1357,4 +1504,25 @@ mod tests {
 
         assert_eq!(range, 0..s.len());
     }
+
+    #[test]
+    fn preedit_is_painted_until_committed() {
+        let text = create_rw_signal("ab".to_string());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 30.0), move || {
+            text_input(text).style(|s| s.size(100.0, 30.0))
+        });
+        window.click(Point::new(95.0, 15.0));
+        let typed = window.render().unwrap();
+
+        window.send_event(Event::ImePreedit {
+            text: "cd".to_string(),
+            cursor: Some((2, 2)),
+        });
+        let composing = window.render().unwrap();
+        assert_eq!(text.get_untracked(), "ab");
+        assert_ne!(composing, typed);
+
+        window.send_event(Event::ImeCommit("cd".to_string()));
+        assert_eq!(text.get_untracked(), "abcd");
+    }
 }
@@ -28,6 +28,7 @@ pub struct Tooltip {
     tip: Rc<dyn Fn() -> Box<dyn View>>,
     style: TooltipStyle,
     window_origin: Option<Point>,
+    show_when: Option<Box<dyn Fn() -> bool>>,
 }
 
 /// A view that displays a tooltip for its child.
@@ -43,6 +44,15 @@ pub fn tooltip<V: View + 'static, T: View + 'static>(
         overlay: None,
         style: Default::default(),
         window_origin: None,
+        show_when: None,
+    }
+}
+
+impl Tooltip {
+    /// Only shows the tooltip when `show_when` returns `true` as the pointer rests on the view.
+    pub fn show_when(mut self, show_when: impl Fn() -> bool + 'static) -> Self {
+        self.show_when = Some(Box::new(show_when));
+        self
     }
 }
 
@@ -77,7 +87,11 @@ impl View for Tooltip {
     fn update(&mut self, _cx: &mut UpdateCx, state: Box<dyn std::any::Any>) {
         if let Ok(token) = state.downcast::<TimerToken>() {
             if let Some(window_origin) = self.window_origin {
-                if self.hover.map(|(_, t)| t) == Some(*token) {
+                let show = self
+                    .show_when
+                    .as_ref()
+                    .map_or(true, |show_when| show_when());
+                if show && self.hover.map(|(_, t)| t) == Some(*token) {
                     let tip = self.tip.clone();
                     self.overlay = Some(add_overlay(
                         window_origin + self.hover.unwrap().0.to_vec2(),
@@ -13,7 +13,9 @@ style_class!(pub LabeledCheckboxClass);
 fn checkbox_svg(checked: ReadSignal<bool>) -> impl View {
     const CHECKBOX_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-2 -2 16 16"><polygon points="5.19,11.83 0.18,7.44 1.82,5.56 4.81,8.17 10,1.25 12,2.75" /></svg>"#;
     let svg_str = move || if checked.get() { CHECKBOX_SVG } else { "" }.to_string();
-    svg(svg_str).class(CheckboxClass)
+    svg(svg_str)
+        .class(CheckboxClass)
+        .checked(move || checked.get())
 }
 
 /// Renders a checkbox the provided checked signal.
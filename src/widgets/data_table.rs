@@ -0,0 +1,574 @@
+//! A data table, with a row for each item and a cell for each column.
+
+use std::{cell::Cell, cmp::Ordering, hash::Hash, rc::Rc};
+
+use floem_reactive::{create_rw_signal, RwSignal};
+
+use crate::{
+    event::{Event, EventListener},
+    id::Id,
+    style::CursorStyle,
+    style_class,
+    view::{View, ViewData},
+    views::{
+        clip, dyn_stack, empty, h_stack, h_stack_from_iter, label, scroll, v_stack, virtual_stack,
+        Decorators, Stack, VirtualDirection, VirtualItemSize,
+    },
+    EventPropagation,
+};
+
+style_class!(pub DataTableClass);
+style_class!(pub TableHeaderClass);
+style_class!(pub TableRowClass);
+style_class!(pub TableCellClass);
+style_class!(pub ColumnResizeHandleClass);
+
+/// The order rows are sorted in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+/// The column the rows of a [`data_table`] are sorted by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SortState {
+    /// The index of the column.
+    pub column: usize,
+    pub direction: SortDirection,
+}
+
+type Compare<T> = Rc<dyn Fn(&T, &T) -> Ordering>;
+
+/// A column of a [`data_table`].
+pub struct Column<T> {
+    title: String,
+    width: f64,
+    min_width: f64,
+    cell_fn: Box<dyn Fn(&T) -> Box<dyn View>>,
+    compare: Option<Compare<T>>,
+}
+
+/// A column titled `title`, where the cell of each row is the view returned by `cell_fn`.
+pub fn column<T, V: View + 'static>(
+    title: impl Into<String>,
+    cell_fn: impl Fn(&T) -> V + 'static,
+) -> Column<T> {
+    Column {
+        title: title.into(),
+        width: 100.0,
+        min_width: 20.0,
+        cell_fn: Box::new(move |row| Box::new(cell_fn(row))),
+        compare: None,
+    }
+}
+
+impl<T> Column<T> {
+    /// Sets the initial width of the column. Defaults to 100px.
+    pub fn width(mut self, width: f64) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets how narrow the column can be resized. Defaults to 20px.
+    pub fn min_width(mut self, min_width: f64) -> Self {
+        self.min_width = min_width;
+        self
+    }
+
+    /// Makes the rows sortable by this column, ordered by `compare`.
+    pub fn sort_by(mut self, compare: impl Fn(&T, &T) -> Ordering + 'static) -> Self {
+        self.compare = Some(Rc::new(compare));
+        self
+    }
+
+    /// Makes the rows sortable by this column, ordered by the key returned by `key_fn`.
+    pub fn sort_by_key<K: Ord>(self, key_fn: impl Fn(&T) -> K + 'static) -> Self {
+        self.sort_by(move |a, b| key_fn(a).cmp(&key_fn(b)))
+    }
+}
+
+/// A table of rows with a cell for each column. See [`data_table`].
+pub struct DataTable {
+    data: ViewData,
+    sort: RwSignal<Option<SortState>>,
+    widths: RwSignal<Vec<f64>>,
+    child: Stack,
+}
+
+impl DataTable {
+    /// The column the rows are sorted by, if any. Setting it sorts the rows.
+    pub fn sort(&self) -> RwSignal<Option<SortState>> {
+        self.sort
+    }
+
+    /// The width of each column.
+    pub fn column_widths(&self) -> RwSignal<Vec<f64>> {
+        self.widths
+    }
+}
+
+/// The rows returned by `rows`, in the order of `sort`.
+fn sorted<T: Clone>(
+    mut rows: im::Vector<T>,
+    sort: Option<SortState>,
+    compares: &[Option<Compare<T>>],
+) -> im::Vector<T> {
+    let compare = sort.and_then(|sort| Some((compares.get(sort.column)?.clone()?, sort)));
+    if let Some((compare, sort)) = compare {
+        rows.sort_by(|a, b| match sort.direction {
+            SortDirection::Ascending => compare(a, b),
+            SortDirection::Descending => compare(b, a),
+        });
+    }
+    rows
+}
+
+fn header<T>(
+    columns: &[Column<T>],
+    sort: RwSignal<Option<SortState>>,
+    widths: RwSignal<Vec<f64>>,
+    offset: RwSignal<f64>,
+) -> impl View {
+    let cells = columns.iter().enumerate().map(|(index, column)| {
+        let title = column.title.clone();
+        let sortable = column.compare.is_some();
+        let min_width = column.min_width;
+        let title = label(move || {
+            let arrow = match sort.get() {
+                Some(sort) if sort.column == index => match sort.direction {
+                    SortDirection::Ascending => " ▲",
+                    SortDirection::Descending => " ▼",
+                },
+                _ => "",
+            };
+            format!("{title}{arrow}")
+        });
+
+        // The horizontal position of the pointer in the handle when the drag started
+        let grab = Rc::new(Cell::new(None));
+        let handle = empty().class(ColumnResizeHandleClass);
+        let handle_id = handle.id();
+        let handle = handle
+            .on_event(EventListener::PointerDown, {
+                let grab = grab.clone();
+                move |e| {
+                    if let Event::PointerDown(e) = e {
+                        grab.set(Some(e.pos.x));
+                        // Keeps the pointer events coming while it's dragged outside the handle
+                        handle_id.request_active();
+                    }
+                    EventPropagation::Stop
+                }
+            })
+            .on_event(EventListener::PointerMove, {
+                let grab = grab.clone();
+                move |e| {
+                    if let (Event::PointerMove(e), Some(grab)) = (e, grab.get()) {
+                        // The handle moves with the edge of the column, so the offset from the
+                        // grabbed position is how much wider the column gets
+                        widths.update(|widths| {
+                            widths[index] = (widths[index] + e.pos.x - grab).max(min_width);
+                        });
+                    }
+                    EventPropagation::Continue
+                }
+            })
+            .on_event(EventListener::PointerUp, move |_| {
+                grab.set(None);
+                EventPropagation::Continue
+            })
+            .style(|s| {
+                s.absolute()
+                    .inset_right(0)
+                    .height_full()
+                    .cursor(CursorStyle::ColResize)
+            });
+
+        h_stack((clip(title), handle))
+            .class(TableCellClass)
+            .on_click_stop(move |_| {
+                if !sortable {
+                    return;
+                }
+                sort.update(|sort| {
+                    *sort = match *sort {
+                        Some(SortState {
+                            column,
+                            direction: SortDirection::Ascending,
+                        }) if column == index => Some(SortState {
+                            column,
+                            direction: SortDirection::Descending,
+                        }),
+                        Some(SortState {
+                            column,
+                            direction: SortDirection::Descending,
+                        }) if column == index => None,
+                        _ => Some(SortState {
+                            column: index,
+                            direction: SortDirection::Ascending,
+                        }),
+                    }
+                });
+            })
+            .style(move |s| {
+                let width = widths.with(|widths| widths[index]);
+                s.width(width)
+                    .flex_shrink(0.)
+                    .apply_if(sortable, |s| s.cursor(CursorStyle::Pointer))
+            })
+    });
+    // The header stays in place while the body scrolls down, and follows it when it scrolls
+    // sideways
+    clip(
+        h_stack_from_iter(cells)
+            .class(TableHeaderClass)
+            .style(move |s| s.inset_left(-offset.get())),
+    )
+    .style(|s| s.width_full().flex_shrink(0.))
+}
+
+fn row_view<T: 'static>(
+    row: T,
+    cell_fns: &Rc<Vec<Box<dyn Fn(&T) -> Box<dyn View>>>>,
+    widths: RwSignal<Vec<f64>>,
+) -> Stack {
+    let cells = cell_fns.iter().enumerate().map(|(index, cell_fn)| {
+        clip(cell_fn(&row)).class(TableCellClass).style(move |s| {
+            let width = widths.with(|widths| widths[index]);
+            s.width(width).flex_shrink(0.)
+        })
+    });
+    h_stack_from_iter(cells).class(TableRowClass)
+}
+
+fn table<T: Clone + 'static>(
+    columns: Vec<Column<T>>,
+    body: impl FnOnce(
+        Rc<Vec<Option<Compare<T>>>>,
+        Rc<Vec<Box<dyn Fn(&T) -> Box<dyn View>>>>,
+        RwSignal<Option<SortState>>,
+        RwSignal<Vec<f64>>,
+    ) -> Box<dyn View>,
+) -> DataTable {
+    let sort = create_rw_signal(None);
+    let widths = create_rw_signal(columns.iter().map(|column| column.width).collect());
+    let offset = create_rw_signal(0.0);
+
+    let header = header(&columns, sort, widths, offset);
+    let compares = Rc::new(
+        columns
+            .iter()
+            .map(|column| column.compare.clone())
+            .collect(),
+    );
+    let cell_fns = Rc::new(columns.into_iter().map(|column| column.cell_fn).collect());
+    let body = scroll(body(compares, cell_fns, sort, widths))
+        .on_scroll(move |viewport| offset.set(viewport.x0))
+        .style(|s| s.width_full().flex_grow(1.));
+
+    DataTable {
+        data: ViewData::new(Id::next()),
+        sort,
+        widths,
+        child: v_stack((header, body)).style(|s| s.size_full()),
+    }
+    .class(DataTableClass)
+}
+
+/// A table with a row for each item returned by `rows`, and the `columns` made with
+/// [`column`]. Each row is identified by the key returned by `key_fn`.
+///
+/// Clicking the header of a column made with [`Column::sort_by`] sorts the rows by it, then
+/// in descending order, then unsorts them. The sort order is in [`DataTable::sort`]. Columns
+/// are resized by dragging the right edge of their header, and keep their width as the table
+/// is scrolled. The header stays at the top while the rows scroll.
+///
+/// All the rows are built, so for a large number of rows use [`virtual_data_table`].
+///
+/// The table has the [`DataTableClass`], the header the [`TableHeaderClass`], rows the
+/// [`TableRowClass`], and cells the [`TableCellClass`], including the header cells. The
+/// handles for resizing columns have the [`ColumnResizeHandleClass`].
+/// ```rust
+/// # use floem::widgets::{column, data_table};
+/// # use floem::views::text;
+/// #[derive(Clone)]
+/// struct Fruit {
+///     name: &'static str,
+///     price: u32,
+/// }
+///
+/// let fruits = im::vector![
+///     Fruit { name: "Apple", price: 3 },
+///     Fruit { name: "Pear", price: 2 },
+/// ];
+/// data_table(
+///     move || fruits.clone(),
+///     |fruit| fruit.name,
+///     vec![
+///         column("Name", |fruit: &Fruit| text(fruit.name)).sort_by_key(|fruit| fruit.name),
+///         column("Price", |fruit: &Fruit| text(fruit.price)).sort_by_key(|fruit| fruit.price),
+///     ],
+/// );
+/// ```
+pub fn data_table<T, K>(
+    rows: impl Fn() -> im::Vector<T> + 'static,
+    key_fn: impl Fn(&T) -> K + 'static,
+    columns: Vec<Column<T>>,
+) -> DataTable
+where
+    T: Clone + 'static,
+    K: Eq + Hash + 'static,
+{
+    table(columns, move |compares, cell_fns, sort, widths| {
+        Box::new(
+            dyn_stack(
+                move || sorted(rows(), sort.get(), &compares),
+                key_fn,
+                move |row| row_view(row, &cell_fns, widths),
+            )
+            .style(|s| s.flex_col()),
+        )
+    })
+}
+
+/// A [`data_table`] where only the rows which are scrolled into view are built, which all have
+/// a height of `row_height`.
+pub fn virtual_data_table<T, K>(
+    row_height: f64,
+    rows: impl Fn() -> im::Vector<T> + 'static,
+    key_fn: impl Fn(&T) -> K + 'static,
+    columns: Vec<Column<T>>,
+) -> DataTable
+where
+    T: Clone + 'static,
+    K: Eq + Hash + 'static,
+{
+    table(columns, move |compares, cell_fns, sort, widths| {
+        Box::new(
+            virtual_stack(
+                VirtualDirection::Vertical,
+                VirtualItemSize::Fixed(Box::new(move || row_height)),
+                move || sorted(rows(), sort.get(), &compares),
+                key_fn,
+                move |row| row_view(row, &cell_fns, widths).style(move |s| s.height(row_height)),
+            )
+            .style(|s| s.flex_col()),
+        )
+    })
+}
+
+impl View for DataTable {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "DataTable".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use floem_reactive::RwSignal;
+    use kurbo::{Point, Size};
+
+    use super::{column, data_table, sorted, Compare, SortDirection, SortState};
+    use crate::{pointer::PointerButton, test_support::HeadlessWindow, views::text};
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Fruit {
+        name: &'static str,
+        price: u32,
+    }
+
+    fn fruits() -> im::Vector<Fruit> {
+        im::vector![
+            Fruit {
+                name: "Pear",
+                price: 2
+            },
+            Fruit {
+                name: "Apple",
+                price: 3
+            },
+            Fruit {
+                name: "Plum",
+                price: 1
+            },
+        ]
+    }
+
+    /// A window with a table of fruits, sortable by name and price, and a third column which
+    /// isn't sortable. The columns are 100px wide.
+    fn fruit_table() -> (
+        HeadlessWindow,
+        RwSignal<Option<SortState>>,
+        RwSignal<Vec<f64>>,
+    ) {
+        let signals = Rc::new(Cell::new(None));
+        let mut window = HeadlessWindow::new(Size::new(400.0, 200.0), {
+            let signals = signals.clone();
+            move || {
+                let table = data_table(
+                    fruits,
+                    |fruit| fruit.name,
+                    vec![
+                        column("Name", |fruit: &Fruit| text(fruit.name))
+                            .sort_by_key(|fruit| fruit.name),
+                        column("Price", |fruit: &Fruit| text(fruit.price))
+                            .sort_by_key(|fruit| fruit.price),
+                        column("Note", |_: &Fruit| text("")),
+                    ],
+                );
+                signals.set(Some((table.sort(), table.column_widths())));
+                table
+            }
+        });
+        window.update();
+        let (sort, widths) = signals.get().unwrap();
+        (window, sort, widths)
+    }
+
+    fn sort(column: usize, direction: SortDirection) -> Option<SortState> {
+        Some(SortState { column, direction })
+    }
+
+    fn names(rows: im::Vector<Fruit>) -> Vec<&'static str> {
+        rows.iter().map(|fruit| fruit.name).collect()
+    }
+
+    #[test]
+    fn rows_are_sorted_by_the_sort_column() {
+        let by_name: Compare<Fruit> = Rc::new(|a, b| a.name.cmp(b.name));
+        let by_price: Compare<Fruit> = Rc::new(|a, b| a.price.cmp(&b.price));
+        let compares = [Some(by_name), Some(by_price), None];
+
+        assert_eq!(
+            names(sorted(fruits(), None, &compares)),
+            ["Pear", "Apple", "Plum"]
+        );
+        assert_eq!(
+            names(sorted(
+                fruits(),
+                sort(0, SortDirection::Ascending),
+                &compares
+            )),
+            ["Apple", "Pear", "Plum"]
+        );
+        assert_eq!(
+            names(sorted(
+                fruits(),
+                sort(1, SortDirection::Descending),
+                &compares
+            )),
+            ["Apple", "Pear", "Plum"]
+        );
+        // Columns which aren't sortable, or don't exist, leave the rows alone
+        assert_eq!(
+            names(sorted(
+                fruits(),
+                sort(2, SortDirection::Ascending),
+                &compares
+            )),
+            ["Pear", "Apple", "Plum"]
+        );
+        assert_eq!(
+            names(sorted(
+                fruits(),
+                sort(3, SortDirection::Ascending),
+                &compares
+            )),
+            ["Pear", "Apple", "Plum"]
+        );
+    }
+
+    #[test]
+    fn clicking_a_header_cycles_its_sort_order() {
+        let (mut window, sort_state, _) = fruit_table();
+        let name = Point::new(30.0, 5.0);
+        window.click(name);
+        assert_eq!(
+            sort_state.get_untracked(),
+            sort(0, SortDirection::Ascending)
+        );
+        window.click(name);
+        assert_eq!(
+            sort_state.get_untracked(),
+            sort(0, SortDirection::Descending)
+        );
+        window.click(name);
+        assert_eq!(sort_state.get_untracked(), None);
+    }
+
+    #[test]
+    fn clicking_another_header_sorts_by_it() {
+        let (mut window, sort_state, _) = fruit_table();
+        window.click(Point::new(30.0, 5.0));
+        window.click(Point::new(30.0, 5.0));
+        window.click(Point::new(130.0, 5.0));
+        assert_eq!(
+            sort_state.get_untracked(),
+            sort(1, SortDirection::Ascending)
+        );
+        window.click(Point::new(30.0, 5.0));
+        assert_eq!(
+            sort_state.get_untracked(),
+            sort(0, SortDirection::Ascending)
+        );
+        // The header of a column which isn't sortable does nothing
+        window.click(Point::new(230.0, 5.0));
+        assert_eq!(
+            sort_state.get_untracked(),
+            sort(0, SortDirection::Ascending)
+        );
+    }
+
+    #[test]
+    fn dragging_the_edge_of_a_header_resizes_its_column() {
+        let (mut window, sort_state, widths) = fruit_table();
+        window.pointer_down(Point::new(97.0, 5.0), PointerButton::Primary);
+        window.pointer_move(Point::new(137.0, 5.0));
+        window.update();
+        // The edge followed the pointer, so it's where the pointer grabbed it
+        window.pointer_move(Point::new(157.0, 5.0));
+        window.pointer_up(Point::new(157.0, 5.0), PointerButton::Primary);
+        window.update();
+        assert_eq!(widths.get_untracked(), [160.0, 100.0, 100.0]);
+        // Resizing isn't a click on the header
+        assert_eq!(sort_state.get_untracked(), None);
+
+        // Columns don't get narrower than their minimum width
+        window.pointer_down(Point::new(157.0, 5.0), PointerButton::Primary);
+        window.pointer_move(Point::new(0.0, 5.0));
+        window.pointer_up(Point::new(0.0, 5.0), PointerButton::Primary);
+        window.update();
+        assert_eq!(widths.get_untracked(), [20.0, 100.0, 100.0]);
+
+        // Moving the pointer after the release doesn't resize
+        window.pointer_move(Point::new(100.0, 5.0));
+        window.update();
+        assert_eq!(widths.get_untracked(), [20.0, 100.0, 100.0]);
+    }
+}
@@ -0,0 +1,517 @@
+//! A dropdown, a control showing a value which opens a list of values to pick from.
+
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use floem_reactive::{create_effect, create_rw_signal, RwSignal};
+use kurbo::{Point, Rect, Size};
+
+use crate::{
+    action::{add_overlay, remove_overlay},
+    context::{ComputeLayoutCx, EventCx, UpdateCx},
+    event::{Event, EventListener},
+    id::Id,
+    keyboard::{Key, NamedKey},
+    style_class,
+    view::{default_compute_layout, default_event, View, ViewData},
+    views::{container, dyn_container, scroll, v_stack_from_iter, Decorators, Item},
+    EventPropagation,
+};
+
+style_class!(pub DropdownClass);
+style_class!(pub DropdownPopupClass);
+style_class!(pub DropdownItemClass);
+
+/// How long after the last key press typing continues the search, instead of starting a new one.
+const SEARCH_TIMEOUT: Duration = Duration::from_secs(1);
+
+enum DropdownUpdate {
+    Close,
+    HighlightChanged,
+}
+
+type ViewFn<T> = Rc<dyn Fn(&T) -> Box<dyn View>>;
+
+/// A control showing a value, which opens a list to pick another one. See [`dropdown`].
+pub struct Dropdown<T: 'static> {
+    data: ViewData,
+    child: Box<dyn View>,
+    selected: RwSignal<T>,
+    items: Rc<dyn Fn() -> Vec<T>>,
+    view_fn: ViewFn<T>,
+    search_fn: Option<Rc<dyn Fn(&T) -> String>>,
+    /// The popup, while it's open.
+    overlay: Option<Id>,
+    /// The item in the popup which Enter picks.
+    highlighted: RwSignal<Option<usize>>,
+    /// The rect of the control in the window.
+    anchor: RwSignal<Rect>,
+    window_size: RwSignal<Size>,
+    search: String,
+    last_search: Option<Instant>,
+}
+
+/// A control showing the value of `selected`, which opens a popup with the values returned by
+/// `items` when clicked. Picking one of them sets `selected` to it and closes the popup, as does
+/// clicking outside of it.
+///
+/// Both the control and the items of the popup show their value with `view_fn`.
+///
+/// The popup is as wide as the control, and opens below it, unless there's only room for it
+/// above. When the control has focus, Enter, Space and the down arrow key open it, the up and
+/// down arrow keys, Home and End move through the items, Enter picks the highlighted item and
+/// Escape closes the popup. With [`Dropdown::search_text`], typing the start of an item
+/// highlights it, or selects it while the popup is closed.
+///
+/// The control has the [`DropdownClass`], the popup the [`DropdownPopupClass`] and its items the
+/// [`DropdownItemClass`], with the `selected` selector applying to the highlighted item.
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::views::text;
+/// # use floem::widgets::dropdown;
+/// let fruit = create_rw_signal("Apple");
+/// dropdown(fruit, || vec!["Apple", "Pear", "Plum"], |fruit| text(fruit))
+///     .search_text(|fruit| fruit.to_string());
+/// ```
+pub fn dropdown<T, V>(
+    selected: RwSignal<T>,
+    items: impl Fn() -> Vec<T> + 'static,
+    view_fn: impl Fn(&T) -> V + 'static,
+) -> Dropdown<T>
+where
+    T: Clone + PartialEq + 'static,
+    V: View + 'static,
+{
+    let view_fn: ViewFn<T> = Rc::new(move |value| Box::new(view_fn(value)));
+    let child = {
+        let view_fn = view_fn.clone();
+        dyn_container(move || selected.get(), move |value| view_fn(&value))
+    };
+    let id = Id::next();
+    Dropdown {
+        data: ViewData::new(id),
+        child: Box::new(child),
+        selected,
+        items: Rc::new(items),
+        view_fn,
+        search_fn: None,
+        overlay: None,
+        highlighted: create_rw_signal(None),
+        anchor: create_rw_signal(Rect::ZERO),
+        window_size: create_rw_signal(Size::ZERO),
+        search: String::new(),
+        last_search: None,
+    }
+    .class(DropdownClass)
+    .keyboard_navigatable()
+    // The popup can't be used from the keyboard once the control loses focus
+    .on_event_cont(EventListener::FocusLost, move |_| {
+        id.update_state(DropdownUpdate::Close)
+    })
+}
+
+impl<T: Clone + PartialEq + 'static> Dropdown<T> {
+    /// Enables type-to-search, matching what's typed against the start of the text returned by
+    /// `search_fn` for each item, ignoring case.
+    pub fn search_text(mut self, search_fn: impl Fn(&T) -> String + 'static) -> Self {
+        self.search_fn = Some(Rc::new(search_fn));
+        self
+    }
+
+    /// The selected value.
+    pub fn selected(&self) -> RwSignal<T> {
+        self.selected
+    }
+
+    fn open(&mut self) {
+        if self.overlay.is_some() {
+            return;
+        }
+        let items = (self.items)();
+        let current = self
+            .selected
+            .with_untracked(|selected| items.iter().position(|item| item == selected));
+        self.highlighted.set(current);
+
+        let id = self.id();
+        let (selected, highlighted, anchor, window_size) = (
+            self.selected,
+            self.highlighted,
+            self.anchor,
+            self.window_size,
+        );
+        let view_fn = self.view_fn.clone();
+        self.overlay = Some(add_overlay(Point::ZERO, move |_| {
+            let popup_height = create_rw_signal(0.0);
+            let items = Rc::new(items);
+            create_effect(move |_| {
+                highlighted.track();
+                id.update_state(DropdownUpdate::HighlightChanged);
+            });
+            let list = v_stack_from_iter(items.iter().enumerate().map(|(index, item)| {
+                let items = items.clone();
+                Item {
+                    data: ViewData::new(Id::next()),
+                    index,
+                    selection: highlighted,
+                    child: Box::new(container(view_fn(item)).class(DropdownItemClass)),
+                }
+                .on_event(EventListener::PointerEnter, move |_| {
+                    highlighted.set(Some(index));
+                    EventPropagation::Continue
+                })
+                .on_click_stop(move |_| {
+                    selected.set(items[index].clone());
+                    id.update_state(DropdownUpdate::Close);
+                })
+            }));
+            let popup = scroll(list.style(|s| s.flex_col().width_full()))
+                .class(DropdownPopupClass)
+                .on_click_stop(|_| {})
+                .on_resize(move |rect| popup_height.set(rect.height()))
+                .style(move |s| {
+                    let anchor = anchor.get();
+                    let height = popup_height.get();
+                    // Opens above the control when it doesn't fit below, and there's room above
+                    let flip = anchor.y1 + height > window_size.get().height && anchor.y0 >= height;
+                    let top = if flip { anchor.y0 - height } else { anchor.y1 };
+                    s.absolute()
+                        .inset_left(anchor.x0)
+                        .inset_top(top)
+                        .width(anchor.width())
+                });
+            // Covers the window, so clicking outside of the popup closes it
+            container(popup)
+                .on_click_stop(move |_| id.update_state(DropdownUpdate::Close))
+                .style(move |s| {
+                    let size = window_size.get();
+                    s.width(size.width).height(size.height)
+                })
+        }));
+    }
+
+    fn close(&mut self) {
+        if let Some(overlay) = self.overlay.take() {
+            remove_overlay(overlay);
+        }
+    }
+
+    fn pick_highlighted(&mut self) {
+        let items = (self.items)();
+        if let Some(item) = self.highlighted.get_untracked().and_then(|i| items.get(i)) {
+            self.selected.set(item.clone());
+        }
+        self.close();
+    }
+
+    /// Moves the highlight, or the selection while closed, to the item at `index`.
+    fn move_to(&mut self, index: usize) {
+        if self.overlay.is_some() {
+            self.highlighted.set(Some(index));
+        } else if let Some(item) = (self.items)().get(index) {
+            self.selected.set(item.clone());
+        }
+    }
+
+    fn current(&self, items: &[T]) -> Option<usize> {
+        if self.overlay.is_some() {
+            self.highlighted.get_untracked()
+        } else {
+            self.selected
+                .with_untracked(|selected| items.iter().position(|item| item == selected))
+        }
+    }
+
+    fn search(&mut self, text: &str) -> bool {
+        let Some(search_fn) = self.search_fn.clone() else {
+            return false;
+        };
+        let now = Instant::now();
+        if self
+            .last_search
+            .map_or(true, |last| now.duration_since(last) > SEARCH_TIMEOUT)
+        {
+            self.search.clear();
+        }
+        self.last_search = Some(now);
+        self.search.push_str(&text.to_lowercase());
+
+        let items = (self.items)();
+        if let Some(index) = items
+            .iter()
+            .position(|item| search_fn(item).to_lowercase().starts_with(&self.search))
+        {
+            self.move_to(index);
+        }
+        true
+    }
+
+    fn key_down(&mut self, key: &Key) -> bool {
+        let open = self.overlay.is_some();
+        let items = (self.items)();
+        let last = items.len().checked_sub(1);
+        let current = self.current(&items);
+        match key {
+            Key::Named(NamedKey::Enter) | Key::Named(NamedKey::Space) if !open => self.open(),
+            Key::Named(NamedKey::Enter) => self.pick_highlighted(),
+            Key::Named(NamedKey::Escape) if open => self.close(),
+            Key::Named(NamedKey::ArrowDown) if !open => self.open(),
+            Key::Named(NamedKey::ArrowDown) => {
+                if let Some(last) = last {
+                    self.move_to(current.map_or(0, |i| (i + 1).min(last)));
+                }
+            }
+            Key::Named(NamedKey::ArrowUp) => {
+                if let Some(last) = last {
+                    self.move_to(current.map_or(last, |i| i.saturating_sub(1)));
+                }
+            }
+            Key::Named(NamedKey::Home) if last.is_some() => self.move_to(0),
+            Key::Named(NamedKey::End) => {
+                if let Some(last) = last {
+                    self.move_to(last);
+                }
+            }
+            Key::Character(text) => return self.search(text),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl<T: Clone + PartialEq + 'static> View for Dropdown<T> {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Dropdown".into()
+    }
+
+    fn update(&mut self, cx: &mut UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(update) = state.downcast::<DropdownUpdate>() {
+            match *update {
+                DropdownUpdate::Close => self.close(),
+                DropdownUpdate::HighlightChanged => {
+                    if let Some(overlay) = self.overlay {
+                        cx.app_state_mut().request_style_recursive(overlay);
+                    }
+                }
+            }
+        }
+    }
+
+    fn event(
+        &mut self,
+        cx: &mut EventCx,
+        id_path: Option<&[Id]>,
+        event: Event,
+    ) -> EventPropagation {
+        match &event {
+            Event::PointerDown(e) if e.button.is_primary() => {
+                if self.overlay.is_some() {
+                    self.close();
+                } else {
+                    self.open();
+                }
+            }
+            Event::KeyDown(e) => {
+                if self.key_down(&e.key.logical_key) {
+                    return EventPropagation::Stop;
+                }
+            }
+            _ => {}
+        }
+        default_event(self, cx, id_path, event)
+    }
+
+    fn compute_layout(&mut self, cx: &mut ComputeLayoutCx) -> Option<Rect> {
+        let size = cx
+            .app_state
+            .get_layout(self.id())
+            .map(|layout| Size::new(layout.size.width as f64, layout.size.height as f64))
+            .unwrap_or_default();
+        let anchor = size.to_rect().with_origin(cx.window_origin);
+        if self.anchor.get_untracked() != anchor {
+            self.anchor.set(anchor);
+        }
+        let window_size = cx.app_state.root_size / cx.app_state.scale;
+        if self.window_size.get_untracked() != window_size {
+            self.window_size.set(window_size);
+        }
+        default_compute_layout(self, cx)
+    }
+}
+
+impl<T: 'static> Drop for Dropdown<T> {
+    fn drop(&mut self) {
+        if let Some(overlay) = self.overlay {
+            remove_overlay(overlay)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use floem_reactive::{create_rw_signal, RwSignal};
+    use kurbo::{Point, Size};
+
+    use super::dropdown;
+    use crate::{
+        keyboard::{Key, ModifiersState, NamedKey},
+        test_support::HeadlessWindow,
+        views::{empty, text, v_stack, Decorators},
+    };
+
+    const FRUITS: [&str; 3] = ["Apple", "Pear", "Plum"];
+
+    /// A window with a dropdown of fruits at the top, which is clicked to open it, and a
+    /// focusable view below it.
+    struct DropdownWindow {
+        window: HeadlessWindow,
+        selected: RwSignal<&'static str>,
+        /// The number of item views alive, which is 1 while the popup is closed.
+        views: Rc<Cell<usize>>,
+    }
+
+    impl DropdownWindow {
+        fn new(selected: &'static str) -> Self {
+            let selected = create_rw_signal(selected);
+            let views = Rc::new(Cell::new(0));
+            let live = views.clone();
+            let mut window = HeadlessWindow::new(Size::new(200.0, 200.0), move || {
+                v_stack((
+                    dropdown(
+                        selected,
+                        || FRUITS.to_vec(),
+                        move |fruit| {
+                            live.set(live.get() + 1);
+                            let live = live.clone();
+                            text(*fruit).on_cleanup(move || live.set(live.get() - 1))
+                        },
+                    )
+                    .search_text(|fruit| fruit.to_string())
+                    .style(|s| s.size(100.0, 20.0)),
+                    empty()
+                        .keyboard_navigatable()
+                        .style(|s| s.size(100.0, 20.0)),
+                ))
+            });
+            window.update();
+            Self {
+                window,
+                selected,
+                views,
+            }
+        }
+
+        fn open(&mut self) {
+            self.window.click(Point::new(50.0, 10.0));
+            self.window.update();
+            assert!(self.is_open());
+        }
+
+        fn is_open(&self) -> bool {
+            self.views.get() > 1
+        }
+
+        fn press(&mut self, key: NamedKey) {
+            self.window
+                .send_key(Key::Named(key), ModifiersState::empty());
+            self.window.update();
+        }
+
+        fn type_text(&mut self, text: &str) {
+            self.window
+                .send_key(Key::Character(text.into()), ModifiersState::empty());
+            self.window.update();
+        }
+    }
+
+    #[test]
+    fn keyboard_moves_the_highlight_and_picks_it() {
+        let mut dropdown = DropdownWindow::new("Apple");
+        dropdown.open();
+        assert_eq!(dropdown.views.get(), 1 + FRUITS.len());
+
+        dropdown.press(NamedKey::ArrowDown);
+        dropdown.press(NamedKey::ArrowDown);
+        dropdown.press(NamedKey::ArrowDown);
+        dropdown.press(NamedKey::Home);
+        dropdown.press(NamedKey::End);
+        dropdown.press(NamedKey::ArrowUp);
+        // Only picking an item selects it
+        assert_eq!(dropdown.selected.get_untracked(), "Apple");
+        dropdown.press(NamedKey::Enter);
+        assert_eq!(dropdown.selected.get_untracked(), "Pear");
+        assert!(!dropdown.is_open());
+
+        // The arrow keys open the popup, and move the selection while it's closed
+        dropdown.press(NamedKey::ArrowDown);
+        assert!(dropdown.is_open());
+        dropdown.press(NamedKey::Escape);
+        assert!(!dropdown.is_open());
+        assert_eq!(dropdown.selected.get_untracked(), "Pear");
+        dropdown.press(NamedKey::ArrowUp);
+        assert_eq!(dropdown.selected.get_untracked(), "Apple");
+        assert!(!dropdown.is_open());
+    }
+
+    #[test]
+    fn typing_searches_the_items() {
+        let mut dropdown = DropdownWindow::new("Apple");
+        dropdown.open();
+        dropdown.press(NamedKey::Escape);
+
+        // While closed, the first match is selected
+        dropdown.type_text("p");
+        assert_eq!(dropdown.selected.get_untracked(), "Pear");
+        dropdown.type_text("L");
+        assert_eq!(dropdown.selected.get_untracked(), "Plum");
+        // Without a match, the selection stays
+        dropdown.type_text("x");
+        assert_eq!(dropdown.selected.get_untracked(), "Plum");
+    }
+
+    #[test]
+    fn clicking_outside_closes_the_popup() {
+        let mut dropdown = DropdownWindow::new("Pear");
+        dropdown.open();
+        dropdown.window.click(Point::new(150.0, 150.0));
+        dropdown.window.update();
+        assert!(!dropdown.is_open());
+        assert_eq!(dropdown.selected.get_untracked(), "Pear");
+    }
+
+    #[test]
+    fn losing_focus_closes_the_popup() {
+        let mut dropdown = DropdownWindow::new("Pear");
+        dropdown.open();
+        dropdown.press(NamedKey::Tab);
+        assert!(!dropdown.is_open());
+        assert_eq!(dropdown.selected.get_untracked(), "Pear");
+    }
+}
@@ -0,0 +1,330 @@
+//! A find bar, which searches a [`TextEditor`](crate::views::TextEditor) and replaces matches.
+
+use std::{ops::Range, time::Duration};
+
+use floem_reactive::{create_effect, create_rw_signal, RwSignal};
+#[cfg(feature = "regex")]
+use regex::{Regex, RegexBuilder};
+
+use crate::{
+    debounce::DebounceExt,
+    event::Event,
+    id::Id,
+    keyboard::{Key, ModifiersState, NamedKey},
+    style_class,
+    view::{View, ViewData},
+    views::{h_stack, label, Decorators, Item},
+};
+
+use super::{button, text_input};
+
+style_class!(pub FindBarClass);
+style_class!(pub FindToggleClass);
+
+/// How long the query has to stay the same before it's searched for.
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// A request from the find bar to the searched editor.
+pub(crate) enum FindAction {
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+}
+
+/// The state of a search, shared by a [`find_bar`] and the
+/// [`TextEditor`](crate::views::TextEditor) it searches, see
+/// [`TextEditor::find`](crate::views::TextEditor::find).
+#[derive(Clone, Copy)]
+pub struct FindState {
+    /// The text or pattern searched for.
+    pub query: RwSignal<String>,
+    /// What [`FindState::replace`] replaces matches with. With [`FindState::regex`], it can
+    /// refer to groups like `$1`.
+    pub replacement: RwSignal<String>,
+    pub case_sensitive: RwSignal<bool>,
+    /// Whether the query is a regular expression. Regular expressions need the `regex`
+    /// feature, without which the query is always searched for as plain text.
+    pub regex: RwSignal<bool>,
+    /// Whether the find bar is shown.
+    pub visible: RwSignal<bool>,
+    /// The query after it stopped changing, which is what's searched for.
+    pub(crate) debounced_query: RwSignal<String>,
+    pub(crate) matches: RwSignal<Vec<Range<usize>>>,
+    pub(crate) current: RwSignal<Option<usize>>,
+    /// The editor which is searched.
+    pub(crate) target: RwSignal<Option<Id>>,
+}
+
+impl FindState {
+    pub fn new() -> Self {
+        let query = create_rw_signal(String::new());
+        let debounced_query = create_rw_signal(String::new());
+        let debounced = query.debounced(SEARCH_DEBOUNCE);
+        create_effect(move |_| debounced_query.set(debounced.get()));
+        Self {
+            query,
+            replacement: create_rw_signal(String::new()),
+            case_sensitive: create_rw_signal(false),
+            regex: create_rw_signal(false),
+            visible: create_rw_signal(false),
+            debounced_query,
+            matches: create_rw_signal(Vec::new()),
+            current: create_rw_signal(None),
+            target: create_rw_signal(None),
+        }
+    }
+
+    /// The number of matches.
+    pub fn match_count(&self) -> usize {
+        self.matches.with(|matches| matches.len())
+    }
+
+    /// The index of the selected match.
+    pub fn current_match(&self) -> Option<usize> {
+        self.current.get()
+    }
+
+    /// Selects the next match after the caret, wrapping around to the first one.
+    pub fn next(&self) {
+        self.send(FindAction::Next);
+    }
+
+    /// Selects the match before the caret, wrapping around to the last one.
+    pub fn previous(&self) {
+        self.send(FindAction::Previous);
+    }
+
+    /// Replaces the selected match and selects the next one.
+    pub fn replace(&self) {
+        self.send(FindAction::Replace);
+    }
+
+    /// Replaces every match, in a single undo step.
+    pub fn replace_all(&self) {
+        self.send(FindAction::ReplaceAll);
+    }
+
+    fn send(&self, action: FindAction) {
+        if let Some(target) = self.target.get_untracked() {
+            target.update_state(action);
+        }
+    }
+
+    /// The pattern the query is searched with, which is `None` when the query is empty or isn't
+    /// a valid regular expression.
+    #[cfg(feature = "regex")]
+    pub(crate) fn pattern(&self) -> Option<Pattern> {
+        let query = self.debounced_query.get();
+        if query.is_empty() {
+            return None;
+        }
+        let pattern = if self.regex.get() {
+            query
+        } else {
+            regex::escape(&query)
+        };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(!self.case_sensitive.get())
+            .multi_line(true)
+            .build()
+            .ok()?;
+        Some(Pattern {
+            regex,
+            expand: self.regex.get_untracked(),
+        })
+    }
+
+    /// The pattern the query is searched with, which is `None` when the query is empty.
+    #[cfg(not(feature = "regex"))]
+    pub(crate) fn pattern(&self) -> Option<Pattern> {
+        let query = self.debounced_query.get();
+        if query.is_empty() {
+            return None;
+        }
+        Some(Pattern {
+            text: query,
+            case_sensitive: self.case_sensitive.get(),
+        })
+    }
+
+    /// What `matched` is replaced with, which expands the groups of a regex.
+    pub(crate) fn replacement_for(&self, pattern: &Pattern, matched: &str) -> String {
+        pattern.replace(matched, self.replacement.get_untracked())
+    }
+}
+
+impl Default for FindState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a query is searched with. Without the `regex` feature, the query is searched for as
+/// plain text, and only ASCII letters are matched regardless of their case.
+pub(crate) struct Pattern {
+    #[cfg(feature = "regex")]
+    regex: Regex,
+    /// Whether the groups of the regex are expanded in replacements.
+    #[cfg(feature = "regex")]
+    expand: bool,
+    #[cfg(not(feature = "regex"))]
+    text: String,
+    #[cfg(not(feature = "regex"))]
+    case_sensitive: bool,
+}
+
+impl Pattern {
+    /// What `matched` is replaced with by `replacement`.
+    #[cfg(feature = "regex")]
+    fn replace(&self, matched: &str, replacement: String) -> String {
+        if self.expand {
+            self.regex
+                .replace(matched, replacement.as_str())
+                .into_owned()
+        } else {
+            replacement
+        }
+    }
+
+    /// What `matched` is replaced with by `replacement`.
+    #[cfg(not(feature = "regex"))]
+    fn replace(&self, _matched: &str, replacement: String) -> String {
+        replacement
+    }
+}
+
+/// The ranges of the non-empty matches of `pattern` in `text`.
+#[cfg(feature = "regex")]
+pub(crate) fn find_matches(text: &str, pattern: &Pattern) -> Vec<Range<usize>> {
+    pattern
+        .regex
+        .find_iter(text)
+        .map(|found| found.range())
+        .filter(|range| !range.is_empty())
+        .collect()
+}
+
+/// The ranges of the matches of `pattern` in `text`, which don't overlap.
+#[cfg(not(feature = "regex"))]
+pub(crate) fn find_matches(text: &str, pattern: &Pattern) -> Vec<Range<usize>> {
+    let query = pattern.text.as_bytes();
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= text.len() {
+        let candidate = &text.as_bytes()[start..start + query.len()];
+        let found = if pattern.case_sensitive {
+            candidate == query
+        } else {
+            candidate.eq_ignore_ascii_case(query)
+        };
+        // The query is valid UTF-8, so a match starting on a char boundary also ends on one
+        if found && text.is_char_boundary(start) {
+            matches.push(start..start + query.len());
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// A bar with inputs for the query and the replacement of `state`, toggles for case-sensitive
+/// and, with the `regex` feature, regex search, the number of matches, and buttons to move between and replace matches.
+///
+/// The bar is only shown while [`FindState::visible`] is `true`, and its query input gets focus
+/// when it's shown. In the query input, Enter selects the next match and Shift+Enter the
+/// previous one, and Escape hides the bar.
+///
+/// It's usually put on top of the editor it searches:
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::views::{scroll, stack, text_editor, Decorators};
+/// # use floem::widgets::{find_bar, FindState};
+/// let text = create_rw_signal(String::new());
+/// let find = FindState::new();
+/// stack((
+///     scroll(text_editor(text).find(find).style(|s| s.width_full())),
+///     find_bar(find).style(|s| s.absolute().inset_top(0.0).inset_right(0.0)),
+/// ));
+/// ```
+///
+/// The bar has the [`FindBarClass`], and the toggles the [`FindToggleClass`], with the
+/// `selected` selector applying while they're on.
+pub fn find_bar(state: FindState) -> impl View {
+    let toggle = |text: &'static str, on: RwSignal<bool>| {
+        // The toggle is the only item of a list, which is selected while it's on
+        let selection = create_rw_signal(None);
+        create_effect(move |_| selection.set(on.get().then_some(0)));
+        let child = label(move || text).class(FindToggleClass).style(move |s| {
+            selection.track();
+            s
+        });
+        Item {
+            data: ViewData::new(Id::next()),
+            index: 0,
+            selection,
+            child: Box::new(child),
+        }
+        .keyboard_navigatable()
+        .on_click_stop(move |_| on.update(|on| *on = !*on))
+    };
+    let hide = move |_: &Event| state.visible.set(false);
+
+    let query = text_input(state.query)
+        .placeholder("Find")
+        .on_key_down(
+            Key::Named(NamedKey::Enter),
+            ModifiersState::empty(),
+            move |_| state.next(),
+        )
+        .on_key_down(
+            Key::Named(NamedKey::Enter),
+            ModifiersState::SHIFT,
+            move |_| state.previous(),
+        )
+        .on_key_down(Key::Named(NamedKey::Escape), ModifiersState::empty(), hide);
+    let query_id = query.id();
+    create_effect(move |_| {
+        if state.visible.get() {
+            query_id.request_focus();
+        }
+    });
+
+    let replacement = text_input(state.replacement)
+        .placeholder("Replace")
+        .on_key_down(
+            Key::Named(NamedKey::Enter),
+            ModifiersState::empty(),
+            move |_| state.replace(),
+        )
+        .on_key_down(Key::Named(NamedKey::Escape), ModifiersState::empty(), hide);
+
+    let count = label(move || {
+        let total = state.match_count();
+        match state.current_match() {
+            Some(current) => format!("{} of {total}", current + 1),
+            None if total == 0 && !state.query.with(String::is_empty) => "No results".to_string(),
+            None => format!("{total} results"),
+        }
+    });
+
+    h_stack((
+        query,
+        toggle("Aa", state.case_sensitive),
+        #[cfg(feature = "regex")]
+        toggle(".*", state.regex),
+        count,
+        button(|| "↑").on_click_stop(move |_| state.previous()),
+        button(|| "↓").on_click_stop(move |_| state.next()),
+        replacement,
+        button(|| "Replace").on_click_stop(move |_| state.replace()),
+        button(|| "All").on_click_stop(move |_| state.replace_all()),
+        button(|| "×").on_click_stop(move |_| state.visible.set(false)),
+    ))
+    .class(FindBarClass)
+    .style(move |s| {
+        s.items_center()
+            .apply_if(!state.visible.get(), |s| s.hide())
+    })
+}
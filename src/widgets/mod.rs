@@ -4,9 +4,10 @@
 //!
 
 use crate::{
-    style::{Background, CursorStyle, Foreground, Style, Transition},
+    menu::{MenuBarClass, MenuBarItemClass, MnemonicClass},
+    style::{Background, CursorStyle, Foreground, InsetLeft, Style, StyleClass, Transition, Width},
     unit::{PxPct, UnitExt},
-    views::scroll,
+    views::{scroll, TextEditorClass},
     widgets::{self, slider::SliderClass},
 };
 use floem_peniko::Color;
@@ -39,11 +40,78 @@ pub use radio_button::*;
 mod text_input;
 pub use text_input::*;
 
+mod segmented_control;
+pub use segmented_control::*;
+
+mod progress;
+pub use progress::*;
+
+mod tree_view;
+pub use tree_view::*;
+
+mod data_table;
+pub use data_table::*;
+
+mod dropdown;
+pub use dropdown::*;
+
+mod find_bar;
+pub use find_bar::*;
+
 pub(crate) struct Theme {
     pub(crate) background: Color,
     pub(crate) style: Rc<Style>,
 }
 
+/// Registers the classes of the built-in widgets, so they can be looked up by name.
+pub(crate) fn register_classes() {
+    // Getting the reference of a class registers it
+    for class_ref in [
+        ButtonClass::class_ref,
+        CheckboxClass::class_ref,
+        LabeledCheckboxClass::class_ref,
+        ListClass::class_ref,
+        ListItemClass::class_ref,
+        RadioButtonClass::class_ref,
+        RadioButtonDotClass::class_ref,
+        RadioButtonDotSelectedClass::class_ref,
+        LabeledRadioButtonClass::class_ref,
+        SliderClass::class_ref,
+        slider::BarClass::class_ref,
+        slider::AccentBarClass::class_ref,
+        slider::FocusedHandleClass::class_ref,
+        TextInputClass::class_ref,
+        PlaceholderTextClass::class_ref,
+        TextEditorClass::class_ref,
+        ToggleButtonClass::class_ref,
+        TooltipClass::class_ref,
+        SegmentedControlClass::class_ref,
+        SegmentClass::class_ref,
+        SegmentIndicatorClass::class_ref,
+        ProgressBarClass::class_ref,
+        SpinnerClass::class_ref,
+        TreeViewClass::class_ref,
+        DisclosureClass::class_ref,
+        DataTableClass::class_ref,
+        TableHeaderClass::class_ref,
+        TableRowClass::class_ref,
+        TableCellClass::class_ref,
+        ColumnResizeHandleClass::class_ref,
+        DropdownClass::class_ref,
+        DropdownPopupClass::class_ref,
+        DropdownItemClass::class_ref,
+        FindBarClass::class_ref,
+        FindToggleClass::class_ref,
+        scroll::Handle::class_ref,
+        scroll::Track::class_ref,
+        MenuBarClass::class_ref,
+        MenuBarItemClass::class_ref,
+        MnemonicClass::class_ref,
+    ] {
+        class_ref();
+    }
+}
+
 pub(crate) fn default_theme() -> Theme {
     let border = Color::rgb8(140, 140, 140);
 
@@ -207,6 +275,31 @@ pub(crate) fn default_theme() -> Theme {
 
     const FONT_SIZE: f32 = 12.0;
 
+    let segmented_control_style = Style::new()
+        .background(Color::rgb8(228, 228, 228))
+        .border_radius(border_radius)
+        .padding(2.0)
+        .apply(focus_style.clone());
+
+    let segment_style = Style::new()
+        .padding_vert(padding)
+        .padding_horiz(padding * 2.0)
+        .border_radius(border_radius)
+        .justify_center()
+        .items_center()
+        .color(Color::rgb8(80, 80, 80))
+        .hover(|s| s.color(Color::BLACK))
+        .selected(|s| s.color(Color::BLACK))
+        .disabled(|s| s.color(Color::GRAY).hover(|s| s.color(Color::GRAY)));
+
+    let segment_indicator_style = Style::new()
+        .background(Color::WHITE)
+        .border_radius(border_radius)
+        .box_shadow_blur(2.0)
+        .box_shadow_color(Color::BLACK.with_alpha_factor(0.2))
+        .transition(InsetLeft, Transition::linear(0.15))
+        .transition(Width, Transition::linear(0.15));
+
     let input_style = Style::new()
         .background(Color::WHITE)
         .hover(|s| s.background(light_hover_bg_color))
@@ -220,6 +313,15 @@ pub(crate) fn default_theme() -> Theme {
                 .color(Color::GRAY)
         });
 
+    let dropdown_style = Style::new()
+        .background(Color::WHITE)
+        .hover(|s| s.background(light_hover_bg_color))
+        .apply(border_style.clone())
+        .apply(focus_style.clone())
+        .padding(padding)
+        .min_width(100.0)
+        .cursor(CursorStyle::Pointer);
+
     let item_focused_style = Style::new().selected(|s| {
         s.background(selected_bg_color)
             .hover(|s| s.background(selected_hover_bg_color))
@@ -242,6 +344,9 @@ pub(crate) fn default_theme() -> Theme {
         .class(RadioButtonClass, |_| radio_button_style)
         .class(RadioButtonDotClass, |_| radio_button_dot_style)
         .class(LabeledRadioButtonClass, |_| labeled_radio_button_style)
+        .class(TextEditorClass, |_| {
+            input_style.clone().padding(8.0).cursor_color(Color::BLACK)
+        })
         .class(TextInputClass, |_| input_style)
         .class(ButtonClass, |_| button_style)
         .class(scroll::Handle, |s| {
@@ -256,12 +361,18 @@ pub(crate) fn default_theme() -> Theme {
             s.hover(|s| s.background(Color::rgba8(166, 166, 166, 30)))
         })
         .class(ToggleButtonClass, |_| toggle_button_style)
+        .class(SegmentedControlClass, |_| segmented_control_style)
+        .class(SegmentClass, |_| segment_style)
+        .class(SegmentIndicatorClass, |_| segment_indicator_style)
         .class(slider::BarClass, |s| {
             s.background(Color::BLACK).border_radius(100.pct())
         })
         .class(slider::AccentBarClass, |s| {
             s.background(Color::GREEN).border_radius(100.pct())
         })
+        .class(slider::FocusedHandleClass, |s| {
+            s.outline(2.0).outline_color(Color::rgb8(114, 74, 140))
+        })
         .class(SliderClass, |s| {
             s.set(Foreground, Color::DARK_GRAY)
                 .height(15)
@@ -269,6 +380,80 @@ pub(crate) fn default_theme() -> Theme {
                 .set(slider::EdgeAlign, true)
                 .set(slider::HandleRadius, PxPct::Pct(100.))
         })
+        .class(ProgressBarClass, |s| {
+            s.set(ProgressTrackColor, Color::rgb8(220, 220, 220))
+                .set(Foreground, Color::rgb8(114, 74, 140))
+                .border_radius(100.pct())
+                .height(6)
+                .width(100)
+        })
+        .class(DisclosureClass, |s| {
+            s.size(12, 12)
+                .margin_right(4)
+                .color(Color::rgb8(100, 100, 100))
+        })
+        .class(TableHeaderClass, |s| {
+            s.font_bold()
+                .border_bottom(1.0)
+                .border_color(border)
+                .background(Color::rgb8(240, 240, 240))
+        })
+        .class(TableRowClass, |s| s.hover(|s| s.background(hover_bg_color)))
+        .class(TableCellClass, |s| {
+            s.padding_horiz(padding).padding_vert(3.0)
+        })
+        .class(ColumnResizeHandleClass, |s| {
+            s.width(5.0)
+                .hover(|s| s.background(Color::rgba8(114, 74, 140, 120)))
+        })
+        .class(DropdownClass, |_| dropdown_style)
+        .class(DropdownPopupClass, |s| {
+            s.max_height(300.0)
+                .border(1.0)
+                .border_color(border)
+                .border_radius(border_radius)
+                .background(Color::WHITE)
+                .box_shadow_blur(3.0)
+        })
+        .class(DropdownItemClass, |s| {
+            s.padding(padding)
+                .width_full()
+                .selected(|s| s.background(selected_bg_color))
+        })
+        .class(FindBarClass, |s| {
+            s.padding(padding)
+                .gap(padding, 0.0)
+                .border(1.0)
+                .border_color(border)
+                .border_radius(border_radius)
+                .background(Color::WHITE)
+                .box_shadow_blur(3.0)
+        })
+        .class(FindToggleClass, |s| {
+            s.padding_horiz(padding)
+                .border_radius(border_radius)
+                .hover(|s| s.background(hover_bg_color))
+                .selected(|s| s.background(selected_bg_color))
+        })
+        .class(SpinnerClass, |s| {
+            s.set(ProgressTrackColor, Color::rgb8(220, 220, 220))
+                .set(Foreground, Color::rgb8(114, 74, 140))
+                .size(20, 20)
+        })
+        .class(MenuBarClass, |s| {
+            s.background(Color::rgb8(240, 240, 240))
+                .border_bottom(1.0)
+                .border_color(Color::rgb8(220, 220, 220))
+        })
+        .class(MenuBarItemClass, |s| {
+            s.padding_horiz(10.0)
+                .padding_vert(padding)
+                .cursor(CursorStyle::Default)
+                .hover(|s| s.background(hover_bg_color))
+                .active(|s| s.background(selected_bg_color))
+                .disabled(|s| s.color(Color::rgb8(160, 160, 160)))
+        })
+        .class(MnemonicClass, |s| s.border_bottom(1.0))
         .class(PlaceholderTextClass, |s| {
             s.color(Color::rgba8(158, 158, 158, 30))
                 .font_size(FONT_SIZE)
@@ -0,0 +1,360 @@
+//! Progress bars and spinners, for showing that work is in progress.
+
+use std::{f64::consts::PI, time::Instant};
+
+use floem_peniko::Color;
+use floem_reactive::create_effect;
+use floem_renderer::Renderer;
+use kurbo::{Arc, Circle, Rect, Vec2};
+
+use crate::{
+    animate::{Easing, EasingFn, EasingMode},
+    clock,
+    id::Id,
+    prop, prop_extracter,
+    style::{BorderRadius, Foreground},
+    style_class,
+    unit::PxPct,
+    view::{View, ViewData},
+    views::Decorators,
+};
+
+style_class!(pub ProgressBarClass);
+style_class!(pub SpinnerClass);
+
+prop!(pub ProgressTrackColor: Option<Color> {} = None);
+prop!(pub SpinnerThickness: PxPct {} = PxPct::Pct(12.));
+
+prop_extracter! {
+    ProgressBarStyle {
+        fill: Foreground,
+        track: ProgressTrackColor,
+        border_radius: BorderRadius,
+    }
+}
+
+prop_extracter! {
+    SpinnerStyle {
+        fill: Foreground,
+        track: ProgressTrackColor,
+        thickness: SpinnerThickness,
+    }
+}
+
+/// How long the indicator of an indeterminate progress bar takes to sweep across it.
+const SWEEP_SECS: f64 = 1.5;
+/// The length of the indicator of an indeterminate progress bar, relative to the bar.
+const SWEEP_LENGTH: f64 = 0.3;
+/// How long a spinner takes to grow and shrink its arc.
+const SPIN_SECS: f64 = 1.4;
+
+fn ease_in_out() -> Easing {
+    Easing {
+        mode: EasingMode::InOut,
+        func: EasingFn::Cubic,
+    }
+}
+
+/// The position within a cycle of `period` seconds, from 0 to 1.
+fn cycle(started: Instant, period: f64) -> f64 {
+    (clock::now()
+        .saturating_duration_since(started)
+        .as_secs_f64()
+        / period)
+        .fract()
+}
+
+enum ProgressBarUpdate {
+    Value(f64),
+    Indeterminate(bool),
+}
+
+/// A progress bar. See [`progress_bar`].
+pub struct ProgressBar {
+    data: ViewData,
+    value: f64,
+    indeterminate: bool,
+    /// When the indeterminate animation started.
+    started: Option<Instant>,
+    style: ProgressBarStyle,
+}
+
+/// A horizontal bar which is filled according to `value`, from 0 for empty to 1 for full.
+///
+/// With [`ProgressBar::indeterminate`], the value is ignored and an indicator sweeps across the
+/// bar instead, for work of unknown length. The animation only runs while the bar is painted,
+/// so it pauses while the bar is hidden or scrolled out of view.
+///
+/// The bar has the [`ProgressBarClass`]. The [`ProgressTrackColor`] is the color of the empty
+/// bar, and the [`Foreground`] is the color of the filled part, which has the
+/// [`BorderRadius`] of the bar.
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::widgets::progress_bar;
+/// let downloaded = create_rw_signal(0.25);
+/// progress_bar(move || downloaded.get());
+/// ```
+pub fn progress_bar(value: impl Fn() -> f64 + 'static) -> ProgressBar {
+    let id = Id::next();
+    create_effect(move |_| {
+        id.update_state(ProgressBarUpdate::Value(value()));
+    });
+    ProgressBar {
+        data: ViewData::new(id),
+        value: 0.0,
+        indeterminate: false,
+        started: None,
+        style: Default::default(),
+    }
+    .class(ProgressBarClass)
+}
+
+impl ProgressBar {
+    /// Shows an animated indicator instead of the value while `indeterminate` returns `true`.
+    pub fn indeterminate(self, indeterminate: impl Fn() -> bool + 'static) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            id.update_state(ProgressBarUpdate::Indeterminate(indeterminate()));
+        });
+        self
+    }
+}
+
+impl View for ProgressBar {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "ProgressBar".into()
+    }
+
+    fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(update) = state.downcast::<ProgressBarUpdate>() {
+            match *update {
+                ProgressBarUpdate::Value(value) => self.value = value.clamp(0., 1.),
+                ProgressBarUpdate::Indeterminate(indeterminate) => {
+                    self.indeterminate = indeterminate;
+                    self.started = None;
+                }
+            }
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn style(&mut self, cx: &mut crate::context::StyleCx<'_>) {
+        if self.style.read(cx) {
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn paint(&mut self, cx: &mut crate::context::PaintCx) {
+        let rect = cx.get_content_rect(self.id());
+        let radius = match self.style.border_radius() {
+            PxPct::Px(px) => px,
+            PxPct::Pct(pct) => rect.height().min(rect.width()) / 2. * (pct / 100.),
+        };
+        let track = rect.to_rounded_rect(radius);
+        if let Some(color) = self.style.track() {
+            cx.fill(&track, color, 0.);
+        }
+
+        let (start, end) = if self.indeterminate {
+            let started = *self.started.get_or_insert_with(clock::now);
+            // The indicator enters on the left and leaves on the right
+            let t = ease_in_out().ease(cycle(started, SWEEP_SECS)) * (1. + SWEEP_LENGTH);
+            let id = self.id();
+            cx.app_state.schedule_paint(id);
+            (t - SWEEP_LENGTH, t)
+        } else {
+            (0., self.value)
+        };
+        let Some(color) = self.style.fill() else {
+            return;
+        };
+        let fill = Rect::new(
+            rect.x0 + rect.width() * start.max(0.),
+            rect.y0,
+            rect.x0 + rect.width() * end.min(1.),
+            rect.y1,
+        );
+        if fill.width() > 0. {
+            cx.save();
+            cx.clip(&track);
+            cx.fill(&fill.to_rounded_rect(radius), color, 0.);
+            cx.restore();
+        }
+    }
+}
+
+/// An indeterminate circular spinner. See [`spinner`].
+pub struct Spinner {
+    data: ViewData,
+    /// When the animation started.
+    started: Option<Instant>,
+    style: SpinnerStyle,
+}
+
+/// A circular spinner for loading states, with an arc which spins around a ring.
+///
+/// Like an indeterminate [`progress_bar`], the animation only runs while the spinner is
+/// painted.
+///
+/// The spinner has the [`SpinnerClass`]. The ring is drawn in the [`ProgressTrackColor`] and the
+/// arc in the [`Foreground`] color, with a width of [`SpinnerThickness`], which is relative to
+/// the diameter when it's a percentage.
+pub fn spinner() -> Spinner {
+    Spinner {
+        data: ViewData::new(Id::next()),
+        started: None,
+        style: Default::default(),
+    }
+    .class(SpinnerClass)
+}
+
+impl View for Spinner {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "Spinner".into()
+    }
+
+    fn style(&mut self, cx: &mut crate::context::StyleCx<'_>) {
+        if self.style.read(cx) {
+            cx.app_state_mut().request_paint(self.id());
+        }
+    }
+
+    fn paint(&mut self, cx: &mut crate::context::PaintCx) {
+        let rect = cx.get_content_rect(self.id());
+        let diameter = rect.width().min(rect.height());
+        let thickness = match self.style.thickness() {
+            PxPct::Px(px) => px,
+            PxPct::Pct(pct) => diameter * (pct / 100.),
+        };
+        let radius = (diameter - thickness) / 2.;
+        if radius <= 0. {
+            return;
+        }
+        let center = rect.center();
+
+        if let Some(color) = self.style.track() {
+            cx.stroke(&Circle::new(center, radius), color, thickness);
+        }
+
+        let started = *self.started.get_or_insert_with(clock::now);
+        let id = self.id();
+        cx.app_state.schedule_paint(id);
+        let Some(color) = self.style.fill() else {
+            return;
+        };
+        // The arc grows during the first half of the cycle and shrinks during the second,
+        // while the whole arc keeps rotating
+        let t = cycle(started, SPIN_SECS);
+        let easing = ease_in_out();
+        let (head, tail) = if t < 0.5 {
+            (easing.ease(t * 2.) * 0.75, 0.)
+        } else {
+            (0.75, easing.ease(t * 2. - 1.) * 0.75)
+        };
+        let rotation = cycle(started, SPIN_SECS * 1.5) + t;
+        let arc = Arc {
+            center,
+            radii: Vec2::new(radius, radius),
+            start_angle: (rotation + tail) * 2. * PI - PI / 2.,
+            sweep_angle: (head - tail + 0.05) * 2. * PI,
+            x_rotation: 0.,
+        };
+        cx.stroke(&arc, color, thickness);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use floem_reactive::create_rw_signal;
+    use image::Rgba;
+    use kurbo::Size;
+
+    use super::{progress_bar, spinner};
+    use crate::{test_support::HeadlessWindow, views::Decorators};
+
+    const TRACK: Rgba<u8> = Rgba([220, 220, 220, 255]);
+    const FILL: Rgba<u8> = Rgba([114, 74, 140, 255]);
+
+    #[test]
+    fn bars_are_filled_up_to_the_value() {
+        let value = create_rw_signal(0.25);
+        let mut window = HeadlessWindow::new(Size::new(100.0, 10.0), move || {
+            progress_bar(move || value.get()).style(|s| s.height(10).border_radius(0))
+        });
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(20, 5), FILL);
+        assert_eq!(*image.get_pixel(30, 5), TRACK);
+
+        // Values outside of 0 to 1 are clamped
+        value.set(1.5);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(98, 5), FILL);
+        value.set(-1.0);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(1, 5), TRACK);
+    }
+
+    #[test]
+    fn indeterminate_bars_sweep_across() {
+        let indeterminate = create_rw_signal(true);
+        let mut window = HeadlessWindow::new(Size::new(100.0, 10.0), move || {
+            progress_bar(|| 0.25)
+                .indeterminate(move || indeterminate.get())
+                .style(|s| s.height(10).border_radius(0))
+        });
+        // The indicator starts out of the bar, on the left
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(1, 5), TRACK);
+        assert_eq!(*image.get_pixel(20, 5), TRACK);
+
+        // Halfway through the sweep, it's in the middle
+        window.advance(Duration::from_millis(750));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(20, 5), TRACK);
+        assert_eq!(*image.get_pixel(50, 5), FILL);
+        assert_eq!(*image.get_pixel(80, 5), TRACK);
+
+        indeterminate.set(false);
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(20, 5), FILL);
+        assert_eq!(*image.get_pixel(50, 5), TRACK);
+    }
+
+    #[test]
+    fn spinners_grow_their_arc_while_spinning() {
+        let mut window = HeadlessWindow::new(Size::new(40.0, 40.0), || {
+            spinner().style(|s| s.size(40, 40))
+        });
+        // The arc starts as a short dash at the top
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(21, 2), FILL);
+        assert_eq!(*image.get_pixel(37, 20), TRACK);
+        assert_eq!(*image.get_pixel(2, 20), TRACK);
+
+        // Halfway through the cycle, it's grown to most of the ring and rotated clockwise,
+        // leaving a gap on the left
+        window.advance(Duration::from_millis(700));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(37, 20), FILL);
+        assert_eq!(*image.get_pixel(20, 37), FILL);
+        assert_eq!(*image.get_pixel(2, 20), TRACK);
+    }
+}
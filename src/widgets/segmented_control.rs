@@ -0,0 +1,355 @@
+//! A segmented control, a row of options where exactly one is selected.
+
+use std::{fmt::Display, rc::Rc};
+
+use floem_reactive::{create_effect, create_rw_signal, RwSignal};
+use kurbo::Rect;
+
+use crate::{
+    event::{Event, EventListener},
+    id::Id,
+    keyboard::{Key, NamedKey},
+    style_class,
+    view::{View, ViewData},
+    views::{container, empty, h_stack_from_iter, text, Decorators, Item, Stack},
+    EventPropagation,
+};
+
+style_class!(pub SegmentedControlClass);
+style_class!(pub SegmentClass);
+style_class!(pub SegmentIndicatorClass);
+
+/// An option of a [`segmented_control`].
+pub struct Segment<T> {
+    value: T,
+    label: String,
+    disabled: Option<Box<dyn Fn() -> bool>>,
+}
+
+/// An option of a [`segmented_control`] for `value`, showing `label`.
+pub fn segment<T>(value: T, label: impl Display) -> Segment<T> {
+    Segment {
+        value,
+        label: label.to_string(),
+        disabled: None,
+    }
+}
+
+impl<T> Segment<T> {
+    /// Disables the segment while `disabled_fn` returns `true`, so it can't be selected.
+    pub fn disabled(mut self, disabled_fn: impl Fn() -> bool + 'static) -> Self {
+        self.disabled = Some(Box::new(disabled_fn));
+        self
+    }
+}
+
+enum SegmentedControlUpdate {
+    SelectionChanged,
+}
+
+/// A row of segments where the one matching a signal is selected. See [`segmented_control`].
+pub struct SegmentedControl {
+    data: ViewData,
+    child: Stack,
+}
+
+/// A horizontal row of `segments`, where the segment with the value of `selected` is selected.
+/// Clicking a segment sets `selected` to its value.
+///
+/// The selected segment is highlighted by an indicator, styled by [`SegmentIndicatorClass`],
+/// which slides to the selected segment when its `inset_left` and `width` have a transition.
+/// The segments are styled by [`SegmentClass`], with the `selected` selector applying to the
+/// selected one.
+///
+/// When the control has focus, the left and right arrow keys select the previous and next
+/// segment, and Home and End select the first and last one. Disabled segments are skipped.
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::widgets::{segment, segmented_control};
+/// #[derive(Clone, PartialEq)]
+/// enum Layout {
+///     List,
+///     Grid,
+///     Columns,
+/// }
+///
+/// let layout = create_rw_signal(Layout::List);
+/// segmented_control(
+///     [
+///         segment(Layout::List, "List"),
+///         segment(Layout::Grid, "Grid"),
+///         segment(Layout::Columns, "Columns").disabled(|| true),
+///     ],
+///     layout,
+/// );
+/// ```
+pub fn segmented_control<T>(
+    segments: impl IntoIterator<Item = Segment<T>>,
+    selected: RwSignal<T>,
+) -> SegmentedControl
+where
+    T: PartialEq + Clone + 'static,
+{
+    let id = Id::next();
+    let mut values = Vec::new();
+    let mut disabled = Vec::new();
+    let mut labels = Vec::new();
+    for segment in segments {
+        values.push(segment.value);
+        disabled.push(segment.disabled);
+        labels.push(segment.label);
+    }
+    let values = Rc::new(values);
+    let disabled = Rc::new(disabled);
+    let length = values.len();
+
+    let selection = create_rw_signal(None);
+    {
+        let values = values.clone();
+        create_effect(move |_| {
+            let index = selected.with(|selected| values.iter().position(|v| v == selected));
+            selection.set(index);
+            id.update_state(SegmentedControlUpdate::SelectionChanged);
+        });
+    }
+
+    let is_enabled = {
+        let disabled = disabled.clone();
+        move |index: usize| !disabled[index].as_ref().is_some_and(|disabled| disabled())
+    };
+    let select = {
+        let is_enabled = is_enabled.clone();
+        move |index: usize| {
+            if is_enabled(index) && selection.get_untracked() != Some(index) {
+                selected.set(values[index].clone());
+            }
+        }
+    };
+
+    // The layout of each segment, which the indicator is placed on
+    let rects = create_rw_signal(vec![Rect::ZERO; length]);
+    let indicator = empty().class(SegmentIndicatorClass).style(move |s| {
+        let rect = selection
+            .get()
+            .and_then(|index| rects.with(|rects| rects.get(index).copied()));
+        match rect {
+            Some(rect) => s
+                .absolute()
+                .inset_left(rect.x0)
+                .inset_top(rect.y0)
+                .width(rect.width())
+                .height(rect.height()),
+            None => s.hide(),
+        }
+    });
+
+    let segments = labels.into_iter().enumerate().map(|(index, label)| {
+        let disabled = disabled.clone();
+        let segment = container(text(label))
+            .class(SegmentClass)
+            .disabled(move || disabled[index].as_ref().is_some_and(|disabled| disabled()));
+        let select = select.clone();
+        Box::new(
+            Item {
+                data: ViewData::new(Id::next()),
+                index,
+                selection,
+                child: Box::new(segment),
+            }
+            .on_click_stop(move |_| select(index))
+            .on_resize(move |rect| rects.update(|rects| rects[index] = rect)),
+        ) as Box<dyn View>
+    });
+    let child =
+        h_stack_from_iter(std::iter::once(Box::new(indicator) as Box<dyn View>).chain(segments));
+
+    SegmentedControl {
+        data: ViewData::new(id),
+        child,
+    }
+    .class(SegmentedControlClass)
+    .keyboard_navigatable()
+    .on_event(EventListener::KeyDown, move |e| {
+        let Event::KeyDown(key_event) = e else {
+            return EventPropagation::Continue;
+        };
+        let current = selection.get_untracked();
+        let target = match key_event.key.logical_key {
+            Key::Named(NamedKey::Home) => (0..length).find(|&i| is_enabled(i)),
+            Key::Named(NamedKey::End) => (0..length).rev().find(|&i| is_enabled(i)),
+            Key::Named(NamedKey::ArrowLeft) => {
+                let end = current.unwrap_or(length);
+                (0..end).rev().find(|&i| is_enabled(i))
+            }
+            Key::Named(NamedKey::ArrowRight) => {
+                let start = current.map_or(0, |i| i + 1);
+                (start..length).find(|&i| is_enabled(i))
+            }
+            _ => return EventPropagation::Continue,
+        };
+        if let Some(index) = target {
+            select(index);
+        }
+        EventPropagation::Stop
+    })
+}
+
+impl View for SegmentedControl {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "SegmentedControl".into()
+    }
+
+    fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(change) = state.downcast::<SegmentedControlUpdate>() {
+            match *change {
+                SegmentedControlUpdate::SelectionChanged => {
+                    cx.app_state_mut().request_style_recursive(self.id())
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, time::Duration};
+
+    use floem_reactive::{create_rw_signal, RwSignal};
+    use image::Rgba;
+    use kurbo::{Point, Size};
+
+    use super::{segment, segmented_control, SegmentClass};
+    use crate::{
+        keyboard::{Key, ModifiersState, NamedKey},
+        test_support::HeadlessWindow,
+        views::Decorators,
+    };
+
+    /// A control with the segments 0 to 3 of 50px each, from x = 2, where 2 is disabled.
+    fn control_window() -> (HeadlessWindow, RwSignal<u32>, RwSignal<bool>) {
+        let signals = Rc::new(Cell::new(None));
+        let mut window = HeadlessWindow::new(Size::new(300.0, 100.0), {
+            let signals = signals.clone();
+            move || {
+                let selected = create_rw_signal(0);
+                let disabled = create_rw_signal(true);
+                signals.set(Some((selected, disabled)));
+                segmented_control(
+                    [
+                        segment(0, "Zero"),
+                        segment(1, "One"),
+                        segment(2, "Two").disabled(move || disabled.get()),
+                        segment(3, "Three"),
+                    ],
+                    selected,
+                )
+                .style(|s| s.class(SegmentClass, |s| s.width(50)))
+            }
+        });
+        window.update();
+        let (selected, disabled) = signals.get().unwrap();
+        (window, selected, disabled)
+    }
+
+    fn press(window: &mut HeadlessWindow, selected: RwSignal<u32>, key: NamedKey) -> u32 {
+        window.send_key(Key::Named(key), ModifiersState::empty());
+        window.update();
+        selected.get_untracked()
+    }
+
+    #[test]
+    fn clicking_a_segment_selects_it() {
+        let (mut window, selected, _) = control_window();
+        window.click(Point::new(77.0, 10.0));
+        window.update();
+        assert_eq!(selected.get_untracked(), 1);
+        window.click(Point::new(177.0, 10.0));
+        window.update();
+        assert_eq!(selected.get_untracked(), 3);
+        // Disabled segments can't be selected
+        window.click(Point::new(127.0, 10.0));
+        window.update();
+        assert_eq!(selected.get_untracked(), 3);
+    }
+
+    #[test]
+    fn arrow_keys_skip_disabled_segments() {
+        let (mut window, selected, disabled) = control_window();
+        // Clicking the control focuses it
+        window.click(Point::new(27.0, 10.0));
+        window.update();
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowLeft), 0);
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowRight), 1);
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowRight), 3);
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowRight), 3);
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowLeft), 1);
+
+        disabled.set(false);
+        window.update();
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowRight), 2);
+    }
+
+    #[test]
+    fn home_and_end_select_the_first_and_last_enabled_segments() {
+        let (mut window, selected, disabled) = control_window();
+        window.click(Point::new(77.0, 10.0));
+        window.update();
+        assert_eq!(press(&mut window, selected, NamedKey::End), 3);
+        assert_eq!(press(&mut window, selected, NamedKey::Home), 0);
+
+        // Without a selected segment, the arrow keys start from the ends
+        selected.set(7);
+        window.update();
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowLeft), 3);
+        selected.set(7);
+        disabled.set(false);
+        window.update();
+        assert_eq!(press(&mut window, selected, NamedKey::ArrowRight), 0);
+    }
+
+    #[test]
+    fn the_indicator_follows_the_selection() {
+        let white = Rgba([255, 255, 255, 255]);
+        let background = Rgba([228, 228, 228, 255]);
+        let (mut window, selected, _) = control_window();
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(27, 4), white);
+        assert_eq!(*image.get_pixel(177, 4), background);
+
+        selected.set(3);
+        window.update();
+        window.advance(Duration::from_millis(200));
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(27, 4), background);
+        assert_eq!(*image.get_pixel(177, 4), white);
+
+        // Nothing is highlighted without a selected segment
+        selected.set(7);
+        window.update();
+        let image = window.render().unwrap();
+        assert_eq!(*image.get_pixel(177, 4), background);
+    }
+}
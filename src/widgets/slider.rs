@@ -8,7 +8,7 @@ use kurbo::{Circle, Point, RoundedRect};
 
 use crate::{
     prop, prop_extracter,
-    style::{Background, BorderRadius, Foreground, Height},
+    style::{Background, BorderRadius, Fill, Foreground, Height, Outline, OutlineColor},
     style_class,
     unit::{PxPct, PxPctAuto},
     view::{View, ViewData},
@@ -34,6 +34,9 @@ prop_extracter! {
 style_class!(pub SliderClass);
 style_class!(pub BarClass);
 style_class!(pub AccentBarClass);
+/// The class of the handle of a [`RangeSlider`] moved by the arrow keys, whose [`Outline`] and
+/// [`OutlineColor`] are drawn around it while the slider has focus.
+style_class!(pub FocusedHandleClass);
 
 prop_extracter! {
     BarStyle {
@@ -44,6 +47,49 @@ prop_extracter! {
     }
 }
 
+prop_extracter! {
+    FocusedHandleStyle {
+        outline: Outline,
+        outline_color: OutlineColor,
+    }
+}
+
+impl SliderStyle {
+    /// The radius of the handles of a slider of `size`.
+    fn handle_radius_in(&self, size: taffy::prelude::Size<f32>) -> f64 {
+        match self.handle_radius() {
+            PxPct::Px(px) => px,
+            PxPct::Pct(pct) => (size.width.min(size.height) / 2.) as f64 * (pct / 100.),
+        }
+    }
+
+    /// The horizontal extent of the base bar of a slider of `width`, with handles of `radius`.
+    fn bar_extent(&self, width: f32, radius: f64) -> (f64, f64) {
+        if self.edge_align() {
+            (0., width as f64)
+        } else {
+            (radius, width as f64 - radius)
+        }
+    }
+}
+
+impl BarStyle {
+    /// The bar from `x0` to `x1`, centered vertically in a slider of `size`.
+    fn bar(&self, size: taffy::prelude::Size<f32>, x0: f64, x1: f64) -> RoundedRect {
+        let height = match self.height() {
+            PxPctAuto::Px(px) => px,
+            PxPctAuto::Pct(pct) => size.height as f64 * (pct / 100.),
+            PxPctAuto::Auto => size.height as f64,
+        };
+        let radius = match self.border_radius() {
+            PxPct::Px(px) => px,
+            PxPct::Pct(pct) => height / 2. * (pct / 100.),
+        };
+        let y0 = size.height as f64 / 2. - height / 2.;
+        kurbo::Rect::new(x0, y0, x1, y0 + height).to_rounded_rect(radius)
+    }
+}
+
 /// A slider. See [`slider`]
 pub struct Slider {
     data: ViewData,
@@ -245,61 +291,15 @@ impl View for Slider {
 
         self.size = layout.size;
 
-        let circle_radius = match self.style.handle_radius() {
-            PxPct::Px(px) => px as f32,
-            PxPct::Pct(pct) => self.size.width.min(self.size.height) / 2. * (pct as f32 / 100.),
-        };
-        let circle_point = Point::new(self.handle_center() as f64, (self.size.height / 2.) as f64);
-        self.handle = crate::kurbo::Circle::new(circle_point, circle_radius as f64);
-
-        let base_bar_height = match self.base_bar_style.height() {
-            PxPctAuto::Px(px) => px,
-            PxPctAuto::Pct(pct) => self.size.height as f64 * (pct / 100.),
-            PxPctAuto::Auto => self.size.height as f64,
-        };
-        let accent_bar_height = match self.accent_bar_style.height() {
-            PxPctAuto::Px(px) => px,
-            PxPctAuto::Pct(pct) => self.size.height as f64 * (pct / 100.),
-            PxPctAuto::Auto => self.size.height as f64,
-        };
-
-        let base_bar_radius = match self.base_bar_style.border_radius() {
-            PxPct::Px(px) => px,
-            PxPct::Pct(pct) => base_bar_height / 2. * (pct / 100.),
-        };
-        let accent_bar_radius = match self.accent_bar_style.border_radius() {
-            PxPct::Px(px) => px,
-            PxPct::Pct(pct) => accent_bar_height / 2. * (pct / 100.),
-        };
-
-        let mut base_bar_length = self.size.width as f64;
-        if !self.style.edge_align() {
-            base_bar_length -= self.handle.radius * 2.;
-        }
-
-        let base_bar_y_start = self.size.height as f64 / 2. - base_bar_height / 2.;
-        let accent_bar_y_start = self.size.height as f64 / 2. - accent_bar_height / 2.;
-
-        let bar_x_start = if self.style.edge_align() {
-            0.
-        } else {
-            self.handle.radius
-        };
+        self.handle.radius = self.style.handle_radius_in(self.size);
+        self.handle.center =
+            Point::new(self.handle_center() as f64, (self.size.height / 2.) as f64);
 
-        self.base_bar = kurbo::Rect::new(
-            bar_x_start,
-            base_bar_y_start,
-            bar_x_start + base_bar_length,
-            base_bar_y_start + base_bar_height,
-        )
-        .to_rounded_rect(base_bar_radius);
-        self.accent_bar = kurbo::Rect::new(
-            bar_x_start,
-            accent_bar_y_start,
-            self.handle_center() as f64,
-            accent_bar_y_start + accent_bar_height,
-        )
-        .to_rounded_rect(accent_bar_radius);
+        let (bar_x_start, bar_x_end) = self.style.bar_extent(self.size.width, self.handle.radius);
+        self.base_bar = self.base_bar_style.bar(self.size, bar_x_start, bar_x_end);
+        self.accent_bar =
+            self.accent_bar_style
+                .bar(self.size, bar_x_start, self.handle_center() as f64);
 
         self.prev_percent = self.percent;
 
@@ -307,15 +307,23 @@ impl View for Slider {
     }
 
     fn paint(&mut self, cx: &mut crate::context::PaintCx) {
+        let base_bar_fill = self
+            .base_bar_style
+            .color()
+            .unwrap_or(Fill::Solid(Color::BLACK));
         cx.fill(
             &self.base_bar,
-            self.base_bar_style.color().unwrap_or(Color::BLACK),
+            &base_bar_fill.to_brush(self.base_bar.rect()),
             0.,
         );
         cx.clip(&self.base_bar);
+        let accent_bar_fill = self
+            .accent_bar_style
+            .color()
+            .unwrap_or(Fill::Solid(Color::GREEN));
         cx.fill(
             &self.accent_bar,
-            self.accent_bar_style.color().unwrap_or(Color::GREEN),
+            &accent_bar_fill.to_brush(self.accent_bar.rect()),
             0.,
         );
 
@@ -356,3 +364,481 @@ impl Slider {
         self
     }
 }
+
+enum RangeSliderUpdate {
+    DisableEvents(bool),
+    Range(f32, f32),
+}
+
+/// One of the two handles of a [`RangeSlider`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Thumb {
+    Start,
+    End,
+}
+
+impl Thumb {
+    fn other(self) -> Self {
+        match self {
+            Thumb::Start => Thumb::End,
+            Thumb::End => Thumb::Start,
+        }
+    }
+}
+
+/// A slider with two handles. See [`range_slider`]
+pub struct RangeSlider {
+    data: ViewData,
+    onchangepct: Option<Box<dyn Fn((f32, f32))>>,
+    /// The handle being dragged.
+    held: Option<Thumb>,
+    /// The handle moved by the arrow keys.
+    focused: Thumb,
+    range: (f32, f32),
+    prev_range: (f32, f32),
+    step: Option<f32>,
+    swap: bool,
+    base_bar_style: BarStyle,
+    accent_bar_style: BarStyle,
+    focused_handle_style: FocusedHandleStyle,
+    handles: [Circle; 2],
+    base_bar: RoundedRect,
+    accent_bar: RoundedRect,
+    size: taffy::prelude::Size<f32>,
+    style: SliderStyle,
+    disable_events: bool,
+}
+
+/// **A reactive slider with two handles, selecting a range.**
+///
+/// `range` returns the start and end of the range as percent values between 0 and 100, and
+/// [`RangeSlider::on_change_pct`] is called with the new range when it's changed by the user.
+/// The start never goes past the end. By default a handle dragged into the other one stops
+/// there; with [`RangeSlider::swap_thumbs`] it moves past it, and the handles swap roles.
+///
+/// Pressing on the slider grabs the closest handle. While it's held, it follows the pointer
+/// even outside the slider. When the slider has focus, the arrow keys move the last handle
+/// which was grabbed, by the [`step`](RangeSlider::step) or 10%, and Tab and Shift+Tab move
+/// to the other handle before moving focus out of the slider.
+///
+/// The range slider has the [`SliderClass`] and is styled the same way as a [`slider`], with
+/// the accent bar drawn between the two handles. While it has focus, the handle moved by the
+/// arrow keys has a ring styled by the [`FocusedHandleClass`].
+/// ```rust
+/// # use floem::reactive::create_rw_signal;
+/// # use floem::widgets::slider::range_slider;
+/// let price = create_rw_signal((20., 80.));
+/// range_slider(move || price.get())
+///     .step(5.)
+///     .on_change_pct(move |range| price.set(range));
+/// ```
+pub fn range_slider(range: impl Fn() -> (f32, f32) + 'static) -> RangeSlider {
+    let id = crate::id::Id::next();
+    create_effect(move |_| {
+        let (start, end) = range();
+        id.update_state(RangeSliderUpdate::Range(start, end));
+    });
+    RangeSlider {
+        data: ViewData::new(id),
+        onchangepct: None,
+        held: None,
+        focused: Thumb::Start,
+        range: (0.0, 100.0),
+        prev_range: (0.0, 100.0),
+        step: None,
+        swap: false,
+        base_bar_style: Default::default(),
+        accent_bar_style: Default::default(),
+        focused_handle_style: Default::default(),
+        handles: Default::default(),
+        base_bar: Default::default(),
+        accent_bar: Default::default(),
+        size: Default::default(),
+        style: Default::default(),
+        disable_events: false,
+    }
+    .class(SliderClass)
+    .keyboard_navigatable()
+}
+
+impl View for RangeSlider {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "RangeSlider".into()
+    }
+
+    fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(update) = state.downcast::<RangeSliderUpdate>() {
+            match *update {
+                RangeSliderUpdate::DisableEvents(disable) => self.disable_events = disable,
+                RangeSliderUpdate::Range(start, end) => {
+                    let start = start.clamp(0., 100.);
+                    self.range = (start, end.clamp(start, 100.));
+                }
+            }
+            cx.request_layout(self.id());
+        }
+    }
+
+    fn event(
+        &mut self,
+        cx: &mut crate::context::EventCx,
+        _id_path: Option<&[crate::id::Id]>,
+        event: crate::event::Event,
+    ) -> EventPropagation {
+        if self.disable_events {
+            return EventPropagation::Continue;
+        }
+        let mut propagation = EventPropagation::Continue;
+        let range_changed = match event {
+            crate::event::Event::PointerDown(event) => {
+                cx.update_active(self.id());
+                cx.app_state_mut().request_layout(self.id());
+                let percent = self.percent_at(event.pos.x);
+                let thumb = self.closest_thumb(percent);
+                self.held = Some(thumb);
+                self.focused = thumb;
+                self.move_thumb(thumb, percent);
+                true
+            }
+            crate::event::Event::PointerMove(event) => match self.held {
+                Some(thumb) => {
+                    cx.app_state_mut().request_layout(self.id());
+                    self.move_thumb(thumb, self.percent_at(event.pos.x));
+                    true
+                }
+                None => false,
+            },
+            crate::event::Event::PointerUp(event) => match self.held.take() {
+                Some(thumb) => {
+                    cx.app_state_mut().request_layout(self.id());
+                    self.move_thumb(thumb, self.percent_at(event.pos.x));
+                    true
+                }
+                None => false,
+            },
+            crate::event::Event::FocusLost => {
+                self.held = None;
+                false
+            }
+            crate::event::Event::KeyDown(event) => {
+                let step = self.step.unwrap_or(10.);
+                let thumb = self.focused;
+                match event.key.logical_key {
+                    Key::Named(NamedKey::ArrowLeft) => {
+                        cx.app_state_mut().request_layout(self.id());
+                        self.move_thumb(thumb, self.value(thumb) - step);
+                        propagation = EventPropagation::Stop;
+                        true
+                    }
+                    Key::Named(NamedKey::ArrowRight) => {
+                        cx.app_state_mut().request_layout(self.id());
+                        self.move_thumb(thumb, self.value(thumb) + step);
+                        propagation = EventPropagation::Stop;
+                        true
+                    }
+                    Key::Named(NamedKey::Tab) => {
+                        // Tab goes from the start handle to the end one, and Shift+Tab back,
+                        // before focus leaves the slider
+                        let target = if event.modifiers.shift_key() {
+                            Thumb::Start
+                        } else {
+                            Thumb::End
+                        };
+                        if self.focused != target {
+                            self.focused = target;
+                            cx.app_state_mut().request_paint(self.id());
+                            propagation = EventPropagation::Stop;
+                        }
+                        false
+                    }
+                    _ => false,
+                }
+            }
+            _ => false,
+        };
+
+        if range_changed && self.range != self.prev_range {
+            self.prev_range = self.range;
+            if let Some(onchangepct) = &self.onchangepct {
+                onchangepct(self.range);
+            }
+        }
+        propagation
+    }
+
+    fn style(&mut self, cx: &mut crate::context::StyleCx<'_>) {
+        let style = cx.style();
+        let mut paint = false;
+
+        let base_bar_style = style.clone().apply_class(BarClass);
+        paint |= self.base_bar_style.read_style(cx, &base_bar_style);
+
+        let accent_bar_style = style.clone().apply_class(AccentBarClass);
+        paint |= self.accent_bar_style.read_style(cx, &accent_bar_style);
+
+        let focused_handle_style = style.apply_class(FocusedHandleClass);
+        paint |= self
+            .focused_handle_style
+            .read_style(cx, &focused_handle_style);
+        paint |= self.style.read(cx);
+        if paint {
+            cx.app_state_mut().request_paint(self.data.id());
+        }
+    }
+
+    fn compute_layout(&mut self, cx: &mut crate::context::ComputeLayoutCx) -> Option<kurbo::Rect> {
+        let layout = cx.get_layout(self.id()).unwrap();
+        self.size = layout.size;
+
+        let radius = self.style.handle_radius_in(self.size);
+        let y = (self.size.height / 2.) as f64;
+        self.handles = [self.range.0, self.range.1]
+            .map(|percent| Circle::new(Point::new(self.handle_center(percent, radius), y), radius));
+
+        let (bar_x_start, bar_x_end) = self.style.bar_extent(self.size.width, radius);
+        self.base_bar = self.base_bar_style.bar(self.size, bar_x_start, bar_x_end);
+        self.accent_bar = self.accent_bar_style.bar(
+            self.size,
+            self.handles[0].center.x,
+            self.handles[1].center.x,
+        );
+
+        None
+    }
+
+    fn paint(&mut self, cx: &mut crate::context::PaintCx) {
+        let base_bar_fill = self
+            .base_bar_style
+            .color()
+            .unwrap_or(Fill::Solid(Color::BLACK));
+        cx.fill(
+            &self.base_bar,
+            &base_bar_fill.to_brush(self.base_bar.rect()),
+            0.,
+        );
+        cx.clip(&self.base_bar);
+        let accent_bar_fill = self
+            .accent_bar_style
+            .color()
+            .unwrap_or(Fill::Solid(Color::GREEN));
+        cx.fill(
+            &self.accent_bar,
+            &accent_bar_fill.to_brush(self.accent_bar.rect()),
+            0.,
+        );
+
+        if let Some(color) = self.style.foreground() {
+            cx.clear_clip();
+            // The focused handle is painted last, so it's on top when the handles overlap
+            let focused = self.focused as usize;
+            cx.fill(&self.handles[1 - focused], color, 0.);
+            cx.fill(&self.handles[focused], color, 0.);
+        }
+
+        let ring_width = self.focused_handle_style.outline().0;
+        if cx.is_focused(self.id()) && ring_width > 0. {
+            cx.clear_clip();
+            let handle = self.handles[self.focused as usize];
+            let ring = Circle::new(handle.center, handle.radius + ring_width / 2.);
+            cx.stroke(&ring, self.focused_handle_style.outline_color(), ring_width);
+        }
+    }
+}
+
+impl RangeSlider {
+    fn value(&self, thumb: Thumb) -> f32 {
+        match thumb {
+            Thumb::Start => self.range.0,
+            Thumb::End => self.range.1,
+        }
+    }
+
+    fn radius(&self) -> f64 {
+        self.handles[0].radius
+    }
+
+    fn handle_center(&self, percent: f32, radius: f64) -> f64 {
+        let width = self.size.width as f64 - radius * 2.;
+        width * (percent as f64 / 100.) + radius
+    }
+
+    /// The percent value at `x`, which is clamped to the slider.
+    fn percent_at(&self, x: f64) -> f32 {
+        let radius = self.radius();
+        let width = self.size.width as f64 - radius * 2.;
+        if width <= 0. {
+            return 0.;
+        }
+        (((x - radius) / width * 100.) as f32).clamp(0., 100.)
+    }
+
+    fn closest_thumb(&self, percent: f32) -> Thumb {
+        let (start, end) = self.range;
+        let (to_start, to_end) = (percent - start, end - percent);
+        if to_start < 0. || to_start < to_end {
+            Thumb::Start
+        } else if to_end < 0. || to_end < to_start {
+            Thumb::End
+        } else if end >= 100. {
+            // On top of both handles, so pick the one which can move
+            Thumb::Start
+        } else {
+            Thumb::End
+        }
+    }
+
+    /// Moves `thumb` to `percent`, snapped to the step, keeping the start before the end.
+    fn move_thumb(&mut self, thumb: Thumb, percent: f32) {
+        let mut percent = percent.clamp(0., 100.);
+        if let Some(step) = self.step.filter(|step| *step > 0.) {
+            let snapped = ((percent / step).round() * step).min(100.);
+            // The end is reachable even when it isn't a multiple of the step
+            percent = if 100. - percent < (percent - snapped).abs() {
+                100.
+            } else {
+                snapped
+            };
+        }
+        let other = self.value(thumb.other());
+        let crossed = match thumb {
+            Thumb::Start => percent > other,
+            Thumb::End => percent < other,
+        };
+        let (thumb, percent) = if !crossed {
+            (thumb, percent)
+        } else if self.swap {
+            // The other handle takes this one's place, and this one continues as the other
+            let other_thumb = thumb.other();
+            if self.held == Some(thumb) {
+                self.held = Some(other_thumb);
+            }
+            if self.focused == thumb {
+                self.focused = other_thumb;
+            }
+            match thumb {
+                Thumb::Start => self.range.0 = other,
+                Thumb::End => self.range.1 = other,
+            }
+            (other_thumb, percent)
+        } else {
+            (thumb, other)
+        };
+        match thumb {
+            Thumb::Start => self.range.0 = percent,
+            Thumb::End => self.range.1 = percent,
+        }
+    }
+
+    /// Add an event handler to be run when the range is changed by the user, with the new start
+    /// and end.
+    ///
+    /// This does not run if the range is changed because of an outside signal.
+    pub fn on_change_pct(mut self, onchangepct: impl Fn((f32, f32)) + 'static) -> Self {
+        self.onchangepct = Some(Box::new(onchangepct));
+        self
+    }
+
+    /// Snaps the handles to multiples of `step` percent, or to 100 when it's closer. The arrow
+    /// keys move them by one step.
+    pub fn step(mut self, step: f32) -> Self {
+        self.step = Some(step);
+        self
+    }
+
+    /// When `swap` is `true`, a handle dragged past the other one moves on, and the two swap
+    /// places, instead of stopping at the other handle.
+    pub fn swap_thumbs(mut self, swap: bool) -> Self {
+        self.swap = swap;
+        self
+    }
+
+    pub fn disable_events(self, state: impl Fn() -> bool + 'static) -> Self {
+        let id = self.id();
+        create_effect(move |_| {
+            let state = state();
+            id.update_state(RangeSliderUpdate::DisableEvents(state));
+        });
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{range_slider, RangeSlider, Thumb};
+
+    fn slider(range: (f32, f32)) -> RangeSlider {
+        let mut slider = range_slider(move || range);
+        slider.range = range;
+        slider
+    }
+
+    #[test]
+    fn dragging_a_thumb_past_the_other_stops_at_it() {
+        let mut slider = slider((20., 60.));
+        slider.move_thumb(Thumb::Start, 80.);
+        assert_eq!(slider.range, (60., 60.));
+        slider.move_thumb(Thumb::End, 10.);
+        assert_eq!(slider.range, (60., 60.));
+    }
+
+    #[test]
+    fn dragging_a_thumb_past_the_other_swaps_them() {
+        let mut slider = slider((20., 60.)).swap_thumbs(true);
+        slider.held = Some(Thumb::Start);
+        slider.move_thumb(Thumb::Start, 80.);
+        assert_eq!(slider.range, (60., 80.));
+        assert_eq!(slider.held, Some(Thumb::End));
+        assert_eq!(slider.focused, Thumb::End);
+
+        slider.move_thumb(Thumb::End, 10.);
+        assert_eq!(slider.range, (10., 60.));
+        assert_eq!(slider.held, Some(Thumb::Start));
+    }
+
+    #[test]
+    fn pressing_between_the_thumbs_grabs_the_closest() {
+        let slider = slider((20., 60.));
+        assert_eq!(slider.closest_thumb(10.), Thumb::Start);
+        assert_eq!(slider.closest_thumb(30.), Thumb::Start);
+        assert_eq!(slider.closest_thumb(50.), Thumb::End);
+        assert_eq!(slider.closest_thumb(90.), Thumb::End);
+    }
+
+    #[test]
+    fn pressing_on_both_thumbs_grabs_the_one_which_can_move() {
+        // Halfway between, or on top of both, the end is grabbed as it can move up
+        assert_eq!(slider((20., 60.)).closest_thumb(40.), Thumb::End);
+        assert_eq!(slider((50., 50.)).closest_thumb(50.), Thumb::End);
+        assert_eq!(slider((0., 0.)).closest_thumb(0.), Thumb::End);
+        // Except at the end, where only the start can move
+        assert_eq!(slider((100., 100.)).closest_thumb(100.), Thumb::Start);
+    }
+
+    #[test]
+    fn thumbs_snap_to_the_step_and_the_ends() {
+        let mut slider = slider((0., 100.)).step(30.);
+        slider.move_thumb(Thumb::Start, 14.);
+        assert_eq!(slider.range.0, 0.);
+        slider.move_thumb(Thumb::Start, 16.);
+        assert_eq!(slider.range.0, 30.);
+        slider.move_thumb(Thumb::Start, -20.);
+        assert_eq!(slider.range.0, 0.);
+
+        // 100 isn't a multiple of the step, but is still reachable
+        slider.move_thumb(Thumb::End, 94.);
+        assert_eq!(slider.range.1, 90.);
+        slider.move_thumb(Thumb::End, 96.);
+        assert_eq!(slider.range.1, 100.);
+        slider.move_thumb(Thumb::End, 90.);
+        slider.move_thumb(Thumb::End, 90. + 30.);
+        assert_eq!(slider.range.1, 100.);
+    }
+}
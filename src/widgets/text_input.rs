@@ -12,6 +12,9 @@ pub fn text_input(buffer: RwSignal<String>) -> TextInput {
 }
 
 impl TextInput {
+    /// Shows `text` while the input is empty and no text is being composed with an input
+    /// method. The placeholder has the [`PlaceholderTextClass`], and can be styled with
+    /// [`Style::placeholder`](crate::style::Style::placeholder).
     pub fn placeholder(mut self, text: impl Into<String>) -> Self {
         self.placeholder_text = Some(text.into());
         self
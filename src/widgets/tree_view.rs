@@ -0,0 +1,531 @@
+//! A tree view, for hierarchical data like a file browser or an outline.
+
+use std::{collections::HashSet, hash::Hash};
+
+use floem_reactive::{create_effect, create_rw_signal, RwSignal};
+use kurbo::{Rect, Size};
+
+use crate::{
+    context::ComputeLayoutCx,
+    event::{Event, EventListener},
+    id::Id,
+    keyboard::{Key, NamedKey},
+    style_class,
+    view::{View, ViewData},
+    views::{
+        empty, h_stack, svg, virtual_stack, Decorators, Item, VirtualDirection, VirtualItemSize,
+        VirtualStack, VirtualVector,
+    },
+    EventPropagation,
+};
+
+use super::{ListClass, ListItemClass};
+
+style_class!(pub TreeViewClass);
+style_class!(pub DisclosureClass);
+
+/// How far each level of the tree is indented.
+const INDENT: f64 = 16.0;
+
+const EXPANDED_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 12 12"><polygon points="2,4 10,4 6,9" /></svg>"#;
+const COLLAPSED_SVG: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 12 12"><polygon points="4,2 9,6 4,10" /></svg>"#;
+
+/// A node of the data shown by a [`tree_view`].
+pub trait TreeNode: Clone + 'static {
+    type Key: Clone + Eq + Hash + 'static;
+
+    /// Identifies the node, which is used for the expanded and selected nodes.
+    fn key(&self) -> Self::Key;
+
+    /// Whether the node can be expanded. This decides if it has a disclosure triangle, so
+    /// it shouldn't have to load the children.
+    fn has_children(&self) -> bool;
+
+    /// The children of the node. This is only called while the node is expanded, so children
+    /// can be loaded lazily. It's called in a reactive context, so children which are loaded
+    /// in the background can be read from a signal.
+    fn children(&self) -> Vec<Self>;
+}
+
+/// A visible node of the tree.
+#[derive(Clone)]
+struct Row<T> {
+    node: T,
+    depth: usize,
+}
+
+enum TreeUpdate {
+    SelectionChanged,
+    ScrollToSelected,
+}
+
+/// A tree of expandable nodes. See [`tree_view`].
+pub struct TreeView<T: TreeNode> {
+    data: ViewData,
+    row_height: f64,
+    child_size: Size,
+    expanded: RwSignal<HashSet<T::Key>>,
+    selected: RwSignal<Option<T::Key>>,
+    /// The index of the selected row.
+    selection: RwSignal<Option<usize>>,
+    child: VirtualStack<Item, (usize, Row<T>)>,
+}
+
+impl<T: TreeNode> TreeView<T> {
+    /// The keys of the expanded nodes. Setting it expands and collapses nodes.
+    pub fn expanded(&self) -> RwSignal<HashSet<T::Key>> {
+        self.expanded
+    }
+
+    /// The key of the selected node. Setting it selects a node, which is only shown as
+    /// selected while its ancestors are expanded.
+    pub fn selected(&self) -> RwSignal<Option<T::Key>> {
+        self.selected
+    }
+
+    pub fn on_select(self, on_select: impl Fn(Option<T::Key>) + 'static) -> Self {
+        let selected = self.selected;
+        create_effect(move |_| {
+            let selected = selected.get();
+            on_select(selected);
+        });
+        self
+    }
+}
+
+fn flatten<T: TreeNode>(
+    nodes: Vec<T>,
+    depth: usize,
+    expanded: &HashSet<T::Key>,
+    rows: &mut im::Vector<Row<T>>,
+) {
+    for node in nodes {
+        let children =
+            (node.has_children() && expanded.contains(&node.key())).then(|| node.children());
+        rows.push_back(Row { node, depth });
+        if let Some(children) = children {
+            flatten(children, depth + 1, expanded, rows);
+        }
+    }
+}
+
+/// A tree of the nodes returned by `roots` and their descendants, where each node is shown as
+/// a row of `row_height` with the view returned by `view_fn`.
+///
+/// Only the rows which are scrolled into view are built, so it should be put in a
+/// [`scroll`](crate::views::scroll). Children are only loaded when their parent is expanded,
+/// see [`TreeNode::children`].
+///
+/// Nodes are expanded and collapsed by clicking their disclosure triangle, or by
+/// double-clicking them. When the tree has focus, the up and down arrow keys, Home and End move
+/// the selection, the right arrow key expands the selected node or moves to its first child,
+/// and the left arrow key collapses it or moves to its parent.
+///
+/// The tree has the [`ListClass`] and its rows the [`ListItemClass`], so it's styled like a
+/// list. The disclosure triangles have the [`DisclosureClass`].
+pub fn tree_view<T, V>(
+    row_height: f64,
+    roots: impl Fn() -> Vec<T> + 'static,
+    view_fn: impl Fn(T) -> V + 'static,
+) -> TreeView<T>
+where
+    T: TreeNode,
+    V: View + 'static,
+{
+    let id = Id::next();
+    let expanded: RwSignal<HashSet<T::Key>> = create_rw_signal(HashSet::new());
+    let selected: RwSignal<Option<T::Key>> = create_rw_signal(None);
+    let selection = create_rw_signal(None);
+    let rows = create_rw_signal(im::Vector::new());
+
+    create_effect(move |_| {
+        let mut new_rows = im::Vector::new();
+        expanded.with(|expanded| flatten(roots(), 0, expanded, &mut new_rows));
+        rows.set(new_rows);
+    });
+    // The selection is kept as a key, so it stays on the same node when rows are added or
+    // removed before it
+    create_effect(move |_| {
+        let index = selected.with(|selected| {
+            let selected = selected.as_ref()?;
+            rows.with(|rows| rows.iter().position(|row| &row.node.key() == selected))
+        });
+        if selection.get_untracked() != index {
+            selection.set(index);
+        }
+    });
+    create_effect(move |_| {
+        selection.track();
+        id.update_state(TreeUpdate::SelectionChanged);
+    });
+
+    let toggle = move |key: T::Key| {
+        expanded.update(|expanded| {
+            if !expanded.remove(&key) {
+                expanded.insert(key);
+            }
+        });
+    };
+
+    let stack = virtual_stack(
+        VirtualDirection::Vertical,
+        VirtualItemSize::Fixed(Box::new(move || row_height)),
+        move || rows.get().enumerate(),
+        // The index is part of the key, as the rows know their index for the selection
+        |(index, row): &(usize, Row<T>)| (*index, row.node.key()),
+        move |(index, row)| {
+            let key = row.node.key();
+            let disclosure = if row.node.has_children() {
+                let expanded_key = key.clone();
+                let is_expanded =
+                    move || expanded.with(|expanded| expanded.contains(&expanded_key));
+                let key = key.clone();
+                let disclosure = svg(move || {
+                    if is_expanded() {
+                        EXPANDED_SVG
+                    } else {
+                        COLLAPSED_SVG
+                    }
+                    .to_string()
+                })
+                .class(DisclosureClass)
+                .on_click_stop(move |_| toggle(key.clone()));
+                Box::new(disclosure) as Box<dyn View>
+            } else {
+                Box::new(empty().class(DisclosureClass))
+            };
+            let content = h_stack((disclosure, view_fn(row.node)))
+                .class(ListItemClass)
+                .style(move |s| {
+                    s.items_center()
+                        .height(row_height)
+                        .padding_left(row.depth as f64 * INDENT)
+                });
+            let expand_key = key.clone();
+            Item {
+                data: ViewData::new(Id::next()),
+                selection,
+                index,
+                child: Box::new(content),
+            }
+            .on_click_stop(move |_| selected.set(Some(key.clone())))
+            .on_double_click_stop(move |_| toggle(expand_key.clone()))
+            .style(|s| s.width_full())
+        },
+    )
+    .style(|s| s.flex_col());
+
+    let select_row = move |index: usize| {
+        if let Some(row) = rows.with_untracked(|rows| rows.get(index).cloned()) {
+            selected.set(Some(row.node.key()));
+            id.update_state(TreeUpdate::ScrollToSelected);
+        }
+    };
+
+    TreeView {
+        data: ViewData::new(id),
+        row_height,
+        child_size: Size::ZERO,
+        expanded,
+        selected,
+        selection,
+        child: stack,
+    }
+    .class(ListClass)
+    .class(TreeViewClass)
+    .keyboard_navigatable()
+    .on_event(EventListener::KeyDown, move |e| {
+        let Event::KeyDown(key_event) = e else {
+            return EventPropagation::Continue;
+        };
+        let length = rows.with_untracked(|rows| rows.len());
+        if length == 0 {
+            return EventPropagation::Continue;
+        }
+        let current = selection.get_untracked();
+        let current_row = current.and_then(|i| rows.with_untracked(|rows| rows.get(i).cloned()));
+        match key_event.key.logical_key {
+            Key::Named(NamedKey::Home) => select_row(0),
+            Key::Named(NamedKey::End) => select_row(length - 1),
+            Key::Named(NamedKey::ArrowUp) => match current {
+                Some(i) => select_row(i.saturating_sub(1)),
+                None => select_row(length - 1),
+            },
+            Key::Named(NamedKey::ArrowDown) => match current {
+                Some(i) => select_row((i + 1).min(length - 1)),
+                None => select_row(0),
+            },
+            Key::Named(NamedKey::ArrowRight) => {
+                let (Some(i), Some(row)) = (current, current_row) else {
+                    return EventPropagation::Continue;
+                };
+                if row.node.has_children() {
+                    let key = row.node.key();
+                    if expanded.with_untracked(|expanded| expanded.contains(&key)) {
+                        // Move to the first child, if it has any
+                        if rows.with_untracked(|rows| {
+                            rows.get(i + 1).is_some_and(|next| next.depth > row.depth)
+                        }) {
+                            select_row(i + 1);
+                        }
+                    } else {
+                        expanded.update(|expanded| {
+                            expanded.insert(key);
+                        });
+                    }
+                }
+            }
+            Key::Named(NamedKey::ArrowLeft) => {
+                let (Some(i), Some(row)) = (current, current_row) else {
+                    return EventPropagation::Continue;
+                };
+                let key = row.node.key();
+                if expanded.with_untracked(|expanded| expanded.contains(&key)) {
+                    expanded.update(|expanded| {
+                        expanded.remove(&key);
+                    });
+                } else if let Some(parent) =
+                    rows.with_untracked(|rows| (0..i).rev().find(|&j| rows[j].depth < row.depth))
+                {
+                    select_row(parent);
+                }
+            }
+            _ => return EventPropagation::Continue,
+        }
+        EventPropagation::Stop
+    })
+}
+
+impl<T: TreeNode> View for TreeView<T> {
+    fn view_data(&self) -> &ViewData {
+        &self.data
+    }
+
+    fn view_data_mut(&mut self) -> &mut ViewData {
+        &mut self.data
+    }
+
+    fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
+        for_each(&self.child);
+    }
+
+    fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
+        for_each(&mut self.child);
+    }
+
+    fn for_each_child_rev_mut<'a>(
+        &'a mut self,
+        for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
+    ) {
+        for_each(&mut self.child);
+    }
+
+    fn debug_name(&self) -> std::borrow::Cow<'static, str> {
+        "TreeView".into()
+    }
+
+    fn update(&mut self, cx: &mut crate::context::UpdateCx, state: Box<dyn std::any::Any>) {
+        if let Ok(change) = state.downcast::<TreeUpdate>() {
+            match *change {
+                TreeUpdate::SelectionChanged => {
+                    cx.app_state_mut().request_style_recursive(self.id())
+                }
+                TreeUpdate::ScrollToSelected => {
+                    if let Some(index) = self.selection.get_untracked() {
+                        let top = index as f64 * self.row_height;
+                        let rect =
+                            Rect::new(0.0, top, self.child_size.width, top + self.row_height);
+                        self.child.id().scroll_to(Some(rect));
+                    }
+                }
+            }
+        }
+    }
+
+    fn compute_layout(&mut self, cx: &mut ComputeLayoutCx) -> Option<Rect> {
+        // The child has no layout yet when the tree is laid out before its first style pass
+        if let Some(layout) = cx.app_state.get_layout(self.child.id()) {
+            self.child_size = Size::new(layout.size.width as f64, layout.size.height as f64);
+        }
+
+        cx.compute_view_layout(&mut self.child)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::HashSet, rc::Rc};
+
+    use floem_reactive::RwSignal;
+    use kurbo::{Point, Size};
+
+    use super::{flatten, tree_view, TreeNode};
+    use crate::{
+        keyboard::{Key, ModifiersState, NamedKey},
+        test_support::HeadlessWindow,
+        views::{scroll, text, Decorators},
+    };
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct Node {
+        name: &'static str,
+        children: Vec<Node>,
+    }
+
+    impl TreeNode for Node {
+        type Key = &'static str;
+
+        fn key(&self) -> Self::Key {
+            self.name
+        }
+
+        fn has_children(&self) -> bool {
+            !self.children.is_empty()
+        }
+
+        fn children(&self) -> Vec<Self> {
+            self.children.clone()
+        }
+    }
+
+    fn node(name: &'static str, children: Vec<Node>) -> Node {
+        Node { name, children }
+    }
+
+    /// `a` with the children `a1`, which has the child `a1x`, and `a2`, then `b`.
+    fn roots() -> Vec<Node> {
+        vec![
+            node(
+                "a",
+                vec![node("a1", vec![node("a1x", vec![])]), node("a2", vec![])],
+            ),
+            node("b", vec![]),
+        ]
+    }
+
+    fn rows(expanded: &[&'static str]) -> Vec<(&'static str, usize)> {
+        let expanded: HashSet<_> = expanded.iter().copied().collect();
+        let mut rows = im::Vector::new();
+        flatten(roots(), 0, &expanded, &mut rows);
+        rows.iter().map(|row| (row.node.name, row.depth)).collect()
+    }
+
+    #[test]
+    fn only_expanded_nodes_show_their_children() {
+        assert_eq!(rows(&[]), [("a", 0), ("b", 0)]);
+        assert_eq!(rows(&["a"]), [("a", 0), ("a1", 1), ("a2", 1), ("b", 0)]);
+        assert_eq!(
+            rows(&["a", "a1"]),
+            [("a", 0), ("a1", 1), ("a1x", 2), ("a2", 1), ("b", 0)]
+        );
+        // The children of a collapsed node are hidden, even when they're expanded
+        assert_eq!(rows(&["a1"]), [("a", 0), ("b", 0)]);
+        // Nodes without children can't be expanded
+        assert_eq!(rows(&["b"]), [("a", 0), ("b", 0)]);
+    }
+
+    /// A window with the tree of [`roots`] in rows of 20px.
+    struct TreeWindow {
+        window: HeadlessWindow,
+        expanded: RwSignal<HashSet<&'static str>>,
+        selected: RwSignal<Option<&'static str>>,
+    }
+
+    impl TreeWindow {
+        fn new() -> Self {
+            let signals = Rc::new(Cell::new(None));
+            let mut window = HeadlessWindow::new(Size::new(200.0, 200.0), {
+                let signals = signals.clone();
+                move || {
+                    let tree = tree_view(20.0, roots, |node| text(node.name));
+                    signals.set(Some((tree.expanded(), tree.selected())));
+                    scroll(tree).style(|s| s.size_full())
+                }
+            });
+            window.update();
+            let (expanded, selected) = signals.get().unwrap();
+            Self {
+                window,
+                expanded,
+                selected,
+            }
+        }
+
+        /// Selects the first row by clicking it, which focuses the tree.
+        fn select_first(&mut self) {
+            self.window.click(Point::new(100.0, 10.0));
+            self.window.update();
+            assert_eq!(self.selected.get_untracked(), Some("a"));
+        }
+
+        fn press(&mut self, key: NamedKey) -> Option<&'static str> {
+            self.window
+                .send_key(Key::Named(key), ModifiersState::empty());
+            self.window.update();
+            self.selected.get_untracked()
+        }
+
+        fn is_expanded(&self, key: &'static str) -> bool {
+            self.expanded
+                .with_untracked(|expanded| expanded.contains(key))
+        }
+    }
+
+    #[test]
+    fn arrow_keys_move_the_selection() {
+        let mut tree = TreeWindow::new();
+        // Without a selection, the arrow keys start from the first or last row
+        tree.window.click(Point::new(100.0, 10.0));
+        tree.selected.set(None);
+        tree.window.update();
+        assert_eq!(tree.press(NamedKey::ArrowUp), Some("b"));
+        tree.selected.set(None);
+        tree.window.update();
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("a"));
+
+        tree.expanded.set(["a"].into_iter().collect());
+        tree.window.update();
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("a1"));
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("a2"));
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("b"));
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("b"));
+        assert_eq!(tree.press(NamedKey::ArrowUp), Some("a2"));
+        assert_eq!(tree.press(NamedKey::Home), Some("a"));
+        assert_eq!(tree.press(NamedKey::ArrowUp), Some("a"));
+        assert_eq!(tree.press(NamedKey::End), Some("b"));
+    }
+
+    #[test]
+    fn right_arrow_expands_then_moves_to_the_first_child() {
+        let mut tree = TreeWindow::new();
+        tree.select_first();
+        assert_eq!(tree.press(NamedKey::ArrowRight), Some("a"));
+        assert!(tree.is_expanded("a"));
+        assert_eq!(tree.press(NamedKey::ArrowRight), Some("a1"));
+        assert_eq!(tree.press(NamedKey::ArrowRight), Some("a1"));
+        assert_eq!(tree.press(NamedKey::ArrowRight), Some("a1x"));
+        // Nodes without children stay as they are
+        assert_eq!(tree.press(NamedKey::ArrowRight), Some("a1x"));
+        assert!(!tree.is_expanded("a1x"));
+    }
+
+    #[test]
+    fn left_arrow_collapses_then_moves_to_the_parent() {
+        let mut tree = TreeWindow::new();
+        tree.expanded.set(["a", "a1"].into_iter().collect());
+        tree.window.update();
+        tree.select_first();
+        assert_eq!(tree.press(NamedKey::End), Some("b"));
+        assert_eq!(tree.press(NamedKey::ArrowUp), Some("a2"));
+        assert_eq!(tree.press(NamedKey::ArrowUp), Some("a1x"));
+        assert_eq!(tree.press(NamedKey::ArrowLeft), Some("a1"));
+        assert_eq!(tree.press(NamedKey::ArrowLeft), Some("a1"));
+        assert!(!tree.is_expanded("a1"));
+        assert_eq!(tree.press(NamedKey::ArrowLeft), Some("a"));
+        assert_eq!(tree.press(NamedKey::ArrowLeft), Some("a"));
+        assert!(!tree.is_expanded("a"));
+        // Roots have no parent to move to
+        assert_eq!(tree.press(NamedKey::ArrowLeft), Some("a"));
+        assert_eq!(tree.press(NamedKey::ArrowDown), Some("b"));
+    }
+}
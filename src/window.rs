@@ -1,16 +1,63 @@
+pub use floem_renderer::Backend;
 pub use floem_winit::window::Fullscreen;
 pub use floem_winit::window::ResizeDirection;
 pub use floem_winit::window::Theme;
 pub use floem_winit::window::WindowButtons;
 pub use floem_winit::window::WindowId;
 pub use floem_winit::window::WindowLevel;
-use kurbo::{Point, Size};
+use std::{fmt, fs, path::PathBuf, rc::Rc};
+
+use floem_reactive::{create_signal, use_context, ReadSignal};
+use floem_winit::dpi::{LogicalPosition, LogicalSize, PhysicalPosition, PhysicalSize};
+use kurbo::{Point, Rect, Size};
 
 use crate::{
-    app::{add_app_update_event, AppUpdateEvent},
+    app::{add_app_update_event, AppUpdateEvent, OPEN_WINDOWS, WINDOW_BACKENDS},
     view::View,
 };
 
+/// The blur applied to what is behind a transparent window.
+/// See [`WindowConfig::blur`] and [`set_window_blur`](crate::action::set_window_blur).
+///
+/// Blur is supported on macOS and on Wayland compositors implementing
+/// `org_kde_kwin_blur_manager`, and is ignored elsewhere.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlurStyle {
+    /// What is behind the window is shown as is.
+    #[default]
+    None,
+    /// What is behind the window is blurred.
+    Blur,
+}
+
+/// What pressing the primary pointer button does in a part of a window.
+/// See [`set_window_hit_test`](crate::action::set_window_hit_test).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowRegion {
+    /// The press is sent to the views.
+    Client,
+    /// The press starts moving the window, and a double click toggles maximizing it.
+    Drag,
+    /// The press starts resizing the window in the direction.
+    Resize(ResizeDirection),
+}
+
+/// When a window renders frames. See [`WindowConfig::render_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RenderMode {
+    /// Frames are rendered only when something changes, like a view requesting to be painted,
+    /// an animation running or the window being resized, and the app sleeps otherwise.
+    ///
+    /// A static app should then idle at 0% CPU, which can be checked by leaving it without
+    /// input and watching its CPU usage, like with `top -p <pid>`. If it doesn't, something
+    /// keeps requesting frames, like an animation which repeats or a timer which repaints.
+    #[default]
+    OnDemand,
+    /// Frames are rendered one after the other, as fast as the display allows or up to
+    /// [`WindowConfig::max_fps`], like for games drawing on a canvas.
+    Continuous,
+}
+
 #[derive(Default, Debug)]
 pub struct WindowConfig {
     pub(crate) size: Option<Size>,
@@ -24,6 +71,16 @@ pub struct WindowConfig {
     pub(crate) resizable: Option<bool>,
     pub(crate) window_level: Option<WindowLevel>,
     pub(crate) apply_default_theme: Option<bool>,
+    pub(crate) traffic_lights_offset: Option<Point>,
+    pub(crate) blur: Option<BlurStyle>,
+    pub(crate) click_through: Option<bool>,
+    pub(crate) parent: Option<WindowId>,
+    pub(crate) modal: bool,
+    pub(crate) partial_present: bool,
+    pub(crate) render_mode: RenderMode,
+    pub(crate) max_fps: Option<u32>,
+    pub(crate) backend: Option<Backend>,
+    pub(crate) persist_geometry: Option<PersistGeometry>,
 }
 
 impl WindowConfig {
@@ -42,11 +99,41 @@ impl WindowConfig {
         self
     }
 
-    pub fn with_transparent(mut self, transparent: bool) -> Self {
+    /// The position of the window buttons on macOS when the title bar is hidden with
+    /// [`WindowConfig::show_titlebar`]. Defaults to `(11.0, 16.0)`.
+    pub fn traffic_lights_offset(mut self, offset: Point) -> Self {
+        self.traffic_lights_offset = Some(offset);
+        self
+    }
+
+    /// If set to true, the window background isn't filled, so parts of the window without a
+    /// background show what is behind the window. This needs the GPU renderer, the software
+    /// renderer always draws an opaque window.
+    ///
+    /// Transparent parts of the window still receive pointer input. Use
+    /// [`WindowConfig::click_through`] to let input pass through the whole window.
+    pub fn transparent(mut self, transparent: bool) -> Self {
         self.transparent = Some(transparent);
         self
     }
 
+    /// Same as [`WindowConfig::transparent`].
+    pub fn with_transparent(self, transparent: bool) -> Self {
+        self.transparent(transparent)
+    }
+
+    /// The blur applied to what is shown behind a [transparent](WindowConfig::transparent) window.
+    pub fn blur(mut self, blur: BlurStyle) -> Self {
+        self.blur = Some(blur);
+        self
+    }
+
+    /// If set to true, pointer input passes through the window to what is behind it.
+    pub fn click_through(mut self, click_through: bool) -> Self {
+        self.click_through = Some(click_through);
+        self
+    }
+
     pub fn fullscreen(mut self, fullscreen: Fullscreen) -> Self {
         self.fullscreen = Some(fullscreen);
         self
@@ -84,6 +171,75 @@ impl WindowConfig {
         self.apply_default_theme = Some(apply_default_theme);
         self
     }
+
+    /// Restores the position, size and maximized state the window with this `key` had when it
+    /// was last closed, and saves them to `store` when the window is closed.
+    ///
+    /// If the saved position is no longer on a monitor, the window is moved onto the nearest one.
+    /// This overrides [`WindowConfig::size`] and [`WindowConfig::position`] when there is a
+    /// saved geometry.
+    pub fn persist_geometry(
+        mut self,
+        key: impl Into<String>,
+        store: impl WindowGeometryStore + 'static,
+    ) -> Self {
+        self.persist_geometry = Some(PersistGeometry {
+            key: key.into(),
+            store: Rc::new(store),
+        });
+        self
+    }
+
+    /// Makes the window a child of `parent`. Child windows are closed with their parent.
+    pub fn parent(mut self, parent: WindowId) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// If set to true, the parent window set with [`WindowConfig::parent`] ignores input and
+    /// can't be closed while this window is open. Clicking the parent focuses this window instead.
+    pub fn modal(mut self, modal: bool) -> Self {
+        self.modal = modal;
+        self
+    }
+
+    /// If set to true, only the parts of the window which changed since the last frame are
+    /// repainted and presented, instead of the whole window. This saves work in apps where
+    /// small parts change often, like a blinking caret or a clock.
+    ///
+    /// The parts which changed are the views which requested to be painted, styled or laid
+    /// out, including their shadows and outlines, and where views were before they moved or
+    /// were removed. Views which paint outside of their box otherwise may leave traces, and the
+    /// whole window is still repainted while dragging views, and by the GPU renderer, which
+    /// doesn't keep the previous frame.
+    pub fn partial_present(mut self, partial_present: bool) -> Self {
+        self.partial_present = partial_present;
+        self
+    }
+
+    /// Sets when the window renders frames, which is only when something changes by default.
+    pub fn render_mode(mut self, render_mode: RenderMode) -> Self {
+        self.render_mode = render_mode;
+        self
+    }
+
+    /// Limits the frames the window renders by itself, for animations and with
+    /// [`RenderMode::Continuous`], to `max_fps` per second, to save battery. Frames rendered for
+    /// input, like a click changing a view, aren't delayed.
+    pub fn max_fps(mut self, max_fps: u32) -> Self {
+        self.max_fps = Some(max_fps);
+        self
+    }
+
+    /// Renders the window with `backend` instead of the GPU backend picked for the platform.
+    /// When the GPU can't be used with the backend, like in virtual machines without GPU
+    /// acceleration, the window falls back to [`Backend::Software`], which
+    /// [`window_backend`] reports. Setting the `FLOEM_FORCE_TINY_SKIA` environment
+    /// variable to `1` forces software rendering for all windows.
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = Some(backend);
+        self
+    }
 }
 
 /// create a new window. You'll need to create Application first, otherwise it
@@ -102,3 +258,372 @@ pub fn new_window<V: View + 'static>(
 pub fn close_window(window_id: WindowId) {
     add_app_update_event(AppUpdateEvent::CloseWindow { window_id });
 }
+
+/// request the window to be focused
+pub fn focus_window_with_id(window_id: WindowId) {
+    add_app_update_event(AppUpdateEvent::FocusWindow { window_id });
+}
+
+/// Shows or hides the window. Hidden windows stay open and keep their state.
+pub fn set_window_visible(window_id: WindowId, visible: bool) {
+    add_app_update_event(AppUpdateEvent::SetWindowVisible { window_id, visible });
+}
+
+/// The ids of the open windows, in the order they were opened.
+///
+/// Windows requested with [`new_window`] are opened on the next event loop iteration, so they
+/// aren't included right away.
+pub fn open_windows() -> Vec<WindowId> {
+    OPEN_WINDOWS.with(|windows| windows.borrow().clone())
+}
+
+/// The scale factor of the current window provided to its views, see [`scale_factor`].
+#[derive(Clone, Copy)]
+pub(crate) struct ScaleFactor(pub(crate) ReadSignal<f64>);
+
+/// A signal with the scale factor of the window the view being built is in, which is the
+/// number of physical pixels per logical pixel, like 2.0 on most high DPI monitors. It changes
+/// when the window is moved to a monitor with a different DPI, which also sends
+/// [`Event::WindowScaleChanged`](crate::event::Event::WindowScaleChanged) with the old and new
+/// factor. Layout and text are in logical pixels and are rendered again for the new factor by
+/// themselves, so the signal is for what depends on the physical pixels, like drawing lines
+/// `1.0 / scale_factor` wide to cover exactly one pixel, or choosing `@2x` images.
+///
+/// It doesn't include the zoom set with
+/// [`Decorators::window_scale`](crate::views::Decorators::window_scale). Outside of a window,
+/// like in tests, it's 1.0.
+pub fn scale_factor() -> ReadSignal<f64> {
+    use_context::<ScaleFactor>()
+        .map(|scale_factor| scale_factor.0)
+        .unwrap_or_else(|| create_signal(1.0).0)
+}
+
+/// The backend the window is rendered with, which is useful for bug reports, or `None` if the
+/// window isn't open. See [`WindowConfig::backend`].
+pub fn window_backend(window_id: WindowId) -> Option<Backend> {
+    WINDOW_BACKENDS.with(|backends| backends.borrow().get(&window_id).copied())
+}
+
+/// The position, size and maximized state of a window. See [`WindowConfig::persist_geometry`].
+///
+/// The position and size are in logical pixels, and are those the window has when it isn't
+/// maximized.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WindowGeometry {
+    pub position: Point,
+    pub size: Size,
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// Moves and shrinks the window onto the nearest monitor if not enough of it is visible on
+    /// any of the `monitors` to grab it.
+    pub fn fit_to_monitors(self, monitors: &[Rect]) -> WindowGeometry {
+        let rect = fit_rect_to_monitors(Rect::from_origin_size(self.position, self.size), monitors);
+        WindowGeometry {
+            position: rect.origin(),
+            size: rect.size(),
+            maximized: self.maximized,
+        }
+    }
+}
+
+/// The rect of a monitor in logical pixels, from its position and size in physical pixels.
+pub(crate) fn monitor_rect(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    scale: f64,
+) -> Rect {
+    let position: LogicalPosition<f64> = position.to_logical(scale);
+    let size: LogicalSize<f64> = size.to_logical(scale);
+    Rect::from_origin_size((position.x, position.y), (size.width, size.height))
+}
+
+/// Moves and shrinks `rect` onto the nearest of the `monitors` if not enough of it is visible
+/// on any of them to grab it, which is 100 by 30 pixels, or all of it if it's smaller.
+fn fit_rect_to_monitors(rect: Rect, monitors: &[Rect]) -> Rect {
+    let grabbable = monitors.iter().any(|monitor| {
+        let visible = monitor.intersect(rect);
+        visible.width() >= rect.width().min(100.0) && visible.height() >= rect.height().min(30.0)
+    });
+    if grabbable {
+        return rect;
+    }
+
+    let center = rect.center();
+    let nearest = monitors.iter().min_by(|a, b| {
+        let a = a.center().distance(center);
+        let b = b.center().distance(center);
+        a.total_cmp(&b)
+    });
+    let monitor = match nearest {
+        Some(monitor) => monitor,
+        None => return rect,
+    };
+    let size = Size::new(
+        rect.width().min(monitor.width()),
+        rect.height().min(monitor.height()),
+    );
+    let position = Point::new(
+        rect.x0.clamp(monitor.x0, monitor.x1 - size.width),
+        rect.y0.clamp(monitor.y0, monitor.y1 - size.height),
+    );
+    Rect::from_origin_size(position, size)
+}
+
+/// Where window geometries are saved. See [`WindowConfig::persist_geometry`].
+pub trait WindowGeometryStore {
+    fn load(&self, key: &str) -> Option<WindowGeometry>;
+    fn save(&self, key: &str, geometry: WindowGeometry);
+}
+
+/// Saves window geometries in a text file, with one line per window. Any key can be used, as
+/// the tabs, line breaks and backslashes in keys are escaped.
+pub struct FileGeometryStore {
+    path: PathBuf,
+}
+
+impl FileGeometryStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn read(&self) -> Vec<(String, WindowGeometry)> {
+        let content = fs::read_to_string(&self.path).unwrap_or_default();
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let key = unescape_key(fields.next()?);
+                let mut number = || fields.next()?.parse::<f64>().ok();
+                let position = Point::new(number()?, number()?);
+                let size = Size::new(number()?, number()?);
+                let maximized = number()? != 0.0;
+                Some((
+                    key,
+                    WindowGeometry {
+                        position,
+                        size,
+                        maximized,
+                    },
+                ))
+            })
+            .collect()
+    }
+}
+
+impl WindowGeometryStore for FileGeometryStore {
+    fn load(&self, key: &str) -> Option<WindowGeometry> {
+        self.read()
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, geometry)| geometry)
+    }
+
+    fn save(&self, key: &str, geometry: WindowGeometry) {
+        let mut geometries = self.read();
+        geometries.retain(|(k, _)| k != key);
+        geometries.push((key.to_string(), geometry));
+        let content: String = geometries
+            .iter()
+            .map(|(key, g)| {
+                format!(
+                    "{}\t{}\t{}\t{}\t{}\t{}\n",
+                    escape_key(key),
+                    g.position.x,
+                    g.position.y,
+                    g.size.width,
+                    g.size.height,
+                    g.maximized as u8
+                )
+            })
+            .collect();
+        if let Some(dir) = self.path.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(&self.path, content);
+    }
+}
+
+/// Escapes the characters of `key` which separate the fields and lines of a
+/// [`FileGeometryStore`].
+fn escape_key(key: &str) -> String {
+    let mut escaped = String::with_capacity(key.len());
+    for c in key.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape_key(escaped: &str) -> String {
+    let mut key = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            key.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => key.push('\t'),
+            Some('n') => key.push('\n'),
+            Some('r') => key.push('\r'),
+            Some(c) => key.push(c),
+            None => key.push('\\'),
+        }
+    }
+    key
+}
+
+#[derive(Clone)]
+pub(crate) struct PersistGeometry {
+    pub(crate) key: String,
+    pub(crate) store: Rc<dyn WindowGeometryStore>,
+}
+
+impl fmt::Debug for PersistGeometry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PersistGeometry")
+            .field("key", &self.key)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        rc::Rc,
+    };
+
+    use floem_winit::dpi::{PhysicalPosition, PhysicalSize};
+    use kurbo::{Point, Rect, Size};
+
+    use super::{
+        fit_rect_to_monitors, monitor_rect, scale_factor, FileGeometryStore, WindowGeometry,
+        WindowGeometryStore,
+    };
+    use crate::{
+        event::{Event, EventListener},
+        test_support::HeadlessWindow,
+        views::{empty, Decorators},
+    };
+
+    #[test]
+    fn scale_factor_changes_are_reported() {
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let signal = Rc::new(Cell::new(None));
+        let (changed, read) = (changes.clone(), signal.clone());
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), move || {
+            read.set(Some(scale_factor()));
+            empty().on_event_stop(EventListener::WindowScaleChanged, move |event| {
+                if let Event::WindowScaleChanged { old, new } = event {
+                    changed.borrow_mut().push((*old, *new));
+                }
+            })
+        });
+        window.update();
+        let scale = signal.get().unwrap();
+        assert_eq!(scale.get_untracked(), 1.0);
+
+        window.set_scale_factor(2.0);
+        window.set_scale_factor(2.0);
+        assert_eq!(*changes.borrow(), vec![(1.0, 2.0)]);
+        assert_eq!(scale.get_untracked(), 2.0);
+    }
+
+    #[test]
+    fn monitors_are_converted_to_logical_pixels() {
+        let monitor = monitor_rect(
+            PhysicalPosition::new(-2560, -200),
+            PhysicalSize::new(2560, 1440),
+            2.0,
+        );
+        assert_eq!(monitor, Rect::new(-1280.0, -100.0, 0.0, 620.0));
+    }
+
+    #[test]
+    fn windows_which_can_be_grabbed_are_kept() {
+        // A monitor on the left of the primary one, with a negative origin
+        let monitors = [
+            Rect::new(0.0, 0.0, 1920.0, 1080.0),
+            Rect::new(-1280.0, -100.0, 0.0, 620.0),
+        ];
+        let on_left = Rect::from_origin_size((-1000.0, -50.0), (800.0, 600.0));
+        assert_eq!(fit_rect_to_monitors(on_left, &monitors), on_left);
+
+        // Across both monitors
+        let across = Rect::from_origin_size((-400.0, 100.0), (800.0, 600.0));
+        assert_eq!(fit_rect_to_monitors(across, &monitors), across);
+
+        // Mostly off the right edge, with enough left to grab
+        let mostly_off = Rect::from_origin_size((1800.0, 500.0), (800.0, 600.0));
+        assert_eq!(fit_rect_to_monitors(mostly_off, &monitors), mostly_off);
+    }
+
+    #[test]
+    fn windows_which_cant_be_grabbed_are_moved_to_the_nearest_monitor() {
+        let monitors = [
+            Rect::new(0.0, 0.0, 1920.0, 1080.0),
+            Rect::new(-1280.0, -100.0, 0.0, 620.0),
+        ];
+
+        // On a monitor left of the left one, which was disconnected
+        let disconnected = Rect::from_origin_size((-2200.0, 0.0), (800.0, 600.0));
+        assert_eq!(
+            fit_rect_to_monitors(disconnected, &monitors),
+            Rect::from_origin_size((-1280.0, 0.0), (800.0, 600.0))
+        );
+
+        // Only a sliver is visible above the left monitor, and it's larger than the monitor
+        let sliver = Rect::from_origin_size((-1300.0, -1500.0), (1600.0, 1410.0));
+        assert_eq!(
+            fit_rect_to_monitors(sliver, &monitors),
+            Rect::new(-1280.0, -100.0, 0.0, 620.0)
+        );
+
+        // Below the primary monitor
+        let below = Rect::from_origin_size((600.0, 1200.0), (800.0, 600.0));
+        assert_eq!(
+            fit_rect_to_monitors(below, &monitors),
+            Rect::from_origin_size((600.0, 480.0), (800.0, 600.0))
+        );
+
+        // Without monitors, there's nowhere to move it to
+        assert_eq!(fit_rect_to_monitors(below, &[]), below);
+    }
+
+    #[test]
+    fn geometries_are_saved_under_any_key() {
+        let path = std::env::temp_dir().join(format!("floem-geometry-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let store = FileGeometryStore::new(&path);
+        let geometry = |x| WindowGeometry {
+            position: Point::new(x, 20.0),
+            size: Size::new(800.0, 600.0),
+            maximized: x > 100.0,
+        };
+        let keys = ["main", "a\tb", "two\nlines\r", "back\\slash\\t", "\\"];
+        for (i, key) in keys.iter().enumerate() {
+            store.save(key, geometry(i as f64 * 100.0));
+        }
+        // Saving again replaces the geometry
+        store.save("a\tb", geometry(50.0));
+
+        let lines = std::fs::read_to_string(&path).unwrap().lines().count();
+        assert_eq!(lines, keys.len());
+        let store = FileGeometryStore::new(&path);
+        assert_eq!(store.load("main"), Some(geometry(0.0)));
+        assert_eq!(store.load("a\tb"), Some(geometry(50.0)));
+        assert_eq!(store.load("two\nlines\r"), Some(geometry(200.0)));
+        assert_eq!(store.load("back\\slash\\t"), Some(geometry(300.0)));
+        assert_eq!(store.load("\\"), Some(geometry(400.0)));
+        assert_eq!(store.load("a"), None);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -1,14 +1,15 @@
 use std::{
     mem,
+    path::PathBuf,
     rc::Rc,
     time::{Duration, Instant},
 };
 
-use floem_reactive::{with_scope, RwSignal, Scope};
-use floem_renderer::Renderer;
+use floem_reactive::{provide_context, with_scope, RwSignal, Scope};
+use floem_renderer::{Backend, Renderer};
 use floem_winit::{
     dpi::{LogicalPosition, LogicalSize},
-    event::{ElementState, Ime, MouseButton, MouseScrollDelta},
+    event::{ElementState, Force, Ime, MouseButton, MouseScrollDelta, Touch, TouchPhase},
     keyboard::{Key, ModifiersState, NamedKey},
     window::{CursorIcon, WindowId},
 };
@@ -17,32 +18,40 @@ use indexmap::IndexMap;
 use kurbo::{Affine, Point, Rect, Size, Vec2};
 
 #[cfg(target_os = "linux")]
-use crate::unit::UnitExt;
-#[cfg(target_os = "linux")]
-use crate::views::{container_box, stack, Decorators};
+use crate::menu::context_menu::context_menu_view;
+use crate::views::{portal_host, Decorators, PortalHost};
 use crate::{
+    action::{cancel_timer, exec_after, TimerToken},
     animate::{AnimPropKind, AnimUpdateMsg, AnimValue, AnimatedProp, SizeUnit},
+    app::{add_app_update_event, AppUpdateEvent},
+    command::execute_shortcut,
     context::{
-        AppState, ComputeLayoutCx, EventCx, FrameUpdate, LayoutCx, MoveListener, PaintCx,
-        PaintState, ResizeListener, StyleCx, UpdateCx,
+        AppState, Autofocus, ComputeLayoutCx, EventCx, EventPropagation, FrameUpdate,
+        IntersectionListener, LayoutCx, MoveListener, PaintCx, PaintState, ResizeListener,
+        SizeListener, StyleCx, UpdateCx, VisibilityListener,
     },
-    event::{Event, EventListener},
+    event::{Event, EventListener, FileDropEvent},
+    gesture::GesturePhase,
     id::{Id, IdPath, ID_PATHS},
     inspector::{self, Capture, CaptureState, CapturedView},
-    keyboard::KeyEvent,
-    menu::Menu,
+    keyboard::{KeyCombination, KeyEvent, KeyInput},
+    menu::{menu_bar_menus, mnemonic, Menu, MenuEntry},
     nav::view_arrow_navigation,
-    pointer::{PointerButton, PointerInputEvent, PointerMoveEvent, PointerWheelEvent},
+    pointer::{
+        PointerButton, PointerInputEvent, PointerMoveEvent, PointerPinchEvent, PointerType,
+        PointerWheelEvent, TouchEvent, WheelDeltaMode, WHEEL_LINE_HEIGHT,
+    },
     profiler::Profile,
     style::{CursorStyle, Style, StyleSelector},
     update::{
-        UpdateMessage, ANIM_UPDATE_MESSAGES, CENTRAL_DEFERRED_UPDATE_MESSAGES,
+        ScreenshotCallback, UpdateMessage, ANIM_UPDATE_MESSAGES, CENTRAL_DEFERRED_UPDATE_MESSAGES,
         CENTRAL_UPDATE_MESSAGES, CURRENT_RUNNING_VIEW_HANDLE, DEFERRED_UPDATE_MESSAGES,
         UPDATE_MESSAGES,
     },
-    view::{view_children_set_parent_id, view_tab_navigation, View, ViewData},
+    view::{stacked_children, view_children_set_parent_id, view_tab_navigation, View, ViewData},
     view_data::{update_data, ChangeFlags},
     widgets::{default_theme, Theme},
+    window::{BlurStyle, PersistGeometry, RenderMode, ScaleFactor, WindowGeometry, WindowRegion},
 };
 
 /// The top-level window handle that owns the winit Window.
@@ -67,12 +76,44 @@ pub(crate) struct WindowHandle {
     is_maximized: bool,
     transparent: bool,
     pub(crate) scale: f64,
+    /// The scale factor of the window, which views read with
+    /// [`scale_factor`](crate::window::scale_factor).
+    scale_factor: RwSignal<f64>,
     pub(crate) modifiers: ModifiersState,
     pub(crate) cursor_position: Point,
     pub(crate) window_position: Point,
     pub(crate) last_pointer_down: Option<(u8, Point, Instant)>,
+    /// The number of mouse buttons which are pressed.
+    pressed_buttons: usize,
+    /// The pressure reported by a pressure-sensitive touchpad.
+    touchpad_pressure: Option<f64>,
     #[cfg(target_os = "linux")]
     pub(crate) context_menu: RwSignal<Option<(Menu, Point)>>,
+    /// The window menu shown by the menu bar, without its actions.
+    menu_bar: RwSignal<Option<Menu>>,
+    /// The menu of the menu bar which was opened last.
+    menu_bar_open: RwSignal<Option<usize>>,
+    pending_screenshots: Vec<(Id, Option<f64>, Box<ScreenshotCallback>)>,
+    pending_clicks: Vec<(Id, Box<dyn FnOnce(bool)>)>,
+    hit_test: Option<Box<dyn Fn(Point) -> WindowRegion>>,
+    /// The files dragged over the window from outside the app, and the view they're over.
+    file_drag: Option<(Vec<PathBuf>, Option<Id>)>,
+    /// The files of the last drop, to skip the drop events of the files after the first one.
+    last_dropped_files: Vec<PathBuf>,
+    pub(crate) persist_geometry: Option<PersistGeometry>,
+    /// The position and size of the window when it was last not maximized.
+    normal_geometry: Rect,
+    render_mode: RenderMode,
+    /// The shortest time between the frames the window renders by itself, from
+    /// [`WindowConfig::max_fps`](crate::window::WindowConfig::max_fps).
+    frame_interval: Option<Duration>,
+    /// When the last frame was rendered.
+    last_frame: Option<Instant>,
+    /// The timer for the next frame, when it was delayed to keep to the maximum frame rate.
+    frame_timer: Option<TimerToken>,
+    /// The styles of the stylesheets, and the theme with those styles applied.
+    #[cfg(feature = "stylesheet")]
+    stylesheet_theme: Option<(Rc<Style>, Rc<Style>)>,
 }
 
 impl WindowHandle {
@@ -81,16 +122,74 @@ impl WindowHandle {
         view_fn: impl FnOnce(floem_winit::window::WindowId) -> Box<dyn View> + 'static,
         transparent: bool,
         apply_default_theme: bool,
+        backend: Option<Backend>,
     ) -> Self {
-        let scope = Scope::new();
         let window_id = window.id();
-        let id = Id::next();
         let scale = window.scale_factor();
         let size: LogicalSize<f64> = window.inner_size().to_logical(scale);
         let size = Size::new(size.width, size.height);
-        let size = scope.create_rw_signal(Size::new(size.width, size.height));
-        let theme = scope.create_rw_signal(window.theme());
-        let is_maximized = window.is_maximized();
+        let paint_state = PaintState::new(&window, scale, size * scale, backend);
+        let mut window_handle = Self::new_with_paint_state(
+            window_id,
+            size,
+            scale,
+            window.theme(),
+            paint_state,
+            view_fn,
+            transparent,
+            apply_default_theme,
+        );
+        window_handle.is_maximized = window.is_maximized();
+        if let Ok(position) = window.outer_position() {
+            let position: LogicalPosition<f64> = position.to_logical(scale);
+            window_handle.window_position = Point::new(position.x, position.y);
+            window_handle.normal_geometry = window_handle
+                .normal_geometry
+                .with_origin(window_handle.window_position);
+        }
+        window_handle.window = Some(window);
+        window_handle
+    }
+
+    /// Creates a window handle without a window, which paints to an offscreen image.
+    #[cfg(any(test, feature = "test-support"))]
+    pub(crate) fn new_headless(
+        view_fn: impl FnOnce(floem_winit::window::WindowId) -> Box<dyn View> + 'static,
+        size: Size,
+        scale: f64,
+        apply_default_theme: bool,
+    ) -> Self {
+        // SAFETY: The id is never passed to winit, as there's no window.
+        let window_id = unsafe { WindowId::dummy() };
+        let paint_state = PaintState::new_headless(scale, size * scale);
+        Self::new_with_paint_state(
+            window_id,
+            size,
+            scale,
+            None,
+            paint_state,
+            view_fn,
+            false,
+            apply_default_theme,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn new_with_paint_state(
+        window_id: WindowId,
+        size: Size,
+        scale: f64,
+        os_theme: Option<floem_winit::window::Theme>,
+        paint_state: PaintState,
+        view_fn: impl FnOnce(floem_winit::window::WindowId) -> Box<dyn View> + 'static,
+        transparent: bool,
+        apply_default_theme: bool,
+    ) -> Self {
+        let scope = Scope::new();
+        let id = Id::next();
+        let size = scope.create_rw_signal(size);
+        let theme = scope.create_rw_signal(os_theme);
+        let scale_factor = scope.create_rw_signal(scale);
 
         set_current_view(id);
 
@@ -100,19 +199,24 @@ impl WindowHandle {
 
         #[cfg(target_os = "linux")]
         let context_menu = scope.create_rw_signal(None);
+        let menu_bar = scope.create_rw_signal(None);
+        let menu_bar_open = scope.create_rw_signal(None);
 
-        #[cfg(not(target_os = "linux"))]
-        let view = with_scope(scope, move || view_fn(window_id));
+        let view = with_scope(scope, move || {
+            provide_context(ScaleFactor(scale_factor.read_only()));
+            view_fn(window_id)
+        });
 
         #[cfg(target_os = "linux")]
-        let view = with_scope(scope, move || {
-            Box::new(
-                stack((
-                    container_box(view_fn(window_id)).style(|s| s.size(100.pct(), 100.pct())),
-                    context_menu_view(scope, window_id, context_menu, size),
-                ))
-                .style(|s| s.size(100.pct(), 100.pct())),
-            )
+        let context_menu_view = with_scope(scope, move || {
+            Box::new(context_menu_view(
+                scope,
+                window_id,
+                context_menu,
+                size,
+                menu_bar,
+                menu_bar_open,
+            )) as Box<dyn View>
         });
 
         view.id().set_parent(id);
@@ -120,13 +224,22 @@ impl WindowHandle {
 
         let view = WindowView {
             data: ViewData::new(id),
+            root: view.id(),
             main: view,
+            portals: portal_host(),
             overlays: Default::default(),
+            #[cfg(target_os = "linux")]
+            context_menu: context_menu_view,
         };
+        view.portals.id().set_parent(id);
+        #[cfg(target_os = "linux")]
+        {
+            view.context_menu.id().set_parent(id);
+            view_children_set_parent_id(&*view.context_menu);
+        }
 
-        let paint_state = PaintState::new(&window, scale, size.get_untracked() * scale);
         let mut window_handle = Self {
-            window: Some(window),
+            window: None,
             window_id,
             id,
             scope,
@@ -136,28 +249,48 @@ impl WindowHandle {
             size,
             theme: apply_default_theme.then(default_theme),
             os_theme: theme,
-            is_maximized,
+            is_maximized: false,
             transparent,
             profile: None,
             scale,
+            scale_factor,
             modifiers: ModifiersState::default(),
             cursor_position: Point::ZERO,
             window_position: Point::ZERO,
             #[cfg(target_os = "linux")]
             context_menu,
+            menu_bar,
+            menu_bar_open,
             last_pointer_down: None,
+            pressed_buttons: 0,
+            touchpad_pressure: None,
+            pending_screenshots: Vec::new(),
+            pending_clicks: Vec::new(),
+            hit_test: None,
+            file_drag: None,
+            last_dropped_files: Vec::new(),
+            persist_geometry: None,
+            normal_geometry: size.get_untracked().to_rect(),
+            render_mode: RenderMode::OnDemand,
+            frame_interval: None,
+            last_frame: None,
+            frame_timer: None,
+            #[cfg(feature = "stylesheet")]
+            stylesheet_theme: None,
         };
         window_handle.app_state.set_root_size(size.get_untracked());
         window_handle
     }
 
-    pub fn event(&mut self, event: Event) {
+    /// Dispatches `event` and returns [`EventPropagation::Stop`] if a view handled it.
+    pub fn event(&mut self, event: Event) -> EventPropagation {
         set_current_view(self.id);
         let event = event.scale(self.app_state.scale);
 
         let mut cx = EventCx {
             app_state: &mut self.app_state,
         };
+        cx.app_state.default_prevented = false;
 
         let is_pointer_move = matches!(&event, Event::PointerMove(_));
         let (was_hovered, was_dragging_over) = if is_pointer_move {
@@ -171,6 +304,18 @@ impl WindowHandle {
         };
 
         let is_pointer_down = matches!(&event, Event::PointerDown(_));
+        // The focus ring of the `focus_visible` selector is only shown after keyboard input, so
+        // it's shown when focus moves with Tab, or when typing in a view focused by clicking it
+        if is_pointer_down {
+            cx.app_state.set_keyboard_navigation(false);
+        } else if let Event::KeyDown(KeyEvent { key, .. }) = &event {
+            if !matches!(
+                key.logical_key,
+                Key::Named(NamedKey::Shift | NamedKey::Control | NamedKey::Alt | NamedKey::Super)
+            ) {
+                cx.app_state.set_keyboard_navigation(true);
+            }
+        }
         let was_focused = if is_pointer_down {
             cx.app_state.clicking.clear();
             cx.app_state.focus.take()
@@ -178,6 +323,7 @@ impl WindowHandle {
             cx.app_state.focus
         };
 
+        let mut handled = false;
         if event.needs_focus() {
             let mut processed = false;
 
@@ -198,29 +344,58 @@ impl WindowHandle {
                 }
 
                 if !processed {
-                    for handler in &self.view.main.view_data().event_handlers {
-                        if (handler)(&event).is_processed() {
-                            processed = true;
-                            break;
+                    let root = self.view.root;
+                    update_data(root, &mut self.view, |data| {
+                        for handler in &data.event_handlers {
+                            if cx.handled((handler)(&event)) {
+                                processed = true;
+                                break;
+                            }
                         }
-                    }
+                    });
 
                     if let Some(listener) = event.listener() {
-                        if let Some(action) = cx.get_event_listener(self.view.main.id(), &listener)
+                        if let Some(action) = cx.get_event_listener(root, &listener) {
+                            let propagation = (*action)(&event);
+                            processed |= cx.handled(propagation);
+                        }
+                    }
+                }
+
+                // Shortcuts and keyboard navigation are the default behavior of the window
+                let default_prevented = cx.is_default_prevented();
+
+                if !processed && !default_prevented {
+                    if let Event::KeyDown(key_event) = &event {
+                        let action =
+                            KeyCombination::from_key_event(key_event).and_then(|shortcut| {
+                                cx.app_state.window_menu_shortcuts.get(&shortcut).copied()
+                            });
+                        if let Some(action) =
+                            action.and_then(|id| cx.app_state.window_menu.get(&id))
                         {
-                            processed |= (*action)(&event).is_processed();
+                            (*action)();
+                            processed = true;
+                        } else {
+                            processed = execute_shortcut(key_event);
                         }
                     }
                 }
 
                 if !processed {
-                    if let Event::KeyDown(KeyEvent { key, modifiers }) = &event {
+                    if let (false, Event::KeyDown(KeyEvent { key, modifiers })) =
+                        (default_prevented, &event)
+                    {
                         if key.logical_key == Key::Named(NamedKey::Tab)
                             && (modifiers.is_empty() || *modifiers == ModifiersState::SHIFT)
                         {
                             let backwards = modifiers.contains(ModifiersState::SHIFT);
                             view_tab_navigation(&self.view, cx.app_state, backwards);
                             // view_debug_tree(&self.view);
+                        } else if let (true, Key::Character(character)) =
+                            (*modifiers == ModifiersState::ALT, &key.logical_key)
+                        {
+                            open_menu_bar_mnemonic(self.menu_bar, self.menu_bar_open, character);
                         } else if let Key::Character(character) = &key.logical_key {
                             // 'I' displays some debug information
                             if character.eq_ignore_ascii_case("i") {
@@ -254,6 +429,28 @@ impl WindowHandle {
                     }
                 }
             }
+            handled = processed;
+        } else if let Event::Touch(touch) = &event {
+            if touch.phase == GesturePhase::Began {
+                handled = cx
+                    .unconditional_view_event(&mut self.view, None, event.clone())
+                    .is_processed();
+            } else if let Some(id) = cx.app_state.touch_targets.get(&touch.id).copied() {
+                // The rest of a touch only goes to the view which handled its beginning
+                let id_path = ID_PATHS.with(|paths| paths.borrow().get(&id).cloned());
+                if let Some(id_path) = id_path {
+                    handled = cx
+                        .unconditional_view_event(
+                            &mut self.view,
+                            Some(id_path.dispatch()),
+                            event.clone(),
+                        )
+                        .is_processed();
+                }
+            }
+            if matches!(touch.phase, GesturePhase::Ended | GesturePhase::Cancelled) {
+                cx.app_state.touch_targets.remove(&touch.id);
+            }
         } else if cx.app_state.active.is_some() && event.is_pointer() {
             if cx.app_state.is_dragging() {
                 cx.unconditional_view_event(&mut self.view, None, event.clone());
@@ -262,11 +459,13 @@ impl WindowHandle {
             let id = cx.app_state.active.unwrap();
             let id_path = ID_PATHS.with(|paths| paths.borrow().get(&id).cloned());
             if let Some(id_path) = id_path {
-                cx.unconditional_view_event(
-                    &mut self.view,
-                    Some(id_path.dispatch()),
-                    event.clone(),
-                );
+                handled = cx
+                    .unconditional_view_event(
+                        &mut self.view,
+                        Some(id_path.dispatch()),
+                        event.clone(),
+                    )
+                    .is_processed();
             }
             if let Event::PointerUp(_) = &event {
                 // To remove the styles applied by the Active selector
@@ -277,7 +476,9 @@ impl WindowHandle {
                 cx.app_state.active = None;
             }
         } else {
-            cx.unconditional_view_event(&mut self.view, None, event.clone());
+            handled = cx
+                .unconditional_view_event(&mut self.view, None, event.clone())
+                .is_processed();
         }
 
         if let Event::PointerUp(_) = &event {
@@ -343,12 +544,52 @@ impl WindowHandle {
         }
 
         self.process_update();
+
+        if handled {
+            EventPropagation::Stop
+        } else {
+            EventPropagation::Continue
+        }
+    }
+
+    /// Clicks the primary pointer button at the center of the view `id`.
+    /// Returns `true` if a view handled the press or the release.
+    pub(crate) fn simulate_click(&mut self, id: Id) -> bool {
+        if self.app_state.is_disabled_recursive(id) || !self.app_state.view_states.contains_key(&id)
+        {
+            return false;
+        }
+        let pos = self.app_state.get_layout_rect(id).center();
+        let pos = Point::new(pos.x * self.app_state.scale, pos.y * self.app_state.scale);
+
+        self.pointer_move(pos);
+        let modifiers = self.modifiers;
+        let event = |count, pressure| PointerInputEvent {
+            pos,
+            button: PointerButton::Primary,
+            modifiers,
+            count,
+            pointer_type: PointerType::Mouse,
+            pressure,
+            tilt: None,
+        };
+        let down = self.event(Event::PointerDown(event(1, 0.5)));
+        let up = self.event(Event::PointerUp(event(0, 0.0)));
+        down.is_processed() || up.is_processed()
     }
 
     pub(crate) fn scale(&mut self, scale: f64) {
+        let old = self.scale;
         self.scale = scale;
-        let scale = self.scale * self.app_state.scale;
-        self.paint_state.set_scale(scale);
+        // The surface is resized for the new scale right away, without waiting for the resize
+        // event which follows
+        let size = self.size.get_untracked();
+        self.paint_state
+            .resize(self.scale * self.app_state.scale, size * self.scale);
+        if scale != old {
+            self.scale_factor.set(scale);
+            self.event(Event::WindowScaleChanged { old, new: scale });
+        }
         self.schedule_repaint();
     }
 
@@ -366,6 +607,9 @@ impl WindowHandle {
 
         if let Some(window) = self.window.as_ref() {
             let is_maximized = window.is_maximized();
+            if !is_maximized {
+                self.normal_geometry = self.normal_geometry.with_size(size);
+            }
             if is_maximized != self.is_maximized {
                 self.is_maximized = is_maximized;
                 self.event(Event::WindowMaximizeChanged(is_maximized));
@@ -380,10 +624,31 @@ impl WindowHandle {
 
     pub(crate) fn position(&mut self, point: Point) {
         self.window_position = point;
+        if !self
+            .window
+            .as_ref()
+            .map(|window| window.is_maximized())
+            .unwrap_or(false)
+        {
+            self.normal_geometry = self.normal_geometry.with_origin(point);
+        }
         self.event(Event::WindowMoved(point));
     }
 
-    pub(crate) fn key_event(&mut self, key_event: floem_winit::event::KeyEvent) {
+    pub(crate) fn save_geometry(&self) {
+        if let Some(persist) = self.persist_geometry.as_ref() {
+            persist.store.save(
+                &persist.key,
+                WindowGeometry {
+                    position: self.normal_geometry.origin(),
+                    size: self.normal_geometry.size(),
+                    maximized: self.is_maximized,
+                },
+            );
+        }
+    }
+
+    pub(crate) fn key_event(&mut self, key_event: KeyInput) {
         let event = KeyEvent {
             key: key_event,
             modifiers: self.modifiers,
@@ -398,14 +663,109 @@ impl WindowHandle {
     pub(crate) fn pointer_move(&mut self, pos: Point) {
         if self.cursor_position != pos {
             self.cursor_position = pos;
+            if self.file_drag.is_some() {
+                self.update_file_drop_target();
+                self.process_update();
+            }
             let event = PointerMoveEvent {
                 pos,
                 modifiers: self.modifiers,
+                pointer_type: PointerType::Mouse,
+                pressure: self.mouse_pressure(),
+                tilt: None,
             };
             self.event(Event::PointerMove(event));
         }
     }
 
+    pub(crate) fn hovered_file(&mut self, path: PathBuf) {
+        // Each file is reported with a separate event.
+        if self.file_drag.is_none() {
+            self.last_dropped_files.clear();
+        }
+        self.file_drag
+            .get_or_insert_with(Default::default)
+            .0
+            .push(path);
+        self.update_file_drop_target();
+        self.process_update();
+    }
+
+    pub(crate) fn hovered_file_cancelled(&mut self) {
+        if let Some((_, Some(target))) = self.file_drag.take() {
+            self.send_file_drop_event(target, FileDropEvent::Leave);
+        }
+        self.process_update();
+    }
+
+    pub(crate) fn dropped_file(&mut self, path: PathBuf) {
+        // Each dropped file is reported with a separate event. The hovered files are all
+        // delivered with the first one, and the events of the rest are skipped.
+        let paths = match self.file_drag.take() {
+            Some((paths, _)) if paths.contains(&path) => paths,
+            Some(_) | None => {
+                if self.last_dropped_files.contains(&path) {
+                    return;
+                }
+                vec![path]
+            }
+        };
+        self.last_dropped_files = paths.clone();
+        let pos = self.file_drop_pos();
+        if let Some((target, pos)) = self.file_drop_target(pos) {
+            self.send_file_drop_event(target, FileDropEvent::Drop { paths, pos });
+        }
+        self.process_update();
+    }
+
+    fn file_drop_pos(&self) -> Point {
+        Point::new(
+            self.cursor_position.x / self.app_state.scale,
+            self.cursor_position.y / self.app_state.scale,
+        )
+    }
+
+    /// The view with a file drop listener under `pos`, with `pos` relative to it.
+    fn file_drop_target(&mut self, pos: Point) -> Option<(Id, Point)> {
+        file_drop_target(&mut self.view, &self.app_state, pos)
+    }
+
+    fn update_file_drop_target(&mut self) {
+        let pos = self.file_drop_pos();
+        let hit = self.file_drop_target(pos);
+        let target = hit.map(|(id, _)| id);
+        let (paths, current) = match self.file_drag.as_mut() {
+            Some((paths, current)) => (paths.clone(), std::mem::replace(current, target)),
+            None => return,
+        };
+        if current != target {
+            if let Some(current) = current {
+                self.send_file_drop_event(current, FileDropEvent::Leave);
+            }
+        }
+        if let Some((target, pos)) = hit {
+            let event = if current != Some(target) {
+                FileDropEvent::Enter { paths, pos }
+            } else {
+                FileDropEvent::Hover { paths, pos }
+            };
+            self.send_file_drop_event(target, event);
+        }
+    }
+
+    fn send_file_drop_event(&self, id: Id, event: FileDropEvent) {
+        set_current_view(id);
+        if let Some(listener) = self
+            .app_state
+            .view_states
+            .get(&id)
+            .and_then(|state| state.file_drop_listener.as_ref())
+        {
+            listener(event);
+        }
+        set_current_view(self.id);
+    }
+
     pub(crate) fn pointer_leave(&mut self) {
         set_current_view(self.id);
         let mut cx = EventCx {
@@ -433,23 +793,94 @@ impl WindowHandle {
     }
 
     pub(crate) fn mouse_wheel(&mut self, delta: MouseScrollDelta) {
-        let delta = match delta {
-            MouseScrollDelta::LineDelta(x, y) => Vec2::new(-x as f64 * 60.0, -y as f64 * 60.0),
+        let (delta, delta_mode) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (
+                Vec2::new(-x as f64, -y as f64) * WHEEL_LINE_HEIGHT,
+                WheelDeltaMode::Line,
+            ),
             MouseScrollDelta::PixelDelta(delta) => {
                 let position: LogicalPosition<f64> = delta.to_logical(self.scale);
-                Vec2::new(-position.x, -position.y)
+                (Vec2::new(-position.x, -position.y), WheelDeltaMode::Pixel)
             }
         };
         let event = PointerWheelEvent {
             pos: self.cursor_position,
             delta,
+            delta_mode,
             modifiers: self.modifiers,
         };
         self.event(Event::PointerWheel(event));
     }
 
+    pub(crate) fn touch(&mut self, touch: Touch) {
+        let position: LogicalPosition<f64> = touch.location.to_logical(self.scale);
+        let tilt = match touch.force {
+            Some(Force::Calibrated { altitude_angle, .. }) => altitude_angle,
+            _ => None,
+        };
+        let pressure = match touch.phase {
+            TouchPhase::Ended | TouchPhase::Cancelled => 0.0,
+            _ => touch.force.map(|force| force.normalized()).unwrap_or(0.5),
+        };
+        let event = TouchEvent {
+            id: touch.id,
+            phase: touch.phase.into(),
+            pos: Point::new(position.x, position.y),
+            pointer_type: if tilt.is_some() {
+                PointerType::Pen
+            } else {
+                PointerType::Touch
+            },
+            pressure,
+            tilt,
+        };
+        self.event(Event::Touch(event));
+    }
+
+    /// The pressure of the mouse, which is reported by some touchpads.
+    fn mouse_pressure(&self) -> f64 {
+        match self.touchpad_pressure {
+            Some(pressure) => pressure,
+            None if self.pressed_buttons > 0 => 0.5,
+            None => 0.0,
+        }
+    }
+
+    pub(crate) fn touchpad_pressure(&mut self, pressure: f64) {
+        self.touchpad_pressure = Some(pressure);
+        let event = PointerMoveEvent {
+            pos: self.cursor_position,
+            modifiers: self.modifiers,
+            pointer_type: PointerType::Mouse,
+            pressure,
+            tilt: None,
+        };
+        self.event(Event::PointerMove(event));
+    }
+
+    pub(crate) fn touchpad_pinch(
+        &mut self,
+        scale_delta: f64,
+        rotation_delta: f64,
+        phase: TouchPhase,
+    ) {
+        let event = PointerPinchEvent {
+            pos: self.cursor_position,
+            scale_delta,
+            rotation_delta,
+            phase: phase.into(),
+            modifiers: self.modifiers,
+        };
+        self.event(Event::PointerPinch(event));
+    }
+
     pub(crate) fn mouse_input(&mut self, button: MouseButton, state: ElementState) {
         let button: PointerButton = button.into();
+        if state.is_pressed() {
+            self.pressed_buttons += 1;
+        } else {
+            self.pressed_buttons = self.pressed_buttons.saturating_sub(1);
+        }
         let count = if state.is_pressed() && button.is_primary() {
             if let Some((count, last_pos, instant)) = self.last_pointer_down.as_mut() {
                 if *count == 4 {
@@ -471,11 +902,38 @@ impl WindowHandle {
         } else {
             0
         };
+        if state.is_pressed() && button.is_primary() {
+            let region = self
+                .hit_test
+                .as_ref()
+                .map(|hit_test| hit_test(self.cursor_position))
+                .unwrap_or(WindowRegion::Client);
+            if let Some(window) = self.window.as_ref() {
+                match region {
+                    WindowRegion::Client => {}
+                    WindowRegion::Drag => {
+                        if count == 2 {
+                            window.set_maximized(!window.is_maximized());
+                        } else {
+                            let _ = window.drag_window();
+                        }
+                        return;
+                    }
+                    WindowRegion::Resize(direction) => {
+                        let _ = window.drag_resize_window(direction);
+                        return;
+                    }
+                }
+            }
+        }
         let event = PointerInputEvent {
             pos: self.cursor_position,
             button,
             modifiers: self.modifiers,
             count,
+            pointer_type: PointerType::Mouse,
+            pressure: self.mouse_pressure(),
+            tilt: None,
         };
         match state {
             ElementState::Pressed => {
@@ -500,9 +958,27 @@ impl WindowHandle {
         if let Some(style) = self.theme.as_ref().map(|theme| theme.style.clone()) {
             cx.current = style;
         }
+        #[cfg(feature = "stylesheet")]
+        if let Some(stylesheet) = crate::stylesheet::stylesheet_style() {
+            cx.current = match &self.stylesheet_theme {
+                Some((sheet, style)) if Rc::ptr_eq(sheet, &stylesheet) => style.clone(),
+                _ => {
+                    let style = Rc::new((*cx.current).clone().apply((*stylesheet).clone()));
+                    self.stylesheet_theme = Some((stylesheet, style.clone()));
+                    style
+                }
+            };
+        }
         cx.style_view(&mut self.view);
     }
 
+    /// Styles all views again, after styles which apply to every view changed.
+    #[cfg(feature = "stylesheet")]
+    pub(crate) fn restyle(&mut self) {
+        self.app_state.request_style_recursive(self.view.id());
+        self.process_update();
+    }
+
     fn layout(&mut self) -> Duration {
         let mut cx = LayoutCx::new(&mut self.app_state);
 
@@ -525,6 +1001,13 @@ impl WindowHandle {
     }
 
     pub fn render_frame(&mut self) {
+        self.render_frame_image();
+    }
+
+    /// Renders a frame, returning the image if the renderer produced one.
+    pub(crate) fn render_frame_image(&mut self) -> Option<DynamicImage> {
+        self.last_frame = Some(Instant::now());
+
         // Processes updates scheduled on this frame.
         for update in mem::take(&mut self.app_state.scheduled_updates) {
             match update {
@@ -535,20 +1018,41 @@ impl WindowHandle {
         }
 
         self.process_update_no_paint();
-        self.paint();
+        self.capture_screenshots();
+        let image = self.paint();
 
         // Request a new frame if there's any scheduled updates.
-        if !self.app_state.scheduled_updates.is_empty() {
-            self.schedule_repaint();
+        if self.render_mode == RenderMode::Continuous
+            || !self.app_state.scheduled_updates.is_empty()
+        {
+            self.request_next_frame();
         }
+
+        image
+    }
+
+    /// Sets whether only the parts of the window which changed are repainted, see
+    /// [`WindowConfig::partial_present`](crate::window::WindowConfig::partial_present).
+    pub(crate) fn set_partial_present(&mut self, partial_present: bool) {
+        self.app_state.partial_present = partial_present;
     }
 
     pub fn paint(&mut self) -> Option<DynamicImage> {
+        let damage = self.app_state.take_damage().and_then(|damage| {
+            let window = self
+                .size
+                .get_untracked()
+                .to_rect()
+                .scale_from_origin(1.0 / self.app_state.scale);
+            let damage = damage.intersect(window);
+            (damage.area() > 0.0 && self.paint_state.renderer.set_damage(damage)).then_some(damage)
+        });
         let mut cx = PaintCx {
             app_state: &mut self.app_state,
             paint_state: &mut self.paint_state,
             transform: Affine::IDENTITY,
             clip: None,
+            damage,
             z_index: None,
             saved_transforms: Vec::new(),
             saved_clips: Vec::new(),
@@ -585,6 +1089,73 @@ impl WindowHandle {
         cx.paint_state.renderer.finish()
     }
 
+    /// Paints the window to an image for the pending screenshots and crops it to each view.
+    ///
+    /// The window is painted at the scale of each screenshot, so the images aren't resampled.
+    fn capture_screenshots(&mut self) {
+        if self.pending_screenshots.is_empty() {
+            return;
+        }
+
+        // The scale the window is rendered at, in pixels per logical pixel.
+        let window_scale = self.scale * self.app_state.scale;
+        let size = self.size.get_untracked();
+        let capture = self.app_state.capture.replace(CaptureState::default());
+        let mut screenshots = mem::take(&mut self.pending_screenshots);
+        let mut resized = false;
+        while let Some(&(_, scale, _)) = screenshots.first() {
+            let scale = scale.unwrap_or(window_scale);
+            let (current, rest): (Vec<_>, Vec<_>) = screenshots
+                .into_iter()
+                .partition(|(_, other, _)| other.unwrap_or(window_scale) == scale);
+            screenshots = rest;
+
+            if scale != window_scale || resized {
+                self.paint_state
+                    .resize(scale, size * (scale / self.app_state.scale));
+                resized = true;
+            }
+            // Captures are painted whole, whatever the damage since the last frame
+            let window = self.paint().map(|image| image.to_rgba8());
+            for (id, _, callback) in current {
+                let image = window.as_ref().and_then(|window| {
+                    let rect = self
+                        .app_state
+                        .get_layout_rect(id)
+                        .scale_from_origin(scale)
+                        .round()
+                        .intersect(Rect::new(
+                            0.0,
+                            0.0,
+                            window.width() as f64,
+                            window.height() as f64,
+                        ));
+                    if rect.width() < 1.0 || rect.height() < 1.0 {
+                        return None;
+                    }
+                    Some(
+                        image::imageops::crop_imm(
+                            window,
+                            rect.x0 as u32,
+                            rect.y0 as u32,
+                            rect.width() as u32,
+                            rect.height() as u32,
+                        )
+                        .to_image(),
+                    )
+                });
+                callback(image);
+            }
+        }
+
+        if resized {
+            self.paint_state.resize(window_scale, size * self.scale);
+        }
+        // The captures took the damage, so the next frame repaints the whole window, which it
+        // has to as the renderer holds the last capture instead of the last frame
+        self.app_state.capture = capture;
+    }
+
     pub(crate) fn capture(&mut self) -> Capture {
         // Capture the view before we run `style` and `layout` to catch missing `request_style`` or
         // `request_layout` flags.
@@ -686,6 +1257,11 @@ impl WindowHandle {
             self.process_anim_update_messages();
         }
 
+        // Clicks are dispatched after the updates so the views are styled and laid out.
+        for (id, callback) in mem::take(&mut self.pending_clicks) {
+            callback(self.simulate_click(id));
+        }
+
         self.set_cursor();
 
         // TODO: This should only use `self.app_state.request_paint)`
@@ -745,7 +1321,11 @@ impl WindowHandle {
                             cx.app_state.request_layout(id);
                         }
                     }
-                    UpdateMessage::RequestPaint => {
+                    UpdateMessage::RequestPaint { id } => {
+                        cx.app_state.request_paint(id);
+                    }
+                    UpdateMessage::RequestFrame => {
+                        self.frame_timer = None;
                         cx.app_state.request_paint = true;
                     }
                     UpdateMessage::Focus(id) => {
@@ -756,8 +1336,10 @@ impl WindowHandle {
                         }
                     }
                     UpdateMessage::ClearFocus(id) => {
-                        cx.app_state.clear_focus();
-                        cx.app_state.focus_changed(Some(id), None);
+                        if cx.app_state.focus == Some(id) {
+                            cx.app_state.clear_focus();
+                            cx.app_state.focus_changed(Some(id), None);
+                        }
                     }
                     UpdateMessage::Active(id) => {
                         let old = cx.app_state.active;
@@ -780,6 +1362,16 @@ impl WindowHandle {
                     UpdateMessage::ScrollTo { id, rect } => {
                         self.view.scroll_to(cx.app_state, id, rect);
                     }
+                    UpdateMessage::Display { id, display } => {
+                        cx.app_state.view_state(id).display = Some(display);
+                        cx.app_state.request_style(id);
+                    }
+                    UpdateMessage::BringToFront { id } => {
+                        cx.app_state.stack_order += 1;
+                        let stack_order = cx.app_state.stack_order;
+                        cx.app_state.view_state(id).stack_order = stack_order;
+                        cx.app_state.request_paint(id);
+                    }
                     UpdateMessage::Disabled { id, is_disabled } => {
                         if is_disabled {
                             cx.app_state.disabled.insert(id);
@@ -789,6 +1381,14 @@ impl WindowHandle {
                         }
                         cx.app_state.request_style_recursive(id);
                     }
+                    UpdateMessage::Checked { id, is_checked } => {
+                        if is_checked {
+                            cx.app_state.checked.insert(id);
+                        } else {
+                            cx.app_state.checked.remove(&id);
+                        }
+                        cx.app_state.request_style(id);
+                    }
                     UpdateMessage::State { id, state } => {
                         let id_path = ID_PATHS.with(|paths| paths.borrow().get(&id).cloned());
                         if let Some(id_path) = id_path {
@@ -811,6 +1411,14 @@ impl WindowHandle {
                         state.class = Some(class);
                         cx.app_state.request_style_recursive(id);
                     }
+                    UpdateMessage::MinSize { id, size } => {
+                        cx.app_state.view_state(id).min_size = size;
+                        cx.request_style(id);
+                    }
+                    UpdateMessage::MaxSize { id, size } => {
+                        cx.app_state.view_state(id).max_size = size;
+                        cx.request_style(id);
+                    }
                     UpdateMessage::StyleSelector {
                         id,
                         style,
@@ -860,6 +1468,29 @@ impl WindowHandle {
                             window.set_minimized(true);
                         }
                     }
+                    UpdateMessage::RequestCloseWindow => {
+                        add_app_update_event(AppUpdateEvent::RequestCloseWindow {
+                            window_id: self.window_id,
+                        });
+                    }
+                    UpdateMessage::SetWindowDecorations(decorations) => {
+                        if let Some(window) = self.window.as_ref() {
+                            window.set_decorations(decorations);
+                        }
+                    }
+                    UpdateMessage::SetWindowBlur(blur) => {
+                        if let Some(window) = self.window.as_ref() {
+                            window.set_blur(blur == BlurStyle::Blur);
+                        }
+                    }
+                    UpdateMessage::SetWindowClickThrough(click_through) => {
+                        if let Some(window) = self.window.as_ref() {
+                            let _ = window.set_cursor_hittest(!click_through);
+                        }
+                    }
+                    UpdateMessage::WindowHitTest(hit_test) => {
+                        self.hit_test = Some(hit_test);
+                    }
                     UpdateMessage::SetWindowDelta(delta) => {
                         if let Some(window) = self.window.as_ref() {
                             let pos = self.window_position + delta;
@@ -876,6 +1507,14 @@ impl WindowHandle {
                         let state = cx.app_state.view_state(id);
                         state.event_listeners.insert(listener, action);
                     }
+                    UpdateMessage::CaptureListener {
+                        id,
+                        listener,
+                        action,
+                    } => {
+                        let state = cx.app_state.view_state(id);
+                        state.capture_listeners.insert(listener, action);
+                    }
                     UpdateMessage::ResizeListener { id, action } => {
                         let state = cx.app_state.view_state(id);
                         state.resize_listener = Some(ResizeListener {
@@ -883,6 +1522,13 @@ impl WindowHandle {
                             callback: action,
                         });
                     }
+                    UpdateMessage::SizeListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.size_listener = Some(SizeListener {
+                            size: Size::ZERO,
+                            callback: action,
+                        });
+                    }
                     UpdateMessage::MoveListener { id, action } => {
                         let state = cx.app_state.view_state(id);
                         state.move_listener = Some(MoveListener {
@@ -890,10 +1536,72 @@ impl WindowHandle {
                             callback: action,
                         });
                     }
+                    UpdateMessage::VisibilityListener {
+                        id,
+                        threshold,
+                        action,
+                    } => {
+                        let state = cx.app_state.view_state(id);
+                        state.visibility_listener = Some(VisibilityListener {
+                            threshold,
+                            visible: false,
+                            callback: action,
+                        });
+                    }
+                    UpdateMessage::IntersectionListener {
+                        id,
+                        mut thresholds,
+                        action,
+                    } => {
+                        thresholds.sort_by(f64::total_cmp);
+                        let state = cx.app_state.view_state(id);
+                        state.intersection_listener = Some(IntersectionListener {
+                            thresholds,
+                            reached: None,
+                            callback: action,
+                        });
+                    }
+                    UpdateMessage::FileDropListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.file_drop_listener = Some(action);
+                    }
                     UpdateMessage::CleanupListener { id, action } => {
                         let state = cx.app_state.view_state(id);
                         state.cleanup_listener = Some(action);
                     }
+                    UpdateMessage::Autofocus { id, force } => {
+                        let focus = cx.app_state.focus;
+                        let state = cx.app_state.view_state(id);
+                        if state.mounted {
+                            id.request_focus();
+                        } else {
+                            state.autofocus = Some(Autofocus { force, focus });
+                        }
+                    }
+                    UpdateMessage::MountListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.mount_listener = Some(action);
+                    }
+                    UpdateMessage::UnmountListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.unmount_listener = Some(action);
+                    }
+                    UpdateMessage::TouchListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.touch_listener = Some(action);
+                    }
+                    UpdateMessage::WheelListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.wheel_listener = Some(action);
+                    }
+                    UpdateMessage::FocusListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.focus_listener = Some(action);
+                    }
+                    UpdateMessage::FocusWithinListener { id, action } => {
+                        let state = cx.app_state.view_state(id);
+                        state.focus_within_listener = Some(action);
+                    }
                     UpdateMessage::Animation { id, animation } => {
                         let view_state = cx.app_state.view_state(id);
                         view_state.animation = Some(animation);
@@ -926,9 +1634,7 @@ impl WindowHandle {
                         self.show_context_menu(menu, platform_menu, pos);
                     }
                     UpdateMessage::WindowMenu { menu } => {
-                        // let platform_menu = menu.platform_menu();
                         self.update_window_menu(menu);
-                        // self.handle.set_menu(platform_menu);
                     }
                     UpdateMessage::SetWindowTitle { title } => {
                         if let Some(window) = self.window.as_ref() {
@@ -979,12 +1685,29 @@ impl WindowHandle {
                         self.view.overlays.insert(id, view);
                         cx.app_state.request_all(self.id);
                     }
+                    UpdateMessage::SimulateClick { id, callback } => {
+                        self.pending_clicks.push((id, callback));
+                    }
+                    UpdateMessage::CaptureScreenshot {
+                        id,
+                        scale,
+                        callback,
+                    } => {
+                        self.pending_screenshots.push((id, scale, callback));
+                        cx.app_state.request_paint(id);
+                    }
                     UpdateMessage::RemoveOverlay { id } => {
                         let mut overlay = self.view.overlays.remove(&id).unwrap();
                         cx.app_state.remove_view(&mut overlay);
                         overlay.scope.dispose();
                         cx.app_state.request_all(self.id);
                     }
+                    UpdateMessage::AddPortal { view, scope } => {
+                        self.view.portals.add(cx.app_state, view, scope);
+                    }
+                    UpdateMessage::RemovePortal { id } => {
+                        self.view.portals.remove(cx.app_state, id);
+                    }
                 }
             }
         }
@@ -1100,23 +1823,37 @@ impl WindowHandle {
         ANIM_UPDATE_MESSAGES.with(|m| !m.borrow().is_empty())
     }
 
-    fn update_window_menu(&mut self, _menu: Menu) {
-        // if let Some(action) = menu.item.action.take() {
-        //     self.window_menu.insert(menu.item.id as u32, action);
-        // }
-        // for child in menu.children {
-        //     match child {
-        //         crate::menu::MenuEntry::Separator => {}
-        //         crate::menu::MenuEntry::Item(mut item) => {
-        //             if let Some(action) = item.action.take() {
-        //                 self.window_menu.insert(item.id as u32, action);
-        //             }
-        //         }
-        //         crate::menu::MenuEntry::SubMenu(m) => {
-        //             self.update_window_menu(m);
-        //         }
-        //     }
-        // }
+    fn update_window_menu(&mut self, menu: Menu) {
+        self.app_state.window_menu.clear();
+        self.app_state.window_menu_shortcuts.clear();
+        #[cfg(target_os = "macos")]
+        {
+            // The native menu bar runs the actions and handles the shortcuts itself
+            crate::menu::native::set_app_menu(menu);
+        }
+        #[cfg(not(target_os = "macos"))]
+        {
+            let mut menu = menu;
+            self.app_state.update_window_menu(&mut menu);
+            if self.menu_bar.with_untracked(|menu_bar| menu_bar.is_none()) {
+                self.add_menu_bar();
+            }
+            self.menu_bar.set(Some(menu));
+        }
+    }
+
+    /// Puts the menu bar above the main view, when the first window menu is set.
+    #[cfg(not(target_os = "macos"))]
+    fn add_menu_bar(&mut self) {
+        let main = std::mem::replace(&mut self.view.main, Box::new(crate::views::empty()));
+        let (menu_bar, menu_bar_open) = (self.menu_bar, self.menu_bar_open);
+        let main = with_scope(self.scope, move || {
+            with_menu_bar(main, menu_bar, menu_bar_open)
+        });
+        main.id().set_parent(self.id);
+        view_children_set_parent_id(&*main);
+        self.view.main = main;
+        self.app_state.request_all(self.id);
     }
 
     fn set_cursor(&mut self) {
@@ -1146,6 +1883,46 @@ impl WindowHandle {
         }
     }
 
+    /// The backend the window is rendered with.
+    pub(crate) fn backend(&self) -> Backend {
+        self.paint_state.renderer.backend()
+    }
+
+    /// Sets when the window renders frames, and the maximum number of frames it renders by
+    /// itself per second.
+    pub(crate) fn set_render_mode(&mut self, render_mode: RenderMode, max_fps: Option<u32>) {
+        self.render_mode = render_mode;
+        self.frame_interval = max_fps
+            .filter(|max_fps| *max_fps > 0)
+            .map(|max_fps| Duration::from_secs_f64(1.0 / max_fps as f64));
+        if render_mode == RenderMode::Continuous {
+            self.schedule_repaint();
+        }
+    }
+
+    /// Requests the next frame, after a delay if it would be too soon for the maximum frame
+    /// rate.
+    fn request_next_frame(&mut self) {
+        if self.frame_timer.is_some() {
+            return;
+        }
+        let delay = self
+            .frame_interval
+            .zip(self.last_frame)
+            .and_then(|(interval, last_frame)| {
+                (last_frame + interval).checked_duration_since(Instant::now())
+            })
+            .filter(|delay| !delay.is_zero());
+        if let Some(delay) = delay {
+            let id = self.id;
+            self.frame_timer = Some(exec_after(delay, move |_| {
+                id.add_update_message(UpdateMessage::RequestFrame)
+            }));
+        } else {
+            self.schedule_repaint();
+        }
+    }
+
     fn schedule_repaint(&self) {
         if let Some(window) = self.window.as_ref() {
             window.request_redraw();
@@ -1153,7 +1930,15 @@ impl WindowHandle {
     }
 
     pub(crate) fn destroy(&mut self) {
+        if let Some(timer) = self.frame_timer.take() {
+            cancel_timer(timer);
+        }
         self.event(Event::WindowClosed);
+        for id in self.app_state.view_states.keys() {
+            id.dispose_scope();
+            id.remove_debug_info();
+            id.remove_visible_fraction();
+        }
         self.scope.dispose();
     }
 
@@ -1245,279 +2030,108 @@ pub(crate) fn set_current_view(id: Id) {
     });
 }
 
-#[cfg(target_os = "linux")]
-fn context_menu_view(
-    cx: Scope,
-    window_id: WindowId,
-    context_menu: RwSignal<Option<(Menu, Point)>>,
-    window_size: RwSignal<Size>,
-) -> impl View {
-    use floem_peniko::Color;
-    use floem_reactive::{create_effect, create_rw_signal};
-
-    use crate::{
-        app::{add_app_update_event, AppUpdateEvent},
-        views::{dyn_stack, empty, svg, text},
+/// Opens the menu of the menu bar with the access key `character`. When several menus share it,
+/// this opens the next of them after the one which was opened last.
+fn open_menu_bar_mnemonic(
+    menu_bar: RwSignal<Option<Menu>>,
+    menu_bar_open: RwSignal<Option<usize>>,
+    character: &str,
+) {
+    let Some(character) = character.chars().next() else {
+        return;
     };
-
-    #[derive(Clone, PartialEq, Eq, Hash)]
-    struct MenuDisplay {
-        id: Option<u64>,
-        enabled: bool,
-        title: String,
-        children: Option<Vec<Option<MenuDisplay>>>,
-    }
-
-    fn format_menu(menu: &Menu) -> Vec<Option<MenuDisplay>> {
-        menu.children
-            .iter()
-            .map(|e| match e {
-                crate::menu::MenuEntry::Separator => None,
-                crate::menu::MenuEntry::Item(i) => Some(MenuDisplay {
-                    id: Some(i.id),
-                    enabled: i.enabled,
-                    title: i.title.clone(),
-                    children: None,
-                }),
-                crate::menu::MenuEntry::SubMenu(m) => Some(MenuDisplay {
-                    id: None,
-                    enabled: m.item.enabled,
-                    title: m.item.title.clone(),
-                    children: Some(format_menu(m)),
-                }),
-            })
+    let character = character.to_lowercase().next().unwrap_or(character);
+    let matching: Vec<usize> = menu_bar.with_untracked(|menu_bar| {
+        menu_bar_menus(menu_bar)
+            .filter(|(_, m)| m.item.enabled && mnemonic(&m.item.title) == Some(character))
+            .map(|(index, _)| index)
             .collect()
-    }
-
-    let context_menu_items = cx.create_memo(move |_| {
-        context_menu.with(|menu| {
-            menu.as_ref()
-                .map(|(menu, _): &(Menu, Point)| format_menu(menu))
-        })
     });
-    let context_menu_size = cx.create_rw_signal(Size::ZERO);
-    let focus_count = cx.create_rw_signal(0);
-
-    fn view_fn(
-        window_id: WindowId,
-        menu: Option<MenuDisplay>,
-        context_menu: RwSignal<Option<(Menu, Point)>>,
-        focus_count: RwSignal<i32>,
-        on_child_submenu_for_parent: RwSignal<bool>,
-    ) -> impl View {
-        if let Some(menu) = menu {
-            let menu_width = create_rw_signal(0.0);
-            let show_submenu = create_rw_signal(false);
-            let on_submenu = create_rw_signal(false);
-            let on_child_submenu = create_rw_signal(false);
-            let has_submenu = menu.children.is_some();
-            let submenu_svg = r#"<svg width="16" height="16" viewBox="0 0 16 16" xmlns="http://www.w3.org/2000/svg" fill="currentColor"><path fill-rule="evenodd" clip-rule="evenodd" d="M10.072 8.024L5.715 3.667l.618-.62L11 7.716v.618L6.333 13l-.618-.619 4.357-4.357z"/></svg>"#;
-            container_box(
-                stack((
-                    stack((
-                        text(menu.title),
-                        svg(|| submenu_svg.to_string()).style(move |s| {
-                            s.size(20.0, 20.0)
-                                .color(Color::rgb8(201, 201, 201))
-                                .margin_right(10.0)
-                                .margin_left(20.0)
-                                .apply_if(!has_submenu, |s| s.hide())
-                        }),
-                    ))
-                    .on_event_stop(EventListener::PointerEnter, move |_| {
-                        if has_submenu {
-                            show_submenu.set(true);
-                        }
-                    })
-                    .on_event_stop(EventListener::PointerLeave, move |_| {
-                        if has_submenu {
-                            show_submenu.set(false);
-                        }
-                    })
-                    .on_resize(move |rect| {
-                        let width = rect.width();
-                        if menu_width.get_untracked() != width {
-                            menu_width.set(width);
-                        }
-                    })
-                    .on_click_stop(move |_| {
-                        context_menu.set(None);
-                        focus_count.set(0);
-                        if let Some(id) = menu.id {
-                            add_app_update_event(AppUpdateEvent::MenuAction {
-                                window_id,
-                                action_id: id as usize,
-                            });
-                        }
-                    })
-                    .on_secondary_click_stop(move |_| {
-                        context_menu.set(None);
-                        focus_count.set(0);
-                        if let Some(id) = menu.id {
-                            add_app_update_event(AppUpdateEvent::MenuAction {
-                                window_id,
-                                action_id: id as usize,
-                            });
-                        }
-                    })
-                    .disabled(move || !menu.enabled)
-                    .style(|s| {
-                        s.width(100.pct())
-                            .min_width(100.pct())
-                            .padding_horiz(20.0)
-                            .justify_between()
-                            .items_center()
-                            .hover(|s| s.border_radius(10.0).background(Color::rgb8(65, 65, 65)))
-                            .active(|s| s.border_radius(10.0).background(Color::rgb8(92, 92, 92)))
-                            .disabled(|s| s.color(Color::rgb8(92, 92, 92)))
-                    }),
-                    dyn_stack(
-                        move || menu.children.clone().unwrap_or_default(),
-                        move |s| s.clone(),
-                        move |menu| {
-                            view_fn(window_id, menu, context_menu, focus_count, on_child_submenu)
-                        },
-                    )
-                    .keyboard_navigatable()
-                    .on_event_stop(EventListener::FocusGained, move |_| {
-                        focus_count.update(|count| {
-                            *count += 1;
-                        });
-                    })
-                    .on_event_stop(EventListener::FocusLost, move |_| {
-                        let count = focus_count
-                            .try_update(|count| {
-                                *count -= 1;
-                                *count
-                            })
-                            .unwrap();
-                        if count < 1 {
-                            context_menu.set(None);
-                        }
-                    })
-                    .on_event_stop(EventListener::KeyDown, move |event| {
-                        if let Event::KeyDown(event) = event {
-                            if event.key.logical_key == Key::Named(NamedKey::Escape) {
-                                context_menu.set(None);
-                            }
-                        }
-                    })
-                    .on_event_stop(EventListener::PointerDown, move |_| {})
-                    .on_event_stop(EventListener::PointerEnter, move |_| {
-                        if has_submenu {
-                            on_submenu.set(true);
-                            on_child_submenu_for_parent.set(true);
-                        }
-                    })
-                    .on_event_stop(EventListener::PointerLeave, move |_| {
-                        if has_submenu {
-                            on_submenu.set(false);
-                            on_child_submenu_for_parent.set(false);
-                        }
-                    })
-                    .style(move |s| {
-                        s.absolute()
-                            .min_width(200.0)
-                            .margin_top(-5.0)
-                            .margin_left(menu_width.get() as f32)
-                            .flex_col()
-                            .border_radius(10.0)
-                            .background(Color::rgb8(44, 44, 44))
-                            .padding(5.0)
-                            .cursor(CursorStyle::Default)
-                            .box_shadow_blur(5.0)
-                            .box_shadow_color(Color::BLACK)
-                            .apply_if(
-                                !show_submenu.get() && !on_submenu.get() && !on_child_submenu.get(),
-                                |s| s.hide(),
-                            )
-                    }),
-                ))
-                .style(|s| s.min_width(100.pct())),
-            )
-            .style(|s| s.min_width(100.pct()))
-        } else {
-            container_box(empty().style(|s| {
-                s.width(100.pct())
-                    .height(1.0)
-                    .margin_vert(5.0)
-                    .background(Color::rgb8(92, 92, 92))
-            }))
-            .style(|s| s.min_width(100.pct()).padding_horiz(20.0))
-        }
+    let last = menu_bar_open.get_untracked();
+    let next = matching
+        .iter()
+        .find(|&&index| last.is_some_and(|last| index > last))
+        .or(matching.first());
+    if let Some(&index) = next {
+        menu_bar_open.set(Some(index));
     }
+}
 
-    let on_child_submenu = create_rw_signal(false);
-    let view = dyn_stack(
-        move || context_menu_items.get().unwrap_or_default(),
-        move |s| s.clone(),
-        move |menu| view_fn(window_id, menu, context_menu, focus_count, on_child_submenu),
-    )
-    .on_resize(move |rect| {
-        context_menu_size.set(rect.size());
-    })
-    .on_event_stop(EventListener::PointerDown, move |_| {})
-    .keyboard_navigatable()
-    .on_event_stop(EventListener::KeyDown, move |event| {
-        if let Event::KeyDown(event) = event {
-            if event.key.logical_key == Key::Named(NamedKey::Escape) {
-                context_menu.set(None);
-            }
-        }
-    })
-    .on_event_stop(EventListener::FocusGained, move |_| {
-        focus_count.update(|count| {
-            *count += 1;
-        });
-    })
-    .on_event_stop(EventListener::FocusLost, move |_| {
-        let count = focus_count
-            .try_update(|count| {
-                *count -= 1;
-                *count
-            })
-            .unwrap();
-        if count < 1 {
-            context_menu.set(None);
-        }
-    })
-    .style(move |s| {
-        let window_size = window_size.get();
-        let menu_size = context_menu_size.get();
-        let is_acitve = context_menu.with(|m| m.is_some());
-        let mut pos = context_menu.with(|m| m.as_ref().map(|(_, pos)| *pos).unwrap_or_default());
-        if pos.x + menu_size.width > window_size.width {
-            pos.x = window_size.width - menu_size.width;
-        }
-        if pos.y + menu_size.height > window_size.height {
-            pos.y = window_size.height - menu_size.height;
-        }
-        s.absolute()
-            .min_width(200.0)
-            .flex_col()
-            .border_radius(10.0)
-            .background(Color::rgb8(44, 44, 44))
-            .color(Color::rgb8(201, 201, 201))
-            .z_index(999)
-            .line_height(2.0)
-            .padding(5.0)
-            .margin_left(pos.x as f32)
-            .margin_top(pos.y as f32)
-            .cursor(CursorStyle::Default)
-            .apply_if(!is_acitve, |s| s.hide())
-            .box_shadow_blur(5.0)
-            .box_shadow_color(Color::BLACK)
-    });
-
-    let id = view.id();
+/// Puts the menu bar above `main`.
+#[cfg(not(target_os = "macos"))]
+fn with_menu_bar(
+    main: Box<dyn View>,
+    menu_bar: RwSignal<Option<Menu>>,
+    menu_bar_open: RwSignal<Option<usize>>,
+) -> Box<dyn View> {
+    use floem_reactive::{create_effect, create_rw_signal};
 
-    create_effect(move |_| {
-        if context_menu.with(|m| m.is_some()) {
-            id.request_focus();
-        }
-    });
+    use crate::{
+        action::show_context_menu,
+        menu::{mnemonic_title, MenuBarClass, MenuBarItemClass},
+        unit::UnitExt,
+        views::{container_box, dyn_stack, v_stack},
+    };
 
-    view
+    let titles = move || {
+        menu_bar.with(|menu_bar| {
+            menu_bar_menus(menu_bar)
+                .map(|(index, m)| (index, m.item.title.clone(), m.item.enabled))
+                .collect::<Vec<_>>()
+        })
+    };
+    let bar = dyn_stack(
+        titles,
+        |title| title.clone(),
+        move |(index, title, enabled)| {
+            let origin = create_rw_signal(Point::ZERO);
+            let height = create_rw_signal(0.0);
+            // Every open request shows the menu again, as native menus close by themselves
+            create_effect(move |_| {
+                if menu_bar_open.get() != Some(index) {
+                    return;
+                }
+                let menu = menu_bar.with_untracked(|menu| {
+                    match menu.as_ref().and_then(|m| m.children.get(index)) {
+                        Some(MenuEntry::SubMenu(m)) => Some(m.without_actions()),
+                        _ => None,
+                    }
+                });
+                if let Some(menu) = menu {
+                    let origin = origin.get_untracked();
+                    show_context_menu(
+                        menu,
+                        Some(Point::new(origin.x, origin.y + height.get_untracked())),
+                    );
+                }
+            });
+            mnemonic_title(&title)
+                .class(MenuBarItemClass)
+                .disabled(move || !enabled)
+                .on_move(move |point| origin.set(point))
+                .on_resize(move |rect| height.set(rect.height()))
+                .on_event_stop(EventListener::PointerDown, move |_| {
+                    if enabled {
+                        menu_bar_open.set(Some(index));
+                    }
+                })
+        },
+    )
+    .class(MenuBarClass)
+    .style(|s| s.flex_row().width_full());
+
+    Box::new(
+        v_stack((
+            bar,
+            container_box(main).style(|s| {
+                s.width_full()
+                    .flex_basis(0.0)
+                    .flex_grow(1.0)
+                    .min_height(0.0)
+            }),
+        ))
+        .style(|s| s.size(100.pct(), 100.pct())),
+    )
 }
 
 struct OverlayView {
@@ -1565,11 +2179,18 @@ impl View for OverlayView {
     }
 }
 
-/// A view representing a window which manages the main window view and any overlays.
+/// A view representing a window which manages the main window view, the content of portals
+/// and any overlays.
 struct WindowView {
     data: ViewData,
+    /// The view built for the window, which is inside `main` when there's a menu bar.
+    root: Id,
     main: Box<dyn View>,
+    portals: PortalHost,
     overlays: IndexMap<Id, OverlayView>,
+    /// The context menu drawn by Floem, above everything else.
+    #[cfg(target_os = "linux")]
+    context_menu: Box<dyn View>,
 }
 
 impl View for WindowView {
@@ -1587,25 +2208,34 @@ impl View for WindowView {
 
     fn for_each_child<'a>(&'a self, for_each: &mut dyn FnMut(&'a dyn View) -> bool) {
         for_each(&self.main);
+        for_each(&self.portals);
         for overlay in self.overlays.values() {
             for_each(overlay);
         }
+        #[cfg(target_os = "linux")]
+        for_each(&self.context_menu);
     }
 
     fn for_each_child_mut<'a>(&'a mut self, for_each: &mut dyn FnMut(&'a mut dyn View) -> bool) {
         for_each(&mut self.main);
+        for_each(&mut self.portals);
         for overlay in self.overlays.values_mut() {
             for_each(overlay);
         }
+        #[cfg(target_os = "linux")]
+        for_each(&mut self.context_menu);
     }
 
     fn for_each_child_rev_mut<'a>(
         &'a mut self,
         for_each: &mut dyn FnMut(&'a mut dyn View) -> bool,
     ) {
+        #[cfg(target_os = "linux")]
+        for_each(&mut self.context_menu);
         for overlay in self.overlays.values_mut().rev() {
             for_each(overlay);
         }
+        for_each(&mut self.portals);
         for_each(&mut self.main);
     }
 
@@ -1613,3 +2243,179 @@ impl View for WindowView {
         "Window".into()
     }
 }
+
+/// Finds the view with a file drop listener under `pos`, which is relative to the parent of
+/// `view`, the same way pointer events are routed: the children on top are tried first, and
+/// hidden and disabled views and their children are skipped. Returns the view with `pos`
+/// relative to it.
+fn file_drop_target(view: &mut dyn View, app_state: &AppState, pos: Point) -> Option<(Id, Point)> {
+    let id = view.id();
+    if app_state.is_hidden(id) || app_state.is_disabled(&id) {
+        return None;
+    }
+    let layout = app_state.get_layout(id)?;
+    let location = Point::new(layout.location.x as f64, layout.location.y as f64);
+    let size = Size::new(layout.size.width as f64, layout.size.height as f64);
+    if !size.to_rect().with_origin(location).contains(pos) {
+        return None;
+    }
+    let state = app_state.view_states.get(&id)?;
+    let viewport = state.viewport.map(|rect| rect.origin()).unwrap_or_default();
+    let pos = pos - location.to_vec2() + viewport.to_vec2();
+    let has_listener = state.file_drop_listener.is_some();
+    stacked_children(view, app_state)
+        .into_iter()
+        .rev()
+        .find_map(|child| file_drop_target(child, app_state, pos))
+        .or_else(|| has_listener.then_some((id, pos)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::{Cell, RefCell},
+        path::PathBuf,
+        rc::Rc,
+    };
+
+    use floem_peniko::Color;
+    use floem_reactive::create_rw_signal;
+    use image::Rgba;
+    use kurbo::{Point, Size};
+
+    use crate::{
+        event::FileDropEvent,
+        test_support::HeadlessWindow,
+        view::View,
+        views::{container, empty, h_stack, stack, Decorators},
+    };
+
+    #[test]
+    fn partial_present_matches_full_repaint() {
+        let color = create_rw_signal(Color::RED);
+        let view = move || {
+            h_stack((
+                empty().style(move |s| s.size(40.0, 40.0).background(color.get())),
+                empty().style(|s| {
+                    s.size(40.0, 40.0)
+                        .margin_left(10.0)
+                        .background(Color::BLUE)
+                        .box_shadow_blur(6.0)
+                        .box_shadow_h_offset(-8.0)
+                }),
+            ))
+        };
+        let mut partial = HeadlessWindow::new(Size::new(100.0, 50.0), view);
+        partial.set_partial_present(true);
+        let mut full = HeadlessWindow::new(Size::new(100.0, 50.0), view);
+        let before = partial.render();
+        assert_eq!(before, full.render());
+
+        color.set(Color::GREEN);
+        let after = partial.render();
+        assert_ne!(after, before);
+        assert_eq!(after, full.render());
+    }
+
+    #[test]
+    fn screenshots_are_rendered_at_their_scale() {
+        let id = Rc::new(Cell::new(None));
+        let view_id = id.clone();
+        let mut window = HeadlessWindow::new(Size::new(50.0, 50.0), move || {
+            let view =
+                empty().style(|s| s.size(10.0, 10.0).margin_left(5.0).background(Color::BLACK));
+            view_id.set(Some(view.id()));
+            view
+        });
+        window.set_partial_present(true);
+        window.render();
+
+        let image = Rc::new(RefCell::new(None));
+        let captured = image.clone();
+        id.get()
+            .unwrap()
+            .capture_screenshot(Some(2.0), move |image| *captured.borrow_mut() = image);
+        window.render();
+        let image = image.borrow_mut().take().unwrap();
+        // Rendering at twice the scale keeps the border sharp, unlike upscaling
+        assert_eq!(image.dimensions(), (20, 20));
+        assert_eq!(*image.get_pixel(1, 10), Rgba([0, 0, 0, 255]));
+        assert_eq!(*image.get_pixel(2, 10), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn files_are_dropped_on_the_view_under_the_pointer() {
+        let drops = Rc::new(RefCell::new(Vec::new()));
+        let view = {
+            let drops = drops.clone();
+            move || {
+                let listener = |name: &'static str| {
+                    let drops = drops.clone();
+                    move |event: FileDropEvent| drops.borrow_mut().push((name, event))
+                };
+                // The second box is placed over the first one, which is deeper in the tree
+                stack((
+                    container(
+                        empty()
+                            .style(|s| s.size(40.0, 40.0))
+                            .on_file_drop(listener("inner")),
+                    )
+                    .style(|s| s.size(60.0, 60.0))
+                    .on_file_drop(listener("outer")),
+                    empty()
+                        .style(|s| {
+                            s.absolute()
+                                .inset_left(20.0)
+                                .inset_top(0.0)
+                                .size(40.0, 40.0)
+                        })
+                        .on_file_drop(listener("top")),
+                ))
+                .style(|s| s.size_full())
+            }
+        };
+        let mut window = HeadlessWindow::new(Size::new(100.0, 100.0), view);
+        window.update();
+
+        let paths = vec![PathBuf::from("a.txt")];
+        window.hover_file("a.txt", Point::new(10.0, 10.0));
+        window.pointer_move(Point::new(30.0, 10.0));
+        window.pointer_move(Point::new(10.0, 50.0));
+        window.drop_file("a.txt", Point::new(10.0, 50.0));
+        assert_eq!(
+            *drops.borrow(),
+            vec![
+                (
+                    "inner",
+                    FileDropEvent::Enter {
+                        paths: paths.clone(),
+                        pos: Point::new(10.0, 10.0)
+                    }
+                ),
+                ("inner", FileDropEvent::Leave),
+                (
+                    "top",
+                    FileDropEvent::Enter {
+                        paths: paths.clone(),
+                        pos: Point::new(10.0, 10.0)
+                    }
+                ),
+                ("top", FileDropEvent::Leave),
+                (
+                    "outer",
+                    FileDropEvent::Enter {
+                        paths: paths.clone(),
+                        pos: Point::new(10.0, 50.0)
+                    }
+                ),
+                (
+                    "outer",
+                    FileDropEvent::Drop {
+                        paths,
+                        pos: Point::new(10.0, 50.0)
+                    }
+                ),
+            ]
+        );
+    }
+}
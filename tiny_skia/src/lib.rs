@@ -10,7 +10,7 @@ use floem_renderer::tiny_skia::{
     PathBuilder, Pattern, Pixmap, RadialGradient, Shader, SpreadMode, Stroke, Transform,
 };
 use floem_renderer::Img;
-use floem_renderer::Renderer;
+use floem_renderer::{Renderer, TextLines};
 use image::DynamicImage;
 use softbuffer::{Context, Surface};
 use std::collections::HashMap;
@@ -37,14 +37,19 @@ struct Glyph {
 struct CacheColor(bool);
 
 pub struct TinySkiaRenderer {
+    /// The window surface to present to. Headless renderers don't have one.
     #[allow(unused)]
-    context: Context,
-    surface: Surface,
+    target: Option<(Context, Surface)>,
+    capture: bool,
     pixmap: Pixmap,
     mask: Mask,
     scale: f64,
     transform: Affine,
     clip: Option<Rect>,
+    /// The part of the window repainted in this frame, if it's not all of it.
+    damage: Option<Rect>,
+    /// Whether the pixmap has the previous frame, so parts of it can be repainted.
+    has_frame: bool,
 
     /// The cache color value set for cache entries accessed this frame.
     cache_color: CacheColor,
@@ -71,19 +76,29 @@ impl TinySkiaRenderer {
                 .map_err(|err| anyhow!("unable to create surface: {}", err))?
         };
 
+        let mut renderer = Self::new_headless(width, height, scale)?;
+        renderer.target = Some((context, surface));
+        Ok(renderer)
+    }
+
+    /// Creates a renderer which renders to an offscreen image instead of a window.
+    /// [`Renderer::finish`] returns the rendered image.
+    pub fn new_headless(width: u32, height: u32, scale: f64) -> Result<Self> {
         let pixmap =
             Pixmap::new(width, height).ok_or_else(|| anyhow!("unable to create pixmap"))?;
 
         let mask = Mask::new(width, height).ok_or_else(|| anyhow!("unable to create mask"))?;
 
         Ok(Self {
-            context,
-            surface,
+            target: None,
+            capture: false,
             pixmap,
             mask,
             scale,
             transform: Affine::IDENTITY,
             clip: None,
+            damage: None,
+            has_frame: false,
             cache_color: CacheColor(false),
             image_cache: Default::default(),
             glyph_cache: Default::default(),
@@ -92,20 +107,38 @@ impl TinySkiaRenderer {
 
     pub fn resize(&mut self, width: u32, height: u32, scale: f64) {
         if width != self.pixmap.width() || height != self.pixmap.width() {
-            self.surface
-                .resize(
-                    NonZeroU32::new(width).unwrap_or(NonZeroU32::new(1).unwrap()),
-                    NonZeroU32::new(height).unwrap_or(NonZeroU32::new(1).unwrap()),
-                )
-                .expect("failed to resize surface");
+            if let Some((_, surface)) = self.target.as_mut() {
+                surface
+                    .resize(
+                        NonZeroU32::new(width).unwrap_or(NonZeroU32::new(1).unwrap()),
+                        NonZeroU32::new(height).unwrap_or(NonZeroU32::new(1).unwrap()),
+                    )
+                    .expect("failed to resize surface");
+            }
             self.pixmap = Pixmap::new(width, height).expect("unable to create pixmap");
             self.mask = Mask::new(width, height).expect("unable to create mask");
+            self.has_frame = false;
         }
-        self.scale = scale;
+        self.set_scale(scale);
     }
 
     pub fn set_scale(&mut self, scale: f64) {
-        self.scale = scale;
+        if scale != self.scale {
+            self.scale = scale;
+            self.has_frame = false;
+        }
+    }
+
+    /// Repaints only `damage` in the next frame, keeping the rest of the previous frame.
+    /// Returns `false` if there's no previous frame, and the whole window has to be repainted.
+    pub fn set_damage(&mut self, damage: Rect) -> bool {
+        if !self.has_frame {
+            return false;
+        }
+        // Round out to whole pixels so the edges of the damage are repainted fully
+        let damage = damage.scale_from_origin(self.scale).expand();
+        self.damage = Some(damage.scale_from_origin(1.0 / self.scale));
+        true
     }
 }
 
@@ -196,6 +229,19 @@ impl TinySkiaRenderer {
         )
     }
 
+    /// Limits the mask to the damage, so that only the repainted part of the window is painted.
+    fn intersect_damage(&mut self) {
+        if let Some(damage) = self.damage {
+            let path = try_ret!(self.shape_to_path(&damage));
+            self.mask.intersect_path(
+                &path,
+                FillRule::Winding,
+                false,
+                Transform::from_scale(self.scale as f32, self.scale as f32),
+            );
+        }
+    }
+
     fn clip_rect(&self, rect: tiny_skia::Rect) -> Option<tiny_skia::Rect> {
         let clip = if let Some(clip) = self.clip {
             clip
@@ -339,10 +385,22 @@ impl TinySkiaRenderer {
 }
 
 impl Renderer for TinySkiaRenderer {
-    fn begin(&mut self, _capture: bool) {
+    fn begin(&mut self, capture: bool) {
+        self.capture = capture;
         self.transform = Affine::IDENTITY;
-        self.pixmap.fill(tiny_skia::Color::WHITE);
-        self.clip = None;
+        let damage = self
+            .damage
+            .and_then(|damage| self.rect(damage.scale_from_origin(self.scale)));
+        if let Some(damage) = damage {
+            let mut paint = Paint::default();
+            paint.set_color(tiny_skia::Color::WHITE);
+            paint.blend_mode = tiny_skia::BlendMode::Source;
+            self.pixmap
+                .fill_rect(damage, &paint, Transform::identity(), None);
+        } else {
+            self.pixmap.fill(tiny_skia::Color::WHITE);
+        }
+        self.clear_clip();
     }
 
     fn stroke<'b>(&mut self, shape: &impl Shape, brush: impl Into<BrushRef<'b>>, width: f64) {
@@ -366,8 +424,12 @@ impl Renderer for TinySkiaRenderer {
         let paint = try_ret!(self.brush_to_paint(brush));
         if let Some(rect) = shape.as_rect() {
             let rect = try_ret!(self.rect(rect));
-            self.pixmap
-                .fill_rect(rect, &paint, self.current_transform(), None);
+            self.pixmap.fill_rect(
+                rect,
+                &paint,
+                self.current_transform(),
+                self.clip.is_some().then_some(&self.mask),
+            );
         } else {
             let path = try_ret!(self.shape_to_path(shape));
             self.pixmap.fill_path(
@@ -381,10 +443,17 @@ impl Renderer for TinySkiaRenderer {
     }
 
     fn draw_text(&mut self, layout: &TextLayout, pos: impl Into<Point>) {
+        self.draw_text_lines(layout, pos, &TextLines::default());
+    }
+
+    fn draw_text_lines(&mut self, layout: &TextLayout, pos: impl Into<Point>, lines: &TextLines) {
         let offset = self.transform.translation();
         let pos: Point = pos.into();
         let clip = self.clip;
-        for line in layout.layout_runs() {
+        let mut runs = layout.layout_runs().peekable();
+        while let Some(line) = runs.next() {
+            let is_paragraph_end = runs.peek().map_or(true, |next| next.line_i != line.line_i);
+            let placement = lines.line(&line, is_paragraph_end);
             if let Some(rect) = clip {
                 let y = pos.y + offset.y + line.line_y as f64;
                 if y + (line.line_height as f64) < rect.y0 {
@@ -395,8 +464,10 @@ impl Renderer for TinySkiaRenderer {
                 }
             }
 
-            'line_loop: for glyph_run in line.glyphs {
-                let x = glyph_run.x + pos.x as f32 + offset.x as f32;
+            let mut spacing = placement.offset;
+            'line_loop: for (index, glyph_run) in line.glyphs.iter().enumerate() {
+                let x = glyph_run.x + spacing + pos.x as f32 + offset.x as f32;
+                spacing += placement.glyph_spacing(&line, index);
                 let y = line.line_y + pos.y as f32 + offset.y as f32;
 
                 if let Some(rect) = clip {
@@ -471,7 +542,12 @@ impl Renderer for TinySkiaRenderer {
 
         let paint = brush.and_then(|brush| self.brush_to_paint(brush));
 
-        if let Some((color, pixmap)) = self.image_cache.get_mut(svg.hash) {
+        // The svg is rasterized at the size it's drawn at, so the size is part of the cache key.
+        let mut cache_key = svg.hash.to_owned();
+        cache_key.extend_from_slice(&width.to_le_bytes());
+        cache_key.extend_from_slice(&height.to_le_bytes());
+
+        if let Some((color, pixmap)) = self.image_cache.get_mut(&cache_key) {
             *color = self.cache_color;
             let pixmap = pixmap.clone();
             self.render_pixmap_paint(&pixmap, rect, paint);
@@ -480,16 +556,12 @@ impl Renderer for TinySkiaRenderer {
 
         let mut pixmap = try_ret!(tiny_skia::Pixmap::new(width, height));
         let rtree = resvg::Tree::from_usvg(svg.tree);
-        let svg_transform = tiny_skia::Transform::from_scale(
-            (width as f64 / rtree.size.width()) as f32,
-            (height as f64 / rtree.size.height()) as f32,
-        );
-        rtree.render(svg_transform, &mut pixmap.as_mut());
+        rtree.render(svg.fit_transform(width, height), &mut pixmap.as_mut());
 
         self.render_pixmap_paint(&pixmap, rect, paint);
 
         self.image_cache
-            .insert(svg.hash.to_owned(), (self.cache_color, Rc::new(pixmap)));
+            .insert(cache_key, (self.cache_color, Rc::new(pixmap)));
     }
 
     fn transform(&mut self, transform: Affine) {
@@ -510,16 +582,28 @@ impl Renderer for TinySkiaRenderer {
         };
 
         let offset = self.transform.translation();
-        self.clip = Some(rect + offset);
+        let rect = rect + offset;
+        self.clip = Some(self.damage.map_or(rect, |damage| damage.intersect(rect)));
 
         self.mask.clear();
         let path = try_ret!(self.shape_to_path(shape));
         self.mask
             .fill_path(&path, FillRule::Winding, false, self.current_transform());
+        self.intersect_damage();
     }
 
     fn clear_clip(&mut self) {
-        self.clip = None;
+        self.clip = self.damage;
+        if let Some(damage) = self.damage {
+            self.mask.clear();
+            let path = try_ret!(self.shape_to_path(&damage));
+            self.mask.fill_path(
+                &path,
+                FillRule::Winding,
+                false,
+                Transform::from_scale(self.scale as f32, self.scale as f32),
+            );
+        }
     }
 
     fn finish(&mut self) -> Option<DynamicImage> {
@@ -530,8 +614,22 @@ impl Renderer for TinySkiaRenderer {
         // Swap the cache color.
         self.cache_color = CacheColor(!self.cache_color.0);
 
-        let mut buffer = self
-            .surface
+        let damage = self.damage.take();
+        self.has_frame = true;
+
+        let surface = match self.target.as_mut() {
+            Some((_, surface)) if !self.capture => surface,
+            _ => {
+                let image =
+                    image::RgbaImage::from_fn(self.pixmap.width(), self.pixmap.height(), |x, y| {
+                        let pixel = self.pixmap.pixel(x, y).unwrap().demultiply();
+                        image::Rgba([pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()])
+                    });
+                return Some(DynamicImage::ImageRgba8(image));
+            }
+        };
+
+        let mut buffer = surface
             .buffer_mut()
             .expect("failed to get the surface buffer");
 
@@ -541,9 +639,26 @@ impl Renderer for TinySkiaRenderer {
                 (pixel.red() as u32) << 16 | (pixel.green() as u32) << 8 | (pixel.blue() as u32);
         }
 
-        buffer
-            .present()
-            .expect("failed to present the surface buffer");
+        // The buffer is copied whole as it may not have the previous frame, but only the damage
+        // is presented as changed
+        let damage = damage.and_then(|damage| {
+            let damage = damage.scale_from_origin(self.scale).expand();
+            Some(softbuffer::Rect {
+                x: damage.x0.max(0.0) as u32,
+                y: damage.y0.max(0.0) as u32,
+                width: NonZeroU32::new(damage.width() as u32)?,
+                height: NonZeroU32::new(damage.height() as u32)?,
+            })
+        });
+        if let Some(damage) = damage {
+            buffer
+                .present_with_damage(&[damage])
+                .expect("failed to present the surface buffer");
+        } else {
+            buffer
+                .present()
+                .expect("failed to present the surface buffer");
+        }
 
         None
     }
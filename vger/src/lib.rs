@@ -5,10 +5,10 @@ use std::sync::Arc;
 use anyhow::Result;
 use floem_peniko::{
     kurbo::{Affine, Point, Rect, Shape, Vec2},
-    BrushRef, Color, GradientKind,
+    BrushRef, Color, ColorStop, GradientKind,
 };
 use floem_renderer::cosmic_text::{SubpixelBin, SwashCache, TextLayout};
-use floem_renderer::{tiny_skia, Img, Renderer};
+use floem_renderer::{tiny_skia, Backend, Img, Renderer, TextLines};
 use floem_vger_rs::{Image, PaintIndex, PixelFormat, Vger};
 use image::{DynamicImage, EncodableLayout, RgbaImage};
 use wgpu::{Device, DeviceType, Queue, StoreOp, Surface, SurfaceConfiguration, TextureFormat};
@@ -25,6 +25,7 @@ pub struct VgerRenderer {
     transform: Affine,
     clip: Option<Rect>,
     capture: bool,
+    backend: Backend,
 }
 
 const CLEAR_COLOR: wgpu::Color = wgpu::Color {
@@ -35,6 +36,7 @@ const CLEAR_COLOR: wgpu::Color = wgpu::Color {
 };
 
 impl VgerRenderer {
+    /// Creates a renderer drawing with `backend`, or with the backend wgpu picks if it's `None`.
     pub fn new<
         W: raw_window_handle::HasRawDisplayHandle + raw_window_handle::HasRawWindowHandle,
     >(
@@ -42,8 +44,22 @@ impl VgerRenderer {
         width: u32,
         height: u32,
         scale: f64,
+        backend: Option<Backend>,
     ) -> Result<Self> {
-        let instance = wgpu::Instance::default();
+        let backends = match backend {
+            None => wgpu::Backends::all(),
+            Some(Backend::Vulkan) => wgpu::Backends::VULKAN,
+            Some(Backend::Metal) => wgpu::Backends::METAL,
+            Some(Backend::Dx12) => wgpu::Backends::DX12,
+            Some(Backend::Gl) => wgpu::Backends::GL,
+            Some(Backend::Software) => {
+                return Err(anyhow::anyhow!("software rendering doesn't use the GPU"))
+            }
+        };
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+            backends,
+            ..Default::default()
+        });
 
         let surface = unsafe { instance.create_surface(window) }?;
 
@@ -59,6 +75,14 @@ impl VgerRenderer {
             return Err(anyhow::anyhow!("only cpu adapter found"));
         }
 
+        let backend = match adapter.get_info().backend {
+            wgpu::Backend::Vulkan => Backend::Vulkan,
+            wgpu::Backend::Metal => Backend::Metal,
+            wgpu::Backend::Dx12 => Backend::Dx12,
+            wgpu::Backend::Gl => Backend::Gl,
+            backend => return Err(anyhow::anyhow!("unsupported backend {backend:?}")),
+        };
+
         let mut required_downlevel_flags = wgpu::DownlevelFlags::empty();
         required_downlevel_flags.set(wgpu::DownlevelFlags::VERTEX_STORAGE, true);
 
@@ -90,13 +114,23 @@ impl VgerRenderer {
             .find(|it| matches!(it, TextureFormat::Rgba8Unorm | TextureFormat::Bgra8Unorm))
             .ok_or_else(|| anyhow::anyhow!("surface should support Rgba8Unorm or Bgra8Unorm"))?;
 
+        // Prefer compositing with the alpha channel so transparent windows show what's behind them.
+        let alpha_mode = if surface_caps
+            .alpha_modes
+            .contains(&wgpu::CompositeAlphaMode::PreMultiplied)
+        {
+            wgpu::CompositeAlphaMode::PreMultiplied
+        } else {
+            wgpu::CompositeAlphaMode::Auto
+        };
+
         let config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: texture_format,
             width,
             height,
             present_mode: wgpu::PresentMode::Fifo,
-            alpha_mode: wgpu::CompositeAlphaMode::Auto,
+            alpha_mode,
             view_formats: vec![],
         };
         surface.configure(&device, &config);
@@ -114,9 +148,15 @@ impl VgerRenderer {
             transform: Affine::IDENTITY,
             clip: None,
             capture: false,
+            backend,
         })
     }
 
+    /// The backend the renderer draws with.
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
     pub fn resize(&mut self, width: u32, height: u32, scale: f64) {
         if width != self.config.width || height != self.config.height {
             self.config.width = width;
@@ -137,16 +177,17 @@ impl VgerRenderer {
             BrushRef::Solid(color) => self.vger.color_paint(vger_color(color)),
             BrushRef::Gradient(g) => match g.kind {
                 GradientKind::Linear { start, end } => {
-                    let mut stops = g.stops.iter();
-                    let inner_color = stops.next()?;
-                    let outer_color = stops.next()?;
-                    let inner_color = vger_color(inner_color.color);
-                    let outer_color = vger_color(outer_color.color);
-                    let start =
-                        floem_vger_rs::defs::LocalPoint::new(start.x as f32, start.y as f32);
-                    let end = floem_vger_rs::defs::LocalPoint::new(end.x as f32, end.y as f32);
-                    self.vger
-                        .linear_gradient(start, end, inner_color, outer_color, 0.0)
+                    let (start, end, inner_color, outer_color) =
+                        two_color_gradient(start, end, &g.stops)?;
+                    let start = self.vger_point(start);
+                    let end = self.vger_point(end);
+                    self.vger.linear_gradient(
+                        start,
+                        end,
+                        vger_color(inner_color),
+                        vger_color(outer_color),
+                        0.0,
+                    )
                 }
                 GradientKind::Radial { .. } => return None,
                 GradientKind::Sweep { .. } => return None,
@@ -306,14 +347,16 @@ impl Renderer for VgerRenderer {
                 width,
                 paint,
             );
-        } else if let Some(rect) = shape.as_rounded_rect() {
+        } else if let Some((rect, radius)) = shape
+            .as_rounded_rect()
+            .and_then(|rect| Some((rect, rect.radii().as_single_radius()?)))
+        {
             let min = rect.origin();
             let max = min + rect.rect().size().to_vec2();
-            let radius = (rect.radii().top_left * self.scale) as f32;
             self.vger.stroke_rect(
                 self.vger_point(min),
                 self.vger_point(max),
-                radius,
+                (radius * self.scale) as f32,
                 width,
                 paint,
             );
@@ -327,7 +370,14 @@ impl Renderer for VgerRenderer {
         } else {
             for segment in shape.path_segments(0.0) {
                 match segment {
-                    floem_peniko::kurbo::PathSeg::Line(_) => todo!(),
+                    floem_peniko::kurbo::PathSeg::Line(line) => {
+                        self.vger.stroke_segment(
+                            self.vger_point(line.p0),
+                            self.vger_point(line.p1),
+                            width,
+                            paint,
+                        );
+                    }
                     floem_peniko::kurbo::PathSeg::Quad(bez) => {
                         self.vger.stroke_bezier(
                             self.vger_point(bez.p0),
@@ -337,7 +387,17 @@ impl Renderer for VgerRenderer {
                             paint,
                         );
                     }
-                    floem_peniko::kurbo::PathSeg::Cubic(_) => todo!(),
+                    floem_peniko::kurbo::PathSeg::Cubic(cubic) => {
+                        for (_, _, bez) in cubic.to_quads(0.1) {
+                            self.vger.stroke_bezier(
+                                self.vger_point(bez.p0),
+                                self.vger_point(bez.p1),
+                                self.vger_point(bez.p2),
+                                width,
+                                paint,
+                            );
+                        }
+                    }
                 }
             }
         }
@@ -355,10 +415,21 @@ impl Renderer for VgerRenderer {
                 paint,
                 (blur_radius * self.scale) as f32,
             );
-        } else if let Some(rect) = path.as_rounded_rect() {
+        } else if let Some(rect) = path
+            .as_rounded_rect()
+            .filter(|rect| blur_radius > 0.0 || rect.radii().as_single_radius().is_some())
+        {
+            // Blurred shapes can only be drawn as rectangles with a single radius, so use the
+            // largest corner radius for those.
+            let radii = rect.radii();
+            let radius = radii
+                .top_left
+                .max(radii.top_right)
+                .max(radii.bottom_right)
+                .max(radii.bottom_left);
             self.vger.fill_rect(
                 self.vger_rect(rect.rect()),
-                (rect.radii().top_left * self.scale) as f32,
+                (radius * self.scale) as f32,
                 paint,
                 (blur_radius * self.scale) as f32,
             );
@@ -388,7 +459,16 @@ impl Renderer for VgerRenderer {
                         self.vger
                             .quad_to(self.vger_point(quad.p1), self.vger_point(quad.p2));
                     }
-                    floem_peniko::kurbo::PathSeg::Cubic(_) => {}
+                    floem_peniko::kurbo::PathSeg::Cubic(cubic) => {
+                        if first {
+                            first = false;
+                            self.vger.move_to(self.vger_point(cubic.p0));
+                        }
+                        for (_, _, quad) in cubic.to_quads(0.1) {
+                            self.vger
+                                .quad_to(self.vger_point(quad.p1), self.vger_point(quad.p2));
+                        }
+                    }
                 }
             }
             self.vger.fill(paint);
@@ -396,12 +476,19 @@ impl Renderer for VgerRenderer {
     }
 
     fn draw_text(&mut self, layout: &TextLayout, pos: impl Into<Point>) {
+        self.draw_text_lines(layout, pos, &TextLines::default());
+    }
+
+    fn draw_text_lines(&mut self, layout: &TextLayout, pos: impl Into<Point>, lines: &TextLines) {
         let mut swash_cache = SwashCache::new();
         let transform = self.transform.as_coeffs();
         let offset = Vec2::new(transform[4], transform[5]);
         let pos: Point = pos.into();
         let clip = self.clip;
-        for line in layout.layout_runs() {
+        let mut runs = layout.layout_runs().peekable();
+        while let Some(line) = runs.next() {
+            let is_paragraph_end = runs.peek().map_or(true, |next| next.line_i != line.line_i);
+            let placement = lines.line(&line, is_paragraph_end);
             if let Some(rect) = clip {
                 let y = pos.y + offset.y + line.line_y as f64;
                 if y + (line.line_height as f64) < rect.y0 {
@@ -411,8 +498,10 @@ impl Renderer for VgerRenderer {
                     break;
                 }
             }
-            'line_loop: for glyph_run in line.glyphs {
-                let x = glyph_run.x + pos.x as f32 + offset.x as f32;
+            let mut spacing = placement.offset;
+            'line_loop: for (index, glyph_run) in line.glyphs.iter().enumerate() {
+                let x = glyph_run.x + spacing + pos.x as f32 + offset.x as f32;
+                spacing += placement.glyph_spacing(&line, index);
                 let y = line.line_y + pos.y as f32 + offset.y as f32;
 
                 if let Some(rect) = clip {
@@ -498,20 +587,22 @@ impl Renderer for VgerRenderer {
         let x = ((origin.x + transform[4]) * self.scale).round() as f32;
         let y = ((origin.y + transform[5]) * self.scale).round() as f32;
 
+        // The svg is rasterized at the size it's drawn at, so the size is part of the cache key.
+        let mut cache_key = svg.hash.to_owned();
+        cache_key.extend_from_slice(&width.to_le_bytes());
+        cache_key.extend_from_slice(&height.to_le_bytes());
+
         let paint = brush.and_then(|brush| self.brush_to_paint(brush));
         self.vger.render_svg(
             x,
             y,
-            svg.hash,
+            &cache_key,
             width,
             height,
             || {
                 let mut img = tiny_skia::Pixmap::new(width, height).unwrap();
                 let rtree = resvg::Tree::from_usvg(svg.tree);
-                let scale = (width as f64 / rtree.size.width())
-                    .min(height as f64 / rtree.size.height()) as f32;
-                let transform = tiny_skia::Transform::from_scale(scale, scale);
-                rtree.render(transform, &mut img.as_mut());
+                rtree.render(svg.fit_transform(width, height), &mut img.as_mut());
                 img.take()
             },
             paint,
@@ -552,7 +643,16 @@ impl Renderer for VgerRenderer {
         if self.capture {
             self.render_image()
         } else {
-            if let Ok(frame) = self.surface.get_current_texture() {
+            let frame = match self.surface.get_current_texture() {
+                // The surface has to be configured again when it's lost, like after the GPU
+                // was reset, or it stays blank
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    self.surface.configure(&self.device, &self.config);
+                    self.surface.get_current_texture()
+                }
+                frame => frame,
+            };
+            if let Ok(frame) = frame {
                 let texture_view = frame
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
@@ -587,3 +687,48 @@ fn vger_color(color: Color) -> floem_vger_rs::Color {
         a: color.a as f32 / 255.0,
     }
 }
+
+/// The line and colors of the two color gradient drawn for a linear gradient from `start` to
+/// `end`. Vger gradients only have two colors, so the stops between the first and the last are
+/// dropped, and the line is moved to the offsets of those two stops.
+fn two_color_gradient(
+    start: Point,
+    end: Point,
+    stops: &[ColorStop],
+) -> Option<(Point, Point, Color, Color)> {
+    let first = stops.first()?;
+    let last = stops.last()?;
+    let line = end - start;
+    Some((
+        start + line * first.offset as f64,
+        start + line * last.offset as f64,
+        first.color,
+        last.color,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use floem_peniko::{kurbo::Point, Color, ColorStop};
+
+    use super::two_color_gradient;
+
+    #[test]
+    fn gradients_keep_the_outer_stops() {
+        let stop = |offset, color| ColorStop { offset, color };
+        let stops = [
+            stop(0.25, Color::RED),
+            stop(0.5, Color::GREEN),
+            stop(0.75, Color::BLUE),
+        ];
+        let (start, end, inner, outer) =
+            two_color_gradient(Point::new(0.0, 0.0), Point::new(100.0, 0.0), &stops).unwrap();
+        assert_eq!(start, Point::new(25.0, 0.0));
+        assert_eq!(end, Point::new(75.0, 0.0));
+        assert_eq!(inner, Color::RED);
+        // The middle stop isn't drawn
+        assert_eq!(outer, Color::BLUE);
+
+        assert!(two_color_gradient(Point::ZERO, Point::new(1.0, 0.0), &[]).is_none());
+    }
+}